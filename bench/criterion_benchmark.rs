@@ -1,10 +1,25 @@
-use std::path::Path;
+use std::{any::Any, collections::HashMap, path::Path};
 
 use cairo_vm::{
     cairo_run,
-    hint_processor::builtin_hint_processor::builtin_hint_processor_definition::BuiltinHintProcessor,
+    hint_processor::{
+        builtin_hint_processor::builtin_hint_processor_definition::{
+            BuiltinHintProcessor, HintProcessorData,
+        },
+        hint_processor_definition::{HintProcessor, HintReference},
+    },
+    types::{
+        builtin_name::BuiltinName, exec_scope::ExecutionScopes, program::Program,
+        relocatable::MaybeRelocatable,
+    },
+    vm::{
+        runners::{builtin_runner::RangeCheckBuiltinRunner, cairo_runner::CairoRunner},
+        vm_core::VirtualMachine,
+    },
 };
-use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use felt::{Felt, NewFelt};
+use num_traits::{Bounded, One};
 
 const BENCH_NAMES: &[&str] = &[
     "compare_arrays_200000",
@@ -24,6 +39,26 @@ const BENCH_NAMES: &[&str] = &[
     "set_integration_benchmark",
 ];
 const BENCH_PATH: &str = "cairo_programs/benchmarks/";
+const SHORT_RUN_PATH: &str = "cairo_programs/manually_compiled/hintless_program.json";
+const SQUASH_DICT_HINT: &str = r#"dict_access_size = ids.DictAccess.SIZE
+address = ids.dict_accesses.address_
+assert ids.ptr_diff % dict_access_size == 0, \
+    'Accesses array size must be divisible by DictAccess.SIZE'
+n_accesses = ids.n_accesses
+if '__squash_dict_max_size' in globals():
+    assert n_accesses <= __squash_dict_max_size, \
+        f'squash_dict() can only be used with n_accesses<={__squash_dict_max_size}. ' \
+        f'Got: n_accesses={n_accesses}.'
+# A map from key to the list of indices accessing it.
+access_indices = {}
+for i in range(n_accesses):
+    key = memory[address + dict_access_size * i]
+    access_indices.setdefault(key, []).append(i)
+# Descending list of keys.
+keys = sorted(access_indices.keys(), reverse=True)
+# Are the keys used bigger than range_check bound.
+ids.big_keys = 1 if keys[0] >= range_check_builtin.bound else 0
+ids.first_key = key = keys.pop()"#;
 
 pub fn criterion_benchmarks(c: &mut Criterion) {
     let mut hint_executor = BuiltinHintProcessor::new_empty();
@@ -44,6 +79,216 @@ pub fn criterion_benchmarks(c: &mut Criterion) {
     }
 }
 
+fn run_short_program(
+    program: &Program,
+    vm: &mut VirtualMachine,
+    hint_executor: &mut BuiltinHintProcessor,
+) {
+    let mut cairo_runner = CairoRunner::new(program, "all", false).unwrap();
+    let end = cairo_runner.initialize(vm).unwrap();
+    cairo_runner.run_until_pc(end, vm, hint_executor).unwrap();
+}
+
+// Compares creating a vm per run against resetting a single one, as done when executing many short entrypoints
+pub fn vm_reuse_benchmarks(c: &mut Criterion) {
+    let program = Program::from_file(Path::new(SHORT_RUN_PATH), Some("main")).unwrap();
+    let mut hint_executor = BuiltinHintProcessor::new_empty();
+    c.bench_function("short_run_fresh_vm", |b| {
+        b.iter(|| {
+            let mut vm = VirtualMachine::new(false);
+            run_short_program(black_box(&program), &mut vm, &mut hint_executor)
+        })
+    });
+    let mut vm = VirtualMachine::new(false);
+    c.bench_function("short_run_reset_vm", |b| {
+        b.iter(|| {
+            vm.reset();
+            run_short_program(black_box(&program), &mut vm, &mut hint_executor)
+        })
+    });
+}
+
+// Builds a vm holding n_accesses DictAccess entries over n_keys keys, visited out of order,
+// and the ids of the squash_dict hint at fp
+fn vm_with_dict_accesses(n_accesses: usize, n_keys: usize) -> VirtualMachine {
+    let mut vm = VirtualMachine::new(false);
+    vm.add_builtin_runner(
+        BuiltinName::RangeCheck,
+        RangeCheckBuiltinRunner::new(8, 8, true).into(),
+    )
+    .unwrap();
+    vm.add_memory_segment();
+    let fp = vm.add_memory_segment();
+    let dict_accesses = vm.add_memory_segment();
+    let accesses = (0..n_accesses)
+        .flat_map(|i| {
+            let key = (i * 7919) % n_keys;
+            [key, i, i + 1].map(MaybeRelocatable::from)
+        })
+        .collect();
+    vm.load_data(&dict_accesses.into(), &accesses).unwrap();
+    // dict_accesses, big_keys, first_key, ptr_diff, n_accesses
+    let ids = vec![
+        dict_accesses.into(),
+        MaybeRelocatable::from(0),
+        MaybeRelocatable::from(0),
+        MaybeRelocatable::from(3 * n_accesses),
+        MaybeRelocatable::from(n_accesses),
+    ];
+    vm.load_data(&fp.into(), &ids).unwrap();
+    vm
+}
+
+// Runs the squash_dict hint alone, its cost should grow close to linearly with the number of accesses
+pub fn squash_dict_benchmarks(c: &mut Criterion) {
+    let ids_data = [
+        "dict_accesses",
+        "big_keys",
+        "first_key",
+        "ptr_diff",
+        "n_accesses",
+    ]
+    .into_iter()
+    .enumerate()
+    .map(|(offset, name)| (name.to_string(), HintReference::new_simple(offset as i32)))
+    .collect();
+    let hint_data: Box<dyn Any> = Box::new(HintProcessorData::new_default(
+        SQUASH_DICT_HINT.to_string(),
+        ids_data,
+    ));
+    let mut hint_executor = BuiltinHintProcessor::new_empty();
+    let mut group = c.benchmark_group("squash_dict_hint");
+    for (n_accesses, n_keys) in [(10_000, 1_000), (100_000, 10_000)] {
+        let mut vm = vm_with_dict_accesses(n_accesses, n_keys);
+        group.bench_with_input(
+            BenchmarkId::from_parameter(n_accesses),
+            &n_accesses,
+            |b, _| {
+                b.iter(|| {
+                    hint_executor
+                        .execute_hint(
+                            &mut vm,
+                            &mut ExecutionScopes::new(),
+                            black_box(&hint_data),
+                            &HashMap::new(),
+                        )
+                        .unwrap()
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+const SCOPE_LOOP_ITERATIONS: u64 = 100_000;
+
+// Decrements a scope variable as loop hints do, replacing it with a new value against updating it
+// in place, and runs memcpy's continue_copying hint, which updates it in place
+pub fn scope_loop_benchmarks(c: &mut Criterion) {
+    let mut group = c.benchmark_group("scope_loop_100k");
+    group.bench_function("insert_value", |b| {
+        b.iter(|| {
+            let mut exec_scopes = ExecutionScopes::new();
+            exec_scopes.insert_value("n", Felt::new(SCOPE_LOOP_ITERATIONS));
+            for _ in 0..SCOPE_LOOP_ITERATIONS {
+                let new_n = exec_scopes.get_ref::<Felt>("n").unwrap() - 1;
+                exec_scopes.insert_value("n", black_box(new_n));
+            }
+        })
+    });
+    group.bench_function("get_mut_int_ref", |b| {
+        b.iter(|| {
+            let mut exec_scopes = ExecutionScopes::new();
+            exec_scopes.insert_value("n", Felt::new(SCOPE_LOOP_ITERATIONS));
+            for _ in 0..SCOPE_LOOP_ITERATIONS {
+                *black_box(exec_scopes.get_mut_int_ref("n").unwrap()) -= Felt::one();
+            }
+        })
+    });
+
+    let mut vm = VirtualMachine::new(false);
+    vm.add_memory_segment();
+    vm.add_memory_segment();
+    let ids_data = HashMap::from([("continue_copying".to_string(), HintReference::new_simple(0))]);
+    let hint_data: Box<dyn Any> = Box::new(HintProcessorData::new_default(
+        "n -= 1\nids.continue_copying = 1 if n > 0 else 0".to_string(),
+        ids_data,
+    ));
+    let mut hint_executor = BuiltinHintProcessor::new_empty();
+    group.bench_function("memcpy_continue_copying", |b| {
+        b.iter(|| {
+            let mut exec_scopes = ExecutionScopes::new();
+            //Stops before n reaches zero, so continue_copying is always 1
+            exec_scopes.insert_value("n", Felt::new(SCOPE_LOOP_ITERATIONS + 1));
+            for _ in 0..SCOPE_LOOP_ITERATIONS {
+                hint_executor
+                    .execute_hint(&mut vm, &mut exec_scopes, &hint_data, &HashMap::new())
+                    .unwrap();
+            }
+        })
+    });
+    group.finish();
+}
+
+const HINT_DISPATCH_ITERATIONS: usize = 100_000;
+
+// Executes a single hint in a loop, measuring the cost of dispatching it to its implementation
+pub fn hint_dispatch_benchmarks(c: &mut Criterion) {
+    let mut vm = VirtualMachine::new(false);
+    let hint_data: Box<dyn Any> = Box::new(HintProcessorData::new_default(
+        "skip_next_instruction()".to_string(),
+        HashMap::new(),
+    ));
+    let mut hint_executor = BuiltinHintProcessor::new_empty();
+    let mut exec_scopes = ExecutionScopes::new();
+    c.bench_function("hint_dispatch_100k", |b| {
+        b.iter(|| {
+            for _ in 0..HINT_DISPATCH_ITERATIONS {
+                hint_executor
+                    .execute_hint(
+                        &mut vm,
+                        &mut exec_scopes,
+                        black_box(&hint_data),
+                        &HashMap::new(),
+                    )
+                    .unwrap();
+            }
+        })
+    });
+}
+
+const OPERANDS_ITERATIONS: usize = 100_000;
+
+// Computes the operands of `[ap] = [ap - 2] * [ap - 1]` over two full size felts, which are
+// borrowed from memory instead of being cloned on every step
+pub fn compute_operands_benchmarks(c: &mut Criterion) {
+    let mut vm = VirtualMachine::new(false);
+    let program = vec![Felt::new(0x40507ffe7fff8000_i64).into()];
+    let pc = vm
+        .load_program(&program, None)
+        .unwrap()
+        .sub_usize(1)
+        .unwrap();
+    let execution_base = vm.add_memory_segment();
+    let factors = vec![
+        MaybeRelocatable::from(Felt::max_value() - 1_u32),
+        MaybeRelocatable::from(Felt::max_value() - 2_u32),
+    ];
+    let ap = vm.load_data(&execution_base.into(), &factors).unwrap();
+    let ap = ap.get_relocatable().unwrap();
+    vm.set_pc(pc);
+    vm.set_ap(ap).unwrap();
+    vm.set_fp(ap).unwrap();
+    let instruction = vm.decode_instruction_at(&pc).unwrap();
+    c.bench_function("compute_operands_mul_100k", |b| {
+        b.iter(|| {
+            for _ in 0..OPERANDS_ITERATIONS {
+                black_box(vm.compute_operands(black_box(&instruction)).unwrap());
+            }
+        })
+    });
+}
+
 fn build_bench_strings() -> Vec<(String, String)> {
     let mut full_string = Vec::<(String, String)>::new();
 
@@ -59,5 +304,13 @@ fn build_bench_strings() -> Vec<(String, String)> {
     full_string
 }
 
-criterion_group!(benches, criterion_benchmarks);
+criterion_group!(
+    benches,
+    criterion_benchmarks,
+    vm_reuse_benchmarks,
+    squash_dict_benchmarks,
+    scope_loop_benchmarks,
+    hint_dispatch_benchmarks,
+    compute_operands_benchmarks
+);
 criterion_main!(benches);
@@ -0,0 +1,12 @@
+%builtins range_check
+
+from starkware.cairo.common.math import assert_nn_le, assert_in_range, assert_250_bit
+
+func main{range_check_ptr: felt}() {
+    assert_nn_le(0, 0);
+    assert_nn_le(5, 17);
+    assert_in_range(5, 5, 6);
+    assert_in_range(10, 0, 2 ** 64);
+    assert_250_bit(2 ** 249);
+    return ();
+}
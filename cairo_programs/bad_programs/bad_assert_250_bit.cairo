@@ -0,0 +1,8 @@
+%builtins range_check
+
+from starkware.cairo.common.math import assert_250_bit
+
+func main{range_check_ptr: felt}() {
+    assert_250_bit(2 ** 250);
+    return ();
+}
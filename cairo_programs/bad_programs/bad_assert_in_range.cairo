@@ -0,0 +1,8 @@
+%builtins range_check
+
+from starkware.cairo.common.math import assert_in_range
+
+func main{range_check_ptr: felt}() {
+    assert_in_range(6, 5, 6);
+    return ();
+}
@@ -0,0 +1,8 @@
+%builtins range_check
+
+from starkware.cairo.common.math import assert_nn_le
+
+func main{range_check_ptr: felt}() {
+    assert_nn_le(18, 17);
+    return ();
+}
@@ -0,0 +1,8 @@
+func main() {
+    alloc_locals;
+    local amount = 3;
+    with_attr error_message("Amount {amount} is too large") {
+        assert amount = 2;
+    }
+    return();
+}
@@ -0,0 +1,47 @@
+%builtins range_check
+
+from starkware.cairo.common.math_cmp import is_le, is_nn_le, is_in_range, is_le_felt
+
+func main{range_check_ptr: felt}() {
+    // is_le, negative values wrap around to PRIME - x
+    let a = is_le(7, 7);
+    assert a = 1;
+    let b = is_le(7, 8);
+    assert b = 1;
+    let c = is_le(8, 7);
+    assert c = 0;
+    let d = is_le(-1, 0);
+    assert d = 1;
+    let e = is_le(0, -1);
+    assert e = 0;
+
+    // is_nn_le
+    let f = is_nn_le(0, 0);
+    assert f = 1;
+    let g = is_nn_le(4, 5);
+    assert g = 1;
+    let h = is_nn_le(5, 4);
+    assert h = 0;
+    let i = is_nn_le(-1, 5);
+    assert i = 0;
+
+    // is_in_range, the lower bound is included and the upper bound isn't
+    let j = is_in_range(5, 5, 6);
+    assert j = 1;
+    let k = is_in_range(6, 5, 6);
+    assert k = 0;
+    let l = is_in_range(4, 5, 6);
+    assert l = 0;
+    let m = is_in_range(-1, -2, 0);
+    assert m = 1;
+
+    // is_le_felt compares the values as unsigned, so -1 is the greatest felt
+    let n = is_le_felt(-1, 0);
+    assert n = 0;
+    let o = is_le_felt(0, -1);
+    assert o = 1;
+    let p = is_le_felt(-1, -1);
+    assert p = 1;
+
+    return ();
+}
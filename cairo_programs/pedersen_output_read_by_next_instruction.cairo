@@ -0,0 +1,18 @@
+%builtins output pedersen
+
+from starkware.cairo.common.cairo_builtins import HashBuiltin
+from starkware.cairo.common.hash import hash2
+
+// The hash output is read by the instruction following the ones writing the inputs, so the vm
+// has to deduce it from the pedersen builtin while computing that instruction's operands
+func main{output_ptr: felt*, pedersen_ptr: HashBuiltin*}() {
+    assert pedersen_ptr.x = 0;
+    assert pedersen_ptr.y = 0;
+    assert [output_ptr] = pedersen_ptr.result;
+    let pedersen_ptr = pedersen_ptr + HashBuiltin.SIZE;
+
+    let (expected) = hash2{hash_ptr=pedersen_ptr}(0, 0);
+    assert [output_ptr] = expected;
+    let output_ptr = output_ptr + 1;
+    return ();
+}
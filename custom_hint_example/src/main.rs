@@ -6,7 +6,9 @@ use cairo_vm::hint_processor::builtin_hint_processor::hint_utils::get_integer_fr
 use cairo_vm::hint_processor::hint_processor_definition::HintReference;
 use cairo_vm::types::exec_scope::ExecutionScopes;
 use cairo_vm::serde::deserialize_program::ApTracking;
-use cairo_vm::vm::{errors::hint_errors::HintError, vm_core::VirtualMachine};
+use cairo_vm::vm::{
+    const_view::VmConstView, errors::hint_errors::HintError, vm_core::VirtualMachine,
+};
 use num_bigint::BigInt;
 use std::collections::HashMap;
 use std::path::Path;
@@ -19,6 +21,7 @@ fn print_a_hint(
     ids_data: &HashMap<String, HintReference>,
     ap_tracking: &ApTracking,
     _constants: &HashMap<String, BigInt>,
+    _const_view: &VmConstView,
 ) -> Result<(), HintError> {
     let a = get_integer_from_var_name("a", vm, ids_data, ap_tracking)?;
     println!("{}", a);
@@ -0,0 +1,69 @@
+// Embeds the VM in another program using only its supported public API: loading a program,
+// registering a custom hint, running until the end and reading back the relocated trace and
+// memory. It is built with the rest of the crate, so it breaks whenever that API does.
+//
+// Run with `cargo run --example embedding` from the repository root.
+
+use cairo_vm::{
+    hint_processor::{
+        builtin_hint_processor::{
+            builtin_hint_processor_definition::{BuiltinHintProcessor, HintFunc},
+            hint_utils::get_integer_from_var_name,
+        },
+        hint_processor_definition::HintReference,
+    },
+    serde::deserialize_program::ApTracking,
+    types::{exec_scope::ExecutionScopes, program::Program},
+    vm::{
+        const_view::VmConstView, errors::hint_errors::HintError,
+        runners::cairo_runner::CairoRunner, trace::verifier::verify_trace, vm_core::VirtualMachine,
+    },
+};
+use felt::Felt;
+use std::{collections::HashMap, error::Error, path::Path, rc::Rc};
+
+const PROGRAM_PATH: &str = "cairo_programs/manually_compiled/hintless_program.json";
+
+// Implements the hint "print(ids.a)"
+fn print_a(
+    vm: &mut VirtualMachine,
+    _exec_scopes: &mut ExecutionScopes,
+    ids_data: &HashMap<String, HintReference>,
+    ap_tracking: &ApTracking,
+    _constants: &HashMap<String, Felt>,
+    _const_view: &VmConstView,
+) -> Result<(), HintError> {
+    let a = get_integer_from_var_name("a", vm, ids_data, ap_tracking)?;
+    println!("{a}");
+    Ok(())
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let program = Program::from_file(Path::new(PROGRAM_PATH), Some("main"))?;
+    let mut hint_processor = BuiltinHintProcessor::new_empty();
+    hint_processor.add_hint(
+        String::from("print(ids.a)"),
+        Rc::new(HintFunc(Box::new(print_a))),
+    );
+
+    let mut cairo_runner = CairoRunner::new(&program, "all", false)?;
+    let mut vm = VirtualMachine::new(true);
+    let end = cairo_runner.initialize(&mut vm)?;
+    cairo_runner.run_until_pc(end, &mut vm, &mut hint_processor)?;
+    cairo_runner.end_run(false, false, &mut vm, &mut hint_processor)?;
+    cairo_runner.relocate(&mut vm)?;
+
+    let resources = cairo_runner.get_execution_resources(&vm)?;
+    println!(
+        "steps: {}, memory holes: {}",
+        resources.n_steps, resources.n_memory_holes
+    );
+
+    let memory = cairo_runner.get_relocated_memory();
+    let trace = cairo_runner
+        .get_relocated_trace()
+        .ok_or("the vm was created without a trace")?;
+    verify_trace(memory, trace, &program.prime)?;
+    println!("verified {} trace entries", trace.len());
+    Ok(())
+}
@@ -34,7 +34,7 @@ macro_rules! from_integer {
                 Self(
                     value
                         .try_into()
-                        .unwrap_or_else(|_| &*CAIRO_PRIME - (-value as u128)),
+                        .unwrap_or_else(|_| &*CAIRO_PRIME - value.unsigned_abs() as u128),
                 )
             }
         }
@@ -67,7 +67,7 @@ from_unsigned!(usize);
 
 impl From<BigUint> for FeltBigInt {
     fn from(value: BigUint) -> Self {
-        if value > *CAIRO_PRIME {
+        if value >= *CAIRO_PRIME {
             Self(value.mod_floor(&CAIRO_PRIME))
         } else {
             Self(value)
@@ -77,7 +77,7 @@ impl From<BigUint> for FeltBigInt {
 
 impl From<&BigUint> for FeltBigInt {
     fn from(value: &BigUint) -> Self {
-        if value > &*CAIRO_PRIME {
+        if value >= &*CAIRO_PRIME {
             Self(value.mod_floor(&CAIRO_PRIME))
         } else {
             Self(value.clone())
@@ -836,4 +836,37 @@ mod tests {
         let d = c.neg();
         assert_eq!(d, FeltBigInt::new(10_i32));
     }
+
+    #[test]
+    fn unreduced_values_are_canonical() {
+        let prime = &*CAIRO_PRIME;
+        assert_eq!(FeltBigInt::new(prime.clone()), FeltBigInt::zero());
+        assert_eq!(FeltBigInt::from(prime), FeltBigInt::zero());
+        assert_eq!(FeltBigInt::new(prime + 3_u32), FeltBigInt::new(3));
+        assert_eq!(
+            FeltBigInt::from_str_radix(&prime.to_str_radix(10), 10),
+            Ok(FeltBigInt::zero())
+        );
+        assert_eq!(
+            FeltBigInt::from_bytes_be(&(prime * 2_u32 + 1_u32).to_bytes_be()),
+            FeltBigInt::one()
+        );
+        assert_eq!(
+            FeltBigInt::new(BigInt::from(prime.clone())),
+            FeltBigInt::zero()
+        );
+    }
+
+    #[test]
+    fn negative_integers_are_canonical() {
+        assert_eq!(FeltBigInt::new(-1_i8), FeltBigInt::max_value());
+        assert_eq!(
+            FeltBigInt::new(i128::MIN),
+            FeltBigInt::new(BigInt::from(i128::MIN))
+        );
+        assert_eq!(
+            FeltBigInt::new(i64::MIN) + FeltBigInt::new(u64::MAX),
+            FeltBigInt::new(i64::MAX)
+        );
+    }
 }
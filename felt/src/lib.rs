@@ -219,11 +219,10 @@ mod test {
         // "With assignment" means that the result of the operation is autommatically assigned to the variable value, replacing its previous content.
         fn shift_right_assign_in_range(ref value in "(0|[1-9][0-9]*)", ref shift_amount in "[0-9]{1,3}"){
             let mut value = Felt::parse_bytes(value.as_bytes(), 10).unwrap();
-            let p = FeltBigInt::parse_bytes(PRIME_STR[2..].as_bytes(), 16).unwrap();
+            let p = &BigUint::parse_bytes(PRIME_STR[2..].as_bytes(), 16).unwrap();
             let shift_amount:usize = shift_amount.parse::<usize>().unwrap();
             value >>= shift_amount;
-            value.to_biguint();
-            prop_assert!(value < p);
+            prop_assert!(&value.to_biguint() < p);
         }
 
         #[test]
@@ -5,15 +5,17 @@ use crate::{
         errors::{
             cairo_run_errors::CairoRunError, runner_errors::RunnerError, vm_exception::VmException,
         },
-        runners::cairo_runner::CairoRunner,
+        runners::{cairo_runner::CairoRunner, output_verifier::OutputVerifier},
         trace::trace_entry::RelocatedTraceEntry,
         vm_core::VirtualMachine,
     },
 };
 use felt::{Felt, FeltOps};
+use num_bigint::BigInt;
+use num_traits::Signed;
 use std::{
     fs::File,
-    io::{self, BufWriter, Error, ErrorKind, Write},
+    io::{self, BufWriter, Write},
     path::Path,
 };
 
@@ -25,6 +27,31 @@ pub fn cairo_run(
     layout: &str,
     proof_mode: bool,
     hint_executor: &mut dyn HintProcessor,
+) -> Result<CairoRunner, CairoRunError> {
+    cairo_run_with_output_verifier(
+        path,
+        entrypoint,
+        trace_enabled,
+        print_output,
+        layout,
+        proof_mode,
+        hint_executor,
+        None,
+    )
+}
+
+///Like cairo_run, but fails with RunnerError::OutputVerificationFailed if output_verifier rejects
+///the values written to the output builtin once the run ended.
+#[allow(clippy::too_many_arguments, clippy::result_large_err)]
+pub fn cairo_run_with_output_verifier(
+    path: &Path,
+    entrypoint: &str,
+    trace_enabled: bool,
+    print_output: bool,
+    layout: &str,
+    proof_mode: bool,
+    hint_executor: &mut dyn HintProcessor,
+    output_verifier: Option<&dyn OutputVerifier>,
 ) -> Result<CairoRunner, CairoRunError> {
     let program = match Program::from_file(path, Some(entrypoint)) {
         Ok(program) => program,
@@ -39,6 +66,9 @@ pub fn cairo_run(
         .run_until_pc(end, &mut vm, hint_executor)
         .map_err(|err| VmException::from_vm_error(&cairo_runner, &vm, err))?;
     cairo_runner.end_run(false, false, &mut vm, hint_executor)?;
+    if let Some(output_verifier) = output_verifier {
+        cairo_runner.verify_output(&mut vm, output_verifier)?;
+    }
 
     vm.verify_auto_deductions()?;
     if proof_mode {
@@ -67,8 +97,8 @@ pub fn write_output(
         .map_err(|_| CairoRunError::Runner(RunnerError::WriteFail))
 }
 
-/// Writes a trace as a binary file. Bincode encodes to little endian by default and each trace
-/// entry is composed of 3 usize values that are padded to always reach 64 bit size.
+/// Writes a trace as a binary file. Each trace entry is written as its ap, fp and pc, each one
+/// as a 64 bit little endian integer, so the file is the same on every platform.
 pub fn write_binary_trace(
     relocated_trace: &[RelocatedTraceEntry],
     trace_file: &Path,
@@ -76,18 +106,20 @@ pub fn write_binary_trace(
     let file = File::create(trace_file)?;
     let mut buffer = BufWriter::new(file);
 
-    for (i, entry) in relocated_trace.iter().enumerate() {
-        bincode::serialize_into(&mut buffer, entry).map_err(|e| {
-            Error::new(
-                ErrorKind::Other,
-                format!("Failed to dump trace at position {i}, serialize error: {e}"),
-            )
-        })?;
-    }
-
+    buffer.write_all(&encode_relocated_trace(relocated_trace))?;
     buffer.flush()
 }
 
+fn encode_relocated_trace(relocated_trace: &[RelocatedTraceEntry]) -> Vec<u8> {
+    let mut trace_bytes = Vec::with_capacity(relocated_trace.len() * 3 * 8);
+    for entry in relocated_trace {
+        for register in [entry.ap, entry.fp, entry.pc] {
+            trace_bytes.extend_from_slice(&(register as u64).to_le_bytes());
+        }
+    }
+    trace_bytes
+}
+
 /*
    Writes a binary memory file with the relocated memory as input.
    The memory pairs (address, value) are encoded and concatenated in the file
@@ -103,30 +135,91 @@ pub fn write_binary_memory(
     let file = File::create(memory_file)?;
     let mut buffer = BufWriter::new(file);
 
-    // initialize bytes vector that will be dumped to file
-    let mut memory_bytes: Vec<u8> = Vec::new();
+    let cells = relocated_memory
+        .iter()
+        .enumerate()
+        .filter_map(|(addr, memory_cell)| Some((addr, memory_cell.as_ref()?)));
+    write_memory_cells(&mut buffer, cells)?;
+    buffer.flush()
+}
 
-    for (i, memory_cell) in relocated_memory.iter().enumerate() {
-        match memory_cell {
-            None => continue,
-            Some(unwrapped_memory_cell) => {
-                encode_relocated_memory(&mut memory_bytes, i, unwrapped_memory_cell);
-            }
-        }
+///Writes the cells of the relocated memory returned by Memory::relocate to writer in the format
+///of write_binary_memory, skipping holes. Fails with InvalidInput, before writing anything, if a
+///value is negative or doesn't fit in 32 bytes.
+pub fn write_binary_memory_to(
+    relocated_memory: &[Option<BigInt>],
+    writer: &mut impl Write,
+) -> io::Result<()> {
+    let cells = || {
+        relocated_memory
+            .iter()
+            .enumerate()
+            .filter_map(|(addr, memory_cell)| Some((addr, memory_cell.as_ref()?)))
+    };
+    if let Some((addr, _)) = cells().find(|(_, value)| value.is_negative() || value.bits() > 256) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("the value at address {addr} doesn't fit in 32 bytes"),
+        ));
     }
 
-    buffer.write_all(&memory_bytes)?;
-    buffer.flush()
+    let mut cell_bytes = Vec::with_capacity(40);
+    for (addr, value) in cells() {
+        cell_bytes.clear();
+        cell_bytes.extend_from_slice(&(addr as u64).to_le_bytes());
+        let (_, mut value_bytes) = value.to_bytes_le();
+        value_bytes.resize(32, 0);
+        cell_bytes.append(&mut value_bytes);
+        writer.write_all(&cell_bytes)?;
+    }
+    Ok(())
+}
+
+///Writes the binary memory file of write_binary_memory straight from the vm's memory, relocating
+///one cell at a time instead of materializing the whole relocated memory first.
+#[allow(clippy::result_large_err)]
+pub fn write_relocated_binary_memory(
+    vm: &mut VirtualMachine,
+    memory_file: &Path,
+) -> Result<(), CairoRunError> {
+    vm.segments.compute_effective_sizes(&vm.memory);
+    let relocation_table = vm.segments.relocate_segments()?;
+    let file = File::create(memory_file).map_err(|_| RunnerError::WriteFail)?;
+    let mut buffer = BufWriter::new(file);
+
+    let mut cell_bytes = Vec::with_capacity(40);
+    for cell in vm.memory.relocated_iter(&relocation_table) {
+        let (addr, value) = cell?;
+        cell_bytes.clear();
+        encode_memory_cell(&mut cell_bytes, addr, &value);
+        buffer
+            .write_all(&cell_bytes)
+            .map_err(|_| RunnerError::WriteFail)?;
+    }
+    buffer.flush().map_err(|_| RunnerError::WriteFail)?;
+    Ok(())
+}
+
+fn write_memory_cells<'a>(
+    buffer: &mut impl Write,
+    cells: impl Iterator<Item = (usize, &'a Felt)>,
+) -> io::Result<()> {
+    let mut cell_bytes = Vec::with_capacity(40);
+    for (addr, memory_cell) in cells {
+        cell_bytes.clear();
+        encode_memory_cell(&mut cell_bytes, addr, memory_cell);
+        buffer.write_all(&cell_bytes)?;
+    }
+    Ok(())
 }
 
-// encodes a given memory cell.
-fn encode_relocated_memory(memory_bytes: &mut Vec<u8>, addr: usize, memory_cell: &Felt) {
+// encodes a given memory cell, both the address and the value are little endian.
+fn encode_memory_cell(memory_bytes: &mut Vec<u8>, addr: usize, memory_cell: &Felt) {
     // append memory address to bytes vector using a 8 bytes representation
-    let mut addr_bytes = (addr as u64).to_le_bytes().to_vec();
-    memory_bytes.append(&mut addr_bytes);
+    memory_bytes.extend_from_slice(&(addr as u64).to_le_bytes());
 
-    // append memory value at address using a 32 bytes representation
-    let mut value_bytes = memory_cell.to_signed_bytes_le();
+    // append memory value at address using a 32 bytes representation, felts are always below 2**252
+    let mut value_bytes = memory_cell.to_biguint().to_bytes_le();
     value_bytes.resize(32, 0);
     memory_bytes.append(&mut value_bytes);
 }
@@ -140,8 +233,13 @@ mod tests {
             hint_processor_definition::HintProcessor,
         },
         utils::test_utils::*,
+        vm::{
+            errors::vm_errors::VirtualMachineError, runners::output_verifier::Sha256OutputVerifier,
+            vm_memory::memory::Memory,
+        },
     };
-    use felt::NewFelt;
+    use felt::{felt_str, NewFelt};
+    use num_traits::One;
     use std::io::Read;
 
     fn run_test_program(
@@ -179,7 +277,7 @@ mod tests {
         assert!(cairo_runner.relocate(&mut vm).is_ok());
         // `main` returns without doing nothing, but `not_main` sets `[ap]` to `1`
         // Memory location was found empirically and simply hardcoded
-        assert_eq!(cairo_runner.relocated_memory[2], Some(Felt::new(123)));
+        assert_eq!(cairo_runner.get_relocated_memory()[2], Some(Felt::new(123)));
     }
 
     fn compare_files(file_path_1: &Path, file_path_2: &Path) -> io::Result<()> {
@@ -200,6 +298,46 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn cairo_run_with_matching_output_hash() {
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        let verifier =
+            Sha256OutputVerifier::new(Sha256OutputVerifier::hash_outputs(&[BigInt::from(7)]));
+        assert!(cairo_run_with_output_verifier(
+            Path::new("cairo_programs/manually_compiled/output_program.json"),
+            "main",
+            false,
+            false,
+            "all",
+            false,
+            &mut hint_processor,
+            Some(&verifier),
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn cairo_run_with_mismatching_output_hash() {
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        let verifier =
+            Sha256OutputVerifier::new(Sha256OutputVerifier::hash_outputs(&[BigInt::from(8)]));
+        assert!(matches!(
+            cairo_run_with_output_verifier(
+                Path::new("cairo_programs/manually_compiled/output_program.json"),
+                "main",
+                false,
+                false,
+                "all",
+                false,
+                &mut hint_processor,
+                Some(&verifier),
+            ),
+            Err(CairoRunError::Runner(
+                RunnerError::OutputVerificationFailed(_)
+            ))
+        ));
+    }
+
     #[test]
     fn cairo_run_with_no_data_program() {
         // a compiled program with no `data` key.
@@ -218,6 +356,62 @@ mod tests {
         .is_err());
     }
 
+    #[test]
+    fn cairo_run_with_empty_data_program() {
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        let result = cairo_run(
+            Path::new("cairo_programs/manually_compiled/empty_data_program.json"),
+            "main",
+            false,
+            false,
+            "plain",
+            false,
+            &mut hint_processor,
+        );
+        assert!(matches!(
+            result,
+            Err(CairoRunError::VmException(VmException {
+                inner_exc: VirtualMachineError::RunnerError(RunnerError::EmptyProgram),
+                ..
+            }))
+        ));
+    }
+
+    #[test]
+    fn cairo_run_main_returns_immediately() {
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        let cairo_runner = cairo_run(
+            Path::new("cairo_programs/manually_compiled/main_returns_immediately.json"),
+            "main",
+            true,
+            false,
+            "plain",
+            false,
+            &mut hint_processor,
+        )
+        .unwrap();
+        //The return fp and pc are relocated to the empty segments after the execution segment
+        assert_eq!(
+            cairo_runner.get_relocated_memory(),
+            [
+                None,
+                Some(felt_str!("2345108766317314046")),
+                Some(Felt::new(4)),
+                Some(Felt::new(4))
+            ]
+        );
+        assert_eq!(
+            cairo_runner.get_relocated_trace(),
+            Some(
+                &[RelocatedTraceEntry {
+                    pc: 1,
+                    ap: 4,
+                    fp: 4
+                }][..]
+            )
+        );
+    }
+
     #[test]
     fn cairo_run_with_no_main_program() {
         // a compiled program with no main scope
@@ -277,12 +471,14 @@ mod tests {
         // relocate memory so we can dump it to file
         assert!(cairo_runner.relocate(&mut vm).is_ok());
         assert!(vm.trace.is_some());
-        assert!(cairo_runner.relocated_trace.is_some());
+        assert!(cairo_runner.get_relocated_trace().is_some());
 
         // write cairo_rs vm trace file
-        assert!(
-            write_binary_trace(&cairo_runner.relocated_trace.unwrap(), cairo_rs_trace_path).is_ok()
-        );
+        assert!(write_binary_trace(
+            cairo_runner.get_relocated_trace().unwrap(),
+            cairo_rs_trace_path
+        )
+        .is_ok());
 
         // compare that the original cairo vm trace file and cairo_rs vm trace files are equal
         assert!(compare_files(cairo_rs_trace_path, expected_trace_path).is_ok());
@@ -303,12 +499,183 @@ mod tests {
         assert!(cairo_runner.relocate(&mut vm).is_ok());
 
         // write cairo_rs vm memory file
-        assert!(write_binary_memory(&cairo_runner.relocated_memory, cairo_rs_memory_path).is_ok());
+        assert!(
+            write_binary_memory(cairo_runner.get_relocated_memory(), cairo_rs_memory_path).is_ok()
+        );
 
         // compare that the original cairo vm memory file and cairo_rs vm memory files are equal
         assert!(compare_files(cairo_rs_memory_path, expected_memory_path).is_ok());
     }
 
+    #[test]
+    fn write_binary_memory_to_matches_golden_file() {
+        let program_path = Path::new("cairo_programs/manually_compiled/fibonacci.json");
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        let (_, mut vm) = run_test_program(program_path, &mut hint_processor).unwrap();
+        vm.segments.compute_effective_sizes(&vm.memory);
+        let relocation_table = vm.segments.relocate_segments().unwrap();
+        let relocated_memory = vm.memory.relocate(&relocation_table).unwrap();
+
+        let mut memory_bytes = Vec::new();
+        write_binary_memory_to(&relocated_memory, &mut memory_bytes).unwrap();
+        assert_eq!(
+            memory_bytes,
+            include_bytes!("../cairo_programs/trace_memory/fibonacci_golden.memory")
+        );
+    }
+
+    #[test]
+    fn fibonacci_memory_matches_loaded_golden_file() {
+        let program_path = Path::new("cairo_programs/manually_compiled/fibonacci.json");
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        let (_, mut vm) = run_test_program(program_path, &mut hint_processor).unwrap();
+        vm.segments.compute_effective_sizes(&vm.memory);
+        let relocation_table = vm.segments.relocate_segments().unwrap();
+        let mut relocated_memory = vm.memory.relocate(&relocation_table).unwrap();
+
+        let loaded_cells = Memory::from_binary_file(Path::new(
+            "cairo_programs/trace_memory/fibonacci_golden.memory",
+        ))
+        .unwrap();
+        assert_eq!(
+            Memory::first_mismatching_address(&loaded_cells, &relocated_memory),
+            None
+        );
+        relocated_memory[5] = Some(BigInt::from(5));
+        assert_eq!(
+            Memory::first_mismatching_address(&loaded_cells, &relocated_memory),
+            Some(5)
+        );
+    }
+
+    #[test]
+    fn write_binary_memory_to_skips_holes() {
+        let relocated_memory = [
+            None,
+            Some(BigInt::one()),
+            None,
+            Some((BigInt::one() << 255) + 7),
+            Some(BigInt::from(0)),
+            None,
+            Some((BigInt::one() << 256) - 1),
+            Some(BigInt::from(0x0102030405060708_u64)),
+        ];
+        let mut memory_bytes = Vec::new();
+        write_binary_memory_to(&relocated_memory, &mut memory_bytes).unwrap();
+        assert_eq!(
+            memory_bytes,
+            include_bytes!("../cairo_programs/trace_memory/holes_golden.memory")
+        );
+    }
+
+    #[test]
+    fn write_binary_memory_to_rejects_values_out_of_range() {
+        for value in [BigInt::one() << 256, BigInt::from(-1)] {
+            let relocated_memory = [None, Some(BigInt::one()), Some(value)];
+            let mut memory_bytes = Vec::new();
+            let error = write_binary_memory_to(&relocated_memory, &mut memory_bytes).unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+            assert_eq!(
+                error.to_string(),
+                "the value at address 2 doesn't fit in 32 bytes"
+            );
+            assert!(memory_bytes.is_empty());
+        }
+    }
+
+    #[test]
+    fn write_relocated_binary_memory_matches_materialized_memory() {
+        let program_path = Path::new("cairo_programs/manually_compiled/fibonacci.json");
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        let (mut cairo_runner, mut vm) =
+            run_test_program(program_path, &mut hint_processor).unwrap();
+        let memory_path = std::env::temp_dir().join("fibonacci_streamed_cairo_rs.memory");
+
+        write_relocated_binary_memory(&mut vm, &memory_path).unwrap();
+        cairo_runner.relocate(&mut vm).unwrap();
+        let mut streamed_memory = Vec::new();
+        File::open(&memory_path)
+            .unwrap()
+            .read_to_end(&mut streamed_memory)
+            .unwrap();
+        assert_eq!(
+            streamed_memory,
+            encode_relocated_memory(cairo_runner.get_relocated_memory())
+        );
+        assert_eq!(
+            streamed_memory,
+            include_bytes!("../cairo_programs/trace_memory/fibonacci_golden.memory")
+        );
+    }
+
+    #[test]
+    fn encode_relocated_trace_known_values() {
+        let trace = [
+            RelocatedTraceEntry {
+                ap: 1,
+                fp: 2,
+                pc: 3,
+            },
+            RelocatedTraceEntry {
+                ap: 0x0102030405060708,
+                fp: 0x100,
+                pc: 0,
+            },
+        ];
+        assert_eq!(
+            encode_relocated_trace(&trace),
+            [
+                [1, 0, 0, 0, 0, 0, 0, 0],
+                [2, 0, 0, 0, 0, 0, 0, 0],
+                [3, 0, 0, 0, 0, 0, 0, 0],
+                [8, 7, 6, 5, 4, 3, 2, 1],
+                [0, 1, 0, 0, 0, 0, 0, 0],
+                [0, 0, 0, 0, 0, 0, 0, 0]
+            ]
+            .concat()
+        );
+    }
+
+    fn encode_relocated_memory(relocated_memory: &[Option<Felt>]) -> Vec<u8> {
+        let mut memory_bytes = Vec::new();
+        let cells = relocated_memory
+            .iter()
+            .enumerate()
+            .filter_map(|(addr, memory_cell)| Some((addr, memory_cell.as_ref()?)));
+        write_memory_cells(&mut memory_bytes, cells).unwrap();
+        memory_bytes
+    }
+
+    #[test]
+    fn encode_relocated_memory_known_values() {
+        let memory = [None, Some(Felt::new(0x0102)), None, Some(Felt::new(-1))];
+        let mut expected = vec![1, 0, 0, 0, 0, 0, 0, 0, 2, 1];
+        expected.resize(40, 0);
+        //The value is PRIME - 1 = 2**251 + 17 * 2**192
+        expected.extend([3, 0, 0, 0, 0, 0, 0, 0]);
+        expected.extend([0; 24]);
+        expected.extend([0x11, 0, 0, 0, 0, 0, 0, 0x08]);
+        assert_eq!(encode_relocated_memory(&memory), expected);
+    }
+
+    #[test]
+    fn fibonacci_trace_and_memory_match_golden_files() {
+        let program_path = Path::new("cairo_programs/manually_compiled/fibonacci.json");
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        let (mut cairo_runner, mut vm) =
+            run_test_program(program_path, &mut hint_processor).unwrap();
+        cairo_runner.relocate(&mut vm).unwrap();
+
+        assert_eq!(
+            encode_relocated_trace(cairo_runner.get_relocated_trace().unwrap()),
+            include_bytes!("../cairo_programs/trace_memory/fibonacci_golden.trace")
+        );
+        assert_eq!(
+            encode_relocated_memory(cairo_runner.get_relocated_memory()),
+            include_bytes!("../cairo_programs/trace_memory/fibonacci_golden.memory")
+        );
+    }
+
     #[test]
     fn run_with_no_trace() {
         let program_path = Path::new("cairo_programs/struct.json");
@@ -1,4 +1,5 @@
 use crate::{
+    any_box,
     hint_processor::{
         builtin_hint_processor::{
             blake2s_utils::{
@@ -12,17 +13,26 @@ use crate::{
                 default_dict_new, dict_new, dict_read, dict_squash_copy_dict,
                 dict_squash_update_ptr, dict_update, dict_write,
             },
+            ed25519_utils::split_xx,
+            expression_hint::{
+                execute_expression_hint, parse_expression_hint, ExpressionHint, StructMembers,
+            },
             find_element_hint::{find_element, search_sorted_lower},
             hint_code,
             keccak_utils::{unsafe_keccak, unsafe_keccak_finalize},
             math_utils::*,
             memcpy_hint_utils::{
-                add_segment, enter_scope, exit_scope, memcpy_continue_copying, memcpy_enter_scope,
+                add_segment, alloc_arena_segment, enter_scope, exit_scope, memcpy_continue_copying,
+                memcpy_enter_scope,
             },
             memset_utils::{memset_continue_loop, memset_enter_scope},
             pow_utils::pow,
             secp::{
                 bigint_utils::{bigint_to_uint256, nondet_bigint3},
+                ec_recover::{
+                    ec_recover_divmod_div_n, ec_recover_product_div_m, ec_recover_product_mod,
+                    ec_recover_sub_a_b,
+                },
                 ec_utils::{
                     compute_doubling_slope, compute_slope, ec_double_assign_new_x,
                     ec_double_assign_new_y, ec_mul_inner, ec_negate, fast_ec_add_assign_new_x,
@@ -37,6 +47,7 @@ use crate::{
             segments::{relocate_segment, temporary_array},
             set::set_add,
             sha256_utils::{sha256_finalize, sha256_input, sha256_main},
+            skip_next_instruction::skip_next_instruction,
             squash_dict_utils::{
                 squash_dict, squash_dict_inner_assert_len_keys,
                 squash_dict_inner_check_access_index, squash_dict_inner_continue_loop,
@@ -52,386 +63,907 @@ use crate::{
                 verify_multiplicity_body, verify_usort,
             },
         },
-        hint_processor_definition::{HintProcessor, HintReference},
+        hint_processor_definition::{get_ids_data, HintProcessor, HintReference},
     },
     serde::deserialize_program::ApTracking,
-    types::exec_scope::ExecutionScopes,
-    vm::{errors::hint_errors::HintError, vm_core::VirtualMachine},
+    types::{exec_scope::ExecutionScopes, program::Program},
+    vm::{
+        const_view::VmConstView,
+        errors::{hint_errors::HintError, vm_errors::VirtualMachineError},
+        vm_core::VirtualMachine,
+    },
 };
-use felt::Felt;
+use felt::{Felt, FeltOps};
+use lazy_static::lazy_static;
+use num_bigint::BigInt;
+use num_traits::Bounded;
 use std::{any::Any, collections::HashMap, rc::Rc};
 
 pub struct HintProcessorData {
     pub code: String,
     pub ap_tracking: ApTracking,
     pub ids_data: HashMap<String, HintReference>,
+    ///Scopes the hint can access, from the outermost to the innermost, used to resolve the
+    ///constants of expression hints
+    pub accessible_scopes: Vec<String>,
+    //Builtin implementation of the code, resolved once when the data is created
+    builtin_hint: Option<BuiltinHintFn>,
+    //Handler registered for the hint's program and pc, see register_for_program
+    program_hint: Option<Rc<HintFunc>>,
 }
 
 impl HintProcessorData {
-    pub fn new_default(code: String, ids_data: HashMap<String, HintReference>) -> Self {
+    ///Creates the data of a hint, resolving its builtin implementation so executing it doesn't
+    ///need to compare the code against every known hint
+    pub fn new(
+        code: String,
+        ap_tracking: ApTracking,
+        ids_data: HashMap<String, HintReference>,
+    ) -> Self {
         HintProcessorData {
+            builtin_hint: BUILTIN_HINTS.resolve(&code),
+            program_hint: None,
             code,
-            ap_tracking: ApTracking::default(),
+            ap_tracking,
             ids_data,
+            accessible_scopes: Vec::new(),
         }
     }
+
+    pub fn new_default(code: String, ids_data: HashMap<String, HintReference>) -> Self {
+        HintProcessorData::new(code, ApTracking::default(), ids_data)
+    }
 }
 
-#[allow(clippy::type_complexity)]
-pub struct HintFunc(
-    pub  Box<
-        dyn Fn(
-                &mut VirtualMachine,
-                &mut ExecutionScopes,
-                &HashMap<String, HintReference>,
-                &ApTracking,
-                &HashMap<String, Felt>,
-            ) -> Result<(), HintError>
-            + Sync,
-    >,
-);
-pub struct BuiltinHintProcessor {
-    pub extra_hints: HashMap<String, Rc<HintFunc>>,
+//Takes the constants of the program, then the limits set with BuiltinHintProcessor::set_limit
+type BuiltinHintFn = fn(
+    &mut VirtualMachine,
+    &mut ExecutionScopes,
+    &HintProcessorData,
+    &HashMap<String, Felt>,
+    &HashMap<String, Felt>,
+) -> Result<(), HintError>;
+
+//Cheap key of a hint code: its length and its first and last 8 bytes
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+struct HintFingerprint {
+    len: usize,
+    prefix: u64,
+    suffix: u64,
 }
-impl BuiltinHintProcessor {
-    pub fn new_empty() -> Self {
-        BuiltinHintProcessor {
-            extra_hints: HashMap::new(),
+
+impl HintFingerprint {
+    fn new(code: &str) -> Self {
+        let bytes = code.as_bytes();
+        let to_u64 = |chunk: &[u8]| {
+            let mut word = [0; 8];
+            word[..chunk.len()].copy_from_slice(chunk);
+            u64::from_le_bytes(word)
+        };
+        HintFingerprint {
+            len: bytes.len(),
+            prefix: to_u64(&bytes[..bytes.len().min(8)]),
+            suffix: to_u64(&bytes[bytes.len().saturating_sub(8)..]),
         }
     }
+}
 
-    pub fn new(extra_hints: HashMap<String, Rc<HintFunc>>) -> Self {
-        BuiltinHintProcessor { extra_hints }
+//Maps hint codes to their implementations by fingerprint, codes sharing a fingerprint are told
+//apart by comparing them in full
+struct HintDispatchTable {
+    hints: HashMap<HintFingerprint, Vec<(&'static str, BuiltinHintFn)>>,
+}
+
+impl HintDispatchTable {
+    fn new(hints: &[(&'static str, BuiltinHintFn)]) -> Self {
+        let mut table = HashMap::<HintFingerprint, Vec<_>>::new();
+        for (code, hint_func) in hints {
+            table
+                .entry(HintFingerprint::new(code))
+                .or_default()
+                .push((*code, *hint_func));
+        }
+        HintDispatchTable { hints: table }
     }
 
-    pub fn add_hint(&mut self, hint_code: String, hint_func: Rc<HintFunc>) {
-        self.extra_hints.insert(hint_code, hint_func);
+    fn resolve(&self, code: &str) -> Option<BuiltinHintFn> {
+        self.hints
+            .get(&HintFingerprint::new(code))?
+            .iter()
+            .find(|(hint_code, _)| *hint_code == code)
+            .map(|(_, hint_func)| *hint_func)
     }
 }
 
-impl HintProcessor for BuiltinHintProcessor {
-    fn execute_hint(
-        &mut self,
-        vm: &mut VirtualMachine,
-        exec_scopes: &mut ExecutionScopes,
-        hint_data: &Box<dyn Any>,
-        constants: &HashMap<String, Felt>,
-    ) -> Result<(), HintError> {
-        let hint_data = hint_data
-            .downcast_ref::<HintProcessorData>()
-            .ok_or(HintError::WrongHintData)?;
+lazy_static! {
+    static ref BUILTIN_HINTS: HintDispatchTable = HintDispatchTable::new(BUILTIN_HINT_FUNCS);
+}
 
-        if let Some(hint_func) = self.extra_hints.get(&hint_data.code) {
-            return hint_func.0(
+const BUILTIN_HINT_FUNCS: &[(&str, BuiltinHintFn)] = &[
+    (hint_code::ADD_SEGMENT, |vm, _, _, _, _| add_segment(vm)),
+    (hint_code::ALLOC_ARENA_SEGMENT, |vm, _, _, _, _| {
+        alloc_arena_segment(vm)
+    }),
+    (hint_code::IS_NN, |vm, _, hint_data, _, _| {
+        is_nn(vm, &hint_data.ids_data, &hint_data.ap_tracking)
+    }),
+    (hint_code::IS_NN_OUT_OF_RANGE, |vm, _, hint_data, _, _| {
+        is_nn_out_of_range(vm, &hint_data.ids_data, &hint_data.ap_tracking)
+    }),
+    (
+        hint_code::ASSERT_LE_FELT,
+        |vm, exec_scopes, hint_data, constants, _| {
+            assert_le_felt(
                 vm,
                 exec_scopes,
                 &hint_data.ids_data,
                 &hint_data.ap_tracking,
                 constants,
-            );
-        }
-
-        match &*hint_data.code {
-            hint_code::ADD_SEGMENT => add_segment(vm),
-            hint_code::IS_NN => is_nn(vm, &hint_data.ids_data, &hint_data.ap_tracking),
-            hint_code::IS_NN_OUT_OF_RANGE => {
-                is_nn_out_of_range(vm, &hint_data.ids_data, &hint_data.ap_tracking)
-            }
-            hint_code::ASSERT_LE_FELT => assert_le_felt(
+            )
+        },
+    ),
+    (
+        hint_code::ASSERT_LE_FELT_EXCLUDED_2,
+        |_, exec_scopes, _, _, _| assert_le_felt_excluded_2(exec_scopes),
+    ),
+    (
+        hint_code::ASSERT_LE_FELT_EXCLUDED_1,
+        |vm, exec_scopes, _, _, _| assert_le_felt_excluded_1(vm, exec_scopes),
+    ),
+    (
+        hint_code::ASSERT_LE_FELT_EXCLUDED_0,
+        |vm, exec_scopes, _, _, _| assert_le_felt_excluded_0(vm, exec_scopes),
+    ),
+    (hint_code::IS_LE_FELT, |vm, _, hint_data, _, _| {
+        is_le_felt(vm, &hint_data.ids_data, &hint_data.ap_tracking)
+    }),
+    (hint_code::ASSERT_250_BITS, |vm, _, hint_data, _, _| {
+        assert_250_bit(vm, &hint_data.ids_data, &hint_data.ap_tracking)
+    }),
+    (hint_code::IS_POSITIVE, |vm, _, hint_data, _, _| {
+        is_positive(vm, &hint_data.ids_data, &hint_data.ap_tracking)
+    }),
+    (
+        hint_code::SPLIT_INT_ASSERT_RANGE,
+        |vm, _, hint_data, _, _| {
+            split_int_assert_range(vm, &hint_data.ids_data, &hint_data.ap_tracking)
+        },
+    ),
+    (hint_code::SPLIT_INT, |vm, _, hint_data, _, _| {
+        split_int(vm, &hint_data.ids_data, &hint_data.ap_tracking)
+    }),
+    (hint_code::ASSERT_NOT_EQUAL, |vm, _, hint_data, _, _| {
+        assert_not_equal(vm, &hint_data.ids_data, &hint_data.ap_tracking)
+    }),
+    (hint_code::ASSERT_NN, |vm, _, hint_data, _, _| {
+        assert_nn(vm, &hint_data.ids_data, &hint_data.ap_tracking)
+    }),
+    (hint_code::SQRT, |vm, _, hint_data, _, _| {
+        sqrt(vm, &hint_data.ids_data, &hint_data.ap_tracking)
+    }),
+    (hint_code::ASSERT_NOT_ZERO, |vm, _, hint_data, _, _| {
+        assert_not_zero(vm, &hint_data.ids_data, &hint_data.ap_tracking)
+    }),
+    (hint_code::VM_EXIT_SCOPE, |_, exec_scopes, _, _, _| {
+        exit_scope(exec_scopes)
+    }),
+    (
+        hint_code::MEMCPY_ENTER_SCOPE,
+        |vm, exec_scopes, hint_data, _, _| {
+            memcpy_enter_scope(vm, exec_scopes, &hint_data.ids_data, &hint_data.ap_tracking)
+        },
+    ),
+    (
+        hint_code::MEMSET_ENTER_SCOPE,
+        |vm, exec_scopes, hint_data, _, _| {
+            memset_enter_scope(vm, exec_scopes, &hint_data.ids_data, &hint_data.ap_tracking)
+        },
+    ),
+    (
+        hint_code::MEMCPY_CONTINUE_COPYING,
+        |vm, exec_scopes, hint_data, _, _| {
+            memcpy_continue_copying(vm, exec_scopes, &hint_data.ids_data, &hint_data.ap_tracking)
+        },
+    ),
+    (
+        hint_code::MEMSET_CONTINUE_LOOP,
+        |vm, exec_scopes, hint_data, _, _| {
+            memset_continue_loop(vm, exec_scopes, &hint_data.ids_data, &hint_data.ap_tracking)
+        },
+    ),
+    (hint_code::SPLIT_FELT, |vm, _, hint_data, _, _| {
+        split_felt(vm, &hint_data.ids_data, &hint_data.ap_tracking)
+    }),
+    (hint_code::UNSIGNED_DIV_REM, |vm, _, hint_data, _, _| {
+        unsigned_div_rem(vm, &hint_data.ids_data, &hint_data.ap_tracking)
+    }),
+    (hint_code::SIGNED_DIV_REM, |vm, _, hint_data, _, _| {
+        signed_div_rem(vm, &hint_data.ids_data, &hint_data.ap_tracking)
+    }),
+    (hint_code::ASSERT_LT_FELT, |vm, _, hint_data, _, _| {
+        assert_lt_felt(vm, &hint_data.ids_data, &hint_data.ap_tracking)
+    }),
+    (
+        hint_code::FIND_ELEMENT,
+        |vm, exec_scopes, hint_data, _, limits| {
+            find_element(
                 vm,
                 exec_scopes,
                 &hint_data.ids_data,
                 &hint_data.ap_tracking,
-                constants,
-            ),
-            hint_code::ASSERT_LE_FELT_EXCLUDED_2 => assert_le_felt_excluded_2(exec_scopes),
-            hint_code::ASSERT_LE_FELT_EXCLUDED_1 => assert_le_felt_excluded_1(vm, exec_scopes),
-            hint_code::ASSERT_LE_FELT_EXCLUDED_0 => assert_le_felt_excluded_0(vm, exec_scopes),
-            hint_code::IS_LE_FELT => is_le_felt(vm, &hint_data.ids_data, &hint_data.ap_tracking),
-            hint_code::ASSERT_250_BITS => {
-                assert_250_bit(vm, &hint_data.ids_data, &hint_data.ap_tracking)
-            }
-            hint_code::IS_POSITIVE => is_positive(vm, &hint_data.ids_data, &hint_data.ap_tracking),
-            hint_code::SPLIT_INT_ASSERT_RANGE => {
-                split_int_assert_range(vm, &hint_data.ids_data, &hint_data.ap_tracking)
-            }
-            hint_code::SPLIT_INT => split_int(vm, &hint_data.ids_data, &hint_data.ap_tracking),
-            hint_code::ASSERT_NOT_EQUAL => {
-                assert_not_equal(vm, &hint_data.ids_data, &hint_data.ap_tracking)
-            }
-            hint_code::ASSERT_NN => assert_nn(vm, &hint_data.ids_data, &hint_data.ap_tracking),
-            hint_code::SQRT => sqrt(vm, &hint_data.ids_data, &hint_data.ap_tracking),
-            hint_code::ASSERT_NOT_ZERO => {
-                assert_not_zero(vm, &hint_data.ids_data, &hint_data.ap_tracking)
-            }
-            hint_code::VM_EXIT_SCOPE => exit_scope(exec_scopes),
-            hint_code::MEMCPY_ENTER_SCOPE => {
-                memcpy_enter_scope(vm, exec_scopes, &hint_data.ids_data, &hint_data.ap_tracking)
-            }
-            hint_code::MEMSET_ENTER_SCOPE => {
-                memset_enter_scope(vm, exec_scopes, &hint_data.ids_data, &hint_data.ap_tracking)
-            }
-            hint_code::MEMCPY_CONTINUE_COPYING => memcpy_continue_copying(
+                limits,
+            )
+        },
+    ),
+    (
+        hint_code::SEARCH_SORTED_LOWER,
+        |vm, exec_scopes, hint_data, _, limits| {
+            search_sorted_lower(
                 vm,
                 exec_scopes,
                 &hint_data.ids_data,
                 &hint_data.ap_tracking,
-            ),
-            hint_code::MEMSET_CONTINUE_LOOP => {
-                memset_continue_loop(vm, exec_scopes, &hint_data.ids_data, &hint_data.ap_tracking)
-            }
-            hint_code::SPLIT_FELT => split_felt(vm, &hint_data.ids_data, &hint_data.ap_tracking),
-            hint_code::UNSIGNED_DIV_REM => {
-                unsigned_div_rem(vm, &hint_data.ids_data, &hint_data.ap_tracking)
-            }
-            hint_code::SIGNED_DIV_REM => {
-                signed_div_rem(vm, &hint_data.ids_data, &hint_data.ap_tracking)
-            }
-            hint_code::ASSERT_LT_FELT => {
-                assert_lt_felt(vm, &hint_data.ids_data, &hint_data.ap_tracking)
-            }
-            hint_code::FIND_ELEMENT => {
-                find_element(vm, exec_scopes, &hint_data.ids_data, &hint_data.ap_tracking)
-            }
-            hint_code::SEARCH_SORTED_LOWER => {
-                search_sorted_lower(vm, exec_scopes, &hint_data.ids_data, &hint_data.ap_tracking)
-            }
-            hint_code::POW => pow(vm, &hint_data.ids_data, &hint_data.ap_tracking),
-            hint_code::SET_ADD => set_add(vm, &hint_data.ids_data, &hint_data.ap_tracking),
-            hint_code::DICT_NEW => dict_new(vm, exec_scopes),
-            hint_code::DICT_READ => {
-                dict_read(vm, exec_scopes, &hint_data.ids_data, &hint_data.ap_tracking)
-            }
-            hint_code::DICT_WRITE => {
-                dict_write(vm, exec_scopes, &hint_data.ids_data, &hint_data.ap_tracking)
-            }
-            hint_code::DEFAULT_DICT_NEW => {
-                default_dict_new(vm, exec_scopes, &hint_data.ids_data, &hint_data.ap_tracking)
-            }
-            hint_code::SQUASH_DICT_INNER_FIRST_ITERATION => squash_dict_inner_first_iteration(
+                limits,
+            )
+        },
+    ),
+    (hint_code::POW, |vm, _, hint_data, _, _| {
+        pow(vm, &hint_data.ids_data, &hint_data.ap_tracking)
+    }),
+    (hint_code::SET_ADD, |vm, _, hint_data, _, _| {
+        set_add(vm, &hint_data.ids_data, &hint_data.ap_tracking)
+    }),
+    (hint_code::DICT_NEW, |vm, exec_scopes, _, _, _| {
+        dict_new(vm, exec_scopes)
+    }),
+    (hint_code::DICT_READ, |vm, exec_scopes, hint_data, _, _| {
+        dict_read(vm, exec_scopes, &hint_data.ids_data, &hint_data.ap_tracking)
+    }),
+    (hint_code::DICT_WRITE, |vm, exec_scopes, hint_data, _, _| {
+        dict_write(vm, exec_scopes, &hint_data.ids_data, &hint_data.ap_tracking)
+    }),
+    (
+        hint_code::DEFAULT_DICT_NEW,
+        |vm, exec_scopes, hint_data, _, _| {
+            default_dict_new(vm, exec_scopes, &hint_data.ids_data, &hint_data.ap_tracking)
+        },
+    ),
+    (
+        hint_code::SQUASH_DICT_INNER_FIRST_ITERATION,
+        |vm, exec_scopes, hint_data, _, _| {
+            squash_dict_inner_first_iteration(
                 vm,
                 exec_scopes,
                 &hint_data.ids_data,
                 &hint_data.ap_tracking,
-            ),
-            hint_code::USORT_ENTER_SCOPE => usort_enter_scope(exec_scopes),
-            hint_code::USORT_BODY => {
-                usort_body(vm, exec_scopes, &hint_data.ids_data, &hint_data.ap_tracking)
-            }
-            hint_code::USORT_VERIFY => {
-                verify_usort(vm, exec_scopes, &hint_data.ids_data, &hint_data.ap_tracking)
-            }
-            hint_code::USORT_VERIFY_MULTIPLICITY_ASSERT => verify_multiplicity_assert(exec_scopes),
-            hint_code::USORT_VERIFY_MULTIPLICITY_BODY => verify_multiplicity_body(
+            )
+        },
+    ),
+    (hint_code::USORT_ENTER_SCOPE, |_, exec_scopes, _, _, _| {
+        usort_enter_scope(exec_scopes)
+    }),
+    (
+        hint_code::USORT_BODY,
+        |vm, exec_scopes, hint_data, _, limits| {
+            usort_body(
                 vm,
                 exec_scopes,
                 &hint_data.ids_data,
                 &hint_data.ap_tracking,
-            ),
-            hint_code::BLAKE2S_COMPUTE => {
-                compute_blake2s(vm, &hint_data.ids_data, &hint_data.ap_tracking)
-            }
-            hint_code::VERIFY_ZERO => {
-                verify_zero(vm, &hint_data.ids_data, &hint_data.ap_tracking, constants)
-            }
-            hint_code::NONDET_BIGINT3 => nondet_bigint3(
+                limits,
+            )
+        },
+    ),
+    (
+        hint_code::USORT_VERIFY,
+        |vm, exec_scopes, hint_data, _, _| {
+            verify_usort(vm, exec_scopes, &hint_data.ids_data, &hint_data.ap_tracking)
+        },
+    ),
+    (
+        hint_code::USORT_VERIFY_MULTIPLICITY_ASSERT,
+        |_, exec_scopes, _, _, _| verify_multiplicity_assert(exec_scopes),
+    ),
+    (
+        hint_code::USORT_VERIFY_MULTIPLICITY_BODY,
+        |vm, exec_scopes, hint_data, _, _| {
+            verify_multiplicity_body(vm, exec_scopes, &hint_data.ids_data, &hint_data.ap_tracking)
+        },
+    ),
+    (hint_code::BLAKE2S_COMPUTE, |vm, _, hint_data, _, _| {
+        compute_blake2s(vm, &hint_data.ids_data, &hint_data.ap_tracking)
+    }),
+    (hint_code::VERIFY_ZERO, |vm, _, hint_data, constants, _| {
+        verify_zero(vm, &hint_data.ids_data, &hint_data.ap_tracking, constants)
+    }),
+    (
+        hint_code::NONDET_BIGINT3,
+        |vm, exec_scopes, hint_data, constants, _| {
+            nondet_bigint3(
                 vm,
                 exec_scopes,
                 &hint_data.ids_data,
                 &hint_data.ap_tracking,
                 constants,
-            ),
-            hint_code::REDUCE => reduce(
+            )
+        },
+    ),
+    (
+        hint_code::REDUCE,
+        |vm, exec_scopes, hint_data, constants, _| {
+            reduce(
                 vm,
                 exec_scopes,
                 &hint_data.ids_data,
                 &hint_data.ap_tracking,
                 constants,
-            ),
-            hint_code::BLAKE2S_FINALIZE => {
-                finalize_blake2s(vm, &hint_data.ids_data, &hint_data.ap_tracking)
-            }
-            hint_code::BLAKE2S_ADD_UINT256 => {
-                blake2s_add_uint256(vm, &hint_data.ids_data, &hint_data.ap_tracking)
-            }
-            hint_code::BLAKE2S_ADD_UINT256_BIGEND => {
-                blake2s_add_uint256_bigend(vm, &hint_data.ids_data, &hint_data.ap_tracking)
-            }
-            hint_code::UNSAFE_KECCAK => {
-                unsafe_keccak(vm, exec_scopes, &hint_data.ids_data, &hint_data.ap_tracking)
-            }
-            hint_code::UNSAFE_KECCAK_FINALIZE => {
-                unsafe_keccak_finalize(vm, &hint_data.ids_data, &hint_data.ap_tracking)
-            }
-            hint_code::SQUASH_DICT_INNER_SKIP_LOOP => squash_dict_inner_skip_loop(
+            )
+        },
+    ),
+    (hint_code::BLAKE2S_FINALIZE, |vm, _, hint_data, _, _| {
+        finalize_blake2s(vm, &hint_data.ids_data, &hint_data.ap_tracking)
+    }),
+    (hint_code::BLAKE2S_ADD_UINT256, |vm, _, hint_data, _, _| {
+        blake2s_add_uint256(vm, &hint_data.ids_data, &hint_data.ap_tracking)
+    }),
+    (
+        hint_code::BLAKE2S_ADD_UINT256_BIGEND,
+        |vm, _, hint_data, _, _| {
+            blake2s_add_uint256_bigend(vm, &hint_data.ids_data, &hint_data.ap_tracking)
+        },
+    ),
+    (
+        hint_code::UNSAFE_KECCAK,
+        |vm, exec_scopes, hint_data, _, limits| {
+            unsafe_keccak(
                 vm,
                 exec_scopes,
                 &hint_data.ids_data,
                 &hint_data.ap_tracking,
-            ),
-            hint_code::SQUASH_DICT_INNER_CHECK_ACCESS_INDEX => {
-                squash_dict_inner_check_access_index(
-                    vm,
-                    exec_scopes,
-                    &hint_data.ids_data,
-                    &hint_data.ap_tracking,
-                )
-            }
-            hint_code::SQUASH_DICT_INNER_CONTINUE_LOOP => squash_dict_inner_continue_loop(
+                limits,
+            )
+        },
+    ),
+    (
+        hint_code::UNSAFE_KECCAK_FINALIZE,
+        |vm, _, hint_data, _, _| {
+            unsafe_keccak_finalize(vm, &hint_data.ids_data, &hint_data.ap_tracking)
+        },
+    ),
+    (
+        hint_code::SQUASH_DICT_INNER_SKIP_LOOP,
+        |vm, exec_scopes, hint_data, _, _| {
+            squash_dict_inner_skip_loop(
                 vm,
                 exec_scopes,
                 &hint_data.ids_data,
                 &hint_data.ap_tracking,
-            ),
-            hint_code::SQUASH_DICT_INNER_ASSERT_LEN_KEYS => {
-                squash_dict_inner_assert_len_keys(exec_scopes)
-            }
-            hint_code::SQUASH_DICT_INNER_LEN_ASSERT => squash_dict_inner_len_assert(exec_scopes),
-            hint_code::SQUASH_DICT_INNER_USED_ACCESSES_ASSERT => {
-                squash_dict_inner_used_accesses_assert(
-                    vm,
-                    exec_scopes,
-                    &hint_data.ids_data,
-                    &hint_data.ap_tracking,
-                )
-            }
-            hint_code::SQUASH_DICT_INNER_NEXT_KEY => squash_dict_inner_next_key(
+            )
+        },
+    ),
+    (
+        hint_code::SQUASH_DICT_INNER_CHECK_ACCESS_INDEX,
+        |vm, exec_scopes, hint_data, _, _| {
+            squash_dict_inner_check_access_index(
                 vm,
                 exec_scopes,
                 &hint_data.ids_data,
                 &hint_data.ap_tracking,
-            ),
-            hint_code::SQUASH_DICT => {
-                squash_dict(vm, exec_scopes, &hint_data.ids_data, &hint_data.ap_tracking)
-            }
-            hint_code::VM_ENTER_SCOPE => enter_scope(exec_scopes),
-            hint_code::DICT_UPDATE => {
-                dict_update(vm, exec_scopes, &hint_data.ids_data, &hint_data.ap_tracking)
-            }
-            hint_code::DICT_SQUASH_COPY_DICT => {
-                dict_squash_copy_dict(vm, exec_scopes, &hint_data.ids_data, &hint_data.ap_tracking)
-            }
-            hint_code::DICT_SQUASH_UPDATE_PTR => {
-                dict_squash_update_ptr(vm, exec_scopes, &hint_data.ids_data, &hint_data.ap_tracking)
-            }
-            hint_code::UINT256_ADD => uint256_add(vm, &hint_data.ids_data, &hint_data.ap_tracking),
-            hint_code::SPLIT_64 => split_64(vm, &hint_data.ids_data, &hint_data.ap_tracking),
-            hint_code::UINT256_SQRT => {
-                uint256_sqrt(vm, &hint_data.ids_data, &hint_data.ap_tracking)
-            }
-            hint_code::UINT256_SIGNED_NN => {
-                uint256_signed_nn(vm, &hint_data.ids_data, &hint_data.ap_tracking)
-            }
-            hint_code::UINT256_UNSIGNED_DIV_REM => {
-                uint256_unsigned_div_rem(vm, &hint_data.ids_data, &hint_data.ap_tracking)
-            }
-            hint_code::BIGINT_TO_UINT256 => {
-                bigint_to_uint256(vm, &hint_data.ids_data, &hint_data.ap_tracking, constants)
-            }
-            hint_code::IS_ZERO_PACK => is_zero_pack(
+            )
+        },
+    ),
+    (
+        hint_code::SQUASH_DICT_INNER_CONTINUE_LOOP,
+        |vm, exec_scopes, hint_data, _, _| {
+            squash_dict_inner_continue_loop(
+                vm,
+                exec_scopes,
+                &hint_data.ids_data,
+                &hint_data.ap_tracking,
+            )
+        },
+    ),
+    (
+        hint_code::SQUASH_DICT_INNER_ASSERT_LEN_KEYS,
+        |_, exec_scopes, _, _, _| squash_dict_inner_assert_len_keys(exec_scopes),
+    ),
+    (
+        hint_code::SQUASH_DICT_INNER_LEN_ASSERT,
+        |_, exec_scopes, _, _, _| squash_dict_inner_len_assert(exec_scopes),
+    ),
+    (
+        hint_code::SQUASH_DICT_INNER_USED_ACCESSES_ASSERT,
+        |vm, exec_scopes, hint_data, _, _| {
+            squash_dict_inner_used_accesses_assert(
+                vm,
+                exec_scopes,
+                &hint_data.ids_data,
+                &hint_data.ap_tracking,
+            )
+        },
+    ),
+    (
+        hint_code::SQUASH_DICT_INNER_NEXT_KEY,
+        |vm, exec_scopes, hint_data, _, _| {
+            squash_dict_inner_next_key(vm, exec_scopes, &hint_data.ids_data, &hint_data.ap_tracking)
+        },
+    ),
+    (
+        hint_code::SQUASH_DICT,
+        |vm, exec_scopes, hint_data, _, limits| {
+            squash_dict(
+                vm,
+                exec_scopes,
+                &hint_data.ids_data,
+                &hint_data.ap_tracking,
+                limits,
+            )
+        },
+    ),
+    (hint_code::VM_ENTER_SCOPE, |_, exec_scopes, _, _, _| {
+        enter_scope(exec_scopes)
+    }),
+    (
+        hint_code::DICT_UPDATE,
+        |vm, exec_scopes, hint_data, _, _| {
+            dict_update(vm, exec_scopes, &hint_data.ids_data, &hint_data.ap_tracking)
+        },
+    ),
+    (
+        hint_code::DICT_SQUASH_COPY_DICT,
+        |vm, exec_scopes, hint_data, _, _| {
+            dict_squash_copy_dict(vm, exec_scopes, &hint_data.ids_data, &hint_data.ap_tracking)
+        },
+    ),
+    (
+        hint_code::DICT_SQUASH_UPDATE_PTR,
+        |vm, exec_scopes, hint_data, _, _| {
+            dict_squash_update_ptr(vm, exec_scopes, &hint_data.ids_data, &hint_data.ap_tracking)
+        },
+    ),
+    (hint_code::UINT256_ADD, |vm, _, hint_data, _, _| {
+        uint256_add(vm, &hint_data.ids_data, &hint_data.ap_tracking)
+    }),
+    (hint_code::SPLIT_64, |vm, _, hint_data, _, _| {
+        split_64(vm, &hint_data.ids_data, &hint_data.ap_tracking)
+    }),
+    (hint_code::UINT256_SQRT, |vm, _, hint_data, _, _| {
+        uint256_sqrt(vm, &hint_data.ids_data, &hint_data.ap_tracking)
+    }),
+    (hint_code::UINT256_SIGNED_NN, |vm, _, hint_data, _, _| {
+        uint256_signed_nn(vm, &hint_data.ids_data, &hint_data.ap_tracking)
+    }),
+    (
+        hint_code::UINT256_UNSIGNED_DIV_REM,
+        |vm, _, hint_data, _, _| {
+            uint256_unsigned_div_rem(vm, &hint_data.ids_data, &hint_data.ap_tracking)
+        },
+    ),
+    (
+        hint_code::BIGINT_TO_UINT256,
+        |vm, _, hint_data, constants, _| {
+            bigint_to_uint256(vm, &hint_data.ids_data, &hint_data.ap_tracking, constants)
+        },
+    ),
+    (
+        hint_code::IS_ZERO_PACK,
+        |vm, exec_scopes, hint_data, constants, _| {
+            is_zero_pack(
                 vm,
                 exec_scopes,
                 &hint_data.ids_data,
                 &hint_data.ap_tracking,
                 constants,
-            ),
-            hint_code::IS_ZERO_NONDET => is_zero_nondet(vm, exec_scopes),
-            hint_code::IS_ZERO_ASSIGN_SCOPE_VARS => {
-                is_zero_assign_scope_variables(exec_scopes, constants)
-            }
-            hint_code::DIV_MOD_N_PACKED_DIVMOD => div_mod_n_packed_divmod(
+            )
+        },
+    ),
+    (hint_code::IS_ZERO_NONDET, |vm, exec_scopes, _, _, _| {
+        is_zero_nondet(vm, exec_scopes)
+    }),
+    (
+        hint_code::IS_ZERO_ASSIGN_SCOPE_VARS,
+        |_, exec_scopes, _, constants, _| is_zero_assign_scope_variables(exec_scopes, constants),
+    ),
+    (
+        hint_code::DIV_MOD_N_PACKED_DIVMOD,
+        |vm, exec_scopes, hint_data, constants, _| {
+            div_mod_n_packed_divmod(
                 vm,
                 exec_scopes,
                 &hint_data.ids_data,
                 &hint_data.ap_tracking,
                 constants,
-            ),
-            hint_code::DIV_MOD_N_SAFE_DIV => div_mod_n_safe_div(exec_scopes, constants),
-            hint_code::GET_POINT_FROM_X => get_point_from_x(
+            )
+        },
+    ),
+    (
+        hint_code::DIV_MOD_N_SAFE_DIV,
+        |_, exec_scopes, _, constants, _| div_mod_n_safe_div(exec_scopes, constants),
+    ),
+    (
+        hint_code::EC_RECOVER_DIV_MOD_N_PACKED,
+        |vm, exec_scopes, hint_data, _, _| {
+            ec_recover_divmod_div_n(vm, exec_scopes, &hint_data.ids_data, &hint_data.ap_tracking)
+        },
+    ),
+    (
+        hint_code::EC_RECOVER_SUB_A_B,
+        |vm, exec_scopes, hint_data, _, _| {
+            ec_recover_sub_a_b(vm, exec_scopes, &hint_data.ids_data, &hint_data.ap_tracking)
+        },
+    ),
+    (
+        hint_code::EC_RECOVER_PRODUCT_MOD,
+        |vm, exec_scopes, hint_data, _, _| {
+            ec_recover_product_mod(vm, exec_scopes, &hint_data.ids_data, &hint_data.ap_tracking)
+        },
+    ),
+    (
+        hint_code::EC_RECOVER_PRODUCT_DIV_M,
+        |_, exec_scopes, _, _, _| ec_recover_product_div_m(exec_scopes),
+    ),
+    (
+        hint_code::GET_POINT_FROM_X,
+        |vm, exec_scopes, hint_data, constants, _| {
+            get_point_from_x(
                 vm,
                 exec_scopes,
                 &hint_data.ids_data,
                 &hint_data.ap_tracking,
                 constants,
-            ),
-            hint_code::EC_NEGATE => ec_negate(
+            )
+        },
+    ),
+    (
+        hint_code::EC_NEGATE,
+        |vm, exec_scopes, hint_data, constants, _| {
+            ec_negate(
                 vm,
                 exec_scopes,
                 &hint_data.ids_data,
                 &hint_data.ap_tracking,
                 constants,
-            ),
-            hint_code::EC_DOUBLE_SCOPE => compute_doubling_slope(
+            )
+        },
+    ),
+    (
+        hint_code::EC_DOUBLE_SCOPE,
+        |vm, exec_scopes, hint_data, constants, _| {
+            compute_doubling_slope(
                 vm,
                 exec_scopes,
                 &hint_data.ids_data,
                 &hint_data.ap_tracking,
                 constants,
-            ),
-            hint_code::COMPUTE_SLOPE => compute_slope(
+            )
+        },
+    ),
+    (
+        hint_code::COMPUTE_SLOPE,
+        |vm, exec_scopes, hint_data, constants, _| {
+            compute_slope(
                 vm,
                 exec_scopes,
                 &hint_data.ids_data,
                 &hint_data.ap_tracking,
                 constants,
-            ),
-            hint_code::EC_DOUBLE_ASSIGN_NEW_X => ec_double_assign_new_x(
+            )
+        },
+    ),
+    (
+        hint_code::EC_DOUBLE_ASSIGN_NEW_X,
+        |vm, exec_scopes, hint_data, constants, _| {
+            ec_double_assign_new_x(
                 vm,
                 exec_scopes,
                 &hint_data.ids_data,
                 &hint_data.ap_tracking,
                 constants,
-            ),
-            hint_code::EC_DOUBLE_ASSIGN_NEW_Y => ec_double_assign_new_y(exec_scopes, constants),
-            hint_code::KECCAK_WRITE_ARGS => {
-                keccak_write_args(vm, &hint_data.ids_data, &hint_data.ap_tracking)
-            }
-            hint_code::COMPARE_BYTES_IN_WORD_NONDET => compare_bytes_in_word_nondet(
+            )
+        },
+    ),
+    (
+        hint_code::EC_DOUBLE_ASSIGN_NEW_Y,
+        |_, exec_scopes, _, constants, _| ec_double_assign_new_y(exec_scopes, constants),
+    ),
+    (hint_code::KECCAK_WRITE_ARGS, |vm, _, hint_data, _, _| {
+        keccak_write_args(vm, &hint_data.ids_data, &hint_data.ap_tracking)
+    }),
+    (
+        hint_code::COMPARE_BYTES_IN_WORD_NONDET,
+        |vm, _, hint_data, constants, _| {
+            compare_bytes_in_word_nondet(vm, &hint_data.ids_data, &hint_data.ap_tracking, constants)
+        },
+    ),
+    (hint_code::SHA256_MAIN, |vm, _, hint_data, _, _| {
+        sha256_main(vm, &hint_data.ids_data, &hint_data.ap_tracking)
+    }),
+    (hint_code::SHA256_INPUT, |vm, _, hint_data, _, _| {
+        sha256_input(vm, &hint_data.ids_data, &hint_data.ap_tracking)
+    }),
+    (hint_code::SHA256_FINALIZE, |vm, _, hint_data, _, _| {
+        sha256_finalize(vm, &hint_data.ids_data, &hint_data.ap_tracking)
+    }),
+    (
+        hint_code::COMPARE_KECCAK_FULL_RATE_IN_BYTES_NONDET,
+        |vm, _, hint_data, constants, _| {
+            compare_keccak_full_rate_in_bytes_nondet(
                 vm,
                 &hint_data.ids_data,
                 &hint_data.ap_tracking,
                 constants,
-            ),
-            hint_code::SHA256_MAIN => sha256_main(vm, &hint_data.ids_data, &hint_data.ap_tracking),
-            hint_code::SHA256_INPUT => {
-                sha256_input(vm, &hint_data.ids_data, &hint_data.ap_tracking)
-            }
-            hint_code::SHA256_FINALIZE => {
-                sha256_finalize(vm, &hint_data.ids_data, &hint_data.ap_tracking)
-            }
-            hint_code::COMPARE_KECCAK_FULL_RATE_IN_BYTES_NONDET => {
-                compare_keccak_full_rate_in_bytes_nondet(
-                    vm,
-                    &hint_data.ids_data,
-                    &hint_data.ap_tracking,
-                    constants,
-                )
-            }
-            hint_code::BLOCK_PERMUTATION => {
-                block_permutation(vm, &hint_data.ids_data, &hint_data.ap_tracking, constants)
-            }
-            hint_code::CAIRO_KECCAK_FINALIZE => {
-                cairo_keccak_finalize(vm, &hint_data.ids_data, &hint_data.ap_tracking, constants)
-            }
-            hint_code::FAST_EC_ADD_ASSIGN_NEW_X => fast_ec_add_assign_new_x(
+            )
+        },
+    ),
+    (
+        hint_code::BLOCK_PERMUTATION,
+        |vm, _, hint_data, constants, _| {
+            block_permutation(vm, &hint_data.ids_data, &hint_data.ap_tracking, constants)
+        },
+    ),
+    (
+        hint_code::CAIRO_KECCAK_FINALIZE,
+        |vm, _, hint_data, constants, _| {
+            cairo_keccak_finalize(vm, &hint_data.ids_data, &hint_data.ap_tracking, constants)
+        },
+    ),
+    (
+        hint_code::FAST_EC_ADD_ASSIGN_NEW_X,
+        |vm, exec_scopes, hint_data, constants, _| {
+            fast_ec_add_assign_new_x(
                 vm,
                 exec_scopes,
                 &hint_data.ids_data,
                 &hint_data.ap_tracking,
                 constants,
-            ),
-            hint_code::FAST_EC_ADD_ASSIGN_NEW_Y => fast_ec_add_assign_new_y(exec_scopes, constants),
-            hint_code::EC_MUL_INNER => {
-                ec_mul_inner(vm, &hint_data.ids_data, &hint_data.ap_tracking)
-            }
-            hint_code::RELOCATE_SEGMENT => {
-                relocate_segment(vm, &hint_data.ids_data, &hint_data.ap_tracking)
+            )
+        },
+    ),
+    (
+        hint_code::FAST_EC_ADD_ASSIGN_NEW_Y,
+        |_, exec_scopes, _, constants, _| fast_ec_add_assign_new_y(exec_scopes, constants),
+    ),
+    (hint_code::EC_MUL_INNER, |vm, _, hint_data, _, _| {
+        ec_mul_inner(vm, &hint_data.ids_data, &hint_data.ap_tracking)
+    }),
+    (hint_code::RELOCATE_SEGMENT, |vm, _, hint_data, _, _| {
+        relocate_segment(vm, &hint_data.ids_data, &hint_data.ap_tracking)
+    }),
+    (hint_code::TEMPORARY_ARRAY, |vm, _, hint_data, _, _| {
+        temporary_array(vm, &hint_data.ids_data, &hint_data.ap_tracking)
+    }),
+    (hint_code::SKIP_NEXT_INSTRUCTION, |vm, _, _, _, _| {
+        skip_next_instruction(vm)
+    }),
+    (
+        hint_code::NONDET_ELEMENTS_OVER_TEN,
+        |vm, _, hint_data, _, _| {
+            nondet_elements_over_x(vm, &hint_data.ids_data, &hint_data.ap_tracking, 10)
+        },
+    ),
+    (
+        hint_code::NONDET_ELEMENTS_OVER_TWO,
+        |vm, _, hint_data, _, _| {
+            nondet_elements_over_x(vm, &hint_data.ids_data, &hint_data.ap_tracking, 2)
+        },
+    ),
+    (hint_code::IS_250_BITS, |vm, _, hint_data, _, _| {
+        is_250_bits(vm, &hint_data.ids_data, &hint_data.ap_tracking)
+    }),
+    (
+        hint_code::IS_ADDR_BOUNDED,
+        |vm, _, hint_data, constants, _| {
+            is_addr_bounded(vm, &hint_data.ids_data, &hint_data.ap_tracking, constants)
+        },
+    ),
+    (hint_code::SPLIT_XX, |vm, _, hint_data, constants, _| {
+        split_xx(vm, &hint_data.ids_data, &hint_data.ap_tracking, constants)
+    }),
+];
+
+#[allow(clippy::type_complexity)]
+pub struct HintFunc(
+    pub  Box<
+        dyn Fn(
+                &mut VirtualMachine,
+                &mut ExecutionScopes,
+                &HashMap<String, HintReference>,
+                &ApTracking,
+                &HashMap<String, Felt>,
+                &VmConstView,
+            ) -> Result<(), HintError>
+            + Sync,
+    >,
+);
+pub struct BuiltinHintProcessor {
+    pub extra_hints: HashMap<String, Rc<HintFunc>>,
+    expression_hints_enabled: bool,
+    struct_members: StructMembers,
+    //PRIME as used by expression hints
+    prime: BigInt,
+    //Parsed unknown hints, None if the code isn't an expression hint
+    expression_hints: HashMap<String, Option<ExpressionHint>>,
+    limits: HashMap<String, Felt>,
+    //Handlers keyed by program hash and pc offset, see register_for_program
+    program_hints: HashMap<(Felt, usize), Rc<HintFunc>>,
+    program_hints_only: bool,
+}
+impl BuiltinHintProcessor {
+    pub fn new_empty() -> Self {
+        BuiltinHintProcessor::new(HashMap::new())
+    }
+
+    pub fn new(extra_hints: HashMap<String, Rc<HintFunc>>) -> Self {
+        BuiltinHintProcessor {
+            extra_hints,
+            expression_hints_enabled: false,
+            struct_members: HashMap::new(),
+            prime: BigInt::from(Felt::max_value().to_biguint()) + 1,
+            expression_hints: HashMap::new(),
+            limits: HashMap::new(),
+            program_hints: HashMap::new(),
+            program_hints_only: false,
+        }
+    }
+
+    pub fn add_hint(&mut self, hint_code: String, hint_func: Rc<HintFunc>) {
+        self.extra_hints.insert(hint_code, hint_func);
+    }
+
+    ///Registers the handler of the hint at pc_offset in the program with the given hash, as
+    ///computed by Program::compute_hash. The handler runs instead of whatever the hint's code
+    ///says, as long as the runner knows the program's hash, see CairoRunner::compute_program_hash.
+    pub fn register_for_program(
+        &mut self,
+        program_hash: Felt,
+        pc_offset: usize,
+        hint_func: Rc<HintFunc>,
+    ) {
+        self.program_hints
+            .insert((program_hash, pc_offset), hint_func);
+    }
+
+    ///Makes the processor refuse every hint which wasn't registered with register_for_program,
+    ///so no hint is dispatched by its code. Disabled by default.
+    pub fn set_program_hints_only(&mut self, enabled: bool) {
+        self.program_hints_only = enabled;
+    }
+
+    ///Enables or disables running unknown hints of the form `ids.x = <expression>` with the
+    ///builtin expression evaluator. They are disabled by default.
+    pub fn set_expression_hints_enabled(&mut self, enabled: bool) {
+        self.expression_hints_enabled = enabled;
+    }
+
+    ///Sets a limit checked by the hints, such as find_element_max_size, overriding the variable
+    ///of the same name in the execution scopes. Hints which bound their inputs with guard_le fail
+    ///with ValueExceedsLimit when the value is greater than the limit.
+    pub fn set_limit(&mut self, limit_name: &str, limit: Felt) {
+        self.limits.insert(limit_name.to_string(), limit);
+    }
+
+    ///Registers the member offsets of a struct, so expression hints can access them as ids.x.member
+    pub fn add_struct_members(&mut self, struct_name: String, members: HashMap<String, usize>) {
+        self.struct_members.insert(struct_name, members);
+    }
+
+    ///Registers the member offsets of every struct declared in the program's identifiers
+    pub fn add_struct_members_from_program(&mut self, program: &Program) {
+        for (name, identifier) in program.identifiers.iter() {
+            if let (Some("struct"), Some(members)) =
+                (identifier.type_.as_deref(), &identifier.members)
+            {
+                self.add_struct_members(
+                    name.clone(),
+                    members
+                        .iter()
+                        .map(|(member, data)| (member.clone(), data.offset))
+                        .collect(),
+                );
             }
-            hint_code::TEMPORARY_ARRAY => {
-                temporary_array(vm, &hint_data.ids_data, &hint_data.ap_tracking)
+        }
+    }
+
+    fn execute_expression_hint(
+        &mut self,
+        vm: &mut VirtualMachine,
+        hint_data: &HintProcessorData,
+        constants: &HashMap<String, Felt>,
+    ) -> Result<(), HintError> {
+        if !self.expression_hints_enabled {
+            return Err(HintError::UnknownHint(hint_data.code.clone()));
+        }
+        let hint = self
+            .expression_hints
+            .entry(hint_data.code.clone())
+            .or_insert_with(|| parse_expression_hint(&hint_data.code));
+        match hint {
+            Some(hint) => execute_expression_hint(
+                hint,
+                vm,
+                hint_data,
+                constants,
+                &self.struct_members,
+                &self.prime,
+            ),
+            _ => Err(HintError::UnknownHint(hint_data.code.clone())),
+        }
+    }
+}
+
+impl HintProcessor for BuiltinHintProcessor {
+    fn execute_hint(
+        &mut self,
+        vm: &mut VirtualMachine,
+        exec_scopes: &mut ExecutionScopes,
+        hint_data: &Box<dyn Any>,
+        constants: &HashMap<String, Felt>,
+    ) -> Result<(), HintError> {
+        let hint_data = hint_data
+            .downcast_ref::<HintProcessorData>()
+            .ok_or(HintError::WrongHintData)?;
+
+        if let Some(hint_func) = &hint_data.program_hint {
+            let const_view = Rc::clone(&vm.const_view);
+            return hint_func.0(
+                vm,
+                exec_scopes,
+                &hint_data.ids_data,
+                &hint_data.ap_tracking,
+                constants,
+                &const_view,
+            );
+        }
+        if self.program_hints_only {
+            return Err(HintError::HintNotRegisteredForProgram(
+                hint_data.code.clone(),
+            ));
+        }
+
+        //Avoids hashing the hint code on every execution when no extra hints were added
+        if !self.extra_hints.is_empty() {
+            if let Some(hint_func) = self.extra_hints.get(&hint_data.code) {
+                let const_view = Rc::clone(&vm.const_view);
+                return hint_func.0(
+                    vm,
+                    exec_scopes,
+                    &hint_data.ids_data,
+                    &hint_data.ap_tracking,
+                    constants,
+                    &const_view,
+                );
             }
-            code => Err(HintError::UnknownHint(code.to_string())),
         }
+
+        match hint_data.builtin_hint {
+            Some(hint_func) => hint_func(vm, exec_scopes, hint_data, constants, &self.limits),
+            None => self.execute_expression_hint(vm, hint_data, constants),
+        }
+    }
+
+    fn compile_hint_at(
+        &self,
+        program_hash: Option<&Felt>,
+        pc_offset: usize,
+        accessible_scopes: &[String],
+        hint_code: &str,
+        ap_tracking_data: &ApTracking,
+        reference_ids: &HashMap<String, usize>,
+        references: &HashMap<usize, HintReference>,
+    ) -> Result<Box<dyn Any>, VirtualMachineError> {
+        let program_hint = program_hash
+            .and_then(|program_hash| self.program_hints.get(&(program_hash.clone(), pc_offset)));
+        if program_hint.is_none() && self.program_hints_only {
+            return Err(VirtualMachineError::CompileHintFail(hint_code.to_string()));
+        }
+        let mut hint_data = HintProcessorData::new(
+            hint_code.to_string(),
+            ap_tracking_data.clone(),
+            get_ids_data(reference_ids, references)?,
+        );
+        hint_data.accessible_scopes = accessible_scopes.to_vec();
+        hint_data.program_hint = program_hint.cloned();
+        Ok(any_box!(hint_data))
     }
 }
 
@@ -439,10 +971,20 @@ impl HintProcessor for BuiltinHintProcessor {
 mod tests {
     use super::*;
     use crate::{
-        any_box,
-        hint_processor::hint_processor_definition::HintProcessor,
-        types::{exec_scope::ExecutionScopes, relocatable::MaybeRelocatable},
+        any_box, cloneable_box,
+        hint_processor::builtin_hint_processor::hint_utils::insert_value_into_ap,
+        hint_processor::{
+            builtin_hint_processor::keccak_utils::KECCAK_MAX_SIZE,
+            hint_processor_definition::HintProcessor,
+        },
+        serde::deserialize_program::{FlowTrackingData, HintParams},
+        types::{
+            builtin_name::BuiltinName,
+            exec_scope::ExecutionScopes,
+            relocatable::{MaybeRelocatable, Relocatable},
+        },
         utils::test_utils::*,
+        vm::runners::cairo_runner::CairoRunner,
         vm::{
             errors::{
                 exec_scope_errors::ExecScopeError, memory_errors::MemoryError,
@@ -488,11 +1030,11 @@ mod tests {
     fn run_alloc_hint_ap_is_not_empty() {
         let hint_code = "memory[ap] = segments.add()";
         let mut vm = vm!();
-        //Add 3 segments to the memory
-        add_segments!(vm, 3);
         vm.run_context.ap = 6;
         //Insert something into ap
         vm.memory = memory![((1, 6), (1, 6))];
+        //Add a third segment to the memory
+        add_segments!(vm, 1);
         //ids and references are not needed for this test
         assert_eq!(
             run_hint!(vm, HashMap::new(), hint_code),
@@ -619,7 +1161,7 @@ mod tests {
         let mut vm = vm!();
         // Create new vm scope with dummy variable
         let mut exec_scopes = ExecutionScopes::new();
-        let a_value: Box<dyn Any> = Box::new(Felt::one());
+        let a_value = cloneable_box!(Felt::one());
         exec_scopes.enter_scope(HashMap::from([(String::from("a"), a_value)]));
         // Initialize memory segments
         add_segments!(vm, 1);
@@ -700,7 +1242,11 @@ mod tests {
         let mut exec_scopes = scope![("__keccak_max_size", Felt::new(2))];
         assert_eq!(
             run_hint!(vm, ids_data, hint_code, &mut exec_scopes),
-            Err(HintError::KeccakMaxSize(Felt::new(5), Felt::new(2)))
+            Err(HintError::ValueExceedsLimit(
+                Felt::new(5),
+                Felt::new(2),
+                KECCAK_MAX_SIZE.to_string()
+            ))
         );
     }
 
@@ -818,6 +1364,7 @@ mod tests {
         _ids_data: &HashMap<String, HintReference>,
         _ap_tracking: &ApTracking,
         _constants: &HashMap<String, Felt>,
+        _const_view: &VmConstView,
     ) -> Result<(), HintError> {
         exec_scopes.enter_scope(HashMap::new());
         Ok(())
@@ -857,4 +1404,181 @@ mod tests {
         );
         assert_eq!(exec_scopes.data.len(), 3);
     }
+
+    #[test]
+    fn dispatch_codes_sharing_fingerprint() {
+        let code_a = "memory[ap] = ids.a # first hint";
+        let code_b = "memory[ap] = ids.b # first hint";
+        let code_c = "memory[ap] = ids.c # first hint";
+        assert_eq!(HintFingerprint::new(code_a), HintFingerprint::new(code_b));
+        assert_eq!(HintFingerprint::new(code_a), HintFingerprint::new(code_c));
+        let table = HintDispatchTable::new(&[
+            (code_a, |_, exec_scopes, _, _, _| {
+                exec_scopes.insert_value("hint", 'a');
+                Ok(())
+            }),
+            (code_b, |_, exec_scopes, _, _, _| {
+                exec_scopes.insert_value("hint", 'b');
+                Ok(())
+            }),
+        ]);
+        let mut vm = vm!();
+        let mut exec_scopes = ExecutionScopes::new();
+        let hint_data = HintProcessorData::new_default(code_a.to_string(), HashMap::new());
+        for (code, expected) in [(code_a, 'a'), (code_b, 'b')] {
+            let hint_func = table.resolve(code).unwrap();
+            assert_eq!(
+                hint_func(
+                    &mut vm,
+                    &mut exec_scopes,
+                    &hint_data,
+                    &HashMap::new(),
+                    &HashMap::new()
+                ),
+                Ok(())
+            );
+            assert_eq!(exec_scopes.get::<char>("hint").unwrap(), expected);
+        }
+        assert!(table.resolve(code_c).is_none());
+    }
+
+    #[test]
+    fn resolve_builtin_hint_on_creation() {
+        let hint_data =
+            HintProcessorData::new_default(hint_code::ADD_SEGMENT.to_string(), HashMap::new());
+        assert!(hint_data.builtin_hint.is_some());
+        let hint_data = HintProcessorData::new_default(String::from("ids.a = 1"), HashMap::new());
+        assert!(hint_data.builtin_hint.is_none());
+    }
+
+    //Program running the given hint before its only instruction
+    fn program_with_hint(instruction: i64, hint_code: &str) -> Program {
+        let mut program = Program {
+            data: vec![mayberelocatable!(instruction)],
+            main: Some(0),
+            ..Program::default()
+        };
+        program.hints.insert(
+            0,
+            vec![HintParams {
+                code: hint_code.to_string(),
+                accessible_scopes: Vec::new(),
+                flow_tracking_data: FlowTrackingData {
+                    ap_tracking: ApTracking::new(),
+                    reference_ids: HashMap::new(),
+                },
+            }],
+        );
+        program
+    }
+
+    #[test]
+    fn program_hints_only_dispatch_by_program_hash_and_pc() {
+        let hint_code = "memory[ap] = segments.add()";
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        hint_processor.set_program_hints_only(true);
+
+        let mut runner = CairoRunner::new(&program_with_hint(1, hint_code), "all", false).unwrap();
+        let program_hash = runner.compute_program_hash().unwrap();
+        hint_processor.register_for_program(
+            program_hash,
+            0,
+            Rc::new(HintFunc(Box::new(|vm, _, _, _, _, _| {
+                insert_value_into_ap(vm, 42)
+            }))),
+        );
+        let hint_data_dictionary = runner
+            .get_hint_data_dictionary(&HashMap::new(), &mut hint_processor)
+            .unwrap();
+        let mut vm = vm!();
+        add_segments!(vm, 2);
+        run_context!(vm, 0, 0, 0);
+        assert_eq!(
+            hint_processor.execute_hint(
+                &mut vm,
+                exec_scopes_ref!(),
+                &hint_data_dictionary[&0][0],
+                &HashMap::new()
+            ),
+            Ok(())
+        );
+        //The registered handler ran instead of the hint's code
+        check_memory![vm.memory, ((1, 0), 42)];
+
+        //Same hint in a different program
+        let mut other_runner =
+            CairoRunner::new(&program_with_hint(2, hint_code), "all", false).unwrap();
+        other_runner.compute_program_hash().unwrap();
+        assert!(matches!(
+            other_runner.get_hint_data_dictionary(&HashMap::new(), &mut hint_processor),
+            Err(VirtualMachineError::CompileHintFail(code)) if code == hint_code
+        ));
+        //Same program, but the runner doesn't know its hash
+        let runner = CairoRunner::new(&program_with_hint(1, hint_code), "all", false).unwrap();
+        assert!(runner
+            .get_hint_data_dictionary(&HashMap::new(), &mut hint_processor)
+            .is_err());
+    }
+
+    #[test]
+    fn custom_hint_reduces_mod_the_prime_of_the_const_view() {
+        let hint_code = "memory[ap] = (PRIME - 1) + 5";
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        hint_processor.add_hint(
+            hint_code.to_string(),
+            Rc::new(HintFunc(Box::new(|vm, _, _, _, _, const_view| {
+                let value = (const_view.prime() - 1_u32 + 5_u32) % const_view.prime();
+                insert_value_into_ap(vm, Felt::from(value))?;
+                let ap = vm.get_ap();
+                vm.insert_value(&(ap + 1), const_view.rc_bound().unwrap())?;
+                vm.insert_value(&(ap + 2), const_view.program_hash().unwrap())?;
+                assert_eq!(const_view.layout(), "small");
+                Ok(())
+            }))),
+        );
+        let mut program = program_with_hint(1, hint_code);
+        program.builtins = vec![BuiltinName::RangeCheck];
+        let mut runner = CairoRunner::new(&program, "small", false).unwrap();
+        let program_hash = runner.compute_program_hash().unwrap();
+        let mut vm = vm!();
+        runner.initialize(&mut vm).unwrap();
+        assert_eq!(vm.const_view().layout(), "small");
+
+        let hint_data_dictionary = runner
+            .get_hint_data_dictionary(&HashMap::new(), &mut hint_processor)
+            .unwrap();
+        assert_eq!(
+            hint_processor.execute_hint(
+                &mut vm,
+                exec_scopes_ref!(),
+                &hint_data_dictionary[&0][0],
+                &HashMap::new()
+            ),
+            Ok(())
+        );
+        let ap = vm.get_ap();
+        assert_eq!(vm.get_integer(&ap).unwrap().as_ref(), &Felt::new(4));
+        assert_eq!(
+            vm.get_integer(&(ap + 1)).unwrap().as_ref(),
+            &(Felt::one() << 128_u32)
+        );
+        assert_eq!(vm.get_integer(&(ap + 2)).unwrap().as_ref(), &program_hash);
+    }
+
+    #[test]
+    fn program_hints_only_refuses_hint_data_without_handler() {
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        hint_processor.set_program_hints_only(true);
+        let hint_data = any_box!(HintProcessorData::new_default(
+            hint_code::ADD_SEGMENT.to_string(),
+            HashMap::new()
+        ));
+        let mut vm = vm!();
+        assert_eq!(
+            hint_processor.execute_hint(&mut vm, exec_scopes_ref!(), &hint_data, &HashMap::new()),
+            Err(HintError::HintNotRegisteredForProgram(
+                hint_code::ADD_SEGMENT.to_string()
+            ))
+        );
+    }
 }
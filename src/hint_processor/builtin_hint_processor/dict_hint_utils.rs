@@ -1,11 +1,14 @@
 use crate::{
-    types::{exec_scope::ExecutionScopes, relocatable::MaybeRelocatable},
+    types::{
+        exec_scope::{CloneableAny, ExecutionScopes},
+        relocatable::MaybeRelocatable,
+    },
     vm::{errors::hint_errors::HintError, vm_core::VirtualMachine},
 };
-use std::{any::Any, cell::RefCell, collections::HashMap, rc::Rc};
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
 
 use crate::{
-    any_box,
+    cloneable_box,
     hint_processor::{
         builtin_hint_processor::hint_utils::{
             get_ptr_from_var_name, insert_value_from_var_name, insert_value_into_ap,
@@ -211,7 +214,7 @@ pub fn dict_squash_copy_dict(
     let dict_accesses_end = get_ptr_from_var_name("dict_accesses_end", vm, ids_data, ap_tracking)?;
     let dict_manager_ref = exec_scopes.get_dict_manager()?;
     let dict_manager = dict_manager_ref.borrow();
-    let dict_copy: Box<dyn Any> = Box::new(
+    let dict_copy: Box<dyn CloneableAny> = Box::new(
         dict_manager
             .get_tracker(&dict_accesses_end)?
             .get_dictionary_copy(),
@@ -219,7 +222,7 @@ pub fn dict_squash_copy_dict(
     exec_scopes.enter_scope(HashMap::from([
         (
             String::from("dict_manager"),
-            any_box!(exec_scopes.get_dict_manager()?),
+            cloneable_box!(exec_scopes.get_dict_manager()?),
         ),
         (String::from("initial_dict"), dict_copy),
     ]));
@@ -267,7 +270,7 @@ mod tests {
         utils::test_utils::*,
         vm::{errors::memory_errors::MemoryError, vm_core::VirtualMachine},
     };
-    use std::collections::HashMap;
+    use std::{any::Any, collections::HashMap};
 
     #[test]
     fn run_dict_new_with_initial_dict_empty() {
@@ -327,7 +330,7 @@ mod tests {
                 MemoryError::InconsistentMemory(
                     MaybeRelocatable::from((1, 0)),
                     MaybeRelocatable::from(1),
-                    MaybeRelocatable::from((0, 0))
+                    MaybeRelocatable::from((2, 0))
                 )
             )))
         );
@@ -409,29 +412,75 @@ mod tests {
         run_hint!(vm, ids_data, hint_code, &mut exec_scopes).expect("Error while executing hint");
         //third new segment is added for the dictionary
         assert_eq!(vm.memory.data.len(), 3);
-        //new segment base (0,0) is inserted into ap (0,0)
-        check_memory![vm.memory, ((1, 1), (0, 0))];
-        //Check the dict manager has a tracker for segment 0,
-        //and that tracker contains the ptr (0,0) and an empty dict
+        assert_eq!(vm.segments.num_segments, 3);
+        //new segment base (2,0) is inserted into ap (1,1)
+        check_memory![vm.memory, ((1, 1), (2, 0))];
+        //Check the dict manager has a tracker for segment 2,
+        //and that tracker contains the ptr (2,0) and an empty dict
         assert_eq!(
             exec_scopes
                 .get_dict_manager()
                 .unwrap()
                 .borrow()
                 .trackers
-                .get(&0),
+                .get(&2),
             Some(&DictTracker::new_default_dict(
-                &relocatable!(0, 0),
+                &relocatable!(2, 0),
                 &MaybeRelocatable::from(17),
                 None
             ))
         );
     }
 
+    #[test]
+    fn run_default_dict_new_relocatable_default_and_write() {
+        let mut vm = vm!();
+        run_context!(vm, 0, 1, 1);
+        //insert ids.default_value into memory
+        vm.memory = memory![((1, 0), (1, 7))];
+        let ids_data = ids_data!["default_value"];
+        let mut exec_scopes = ExecutionScopes::new();
+        assert_eq!(
+            run_hint!(vm, ids_data, hint_code::DEFAULT_DICT_NEW, &mut exec_scopes),
+            Ok(())
+        );
+        //new segment base (2,0) is inserted into ap (1,1)
+        check_memory![vm.memory, ((1, 1), (2, 0))];
+        //Write into a key which isn't in the dictionary yet
+        vm.run_context.fp = 5;
+        vm.insert_value(&relocatable!(1, 2), 5).unwrap();
+        vm.insert_value(&relocatable!(1, 3), 17).unwrap();
+        vm.insert_value(&relocatable!(1, 4), relocatable!(2, 0))
+            .unwrap();
+        let ids_data = ids_data!["key", "new_value", "dict_ptr"];
+        assert_eq!(
+            run_hint!(vm, ids_data, hint_code::DICT_WRITE, &mut exec_scopes),
+            Ok(())
+        );
+        //dict_ptr.prev_value holds the default pointer
+        check_memory![vm.memory, ((2, 1), (1, 7))];
+        assert_eq!(
+            exec_scopes
+                .get_dict_manager()
+                .unwrap()
+                .borrow()
+                .trackers
+                .get(&2),
+            Some(&DictTracker {
+                data: Dictionary::DefaultDictionary {
+                    dict: HashMap::from([(MaybeRelocatable::from(5), MaybeRelocatable::from(17))]),
+                    default_value: MaybeRelocatable::from((1, 7)),
+                },
+                current_ptr: relocatable!(2, 3),
+            })
+        );
+    }
+
     #[test]
     fn run_default_dict_new_no_default_value() {
         let hint_code = "if '__dict_manager' not in globals():\n    from starkware.cairo.common.dict import DictManager\n    __dict_manager = DictManager()\n\nmemory[ap] = __dict_manager.new_default_dict(segments, ids.default_value)";
         let mut vm = vm!();
+        add_segments!(vm, 2);
         //Initialize fp
         vm.run_context.fp = 1;
         let ids_data = ids_data!["default_value"];
@@ -622,6 +671,67 @@ mod tests {
         );
     }
 
+    #[test]
+    fn run_dict_update_wrong_prev_value_through_step_is_wrapped() {
+        let hint_code = "# Verify dict pointer and prev value.\ndict_tracker = __dict_manager.get_tracker(ids.dict_ptr)\ncurrent_value = dict_tracker.data[ids.key]\nassert current_value == ids.prev_value, \\\n    f'Wrong previous value in dict. Got {ids.prev_value}, expected {current_value}.'\n\n# Update value.\ndict_tracker.data[ids.key] = ids.new_value\ndict_tracker.current_ptr += ids.DictAccess.SIZE";
+        let mut vm = vm!();
+        //Initialize fp
+        vm.run_context.fp = 4;
+        let mut exec_scopes = ExecutionScopes::new();
+        dict_manager!(exec_scopes, 2, (5, 10));
+        //Insert ids into memory
+        vm.memory = memory![((1, 0), 5), ((1, 1), 11), ((1, 2), 20), ((1, 3), (2, 0))];
+        add_segments!(vm, 1);
+        let ids_data = ids_data!["key", "prev_value", "new_value", "dict_ptr"];
+        let hint_data_dictionary = HashMap::from([(
+            0_usize,
+            vec![any_box!(HintProcessorData::new_default(
+                hint_code.to_string(),
+                ids_data
+            ))],
+        )]);
+        //Run the hint through the step loop
+        let error = vm
+            .step(
+                &mut BuiltinHintProcessor::new_empty(),
+                &mut exec_scopes,
+                &hint_data_dictionary,
+                &HashMap::new(),
+            )
+            .unwrap_err();
+        let hint_exception = match error {
+            VirtualMachineError::Hint(hint_exception) => hint_exception,
+            _ => panic!("Expected a hint error, got {error:?}"),
+        };
+        assert_eq!(hint_exception.pc, Relocatable::from((0, 0)));
+        assert_eq!(hint_exception.hint_index, 0);
+        assert_eq!(
+            hint_exception.hint_code_snippet,
+            "# Verify dict pointer and prev value."
+        );
+        assert_eq!(
+            hint_exception.ids_values_at_failure,
+            vec![
+                ("dict_ptr".to_string(), MaybeRelocatable::from((2, 0))),
+                ("key".to_string(), MaybeRelocatable::from(5)),
+                ("new_value".to_string(), MaybeRelocatable::from(20)),
+                ("prev_value".to_string(), MaybeRelocatable::from(11)),
+            ]
+        );
+        assert_eq!(
+            hint_exception.inner,
+            HintError::WrongPrevValue(
+                MaybeRelocatable::from(11),
+                MaybeRelocatable::from(10),
+                MaybeRelocatable::from(5)
+            )
+        );
+        assert_eq!(
+            hint_exception.to_string(),
+            "Got an exception while executing a hint at pc=0:0 (`# Verify dict pointer and prev value.`): Dict Error: Got the wrong value for dict_update, expected value: 11, got: 10 for key: 5"
+        );
+    }
+
     #[test]
     fn run_dict_update_simple_invalid_wrong_key() {
         let hint_code = "# Verify dict pointer and prev value.\ndict_tracker = __dict_manager.get_tracker(ids.dict_ptr)\ncurrent_value = dict_tracker.data[ids.key]\nassert current_value == ids.prev_value, \\\n    f'Wrong previous value in dict. Got {ids.prev_value}, expected {current_value}.'\n\n# Update value.\ndict_tracker.data[ids.key] = ids.new_value\ndict_tracker.current_ptr += ids.DictAccess.SIZE";
@@ -1,7 +1,7 @@
 use std::collections::HashMap;
 
 use crate::{
-    types::relocatable::{MaybeRelocatable, Relocatable},
+    types::relocatable::{relocate_value, MaybeRelocatable, Relocatable},
     vm::{
         errors::{
             hint_errors::HintError, memory_errors::MemoryError, vm_errors::VirtualMachineError,
@@ -15,6 +15,8 @@ use crate::{
 ///Uses the segment index to associate the corresponding python dict with the Cairo dict.
 pub struct DictManager {
     pub trackers: HashMap<isize, DictTracker>,
+    //Trackers as they were before their first change since start_overlay, None for new ones
+    overlay: Option<HashMap<isize, Option<DictTracker>>>,
 }
 
 #[derive(PartialEq, Eq, Debug, Clone)]
@@ -59,12 +61,87 @@ impl Dictionary {
         };
         dict.insert(key.clone(), value.clone());
     }
+
+    //Copy of the dictionary with its relocatable keys and values relocated, fails with
+    //RelocatedDictKeyCollision if a relocated key is equal to another key
+    fn relocated(
+        &self,
+        segment_index: isize,
+        relocation_table: &[usize],
+    ) -> Result<Dictionary, MemoryError> {
+        let relocate_dict = |dict: &HashMap<MaybeRelocatable, MaybeRelocatable>| {
+            let mut relocated = HashMap::with_capacity(dict.len());
+            for (key, value) in dict.iter() {
+                let key: MaybeRelocatable = relocate_value(key.clone(), relocation_table)?.into();
+                let value = relocate_value(value.clone(), relocation_table)?.into();
+                if relocated.insert(key.clone(), value).is_some() {
+                    return Err(MemoryError::RelocatedDictKeyCollision(segment_index, key));
+                }
+            }
+            Ok(relocated)
+        };
+        Ok(match self {
+            Self::SimpleDictionary(dict) => Self::SimpleDictionary(relocate_dict(dict)?),
+            Self::DefaultDictionary {
+                dict,
+                default_value,
+            } => Self::DefaultDictionary {
+                dict: relocate_dict(dict)?,
+                default_value: relocate_value(default_value.clone(), relocation_table)?.into(),
+            },
+        })
+    }
 }
 
 impl DictManager {
     pub fn new() -> Self {
         DictManager {
             trackers: HashMap::<isize, DictTracker>::new(),
+            overlay: None,
+        }
+    }
+
+    ///Removes every tracked dictionary
+    pub fn reset(&mut self) {
+        self.trackers.clear();
+        self.overlay = None;
+    }
+
+    ///Starts recording changes to the dictionaries, to be used along with
+    ///VirtualMachine::start_overlay. Each tracker is copied the first time it's changed, through
+    ///get_tracker_mut, so dictionaries that aren't used aren't copied.
+    pub fn start_overlay(&mut self) -> Result<(), MemoryError> {
+        if self.overlay.is_some() {
+            return Err(MemoryError::OverlayAlreadyStarted);
+        }
+        self.overlay = Some(HashMap::new());
+        Ok(())
+    }
+
+    ///Keeps the changes made since start_overlay
+    pub fn commit_overlay(&mut self) -> Result<(), MemoryError> {
+        self.overlay.take().ok_or(MemoryError::NoOverlay)?;
+        Ok(())
+    }
+
+    ///Restores the dictionaries changed since start_overlay and removes the ones created since
+    pub fn discard_overlay(&mut self) -> Result<(), MemoryError> {
+        let overlay = self.overlay.take().ok_or(MemoryError::NoOverlay)?;
+        for (segment_index, tracker) in overlay {
+            match tracker {
+                Some(tracker) => self.trackers.insert(segment_index, tracker),
+                None => self.trackers.remove(&segment_index),
+            };
+        }
+        Ok(())
+    }
+
+    //Records the state of the tracker at segment_index before it's changed under an overlay
+    fn save_tracker(&mut self, segment_index: isize) {
+        if let Some(overlay) = &mut self.overlay {
+            overlay
+                .entry(segment_index)
+                .or_insert_with(|| self.trackers.get(&segment_index).cloned());
         }
     }
     //Creates a new Cairo dictionary. The values of initial_dict can be integers, tuples or
@@ -88,6 +165,7 @@ impl DictManager {
             ))?;
         };
 
+        self.save_tracker(base.segment_index);
         self.trackers.insert(
             base.segment_index,
             DictTracker::new_with_initial(&base, initial_dict),
@@ -108,6 +186,7 @@ impl DictManager {
                 base.segment_index,
             ));
         }
+        self.save_tracker(base.segment_index);
         self.trackers.insert(
             base.segment_index,
             DictTracker::new_default_dict(&base, default_value, initial_dict),
@@ -120,6 +199,7 @@ impl DictManager {
         &mut self,
         dict_ptr: &Relocatable,
     ) -> Result<&mut DictTracker, HintError> {
+        self.save_tracker(dict_ptr.segment_index);
         let tracker = self
             .trackers
             .get_mut(&dict_ptr.segment_index)
@@ -141,6 +221,24 @@ impl DictManager {
         }
         Ok(tracker)
     }
+
+    ///Returns a copy of every dictionary, by segment index, with its relocatable keys and values
+    ///replaced by their relocated address, so that they can be compared against the relocated
+    ///memory once the run is over. The trackers are left as they are.
+    pub fn relocated_dicts(
+        &self,
+        relocation_table: &[usize],
+    ) -> Result<HashMap<isize, Dictionary>, MemoryError> {
+        self.trackers
+            .iter()
+            .map(|(segment_index, tracker)| {
+                Ok((
+                    *segment_index,
+                    tracker.data.relocated(*segment_index, relocation_table)?,
+                ))
+            })
+            .collect()
+    }
 }
 
 impl Default for DictManager {
@@ -210,7 +308,12 @@ impl DictTracker {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{relocatable, utils::test_utils::*, vm::vm_core::VirtualMachine};
+    use crate::{
+        relocatable,
+        utils::test_utils::*,
+        vm::{vm_core::VirtualMachine, vm_memory::memory::Memory},
+    };
+    use felt::{felt_str, Felt, NewFelt};
 
     #[test]
     fn create_dict_manager() {
@@ -218,6 +321,23 @@ mod tests {
         assert_eq!(dict_manager.trackers, HashMap::new());
     }
 
+    #[test]
+    fn dict_tracker_negative_and_prime_minus_one_keys() {
+        let mut dict_tracker = DictTracker::new_empty(&relocatable!(1, 0));
+        dict_tracker.insert_value(
+            &MaybeRelocatable::from(Felt::new(-1)),
+            &mayberelocatable!(7),
+        );
+        let prime_minus_one = MaybeRelocatable::from(felt_str!(
+            "3618502788666131213697322783095070105623107215331596699973092056135872020480"
+        ));
+        assert_eq!(
+            dict_tracker.get_value(&prime_minus_one),
+            Ok(&mayberelocatable!(7))
+        );
+        assert_eq!(dict_tracker.get_dictionary_copy().len(), 1);
+    }
+
     #[test]
     fn create_dict_tracker_empty() {
         let dict_tracker = DictTracker::new_empty(&relocatable!(1, 0));
@@ -317,6 +437,49 @@ mod tests {
         assert_eq!(vm.segments.num_segments, 1);
     }
 
+    #[test]
+    fn dict_manager_discard_overlay() {
+        let mut dict_manager = DictManager::new();
+        let mut vm = vm!();
+        vm.memory = memory![((1, 0), 1)];
+        dict_manager.new_dict(&mut vm, HashMap::new()).unwrap();
+        let trackers = dict_manager.trackers.clone();
+
+        dict_manager.start_overlay().unwrap();
+        dict_manager
+            .get_tracker_mut(&relocatable!(2, 0))
+            .unwrap()
+            .insert_value(&MaybeRelocatable::from(1), &MaybeRelocatable::from(2));
+        dict_manager
+            .new_default_dict(&mut vm, &MaybeRelocatable::from(0), None)
+            .unwrap();
+        assert_eq!(dict_manager.trackers.len(), 2);
+
+        assert_eq!(dict_manager.discard_overlay(), Ok(()));
+        assert_eq!(dict_manager.trackers, trackers);
+    }
+
+    #[test]
+    fn dict_manager_new_dicts_in_consecutive_segments() {
+        let mut dict_manager = DictManager::new();
+        let mut vm = vm!();
+        vm.memory = memory![((1, 0), 1)];
+        let first = dict_manager.new_dict(&mut vm, HashMap::new());
+        let second = dict_manager.new_default_dict(&mut vm, &MaybeRelocatable::from(3), None);
+        let third = dict_manager.new_dict(&mut vm, HashMap::new());
+        assert_eq!(first, Ok(MaybeRelocatable::from((2, 0))));
+        assert_eq!(second, Ok(MaybeRelocatable::from((3, 0))));
+        assert_eq!(third, Ok(MaybeRelocatable::from((4, 0))));
+        assert_eq!(dict_manager.trackers.len(), 3);
+        for index in 2..5 {
+            assert_eq!(
+                dict_manager.trackers.get(&index).map(|t| t.current_ptr),
+                Some(relocatable!(index, 0))
+            );
+        }
+        assert_eq!(vm.segments.num_segments, 5);
+    }
+
     #[test]
     fn dict_manager_new_dict_empty_same_segment() {
         let mut dict_manager = DictManager::new();
@@ -371,4 +534,83 @@ mod tests {
             Some(&MaybeRelocatable::from(7))
         );
     }
+
+    #[test]
+    fn dict_manager_relocate() {
+        let mut dict_manager = DictManager::new();
+        dict_manager.trackers.insert(
+            2,
+            DictTracker::new_with_initial(
+                &relocatable!(2, 0),
+                HashMap::from([
+                    (MaybeRelocatable::from(1), MaybeRelocatable::from((1, 3))),
+                    (MaybeRelocatable::from((0, 1)), MaybeRelocatable::from(4)),
+                ]),
+            ),
+        );
+        dict_manager.trackers.insert(
+            3,
+            DictTracker::new_default_dict(
+                &relocatable!(3, 0),
+                &MaybeRelocatable::from((2, 2)),
+                None,
+            ),
+        );
+        let live_dict_manager = dict_manager.clone();
+        let relocated = dict_manager.relocated_dicts(&[1, 5, 12, 20]).unwrap();
+        assert_eq!(
+            relocated[&2],
+            Dictionary::SimpleDictionary(HashMap::from([
+                (MaybeRelocatable::from(1), MaybeRelocatable::from(8)),
+                (MaybeRelocatable::from(2), MaybeRelocatable::from(4)),
+            ]))
+        );
+        assert_eq!(
+            relocated[&3],
+            Dictionary::DefaultDictionary {
+                dict: HashMap::new(),
+                default_value: MaybeRelocatable::from(14),
+            }
+        );
+        assert_eq!(dict_manager, live_dict_manager);
+    }
+
+    #[test]
+    fn dict_manager_relocate_key_collision() {
+        let mut dict_manager = DictManager::new();
+        //(0, 7) is relocated to 8, the address of the int key
+        dict_manager.trackers.insert(
+            2,
+            DictTracker::new_with_initial(
+                &relocatable!(2, 0),
+                HashMap::from([
+                    (MaybeRelocatable::from(8), MaybeRelocatable::from(1)),
+                    (MaybeRelocatable::from((0, 7)), MaybeRelocatable::from(2)),
+                ]),
+            ),
+        );
+        assert_eq!(
+            dict_manager.relocated_dicts(&[1, 10, 12]),
+            Err(MemoryError::RelocatedDictKeyCollision(
+                2,
+                MaybeRelocatable::from(8)
+            ))
+        );
+    }
+
+    #[test]
+    fn dict_manager_relocate_temporary_segment() {
+        let mut dict_manager = DictManager::new();
+        dict_manager.trackers.insert(
+            2,
+            DictTracker::new_with_initial(
+                &relocatable!(2, 0),
+                HashMap::from([(MaybeRelocatable::from(1), MaybeRelocatable::from((-1, 3)))]),
+            ),
+        );
+        assert_eq!(
+            dict_manager.relocated_dicts(&[1, 5, 12]),
+            Err(MemoryError::TemporarySegmentInRelocation(-1))
+        );
+    }
 }
@@ -0,0 +1,204 @@
+use crate::{
+    hint_processor::{
+        builtin_hint_processor::{
+            hint_utils::get_relocatable_from_var_name, secp::secp_utils::split_limbs,
+        },
+        hint_processor_definition::HintReference,
+    },
+    serde::deserialize_program::ApTracking,
+    vm::{errors::hint_errors::HintError, vm_core::VirtualMachine},
+};
+use felt::{Felt, FeltOps, NewFelt};
+use num_bigint::BigUint;
+use num_integer::Integer;
+use std::collections::HashMap;
+
+// Constants in package "starkware.cairo.common.cairo_ed25519.constants", holding the low and
+// high 128 bits of the field's prime
+pub const PRIME_LOW: &str = "starkware.cairo.common.cairo_ed25519.constants.PRIME_LOW";
+pub const PRIME_HIGH: &str = "starkware.cairo.common.cairo_ed25519.constants.PRIME_HIGH";
+
+///Returns the field's prime from the PRIME_LOW and PRIME_HIGH constants
+fn get_prime(constants: &HashMap<String, Felt>) -> Result<BigUint, HintError> {
+    let low = constants
+        .get(PRIME_LOW)
+        .ok_or(HintError::MissingConstant(PRIME_LOW))?;
+    let high = constants
+        .get(PRIME_HIGH)
+        .ok_or(HintError::MissingConstant(PRIME_HIGH))?;
+    Ok((high.to_biguint() << 128_usize) + low.to_biguint())
+}
+
+/*
+Implements hint:
+%{
+    PRIME = 2**255 - 19
+    II = pow(2, (PRIME - 1) // 4, PRIME)
+
+    xx = ids.xx.low + (ids.xx.high<<128)
+    x = pow(xx, (PRIME + 3) // 8, PRIME)
+    if (x * x - xx) % PRIME != 0:
+        x = (x * II) % PRIME
+    if x % 2 != 0:
+        x = PRIME - x
+    ids.x.low = x & ((1<<128)-1)
+    ids.x.high = x >> 128
+%}
+Where PRIME is taken from the PRIME_LOW and PRIME_HIGH constants
+*/
+pub fn split_xx(
+    vm: &mut VirtualMachine,
+    ids_data: &HashMap<String, HintReference>,
+    ap_tracking: &ApTracking,
+    constants: &HashMap<String, Felt>,
+) -> Result<(), HintError> {
+    let prime = get_prime(constants)?;
+    let xx_addr = get_relocatable_from_var_name("xx", vm, ids_data, ap_tracking)?;
+    let x_addr = get_relocatable_from_var_name("x", vm, ids_data, ap_tracking)?;
+
+    let xx_low = vm.get_integer(&xx_addr)?;
+    let xx_high = vm.get_integer(&(xx_addr + 1_usize))?;
+    let xx = (xx_high.to_biguint() << 128_usize) + xx_low.to_biguint();
+
+    let ii = BigUint::from(2_u32).modpow(&((&prime - 1_u32) >> 2_usize), &prime);
+    let mut x = xx.modpow(&((&prime + 3_u32) >> 3_usize), &prime);
+    if (&x * &x) % &prime != &xx % &prime {
+        x = (x * ii) % &prime;
+    }
+    if x.is_odd() {
+        x = &prime - x;
+    }
+
+    let limbs = split_limbs(&x, 2, 128)?;
+    vm.insert_value(&x_addr, Felt::new(limbs[0].clone()))?;
+    vm.insert_value(&(x_addr + 1_usize), Felt::new(limbs[1].clone()))
+        .map_err(HintError::Internal)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        any_box,
+        hint_processor::{
+            builtin_hint_processor::{
+                builtin_hint_processor_definition::{BuiltinHintProcessor, HintProcessorData},
+                hint_code,
+            },
+            hint_processor_definition::HintProcessor,
+        },
+        types::{exec_scope::ExecutionScopes, relocatable::MaybeRelocatable},
+        utils::test_utils::*,
+        vm::{errors::memory_errors::MemoryError, vm_memory::memory::Memory},
+    };
+    use felt::felt_str;
+    use std::any::Any;
+
+    #[test]
+    fn run_split_xx_ed25519_prime() {
+        let mut vm = vm!();
+        vm.run_context.fp = 4;
+        //xx = 9
+        vm.memory = memory![((1, 0), 9), ((1, 1), 0)];
+        add_segments!(vm, 1);
+        let ids_data = non_continuous_ids_data![("xx", -4), ("x", -2)];
+        //PRIME = 2**255 - 19
+        let constants = HashMap::from([
+            (
+                PRIME_LOW.to_string(),
+                felt_str!("340282366920938463463374607431768211437"),
+            ),
+            (
+                PRIME_HIGH.to_string(),
+                felt_str!("170141183460469231731687303715884105727"),
+            ),
+        ]);
+        assert_eq!(
+            run_hint!(
+                vm,
+                ids_data,
+                hint_code::SPLIT_XX,
+                exec_scopes_ref!(),
+                &constants
+            ),
+            Ok(())
+        );
+        //x = 2**255 - 19 - 3, the even root of 9
+        check_memory![
+            vm.memory,
+            ((1, 2), ("340282366920938463463374607431768211434", 10)),
+            ((1, 3), ("170141183460469231731687303715884105727", 10))
+        ];
+    }
+
+    #[test]
+    fn run_split_xx_prime_from_constants() {
+        let mut vm = vm!();
+        vm.run_context.fp = 4;
+        //xx = (2**200 + 12345)**2 % (2**255 - 475)
+        vm.memory = memory![
+            ((1, 0), 152399025),
+            ((1, 1), ("116595228462051540388413440", 10))
+        ];
+        add_segments!(vm, 1);
+        let ids_data = non_continuous_ids_data![("xx", -4), ("x", -2)];
+        let constants = HashMap::from([
+            (
+                PRIME_LOW.to_string(),
+                felt_str!("340282366920938463463374607431768210981"),
+            ),
+            (
+                PRIME_HIGH.to_string(),
+                felt_str!("170141183460469231731687303715884105727"),
+            ),
+        ]);
+        assert_eq!(
+            run_hint!(
+                vm,
+                ids_data,
+                hint_code::SPLIT_XX,
+                exec_scopes_ref!(),
+                &constants
+            ),
+            Ok(())
+        );
+        //x = 2**255 - 475 - (2**200 + 12345)
+        check_memory![
+            vm.memory,
+            ((1, 2), ("340282366920938463463374607431768198636", 10)),
+            ((1, 3), ("170141183460469227009320820846238892031", 10))
+        ];
+    }
+
+    #[test]
+    fn run_split_xx_missing_prime_high() {
+        let mut vm = vm!();
+        vm.run_context.fp = 4;
+        vm.memory = memory![((1, 0), 9), ((1, 1), 0)];
+        add_segments!(vm, 1);
+        let ids_data = non_continuous_ids_data![("xx", -4), ("x", -2)];
+        let constants = HashMap::from([(PRIME_LOW.to_string(), Felt::new(5))]);
+        assert_eq!(
+            run_hint!(
+                vm,
+                ids_data,
+                hint_code::SPLIT_XX,
+                exec_scopes_ref!(),
+                &constants
+            ),
+            Err(HintError::MissingConstant(PRIME_HIGH))
+        );
+    }
+    #[test]
+    fn run_split_xx_missing_prime() {
+        let mut vm = vm!();
+        vm.run_context.fp = 4;
+        vm.memory = memory![((1, 0), 9), ((1, 1), 0)];
+        add_segments!(vm, 1);
+        let ids_data = non_continuous_ids_data![("xx", -4), ("x", -2)];
+        assert_eq!(
+            run_hint!(vm, ids_data, hint_code::SPLIT_XX),
+            Err(HintError::MissingConstant(PRIME_LOW))
+        );
+    }
+}
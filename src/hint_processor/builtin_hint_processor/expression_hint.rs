@@ -0,0 +1,713 @@
+use crate::{
+    hint_processor::{
+        builtin_hint_processor::{
+            builtin_hint_processor_definition::HintProcessorData,
+            hint_utils::{
+                get_maybe_relocatable_from_var_name, get_ptr_from_var_name,
+                get_relocatable_from_var_name, insert_value_from_var_name,
+            },
+        },
+        hint_processor_definition::HintReference,
+    },
+    serde::deserialize_program::ApTracking,
+    types::relocatable::{MaybeRelocatable, Relocatable},
+    vm::{
+        errors::{hint_errors::HintError, vm_errors::VirtualMachineError},
+        vm_core::VirtualMachine,
+    },
+};
+use felt::{Felt, FeltOps};
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_traits::{Num, ToPrimitive, Zero};
+use std::collections::HashMap;
+
+///Shifts by more bits than this are rejected, it's well above the size of any felt
+const MAX_SHIFT: usize = 1024;
+
+const SYMBOLS: [&str; 18] = [
+    "//", "<<", ">>", "<=", ">=", "==", "!=", "+", "-", "*", "%", "&", "<", ">", "(", ")", ".", "=",
+];
+
+///Member offsets of each struct, by the struct's full name
+pub type StructMembers = HashMap<String, HashMap<String, usize>>;
+
+///A hint made of a single assignment to an ids variable, such as
+///`ids.carry = 1 if ids.a + ids.b >= ids.SHIFT else 0`.
+///The expression can use integer literals, PRIME, ids variables and constants, struct members
+///as ids.x.y, parentheses, the operators + - * // % & << >> and unary -, one comparison
+///(< <= > >= == !=) per operand and the `a if cond else b` ternary.
+///It's evaluated with Python's integer semantics and the result is written modulo PRIME.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct ExpressionHint {
+    target: IdsPath,
+    value: Expr,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct IdsPath {
+    name: String,
+    member: Option<String>,
+}
+
+impl IdsPath {
+    fn display_name(&self) -> String {
+        match &self.member {
+            Some(member) => format!("{}.{}", self.name, member),
+            None => self.name.clone(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Expr {
+    Int(BigInt),
+    Prime,
+    Ids(IdsPath),
+    Neg(Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+    Ternary(Box<Expr>, Box<Expr>, Box<Expr>),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    FloorDiv,
+    Mod,
+    And,
+    Shl,
+    Shr,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    Ne,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Token {
+    Int(BigInt),
+    Name(String),
+    Symbol(&'static str),
+}
+
+fn parse_int(literal: &str) -> Option<BigInt> {
+    match literal
+        .strip_prefix("0x")
+        .or_else(|| literal.strip_prefix("0X"))
+    {
+        Some(hex) => BigInt::from_str_radix(hex, 16).ok(),
+        None if literal.bytes().all(|byte| byte.is_ascii_digit()) => {
+            BigInt::from_str_radix(literal, 10).ok()
+        }
+        None => None,
+    }
+}
+
+fn tokenize(code: &str) -> Option<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut rest = code.trim();
+    while let Some(c) = rest.chars().next() {
+        if c == ' ' || c == '\t' {
+            rest = &rest[1..];
+        } else if c.is_ascii_alphanumeric() || c == '_' {
+            let end = rest
+                .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+                .unwrap_or(rest.len());
+            let word = &rest[..end];
+            tokens.push(if c.is_ascii_digit() {
+                Token::Int(parse_int(word)?)
+            } else {
+                Token::Name(word.to_string())
+            });
+            rest = &rest[end..];
+        } else {
+            let symbol = SYMBOLS.iter().find(|symbol| rest.starts_with(*symbol))?;
+            tokens.push(Token::Symbol(symbol));
+            rest = &rest[symbol.len()..];
+        }
+    }
+    Some(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn eat_symbol(&mut self, symbol: &str) -> bool {
+        let found = matches!(self.tokens.get(self.pos), Some(Token::Symbol(s)) if *s == symbol);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn eat_name(&mut self, name: &str) -> bool {
+        let found = matches!(self.tokens.get(self.pos), Some(Token::Name(n)) if n == name);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn name(&mut self) -> Option<String> {
+        match self.tokens.get(self.pos) {
+            Some(Token::Name(name)) => {
+                self.pos += 1;
+                Some(name.clone())
+            }
+            _ => None,
+        }
+    }
+
+    //Tries each of the operators, in order, against the next token
+    fn binary_op(&mut self, ops: &[(&str, BinaryOp)]) -> Option<BinaryOp> {
+        ops.iter()
+            .find(|(symbol, _)| self.eat_symbol(symbol))
+            .map(|(_, op)| *op)
+    }
+
+    //Parses a left-associative chain of operands
+    fn chain(
+        &mut self,
+        ops: &[(&str, BinaryOp)],
+        operand: fn(&mut Self) -> Option<Expr>,
+    ) -> Option<Expr> {
+        let mut expr = operand(self)?;
+        while let Some(op) = self.binary_op(ops) {
+            expr = Expr::Binary(op, Box::new(expr), Box::new(operand(self)?));
+        }
+        Some(expr)
+    }
+
+    //ids.name or ids.name.member
+    fn ids_path(&mut self) -> Option<IdsPath> {
+        if !(self.eat_name("ids") && self.eat_symbol(".")) {
+            return None;
+        }
+        let name = self.name()?;
+        let member = match self.eat_symbol(".") {
+            true => Some(self.name()?),
+            false => None,
+        };
+        Some(IdsPath { name, member })
+    }
+
+    //a if cond else b, where b can be another ternary
+    fn expression(&mut self) -> Option<Expr> {
+        let value = self.comparison()?;
+        if !self.eat_name("if") {
+            return Some(value);
+        }
+        let condition = self.comparison()?;
+        if !self.eat_name("else") {
+            return None;
+        }
+        let otherwise = self.expression()?;
+        Some(Expr::Ternary(
+            Box::new(condition),
+            Box::new(value),
+            Box::new(otherwise),
+        ))
+    }
+
+    //Chained comparisons, like a < b < c, aren't supported
+    fn comparison(&mut self) -> Option<Expr> {
+        const OPS: [(&str, BinaryOp); 6] = [
+            ("<=", BinaryOp::Le),
+            (">=", BinaryOp::Ge),
+            ("==", BinaryOp::Eq),
+            ("!=", BinaryOp::Ne),
+            ("<", BinaryOp::Lt),
+            (">", BinaryOp::Gt),
+        ];
+        let lhs = self.bit_and()?;
+        let op = match self.binary_op(&OPS) {
+            Some(op) => op,
+            None => return Some(lhs),
+        };
+        let rhs = self.bit_and()?;
+        if self.binary_op(&OPS).is_some() {
+            return None;
+        }
+        Some(Expr::Binary(op, Box::new(lhs), Box::new(rhs)))
+    }
+
+    fn bit_and(&mut self) -> Option<Expr> {
+        self.chain(&[("&", BinaryOp::And)], Self::shift)
+    }
+
+    fn shift(&mut self) -> Option<Expr> {
+        self.chain(&[("<<", BinaryOp::Shl), (">>", BinaryOp::Shr)], Self::arith)
+    }
+
+    fn arith(&mut self) -> Option<Expr> {
+        self.chain(&[("+", BinaryOp::Add), ("-", BinaryOp::Sub)], Self::term)
+    }
+
+    fn term(&mut self) -> Option<Expr> {
+        self.chain(
+            &[
+                ("*", BinaryOp::Mul),
+                ("//", BinaryOp::FloorDiv),
+                ("%", BinaryOp::Mod),
+            ],
+            Self::factor,
+        )
+    }
+
+    fn factor(&mut self) -> Option<Expr> {
+        if self.eat_symbol("-") {
+            return Some(Expr::Neg(Box::new(self.factor()?)));
+        }
+        self.atom()
+    }
+
+    fn atom(&mut self) -> Option<Expr> {
+        if self.eat_symbol("(") {
+            let expr = self.expression()?;
+            return self.eat_symbol(")").then_some(expr);
+        }
+        match self.tokens.get(self.pos)? {
+            Token::Int(value) => {
+                let value = value.clone();
+                self.pos += 1;
+                Some(Expr::Int(value))
+            }
+            Token::Name(name) if name == "PRIME" => {
+                self.pos += 1;
+                Some(Expr::Prime)
+            }
+            _ => self.ids_path().map(Expr::Ids),
+        }
+    }
+}
+
+///Parses code as an assignment of a restricted expression to an ids variable, returns None if it
+///isn't one
+pub(crate) fn parse_expression_hint(code: &str) -> Option<ExpressionHint> {
+    let mut parser = Parser {
+        tokens: tokenize(code)?,
+        pos: 0,
+    };
+    let target = parser.ids_path()?;
+    if !parser.eat_symbol("=") {
+        return None;
+    }
+    let value = parser.expression()?;
+    (parser.pos == parser.tokens.len()).then_some(ExpressionHint { target, value })
+}
+
+struct Evaluator<'a> {
+    vm: &'a VirtualMachine,
+    ids_data: &'a HashMap<String, HintReference>,
+    ap_tracking: &'a ApTracking,
+    accessible_scopes: &'a [String],
+    constants: &'a HashMap<String, Felt>,
+    struct_members: &'a StructMembers,
+    prime: &'a BigInt,
+}
+
+impl Evaluator<'_> {
+    fn evaluate(&self, expr: &Expr) -> Result<BigInt, HintError> {
+        Ok(match expr {
+            Expr::Int(value) => value.clone(),
+            Expr::Prime => self.prime.clone(),
+            Expr::Ids(path) => self.read(path)?,
+            Expr::Neg(expr) => -self.evaluate(expr)?,
+            Expr::Ternary(condition, value, otherwise) => {
+                if self.evaluate(condition)?.is_zero() {
+                    self.evaluate(otherwise)?
+                } else {
+                    self.evaluate(value)?
+                }
+            }
+            Expr::Binary(op, lhs, rhs) => {
+                let (lhs, rhs) = (self.evaluate(lhs)?, self.evaluate(rhs)?);
+                match op {
+                    BinaryOp::Add => lhs + rhs,
+                    BinaryOp::Sub => lhs - rhs,
+                    BinaryOp::Mul => lhs * rhs,
+                    BinaryOp::FloorDiv | BinaryOp::Mod if rhs.is_zero() => {
+                        return Err(VirtualMachineError::DividedByZero.into())
+                    }
+                    BinaryOp::FloorDiv => lhs.div_floor(&rhs),
+                    BinaryOp::Mod => lhs.mod_floor(&rhs),
+                    BinaryOp::And => lhs & rhs,
+                    BinaryOp::Shl => lhs << shift_count(rhs)?,
+                    BinaryOp::Shr => lhs >> shift_count(rhs)?,
+                    BinaryOp::Lt => BigInt::from((lhs < rhs) as u8),
+                    BinaryOp::Le => BigInt::from((lhs <= rhs) as u8),
+                    BinaryOp::Gt => BigInt::from((lhs > rhs) as u8),
+                    BinaryOp::Ge => BigInt::from((lhs >= rhs) as u8),
+                    BinaryOp::Eq => BigInt::from((lhs == rhs) as u8),
+                    BinaryOp::Ne => BigInt::from((lhs != rhs) as u8),
+                }
+            }
+        })
+    }
+
+    //Values are read as Python does, in the range [0, PRIME)
+    fn read(&self, path: &IdsPath) -> Result<BigInt, HintError> {
+        let value = match (&path.member, self.ids_data.contains_key(&path.name)) {
+            (None, true) => get_maybe_relocatable_from_var_name(
+                &path.name,
+                self.vm,
+                self.ids_data,
+                self.ap_tracking,
+            )?,
+            (None, false) => return self.constant(&path.name),
+            (Some(member), _) => self
+                .vm
+                .get_maybe(&self.member_address(&path.name, member)?)
+                .map_err(VirtualMachineError::MemoryError)?
+                .ok_or(HintError::FailedToGetIds)?,
+        };
+        match value {
+            MaybeRelocatable::Int(value) => Ok(value.to_biguint().into()),
+            MaybeRelocatable::RelocatableValue(_) => Err(
+                HintError::ExpressionIdentifierNotInteger(path.display_name()),
+            ),
+        }
+    }
+
+    //Constants are looked up by their full name in the hint's accessible scopes, from the
+    //innermost one outwards, as cairo-lang does
+    fn constant(&self, name: &str) -> Result<BigInt, HintError> {
+        self.accessible_scopes
+            .iter()
+            .rev()
+            .find_map(|scope| self.constants.get(&format!("{scope}.{name}")))
+            .map(|value| value.to_biguint().into())
+            .ok_or_else(|| HintError::UnknownExpressionIdentifier(name.to_string()))
+    }
+
+    //The address of ids.name.member, following the pointer if ids.name is one
+    fn member_address(&self, name: &str, member: &str) -> Result<Relocatable, HintError> {
+        let cairo_type = self
+            .ids_data
+            .get(name)
+            .ok_or_else(|| HintError::UnknownExpressionIdentifier(name.to_string()))?
+            .cairo_type
+            .as_deref()
+            .unwrap_or("felt");
+        let (struct_name, base) = match cairo_type.strip_suffix('*') {
+            Some(struct_name) => (
+                struct_name,
+                get_ptr_from_var_name(name, self.vm, self.ids_data, self.ap_tracking)?,
+            ),
+            None => (
+                cairo_type,
+                get_relocatable_from_var_name(name, self.vm, self.ids_data, self.ap_tracking)?,
+            ),
+        };
+        let offset = self
+            .struct_members
+            .get(struct_name)
+            .and_then(|members| members.get(member))
+            .ok_or_else(|| {
+                HintError::UnknownStructMember(struct_name.to_string(), member.to_string())
+            })?;
+        Ok(base + *offset)
+    }
+}
+
+fn shift_count(count: BigInt) -> Result<usize, HintError> {
+    match count.to_usize() {
+        Some(count) if count <= MAX_SHIFT => Ok(count),
+        _ => Err(HintError::ExpressionShiftOutOfRange(count)),
+    }
+}
+
+///Evaluates the expression and writes it, modulo PRIME, to the target ids variable or member
+pub(crate) fn execute_expression_hint(
+    hint: &ExpressionHint,
+    vm: &mut VirtualMachine,
+    hint_data: &HintProcessorData,
+    constants: &HashMap<String, Felt>,
+    struct_members: &StructMembers,
+    prime: &BigInt,
+) -> Result<(), HintError> {
+    let ids_data = &hint_data.ids_data;
+    let ap_tracking = &hint_data.ap_tracking;
+    let evaluator = Evaluator {
+        vm,
+        ids_data,
+        ap_tracking,
+        accessible_scopes: &hint_data.accessible_scopes,
+        constants,
+        struct_members,
+        prime,
+    };
+    let value = Felt::from(evaluator.evaluate(&hint.value)?);
+    match &hint.target.member {
+        None => insert_value_from_var_name(&hint.target.name, value, vm, ids_data, ap_tracking),
+        Some(member) => {
+            let addr = evaluator.member_address(&hint.target.name, member)?;
+            vm.insert_value(&addr, value).map_err(HintError::Internal)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        any_box,
+        hint_processor::{
+            builtin_hint_processor::builtin_hint_processor_definition::{
+                BuiltinHintProcessor, HintProcessorData,
+            },
+            hint_processor_definition::HintProcessor,
+        },
+        relocatable,
+        types::exec_scope::ExecutionScopes,
+        utils::test_utils::*,
+        vm::{errors::memory_errors::MemoryError, vm_memory::memory::Memory},
+    };
+    use felt::{NewFelt, PRIME_STR};
+    use num_traits::One;
+    use std::any::Any;
+
+    fn prime() -> BigInt {
+        BigInt::from_str_radix(&PRIME_STR[2..], 16).unwrap()
+    }
+
+    //Runs the hint from the __main__ scope, with a processor which knows the members of
+    //__main__.Point
+    fn run_expression(
+        vm: &mut VirtualMachine,
+        ids_data: HashMap<String, HintReference>,
+        code: &str,
+        constants: &HashMap<String, Felt>,
+    ) -> Result<(), HintError> {
+        let mut hint_data = HintProcessorData::new_default(code.to_string(), ids_data);
+        hint_data.accessible_scopes = vec!["__main__".to_string()];
+        run_hint_data(vm, hint_data, constants)
+    }
+
+    fn run_hint_data(
+        vm: &mut VirtualMachine,
+        hint_data: HintProcessorData,
+        constants: &HashMap<String, Felt>,
+    ) -> Result<(), HintError> {
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        hint_processor.set_expression_hints_enabled(true);
+        hint_processor.add_struct_members(
+            "__main__.Point".to_string(),
+            HashMap::from([("x".to_string(), 0), ("y".to_string(), 1)]),
+        );
+        hint_processor.execute_hint(vm, exec_scopes_ref!(), &any_box!(hint_data), constants)
+    }
+
+    //Writes the expression to ids.a, at fp - 1, with ids.b at fp - 2
+    fn evaluate(code: &str, b: i64) -> Felt {
+        let mut vm = vm!();
+        vm.run_context.fp = 2;
+        vm.memory = memory![((1, 0), b)];
+        let ids_data = non_continuous_ids_data![("a", -1), ("b", -2)];
+        run_expression(&mut vm, ids_data, code, &HashMap::new()).unwrap();
+        vm.get_integer(&relocatable!(1, 1)).unwrap().into_owned()
+    }
+
+    #[test]
+    fn operator_precedence_and_associativity() {
+        for (code, expected) in [
+            ("ids.a = 2 + 3 * 4 - 10 // 3", 11),
+            ("ids.a = 100 - 10 - 1", 89),
+            ("ids.a = 64 // 4 // 2", 8),
+            ("ids.a = (2 + 3) * 4", 20),
+            ("ids.a = 1 << 2 + 1", 8),
+            ("ids.a = 6 & 3 + 1", 4),
+            ("ids.a = 0x10 >> 2", 4),
+            ("ids.a = - -3 * 2", 6),
+        ] {
+            assert_eq!(evaluate(code, 0), Felt::new(expected), "{code}");
+        }
+    }
+
+    #[test]
+    fn floor_division_and_modulo_of_negatives() {
+        assert_eq!(evaluate("ids.a = -7 // 2 + 10", 0), Felt::new(6));
+        assert_eq!(evaluate("ids.a = -7 % 3", 0), Felt::new(2));
+        assert_eq!(evaluate("ids.a = 7 % -3 + 5", 0), Felt::new(3));
+    }
+
+    #[test]
+    fn nested_ternary_and_comparisons() {
+        let code = "ids.a = 1 if ids.b < 5 else 2 if ids.b < 10 else 3";
+        assert_eq!(evaluate(code, 4), Felt::new(1));
+        assert_eq!(evaluate(code, 7), Felt::new(2));
+        assert_eq!(evaluate(code, 12), Felt::new(3));
+        assert_eq!(evaluate("ids.a = ids.b == 3", 3), Felt::one());
+        assert_eq!(evaluate("ids.a = (ids.b != 3) + 5", 3), Felt::new(5));
+    }
+
+    #[test]
+    fn result_is_written_modulo_prime() {
+        assert_eq!(evaluate("ids.a = -1", 0), Felt::from(prime() - 1));
+        assert_eq!(evaluate("ids.a = PRIME + 5", 0), Felt::new(5));
+    }
+
+    #[test]
+    fn ids_are_read_as_unsigned() {
+        //ids.b = -1 is read as PRIME - 1, as in python
+        assert_eq!(
+            evaluate("ids.a = ids.b // 2", -1),
+            Felt::from((prime() - 1) / 2)
+        );
+        assert_eq!(evaluate("ids.a = ids.b * ids.b % PRIME", -1), Felt::one());
+    }
+
+    #[test]
+    fn carry_with_constant() {
+        //ids.a = ids.b = 2**100, SHIFT = 2**101
+        let mut vm = vm!();
+        vm.run_context.fp = 3;
+        vm.memory = memory![
+            ((1, 0), 1267650600228229401496703205376_u128),
+            ((1, 1), 1267650600228229401496703205376_u128)
+        ];
+        let ids_data = non_continuous_ids_data![("a", -3), ("b", -2), ("carry", -1)];
+        let constants = HashMap::from([(
+            "starkware.cairo.common.uint256.SHIFT".to_string(),
+            Felt::one() << 101_u32,
+        )]);
+        let code = "ids.carry = 1 if ids.a + ids.b >= ids.SHIFT else 0";
+        let mut hint_data = HintProcessorData::new_default(code.to_string(), ids_data);
+        hint_data.accessible_scopes = vec![
+            "starkware.cairo.common.uint256".to_string(),
+            "starkware.cairo.common.uint256.uint256_add".to_string(),
+        ];
+        assert_eq!(run_hint_data(&mut vm, hint_data, &constants), Ok(()));
+        assert_eq!(
+            vm.get_integer(&relocatable!(1, 2)).unwrap().as_ref(),
+            &Felt::one()
+        );
+    }
+
+    #[test]
+    fn constants_are_resolved_by_full_path() {
+        let mut vm = vm!();
+        vm.run_context.fp = 1;
+        add_segments!(vm, 2);
+        let ids_data = non_continuous_ids_data![("a", -1)];
+        let constants = HashMap::from([
+            ("__main__.SHIFT".to_string(), Felt::new(1)),
+            ("__main__.main.SHIFT".to_string(), Felt::new(2)),
+            ("other.BASE".to_string(), Felt::new(3)),
+        ]);
+        //The innermost scope shadows the outer ones
+        let mut hint_data =
+            HintProcessorData::new_default("ids.a = ids.SHIFT".to_string(), ids_data.clone());
+        hint_data.accessible_scopes = vec!["__main__".to_string(), "__main__.main".to_string()];
+        assert_eq!(run_hint_data(&mut vm, hint_data, &constants), Ok(()));
+        assert_eq!(
+            vm.get_integer(&relocatable!(1, 0)).unwrap().as_ref(),
+            &Felt::new(2)
+        );
+        //other.BASE isn't accessible from __main__, even if its name ends in BASE
+        assert_eq!(
+            run_expression(&mut vm, ids_data, "ids.a = ids.BASE", &constants),
+            Err(HintError::UnknownExpressionIdentifier("BASE".to_string()))
+        );
+    }
+
+    #[test]
+    fn struct_and_pointer_members() {
+        let mut vm = vm!();
+        vm.run_context.fp = 4;
+        //ids.p is a Point at fp - 4, ids.q a Point* pointing to (2, 0)
+        vm.memory = memory![((1, 0), 3), ((1, 1), 4), ((1, 2), (2, 0)), ((2, 0), 10)];
+        add_segments!(vm, 3);
+        let mut ids_data = non_continuous_ids_data![("p", -4), ("q", -2), ("a", -1)];
+        ids_data.get_mut("p").unwrap().cairo_type = Some("__main__.Point".to_string());
+        ids_data.get_mut("q").unwrap().cairo_type = Some("__main__.Point*".to_string());
+        let code = "ids.q.y = ids.p.x * ids.p.y + ids.q.x";
+        assert_eq!(
+            run_expression(&mut vm, ids_data.clone(), code, &HashMap::new()),
+            Ok(())
+        );
+        assert_eq!(
+            vm.get_integer(&relocatable!(2, 1)).unwrap().as_ref(),
+            &Felt::new(22)
+        );
+        assert_eq!(
+            run_expression(&mut vm, ids_data, "ids.a = ids.p.z", &HashMap::new()),
+            Err(HintError::UnknownStructMember(
+                "__main__.Point".to_string(),
+                "z".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn unsupported_code_is_not_parsed() {
+        for code in [
+            "ids.a = f(ids.b)",
+            "ids.a = ids.b.c.d",
+            "ids.a = 2 ** 3",
+            "ids.a = 4 / 2",
+            "ids.a = memory[ap]",
+            "ids.a = b",
+            "a = 1",
+            "ids.a = 1 < ids.b < 3",
+            "ids.a = 1\nids.b = 2",
+            "ids.a == 1",
+            "ids.a = 1 if ids.b",
+            "ids.a = (1",
+            "ids.a = 1 # comment",
+        ] {
+            assert_eq!(parse_expression_hint(code), None, "{code}");
+        }
+    }
+
+    #[test]
+    fn disabled_expression_hints_are_unknown() {
+        let mut vm = vm!();
+        vm.run_context.fp = 1;
+        let code = "ids.a = 1";
+        let hint_processor = &mut BuiltinHintProcessor::new_empty();
+        let hint_data =
+            HintProcessorData::new_default(code.to_string(), non_continuous_ids_data![("a", -1)]);
+        assert_eq!(
+            hint_processor.execute_hint(
+                &mut vm,
+                exec_scopes_ref!(),
+                &any_box!(hint_data),
+                &HashMap::new()
+            ),
+            Err(HintError::UnknownHint(code.to_string()))
+        );
+    }
+
+    #[test]
+    fn evaluation_errors() {
+        let mut vm = vm!();
+        vm.run_context.fp = 2;
+        vm.memory = memory![((1, 0), (2, 0))];
+        let ids_data = non_continuous_ids_data![("a", -1), ("b", -2)];
+        assert_eq!(
+            run_expression(&mut vm, ids_data.clone(), "ids.a = 1 % 0", &HashMap::new()),
+            Err(HintError::Internal(VirtualMachineError::DividedByZero))
+        );
+        assert_eq!(
+            run_expression(
+                &mut vm,
+                ids_data.clone(),
+                "ids.a = ids.b + 1",
+                &HashMap::new()
+            ),
+            Err(HintError::ExpressionIdentifierNotInteger("b".to_string()))
+        );
+        assert_eq!(
+            run_expression(&mut vm, ids_data, "ids.a = 1 << 5000", &HashMap::new()),
+            Err(HintError::ExpressionShiftOutOfRange(BigInt::from(5000)))
+        );
+    }
+}
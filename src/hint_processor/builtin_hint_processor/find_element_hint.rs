@@ -1,8 +1,8 @@
 use crate::{
     hint_processor::{
         builtin_hint_processor::hint_utils::{
-            get_integer_from_var_name, get_ptr_from_var_name, get_relocatable_from_var_name,
-            insert_value_from_var_name,
+            get_integer_from_var_name, get_maybe_relocatable_from_var_name, get_ptr_from_var_name,
+            get_relocatable_from_var_name, guard_le, insert_value_from_var_name,
         },
         hint_processor_definition::HintReference,
         hint_processor_utils::felt_to_usize,
@@ -18,11 +18,14 @@ use felt::{Felt, NewFelt};
 use num_traits::{Signed, ToPrimitive};
 use std::collections::HashMap;
 
+pub const FIND_ELEMENT_MAX_SIZE: &str = "find_element_max_size";
+
 pub fn find_element(
     vm: &mut VirtualMachine,
     exec_scopes: &mut ExecutionScopes,
     ids_data: &HashMap<String, HintReference>,
     ap_tracking: &ApTracking,
+    limits: &HashMap<String, Felt>,
 ) -> Result<(), HintError> {
     let key = get_integer_from_var_name("key", vm, ids_data, ap_tracking)?;
     let elm_size_bigint = get_integer_from_var_name("elm_size", vm, ids_data, ap_tracking)?;
@@ -57,14 +60,7 @@ pub fn find_element(
             return Err(HintError::ValueOutOfRange(n_elms.into_owned()));
         }
 
-        if let Ok(find_element_max_size) = exec_scopes.get_ref::<Felt>("find_element_max_size") {
-            if n_elms.as_ref() > find_element_max_size {
-                return Err(HintError::FindElemMaxSize(
-                    find_element_max_size.clone(),
-                    n_elms.into_owned(),
-                ));
-            }
-        }
+        guard_le(&n_elms, FIND_ELEMENT_MAX_SIZE, exec_scopes, limits)?;
         let n_elms_iter: i32 = n_elms
             .to_i32()
             .ok_or_else(|| VirtualMachineError::OffsetExceeded(n_elms.into_owned()))?;
@@ -94,12 +90,12 @@ pub fn search_sorted_lower(
     exec_scopes: &mut ExecutionScopes,
     ids_data: &HashMap<String, HintReference>,
     ap_tracking: &ApTracking,
+    limits: &HashMap<String, Felt>,
 ) -> Result<(), HintError> {
-    let find_element_max_size = exec_scopes.get::<Felt>("find_element_max_size");
     let n_elms = get_integer_from_var_name("n_elms", vm, ids_data, ap_tracking)?;
     let rel_array_ptr = get_relocatable_from_var_name("array_ptr", vm, ids_data, ap_tracking)?;
     let elm_size = get_integer_from_var_name("elm_size", vm, ids_data, ap_tracking)?;
-    let key = get_integer_from_var_name("key", vm, ids_data, ap_tracking)?;
+    let key = get_maybe_relocatable_from_var_name("key", vm, ids_data, ap_tracking)?;
 
     if !elm_size.is_positive() {
         return Err(HintError::ValueOutOfRange(elm_size.into_owned()));
@@ -109,23 +105,20 @@ pub fn search_sorted_lower(
         return Err(HintError::ValueOutOfRange(n_elms.into_owned()));
     }
 
-    if let Ok(find_element_max_size) = find_element_max_size {
-        if n_elms.as_ref() > &find_element_max_size {
-            return Err(HintError::FindElemMaxSize(
-                find_element_max_size,
-                n_elms.into_owned(),
-            ));
-        }
-    }
+    guard_le(&n_elms, FIND_ELEMENT_MAX_SIZE, exec_scopes, limits)?;
 
     let mut array_iter = vm.get_relocatable(&rel_array_ptr)?;
     let n_elms_usize = n_elms.to_usize().ok_or(HintError::KeyNotFound)?;
     let elm_size_usize = elm_size.to_usize().ok_or(HintError::KeyNotFound)?;
 
+    //Pointers of the key's segment are compared by offset, see MaybeRelocatable::try_cmp
     for i in 0..n_elms_usize {
-        let value = vm.get_integer(&array_iter)?;
-        if value.as_ref() >= key.as_ref() {
-            return insert_value_from_var_name("index", Felt::new(i), vm, ids_data, ap_tracking);
+        let value = vm
+            .get_maybe(&array_iter)
+            .map_err(VirtualMachineError::MemoryError)?
+            .ok_or_else(|| VirtualMachineError::MemoryGet(array_iter.into()))?;
+        if key.try_le(&value)? {
+            return insert_value_from_var_name("index", i, vm, ids_data, ap_tracking);
         }
         array_iter.offset += elm_size_usize;
     }
@@ -144,7 +137,8 @@ mod tests {
             },
             hint_processor_definition::HintProcessor,
         },
-        types::relocatable::MaybeRelocatable,
+        relocatable,
+        types::relocatable::{MaybeRelocatable, Relocatable},
         utils::test_utils::*,
         vm::vm_core::VirtualMachine,
     };
@@ -263,6 +257,7 @@ mod tests {
     #[test]
     fn find_elm_failed_ids_get_from_mem() {
         let mut vm = vm!();
+        add_segments!(vm, 2);
         vm.run_context.fp = 5;
         let ids_data = ids_data!["array_ptr", "elm_size", "n_elms", "index", "key"];
         assert_eq!(
@@ -348,10 +343,34 @@ mod tests {
         let mut exec_scopes = scope![("find_element_max_size", Felt::one())];
         assert_eq!(
             run_hint!(vm, ids_data, hint_code::FIND_ELEMENT, &mut exec_scopes),
-            Err(HintError::FindElemMaxSize(Felt::one(), Felt::new(2)))
+            Err(HintError::ValueExceedsLimit(
+                Felt::new(2),
+                Felt::one(),
+                FIND_ELEMENT_MAX_SIZE.to_string()
+            ))
         );
     }
 
+    #[test]
+    fn find_elm_max_size_overridden_by_processor() {
+        let (mut vm, ids_data) = init_vm_ids_data(HashMap::new());
+        let mut exec_scopes = scope![("find_element_max_size", Felt::one())];
+        let hint_data =
+            HintProcessorData::new_default(hint_code::FIND_ELEMENT.to_string(), ids_data);
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        hint_processor.set_limit(FIND_ELEMENT_MAX_SIZE, Felt::new(2));
+        assert_eq!(
+            hint_processor.execute_hint(
+                &mut vm,
+                &mut exec_scopes,
+                &any_box!(hint_data),
+                &HashMap::new()
+            ),
+            Ok(())
+        );
+        check_memory![vm.memory, ((1, 3), 1)];
+    }
+
     #[test]
     fn find_elm_key_not_int() {
         let relocatable = MaybeRelocatable::from((1, 4));
@@ -473,7 +492,58 @@ mod tests {
                 hint_code::SEARCH_SORTED_LOWER,
                 &mut exec_scopes
             ),
-            Err(HintError::FindElemMaxSize(Felt::one(), Felt::new(2)))
+            Err(HintError::ValueExceedsLimit(
+                Felt::new(2),
+                Felt::one(),
+                FIND_ELEMENT_MAX_SIZE.to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn search_sorted_lower_pointers() {
+        //Pointers of the key's segment are compared by offset
+        let (mut vm, ids_data) = init_vm_ids_data(HashMap::from([
+            ("key".to_string(), MaybeRelocatable::from((3, 5))),
+            ("arr[0].a".to_string(), MaybeRelocatable::from((3, 4))),
+            ("arr[1].a".to_string(), MaybeRelocatable::from((3, 5))),
+        ]));
+        assert_eq!(
+            run_hint!(vm, ids_data, hint_code::SEARCH_SORTED_LOWER),
+            Ok(())
+        );
+        check_memory![vm.memory, ((1, 3), 1)];
+    }
+
+    #[test]
+    fn search_sorted_lower_pointers_of_different_segments() {
+        let (mut vm, ids_data) = init_vm_ids_data(HashMap::from([
+            ("key".to_string(), MaybeRelocatable::from((3, 5))),
+            ("arr[0].a".to_string(), MaybeRelocatable::from((4, 0))),
+        ]));
+        assert_eq!(
+            run_hint!(vm, ids_data, hint_code::SEARCH_SORTED_LOWER),
+            Err(HintError::Internal(VirtualMachineError::DiffIndexComp(
+                relocatable!(3, 5),
+                relocatable!(4, 0)
+            )))
+        );
+    }
+
+    #[test]
+    fn search_sorted_lower_pointer_key_and_felt_elements() {
+        let (mut vm, ids_data) = init_vm_ids_data(HashMap::from([(
+            "key".to_string(),
+            MaybeRelocatable::from((3, 5)),
+        )]));
+        assert_eq!(
+            run_hint!(vm, ids_data, hint_code::SEARCH_SORTED_LOWER),
+            Err(HintError::Internal(
+                VirtualMachineError::DiffTypeComparison(
+                    MaybeRelocatable::from((3, 5)),
+                    MaybeRelocatable::from(Felt::one())
+                )
+            ))
         );
     }
 }
@@ -1,5 +1,7 @@
 pub(crate) const ADD_SEGMENT: &str = "memory[ap] = segments.add()";
 
+pub(crate) const ALLOC_ARENA_SEGMENT: &str = "memory[ap + 0] = segments.add()";
+
 pub(crate) const VM_ENTER_SCOPE: &str = "vm_enter_scope()";
 pub(crate) const VM_EXIT_SCOPE: &str = "vm_exit_scope()";
 
@@ -420,6 +422,34 @@ value = res = div_mod(a, b, N)"#;
 
 pub(crate) const DIV_MOD_N_SAFE_DIV: &str = r#"value = k = safe_div(res * b - a, N)"#;
 
+pub(crate) const EC_RECOVER_DIV_MOD_N_PACKED: &str = r#"from starkware.cairo.common.cairo_secp.secp_utils import pack
+from starkware.python.math_utils import div_mod, safe_div
+
+N = pack(ids.n, PRIME)
+x = pack(ids.x, PRIME) % N
+s = pack(ids.s, PRIME) % N
+value = res = div_mod(x, s, N)"#;
+
+pub(crate) const EC_RECOVER_SUB_A_B: &str = r#"from starkware.cairo.common.cairo_secp.secp_utils import pack
+from starkware.python.math_utils import div_mod, safe_div
+
+a = pack(ids.a, PRIME)
+b = pack(ids.b, PRIME)
+
+value = res = a - b"#;
+
+pub(crate) const EC_RECOVER_PRODUCT_MOD: &str = r#"from starkware.cairo.common.cairo_secp.secp_utils import pack
+from starkware.python.math_utils import div_mod, safe_div
+
+a = pack(ids.a, PRIME)
+b = pack(ids.b, PRIME)
+product = a * b
+m = pack(ids.m, PRIME)
+
+value = res = product % m"#;
+
+pub(crate) const EC_RECOVER_PRODUCT_DIV_M: &str = r#"value = k = product // m"#;
+
 pub(crate) const GET_POINT_FROM_X: &str = r#"from starkware.cairo.common.cairo_secp.secp_utils import SECP_P, pack
 
 x_cube_int = pack(ids.x_cube, PRIME) % SECP_P
@@ -539,3 +569,32 @@ pub(crate) const RELOCATE_SEGMENT: &str =
     r#"memory.add_relocation_rule(src_ptr=ids.src_ptr, dest_ptr=ids.dest_ptr)"#;
 
 pub(crate) const TEMPORARY_ARRAY: &str = r#"ids.temporary_array = segments.add_temp_segment()"#;
+
+pub(crate) const SKIP_NEXT_INSTRUCTION: &str = r#"skip_next_instruction()"#;
+
+pub(crate) const NONDET_ELEMENTS_OVER_TEN: &str =
+    r#"memory[ap] = to_felt_or_relocatable(ids.elements_end - ids.elements >= 10)"#;
+
+pub(crate) const NONDET_ELEMENTS_OVER_TWO: &str =
+    r#"memory[ap] = to_felt_or_relocatable(ids.elements_end - ids.elements >= 2)"#;
+
+pub(crate) const SPLIT_XX: &str = r#"PRIME = 2**255 - 19
+II = pow(2, (PRIME - 1) // 4, PRIME)
+
+xx = ids.xx.low + (ids.xx.high<<128)
+x = pow(xx, (PRIME + 3) // 8, PRIME)
+if (x * x - xx) % PRIME != 0:
+    x = (x * II) % PRIME
+if x % 2 != 0:
+    x = PRIME - x
+ids.x.low = x & ((1<<128)-1)
+ids.x.high = x >> 128"#;
+
+pub(crate) const IS_250_BITS: &str = r#"ids.is_250 = 1 if ids.addr < 2**250 else 0"#;
+
+pub(crate) const IS_ADDR_BOUNDED: &str = r#"# Verify the assumptions on the relationship between 2**250, ADDR_BOUND and PRIME.
+ADDR_BOUND = ids.ADDR_BOUND % PRIME
+assert (2**250 < ADDR_BOUND <= 2**251) and (2 * 2**250 < PRIME) and (
+        ADDR_BOUND * 2 > PRIME), \
+    'normalize_address() cannot be used with the current constants.'
+ids.is_small = 1 if ids.addr < ADDR_BOUND else 0"#;
@@ -1,4 +1,4 @@
-use felt::Felt;
+use felt::{Felt, NewFelt};
 
 use crate::hint_processor::hint_processor_definition::HintReference;
 use crate::hint_processor::hint_processor_utils::compute_addr_from_reference;
@@ -6,6 +6,7 @@ use crate::hint_processor::hint_processor_utils::{
     get_integer_from_reference, get_maybe_relocatable_from_reference,
 };
 use crate::serde::deserialize_program::ApTracking;
+use crate::types::exec_scope::ExecutionScopes;
 use crate::types::relocatable::MaybeRelocatable;
 use crate::types::relocatable::Relocatable;
 use crate::vm::errors::hint_errors::HintError;
@@ -114,6 +115,100 @@ pub fn get_reference_from_var_name<'a>(
     ids_data.get(var_name).ok_or(HintError::FailedToGetIds)
 }
 
+//Follows a chain of pointers starting at the given ids variable, as done by hints accessing
+//ids.a.b.c where a and b are pointers to structs. Hop 0 reads the pointer held by the variable,
+//and hop i reads the pointer stored at member_offsets[i - 1] of the struct reached so far.
+//Returns the address of the last member, which is read by the caller as hop member_offsets.len()
+fn get_address_from_nested(
+    var_name: &str,
+    member_offsets: &[usize],
+    vm: &VirtualMachine,
+    ids_data: &HashMap<String, HintReference>,
+    ap_tracking: &ApTracking,
+) -> Result<Relocatable, HintError> {
+    let var_addr = get_relocatable_from_var_name(var_name, vm, ids_data, ap_tracking)?;
+    let mut addr = if get_reference_from_var_name(var_name, ids_data)?.dereference {
+        get_nested_ptr(var_name, 0, &var_addr, vm)?
+    } else {
+        var_addr
+    };
+    for (hop, offset) in member_offsets.iter().enumerate() {
+        if hop > 0 {
+            addr = get_nested_ptr(var_name, hop, &addr, vm)?;
+        }
+        addr = addr + *offset;
+    }
+    Ok(addr)
+}
+
+fn get_nested_ptr(
+    var_name: &str,
+    hop: usize,
+    addr: &Relocatable,
+    vm: &VirtualMachine,
+) -> Result<Relocatable, HintError> {
+    vm.get_relocatable(addr)
+        .map_err(|_| HintError::NestedExpectedPointer(var_name.to_string(), hop, *addr))
+}
+
+//Returns the Relocatable value stored in the member reached by following member_offsets from the
+//given ids variable, ie: ids.a.b.c_ptr
+pub fn get_ptr_from_nested(
+    var_name: &str,
+    member_offsets: &[usize],
+    vm: &VirtualMachine,
+    ids_data: &HashMap<String, HintReference>,
+    ap_tracking: &ApTracking,
+) -> Result<Relocatable, HintError> {
+    let addr = get_address_from_nested(var_name, member_offsets, vm, ids_data, ap_tracking)?;
+    get_nested_ptr(var_name, member_offsets.len(), &addr, vm)
+}
+
+//Returns the integer value stored in the member reached by following member_offsets from the
+//given ids variable, ie: ids.a.b.c
+pub fn get_integer_from_nested<'a>(
+    var_name: &str,
+    member_offsets: &[usize],
+    vm: &'a VirtualMachine,
+    ids_data: &HashMap<String, HintReference>,
+    ap_tracking: &ApTracking,
+) -> Result<Cow<'a, Felt>, HintError> {
+    let addr = get_address_from_nested(var_name, member_offsets, vm, ids_data, ap_tracking)?;
+    vm.get_integer(&addr).map_err(|_| {
+        HintError::NestedExpectedInteger(var_name.to_string(), member_offsets.len(), addr)
+    })
+}
+
+//Checks that value <= the limit named limit_name, as done by the hints asserting
+//ids.x <= x_max_size. The limit is taken from the ones set on the hint processor, then from the
+//execution scopes. Limits which aren't set don't bound the value.
+pub fn guard_le(
+    value: &Felt,
+    limit_name: &str,
+    exec_scopes: &ExecutionScopes,
+    limits: &HashMap<String, Felt>,
+) -> Result<(), HintError> {
+    let limit = match limits.get(limit_name) {
+        Some(limit) => Cow::Borrowed(limit),
+        None => match exec_scopes.get_ref::<Felt>(limit_name) {
+            Ok(limit) => Cow::Borrowed(limit),
+            //usort_max_size is stored as an u64
+            Err(_) => match exec_scopes.get::<u64>(limit_name) {
+                Ok(limit) => Cow::Owned(Felt::new(limit)),
+                Err(_) => return Ok(()),
+            },
+        },
+    };
+    if value > limit.as_ref() {
+        return Err(HintError::ValueExceedsLimit(
+            value.clone(),
+            limit.into_owned(),
+            limit_name.to_string(),
+        ));
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use felt::NewFelt;
@@ -162,6 +257,7 @@ mod tests {
     fn get_maybe_relocatable_from_var_name_invalid() {
         let mut vm = vm!();
         vm.memory = Memory::new();
+        add_segments!(vm, 2);
         let hint_ref = HintReference::new_simple(0);
         let ids_data = HashMap::from([("value".to_string(), hint_ref)]);
 
@@ -252,4 +348,122 @@ mod tests {
             )))
         );
     }
+
+    #[test]
+    fn get_integer_from_nested_two_levels() {
+        let mut vm = vm!();
+        //ids.a points to (1, 5), whose member at offset 1 points to (2, 0)
+        vm.memory = memory![((1, 0), (1, 5)), ((1, 6), (2, 0)), ((2, 2), 7)];
+        let ids_data = HashMap::from([("a".to_string(), HintReference::new_simple(0))]);
+
+        assert_eq!(
+            get_integer_from_nested("a", &[1, 2], &vm, &ids_data, &ApTracking::new()),
+            Ok(Cow::Borrowed(&Felt::new(7)))
+        );
+        assert_eq!(
+            get_ptr_from_nested("a", &[1], &vm, &ids_data, &ApTracking::new()),
+            Ok(relocatable!(2, 0))
+        );
+    }
+
+    #[test]
+    fn get_integer_from_nested_not_dereferenced() {
+        let mut vm = vm!();
+        //ids.a = cast(fp, A*), so the variable's address is the pointer itself
+        vm.memory = memory![((1, 1), 7)];
+        let ids_data = HashMap::from([("a".to_string(), HintReference::new(0, 0, false, false))]);
+
+        assert_eq!(
+            get_integer_from_nested("a", &[1], &vm, &ids_data, &ApTracking::new()),
+            Ok(Cow::Borrowed(&Felt::new(7)))
+        );
+    }
+
+    #[test]
+    fn get_integer_from_nested_broken_middle_pointer() {
+        let mut vm = vm!();
+        //The member at offset 1 of *ids.a holds an integer instead of a pointer
+        vm.memory = memory![((1, 0), (1, 5)), ((1, 6), 3)];
+        let ids_data = HashMap::from([("a".to_string(), HintReference::new_simple(0))]);
+
+        assert_eq!(
+            get_integer_from_nested("a", &[1, 2], &vm, &ids_data, &ApTracking::new()),
+            Err(HintError::NestedExpectedPointer(
+                "a".to_string(),
+                1,
+                relocatable!(1, 6)
+            ))
+        );
+    }
+
+    #[test]
+    fn get_ptr_from_nested_missing_last_member() {
+        let mut vm = vm!();
+        vm.memory = memory![((1, 0), (1, 5))];
+        let ids_data = HashMap::from([("a".to_string(), HintReference::new_simple(0))]);
+
+        assert_eq!(
+            get_ptr_from_nested("a", &[3], &vm, &ids_data, &ApTracking::new()),
+            Err(HintError::NestedExpectedPointer(
+                "a".to_string(),
+                1,
+                relocatable!(1, 8)
+            ))
+        );
+    }
+
+    #[test]
+    fn guard_le_with_limit() {
+        let exec_scopes = scope![("x_max_size", Felt::new(10))];
+        let limits = HashMap::new();
+        assert_eq!(
+            guard_le(&Felt::new(10), "x_max_size", &exec_scopes, &limits),
+            Ok(())
+        );
+        let error = guard_le(&Felt::new(11), "x_max_size", &exec_scopes, &limits).unwrap_err();
+        assert_eq!(
+            error,
+            HintError::ValueExceedsLimit(Felt::new(11), Felt::new(10), "x_max_size".to_string())
+        );
+        assert_eq!(
+            error.to_string(),
+            "Value 11 exceeds the limit x_max_size = 10"
+        );
+    }
+
+    #[test]
+    fn guard_le_with_u64_limit() {
+        let exec_scopes = scope![("x_max_size", 10_u64)];
+        assert_eq!(
+            guard_le(&Felt::new(11), "x_max_size", &exec_scopes, &HashMap::new()),
+            Err(HintError::ValueExceedsLimit(
+                Felt::new(11),
+                Felt::new(10),
+                "x_max_size".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn guard_le_limit_overrides_scope() {
+        let exec_scopes = scope![("x_max_size", Felt::new(10))];
+        let limits = HashMap::from([("x_max_size".to_string(), Felt::new(20))]);
+        assert_eq!(
+            guard_le(&Felt::new(11), "x_max_size", &exec_scopes, &limits),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn guard_le_without_limit() {
+        assert_eq!(
+            guard_le(
+                &Felt::new(11),
+                "x_max_size",
+                &ExecutionScopes::new(),
+                &HashMap::new()
+            ),
+            Ok(())
+        );
+    }
 }
@@ -2,6 +2,7 @@ use crate::{
     hint_processor::{
         builtin_hint_processor::hint_utils::{
             get_integer_from_var_name, get_ptr_from_var_name, get_relocatable_from_var_name,
+            guard_le,
         },
         hint_processor_definition::HintReference,
     },
@@ -20,6 +21,8 @@ use num_traits::{One, Signed, ToPrimitive};
 use sha3::{Digest, Keccak256};
 use std::{cmp, collections::HashMap, ops::Shl};
 
+pub const KECCAK_MAX_SIZE: &str = "__keccak_max_size";
+
 /* Implements hint:
    %{
        from eth_hash.auto import keccak
@@ -48,17 +51,11 @@ pub fn unsafe_keccak(
     exec_scopes: &mut ExecutionScopes,
     ids_data: &HashMap<String, HintReference>,
     ap_tracking: &ApTracking,
+    limits: &HashMap<String, Felt>,
 ) -> Result<(), HintError> {
     let length = get_integer_from_var_name("length", vm, ids_data, ap_tracking)?;
 
-    if let Ok(keccak_max_size) = exec_scopes.get::<Felt>("__keccak_max_size") {
-        if length.as_ref() > &keccak_max_size {
-            return Err(HintError::KeccakMaxSize(
-                length.into_owned(),
-                keccak_max_size,
-            ));
-        }
-    }
+    guard_le(&length, KECCAK_MAX_SIZE, exec_scopes, limits)?;
 
     // `data` is an array, represented by a pointer to the first element.
     let data = get_ptr_from_var_name("data", vm, ids_data, ap_tracking)?;
@@ -1,5 +1,5 @@
 use crate::{
-    any_box,
+    cloneable_box,
     hint_processor::{
         builtin_hint_processor::hint_utils::{
             get_address_from_var_name, get_integer_from_var_name, get_ptr_from_var_name,
@@ -21,11 +21,14 @@ use num_integer::Integer;
 use num_traits::One;
 use num_traits::{Num, Signed, Zero};
 use std::{
-    any::Any,
+    cmp::Ordering,
     collections::HashMap,
     ops::{Shl, Shr},
 };
 
+// Constant in package "starkware.starknet.common.storage".
+pub const ADDR_BOUND: &str = "starkware.starknet.common.storage.ADDR_BOUND";
+
 //Implements hint: memory[ap] = 0 if 0 <= (ids.a % PRIME) < range_check_builtin.bound else 1
 pub fn is_nn(
     vm: &mut VirtualMachine,
@@ -113,7 +116,7 @@ pub fn assert_le_felt(
     }
 
     let excluded = lengths_and_indices[2].1;
-    exec_scopes.assign_or_update_variable("excluded", any_box!(Felt::new(excluded)));
+    exec_scopes.assign_or_update_variable("excluded", cloneable_box!(Felt::new(excluded)));
 
     let (q_0, r_0) = (lengths_and_indices[0].0).div_mod_floor(prime_over_3_high);
     let (q_1, r_1) = (lengths_and_indices[1].0).div_mod_floor(prime_over_2_high);
@@ -195,36 +198,11 @@ pub fn assert_not_equal(
     let b_addr = get_address_from_var_name("b", vm, ids_data, ap_tracking)?;
     //Check that the ids are in memory
     match (vm.get_maybe(&a_addr), vm.get_maybe(&b_addr)) {
-        (Ok(Some(maybe_rel_a)), Ok(Some(maybe_rel_b))) => {
-            let maybe_rel_a = maybe_rel_a;
-            let maybe_rel_b = maybe_rel_b;
-            match (maybe_rel_a, maybe_rel_b) {
-                (MaybeRelocatable::Int(a), MaybeRelocatable::Int(b)) => {
-                    if (&a - &b).is_zero() {
-                        return Err(HintError::AssertNotEqualFail(
-                            MaybeRelocatable::Int(a),
-                            MaybeRelocatable::Int(b),
-                        ));
-                    };
-                    Ok(())
-                }
-                (MaybeRelocatable::RelocatableValue(a), MaybeRelocatable::RelocatableValue(b)) => {
-                    if a.segment_index != b.segment_index {
-                        Err(VirtualMachineError::DiffIndexComp(a, b))?;
-                    };
-                    if a.offset == b.offset {
-                        return Err(HintError::AssertNotEqualFail(
-                            MaybeRelocatable::RelocatableValue(a),
-                            MaybeRelocatable::RelocatableValue(b),
-                        ));
-                    };
-                    Ok(())
-                }
-                (maybe_rel_a, maybe_rel_b) => Err(VirtualMachineError::DiffTypeComparison(
-                    maybe_rel_a,
-                    maybe_rel_b,
-                ))?,
+        (Ok(Some(a)), Ok(Some(b))) => {
+            if a.try_cmp(&b)? == Ordering::Equal {
+                return Err(HintError::AssertNotEqualFail(a, b));
             }
+            Ok(())
         }
         _ => Err(HintError::FailedToGetIds),
     }
@@ -431,8 +409,8 @@ pub fn signed_div_rem(
     }
 
     let biased_q = q + int_bound;
-    insert_value_from_var_name("r", Felt::new(r), vm, ids_data, ap_tracking)?;
-    insert_value_from_var_name("biased_q", Felt::new(biased_q), vm, ids_data, ap_tracking)
+    insert_value_from_var_name("r", r, vm, ids_data, ap_tracking)?;
+    insert_value_from_var_name("biased_q", biased_q, vm, ids_data, ap_tracking)
 }
 
 /*
@@ -530,6 +508,86 @@ pub fn assert_lt_felt(
     Ok(())
 }
 
+//Implements hint: %{ ids.is_250 = 1 if ids.addr < 2**250 else 0 %}
+pub fn is_250_bits(
+    vm: &mut VirtualMachine,
+    ids_data: &HashMap<String, HintReference>,
+    ap_tracking: &ApTracking,
+) -> Result<(), HintError> {
+    let addr = get_integer_from_var_name("addr", vm, ids_data, ap_tracking)?;
+    //Main logic
+    //ids.is_250 = 1 if ids.addr < 2**250 else 0
+    let is_250 = if addr.bits() <= 250 {
+        Felt::one()
+    } else {
+        Felt::zero()
+    };
+    insert_value_from_var_name("is_250", is_250, vm, ids_data, ap_tracking)
+}
+
+/*
+Implements hint:
+%{
+    # Verify the assumptions on the relationship between 2**250, ADDR_BOUND and PRIME.
+    ADDR_BOUND = ids.ADDR_BOUND % PRIME
+    assert (2**250 < ADDR_BOUND <= 2**251) and (2 * 2**250 < PRIME) and (
+            ADDR_BOUND * 2 > PRIME), \
+        'normalize_address() cannot be used with the current constants.'
+    ids.is_small = 1 if ids.addr < ADDR_BOUND else 0
+%}
+*/
+pub fn is_addr_bounded(
+    vm: &mut VirtualMachine,
+    ids_data: &HashMap<String, HintReference>,
+    ap_tracking: &ApTracking,
+    constants: &HashMap<String, Felt>,
+) -> Result<(), HintError> {
+    let addr = get_integer_from_var_name("addr", vm, ids_data, ap_tracking)?;
+    let addr_bound = constants
+        .get(ADDR_BOUND)
+        .ok_or(HintError::MissingConstant(ADDR_BOUND))?
+        .to_biguint();
+    let prime = BigUint::from_str_radix(&PRIME_STR[2..], 16)
+        .map_err(|_| VirtualMachineError::CouldntParsePrime(PRIME_STR.to_string()))?;
+    let lower_bound = BigUint::one() << 250_usize;
+    let upper_bound = BigUint::one() << 251_usize;
+    //Main logic
+    if !(lower_bound < addr_bound
+        && addr_bound <= upper_bound
+        && (lower_bound << 1_usize) < prime
+        && (&addr_bound << 1_usize) > prime)
+    {
+        return Err(HintError::AssertionFailed(String::from(
+            "normalize_address() cannot be used with the current constants.",
+        )));
+    }
+    //ids.is_small = 1 if ids.addr < ADDR_BOUND else 0
+    let is_small = if addr.to_biguint() < addr_bound {
+        Felt::one()
+    } else {
+        Felt::zero()
+    };
+    insert_value_from_var_name("is_small", is_small, vm, ids_data, ap_tracking)
+}
+
+/*
+Implements hints:
+%{ memory[ap] = to_felt_or_relocatable(ids.elements_end - ids.elements >= 10) %}
+%{ memory[ap] = to_felt_or_relocatable(ids.elements_end - ids.elements >= 2) %}
+*/
+pub fn nondet_elements_over_x(
+    vm: &mut VirtualMachine,
+    ids_data: &HashMap<String, HintReference>,
+    ap_tracking: &ApTracking,
+    x: usize,
+) -> Result<(), HintError> {
+    let elements_end = get_ptr_from_var_name("elements_end", vm, ids_data, ap_tracking)?;
+    let elements = get_ptr_from_var_name("elements", vm, ids_data, ap_tracking)?;
+    //elements_end - elements >= x, without failing when elements_end is behind elements
+    let over_x = MaybeRelocatable::from(elements + x).try_le(&elements_end.into())?;
+    insert_value_into_ap(vm, over_x as usize)
+}
+
 fn div_prime_by_bound(bound: Felt) -> Result<Felt, VirtualMachineError> {
     let prime = BigUint::from_str_radix(&PRIME_STR[2..], 16)
         .map_err(|_| VirtualMachineError::CouldntParsePrime(PRIME_STR.to_string()))?;
@@ -544,7 +602,7 @@ mod tests {
         any_box,
         hint_processor::builtin_hint_processor::{
             builtin_hint_processor_definition::{BuiltinHintProcessor, HintProcessorData},
-            hint_code::ASSERT_LE_FELT,
+            hint_code::{ASSERT_LE_FELT, IS_ADDR_BOUNDED, NONDET_ELEMENTS_OVER_TWO},
         },
         hint_processor::hint_processor_definition::HintProcessor,
         relocatable,
@@ -577,6 +635,31 @@ mod tests {
         check_memory![vm.memory, ((1, 0), 1)];
     }
 
+    #[test]
+    fn run_is_nn_hint_reads_range_check_bound() {
+        let hint_code = "memory[ap] = 0 if 0 <= (ids.a % PRIME) < range_check_builtin.bound else 1";
+        let mut vm = vm_with_range_check!();
+        let bound = Felt::one() << 128_u32;
+        assert_eq!(
+            vm.get_range_check_builtin().unwrap()._bound,
+            Some(bound.clone())
+        );
+        add_segments!(vm, 2);
+        vm.insert_value(&(1, 0).into(), bound.clone() - Felt::one())
+            .unwrap();
+        vm.insert_value(&(1, 1).into(), bound).unwrap();
+        let ids_data = ids_data!["a"];
+        //a = bound - 1 is in range
+        vm.run_context.ap = 2;
+        vm.run_context.fp = 1;
+        run_hint!(vm, ids_data.clone(), hint_code).expect("Error while executing hint");
+        //a = bound isn't
+        vm.run_context.ap = 3;
+        vm.run_context.fp = 2;
+        run_hint!(vm, ids_data, hint_code).expect("Error while executing hint");
+        check_memory![vm.memory, ((1, 2), 0), ((1, 3), 1)];
+    }
+
     #[test]
     fn run_is_nn_hint_true() {
         let hint_code = "memory[ap] = 0 if 0 <= (ids.a % PRIME) < range_check_builtin.bound else 1";
@@ -735,6 +818,58 @@ mod tests {
         check_memory![vm.memory, ((1, 0), 0)];
     }
 
+    #[test]
+    fn is_le_felt_hint_boundaries() {
+        let hint_code = "memory[ap] = 0 if (ids.a % PRIME) <= (ids.b % PRIME) else 1";
+        let mut vm = vm_with_range_check!();
+        //(a, b) pairs, -1 wraps around to PRIME - 1, the greatest felt
+        vm.memory = memory![
+            ((1, 0), 2),
+            ((1, 1), 2),
+            ((1, 2), 3),
+            ((1, 3), 2),
+            ((1, 4), (-1)),
+            ((1, 5), 0),
+            ((1, 6), 0),
+            ((1, 7), (-1))
+        ];
+        add_segments!(vm, 1);
+        let ids_data = ids_data!["a", "b"];
+        for fp in [2, 4, 6, 8] {
+            vm.run_context.fp = fp;
+            vm.run_context.ap = 8 + fp / 2;
+            run_hint!(vm, ids_data.clone(), hint_code).expect("Error while executing hint");
+        }
+        check_memory![
+            vm.memory,
+            ((1, 9), 0),
+            ((1, 10), 1),
+            ((1, 11), 1),
+            ((1, 12), 0)
+        ];
+    }
+
+    #[test]
+    fn run_is_nn_hint_ap_taken() {
+        let hint_code = "memory[ap] = 0 if 0 <= (ids.a % PRIME) < range_check_builtin.bound else 1";
+        let mut vm = vm_with_range_check!();
+        vm.run_context.fp = 1;
+        //ap already holds the opposite result
+        vm.run_context.ap = 1;
+        vm.memory = memory![((1, 0), 5), ((1, 1), 1)];
+        let ids_data = ids_data!["a"];
+        assert_eq!(
+            run_hint!(vm, ids_data, hint_code),
+            Err(HintError::Internal(VirtualMachineError::MemoryError(
+                MemoryError::InconsistentMemory(
+                    MaybeRelocatable::from((1, 1)),
+                    MaybeRelocatable::Int(Felt::one()),
+                    MaybeRelocatable::Int(Felt::zero())
+                )
+            )))
+        );
+    }
+
     #[test]
     fn run_is_le_felt_hint_inconsistent_memory() {
         let hint_code = "memory[ap] = 0 if (ids.a % PRIME) <= (ids.b % PRIME) else 1";
@@ -859,7 +994,7 @@ mod tests {
     fn run_assert_nn_reference_is_not_in_memory() {
         let hint_code = "from starkware.cairo.common.math_utils import assert_integer\nassert_integer(ids.a)\nassert 0 <= ids.a % PRIME < range_check_builtin.bound, f'a = {ids.a} is out of range.'";
         let mut vm = vm_with_range_check!();
-        add_segments!(vm, 1);
+        add_segments!(vm, 2);
         //Initialize fp
         vm.run_context.fp = 4;
         let ids_data = ids_data!["a"];
@@ -1874,4 +2009,122 @@ mod tests {
             )))
         );
     }
+
+    #[test]
+    fn run_nondet_elements_over_ten_true() {
+        let hint_code =
+            "memory[ap] = to_felt_or_relocatable(ids.elements_end - ids.elements >= 10)";
+        let mut vm = vm!();
+        vm.run_context.fp = 2;
+        vm.run_context.ap = 2;
+        vm.memory = memory![((1, 0), (2, 10)), ((1, 1), (2, 0))];
+        let ids_data = ids_data!["elements_end", "elements"];
+        assert_eq!(run_hint!(vm, ids_data, hint_code), Ok(()));
+        check_memory![vm.memory, ((1, 2), 1)];
+    }
+
+    #[test]
+    fn run_nondet_elements_over_two_false() {
+        let hint_code = "memory[ap] = to_felt_or_relocatable(ids.elements_end - ids.elements >= 2)";
+        let mut vm = vm!();
+        vm.run_context.fp = 2;
+        vm.run_context.ap = 2;
+        vm.memory = memory![((1, 0), (2, 1)), ((1, 1), (2, 0))];
+        let ids_data = ids_data!["elements_end", "elements"];
+        assert_eq!(run_hint!(vm, ids_data, hint_code), Ok(()));
+        check_memory![vm.memory, ((1, 2), 0)];
+    }
+
+    #[test]
+    fn run_nondet_elements_over_two_end_before_elements() {
+        let mut vm = vm!();
+        vm.run_context.fp = 2;
+        vm.run_context.ap = 2;
+        vm.memory = memory![((1, 0), (2, 0)), ((1, 1), (2, 3))];
+        let ids_data = ids_data!["elements_end", "elements"];
+        assert_eq!(run_hint!(vm, ids_data, NONDET_ELEMENTS_OVER_TWO), Ok(()));
+        check_memory![vm.memory, ((1, 2), 0)];
+    }
+
+    #[test]
+    fn run_nondet_elements_over_two_different_segments() {
+        let mut vm = vm!();
+        vm.run_context.fp = 2;
+        vm.run_context.ap = 2;
+        vm.memory = memory![((1, 0), (3, 5)), ((1, 1), (2, 0))];
+        let ids_data = ids_data!["elements_end", "elements"];
+        assert_eq!(
+            run_hint!(vm, ids_data, NONDET_ELEMENTS_OVER_TWO),
+            Err(HintError::Internal(VirtualMachineError::DiffIndexComp(
+                relocatable!(2, 2),
+                relocatable!(3, 5)
+            )))
+        );
+    }
+
+    #[test]
+    fn run_is_250_bits_true() {
+        let hint_code = "ids.is_250 = 1 if ids.addr < 2**250 else 0";
+        let mut vm = vm!();
+        vm.run_context.fp = 2;
+        vm.memory = memory![((1, 0), 1152251)];
+        let ids_data = ids_data!["addr", "is_250"];
+        assert_eq!(run_hint!(vm, ids_data, hint_code), Ok(()));
+        check_memory![vm.memory, ((1, 1), 1)];
+    }
+
+    #[test]
+    fn run_is_250_bits_false() {
+        let hint_code = "ids.is_250 = 1 if ids.addr < 2**250 else 0";
+        let mut vm = vm!();
+        vm.run_context.fp = 2;
+        //2**250
+        vm.memory = memory![(
+            (1, 0),
+            (
+                "1809251394333065553493296640760748560207343510400633813116524750123642650624",
+                10
+            )
+        )];
+        let ids_data = ids_data!["addr", "is_250"];
+        assert_eq!(run_hint!(vm, ids_data, hint_code), Ok(()));
+        check_memory![vm.memory, ((1, 1), 0)];
+    }
+
+    #[test]
+    fn run_is_addr_bounded() {
+        let hint_code = IS_ADDR_BOUNDED;
+        let mut vm = vm!();
+        vm.run_context.fp = 2;
+        vm.memory = memory![((1, 0), 1152251)];
+        let ids_data = ids_data!["addr", "is_small"];
+        //2**251 - 256
+        let constants = HashMap::from([(
+            ADDR_BOUND.to_string(),
+            felt_str!(
+                "3618502788666131106986593281521497120414687020801267626233049500247285300992"
+            ),
+        )]);
+        assert_eq!(
+            run_hint!(vm, ids_data, hint_code, exec_scopes_ref!(), &constants),
+            Ok(())
+        );
+        check_memory![vm.memory, ((1, 1), 1)];
+    }
+
+    #[test]
+    fn run_is_addr_bounded_invalid_bound() {
+        let hint_code = IS_ADDR_BOUNDED;
+        let mut vm = vm!();
+        vm.run_context.fp = 2;
+        vm.memory = memory![((1, 0), 1152251)];
+        let ids_data = ids_data!["addr", "is_small"];
+        let constants = HashMap::from([(ADDR_BOUND.to_string(), Felt::new(1))]);
+        assert_eq!(
+            run_hint!(vm, ids_data, hint_code, exec_scopes_ref!(), &constants),
+            Err(HintError::AssertionFailed(String::from(
+                "normalize_address() cannot be used with the current constants."
+            )))
+        );
+    }
 }
@@ -6,12 +6,12 @@ use crate::{
         hint_processor_definition::HintReference,
     },
     serde::deserialize_program::ApTracking,
-    types::exec_scope::ExecutionScopes,
+    types::exec_scope::{CloneableAny, ExecutionScopes},
     vm::{errors::hint_errors::HintError, vm_core::VirtualMachine},
 };
 use felt::Felt;
 use num_traits::{One, Zero};
-use std::{any::Any, collections::HashMap};
+use std::collections::HashMap;
 
 //Implements hint: memory[ap] = segments.add()
 pub fn add_segment(vm: &mut VirtualMachine) -> Result<(), HintError> {
@@ -19,6 +19,13 @@ pub fn add_segment(vm: &mut VirtualMachine) -> Result<(), HintError> {
     insert_value_into_ap(vm, new_segment_base)
 }
 
+//Implements the Cairo 1 hint AllocSegment: memory[ap + 0] = segments.add()
+//The segment is allocated through the segment_arena builtin when the program uses it
+pub fn alloc_arena_segment(vm: &mut VirtualMachine) -> Result<(), HintError> {
+    let new_segment_base = vm.add_arena_segment()?;
+    insert_value_into_ap(vm, new_segment_base)
+}
+
 //Implements hint: vm_enter_scope()
 pub fn enter_scope(exec_scopes: &mut ExecutionScopes) -> Result<(), HintError> {
     exec_scopes.enter_scope(HashMap::new());
@@ -39,7 +46,7 @@ pub fn memcpy_enter_scope(
     ids_data: &HashMap<String, HintReference>,
     ap_tracking: &ApTracking,
 ) -> Result<(), HintError> {
-    let len: Box<dyn Any> =
+    let len: Box<dyn CloneableAny> =
         Box::new(get_integer_from_var_name("len", vm, ids_data, ap_tracking)?.into_owned());
     exec_scopes.enter_scope(HashMap::from([(String::from("n"), len)]));
     Ok(())
@@ -57,9 +64,9 @@ pub fn memcpy_continue_copying(
     ap_tracking: &ApTracking,
 ) -> Result<(), HintError> {
     // get `n` variable from vm scope
-    let n = exec_scopes.get_ref::<Felt>("n")?;
+    let n = exec_scopes.get_mut_int_ref("n")?;
     // this variable will hold the value of `n - 1`
-    let new_n = n - 1;
+    let new_n = &*n - 1;
     // if it is positive, insert 1 in the address of `continue_copying`
     // else, insert 0
     if new_n.is_zero() {
@@ -67,7 +74,8 @@ pub fn memcpy_continue_copying(
     } else {
         insert_value_from_var_name("continue_copying", Felt::one(), vm, ids_data, ap_tracking)?;
     }
-    exec_scopes.insert_value("n", new_n);
+    // Reassign `n` with `n - 1` in place
+    *n = new_n;
     Ok(())
 }
 
@@ -6,12 +6,12 @@ use crate::{
         hint_processor_definition::HintReference,
     },
     serde::deserialize_program::ApTracking,
-    types::exec_scope::ExecutionScopes,
+    types::exec_scope::{CloneableAny, ExecutionScopes},
     vm::{errors::hint_errors::HintError, vm_core::VirtualMachine},
 };
 use felt::{Felt, NewFelt};
 use num_traits::Signed;
-use std::{any::Any, collections::HashMap};
+use std::collections::HashMap;
 
 //  Implements hint:
 //  %{ vm_enter_scope({'n': ids.n}) %}
@@ -21,7 +21,7 @@ pub fn memset_enter_scope(
     ids_data: &HashMap<String, HintReference>,
     ap_tracking: &ApTracking,
 ) -> Result<(), HintError> {
-    let n: Box<dyn Any> =
+    let n: Box<dyn CloneableAny> =
         Box::new(get_integer_from_var_name("n", vm, ids_data, ap_tracking)?.into_owned());
     exec_scopes.enter_scope(HashMap::from([(String::from("n"), n)]));
     Ok(())
@@ -40,16 +40,16 @@ pub fn memset_continue_loop(
     ap_tracking: &ApTracking,
 ) -> Result<(), HintError> {
     // get `n` variable from vm scope
-    let n = exec_scopes.get_ref::<Felt>("n")?;
+    let n = exec_scopes.get_mut_int_ref("n")?;
     // this variable will hold the value of `n - 1`
-    let new_n = n - 1;
+    let new_n = &*n - 1;
     // if `new_n` is positive, insert 1 in the address of `continue_loop`
     // else, insert 0
     let should_continue = Felt::new(new_n.is_positive() as i32);
     insert_value_from_var_name("continue_loop", should_continue, vm, ids_data, ap_tracking)?;
-    // Reassign `n` with `n - 1`
-    // we do it at the end of the function so that the borrow checker doesn't complain
-    exec_scopes.insert_value("n", new_n);
+    // Reassign `n` with `n - 1` in place
+    // we do it at the end of the function so that `n` isn't updated if the insertion fails
+    *n = new_n;
     Ok(())
 }
 
@@ -72,6 +72,7 @@ mod tests {
         },
     };
     use num_traits::{One, Zero};
+    use std::any::Any;
 
     #[test]
     fn memset_enter_scope_valid() {
@@ -1,21 +1,49 @@
+pub mod builtin_hint_processor_definition;
+pub mod dict_manager;
+//The code of the builtin hints, hidden from the docs, will become pub(crate) in the next release.
+#[doc(hidden)]
+pub mod hint_code;
+pub mod hint_utils;
+
+//Implementations of the builtin hints, only meant to be run through BuiltinHintProcessor.
+//They are hidden from the docs and will become pub(crate) in the next release.
+#[doc(hidden)]
 pub mod blake2s_hash;
+#[doc(hidden)]
 pub mod blake2s_utils;
-pub mod builtin_hint_processor_definition;
+#[doc(hidden)]
 pub mod cairo_keccak;
+#[doc(hidden)]
 pub mod dict_hint_utils;
-pub mod dict_manager;
+#[doc(hidden)]
+pub mod ed25519_utils;
+#[doc(hidden)]
+pub mod expression_hint;
+#[doc(hidden)]
 pub mod find_element_hint;
-pub mod hint_code;
-pub mod hint_utils;
+#[doc(hidden)]
 pub mod keccak_utils;
+#[doc(hidden)]
 pub mod math_utils;
+#[doc(hidden)]
 pub mod memcpy_hint_utils;
+#[doc(hidden)]
 pub mod memset_utils;
+#[doc(hidden)]
 pub mod pow_utils;
+#[doc(hidden)]
 pub mod secp;
+#[doc(hidden)]
 pub mod segments;
+#[doc(hidden)]
 pub mod set;
+#[doc(hidden)]
 pub mod sha256_utils;
+#[doc(hidden)]
+pub mod skip_next_instruction;
+#[doc(hidden)]
 pub mod squash_dict_utils;
+#[doc(hidden)]
 pub mod uint256_utils;
+#[doc(hidden)]
 pub mod usort;
@@ -1,14 +1,11 @@
 use crate::{
     hint_processor::{
-        builtin_hint_processor::hint_utils::{
-            get_relocatable_from_var_name, insert_value_from_var_name,
-        },
+        builtin_hint_processor::hint_utils::{get_integer_from_nested, insert_value_from_var_name},
         hint_processor_definition::HintReference,
     },
     serde::deserialize_program::ApTracking,
     vm::{errors::hint_errors::HintError, vm_core::VirtualMachine},
 };
-use felt::{Felt, NewFelt};
 use num_integer::Integer;
 use std::collections::HashMap;
 
@@ -21,10 +18,10 @@ pub fn pow(
     ids_data: &HashMap<String, HintReference>,
     ap_tracking: &ApTracking,
 ) -> Result<(), HintError> {
-    let prev_locs_addr = get_relocatable_from_var_name("prev_locs", vm, ids_data, ap_tracking)?;
-    let prev_locs_exp = vm.get_integer(&(&prev_locs_addr + 4_i32))?;
+    //prev_locs is a LoopLocals* and exp is its fifth member
+    let prev_locs_exp = get_integer_from_nested("prev_locs", &[4], vm, ids_data, ap_tracking)?;
     let locs_bit = prev_locs_exp.is_odd();
-    insert_value_from_var_name("locs", Felt::new(locs_bit as u8), vm, ids_data, ap_tracking)?;
+    insert_value_from_var_name("locs", locs_bit as usize, vm, ids_data, ap_tracking)?;
     Ok(())
 }
 
@@ -38,7 +35,11 @@ mod tests {
             builtin_hint_processor::builtin_hint_processor_definition::HintProcessorData,
             hint_processor_definition::HintProcessor,
         },
-        types::{exec_scope::ExecutionScopes, relocatable::MaybeRelocatable},
+        relocatable,
+        types::{
+            exec_scope::ExecutionScopes,
+            relocatable::{MaybeRelocatable, Relocatable},
+        },
         utils::test_utils::*,
         vm::{
             errors::{memory_errors::MemoryError, vm_errors::VirtualMachineError},
@@ -47,6 +48,7 @@ mod tests {
             vm_memory::memory::Memory,
         },
     };
+    use felt::{Felt, NewFelt};
     use num_traits::One;
     use std::any::Any;
 
@@ -57,7 +59,14 @@ mod tests {
         //Initialize ap
         vm.run_context.fp = 12;
         vm.memory = memory![((1, 11), 3)];
-        let ids_data = non_continuous_ids_data![("prev_locs", -5), ("locs", 0)];
+        let ids_data = HashMap::from([
+            //prev_locs = cast(fp - 5, LoopLocals*) isn't dereferenced
+            (
+                "prev_locs".to_string(),
+                HintReference::new(-5, 0, false, false),
+            ),
+            ("locs".to_string(), HintReference::new_simple(0)),
+        ]);
         assert_eq!(run_hint!(vm, ids_data, hint_code), Ok(()));
         //Check hint memory inserts
         check_memory![vm.memory, ((1, 12), 1)];
@@ -87,13 +96,22 @@ mod tests {
         //Initialize fp
         vm.run_context.fp = 11;
         //Create hint_data
-        let ids_data = non_continuous_ids_data![("prev_locs", -5), ("locs", -12)];
+        let ids_data = HashMap::from([
+            //prev_locs = cast(fp - 5, LoopLocals*) isn't dereferenced
+            (
+                "prev_locs".to_string(),
+                HintReference::new(-5, 0, false, false),
+            ),
+            ("locs".to_string(), HintReference::new_simple(-12)),
+        ]);
         //Execute the hint
         assert_eq!(
             run_hint!(vm, ids_data, hint_code),
-            Err(HintError::Internal(VirtualMachineError::ExpectedInteger(
-                MaybeRelocatable::from((1, 10))
-            )))
+            Err(HintError::NestedExpectedInteger(
+                "prev_locs".to_string(),
+                1,
+                relocatable!(1, 10)
+            ))
         );
     }
 
@@ -104,16 +122,25 @@ mod tests {
         //Initialize fp
         vm.run_context.fp = 11;
         //Create hint_data
-        let ids_data = non_continuous_ids_data![("prev_locs", -5), ("locs", -12)];
+        let ids_data = HashMap::from([
+            //prev_locs = cast(fp - 5, LoopLocals*) isn't dereferenced
+            (
+                "prev_locs".to_string(),
+                HintReference::new(-5, 0, false, false),
+            ),
+            ("locs".to_string(), HintReference::new_simple(-12)),
+        ]);
         //Insert ids.prev_locs.exp into memory as a RelocatableValue
         vm.memory = memory![((1, 10), (1, 11))];
         add_segments!(vm, 1);
         //Execute the hint
         assert_eq!(
             run_hint!(vm, ids_data, hint_code),
-            Err(HintError::Internal(VirtualMachineError::ExpectedInteger(
-                MaybeRelocatable::from((1, 10))
-            )))
+            Err(HintError::NestedExpectedInteger(
+                "prev_locs".to_string(),
+                1,
+                relocatable!(1, 10)
+            ))
         );
     }
 
@@ -124,7 +151,14 @@ mod tests {
         //Initialize ap
         vm.run_context.fp = 11;
         //Create hint_data
-        let ids_data = non_continuous_ids_data![("prev_locs", -5), ("locs", 0)];
+        let ids_data = HashMap::from([
+            //prev_locs = cast(fp - 5, LoopLocals*) isn't dereferenced
+            (
+                "prev_locs".to_string(),
+                HintReference::new(-5, 0, false, false),
+            ),
+            ("locs".to_string(), HintReference::new_simple(0)),
+        ]);
         //Insert ids into memory
         vm.memory = memory![((1, 10), 3), ((1, 11), 3)];
         //Execute the hint
@@ -0,0 +1,348 @@
+use crate::{
+    hint_processor::{
+        builtin_hint_processor::secp::secp_utils::pack_from_var_name,
+        hint_processor_definition::HintReference,
+    },
+    math_utils::div_mod,
+    serde::deserialize_program::ApTracking,
+    types::exec_scope::ExecutionScopes,
+    vm::{
+        errors::{hint_errors::HintError, vm_errors::VirtualMachineError},
+        vm_core::VirtualMachine,
+    },
+};
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_traits::Zero;
+use std::collections::HashMap;
+
+/* Implements hint:
+from starkware.cairo.common.cairo_secp.secp_utils import pack
+from starkware.python.math_utils import div_mod, safe_div
+
+N = pack(ids.n, PRIME)
+x = pack(ids.x, PRIME) % N
+s = pack(ids.s, PRIME) % N
+value = res = div_mod(x, s, N)
+*/
+pub fn ec_recover_divmod_div_n(
+    vm: &mut VirtualMachine,
+    exec_scopes: &mut ExecutionScopes,
+    ids_data: &HashMap<String, HintReference>,
+    ap_tracking: &ApTracking,
+) -> Result<(), HintError> {
+    let n = pack_from_var_name("n", vm, ids_data, ap_tracking)?;
+    if n.is_zero() {
+        return Err(VirtualMachineError::DividedByZero.into());
+    }
+    let x = pack_from_var_name("x", vm, ids_data, ap_tracking)?.mod_floor(&n);
+    let s = pack_from_var_name("s", vm, ids_data, ap_tracking)?.mod_floor(&n);
+
+    let value = div_mod(&x, &s, &n);
+    exec_scopes.insert_value("value", value.clone());
+    exec_scopes.insert_value("res", value);
+    Ok(())
+}
+
+/* Implements hint:
+from starkware.cairo.common.cairo_secp.secp_utils import pack
+from starkware.python.math_utils import div_mod, safe_div
+
+a = pack(ids.a, PRIME)
+b = pack(ids.b, PRIME)
+
+value = res = a - b
+*/
+pub fn ec_recover_sub_a_b(
+    vm: &mut VirtualMachine,
+    exec_scopes: &mut ExecutionScopes,
+    ids_data: &HashMap<String, HintReference>,
+    ap_tracking: &ApTracking,
+) -> Result<(), HintError> {
+    let a = pack_from_var_name("a", vm, ids_data, ap_tracking)?;
+    let b = pack_from_var_name("b", vm, ids_data, ap_tracking)?;
+
+    let value = a - b;
+    exec_scopes.insert_value("value", value.clone());
+    exec_scopes.insert_value("res", value);
+    Ok(())
+}
+
+/* Implements hint:
+from starkware.cairo.common.cairo_secp.secp_utils import pack
+from starkware.python.math_utils import div_mod, safe_div
+
+a = pack(ids.a, PRIME)
+b = pack(ids.b, PRIME)
+product = a * b
+m = pack(ids.m, PRIME)
+
+value = res = product % m
+*/
+pub fn ec_recover_product_mod(
+    vm: &mut VirtualMachine,
+    exec_scopes: &mut ExecutionScopes,
+    ids_data: &HashMap<String, HintReference>,
+    ap_tracking: &ApTracking,
+) -> Result<(), HintError> {
+    let a = pack_from_var_name("a", vm, ids_data, ap_tracking)?;
+    let b = pack_from_var_name("b", vm, ids_data, ap_tracking)?;
+    let m = pack_from_var_name("m", vm, ids_data, ap_tracking)?;
+    if m.is_zero() {
+        return Err(VirtualMachineError::DividedByZero.into());
+    }
+
+    let product = a * b;
+    let value = product.mod_floor(&m);
+    exec_scopes.insert_value("product", product);
+    exec_scopes.insert_value("m", m);
+    exec_scopes.insert_value("value", value.clone());
+    exec_scopes.insert_value("res", value);
+    Ok(())
+}
+
+// Implements hint:
+// value = k = product // m
+pub fn ec_recover_product_div_m(exec_scopes: &mut ExecutionScopes) -> Result<(), HintError> {
+    let product = exec_scopes.get_ref::<BigInt>("product")?;
+    let m = exec_scopes.get_ref::<BigInt>("m")?;
+    if m.is_zero() {
+        return Err(VirtualMachineError::DividedByZero.into());
+    }
+
+    let value = product.div_floor(m);
+    exec_scopes.insert_value("value", value.clone());
+    exec_scopes.insert_value("k", value);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        any_box,
+        hint_processor::{
+            builtin_hint_processor::{
+                builtin_hint_processor_definition::{BuiltinHintProcessor, HintProcessorData},
+                hint_code,
+            },
+            hint_processor_definition::HintProcessor,
+        },
+        types::relocatable::MaybeRelocatable,
+        utils::test_utils::*,
+        vm::{errors::memory_errors::MemoryError, vm_memory::memory::Memory},
+    };
+    use std::any::Any;
+
+    #[test]
+    fn ec_recover_divmod_div_n_ok() {
+        let hint_code = hint_code::EC_RECOVER_DIV_MOD_N_PACKED;
+        let mut vm = vm!();
+        vm.run_context.fp = 9;
+        //n = 2**86 - 1 + 12345 * 2**86 + 3 * 2**172, x = 100 + 200 * 2**86 + 300 * 2**172, s = 7
+        vm.memory = memory![
+            ((1, 0), 77371252455336267181195263_u128),
+            ((1, 1), 12345),
+            ((1, 2), 3),
+            ((1, 3), 100),
+            ((1, 4), 200),
+            ((1, 5), 300),
+            ((1, 6), 7),
+            ((1, 7), 0),
+            ((1, 8), 0)
+        ];
+        let ids_data = non_continuous_ids_data![("n", -9), ("x", -6), ("s", -3)];
+        let mut exec_scopes = ExecutionScopes::new();
+        assert_eq!(run_hint!(vm, ids_data, hint_code, &mut exec_scopes), Ok(()));
+        //Value computed by the python hint
+        let expected = bigint_str!("12827808656801525042049095025067375649603589027172965");
+        check_scope!(
+            &exec_scopes,
+            [("value", expected.clone()), ("res", expected)]
+        );
+    }
+
+    #[test]
+    fn ec_recover_divmod_div_n_zero_modulus() {
+        let hint_code = hint_code::EC_RECOVER_DIV_MOD_N_PACKED;
+        let mut vm = vm!();
+        vm.run_context.fp = 9;
+        vm.memory = memory![
+            ((1, 0), 0),
+            ((1, 1), 0),
+            ((1, 2), 0),
+            ((1, 3), 25),
+            ((1, 4), 0),
+            ((1, 5), 0),
+            ((1, 6), 5),
+            ((1, 7), 0),
+            ((1, 8), 0)
+        ];
+        let ids_data = non_continuous_ids_data![("n", -9), ("x", -6), ("s", -3)];
+        assert_eq!(
+            run_hint!(vm, ids_data, hint_code),
+            Err(HintError::Internal(VirtualMachineError::DividedByZero))
+        );
+    }
+
+    #[test]
+    fn ec_recover_sub_a_b_ok() {
+        let hint_code = hint_code::EC_RECOVER_SUB_A_B;
+        let mut vm = vm!();
+        vm.run_context.fp = 6;
+        vm.memory = memory![
+            ((1, 0), 100),
+            ((1, 1), 0),
+            ((1, 2), 0),
+            ((1, 3), 120),
+            ((1, 4), 0),
+            ((1, 5), 0)
+        ];
+        let ids_data = non_continuous_ids_data![("a", -6), ("b", -3)];
+        let mut exec_scopes = ExecutionScopes::new();
+        assert_eq!(run_hint!(vm, ids_data, hint_code, &mut exec_scopes), Ok(()));
+        //The result isn't reduced, so it can be negative as in python
+        check_scope!(
+            &exec_scopes,
+            [("value", BigInt::from(-20)), ("res", BigInt::from(-20))]
+        );
+    }
+
+    #[test]
+    fn ec_recover_product_mod_ok() {
+        let hint_code = hint_code::EC_RECOVER_PRODUCT_MOD;
+        let mut vm = vm!();
+        vm.run_context.fp = 9;
+        //a = 2**85 + 7 * 2**86 + 2**172, b = 3 + 2**40 * 2**86, m = 1000003 + 17 * 2**86
+        vm.memory = memory![
+            ((1, 0), 38685626227668133590597632_u128),
+            ((1, 1), 7),
+            ((1, 2), 1),
+            ((1, 3), 3),
+            ((1, 4), 1099511627776_u64),
+            ((1, 5), 0),
+            ((1, 6), 1000003),
+            ((1, 7), 17),
+            ((1, 8), 0)
+        ];
+        let ids_data = non_continuous_ids_data![("a", -9), ("b", -6), ("m", -3)];
+        let mut exec_scopes = ExecutionScopes::new();
+        assert_eq!(run_hint!(vm, ids_data, hint_code, &mut exec_scopes), Ok(()));
+        //Values computed by the python hint
+        let expected = bigint_str!("622628803989743062911736612");
+        check_scope!(
+            &exec_scopes,
+            [
+                ("product", bigint_str!("509258994083621521567111471467481259917007295185081674438437142785942159938828187669102592")),
+                ("m", bigint_str!("1315311291740716542081319491")),
+                ("value", expected.clone()),
+                ("res", expected)
+            ]
+        );
+    }
+
+    #[test]
+    fn ec_recover_product_div_m_ok() {
+        let mut exec_scopes = scope![
+            ("product", bigint_str!("509258994083621521567111471467481259917007295185081674438437142785942159938828187669102592")),
+            ("m", bigint_str!("1315311291740716542081319491"))
+        ];
+        assert_eq!(ec_recover_product_div_m(&mut exec_scopes), Ok(()));
+        //Value computed by the python hint
+        let expected =
+            bigint_str!("387177542899107304036169011811274291145729650703386059570593780");
+        check_scope!(&exec_scopes, [("value", expected.clone()), ("k", expected)]);
+    }
+
+    #[test]
+    fn ec_recover_product_div_m_missing_product() {
+        let mut exec_scopes = scope![("m", BigInt::from(100))];
+        assert_eq!(
+            ec_recover_product_div_m(&mut exec_scopes),
+            Err(HintError::VariableNotInScopeError("product".to_string()))
+        );
+    }
+
+    #[test]
+    fn ec_recover_hints_share_scope_variables() {
+        let mut vm = vm!();
+        vm.run_context.fp = 9;
+        //a = 60, b = 7, m = 100
+        vm.memory = memory![
+            ((1, 0), 60),
+            ((1, 1), 0),
+            ((1, 2), 0),
+            ((1, 3), 7),
+            ((1, 4), 0),
+            ((1, 5), 0),
+            ((1, 6), 100),
+            ((1, 7), 0),
+            ((1, 8), 0)
+        ];
+        let mut exec_scopes = ExecutionScopes::new();
+
+        //value = res = 60 - 7
+        let ids_data = non_continuous_ids_data![("a", -9), ("b", -6)];
+        assert_eq!(
+            run_hint!(
+                vm,
+                ids_data,
+                hint_code::EC_RECOVER_SUB_A_B,
+                &mut exec_scopes
+            ),
+            Ok(())
+        );
+        check_scope!(&exec_scopes, [("value", BigInt::from(53))]);
+
+        //product = 420, value = res = 420 % 100
+        let ids_data = non_continuous_ids_data![("a", -9), ("b", -6), ("m", -3)];
+        assert_eq!(
+            run_hint!(
+                vm,
+                ids_data,
+                hint_code::EC_RECOVER_PRODUCT_MOD,
+                &mut exec_scopes
+            ),
+            Ok(())
+        );
+        check_scope!(
+            &exec_scopes,
+            [("value", BigInt::from(20)), ("res", BigInt::from(20))]
+        );
+
+        //value = k = product // m, reading product and m from the previous hint
+        assert_eq!(
+            run_hint!(
+                vm,
+                HashMap::new(),
+                hint_code::EC_RECOVER_PRODUCT_DIV_M,
+                &mut exec_scopes
+            ),
+            Ok(())
+        );
+        check_scope!(
+            &exec_scopes,
+            [("value", BigInt::from(4)), ("k", BigInt::from(4))]
+        );
+
+        //value = res = div_mod(60, 7, 100) with n = m, x = a and s = b, overriding the previous ones
+        let ids_data = non_continuous_ids_data![("n", -3), ("x", -9), ("s", -6)];
+        assert_eq!(
+            run_hint!(
+                vm,
+                ids_data,
+                hint_code::EC_RECOVER_DIV_MOD_N_PACKED,
+                &mut exec_scopes
+            ),
+            Ok(())
+        );
+        check_scope!(
+            &exec_scopes,
+            [
+                ("value", BigInt::from(80)),
+                ("res", BigInt::from(80)),
+                ("k", BigInt::from(4))
+            ]
+        );
+    }
+}
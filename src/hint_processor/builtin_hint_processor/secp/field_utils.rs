@@ -12,7 +12,7 @@ use crate::{
     types::exec_scope::ExecutionScopes,
     vm::{errors::hint_errors::HintError, vm_core::VirtualMachine},
 };
-use felt::{Felt, FeltOps, NewFelt};
+use felt::{Felt, FeltOps};
 use num_bigint::BigInt;
 use num_integer::Integer;
 use num_traits::{One, Zero};
@@ -46,7 +46,7 @@ pub fn verify_zero(
         return Err(HintError::SecpVerifyZero(val));
     }
 
-    insert_value_from_var_name("q", Felt::new(q), vm, ids_data, ap_tracking)
+    insert_value_from_var_name("q", q, vm, ids_data, ap_tracking)
 }
 
 /*
@@ -157,7 +157,7 @@ pub fn is_zero_assign_scope_variables(
 mod tests {
     use super::*;
     use crate::{
-        any_box,
+        any_box, cloneable_box,
         hint_processor::{
             builtin_hint_processor::builtin_hint_processor_definition::{
                 BuiltinHintProcessor, HintProcessorData,
@@ -445,6 +445,7 @@ mod tests {
     fn run_is_zero_pack_error() {
         let hint_code = "from starkware.cairo.common.cairo_secp.secp_utils import SECP_P, pack\n\nx = pack(ids.x, PRIME) % SECP_P";
         let mut vm = vm_with_range_check!();
+        add_segments!(vm, 2);
 
         //Initialize fp
         vm.run_context.fp = 15;
@@ -494,7 +495,7 @@ mod tests {
 
         let mut exec_scopes = ExecutionScopes::new();
         //Initialize vm scope with variable `x`
-        exec_scopes.assign_or_update_variable("x", any_box!(BigInt::zero()));
+        exec_scopes.assign_or_update_variable("x", cloneable_box!(BigInt::zero()));
         //Create hint data
         //Execute the hint
         assert_eq!(
@@ -520,7 +521,7 @@ mod tests {
 
         //Initialize vm scope with variable `x`
         let mut exec_scopes = ExecutionScopes::new();
-        exec_scopes.assign_or_update_variable("x", any_box!(bigint!(123890i32)));
+        exec_scopes.assign_or_update_variable("x", cloneable_box!(bigint!(123890i32)));
 
         //Execute the hint
         assert_eq!(
@@ -566,7 +567,7 @@ mod tests {
 
         //Initialize vm scope with variable `x`
         let mut exec_scopes = ExecutionScopes::new();
-        exec_scopes.assign_or_update_variable("x", any_box!(BigInt::zero()));
+        exec_scopes.assign_or_update_variable("x", cloneable_box!(BigInt::zero()));
         //Execute the hint
         assert_eq!(
             run_hint!(vm, HashMap::new(), hint_code, &mut exec_scopes),
@@ -589,7 +590,7 @@ mod tests {
         let mut exec_scopes = ExecutionScopes::new();
         exec_scopes.assign_or_update_variable(
             "x",
-            any_box!(bigint_str!(
+            cloneable_box!(bigint_str!(
                 "52621538839140286024584685587354966255185961783273479086367"
             )),
         );
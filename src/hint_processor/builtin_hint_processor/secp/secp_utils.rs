@@ -8,8 +8,8 @@ use crate::{
     vm::{errors::hint_errors::HintError, vm_core::VirtualMachine},
 };
 use felt::{Felt, FeltOps};
-use num_bigint::BigInt;
-use num_traits::Zero;
+use num_bigint::{BigInt, BigUint};
+use num_traits::{One, Zero};
 use std::collections::HashMap;
 use std::ops::Shl;
 
@@ -25,48 +25,85 @@ pub const P2: &str = "starkware.cairo.common.cairo_secp.constants.P2";
 pub const SECP_REM: &str = "starkware.cairo.common.cairo_secp.constants.SECP_REM";
 
 /*
-Takes a 256-bit integer and returns its canonical representation as:
-d0 + BASE * d1 + BASE**2 * d2,
-where BASE = 2**86.
+Takes an integer and returns its canonical representation as n_limbs limbs:
+d0 + BASE * d1 + ... + BASE**(n_limbs - 1) * d(n_limbs - 1),
+where BASE = 2**limb_bits.
 */
-pub fn split(
-    integer: &num_bigint::BigUint,
-    constants: &HashMap<String, Felt>,
-) -> Result<[num_bigint::BigUint; 3], HintError> {
-    let base_86_max = constants
-        .get(BASE_86)
-        .ok_or(HintError::MissingConstant(BASE_86))?
-        .to_biguint()
-        - 1_u32;
+pub fn split_limbs(
+    integer: &BigUint,
+    n_limbs: usize,
+    limb_bits: usize,
+) -> Result<Vec<BigUint>, HintError> {
+    let limb_max = (BigUint::one() << limb_bits) - 1_u32;
 
-    let mut canonical_repr: [num_bigint::BigUint; 3] = Default::default();
+    let mut limbs = Vec::with_capacity(n_limbs);
     let mut num = integer.clone();
-    for item in &mut canonical_repr {
-        *item = &num & &base_86_max;
-        num >>= 86_usize;
+    for _ in 0..n_limbs {
+        limbs.push(&num & &limb_max);
+        num >>= limb_bits;
     }
 
     if !num.is_zero() {
         return Err(HintError::SecpSplitOutOfRange(integer.clone()));
     }
-    Ok(canonical_repr)
+    Ok(limbs)
 }
 
 /*
-Takes an UnreducedFelt3 struct which represents a triple of limbs (d0, d1, d2) of field
-elements and reconstructs the corresponding 256-bit integer (see split()).
+Takes limbs (d0, d1, ...) of field elements and reconstructs the corresponding integer
+d0 + BASE * d1 + ..., where BASE = 2**limb_bits (see split_limbs()).
 Note that the limbs do not have to be in the range [0, BASE).
 */
-pub fn pack(d0: &Felt, d1: &Felt, d2: &Felt) -> num_bigint::BigInt {
-    let unreduced_big_int_3 = vec![d0, d1, d2];
-
-    unreduced_big_int_3
+pub fn pack_limbs<'a>(limbs: impl IntoIterator<Item = &'a Felt>, limb_bits: usize) -> BigInt {
+    limbs
         .into_iter()
         .enumerate()
-        .map(|(idx, value)| value.to_bigint().shl(idx * 86))
+        .map(|(idx, value)| value.to_bigint().shl(idx * limb_bits))
         .sum()
 }
 
+///Reads n_limbs consecutive limbs starting at rel and packs them (see pack_limbs())
+pub fn pack_limbs_from_relocatable(
+    rel: Relocatable,
+    n_limbs: usize,
+    limb_bits: usize,
+    vm: &VirtualMachine,
+) -> Result<BigInt, HintError> {
+    let limbs = (0..n_limbs)
+        .map(|i| vm.get_integer(&(rel + i)))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(pack_limbs(limbs.iter().map(AsRef::as_ref), limb_bits))
+}
+
+/*
+Takes a 256-bit integer and returns its canonical representation as:
+d0 + BASE * d1 + BASE**2 * d2,
+where BASE = 2**86.
+*/
+pub fn split(
+    integer: &BigUint,
+    constants: &HashMap<String, Felt>,
+) -> Result<[BigUint; 3], HintError> {
+    let base_bits = constants
+        .get(BASE_86)
+        .ok_or(HintError::MissingConstant(BASE_86))?
+        .bits()
+        .saturating_sub(1);
+
+    split_limbs(integer, 3, base_bits as usize)?
+        .try_into()
+        .map_err(|_| HintError::SecpSplitOutOfRange(integer.clone()))
+}
+
+/*
+Takes an UnreducedFelt3 struct which represents a triple of limbs (d0, d1, d2) of field
+elements and reconstructs the corresponding 256-bit integer (see split()).
+Note that the limbs do not have to be in the range [0, BASE).
+*/
+pub fn pack(d0: &Felt, d1: &Felt, d2: &Felt) -> BigInt {
+    pack_limbs([d0, d1, d2], 86)
+}
+
 pub fn pack_from_var_name(
     name: &str,
     vm: &VirtualMachine,
@@ -74,19 +111,11 @@ pub fn pack_from_var_name(
     ap_tracking: &ApTracking,
 ) -> Result<BigInt, HintError> {
     let to_pack = get_relocatable_from_var_name(name, vm, ids_data, ap_tracking)?;
-
-    let d0 = vm.get_integer(&to_pack)?;
-    let d1 = vm.get_integer(&(&to_pack + 1_usize))?;
-    let d2 = vm.get_integer(&(&to_pack + 2_usize))?;
-    Ok(pack(d0.as_ref(), d1.as_ref(), d2.as_ref()))
+    pack_from_relocatable(to_pack, vm)
 }
 
 pub fn pack_from_relocatable(rel: Relocatable, vm: &VirtualMachine) -> Result<BigInt, HintError> {
-    let d0 = vm.get_integer(&rel)?;
-    let d1 = vm.get_integer(&(&rel + 1_usize))?;
-    let d2 = vm.get_integer(&(&rel + 2_usize))?;
-
-    Ok(pack(d0.as_ref(), d1.as_ref(), d2.as_ref()))
+    pack_limbs_from_relocatable(rel, 3, 86, vm)
 }
 
 #[cfg(test)]
@@ -95,7 +124,7 @@ mod tests {
     use crate::utils::test_utils::*;
     use felt::{felt_str, NewFelt};
     use num_bigint::BigUint;
-    use num_traits::One;
+    use num_traits::{Num, One};
 
     #[test]
     fn secp_split() {
@@ -183,4 +212,49 @@ mod tests {
             bigint_str!("7737125245533626718119526477371252455336267181195264773712524553362")
         );
     }
+
+    #[test]
+    fn split_limbs_round_trip_255_bit_prime() {
+        //2**255 - 19
+        let prime = BigUint::from_str_radix(
+            "57896044618658097711785492504343953926634992332820282019728792003956564819949",
+            10,
+        )
+        .unwrap();
+        for value in [
+            BigUint::zero(),
+            BigUint::from(987654321_u32),
+            &prime - 1_u32,
+            prime.clone(),
+        ] {
+            let limbs = split_limbs(&value, 4, 64).unwrap();
+            assert_eq!(limbs.len(), 4);
+            assert!(limbs.iter().all(|limb| limb.bits() <= 64));
+            let felts: Vec<Felt> = limbs.into_iter().map(Felt::new).collect();
+            assert_eq!(pack_limbs(&felts, 64), BigInt::from(value));
+        }
+    }
+
+    #[test]
+    fn split_limbs_4_limbs() {
+        let value = (BigUint::one() << 254_usize) + 987654321_u32;
+        assert_eq!(
+            split_limbs(&value, 4, 64),
+            Ok(vec![
+                BigUint::from(987654321_u32),
+                BigUint::zero(),
+                BigUint::zero(),
+                BigUint::from(4611686018427387904_u64)
+            ])
+        );
+    }
+
+    #[test]
+    fn split_limbs_out_of_range() {
+        let value = BigUint::one() << 256_usize;
+        assert_eq!(
+            split_limbs(&value, 4, 64),
+            Err(HintError::SecpSplitOutOfRange(value))
+        );
+    }
 }
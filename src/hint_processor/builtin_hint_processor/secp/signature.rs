@@ -6,7 +6,7 @@ use crate::{
         },
         hint_processor_definition::HintReference,
     },
-    math_utils::{div_mod, safe_div_bigint},
+    math_utils::{div_mod, pow_mod, safe_div_bigint},
     serde::deserialize_program::ApTracking,
     types::exec_scope::ExecutionScopes,
     vm::errors::hint_errors::HintError,
@@ -127,7 +127,7 @@ pub fn get_point_from_x(
     //.to_biguint().ok_or(VirtualMachineError::BigIntToBigUintFail)?;
     let y_cube_int = (x_cube_int + beta).mod_floor(&secp_p);
     // Divide by 4
-    let mut y = y_cube_int.modpow(&(&secp_p + 1_u32).shr(2_u32), &secp_p);
+    let mut y = pow_mod(&y_cube_int, &(&secp_p + 1_u32).shr(2_u32), &secp_p)?;
 
     let v = get_integer_from_var_name("v", vm, ids_data, ap_tracking)?.to_biguint();
     if v.is_even() != y.is_even() {
@@ -6,9 +6,8 @@ use crate::{
         hint_processor_utils::felt_to_u32,
     },
     serde::deserialize_program::ApTracking,
-    types::relocatable::MaybeRelocatable,
     vm::errors::{hint_errors::HintError, vm_errors::VirtualMachineError},
-    vm::vm_core::VirtualMachine,
+    vm::{vm_core::VirtualMachine, vm_memory::memory_segments::PackingMode},
 };
 use felt::{Felt, NewFelt};
 use generic_array::GenericArray;
@@ -65,15 +64,11 @@ pub fn sha256_main(
     let new_message = GenericArray::clone_from_slice(&message);
     compress256(&mut iv, &[new_message]);
 
-    let mut output: Vec<MaybeRelocatable> = Vec::with_capacity(SHA256_STATE_SIZE_FELTS);
-
-    for new_state in iv {
-        output.push(Felt::new(new_state).into());
-    }
+    let output: Vec<u8> = iv.iter().flat_map(|word| word.to_be_bytes()).collect();
 
     let output_base = get_ptr_from_var_name("output", vm, ids_data, ap_tracking)?;
 
-    vm.write_arg(&output_base, &output)
+    vm.write_bytes(&output_base, &output, PackingMode::BigEndianU32)
         .map_err(VirtualMachineError::MemoryError)?;
     Ok(())
 }
@@ -83,33 +78,28 @@ pub fn sha256_finalize(
     ids_data: &HashMap<String, HintReference>,
     ap_tracking: &ApTracking,
 ) -> Result<(), HintError> {
-    let message: Vec<u8> = vec![0; 64];
+    let message: Vec<u8> = vec![0; 4 * SHA256_INPUT_CHUNK_SIZE_FELTS];
 
     let mut iv = IV;
 
-    let iv_static: Vec<MaybeRelocatable> = iv.iter().map(|n| Felt::new(*n).into()).collect();
+    let iv_static: Vec<u8> = iv.iter().flat_map(|word| word.to_be_bytes()).collect();
 
     let new_message = GenericArray::clone_from_slice(&message);
     compress256(&mut iv, &[new_message]);
 
-    let mut output: Vec<MaybeRelocatable> = Vec::with_capacity(SHA256_STATE_SIZE_FELTS);
-
-    for new_state in iv {
-        output.push(Felt::new(new_state).into());
-    }
+    let output: Vec<u8> = iv.iter().flat_map(|word| word.to_be_bytes()).collect();
 
     let sha256_ptr_end = get_ptr_from_var_name("sha256_ptr_end", vm, ids_data, ap_tracking)?;
 
-    let mut padding: Vec<MaybeRelocatable> = Vec::new();
-    let zero_vector_message: Vec<MaybeRelocatable> = vec![Felt::zero().into(); 16];
+    let mut padding: Vec<u8> = Vec::new();
 
     for _ in 0..BLOCK_SIZE - 1 {
-        padding.extend_from_slice(zero_vector_message.as_slice());
-        padding.extend_from_slice(iv_static.as_slice());
-        padding.extend_from_slice(output.as_slice());
+        padding.extend_from_slice(&message);
+        padding.extend_from_slice(&iv_static);
+        padding.extend_from_slice(&output);
     }
 
-    vm.write_arg(&sha256_ptr_end, &padding)
+    vm.write_bytes(&sha256_ptr_end, &padding, PackingMode::BigEndianU32)
         .map_err(VirtualMachineError::MemoryError)?;
     Ok(())
 }
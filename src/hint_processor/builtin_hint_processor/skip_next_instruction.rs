@@ -0,0 +1,61 @@
+use crate::vm::{errors::hint_errors::HintError, vm_core::VirtualMachine};
+
+/*
+This hint doesn't belong to the Cairo common library
+It's used by testing frameworks to skip instructions that are expected to fail
+
+Implements hint:
+%{ skip_next_instruction() %}
+*/
+pub fn skip_next_instruction(vm: &mut VirtualMachine) -> Result<(), HintError> {
+    vm.skip_next_instruction_execution();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        hint_processor::builtin_hint_processor::builtin_hint_processor_definition::BuiltinHintProcessor,
+        types::program::Program,
+        utils::test_utils::*,
+        vm::{
+            errors::vm_errors::VirtualMachineError,
+            runners::cairo_runner::{CairoRunner, RunOutcome},
+            vm_core::VirtualMachine,
+        },
+    };
+    use std::path::Path;
+
+    fn run_program(program: &Program) -> Result<RunOutcome, VirtualMachineError> {
+        let mut cairo_runner = cairo_runner!(program);
+        let mut vm = vm!();
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        let end = cairo_runner.initialize(&mut vm).unwrap();
+        cairo_runner.run_until_pc(end, &mut vm, &mut hint_processor)
+    }
+
+    #[test]
+    fn skipped_failing_assert_runs_to_completion() {
+        //[ap] = 1, ap++; %{ skip_next_instruction() %} [ap - 1] = 2; ret
+        let program = Program::from_file(
+            Path::new("cairo_programs/manually_compiled/skip_next_instruction.json"),
+            Some("main"),
+        )
+        .unwrap();
+        assert!(run_program(&program).is_ok());
+    }
+
+    #[test]
+    fn failing_assert_fails_without_hint() {
+        let mut program = Program::from_file(
+            Path::new("cairo_programs/manually_compiled/skip_next_instruction.json"),
+            Some("main"),
+        )
+        .unwrap();
+        program.hints.clear();
+        assert!(matches!(
+            run_program(&program),
+            Err(VirtualMachineError::DiffAssertValues(_, _))
+        ));
+    }
+}
@@ -4,7 +4,7 @@ use crate::{
             dict_hint_utils::DICT_ACCESS_SIZE,
             hint_utils::{
                 get_integer_from_var_name, get_ptr_from_var_name, get_relocatable_from_var_name,
-                insert_value_from_var_name,
+                guard_le, insert_value_from_var_name,
             },
         },
         hint_processor_definition::HintReference,
@@ -19,17 +19,20 @@ use crate::{
 use felt::{Felt, NewFelt};
 use num_integer::Integer;
 use num_traits::{One, ToPrimitive, Zero};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
-fn get_access_indices(
-    exec_scopes: &mut ExecutionScopes,
-) -> Result<&HashMap<Felt, Vec<Felt>>, HintError> {
-    let mut access_indices: Option<&HashMap<Felt, Vec<Felt>>> = None;
+pub const SQUASH_DICT_MAX_SIZE: &str = "__squash_dict_max_size";
+
+///Indices of the accesses to each key, keys are kept in ascending order so the hints don't need to sort them
+pub type AccessIndices = BTreeMap<Felt, Vec<usize>>;
+
+fn get_access_indices(exec_scopes: &mut ExecutionScopes) -> Result<&AccessIndices, HintError> {
+    let mut access_indices: Option<&AccessIndices> = None;
     if let Some(variable) = exec_scopes
         .get_local_variables_mut()?
         .get_mut("access_indices")
     {
-        if let Some(py_access_indices) = variable.downcast_mut::<HashMap<Felt, Vec<Felt>>>() {
+        if let Some(py_access_indices) = variable.downcast_mut::<AccessIndices>() {
             access_indices = Some(py_access_indices);
         }
     }
@@ -56,17 +59,16 @@ pub fn squash_dict_inner_first_iteration(
         .get(&key)
         .ok_or_else(|| HintError::NoKeyInAccessIndices(key.clone()))?
         .clone();
-    current_access_indices.sort();
-    current_access_indices.reverse();
+    current_access_indices.sort_unstable_by(|a, b| b.cmp(a));
     //Get current_access_index
     let first_val = current_access_indices
         .pop()
         .ok_or(HintError::EmptyCurrentAccessIndices)?;
     //Store variables in scope
     exec_scopes.insert_value("current_access_indices", current_access_indices);
-    exec_scopes.insert_value("current_access_index", first_val.clone());
+    exec_scopes.insert_value("current_access_index", first_val);
     //Insert current_accesss_index into range_check_ptr
-    vm.insert_value(&range_check_ptr, first_val)
+    vm.insert_value(&range_check_ptr, Felt::new(first_val))
         .map_err(HintError::Internal)
 }
 
@@ -78,7 +80,7 @@ pub fn squash_dict_inner_skip_loop(
     ap_tracking: &ApTracking,
 ) -> Result<(), HintError> {
     //Check that current_access_indices is in scope
-    let current_access_indices = exec_scopes.get_list_ref::<Felt>("current_access_indices")?;
+    let current_access_indices = exec_scopes.get_list_ref::<usize>("current_access_indices")?;
     //Main Logic
     let should_skip_loop = if current_access_indices.is_empty() {
         Felt::one()
@@ -106,18 +108,19 @@ pub fn squash_dict_inner_check_access_index(
     ap_tracking: &ApTracking,
 ) -> Result<(), HintError> {
     //Check that current_access_indices and current_access_index are in scope
-    let current_access_index = exec_scopes.get::<Felt>("current_access_index")?;
-    let current_access_indices = exec_scopes.get_mut_list_ref::<Felt>("current_access_indices")?;
+    let current_access_index = exec_scopes.get::<usize>("current_access_index")?;
+    let current_access_indices = exec_scopes.get_mut_list_ref::<usize>("current_access_indices")?;
     //Main Logic
     let new_access_index = current_access_indices
         .pop()
         .ok_or(HintError::EmptyCurrentAccessIndices)?;
-    let index_delta_minus1 = new_access_index.clone() - current_access_index - Felt::one();
+    let index_delta_minus1 =
+        Felt::new(new_access_index) - Felt::new(current_access_index) - Felt::one();
     //loop_temps.delta_minus1 = loop_temps + 0 as it is the first field of the struct
     //Insert loop_temps.delta_minus1 into memory
     insert_value_from_var_name("loop_temps", index_delta_minus1, vm, ids_data, ap_tracking)?;
-    exec_scopes.insert_value("new_access_index", new_access_index.clone());
-    exec_scopes.insert_value("current_access_index", new_access_index);
+    exec_scopes.insert_value("new_access_index", new_access_index);
+    *exec_scopes.get_mut_ref::<usize>("current_access_index")? = new_access_index;
     Ok(())
 }
 
@@ -132,7 +135,7 @@ pub fn squash_dict_inner_continue_loop(
     //Get addr for ids variables
     let loop_temps_addr = get_relocatable_from_var_name("loop_temps", vm, ids_data, ap_tracking)?;
     //Check that current_access_indices is in scope
-    let current_access_indices = exec_scopes.get_list_ref::<Felt>("current_access_indices")?;
+    let current_access_indices = exec_scopes.get_list_ref::<usize>("current_access_indices")?;
     //Main Logic
     let should_continue = if current_access_indices.is_empty() {
         Felt::zero()
@@ -149,7 +152,7 @@ pub fn squash_dict_inner_continue_loop(
 // Implements Hint: assert len(current_access_indices) == 0
 pub fn squash_dict_inner_len_assert(exec_scopes: &mut ExecutionScopes) -> Result<(), HintError> {
     //Check that current_access_indices is in scope
-    let current_access_indices = exec_scopes.get_list_ref::<Felt>("current_access_indices")?;
+    let current_access_indices = exec_scopes.get_list_ref::<usize>("current_access_indices")?;
     if !current_access_indices.is_empty() {
         return Err(HintError::CurrentAccessIndicesNotEmpty);
     }
@@ -238,6 +241,7 @@ pub fn squash_dict(
     exec_scopes: &mut ExecutionScopes,
     ids_data: &HashMap<String, HintReference>,
     ap_tracking: &ApTracking,
+    limits: &HashMap<String, Felt>,
 ) -> Result<(), HintError> {
     //Get necessary variables addresses from ids
     let address = get_ptr_from_var_name("dict_accesses", vm, ids_data, ap_tracking)?;
@@ -250,36 +254,23 @@ pub fn squash_dict(
     if ptr_diff.mod_floor(&Felt::new(DICT_ACCESS_SIZE)) != Felt::zero() {
         return Err(HintError::PtrDiffNotDivisibleByDictAccessSize);
     }
-    let squash_dict_max_size = exec_scopes.get::<Felt>("__squash_dict_max_size");
-    if let Ok(max_size) = squash_dict_max_size {
-        if n_accesses.as_ref() > &max_size {
-            return Err(HintError::SquashDictMaxSizeExceeded(
-                max_size,
-                n_accesses.into_owned(),
-            ));
-        };
-    };
+    guard_le(&n_accesses, SQUASH_DICT_MAX_SIZE, exec_scopes, limits)?;
     let n_accesses_usize = n_accesses
         .to_usize()
         .ok_or_else(|| HintError::NAccessesTooBig(n_accesses.into_owned()))?;
-    //A map from key to the list of indices accessing it.
-    let mut access_indices = HashMap::<Felt, Vec<Felt>>::new();
+    //A map from key to the list of indices accessing it, built in a single pass.
+    let mut access_indices = AccessIndices::new();
     for i in 0..n_accesses_usize {
         let key_addr = address + DICT_ACCESS_SIZE * i;
         let key = vm
             .get_integer(&key_addr)
             .map_err(|_| VirtualMachineError::ExpectedInteger(MaybeRelocatable::from(key_addr)))?;
-        access_indices
-            .entry(key.into_owned())
-            .or_default()
-            .push(Felt::new(i));
+        access_indices.entry(key.into_owned()).or_default().push(i);
     }
-    //Descending list of keys.
-    let mut keys: Vec<Felt> = access_indices.keys().cloned().collect();
-    keys.sort();
-    keys.reverse();
+    //Descending list of keys, popped from the back in ascending order.
+    let mut keys: Vec<Felt> = access_indices.keys().rev().cloned().collect();
     //Are the keys used bigger than the range_check bound.
-    let big_keys = if keys[0] >= range_check_bound.unwrap() {
+    let big_keys = if keys.first().ok_or(HintError::EmptyKeys)? >= &range_check_bound.unwrap() {
         Felt::one()
     } else {
         Felt::zero()
@@ -332,9 +323,8 @@ mod tests {
     fn squash_dict_inner_first_iteration_valid() {
         let hint_code = SQUASH_DICT_INNER_FIRST_ITERATION;
         //Prepare scope variables
-        let mut access_indices = HashMap::<Felt, Vec<Felt>>::new();
-        let current_accessed_indices =
-            vec![Felt::new(9), Felt::new(3), Felt::new(10), Felt::new(7)];
+        let mut access_indices = AccessIndices::new();
+        let current_accessed_indices = vec![9, 3, 10, 7];
         access_indices.insert(Felt::new(5), current_accessed_indices);
         //Create vm
         let mut vm = vm!();
@@ -353,11 +343,8 @@ mod tests {
         check_scope!(
             &exec_scopes,
             [
-                (
-                    "current_access_indices",
-                    vec![Felt::new(10), Felt::new(9), Felt::new(7)]
-                ),
-                ("current_access_index", Felt::new(3))
+                ("current_access_indices", vec![10_usize, 9, 7]),
+                ("current_access_index", 3_usize)
             ]
         );
         //Check that current_access_index is now at range_check_ptr
@@ -368,9 +355,9 @@ mod tests {
     fn squash_dict_inner_first_iteration_empty_accessed_indices() {
         let hint_code = SQUASH_DICT_INNER_FIRST_ITERATION;
         //Prepare scope variables
-        let mut access_indices = HashMap::<Felt, Vec<Felt>>::new();
+        let mut access_indices = AccessIndices::new();
         //Leave current_accessed_indices empty
-        let current_accessed_indices = Vec::<Felt>::new();
+        let current_accessed_indices = Vec::<usize>::new();
         access_indices.insert(Felt::new(5), current_accessed_indices);
         //Create vm
         let mut vm = vm!();
@@ -415,7 +402,7 @@ mod tests {
         let mut vm = vm!();
         add_segments!(vm, 2);
         //Store scope variables
-        let mut exec_scopes = scope![("current_access_indices", Vec::<Felt>::new())];
+        let mut exec_scopes = scope![("current_access_indices", Vec::<usize>::new())];
         //Initialize fp
         vm.run_context.fp = 1;
         //Create ids_data
@@ -433,7 +420,7 @@ mod tests {
         let mut vm = vm!();
         add_segments!(vm, 2);
         //Store scope variables
-        let mut exec_scopes = scope![("current_access_indices", vec![Felt::new(4), Felt::new(7)])];
+        let mut exec_scopes = scope![("current_access_indices", vec![4_usize, 7])];
         //Initialize fp
         vm.run_context.fp = 1;
         //Create ids_data
@@ -452,11 +439,8 @@ mod tests {
         add_segments!(vm, 2);
         //Store scope variables
         let mut exec_scopes = scope![
-            (
-                "current_access_indices",
-                vec![Felt::new(10), Felt::new(9), Felt::new(7), Felt::new(5)]
-            ),
-            ("current_access_index", Felt::one())
+            ("current_access_indices", vec![10_usize, 9, 7, 5]),
+            ("current_access_index", 1_usize)
         ];
         //Initialize fp
         vm.run_context.fp = 1;
@@ -468,12 +452,9 @@ mod tests {
         check_scope!(
             &exec_scopes,
             [
-                (
-                    "current_access_indices",
-                    vec![Felt::new(10), Felt::new(9), Felt::new(7)]
-                ),
-                ("new_access_index", Felt::new(5)),
-                ("current_access_index", Felt::new(5))
+                ("current_access_indices", vec![10_usize, 9, 7]),
+                ("new_access_index", 5_usize),
+                ("current_access_index", 5_usize)
             ]
         );
         //Check the value of loop_temps.index_delta_minus_1
@@ -489,8 +470,8 @@ mod tests {
         let mut vm = vm!();
         //Store scope variables
         let mut exec_scopes = scope![
-            ("current_access_indices", Vec::<Felt>::new()),
-            ("current_access_index", Felt::one())
+            ("current_access_indices", Vec::<usize>::new()),
+            ("current_access_index", 1_usize)
         ];
         //Initialize fp
         vm.run_context.fp = 1;
@@ -512,7 +493,7 @@ mod tests {
         let mut vm = vm!();
         add_segments!(vm, 2);
         //Store scope variables
-        let mut exec_scopes = scope![("current_access_indices", vec![Felt::new(4), Felt::new(7)])];
+        let mut exec_scopes = scope![("current_access_indices", vec![4_usize, 7])];
         //Initialize fp
         vm.run_context.fp = 1;
         //Create ids_data
@@ -530,7 +511,7 @@ mod tests {
         let mut vm = vm!();
         add_segments!(vm, 2);
         //Store scope variables
-        let mut exec_scopes = scope![("current_access_indices", Vec::<Felt>::new())];
+        let mut exec_scopes = scope![("current_access_indices", Vec::<usize>::new())];
         //Initialize fp
         vm.run_context.fp = 1;
         //Create ids_data
@@ -547,7 +528,7 @@ mod tests {
         //Create vm
         let mut vm = vm!();
         //Store scope variables
-        let mut exec_scopes = scope![("current_access_indices", Vec::<Felt>::new())];
+        let mut exec_scopes = scope![("current_access_indices", Vec::<usize>::new())];
         //Execute the hint
         //Hint should produce an error if assertion fails
         assert_eq!(
@@ -562,7 +543,7 @@ mod tests {
         //Create vm
         let mut vm = vm!();
         //Store scope variables
-        let mut exec_scopes = scope![("current_access_indices", vec![Felt::new(29)])];
+        let mut exec_scopes = scope![("current_access_indices", vec![29_usize])];
         //Execute the hint
         //Hint should produce an error if assertion fails
         assert_eq!(
@@ -575,9 +556,8 @@ mod tests {
     fn squash_dict_inner_uses_accesses_assert_valid() {
         let hint_code = SQUASH_DICT_INNER_USED_ACCESSES_ASSERT;
         //Prepare scope variables
-        let mut access_indices = HashMap::<Felt, Vec<Felt>>::new();
-        let current_accessed_indices =
-            vec![Felt::new(9), Felt::new(3), Felt::new(10), Felt::new(7)];
+        let mut access_indices = AccessIndices::new();
+        let current_accessed_indices = vec![9, 3, 10, 7];
         access_indices.insert(Felt::new(5), current_accessed_indices);
         //Create vm
         let mut vm = vm!();
@@ -598,9 +578,8 @@ mod tests {
     fn squash_dict_inner_uses_accesses_assert_wrong_used_access_number() {
         let hint_code = SQUASH_DICT_INNER_USED_ACCESSES_ASSERT;
         //Prepare scope variables
-        let mut access_indices = HashMap::<Felt, Vec<Felt>>::new();
-        let current_accessed_indices =
-            vec![Felt::new(9), Felt::new(3), Felt::new(10), Felt::new(7)];
+        let mut access_indices = AccessIndices::new();
+        let current_accessed_indices = vec![9, 3, 10, 7];
         access_indices.insert(Felt::new(5), current_accessed_indices);
         //Create vm
         let mut vm = vm!();
@@ -627,9 +606,8 @@ mod tests {
     fn squash_dict_inner_uses_accesses_assert_used_access_number_relocatable() {
         let hint_code = SQUASH_DICT_INNER_USED_ACCESSES_ASSERT;
         //Prepare scope variables
-        let mut access_indices = HashMap::<Felt, Vec<Felt>>::new();
-        let current_accessed_indices =
-            vec![Felt::new(9), Felt::new(3), Felt::new(10), Felt::new(7)];
+        let mut access_indices = AccessIndices::new();
+        let current_accessed_indices = vec![9, 3, 10, 7];
         access_indices.insert(Felt::new(5), current_accessed_indices);
         //Create vm
         let mut vm = vm!();
@@ -731,6 +709,65 @@ mod tests {
         );
     }
 
+    #[test]
+    fn squash_dict_negative_and_prime_minus_one_keys() {
+        //Dict = {-1: (1,1), prime - 1: (1,2)}, both keys are the same felt
+        let hint_code = SQUASH_DICT;
+        let mut vm = vm_with_range_check!();
+        vm.run_context.fp = 5;
+        vm.memory = memory![
+            ((1, 0), (2, 0)),
+            ((1, 3), 6),
+            ((1, 4), 2),
+            ((2, 0), (-1)),
+            ((2, 1), 1),
+            ((2, 2), 1),
+            (
+                (2, 3),
+                (
+                    "3618502788666131213697322783095070105623107215331596699973092056135872020480",
+                    10
+                )
+            ),
+            ((2, 4), 1),
+            ((2, 5), 2)
+        ];
+        let ids_data = ids_data![
+            "dict_accesses",
+            "big_keys",
+            "first_key",
+            "ptr_diff",
+            "n_accesses"
+        ];
+        let mut exec_scopes = ExecutionScopes::new();
+        assert_eq!(run_hint!(vm, ids_data, hint_code, &mut exec_scopes), Ok(()));
+        let key = felt_str!(
+            "3618502788666131213697322783095070105623107215331596699973092056135872020480"
+        );
+        check_scope!(
+            &exec_scopes,
+            [
+                (
+                    "access_indices",
+                    AccessIndices::from([(key.clone(), vec![0, 1])])
+                ),
+                ("keys", Vec::<Felt>::new()),
+                ("key", key.clone())
+            ]
+        );
+        check_memory![
+            vm.memory,
+            ((1, 1), 1),
+            (
+                (1, 2),
+                (
+                    "3618502788666131213697322783095070105623107215331596699973092056135872020480",
+                    10
+                )
+            )
+        ];
+    }
+
     #[test]
     fn squash_dict_valid_one_key_dict_no_max_size() {
         //Dict = {1: (1,1), 1: (1,2)}
@@ -768,7 +805,7 @@ mod tests {
             [
                 (
                     "access_indices",
-                    HashMap::from([(Felt::one(), vec![Felt::zero(), Felt::one()])])
+                    AccessIndices::from([(Felt::one(), vec![0, 1])])
                 ),
                 ("keys", Vec::<Felt>::new()),
                 ("key", Felt::one())
@@ -821,10 +858,7 @@ mod tests {
             [
                 (
                     "access_indices",
-                    HashMap::from([
-                        (Felt::one(), vec![Felt::zero(), Felt::one()]),
-                        (Felt::new(2), vec![Felt::new(2), Felt::new(3)])
-                    ])
+                    AccessIndices::from([(Felt::one(), vec![0, 1]), (Felt::new(2), vec![2, 3])])
                 ),
                 ("keys", vec![Felt::new(2)]),
                 ("key", Felt::one())
@@ -836,6 +870,57 @@ mod tests {
         check_memory![vm.memory, ((1, 1), 0), ((1, 2), 1)];
     }
 
+    #[test]
+    fn squash_dict_unsorted_keys_matches_python() {
+        //Accesses to keys 5, 1, 5, 3, 1, in that order
+        let hint_code = SQUASH_DICT;
+        //Create vm
+        let mut vm = vm_with_range_check!();
+        //Initialize fp
+        vm.run_context.fp = 5;
+        //Insert ids into memory
+        vm.memory = memory![
+            ((1, 0), (2, 0)),
+            ((1, 3), 15),
+            ((1, 4), 5),
+            ((2, 0), 5),
+            ((2, 3), 1),
+            ((2, 6), 5),
+            ((2, 9), 3),
+            ((2, 12), 1)
+        ];
+        //Create hint_data
+        let ids_data = ids_data![
+            "dict_accesses",
+            "big_keys",
+            "first_key",
+            "ptr_diff",
+            "n_accesses"
+        ];
+        let mut exec_scopes = ExecutionScopes::new();
+        //Execute the hint
+        assert_eq!(run_hint!(vm, ids_data, hint_code, &mut exec_scopes), Ok(()));
+        //Check scope variables against the ones left by the python hint:
+        //access_indices = {5: [0, 2], 1: [1, 4], 3: [3]}, keys = [5, 3], key = 1
+        check_scope!(
+            &exec_scopes,
+            [
+                (
+                    "access_indices",
+                    AccessIndices::from([
+                        (Felt::new(5), vec![0, 2]),
+                        (Felt::one(), vec![1, 4]),
+                        (Felt::new(3), vec![3])
+                    ])
+                ),
+                ("keys", vec![Felt::new(5), Felt::new(3)]),
+                ("key", Felt::one())
+            ]
+        );
+        //Check ids variables
+        check_memory![vm.memory, ((1, 1), 0), ((1, 2), 1)];
+    }
+
     #[test]
     fn squash_dict_valid_one_key_dict_with_max_size() {
         //Dict = {1: (1,1), 1: (1,2)}
@@ -874,7 +959,7 @@ mod tests {
             [
                 (
                     "access_indices",
-                    HashMap::from([(Felt::one(), vec![Felt::zero(), Felt::one()])])
+                    AccessIndices::from([(Felt::one(), vec![0, 1])])
                 ),
                 ("keys", Vec::<Felt>::new()),
                 ("key", Felt::one())
@@ -917,9 +1002,10 @@ mod tests {
         //Execute the hint
         assert_eq!(
             run_hint!(vm, ids_data, hint_code, &mut exec_scopes),
-            Err(HintError::SquashDictMaxSizeExceeded(
+            Err(HintError::ValueExceedsLimit(
+                Felt::new(2),
                 Felt::one(),
-                Felt::new(2)
+                SQUASH_DICT_MAX_SIZE.to_string()
             ))
         );
     }
@@ -1045,9 +1131,9 @@ mod tests {
         //Execute the hint
         assert_eq!(run_hint!(vm, ids_data, hint_code, &mut exec_scopes), Ok(()));
         //Check scope variables
-        check_scope!(&exec_scopes, [("access_indices", HashMap::from([(
+        check_scope!(&exec_scopes, [("access_indices", AccessIndices::from([(
            felt_str!("3618502761706184546546682988428055018603476541694452277432519575032261771265"),
-            vec![Felt::zero(), Felt::one()]
+            vec![0, 1]
         )])), ("keys", Vec::<Felt>::new()), ("key", felt_str!("3618502761706184546546682988428055018603476541694452277432519575032261771265"))]);
         //Check ids variables
         check_memory![
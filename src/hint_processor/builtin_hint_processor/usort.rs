@@ -1,28 +1,30 @@
 use crate::{
     hint_processor::{
         builtin_hint_processor::hint_utils::{
-            get_integer_from_var_name, get_ptr_from_var_name, insert_value_from_var_name,
+            get_integer_from_var_name, get_ptr_from_var_name, guard_le, insert_value_from_var_name,
         },
         hint_processor_definition::HintReference,
     },
     serde::deserialize_program::ApTracking,
-    types::exec_scope::ExecutionScopes,
-    vm::{errors::hint_errors::HintError, vm_core::VirtualMachine},
+    types::{exec_scope::ExecutionScopes, relocatable::MaybeRelocatable},
+    vm::{
+        errors::{hint_errors::HintError, vm_errors::VirtualMachineError},
+        vm_core::VirtualMachine,
+    },
 };
 use felt::{Felt, NewFelt};
 use num_traits::{ToPrimitive, Zero};
-use std::{any::Any, collections::HashMap};
+use std::collections::HashMap;
+
+pub const USORT_MAX_SIZE: &str = "usort_max_size";
 
+//The limit on the input's length is carried over to the new scope, so usort_body can check it
 pub fn usort_enter_scope(exec_scopes: &mut ExecutionScopes) -> Result<(), HintError> {
-    if let Ok(usort_max_size) = exec_scopes.get::<Felt>("usort_max_size") {
-        let boxed_max_size: Box<dyn Any> = Box::new(usort_max_size);
-        exec_scopes.enter_scope(HashMap::from([(
-            "usort_max_size".to_string(),
-            boxed_max_size,
-        )]));
-    } else {
-        exec_scopes.enter_scope(HashMap::new());
-    }
+    let new_scope = match exec_scopes.get_any_boxed_ref(USORT_MAX_SIZE) {
+        Ok(usort_max_size) => HashMap::from([(USORT_MAX_SIZE.to_string(), usort_max_size.clone())]),
+        Err(_) => HashMap::new(),
+    };
+    exec_scopes.enter_scope(new_scope);
     Ok(())
 }
 
@@ -31,20 +33,12 @@ pub fn usort_body(
     exec_scopes: &mut ExecutionScopes,
     ids_data: &HashMap<String, HintReference>,
     ap_tracking: &ApTracking,
+    limits: &HashMap<String, Felt>,
 ) -> Result<(), HintError> {
     let input_ptr = get_ptr_from_var_name("input", vm, ids_data, ap_tracking)?;
-    let usort_max_size = exec_scopes.get::<u64>("usort_max_size");
     let input_len = get_integer_from_var_name("input_len", vm, ids_data, ap_tracking)?;
+    guard_le(&input_len, USORT_MAX_SIZE, exec_scopes, limits)?;
     let input_len_u64 = input_len.to_u64().ok_or(HintError::BigintToUsizeFail)?;
-
-    if let Ok(usort_max_size) = usort_max_size {
-        if input_len_u64 > usort_max_size {
-            return Err(HintError::UsortOutOfRange(
-                usort_max_size,
-                input_len.into_owned(),
-            ));
-        }
-    }
     let mut positions_dict: HashMap<Felt, Vec<u64>> = HashMap::new();
     let mut output: Vec<Felt> = Vec::new();
     for i in 0..input_len_u64 {
@@ -64,13 +58,15 @@ pub fn usort_body(
     let multiplicities_base = vm.add_memory_segment();
     let output_len = output.len();
 
-    for (i, sorted_element) in output.into_iter().enumerate() {
-        vm.insert_value(&(output_base + i), sorted_element)?;
-    }
-
-    for (i, repetition_amount) in multiplicities.into_iter().enumerate() {
-        vm.insert_value(&(multiplicities_base + i), Felt::new(repetition_amount))?;
-    }
+    let output = output.into_iter().map(MaybeRelocatable::from).collect();
+    vm.load_data(&output_base.into(), &output)
+        .map_err(VirtualMachineError::MemoryError)?;
+    let multiplicities = multiplicities
+        .into_iter()
+        .map(|repetition_amount| MaybeRelocatable::from(Felt::new(repetition_amount)))
+        .collect();
+    vm.load_data(&multiplicities_base.into(), &multiplicities)
+        .map_err(VirtualMachineError::MemoryError)?;
 
     insert_value_from_var_name(
         "output_len",
@@ -151,6 +147,8 @@ mod tests {
             vm_core::VirtualMachine, vm_memory::memory::Memory,
         },
     };
+    use num_traits::One;
+    use std::any::Any;
 
     #[test]
     fn usort_with_max_size() {
@@ -169,7 +167,11 @@ mod tests {
         let mut exec_scopes = scope![("usort_max_size", 1_u64)];
         assert_eq!(
             run_hint!(vm, ids_data, USORT_BODY, &mut exec_scopes),
-            Err(HintError::UsortOutOfRange(1, Felt::new(5_i32)))
+            Err(HintError::ValueExceedsLimit(
+                Felt::new(5_i32),
+                Felt::one(),
+                USORT_MAX_SIZE.to_string()
+            ))
         );
     }
 }
@@ -1,9 +1,11 @@
 use crate::any_box;
+use crate::hint_processor::hint_processor_utils::get_maybe_relocatable_from_reference;
 use crate::serde::deserialize_program::ApTracking;
 use crate::serde::deserialize_program::OffsetValue;
 use crate::serde::deserialize_program::Reference;
 use crate::types::exec_scope::ExecutionScopes;
 use crate::types::instruction::Register;
+use crate::types::relocatable::MaybeRelocatable;
 use crate::vm::errors::hint_errors::HintError;
 use crate::vm::errors::vm_errors::VirtualMachineError;
 use crate::vm::vm_core::VirtualMachine;
@@ -42,15 +44,67 @@ pub trait HintProcessor {
         //List of all references (key corresponds to element of the previous dictionary)
         references: &HashMap<usize, HintReference>,
     ) -> Result<Box<dyn Any>, VirtualMachineError> {
-        Ok(any_box!(HintProcessorData {
-            code: hint_code.to_string(),
-            ap_tracking: ap_tracking_data.clone(),
-            ids_data: get_ids_data(reference_ids, references)?,
-        }))
+        Ok(any_box!(HintProcessorData::new(
+            hint_code.to_string(),
+            ap_tracking_data.clone(),
+            get_ids_data(reference_ids, references)?,
+        )))
+    }
+
+    //Like compile_hint, but also receives the hash of the program the hint belongs to, when the
+    //runner computed it, the pc offset of the hint, so hints can be told apart by their
+    //location instead of their code, and the scopes the hint can access
+    #[allow(clippy::too_many_arguments)]
+    fn compile_hint_at(
+        &self,
+        //Hash of the program, see Program::compute_hash
+        _program_hash: Option<&Felt>,
+        //Offset of the instruction the hint runs before
+        _pc_offset: usize,
+        //Scopes accessible from the hint, from the outermost to the innermost
+        _accessible_scopes: &[String],
+        hint_code: &str,
+        ap_tracking_data: &ApTracking,
+        reference_ids: &HashMap<String, usize>,
+        references: &HashMap<usize, HintReference>,
+    ) -> Result<Box<dyn Any>, VirtualMachineError> {
+        self.compile_hint(hint_code, ap_tracking_data, reference_ids, references)
+    }
+
+    //Returns the code of the hint compiled into hint_data, or None if the processor doesn't keep it.
+    //Used to describe a failing hint and to group the hint statistics
+    fn get_hint_code<'a>(&self, hint_data: &'a Box<dyn Any>) -> Option<&'a str> {
+        hint_data
+            .downcast_ref::<HintProcessorData>()
+            .map(|hint_data| hint_data.code.as_str())
+    }
+
+    //Returns the current values of the ids accessible from the hint compiled into hint_data,
+    //sorted by name. Ids which can't be resolved are left out
+    fn get_ids_values(
+        &self,
+        vm: &VirtualMachine,
+        hint_data: &Box<dyn Any>,
+    ) -> Vec<(String, MaybeRelocatable)> {
+        let hint_data = match hint_data.downcast_ref::<HintProcessorData>() {
+            Some(hint_data) => hint_data,
+            None => return Vec::new(),
+        };
+        let mut ids_values: Vec<(String, MaybeRelocatable)> = hint_data
+            .ids_data
+            .iter()
+            .filter_map(|(name, reference)| {
+                get_maybe_relocatable_from_reference(vm, reference, &hint_data.ap_tracking)
+                    .ok()
+                    .map(|value| (name.clone(), value))
+            })
+            .collect();
+        ids_values.sort_by(|a, b| a.0.cmp(&b.0));
+        ids_values
     }
 }
 
-fn get_ids_data(
+pub(crate) fn get_ids_data(
     reference_ids: &HashMap<String, usize>,
     references: &HashMap<usize, HintReference>,
 ) -> Result<HashMap<String, HintReference>, VirtualMachineError> {
@@ -316,6 +316,7 @@ mod tests {
     fn get_maybe_relocatable_from_reference_invalid() {
         let mut vm = vm!();
         vm.memory = Memory::new();
+        add_segments!(vm, 2);
         let hint_ref = HintReference::new_simple(0);
         assert_eq!(
             get_maybe_relocatable_from_reference(&vm, &hint_ref, &ApTracking::new()),
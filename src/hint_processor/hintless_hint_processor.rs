@@ -0,0 +1,161 @@
+use crate::{
+    hint_processor::{
+        builtin_hint_processor::{
+            builtin_hint_processor_definition::HintProcessorData,
+            hint_code,
+            math_utils::{
+                assert_250_bit, assert_nn, is_250_bits, is_addr_bounded, is_nn, is_nn_out_of_range,
+                split_felt,
+            },
+            pow_utils::pow,
+        },
+        hint_processor_definition::{HintProcessor, HintReference},
+    },
+    serde::deserialize_program::ApTracking,
+    types::exec_scope::ExecutionScopes,
+    vm::{errors::hint_errors::HintError, vm_core::VirtualMachine},
+};
+use felt::Felt;
+use std::{any::Any, collections::HashMap};
+
+///Deterministic computation replacing a hint, it may only read memory that is already known
+///and write the values the hint would have written
+pub type HintDeduction = fn(
+    &mut VirtualMachine,
+    &HashMap<String, HintReference>,
+    &ApTracking,
+    &HashMap<String, Felt>,
+) -> Result<(), HintError>;
+
+///Hint processor for environments that disallow hints.
+///Only the whitelisted hints, which are pure functions of already known memory, are run as
+///VM-side deductions. Any other hint fails with HintError::HintsDisallowed.
+pub struct HintlessHintProcessor {
+    deductions: HashMap<&'static str, HintDeduction>,
+}
+
+impl HintlessHintProcessor {
+    pub fn new() -> Self {
+        let deductions: [(&'static str, HintDeduction); 8] = [
+            (hint_code::SPLIT_FELT, |vm, ids_data, ap_tracking, _| {
+                split_felt(vm, ids_data, ap_tracking)
+            }),
+            (hint_code::ASSERT_NN, |vm, ids_data, ap_tracking, _| {
+                assert_nn(vm, ids_data, ap_tracking)
+            }),
+            (hint_code::IS_NN, |vm, ids_data, ap_tracking, _| {
+                is_nn(vm, ids_data, ap_tracking)
+            }),
+            (
+                hint_code::IS_NN_OUT_OF_RANGE,
+                |vm, ids_data, ap_tracking, _| is_nn_out_of_range(vm, ids_data, ap_tracking),
+            ),
+            (hint_code::POW, |vm, ids_data, ap_tracking, _| {
+                pow(vm, ids_data, ap_tracking)
+            }),
+            (
+                hint_code::ASSERT_250_BITS,
+                |vm, ids_data, ap_tracking, _| assert_250_bit(vm, ids_data, ap_tracking),
+            ),
+            (hint_code::IS_250_BITS, |vm, ids_data, ap_tracking, _| {
+                is_250_bits(vm, ids_data, ap_tracking)
+            }),
+            (hint_code::IS_ADDR_BOUNDED, is_addr_bounded),
+        ];
+        HintlessHintProcessor {
+            deductions: HashMap::from(deductions),
+        }
+    }
+
+    ///Returns true if the hint can be run in hint-less mode
+    pub fn is_whitelisted(&self, hint_code: &str) -> bool {
+        self.deductions.contains_key(hint_code)
+    }
+}
+
+impl Default for HintlessHintProcessor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HintProcessor for HintlessHintProcessor {
+    fn execute_hint(
+        &mut self,
+        vm: &mut VirtualMachine,
+        _exec_scopes: &mut ExecutionScopes,
+        hint_data: &Box<dyn Any>,
+        constants: &HashMap<String, Felt>,
+    ) -> Result<(), HintError> {
+        let hint_data = hint_data
+            .downcast_ref::<HintProcessorData>()
+            .ok_or(HintError::WrongHintData)?;
+        match self.deductions.get(hint_data.code.as_str()) {
+            Some(deduction) => {
+                deduction(vm, &hint_data.ids_data, &hint_data.ap_tracking, constants)
+            }
+            None => Err(HintError::HintsDisallowed(hint_data.code.clone())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        types::program::Program,
+        utils::test_utils::*,
+        vm::{errors::vm_errors::VirtualMachineError, runners::cairo_runner::CairoRunner},
+    };
+    use std::path::Path;
+
+    fn hintless_program() -> Program {
+        Program::from_file(
+            Path::new("cairo_programs/manually_compiled/hintless_program.json"),
+            Some("main"),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn run_whitelisted_hints() {
+        let program = hintless_program();
+        let mut cairo_runner = cairo_runner!(program);
+        let mut vm = vm!();
+        let mut hint_processor = HintlessHintProcessor::new();
+
+        let end = cairo_runner.initialize(&mut vm).unwrap();
+        cairo_runner
+            .run_until_pc(end, &mut vm, &mut hint_processor)
+            .unwrap();
+    }
+
+    #[test]
+    fn reject_dict_hints() {
+        let mut program = hintless_program();
+        for hint in program.hints.values_mut().flatten() {
+            hint.code = hint_code::DICT_NEW.to_string();
+        }
+        let mut cairo_runner = cairo_runner!(program);
+        let mut vm = vm!();
+        let mut hint_processor = HintlessHintProcessor::new();
+
+        let end = cairo_runner.initialize(&mut vm).unwrap();
+        match cairo_runner.run_until_pc(end, &mut vm, &mut hint_processor) {
+            Err(VirtualMachineError::Hint(hint_exception)) => assert_eq!(
+                hint_exception.inner,
+                HintError::HintsDisallowed(hint_code::DICT_NEW.to_string())
+            ),
+            result => panic!("Expected HintsDisallowed, got {result:?}"),
+        }
+    }
+
+    #[test]
+    fn whitelist() {
+        let hint_processor = HintlessHintProcessor::new();
+        assert!(hint_processor.is_whitelisted(hint_code::SPLIT_FELT));
+        assert!(hint_processor.is_whitelisted(hint_code::POW));
+        assert!(hint_processor.is_whitelisted(hint_code::IS_ADDR_BOUNDED));
+        assert!(!hint_processor.is_whitelisted(hint_code::DICT_NEW));
+    }
+}
@@ -36,9 +36,14 @@ struct Args {
 
 fn validate_layout(value: &str) -> Result<(), String> {
     match value {
-        "plain" | "small" | "dex" | "bitwise" | "perpetual_with_bitwise" | "recursive" | "all" => {
-            Ok(())
-        }
+        "plain"
+        | "small"
+        | "dex"
+        | "bitwise"
+        | "perpetual_with_bitwise"
+        | "recursive"
+        | "all"
+        | "all_cairo" => Ok(()),
         _ => Err(format!("{} is not a valid layout", value)),
     }
 }
@@ -65,8 +70,7 @@ fn main() -> Result<(), CairoRunError> {
 
     if let Some(trace_path) = args.trace_file {
         let relocated_trace = cairo_runner
-            .relocated_trace
-            .as_ref()
+            .get_relocated_trace()
             .ok_or(CairoRunError::Trace(TraceError::TraceNotEnabled))?;
         match cairo_run::write_binary_trace(relocated_trace, &trace_path) {
             Ok(()) => (),
@@ -75,7 +79,7 @@ fn main() -> Result<(), CairoRunError> {
     }
 
     if let Some(memory_path) = args.memory_file {
-        match cairo_run::write_binary_memory(&cairo_runner.relocated_memory, &memory_path) {
+        match cairo_run::write_binary_memory(cairo_runner.get_relocated_memory(), &memory_path) {
             Ok(()) => (),
             Err(_e) => return Err(CairoRunError::Runner(RunnerError::WriteFail)),
         }
@@ -98,6 +102,7 @@ mod tests {
             "perpetual_with_bitwise",
             "recursive",
             "all",
+            "all_cairo",
         ];
 
         for layout in valid_layouts {
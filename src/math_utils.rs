@@ -122,6 +122,22 @@ fn igcdex(num_a: &BigInt, num_b: &BigInt) -> (BigInt, BigInt, BigInt) {
     }
 }
 
+///Returns base ** exp % modulus, in the range [0, modulus). The base may be negative, but the
+///exponent can't be, and the modulus must be positive.
+pub fn pow_mod(
+    base: &BigInt,
+    exp: &BigInt,
+    modulus: &BigInt,
+) -> Result<BigInt, VirtualMachineError> {
+    if !modulus.is_positive() {
+        return Err(VirtualMachineError::DividedByZero);
+    }
+    if exp.is_negative() {
+        return Err(VirtualMachineError::NegativeExponent(exp.clone()));
+    }
+    Ok(base.mod_floor(modulus).modpow(exp, modulus))
+}
+
 ///Finds a nonnegative integer x < p such that (m * x) % p == n.
 pub fn div_mod(n: &BigInt, m: &BigInt, p: &BigInt) -> BigInt {
     let (a, _, c) = igcdex(m, p);
@@ -182,6 +198,15 @@ mod tests {
     use felt::NewFelt;
     use num_traits::Num;
 
+    #[test]
+    fn div_mod_inexact_division() {
+        //2 * 4 = 8 = 1 (mod 7)
+        assert_eq!(
+            div_mod(&BigInt::one(), &BigInt::from(2), &BigInt::from(7)),
+            BigInt::from(4)
+        );
+    }
+
     #[test]
     fn calculate_divmod_a() {
         let a = bigint_str!(
@@ -571,4 +596,96 @@ mod tests {
         let n = BigUint::zero();
         assert_eq!(isqrt(&n), Ok(BigUint::zero()));
     }
+
+    #[test]
+    fn calculate_isqrt_one() {
+        assert_eq!(isqrt(&BigUint::one()), Ok(BigUint::one()));
+    }
+
+    #[test]
+    fn calculate_isqrt_perfect_square_minus_one() {
+        for root in [
+            biguint!(2),
+            biguint!(3),
+            BigUint::one() << 128,
+            BigUint::one() << 192,
+        ] {
+            assert_eq!(isqrt(&(root.pow(2) - 1_u32)), Ok(root - 1_u32));
+        }
+    }
+
+    //Returns deterministic pseudo-random values with up to the given amount of bits
+    fn pseudo_random_values(bits: usize, count: usize) -> Vec<BigUint> {
+        let modulus = BigUint::one() << bits;
+        let mut value = biguint_str!("6364136223846793005");
+        (0..count)
+            .map(|_| {
+                value = (&value * &value + 1442695040888963407_u64) % &modulus;
+                value.clone()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn calculate_isqrt_random_values() {
+        for n in pseudo_random_values(256, 50)
+            .into_iter()
+            .chain(pseudo_random_values(384, 50))
+        {
+            let root = isqrt(&n).unwrap();
+            assert!(root.pow(2) <= n);
+            assert!((&root + 1_u32).pow(2) > n);
+            assert_eq!(isqrt(&root.pow(2)), Ok(root.clone()));
+            if !root.is_zero() {
+                assert_eq!(isqrt(&(root.pow(2) - 1_u32)), Ok(root - 1_u32));
+            }
+        }
+    }
+
+    #[test]
+    fn calculate_pow_mod() {
+        assert_eq!(
+            pow_mod(&bigint!(3), &bigint!(4), &bigint!(7)),
+            Ok(bigint!(4))
+        );
+        assert_eq!(
+            pow_mod(&bigint!(-3), &bigint!(3), &bigint!(7)),
+            Ok(bigint!(1))
+        );
+        assert_eq!(
+            pow_mod(&bigint!(5), &bigint!(0), &bigint!(7)),
+            Ok(bigint!(1))
+        );
+        assert_eq!(
+            pow_mod(&bigint!(5), &bigint!(0), &bigint!(1)),
+            Ok(bigint!(0))
+        );
+    }
+
+    #[test]
+    fn calculate_pow_mod_fermat() {
+        let prime = bigint_str!(
+            "115792089237316195423570985008687907853269984665640564039457584007908834671663"
+        );
+        for base in pseudo_random_values(384, 20) {
+            let base = BigInt::from(base);
+            if (&base % &prime).is_zero() {
+                continue;
+            }
+            assert_eq!(pow_mod(&base, &(&prime - 1_u32), &prime), Ok(bigint!(1)));
+            assert_eq!(pow_mod(&base, &prime, &prime), Ok(base.mod_floor(&prime)));
+        }
+    }
+
+    #[test]
+    fn calculate_pow_mod_invalid_args() {
+        assert_eq!(
+            pow_mod(&bigint!(2), &bigint!(3), &bigint!(0)),
+            Err(VirtualMachineError::DividedByZero)
+        );
+        assert_eq!(
+            pow_mod(&bigint!(2), &bigint!(-3), &bigint!(7)),
+            Err(VirtualMachineError::NegativeExponent(bigint!(-3)))
+        );
+    }
 }
@@ -1,8 +1,8 @@
 use crate::{
     serde::deserialize_utils,
     types::{
-        errors::program_errors::ProgramError, instruction::Register, program::Program,
-        relocatable::MaybeRelocatable,
+        builtin_name::BuiltinName, errors::program_errors::ProgramError, instruction::Register,
+        program::Program, relocatable::MaybeRelocatable,
     },
 };
 use felt::{Felt, FeltOps, PRIME_STR};
@@ -69,6 +69,8 @@ pub struct Identifier {
 
     pub full_name: Option<String>,
     pub members: Option<HashMap<String, Member>>,
+    pub size: Option<usize>,
+    pub decorators: Option<Vec<String>>,
 }
 
 #[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
@@ -105,6 +107,9 @@ pub struct DebugInfo {
 pub struct InstructionLocation {
     pub inst: Location,
     pub hints: Vec<HintLocation>,
+    ///Scopes visible from the instruction, the last one is the function it belongs to
+    #[serde(default)]
+    pub accessible_scopes: Vec<String>,
 }
 
 #[derive(Deserialize, Clone, Debug, PartialEq, Eq)]
@@ -341,8 +346,14 @@ pub fn deserialize_program(
         None => None,
     };
 
+    let builtins = program_json
+        .builtins
+        .iter()
+        .map(|name| name.parse())
+        .collect::<Result<Vec<BuiltinName>, ProgramError>>()?;
+
     Ok(Program {
-        builtins: program_json.builtins,
+        builtins,
         prime: PRIME_STR.to_string(),
         data: program_json.data,
         constants: {
@@ -728,7 +739,7 @@ mod tests {
         let program: Program =
             deserialize_program(reader, Some("main")).expect("Failed to deserialize program");
 
-        let builtins: Vec<String> = Vec::new();
+        let builtins: Vec<BuiltinName> = Vec::new();
         let data: Vec<MaybeRelocatable> = vec![
             MaybeRelocatable::Int(Felt::new(5189976364521848832_i64)),
             MaybeRelocatable::Int(Felt::new(1000)),
@@ -781,6 +792,55 @@ mod tests {
         assert_eq!(program.hints, hints);
     }
 
+    #[test]
+    fn deserialize_program_with_builtins() {
+        let program_json = r#"
+            {
+                "prime": "0x800000000000011000000000000000000000000000000000000000000000001",
+                "attributes": [],
+                "debug_info": null,
+                "builtins": ["output", "pedersen", "range_check"],
+                "data": [],
+                "identifiers": {},
+                "hints": {},
+                "reference_manager": {
+                    "references": []
+                }
+            }"#;
+
+        let program = deserialize_program(program_json.as_bytes(), None).unwrap();
+        assert_eq!(
+            program.builtins,
+            vec![
+                BuiltinName::Output,
+                BuiltinName::Pedersen,
+                BuiltinName::RangeCheck
+            ]
+        );
+    }
+
+    #[test]
+    fn deserialize_program_with_misspelled_builtin() {
+        let program_json = r#"
+            {
+                "prime": "0x800000000000011000000000000000000000000000000000000000000000001",
+                "attributes": [],
+                "debug_info": null,
+                "builtins": ["output", "pederson"],
+                "data": [],
+                "identifiers": {},
+                "hints": {},
+                "reference_manager": {
+                    "references": []
+                }
+            }"#;
+
+        assert!(matches!(
+            deserialize_program(program_json.as_bytes(), None),
+            Err(ProgramError::UnknownBuiltinName(name)) if name == "pederson"
+        ));
+    }
+
     /// Deserialize a program without an entrypoint.
     #[test]
     fn deserialize_program_without_entrypoint_test() {
@@ -791,7 +851,7 @@ mod tests {
         let program: Program =
             deserialize_program(reader, None).expect("Failed to deserialize program");
 
-        let builtins: Vec<String> = Vec::new();
+        let builtins: Vec<BuiltinName> = Vec::new();
         let data: Vec<MaybeRelocatable> = vec![
             MaybeRelocatable::Int(Felt::new(5189976364521848832_i64)),
             MaybeRelocatable::Int(Felt::new(1000)),
@@ -861,6 +921,8 @@ mod tests {
                 value: None,
                 full_name: None,
                 members: None,
+                size: None,
+                decorators: Some(Vec::new()),
             },
         );
         identifiers.insert(
@@ -873,6 +935,8 @@ mod tests {
                 )),
                 full_name: None,
                 members: None,
+                size: None,
+                decorators: None,
             },
         );
         identifiers.insert(
@@ -883,6 +947,8 @@ mod tests {
                 value: None,
                 full_name: None,
                 members: None,
+                size: None,
+                decorators: None,
             },
         );
         identifiers.insert(
@@ -895,6 +961,8 @@ mod tests {
                 )),
                 full_name: None,
                 members: None,
+                size: None,
+                decorators: None,
             },
         );
         identifiers.insert(
@@ -905,6 +973,8 @@ mod tests {
                 value: Some(Felt::new(3)),
                 full_name: None,
                 members: None,
+                size: None,
+                decorators: None,
             },
         );
         identifiers.insert(
@@ -915,6 +985,8 @@ mod tests {
                 value: Some(Felt::zero()),
                 full_name: None,
                 members: None,
+                size: None,
+                decorators: None,
             },
         );
         identifiers.insert(
@@ -925,6 +997,8 @@ mod tests {
                 value: Some(felt_str!("340282366920938463463374607431768211456")),
                 full_name: None,
                 members: None,
+                size: None,
+                decorators: None,
             },
         );
 
@@ -1156,6 +1230,10 @@ mod tests {
                             start_col: 5,
                         },
                         hints: vec![],
+                        accessible_scopes: vec![
+                            String::from("starkware.cairo.lang.compiler.lib.registers"),
+                            String::from("starkware.cairo.lang.compiler.lib.registers.get_fp_and_pc"),
+                        ],
                     },
                 ),
                 (
@@ -1170,6 +1248,10 @@ mod tests {
                             start_col: 5,
                         },
                         hints: vec![],
+                        accessible_scopes: vec![
+                            String::from("starkware.cairo.common.alloc"),
+                            String::from("starkware.cairo.common.alloc.alloc"),
+                        ],
                     },
                 ),
             ]),
@@ -1271,6 +1353,11 @@ mod tests {
                         }), String::from( "While expanding the reference 'syscall_ptr' in:"))
                     ), start_line: 9, start_col: 18 },
                     hints: vec![],
+                    accessible_scopes: vec![
+                        String::from("__main__"),
+                        String::from("__main__"),
+                        String::from("__main__.constructor"),
+                    ],
                 }),
             ]
         ) };
@@ -0,0 +1,103 @@
+use crate::types::errors::program_errors::ProgramError;
+use serde::{Serialize, Serializer};
+use std::{fmt, str::FromStr};
+
+///Name of a builtin, as listed in a program's builtins
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum BuiltinName {
+    Output,
+    Pedersen,
+    RangeCheck,
+    Ecdsa,
+    Bitwise,
+    EcOp,
+    Keccak,
+    SegmentArena,
+    AddMod,
+    MulMod,
+    ///The extra pedersen builtin added by CairoRunner::add_additional_hash_builtin, programs can't list it
+    AdditionalHash,
+}
+
+impl BuiltinName {
+    ///Builtins a program can list, in the order they must appear in
+    pub const ORDERED: [BuiltinName; 10] = [
+        BuiltinName::Output,
+        BuiltinName::Pedersen,
+        BuiltinName::RangeCheck,
+        BuiltinName::Ecdsa,
+        BuiltinName::Bitwise,
+        BuiltinName::EcOp,
+        BuiltinName::Keccak,
+        BuiltinName::SegmentArena,
+        BuiltinName::AddMod,
+        BuiltinName::MulMod,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BuiltinName::Output => "output",
+            BuiltinName::Pedersen => "pedersen",
+            BuiltinName::RangeCheck => "range_check",
+            BuiltinName::Ecdsa => "ecdsa",
+            BuiltinName::Bitwise => "bitwise",
+            BuiltinName::EcOp => "ec_op",
+            BuiltinName::Keccak => "keccak",
+            BuiltinName::SegmentArena => "segment_arena",
+            BuiltinName::AddMod => "add_mod",
+            BuiltinName::MulMod => "mul_mod",
+            BuiltinName::AdditionalHash => "hash_builtin",
+        }
+    }
+}
+
+impl fmt::Display for BuiltinName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl Serialize for BuiltinName {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl FromStr for BuiltinName {
+    type Err = ProgramError;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        BuiltinName::ORDERED
+            .into_iter()
+            .find(|builtin| builtin.as_str() == name)
+            .ok_or_else(|| ProgramError::UnknownBuiltinName(name.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_builtin_names() {
+        for builtin in BuiltinName::ORDERED {
+            assert_eq!(
+                builtin.to_string().parse::<BuiltinName>().ok(),
+                Some(builtin)
+            );
+        }
+    }
+
+    #[test]
+    fn parse_unknown_builtin_name() {
+        assert!(matches!(
+            "pederson".parse::<BuiltinName>(),
+            Err(ProgramError::UnknownBuiltinName(name)) if name == "pederson"
+        ));
+        //The additional hash builtin is added by the runner, programs can't request it
+        assert!(matches!(
+            "hash_builtin".parse::<BuiltinName>(),
+            Err(ProgramError::UnknownBuiltinName(_))
+        ));
+    }
+}
@@ -14,6 +14,10 @@ pub enum ProgramError {
     ConstWithoutValue(String),
     #[error("Expected prime {PRIME_STR}, got {0}")]
     PrimeDiffers(String),
+    #[error("Unknown builtin name: {0}")]
+    UnknownBuiltinName(String),
+    #[error("Program data at offset {0} isn't an integer")]
+    NonIntegerData(usize),
 }
 
 #[cfg(test)]
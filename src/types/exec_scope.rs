@@ -1,12 +1,89 @@
 use crate::{
-    any_box,
+    cloneable_box,
     hint_processor::builtin_hint_processor::dict_manager::DictManager,
     vm::errors::{exec_scope_errors::ExecScopeError, hint_errors::HintError},
 };
+use felt::Felt;
 use std::{any::Any, cell::RefCell, collections::HashMap, rc::Rc};
 
+///A value that can be stored in an execution scope. Every `Any + Clone` type implements it, so
+///that scopes can be cloned along with their variables.
+pub trait CloneableAny: Any {
+    fn clone_box(&self) -> Box<dyn CloneableAny>;
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<T: Any + Clone> CloneableAny for T {
+    fn clone_box(&self) -> Box<dyn CloneableAny> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+impl dyn CloneableAny {
+    pub fn is<T: Any>(&self) -> bool {
+        self.as_any().is::<T>()
+    }
+
+    pub fn downcast_ref<T: Any>(&self) -> Option<&T> {
+        self.as_any().downcast_ref::<T>()
+    }
+
+    pub fn downcast_mut<T: Any>(&mut self) -> Option<&mut T> {
+        self.as_any_mut().downcast_mut::<T>()
+    }
+}
+
+impl Clone for Box<dyn CloneableAny> {
+    fn clone(&self) -> Self {
+        //Dispatch on the boxed value, not on the box, which is CloneableAny itself
+        (**self).clone_box()
+    }
+}
+
 pub struct ExecutionScopes {
-    pub data: Vec<HashMap<String, Box<dyn Any>>>,
+    pub data: Vec<HashMap<String, Box<dyn CloneableAny>>>,
+}
+
+///Clones every scope along with its variables. Dict managers are deep-cloned, so that the
+///dictionaries of the clone can be modified without affecting the original, while a dict manager
+///shared by several scopes is still shared by the same scopes of the clone. Other reference
+///counted values are shared with the original.
+impl Clone for ExecutionScopes {
+    fn clone(&self) -> Self {
+        let mut dict_managers =
+            HashMap::<*const RefCell<DictManager>, Rc<RefCell<DictManager>>>::new();
+        let data = self
+            .data
+            .iter()
+            .map(|scope| {
+                scope
+                    .iter()
+                    .map(|(name, value)| {
+                        let value = match value.downcast_ref::<Rc<RefCell<DictManager>>>() {
+                            Some(dict_manager) => cloneable_box!(dict_managers
+                                .entry(Rc::as_ptr(dict_manager))
+                                .or_insert_with(|| {
+                                    Rc::new(RefCell::new(dict_manager.borrow().clone()))
+                                })
+                                .clone()),
+                            None => value.clone(),
+                        };
+                        (name.clone(), value)
+                    })
+                    .collect()
+            })
+            .collect();
+        ExecutionScopes { data }
+    }
 }
 
 impl ExecutionScopes {
@@ -16,7 +93,16 @@ impl ExecutionScopes {
         }
     }
 
-    pub fn enter_scope(&mut self, new_scope_locals: HashMap<String, Box<dyn Any>>) {
+    ///Exits every scope and removes the variables of the main scope
+    pub fn reset(&mut self) {
+        self.data.truncate(1);
+        match self.data.first_mut() {
+            Some(main_scope) => main_scope.clear(),
+            None => self.data.push(HashMap::new()),
+        }
+    }
+
+    pub fn enter_scope(&mut self, new_scope_locals: HashMap<String, Box<dyn CloneableAny>>) {
         self.data.push(new_scope_locals);
     }
 
@@ -32,14 +118,16 @@ impl ExecutionScopes {
     ///Returns a mutable reference to the dictionary containing the variables present in the current scope
     pub fn get_local_variables_mut(
         &mut self,
-    ) -> Result<&mut HashMap<String, Box<dyn Any>>, HintError> {
+    ) -> Result<&mut HashMap<String, Box<dyn CloneableAny>>, HintError> {
         self.data
             .last_mut()
             .ok_or(HintError::FromScopeError(ExecScopeError::NoScopeError))
     }
 
     ///Returns a dictionary containing the variables present in the current scope
-    pub fn get_local_variables(&self) -> Result<&HashMap<String, Box<dyn Any>>, HintError> {
+    pub fn get_local_variables(
+        &self,
+    ) -> Result<&HashMap<String, Box<dyn CloneableAny>>, HintError> {
         self.data
             .last()
             .ok_or(HintError::FromScopeError(ExecScopeError::NoScopeError))
@@ -53,9 +141,15 @@ impl ExecutionScopes {
     }
 
     ///Creates or updates an existing variable given its name and boxed value
-    pub fn assign_or_update_variable(&mut self, var_name: &str, var_value: Box<dyn Any>) {
+    ///Existing variables are updated in place, so their name is only allocated when first created
+    pub fn assign_or_update_variable(&mut self, var_name: &str, var_value: Box<dyn CloneableAny>) {
         if let Ok(local_variables) = self.get_local_variables_mut() {
-            local_variables.insert(var_name.to_string(), var_value);
+            match local_variables.get_mut(var_name) {
+                Some(variable) => *variable = var_value,
+                None => {
+                    local_variables.insert(var_name.to_string(), var_value);
+                }
+            }
         }
     }
 
@@ -92,8 +186,16 @@ impl ExecutionScopes {
         val.ok_or_else(|| HintError::VariableNotInScopeError(name.to_string()))
     }
 
+    ///Returns a mutable reference to the integer in the current execution scope that matches the name
+    ///Hints that update a counter on every iteration should modify it through this reference
+    ///instead of inserting a new value
+    pub fn get_mut_int_ref(&mut self, name: &str) -> Result<&mut Felt, HintError> {
+        self.get_mut_ref::<Felt>(name)
+    }
+
     ///Returns the value in the current execution scope that matches the name
-    pub fn get_any_boxed_ref(&self, name: &str) -> Result<&Box<dyn Any>, HintError> {
+    #[allow(clippy::borrowed_box)]
+    pub fn get_any_boxed_ref(&self, name: &str) -> Result<&Box<dyn CloneableAny>, HintError> {
         if let Some(variable) = self.get_local_variables()?.get(name) {
             return Ok(variable);
         }
@@ -101,7 +203,10 @@ impl ExecutionScopes {
     }
 
     ///Returns the value in the current execution scope that matches the name
-    pub fn get_any_boxed_mut(&mut self, name: &str) -> Result<&mut Box<dyn Any>, HintError> {
+    pub fn get_any_boxed_mut(
+        &mut self,
+        name: &str,
+    ) -> Result<&mut Box<dyn CloneableAny>, HintError> {
         if let Some(variable) = self.get_local_variables_mut()?.get_mut(name) {
             return Ok(variable);
         }
@@ -167,13 +272,13 @@ impl ExecutionScopes {
     }
 
     ///Inserts the boxed value into the current scope
-    pub fn insert_box(&mut self, name: &str, value: Box<dyn Any>) {
+    pub fn insert_box(&mut self, name: &str, value: Box<dyn CloneableAny>) {
         self.assign_or_update_variable(name, value);
     }
 
     ///Inserts the value into the current scope
-    pub fn insert_value<T: 'static>(&mut self, name: &str, value: T) {
-        self.assign_or_update_variable(name, any_box!(value));
+    pub fn insert_value<T: Any + Clone>(&mut self, name: &str, value: T) {
+        self.assign_or_update_variable(name, cloneable_box!(value));
     }
 }
 
@@ -186,9 +291,30 @@ impl Default for ExecutionScopes {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{
+        hint_processor::builtin_hint_processor::dict_manager::DictTracker,
+        relocatable,
+        types::relocatable::{MaybeRelocatable, Relocatable},
+        utils::test_utils::*,
+    };
     use felt::{Felt, NewFelt};
+    use num_bigint::BigInt;
     use num_traits::One;
 
+    #[test]
+    fn reset_execution_scopes() {
+        let mut scopes = ExecutionScopes::new();
+        scopes.assign_or_update_variable("a", cloneable_box!(Felt::new(2)));
+        scopes.enter_scope(HashMap::from([(
+            String::from("b"),
+            cloneable_box!(Felt::new(3)),
+        )]));
+
+        scopes.reset();
+        assert_eq!(scopes.data.len(), 1);
+        assert!(scopes.data[0].is_empty());
+    }
+
     #[test]
     fn initialize_execution_scopes() {
         let scopes = ExecutionScopes::new();
@@ -198,7 +324,7 @@ mod tests {
     #[test]
     fn get_local_variables_test() {
         let var_name = String::from("a");
-        let var_value: Box<dyn Any> = Box::new(Felt::new(2));
+        let var_value: Box<dyn CloneableAny> = Box::new(Felt::new(2));
 
         let scope = HashMap::from([(var_name, var_value)]);
 
@@ -218,14 +344,14 @@ mod tests {
     #[test]
     fn enter_new_scope_test() {
         let var_name = String::from("a");
-        let var_value: Box<dyn Any> = Box::new(Felt::new(2_i32));
+        let var_value: Box<dyn CloneableAny> = Box::new(Felt::new(2_i32));
 
         let new_scope = HashMap::from([(var_name, var_value)]);
 
         let mut scopes = ExecutionScopes {
             data: vec![HashMap::from([(
                 String::from("b"),
-                (Box::new(Felt::one()) as Box<dyn Any>),
+                (Box::new(Felt::one()) as Box<dyn CloneableAny>),
             )])],
         };
 
@@ -260,7 +386,7 @@ mod tests {
     #[test]
     fn exit_scope_test() {
         let var_name = String::from("a");
-        let var_value: Box<dyn Any> = Box::new(Felt::new(2));
+        let var_value: Box<dyn CloneableAny> = Box::new(Felt::new(2));
 
         let new_scope = HashMap::from([(var_name, var_value)]);
 
@@ -295,7 +421,7 @@ mod tests {
 
     #[test]
     fn assign_local_variable_test() {
-        let var_value: Box<dyn Any> = Box::new(Felt::new(2));
+        let var_value: Box<dyn CloneableAny> = Box::new(Felt::new(2));
 
         let mut scopes = ExecutionScopes::new();
 
@@ -316,13 +442,13 @@ mod tests {
     #[test]
     fn re_assign_local_variable_test() {
         let var_name = String::from("a");
-        let var_value: Box<dyn Any> = Box::new(Felt::new(2));
+        let var_value: Box<dyn CloneableAny> = Box::new(Felt::new(2));
 
         let scope = HashMap::from([(var_name, var_value)]);
 
         let mut scopes = ExecutionScopes { data: vec![scope] };
 
-        let var_value_new: Box<dyn Any> = Box::new(Felt::new(3));
+        let var_value_new: Box<dyn CloneableAny> = Box::new(Felt::new(3));
 
         scopes.assign_or_update_variable("a", var_value_new);
 
@@ -341,7 +467,7 @@ mod tests {
     #[test]
     fn delete_local_variable_test() {
         let var_name = String::from("a");
-        let var_value: Box<dyn Any> = Box::new(Felt::new(2));
+        let var_value: Box<dyn CloneableAny> = Box::new(Felt::new(2));
 
         let scope = HashMap::from([(var_name, var_value)]);
 
@@ -369,7 +495,7 @@ mod tests {
 
     #[test]
     fn get_listu64_test() {
-        let list_u64: Box<dyn Any> = Box::new(vec![20_u64, 18_u64]);
+        let list_u64: Box<dyn CloneableAny> = Box::new(vec![20_u64, 18_u64]);
 
         let mut scopes = ExecutionScopes::default();
 
@@ -387,7 +513,7 @@ mod tests {
 
     #[test]
     fn get_u64_test() {
-        let u64: Box<dyn Any> = Box::new(9_u64);
+        let u64: Box<dyn CloneableAny> = Box::new(9_u64);
 
         let mut scopes = ExecutionScopes::new();
 
@@ -412,7 +538,7 @@ mod tests {
 
     #[test]
     fn get_mut_int_ref_test() {
-        let bigint: Box<dyn Any> = Box::new(Felt::new(12));
+        let bigint: Box<dyn CloneableAny> = Box::new(Felt::new(12));
 
         let mut scopes = ExecutionScopes::new();
         scopes.assign_or_update_variable("bigint", bigint);
@@ -422,7 +548,7 @@ mod tests {
 
     #[test]
     fn get_any_boxed_test() {
-        let list_u64: Box<dyn Any> = Box::new(vec![20_u64, 18_u64]);
+        let list_u64: Box<dyn CloneableAny> = Box::new(vec![20_u64, 18_u64]);
 
         let mut scopes = ExecutionScopes::default();
 
@@ -447,4 +573,107 @@ mod tests {
         assert!(scopes.get_any_boxed_mut("no_variable").is_err());
         assert!(scopes.get_any_boxed_ref("no_variable").is_err());
     }
+
+    #[test]
+    fn update_variable_in_place() {
+        let mut scopes = ExecutionScopes::new();
+        scopes.insert_value("n", Felt::new(3));
+        //Updating with a value of another type replaces it
+        scopes.insert_value("n", 3_usize);
+        assert_eq!(scopes.get::<usize>("n"), Ok(3));
+        assert_eq!(scopes.get_local_variables().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn get_mut_int_ref_decrement_in_place() {
+        let mut scopes = ExecutionScopes::new();
+        scopes.insert_value("n", Felt::new(3));
+        *scopes.get_mut_int_ref("n").unwrap() -= Felt::one();
+        assert_eq!(scopes.get::<Felt>("n"), Ok(Felt::new(2)));
+
+        scopes.insert_value("m", 3_usize);
+        assert_eq!(
+            scopes.get_mut_int_ref("m"),
+            Err(HintError::VariableNotInScopeError("m".to_string()))
+        );
+        assert_eq!(
+            scopes.get_mut_int_ref("k"),
+            Err(HintError::VariableNotInScopeError("k".to_string()))
+        );
+    }
+
+    #[test]
+    fn clone_scopes_with_bigint_list() {
+        let mut scopes = ExecutionScopes::new();
+        scopes.insert_value("list", vec![bigint!(1), bigint!(2)]);
+        scopes.enter_scope(HashMap::from([(
+            String::from("x"),
+            cloneable_box!(bigint!(3)),
+        )]));
+
+        let mut cloned = scopes.clone();
+        cloned
+            .get_mut_ref::<BigInt>("x")
+            .unwrap()
+            .clone_from(&bigint!(4));
+        cloned.exit_scope().unwrap();
+        cloned
+            .get_mut_list_ref::<BigInt>("list")
+            .unwrap()
+            .push(bigint!(5));
+
+        assert_eq!(scopes.data.len(), 2);
+        assert_eq!(scopes.get::<BigInt>("x"), Ok(bigint!(3)));
+        scopes.exit_scope().unwrap();
+        assert_eq!(
+            scopes.get_list::<BigInt>("list"),
+            Ok(vec![bigint!(1), bigint!(2)])
+        );
+        assert_eq!(
+            cloned.get_list::<BigInt>("list"),
+            Ok(vec![bigint!(1), bigint!(2), bigint!(5)])
+        );
+    }
+
+    #[test]
+    fn clone_scopes_deep_clones_dict_manager() {
+        let mut dict_manager = DictManager::new();
+        dict_manager.trackers.insert(
+            2,
+            DictTracker::new_default_dict(&relocatable!(2, 0), &mayberelocatable!(17), None),
+        );
+        let dict_manager = Rc::new(RefCell::new(dict_manager));
+        let mut scopes = ExecutionScopes::new();
+        scopes.insert_value("dict_manager", dict_manager.clone());
+        scopes.enter_scope(HashMap::from([(
+            String::from("dict_manager"),
+            cloneable_box!(dict_manager.clone()),
+        )]));
+
+        let cloned = scopes.clone();
+        let cloned_dict_manager = cloned.get_dict_manager().unwrap();
+        assert!(!Rc::ptr_eq(&cloned_dict_manager, &dict_manager));
+        //Scopes sharing a dict manager still share it in the clone
+        assert!(Rc::ptr_eq(
+            &cloned_dict_manager,
+            cloned.data[0]["dict_manager"]
+                .downcast_ref::<Rc<RefCell<DictManager>>>()
+                .unwrap()
+        ));
+
+        let mut cloned_dict_manager = cloned_dict_manager.borrow_mut();
+        let tracker = cloned_dict_manager.trackers.get_mut(&2).unwrap();
+        //The default value is cloned along with the dictionary
+        assert_eq!(
+            tracker.get_value(&mayberelocatable!(5)),
+            Ok(&mayberelocatable!(17))
+        );
+        tracker.insert_value(&mayberelocatable!(6), &mayberelocatable!(1));
+        tracker.current_ptr = relocatable!(2, 6);
+
+        assert_eq!(
+            dict_manager.borrow().trackers[&2],
+            DictTracker::new_default_dict(&relocatable!(2, 0), &mayberelocatable!(17), None)
+        );
+    }
 }
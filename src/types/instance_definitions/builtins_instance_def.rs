@@ -1,7 +1,8 @@
 use super::{
     bitwise_instance_def::BitwiseInstanceDef, ec_op_instance_def::EcOpInstanceDef,
     ecdsa_instance_def::EcdsaInstanceDef, keccak_instance_def::KeccakInstanceDef,
-    pedersen_instance_def::PedersenInstanceDef, range_check_instance_def::RangeCheckInstanceDef,
+    mod_instance_def::ModInstanceDef, pedersen_instance_def::PedersenInstanceDef,
+    range_check_instance_def::RangeCheckInstanceDef,
 };
 
 #[derive(Debug, PartialEq)]
@@ -13,6 +14,8 @@ pub(crate) struct BuiltinsInstanceDef {
     pub(crate) bitwise: Option<BitwiseInstanceDef>,
     pub(crate) ec_op: Option<EcOpInstanceDef>,
     pub(crate) keccak: Option<KeccakInstanceDef>,
+    pub(crate) add_mod: Option<ModInstanceDef>,
+    pub(crate) mul_mod: Option<ModInstanceDef>,
 }
 
 impl BuiltinsInstanceDef {
@@ -25,6 +28,8 @@ impl BuiltinsInstanceDef {
             bitwise: None,
             ec_op: None,
             keccak: None,
+            add_mod: None,
+            mul_mod: None,
         }
     }
 
@@ -37,6 +42,8 @@ impl BuiltinsInstanceDef {
             bitwise: None,
             ec_op: None,
             keccak: None,
+            add_mod: None,
+            mul_mod: None,
         }
     }
 
@@ -49,6 +56,8 @@ impl BuiltinsInstanceDef {
             bitwise: None,
             ec_op: None,
             keccak: None,
+            add_mod: None,
+            mul_mod: None,
         }
     }
 
@@ -61,6 +70,8 @@ impl BuiltinsInstanceDef {
             bitwise: Some(BitwiseInstanceDef::new(64)),
             ec_op: Some(EcOpInstanceDef::new(1024)),
             keccak: None,
+            add_mod: None,
+            mul_mod: None,
         }
     }
 
@@ -73,6 +84,8 @@ impl BuiltinsInstanceDef {
             bitwise: Some(BitwiseInstanceDef::new(8)),
             ec_op: None,
             keccak: None,
+            add_mod: None,
+            mul_mod: None,
         }
     }
 
@@ -85,6 +98,22 @@ impl BuiltinsInstanceDef {
             bitwise: Some(BitwiseInstanceDef::new(16)),
             ec_op: None,
             keccak: Some(KeccakInstanceDef::new(2048)),
+            add_mod: None,
+            mul_mod: None,
+        }
+    }
+
+    pub(crate) fn all_cairo() -> BuiltinsInstanceDef {
+        BuiltinsInstanceDef {
+            _output: true,
+            pedersen: Some(PedersenInstanceDef::new(256, 1)),
+            range_check: Some(RangeCheckInstanceDef::default()),
+            _ecdsa: Some(EcdsaInstanceDef::new(2048)),
+            bitwise: Some(BitwiseInstanceDef::new(16)),
+            ec_op: Some(EcOpInstanceDef::new(1024)),
+            keccak: Some(KeccakInstanceDef::new(2048)),
+            add_mod: Some(ModInstanceDef::new(128, 1)),
+            mul_mod: Some(ModInstanceDef::new(256, 1)),
         }
     }
 
@@ -97,6 +126,8 @@ impl BuiltinsInstanceDef {
             bitwise: Some(BitwiseInstanceDef::default()),
             ec_op: Some(EcOpInstanceDef::default()),
             keccak: None,
+            add_mod: None,
+            mul_mod: None,
         }
     }
 }
@@ -181,4 +212,13 @@ mod tests {
         assert!(builtins.bitwise.is_some());
         assert!(builtins.ec_op.is_some());
     }
+
+    #[test]
+    fn get_builtins_all_cairo() {
+        let builtins = BuiltinsInstanceDef::all_cairo();
+        assert!(builtins._output);
+        assert!(builtins.keccak.is_some());
+        assert_eq!(builtins.add_mod, Some(ModInstanceDef::new(128, 1)));
+        assert_eq!(builtins.mul_mod, Some(ModInstanceDef::new(256, 1)));
+    }
 }
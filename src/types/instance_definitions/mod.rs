@@ -5,5 +5,6 @@ pub mod diluted_pool_instance_def;
 pub mod ec_op_instance_def;
 pub mod ecdsa_instance_def;
 pub mod keccak_instance_def;
+pub mod mod_instance_def;
 pub mod pedersen_instance_def;
 pub mod range_check_instance_def;
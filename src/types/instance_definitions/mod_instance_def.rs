@@ -0,0 +1,49 @@
+//Each instance holds p as N_WORDS words, then values_ptr, offsets_ptr and n
+pub(crate) const N_WORDS: usize = 4;
+pub(crate) const CELLS_PER_MOD: u32 = 7;
+pub(crate) const INPUT_CELLS_PER_MOD: u32 = 7;
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct ModInstanceDef {
+    pub(crate) ratio: u32,
+    pub(crate) word_bit_len: u32,
+    pub(crate) batch_size: usize,
+}
+
+impl ModInstanceDef {
+    pub(crate) fn new(ratio: u32, batch_size: usize) -> Self {
+        ModInstanceDef {
+            ratio,
+            word_bit_len: 96,
+            batch_size,
+        }
+    }
+
+    pub(crate) fn _cells_per_builtin(&self) -> u32 {
+        CELLS_PER_MOD
+    }
+
+    pub(crate) fn _range_check_units_per_builtin(&self) -> u32 {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new() {
+        let builtin_instance = ModInstanceDef {
+            ratio: 128,
+            word_bit_len: 96,
+            batch_size: 1,
+        };
+        assert_eq!(ModInstanceDef::new(128, 1), builtin_instance);
+    }
+
+    #[test]
+    fn get_cells_per_builtin() {
+        assert_eq!(ModInstanceDef::new(128, 1)._cells_per_builtin(), 7);
+    }
+}
@@ -10,12 +10,21 @@ pub enum Register {
     FP,
 }
 
-#[derive(Debug, PartialEq, Eq)]
+///Any of the vm's registers, unlike Register, which only covers the ones instructions address
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum VmRegister {
+    PC,
+    AP,
+    FP,
+}
+
+///A decoded instruction. The immediate, if any, isn't copied out of memory: it is read lazily
+///from pc + 1 as op1 when op1_addr is Op1Addr::Imm.
+#[derive(Debug, PartialEq, Eq, Clone)]
 pub struct Instruction {
     pub off0: isize,
     pub off1: isize,
     pub off2: isize,
-    pub imm: Option<Felt>,
     pub dst_register: Register,
     pub op0_register: Register,
     pub op1_addr: Op1Addr,
@@ -26,7 +35,7 @@ pub struct Instruction {
     pub opcode: Opcode,
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Clone)]
 pub enum Op1Addr {
     Imm,
     AP,
@@ -34,7 +43,7 @@ pub enum Op1Addr {
     Op0,
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Clone)]
 pub enum Res {
     Op1,
     Add,
@@ -42,7 +51,7 @@ pub enum Res {
     Unconstrained,
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Clone)]
 pub enum PcUpdate {
     Regular,
     Jump,
@@ -50,7 +59,7 @@ pub enum PcUpdate {
     Jnz,
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Clone)]
 pub enum ApUpdate {
     Regular,
     Add,
@@ -58,14 +67,14 @@ pub enum ApUpdate {
     Add2,
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Clone)]
 pub enum FpUpdate {
     Regular,
     APPlus2,
     Dst,
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Clone)]
 pub enum Opcode {
     NOp,
     AssertEq,
@@ -75,9 +84,9 @@ pub enum Opcode {
 
 impl Instruction {
     pub fn size(&self) -> usize {
-        match self.imm {
-            Some(_) => 2,
-            None => 1,
+        match self.op1_addr {
+            Op1Addr::Imm => 2,
+            _ => 1,
         }
     }
 }
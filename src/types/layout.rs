@@ -101,6 +101,20 @@ impl CairoLayout {
         }
     }
 
+    pub(crate) fn all_cairo_instance() -> CairoLayout {
+        CairoLayout {
+            _name: String::from("all_cairo"),
+            _cpu_component_step: 1,
+            rc_units: 4,
+            builtins: BuiltinsInstanceDef::all_cairo(),
+            _public_memory_fraction: 8,
+            _memory_units_per_step: 8,
+            diluted_pool_instance_def: Some(DilutedPoolInstanceDef::default()),
+            _n_trace_colums: 11,
+            _cpu_instance_def: CpuInstanceDef::default(),
+        }
+    }
+
     pub(crate) fn all_instance() -> CairoLayout {
         CairoLayout {
             _name: String::from("all"),
@@ -236,4 +250,16 @@ mod tests {
         assert_eq!(layout._n_trace_colums, 27);
         assert_eq!(layout._cpu_instance_def, CpuInstanceDef::default());
     }
+
+    #[test]
+    fn get_all_cairo_instance() {
+        let layout = CairoLayout::all_cairo_instance();
+        assert_eq!(&layout._name, "all_cairo");
+        assert_eq!(layout.rc_units, 4);
+        assert_eq!(layout.builtins, BuiltinsInstanceDef::all_cairo());
+        assert_eq!(
+            layout.diluted_pool_instance_def,
+            Some(DilutedPoolInstanceDef::default())
+        );
+    }
 }
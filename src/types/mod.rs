@@ -1,7 +1,9 @@
+pub mod builtin_name;
 pub mod errors;
 pub mod exec_scope;
 pub mod instance_definitions;
 pub mod instruction;
 pub mod layout;
 pub mod program;
+pub mod program_lint;
 pub mod relocatable;
@@ -1,11 +1,15 @@
 use crate::{
     serde::deserialize_program::{
-        deserialize_program, Attribute, HintParams, Identifier, InstructionLocation,
-        ReferenceManager,
+        deserialize_program, Attribute, HintParams, Identifier, InstructionLocation, Location,
+        Member, ReferenceManager,
+    },
+    types::{
+        builtin_name::BuiltinName, errors::program_errors::ProgramError,
+        relocatable::MaybeRelocatable,
     },
-    types::{errors::program_errors::ProgramError, relocatable::MaybeRelocatable},
 };
-use felt::{Felt, PRIME_STR};
+use felt::{Felt, FeltOps, NewFelt, PRIME_STR};
+use starknet_crypto::{pedersen_hash, FieldElement};
 use std::{
     fs::File,
     io::{BufReader, Read},
@@ -14,7 +18,7 @@ use std::{
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Program {
-    pub builtins: Vec<String>,
+    pub builtins: Vec<BuiltinName>,
     pub prime: String,
     pub data: Vec<MaybeRelocatable>,
     pub constants: HashMap<String, Felt>,
@@ -32,7 +36,7 @@ pub struct Program {
 impl Program {
     #[allow(clippy::too_many_arguments)]
     pub fn new(
-        builtins: Vec<String>,
+        builtins: Vec<BuiltinName>,
         prime: String,
         data: Vec<MaybeRelocatable>,
         main: Option<usize>,
@@ -84,6 +88,173 @@ impl Program {
     ) -> Result<Program, ProgramError> {
         deserialize_program(reader, entrypoint)
     }
+
+    ///Returns the pc of the label or function with the given full name (ie: "__main__.my_label")
+    pub fn get_label(&self, name: &str) -> Option<usize> {
+        let identifier = self.identifiers.get(name)?;
+        match identifier.type_.as_deref() {
+            Some("label") | Some("function") => identifier.pc,
+            _ => None,
+        }
+    }
+
+    ///Returns the value of the constant with the given full name
+    pub fn get_const(&self, name: &str) -> Option<&Felt> {
+        self.constants.get(name)
+    }
+
+    ///Returns the definition of the struct with the given full name
+    pub fn get_struct(&self, name: &str) -> Option<StructDef<'_>> {
+        let identifier = self.identifiers.get(name)?;
+        if identifier.type_.as_deref() != Some("struct") {
+            return None;
+        }
+        Some(StructDef {
+            members: identifier.members.as_ref()?,
+            size: identifier.size?,
+        })
+    }
+
+    ///Returns the pc and decorators of the function with the given full name
+    pub fn get_function(&self, name: &str) -> Option<FunctionDef<'_>> {
+        let identifier = self.identifiers.get(name)?;
+        if identifier.type_.as_deref() != Some("function") {
+            return None;
+        }
+        Some(FunctionDef {
+            pc: identifier.pc?,
+            decorators: identifier.decorators.as_deref().unwrap_or_default(),
+        })
+    }
+
+    ///Returns the source location of the instruction at pc_offset, if the program was compiled
+    ///with debug info.
+    pub fn get_location(&self, pc_offset: usize) -> Option<&Location> {
+        self.instruction_locations
+            .as_ref()?
+            .get(&pc_offset)
+            .map(|instruction_location| &instruction_location.inst)
+    }
+
+    ///Returns the full name of the function the instruction at pc_offset belongs to, if the
+    ///program was compiled with debug info.
+    pub fn get_function_name(&self, pc_offset: usize) -> Option<&str> {
+        self.instruction_locations
+            .as_ref()?
+            .get(&pc_offset)?
+            .accessible_scopes
+            .last()
+            .map(String::as_str)
+    }
+
+    ///Returns the full name of the function the instruction at pc_offset belongs to. Uses the
+    ///debug info if available, and otherwise the function identifier with the closest pc at or
+    ///before pc_offset.
+    pub fn find_function_name(&self, pc_offset: usize) -> Option<&str> {
+        if self.instruction_locations.is_some() {
+            return self.get_function_name(pc_offset);
+        }
+        self.identifiers
+            .iter()
+            .filter(|(_, identifier)| identifier.type_.as_deref() == Some("function"))
+            .filter_map(|(name, identifier)| Some((identifier.pc?, name)))
+            .filter(|(pc, _)| *pc <= pc_offset)
+            .max()
+            .map(|(_, name)| name.as_str())
+    }
+
+    ///Computes the hash of the program as the bootloader does, like cairo-lang's
+    ///compute_program_hash_chain: the pedersen hash chain of a header, holding the main offset
+    ///and the builtins, followed by the program's data.
+    pub fn compute_hash(&self) -> Result<Felt, ProgramError> {
+        let main = self
+            .main
+            .ok_or_else(|| ProgramError::EntrypointNotFound("main".to_string()))?;
+        //Bootloader version, main and builtins
+        let mut data_chain = vec![
+            Felt::new(0),
+            Felt::new(main),
+            Felt::new(self.builtins.len()),
+        ];
+        data_chain.extend(
+            self.builtins
+                .iter()
+                .map(|builtin| Felt::from_bytes_be(builtin.as_str().as_bytes())),
+        );
+        for (offset, word) in self.data.iter().enumerate() {
+            match word {
+                MaybeRelocatable::Int(value) => data_chain.push(value.clone()),
+                MaybeRelocatable::RelocatableValue(_) => {
+                    return Err(ProgramError::NonIntegerData(offset))
+                }
+            }
+        }
+        data_chain.insert(0, Felt::new(data_chain.len()));
+        Ok(compute_hash_chain(&data_chain))
+    }
+
+    ///Returns the names of the identifiers which are close to the given name, sorted.
+    ///Used to suggest alternatives when a lookup fails because of a typo.
+    pub fn get_near_miss_identifiers(&self, name: &str) -> Vec<String> {
+        let mut near_misses: Vec<String> = self
+            .identifiers
+            .keys()
+            .filter(|identifier| edit_distance(identifier, name) <= MAX_NEAR_MISS_DISTANCE)
+            .cloned()
+            .collect();
+        near_misses.sort();
+        near_misses
+    }
+}
+
+const MAX_NEAR_MISS_DISTANCE: usize = 2;
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct StructDef<'a> {
+    pub members: &'a HashMap<String, Member>,
+    pub size: usize,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct FunctionDef<'a> {
+    pub pc: usize,
+    pub decorators: &'a [String],
+}
+
+//Pedersen hash chain of the values: h(v_0, h(v_1, ..., h(v_n-1, v_n)))
+fn compute_hash_chain(values: &[Felt]) -> Felt {
+    //Felts are always below the prime, so they are valid field elements
+    let to_field_element = |value: &Felt| {
+        FieldElement::from_dec_str(&value.to_str_radix(10)).expect("felt out of the field")
+    };
+    values
+        .iter()
+        .rev()
+        .map(to_field_element)
+        .reduce(|hash, value| pedersen_hash(&value, &hash))
+        .map_or_else(
+            || Felt::new(0),
+            |hash| Felt::from_bytes_be(&hash.to_bytes_be()),
+        )
+}
+
+///Levenshtein distance between two strings
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous_row: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current_row = vec![i + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous_row[j] + usize::from(a_char != *b_char);
+            current_row.push(
+                substitution
+                    .min(previous_row[j + 1] + 1)
+                    .min(current_row[j] + 1),
+            );
+        }
+        previous_row = current_row;
+    }
+    previous_row[b.len()]
 }
 
 impl Default for Program {
@@ -109,7 +280,7 @@ impl Default for Program {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::serde::deserialize_program::{ApTracking, FlowTrackingData};
+    use crate::serde::deserialize_program::{ApTracking, FlowTrackingData, InputFile};
     use crate::utils::test_utils::mayberelocatable;
     use felt::{felt_str, NewFelt};
     use num_traits::Zero;
@@ -120,7 +291,7 @@ mod tests {
             references: Vec::new(),
         };
 
-        let builtins: Vec<String> = Vec::new();
+        let builtins: Vec<BuiltinName> = Vec::new();
         let _r: MaybeRelocatable = mayberelocatable!(33);
         let data: Vec<MaybeRelocatable> = vec![
             mayberelocatable!(5189976364521848832),
@@ -156,7 +327,7 @@ mod tests {
             references: Vec::new(),
         };
 
-        let builtins: Vec<String> = Vec::new();
+        let builtins: Vec<BuiltinName> = Vec::new();
 
         let data: Vec<MaybeRelocatable> = vec![
             mayberelocatable!(5189976364521848832),
@@ -177,6 +348,8 @@ mod tests {
                 value: None,
                 full_name: None,
                 members: None,
+                size: None,
+                decorators: None,
             },
         );
 
@@ -188,6 +361,8 @@ mod tests {
                 value: Some(Felt::zero()),
                 full_name: None,
                 members: None,
+                size: None,
+                decorators: None,
             },
         );
 
@@ -223,7 +398,7 @@ mod tests {
             references: Vec::new(),
         };
 
-        let builtins: Vec<String> = Vec::new();
+        let builtins: Vec<BuiltinName> = Vec::new();
 
         let data: Vec<MaybeRelocatable> = vec![
             mayberelocatable!(5189976364521848832),
@@ -244,6 +419,8 @@ mod tests {
                 value: None,
                 full_name: None,
                 members: None,
+                size: None,
+                decorators: None,
             },
         );
 
@@ -255,6 +432,8 @@ mod tests {
                 value: None,
                 full_name: None,
                 members: None,
+                size: None,
+                decorators: None,
             },
         );
 
@@ -281,7 +460,7 @@ mod tests {
         )
         .expect("Failed to deserialize program");
 
-        let builtins: Vec<String> = Vec::new();
+        let builtins: Vec<BuiltinName> = Vec::new();
         let data: Vec<MaybeRelocatable> = vec![
             mayberelocatable!(5189976364521848832),
             mayberelocatable!(1000),
@@ -301,6 +480,8 @@ mod tests {
                 value: None,
                 full_name: None,
                 members: None,
+                size: None,
+                decorators: Some(Vec::new()),
             },
         );
         identifiers.insert(
@@ -311,6 +492,8 @@ mod tests {
                 value: None,
                 full_name: Some("__main__.main.Args".to_string()),
                 members: Some(HashMap::new()),
+                size: Some(0),
+                decorators: None,
             },
         );
         identifiers.insert(
@@ -321,6 +504,8 @@ mod tests {
                 value: None,
                 full_name: Some("__main__.main.ImplicitArgs".to_string()),
                 members: Some(HashMap::new()),
+                size: Some(0),
+                decorators: None,
             },
         );
         identifiers.insert(
@@ -331,6 +516,8 @@ mod tests {
                 value: None,
                 full_name: Some("__main__.main.Return".to_string()),
                 members: Some(HashMap::new()),
+                size: Some(0),
+                decorators: None,
             },
         );
         identifiers.insert(
@@ -341,6 +528,8 @@ mod tests {
                 value: Some(Felt::zero()),
                 full_name: None,
                 members: None,
+                size: None,
+                decorators: None,
             },
         );
 
@@ -360,7 +549,7 @@ mod tests {
         )
         .expect("Failed to deserialize program");
 
-        let builtins: Vec<String> = Vec::new();
+        let builtins: Vec<BuiltinName> = Vec::new();
 
         let error_message_attributes: Vec<Attribute> = vec![Attribute {
             name: String::from("error_message"),
@@ -395,6 +584,8 @@ mod tests {
                 value: None,
                 full_name: None,
                 members: None,
+                size: None,
+                decorators: Some(Vec::new()),
             },
         );
         identifiers.insert(
@@ -405,6 +596,8 @@ mod tests {
                 value: None,
                 full_name: Some("__main__.main.Args".to_string()),
                 members: Some(HashMap::new()),
+                size: Some(0),
+                decorators: None,
             },
         );
         identifiers.insert(
@@ -415,6 +608,8 @@ mod tests {
                 value: None,
                 full_name: Some("__main__.main.ImplicitArgs".to_string()),
                 members: Some(HashMap::new()),
+                size: Some(0),
+                decorators: None,
             },
         );
         identifiers.insert(
@@ -425,6 +620,8 @@ mod tests {
                 value: None,
                 full_name: Some("__main__.main.Return".to_string()),
                 members: Some(HashMap::new()),
+                size: Some(0),
+                decorators: None,
             },
         );
         identifiers.insert(
@@ -435,6 +632,8 @@ mod tests {
                 value: Some(Felt::zero()),
                 full_name: None,
                 members: None,
+                size: None,
+                decorators: None,
             },
         );
 
@@ -505,4 +704,182 @@ mod tests {
 
         assert_eq!(program, Program::default())
     }
+
+    fn identifiers_program() -> Program {
+        Program::from_file(
+            Path::new("cairo_programs/manually_compiled/identifiers_program.json"),
+            Some("main"),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn get_label() {
+        let program = identifiers_program();
+        assert_eq!(program.get_label("__main__.main.end"), Some(2));
+        assert_eq!(program.get_label("__main__.main"), Some(0));
+        assert_eq!(program.get_label("__main__.MAX"), None);
+        assert_eq!(program.get_label("__main__.missing"), None);
+    }
+
+    #[test]
+    fn get_const() {
+        let program = identifiers_program();
+        assert_eq!(program.get_const("__main__.MAX"), Some(&Felt::new(100)));
+        assert_eq!(program.get_const("__main__.main"), None);
+    }
+
+    #[test]
+    fn get_struct() {
+        let program = identifiers_program();
+        let members = HashMap::from([
+            (
+                String::from("x"),
+                Member {
+                    cairo_type: String::from("felt"),
+                    offset: 0,
+                },
+            ),
+            (
+                String::from("y"),
+                Member {
+                    cairo_type: String::from("felt"),
+                    offset: 1,
+                },
+            ),
+        ]);
+        assert_eq!(
+            program.get_struct("__main__.Point"),
+            Some(StructDef {
+                members: &members,
+                size: 2
+            })
+        );
+        assert_eq!(program.get_struct("__main__.main"), None);
+    }
+
+    #[test]
+    fn get_location_and_function_name() {
+        let program = Program::from_file(
+            Path::new("cairo_programs/manually_compiled/valid_program_b.json"),
+            Some("main"),
+        )
+        .unwrap();
+        assert_eq!(
+            program.get_location(4),
+            Some(&Location {
+                end_line: 8,
+                end_col: 28,
+                input_file: InputFile {
+                    filename: String::from("main1.cairo")
+                },
+                parent_location: None,
+                start_line: 8,
+                start_col: 5,
+            })
+        );
+        assert_eq!(program.get_function_name(4), Some("__main__.check_range"));
+        assert_eq!(program.get_function_name(13), Some("__main__.main"));
+        //The immediate of the instruction at pc 1 has no location
+        assert_eq!(program.get_location(2), None);
+        assert_eq!(program.get_function_name(2), None);
+    }
+
+    #[test]
+    fn get_location_without_debug_info() {
+        let program = Program::from_file(
+            Path::new("cairo_programs/manually_compiled/hintless_program.json"),
+            Some("main"),
+        )
+        .unwrap();
+        assert_eq!(program.instruction_locations, None);
+        assert_eq!(program.get_location(0), None);
+        assert_eq!(program.get_function_name(0), None);
+    }
+
+    #[test]
+    fn get_function() {
+        let program = identifiers_program();
+        assert_eq!(
+            program.get_function("__main__.main"),
+            Some(FunctionDef {
+                pc: 0,
+                decorators: &[String::from("view")]
+            })
+        );
+        assert_eq!(program.get_function("__main__.main.end"), None);
+    }
+
+    #[test]
+    fn compute_hash_depends_on_data_and_builtins() {
+        let program = Program {
+            data: vec![mayberelocatable!(1), mayberelocatable!(2)],
+            main: Some(0),
+            ..Program::default()
+        };
+        let program_hash = program.compute_hash().unwrap();
+        assert_eq!(program.clone().compute_hash().unwrap(), program_hash);
+
+        let mut other_program = program.clone();
+        other_program.data[1] = mayberelocatable!(3);
+        assert_ne!(other_program.compute_hash().unwrap(), program_hash);
+
+        let mut other_program = program;
+        other_program.builtins.push(BuiltinName::Output);
+        assert_ne!(other_program.compute_hash().unwrap(), program_hash);
+    }
+
+    #[test]
+    fn compute_hash_of_empty_program() {
+        let program = Program {
+            main: Some(0),
+            ..Program::default()
+        };
+        //Hash chain of [3, 0, 0, 0]: the length, version, main and number of builtins
+        let expected =
+            compute_hash_chain(&[Felt::new(3), Felt::zero(), Felt::zero(), Felt::zero()]);
+        assert_eq!(program.compute_hash().unwrap(), expected);
+        assert_eq!(
+            compute_hash_chain(&[Felt::zero(), Felt::zero()]),
+            felt_str!(
+                "2089986280348253421170679821480865132823066470938446095505822317253594081284"
+            )
+        );
+    }
+
+    #[test]
+    fn compute_hash_errors() {
+        let program = Program {
+            data: vec![mayberelocatable!(1), mayberelocatable!(1, 0)],
+            ..Program::default()
+        };
+        assert!(matches!(
+            program.compute_hash(),
+            Err(ProgramError::EntrypointNotFound(name)) if name == "main"
+        ));
+        let program = Program {
+            main: Some(0),
+            ..program
+        };
+        assert!(matches!(
+            program.compute_hash(),
+            Err(ProgramError::NonIntegerData(1))
+        ));
+    }
+
+    #[test]
+    fn get_near_miss_identifiers() {
+        let program = identifiers_program();
+        assert_eq!(
+            program.get_near_miss_identifiers("__main__.mian"),
+            vec![String::from("__main__.main")]
+        );
+        assert_eq!(
+            program.get_near_miss_identifiers("__main__.Pont"),
+            vec![String::from("__main__.Point")]
+        );
+        assert!(program
+            .get_near_miss_identifiers("something_else")
+            .is_empty());
+    }
 }
@@ -0,0 +1,229 @@
+use crate::{
+    serde::deserialize_program::{HintParams, OffsetValue, Reference},
+    types::{builtin_name::BuiltinName, instruction::Register, program::Program},
+};
+use std::collections::BTreeSet;
+use thiserror::Error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LintSeverity {
+    Warning,
+    Error,
+}
+
+///An issue found by Program::lint
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum LintIssue {
+    #[error("Hint at pc {0} uses ids.{1}, which isn't one of its references")]
+    UnknownHintId(usize, String),
+    #[error("Hint at pc {0} refers to ids.{1} with reference id {2}, which doesn't exist")]
+    MissingReference(usize, String, usize),
+    #[error("Hint at pc {0} is in ap tracking group {3}, but ids.{1} depends on ap in group {2}")]
+    ApTrackingGroupMismatch(usize, String, usize, usize),
+    #[error("Hint at pc {0} is at ap tracking offset {3}, before the offset {2} where ids.{1} was defined")]
+    ApTrackingOffsetAhead(usize, String, usize, usize),
+    #[error("Builtin {0} is declared by the program but the entrypoint has no {0}_ptr argument")]
+    MissingBuiltinPointer(BuiltinName),
+    #[error(
+        "The entrypoint has a {0}_ptr argument but the program doesn't declare the {0} builtin"
+    )]
+    UndeclaredBuiltinPointer(BuiltinName),
+}
+
+impl LintIssue {
+    pub fn severity(&self) -> LintSeverity {
+        match self {
+            LintIssue::UnknownHintId(..)
+            | LintIssue::MissingReference(..)
+            | LintIssue::ApTrackingGroupMismatch(..)
+            | LintIssue::ApTrackingOffsetAhead(..)
+            | LintIssue::MissingBuiltinPointer(_) => LintSeverity::Error,
+            //The argument could be a plain pointer which happens to share the builtin's name
+            LintIssue::UndeclaredBuiltinPointer(_) => LintSeverity::Warning,
+        }
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity() == LintSeverity::Error
+    }
+}
+
+impl Program {
+    ///Runs static checks over the program, returning the issues found in the hints, by pc, followed
+    ///by the ones found in the entrypoint's builtin pointers.
+    ///Hints must only use ids they have references for, and the references which depend on ap
+    ///must be usable from the hint's ap tracking data. The builtin pointers taken by the
+    ///entrypoint must match the program's builtins, this is skipped if the program doesn't
+    ///describe the entrypoint's arguments.
+    pub fn lint(&self) -> Vec<LintIssue> {
+        let mut hint_pcs: Vec<&usize> = self.hints.keys().collect();
+        hint_pcs.sort();
+        let mut issues = Vec::new();
+        for pc in hint_pcs {
+            for hint in &self.hints[pc] {
+                self.lint_hint(*pc, hint, &mut issues);
+            }
+        }
+        self.lint_builtin_pointers(&mut issues);
+        issues
+    }
+
+    fn lint_hint(&self, pc: usize, hint: &HintParams, issues: &mut Vec<LintIssue>) {
+        let flow_tracking_data = &hint.flow_tracking_data;
+        for name in used_ids(&hint.code) {
+            let reference_id = match flow_tracking_data
+                .reference_ids
+                .iter()
+                .find(|(path, _)| path.rsplit('.').next() == Some(name))
+            {
+                Some((_, reference_id)) => *reference_id,
+                None => {
+                    issues.push(LintIssue::UnknownHintId(pc, name.to_string()));
+                    continue;
+                }
+            };
+            let reference = match self.reference_manager.references.get(reference_id) {
+                Some(reference) => reference,
+                None => {
+                    issues.push(LintIssue::MissingReference(
+                        pc,
+                        name.to_string(),
+                        reference_id,
+                    ));
+                    continue;
+                }
+            };
+            if !depends_on_ap(reference) {
+                continue;
+            }
+            let (reference_tracking, hint_tracking) =
+                (&reference.ap_tracking_data, &flow_tracking_data.ap_tracking);
+            if reference_tracking.group != hint_tracking.group {
+                issues.push(LintIssue::ApTrackingGroupMismatch(
+                    pc,
+                    name.to_string(),
+                    reference_tracking.group,
+                    hint_tracking.group,
+                ));
+            } else if reference_tracking.offset > hint_tracking.offset {
+                issues.push(LintIssue::ApTrackingOffsetAhead(
+                    pc,
+                    name.to_string(),
+                    reference_tracking.offset,
+                    hint_tracking.offset,
+                ));
+            }
+        }
+    }
+
+    fn lint_builtin_pointers(&self, issues: &mut Vec<LintIssue>) {
+        let entrypoint = match self.identifiers.iter().find(|(_, identifier)| {
+            identifier.type_.as_deref() == Some("function") && identifier.pc == self.main
+        }) {
+            Some((name, _)) if self.main.is_some() => name,
+            _ => return,
+        };
+        let argument_structs: Vec<_> = ["Args", "ImplicitArgs"]
+            .iter()
+            .filter_map(|suffix| self.get_struct(&format!("{entrypoint}.{suffix}")))
+            .collect();
+        if argument_structs.is_empty() {
+            return;
+        }
+        let pointers: BTreeSet<BuiltinName> = argument_structs
+            .iter()
+            .flat_map(|arguments| arguments.members.keys())
+            .filter_map(|argument| argument.strip_suffix("_ptr")?.parse().ok())
+            .collect();
+        for builtin in BuiltinName::ORDERED {
+            match (
+                self.builtins.contains(&builtin),
+                pointers.contains(&builtin),
+            ) {
+                (true, false) => issues.push(LintIssue::MissingBuiltinPointer(builtin)),
+                (false, true) => issues.push(LintIssue::UndeclaredBuiltinPointer(builtin)),
+                _ => (),
+            }
+        }
+    }
+}
+
+//Names used as ids.NAME in the hint's code, in order of first use
+fn used_ids(code: &str) -> Vec<&str> {
+    let is_identifier_char = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    let mut names = Vec::new();
+    for (start, _) in code.match_indices("ids.") {
+        if matches!(code[..start].chars().next_back(), Some(c) if is_identifier_char(c) || c == '.')
+        {
+            continue;
+        }
+        let rest = &code[start + 4..];
+        let name = &rest[..rest.find(|c| !is_identifier_char(c)).unwrap_or(rest.len())];
+        if !name.is_empty() && !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    names
+}
+
+fn depends_on_ap(reference: &Reference) -> bool {
+    let address = &reference.value_address;
+    [&address.offset1, &address.offset2]
+        .iter()
+        .any(|offset| matches!(offset, OffsetValue::Reference(Register::AP, _, _)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn load(program_path: &str) -> Program {
+        Program::from_file(Path::new(program_path), Some("main")).unwrap()
+    }
+
+    #[test]
+    fn lint_programs_without_issues() {
+        for program_path in [
+            "cairo_programs/manually_compiled/valid_program_a.json",
+            //Doesn't describe main's arguments, so its builtins aren't checked
+            "cairo_programs/manually_compiled/output_program.json",
+        ] {
+            assert_eq!(load(program_path).lint(), Vec::new(), "{program_path}");
+        }
+    }
+
+    #[test]
+    fn lint_program_with_issues() {
+        let program = load("cairo_programs/manually_compiled/lint_issues_program.json");
+        assert_eq!(
+            program.lint(),
+            vec![
+                LintIssue::UnknownHintId(0, "c".to_string()),
+                LintIssue::ApTrackingGroupMismatch(2, "b".to_string(), 1, 2),
+                LintIssue::ApTrackingOffsetAhead(3, "b".to_string(), 1, 0),
+                LintIssue::MissingReference(3, "d".to_string(), 7),
+                LintIssue::UndeclaredBuiltinPointer(BuiltinName::Pedersen),
+                LintIssue::MissingBuiltinPointer(BuiltinName::RangeCheck),
+            ]
+        );
+    }
+
+    #[test]
+    fn lint_issue_severity() {
+        assert!(LintIssue::UnknownHintId(0, "c".to_string()).is_error());
+        assert!(LintIssue::MissingBuiltinPointer(BuiltinName::Output).is_error());
+        assert_eq!(
+            LintIssue::UndeclaredBuiltinPointer(BuiltinName::Output).severity(),
+            LintSeverity::Warning
+        );
+    }
+
+    #[test]
+    fn used_ids_skips_other_names() {
+        assert_eq!(
+            used_ids("ids.a = my_ids.b + x.ids.c + ids.d.low\nids.a += 1"),
+            vec!["a", "d"]
+        );
+    }
+}
@@ -3,13 +3,16 @@ use crate::{
     vm::errors::{memory_errors::MemoryError, vm_errors::VirtualMachineError},
 };
 use felt::{Felt, NewFelt};
-use num_traits::{FromPrimitive, ToPrimitive, Zero};
+use num_bigint::BigInt;
+use num_traits::{FromPrimitive, Signed, ToPrimitive, Zero};
+use serde::Serialize;
 use std::{
+    cmp::Ordering,
     fmt::{self, Display},
     ops::Add,
 };
 
-#[derive(Eq, Hash, PartialEq, PartialOrd, Clone, Copy, Debug)]
+#[derive(Eq, Hash, PartialEq, PartialOrd, Clone, Copy, Debug, Serialize)]
 pub struct Relocatable {
     pub segment_index: isize,
     pub offset: usize,
@@ -60,6 +63,12 @@ impl From<&Relocatable> for Relocatable {
     }
 }
 
+impl From<BigInt> for MaybeRelocatable {
+    fn from(num: BigInt) -> Self {
+        MaybeRelocatable::Int(Felt::from(num))
+    }
+}
+
 impl From<&Felt> for MaybeRelocatable {
     fn from(val: &Felt) -> Self {
         MaybeRelocatable::Int(val.clone())
@@ -148,6 +157,22 @@ impl TryFrom<&MaybeRelocatable> for Relocatable {
     }
 }
 
+///Adds a Felt to an offset. Felts above half the prime stand for negative numbers, so adding
+///`PRIME - k` subtracts k from the offset, failing if it would go below zero.
+fn add_felt_to_offset(offset: usize, num: &Felt) -> Result<usize, VirtualMachineError> {
+    if num.is_negative() {
+        if let Some(decrement) = (-num).to_usize() {
+            return offset
+                .checked_sub(decrement)
+                .ok_or(VirtualMachineError::CantSubOffset(offset, decrement));
+        }
+    }
+    let big_offset = num + offset;
+    big_offset
+        .to_usize()
+        .ok_or(VirtualMachineError::OffsetExceeded(big_offset))
+}
+
 impl Relocatable {
     pub fn sub_usize(&self, other: usize) -> Result<Self, VirtualMachineError> {
         if self.offset < other {
@@ -159,13 +184,9 @@ impl Relocatable {
 
     ///Adds a Felt to self
     pub fn add_int(&self, other: &Felt) -> Result<Relocatable, VirtualMachineError> {
-        let big_offset = other + self.offset;
-        let new_offset = big_offset
-            .to_usize()
-            .ok_or(VirtualMachineError::OffsetExceeded(big_offset))?;
         Ok(Relocatable {
             segment_index: self.segment_index,
-            offset: new_offset,
+            offset: add_felt_to_offset(self.offset, other)?,
         })
     }
 
@@ -175,15 +196,7 @@ impl Relocatable {
         let num_ref = other
             .get_int_ref()
             .map_err(|_| VirtualMachineError::RelocatableAdd)?;
-
-        let big_offset: Felt = num_ref + self.offset;
-        let new_offset = big_offset
-            .to_usize()
-            .ok_or(VirtualMachineError::OffsetExceeded(big_offset))?;
-        Ok(Relocatable {
-            segment_index: self.segment_index,
-            offset: new_offset,
-        })
+        self.add_int(num_ref)
     }
 
     pub fn sub(&self, other: &Self) -> Result<usize, VirtualMachineError> {
@@ -207,14 +220,7 @@ impl MaybeRelocatable {
         match *self {
             MaybeRelocatable::Int(ref value) => Ok(MaybeRelocatable::Int(value + other)),
             MaybeRelocatable::RelocatableValue(ref rel) => {
-                let big_offset = other + rel.offset;
-                let new_offset = big_offset
-                    .to_usize()
-                    .ok_or(VirtualMachineError::OffsetExceeded(big_offset))?;
-                Ok(MaybeRelocatable::RelocatableValue(Relocatable {
-                    segment_index: rel.segment_index,
-                    offset: new_offset,
-                }))
+                Ok(MaybeRelocatable::RelocatableValue(rel.add_int(other)?))
             }
         }
     }
@@ -243,16 +249,8 @@ impl MaybeRelocatable {
                 Err(VirtualMachineError::RelocatableAdd)
             }
             (&MaybeRelocatable::RelocatableValue(ref rel), &MaybeRelocatable::Int(ref num_ref))
-            | (&MaybeRelocatable::Int(ref num_ref), &MaybeRelocatable::RelocatableValue(ref rel)) =>
-            {
-                let big_offset: Felt = num_ref + rel.offset;
-                let new_offset = big_offset
-                    .to_usize()
-                    .ok_or(VirtualMachineError::OffsetExceeded(big_offset))?;
-                Ok(MaybeRelocatable::RelocatableValue(Relocatable {
-                    segment_index: rel.segment_index,
-                    offset: new_offset,
-                }))
+            | (&MaybeRelocatable::Int(ref num_ref), &MaybeRelocatable::RelocatableValue(ref rel)) => {
+                Ok(MaybeRelocatable::RelocatableValue(rel.add_int(num_ref)?))
             }
         }
     }
@@ -270,24 +268,54 @@ impl MaybeRelocatable {
                 MaybeRelocatable::RelocatableValue(rel_b),
             ) => {
                 if rel_a.segment_index == rel_b.segment_index {
-                    return Ok(MaybeRelocatable::from(Felt::new(
-                        rel_a.offset - rel_b.offset,
-                    )));
+                    return Ok(MaybeRelocatable::from(
+                        Felt::new(rel_a.offset) - Felt::new(rel_b.offset),
+                    ));
                 }
                 Err(VirtualMachineError::DiffIndexSub)
             }
             (MaybeRelocatable::RelocatableValue(rel_a), MaybeRelocatable::Int(ref num_b)) => {
-                Ok(MaybeRelocatable::from((
-                    rel_a.segment_index,
-                    (rel_a.offset - num_b)
-                        .to_usize()
-                        .ok_or_else(|| VirtualMachineError::OffsetExceeded(rel_a.offset - num_b))?,
-                )))
+                Ok(MaybeRelocatable::RelocatableValue(rel_a.add_int(&-num_b)?))
             }
-            _ => Err(VirtualMachineError::NotImplemented),
+            (MaybeRelocatable::Int(num_a), MaybeRelocatable::RelocatableValue(rel_b)) => Err(
+                VirtualMachineError::SubRelocatableFromInt(num_a.clone(), *rel_b),
+            ),
         }
     }
 
+    /// Compares two integers by value or two relocatable values of the same segment by offset.
+    /// Unlike the derived PartialOrd, which orders every integer before every relocatable value,
+    /// comparing an integer to a relocatable value or relocatable values of different segments
+    /// is an error.
+    pub fn try_cmp(&self, other: &MaybeRelocatable) -> Result<Ordering, VirtualMachineError> {
+        match (self, other) {
+            (MaybeRelocatable::Int(num_a), MaybeRelocatable::Int(num_b)) => Ok(num_a.cmp(num_b)),
+            (
+                MaybeRelocatable::RelocatableValue(rel_a),
+                MaybeRelocatable::RelocatableValue(rel_b),
+            ) => {
+                if rel_a.segment_index != rel_b.segment_index {
+                    return Err(VirtualMachineError::DiffIndexComp(*rel_a, *rel_b));
+                }
+                Ok(rel_a.offset.cmp(&rel_b.offset))
+            }
+            _ => Err(VirtualMachineError::DiffTypeComparison(
+                self.clone(),
+                other.clone(),
+            )),
+        }
+    }
+
+    /// Returns self < other, with the restrictions of try_cmp
+    pub fn try_lt(&self, other: &MaybeRelocatable) -> Result<bool, VirtualMachineError> {
+        Ok(self.try_cmp(other)? == Ordering::Less)
+    }
+
+    /// Returns self <= other, with the restrictions of try_cmp
+    pub fn try_le(&self, other: &MaybeRelocatable) -> Result<bool, VirtualMachineError> {
+        Ok(self.try_cmp(other)? != Ordering::Greater)
+    }
+
     /// Performs integer division and module on a MaybeRelocatable::Int by another
     /// MaybeRelocatable::Int and returns the quotient and reminder.
     pub fn divmod(
@@ -300,7 +328,10 @@ impl MaybeRelocatable {
                 // NOTE: elements on a field element always have multiplicative inverse
                 MaybeRelocatable::from(Felt::zero()),
             )),
-            _ => Err(VirtualMachineError::NotImplemented),
+            _ => Err(VirtualMachineError::DivModNonInt(
+                self.clone(),
+                other.clone(),
+            )),
         }
     }
 
@@ -339,7 +370,7 @@ impl<'a> Add<usize> for &'a Relocatable {
 /// If the value is Relocatable, it will return an error since it should've already been relocated.
 pub fn relocate_value(
     value: MaybeRelocatable,
-    relocation_table: &Vec<usize>,
+    relocation_table: &[usize],
 ) -> Result<Felt, MemoryError> {
     match value {
         MaybeRelocatable::Int(num) => Ok(num),
@@ -352,7 +383,7 @@ pub fn relocate_value(
 
 pub fn relocate_address(
     relocatable: Relocatable,
-    relocation_table: &Vec<usize>,
+    relocation_table: &[usize],
 ) -> Result<usize, MemoryError> {
     let (segment_index, offset) = if relocatable.segment_index >= 0 {
         (relocatable.segment_index as usize, relocatable.offset)
@@ -376,6 +407,23 @@ mod tests {
     use felt::felt_str;
     use num_traits::{One, Zero};
 
+    #[test]
+    fn maybe_relocatable_from_conversions() {
+        assert_eq!(
+            MaybeRelocatable::from(7_usize),
+            MaybeRelocatable::Int(Felt::new(7))
+        );
+        assert_eq!(
+            MaybeRelocatable::from(BigInt::from(-1)),
+            MaybeRelocatable::Int(Felt::zero() - 1_u32)
+        );
+        assert_eq!(
+            MaybeRelocatable::from(Relocatable::from((1, 2))),
+            mayberelocatable!(1, 2)
+        );
+        assert_eq!(MaybeRelocatable::from((-1, 2)), mayberelocatable!(-1, 2));
+    }
+
     #[test]
     fn add_bigint_to_int() {
         let addr = MaybeRelocatable::from(Felt::new(7i32));
@@ -540,6 +588,16 @@ mod tests {
         assert_eq!(Ok(MaybeRelocatable::from(Felt::new(10))), sub_addr);
     }
 
+    #[test]
+    fn sub_relocatable_from_relocatable_negative_difference() {
+        let addr_a = &MaybeRelocatable::from((7, 7));
+        let addr_b = &MaybeRelocatable::from((7, 17));
+        assert_eq!(
+            addr_a.sub(addr_b),
+            Ok(MaybeRelocatable::from(Felt::zero() - Felt::new(10)))
+        );
+    }
+
     #[test]
     fn sub_relocatable_from_relocatable_diff_offset() {
         let addr_a = &MaybeRelocatable::from((7, 17));
@@ -564,7 +622,38 @@ mod tests {
     fn sub_rel_to_int_error() {
         let a = &MaybeRelocatable::from(Felt::new(7_i32));
         let b = &MaybeRelocatable::from((7, 10));
-        assert_eq!(Err(VirtualMachineError::NotImplemented), a.sub(b));
+        assert_eq!(
+            Err(VirtualMachineError::SubRelocatableFromInt(
+                Felt::new(7_i32),
+                Relocatable::from((7, 10))
+            )),
+            a.sub(b)
+        );
+    }
+
+    #[test]
+    fn add_negative_int_to_relocatable() {
+        let addr = relocatable!(3, 10);
+        assert_eq!(
+            addr.add_int(&(Felt::zero() - Felt::new(4))),
+            Ok(relocatable!(3, 6))
+        );
+    }
+
+    #[test]
+    fn add_negative_int_to_relocatable_underflow() {
+        let addr = relocatable!(3, 2);
+        assert_eq!(
+            addr.add_int(&(Felt::zero() - Felt::new(4))),
+            Err(VirtualMachineError::CantSubOffset(2, 4))
+        );
+    }
+
+    #[test]
+    fn sub_int_from_relocatable_underflow() {
+        let a = &MaybeRelocatable::from((7, 3));
+        let b = &MaybeRelocatable::from(Felt::new(5));
+        assert_eq!(a.sub(b), Err(VirtualMachineError::CantSubOffset(3, 5)));
     }
 
     #[test]
@@ -580,7 +669,13 @@ mod tests {
     fn divmod_bad_type() {
         let value = &MaybeRelocatable::from(Felt::new(10));
         let div = &MaybeRelocatable::from((2, 7));
-        assert_eq!(value.divmod(div), Err(VirtualMachineError::NotImplemented));
+        assert_eq!(
+            value.divmod(div),
+            Err(VirtualMachineError::DivModNonInt(
+                MaybeRelocatable::from(Felt::new(10)),
+                MaybeRelocatable::from((2, 7))
+            ))
+        );
     }
 
     #[test]
@@ -704,6 +799,75 @@ mod tests {
         assert_eq!(Err(VirtualMachineError::DiffIndexSub), a.sub(&b));
     }
 
+    #[test]
+    fn try_cmp_operand_matrix() {
+        let int_a = mayberelocatable!(3);
+        let int_b = mayberelocatable!(4);
+        let ptr_a = mayberelocatable!(1, 3);
+        let ptr_b = mayberelocatable!(1, 4);
+        let other_segment = mayberelocatable!(2, 3);
+
+        //Integers compare by value, pointers of the same segment by offset
+        assert_eq!(int_a.try_cmp(&int_b), Ok(Ordering::Less));
+        assert_eq!(int_b.try_cmp(&int_a), Ok(Ordering::Greater));
+        assert_eq!(int_a.try_cmp(&int_a), Ok(Ordering::Equal));
+        assert_eq!(ptr_a.try_cmp(&ptr_b), Ok(Ordering::Less));
+        assert_eq!(ptr_b.try_cmp(&ptr_a), Ok(Ordering::Greater));
+        assert_eq!(ptr_a.try_cmp(&ptr_a.clone()), Ok(Ordering::Equal));
+        assert_eq!(other_segment.try_cmp(&other_segment), Ok(Ordering::Equal));
+
+        //Pointers of different segments can't be compared, even with equal offsets
+        assert_eq!(
+            ptr_a.try_cmp(&other_segment),
+            Err(VirtualMachineError::DiffIndexComp(
+                relocatable!(1, 3),
+                relocatable!(2, 3)
+            ))
+        );
+        assert_eq!(
+            other_segment.try_cmp(&ptr_b),
+            Err(VirtualMachineError::DiffIndexComp(
+                relocatable!(2, 3),
+                relocatable!(1, 4)
+            ))
+        );
+
+        //Nor can pointers and integers
+        for (a, b) in [
+            (&int_a, &ptr_a),
+            (&ptr_a, &int_a),
+            (&int_b, &other_segment),
+            (&other_segment, &int_b),
+        ] {
+            assert_eq!(
+                a.try_cmp(b),
+                Err(VirtualMachineError::DiffTypeComparison(
+                    a.clone(),
+                    b.clone()
+                ))
+            );
+        }
+    }
+
+    #[test]
+    fn try_lt_and_try_le() {
+        let ptr_a = mayberelocatable!(1, 3);
+        let ptr_b = mayberelocatable!(1, 4);
+        assert_eq!(ptr_a.try_lt(&ptr_b), Ok(true));
+        assert_eq!(ptr_a.try_le(&ptr_b), Ok(true));
+        assert_eq!(ptr_a.try_lt(&ptr_a), Ok(false));
+        assert_eq!(ptr_a.try_le(&ptr_a), Ok(true));
+        assert_eq!(ptr_b.try_lt(&ptr_a), Ok(false));
+        assert_eq!(ptr_b.try_le(&ptr_a), Ok(false));
+        assert_eq!(
+            mayberelocatable!(1).try_le(&ptr_a),
+            Err(VirtualMachineError::DiffTypeComparison(
+                mayberelocatable!(1),
+                ptr_a
+            ))
+        );
+    }
+
     #[test]
     fn add_maybe_mod_ok() {
         assert_eq!(
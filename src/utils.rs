@@ -19,6 +19,13 @@ macro_rules! any_box {
     };
 }
 
+#[macro_export]
+macro_rules! cloneable_box {
+    ($val : expr) => {
+        Box::new($val) as Box<dyn $crate::types::exec_scope::CloneableAny>
+    };
+}
+
 pub fn is_subsequence<T: PartialEq>(subsequence: &[T], mut sequence: &[T]) -> bool {
     for search in subsequence {
         if let Some(index) = sequence.iter().position(|element| search == element) {
@@ -203,7 +210,7 @@ pub mod test_utils {
         () => {{
             let mut vm = VirtualMachine::new(false);
             vm.builtin_runners = vec![(
-                "range_check".to_string(),
+                crate::types::builtin_name::BuiltinName::RangeCheck,
                 RangeCheckBuiltinRunner::new(8, 8, true).into(),
             )];
             vm
@@ -235,7 +242,7 @@ pub mod test_utils {
         //Program with builtins
         ( $( $builtin_name: expr ),* ) => {
             Program {
-                builtins: vec![$( $builtin_name.to_string() ),*],
+                builtins: vec![$( $builtin_name ),*],
                 prime: "0x800000000000011000000000000000000000000000000000000000000000001".to_string(),
                 data: Vec::new(),
                 constants: HashMap::new(),
@@ -399,7 +406,7 @@ pub mod test_utils {
                 $(
                     exec_scopes.assign_or_update_variable(
                         $name,
-                        any_box!($val),
+                        $crate::cloneable_box!($val),
                     );
                 )*
                 exec_scopes
@@ -521,7 +528,10 @@ mod test {
             hint_processor_definition::{HintProcessor, HintReference},
         },
         serde::deserialize_program::ReferenceManager,
-        types::{exec_scope::ExecutionScopes, program::Program, relocatable::MaybeRelocatable},
+        types::{
+            builtin_name::BuiltinName, exec_scope::ExecutionScopes, program::Program,
+            relocatable::MaybeRelocatable,
+        },
         utils::test_utils::*,
         vm::{
             errors::memory_errors::MemoryError, trace::trace_entry::TraceEntry,
@@ -692,12 +702,12 @@ mod test {
     #[test]
     fn check_scope_test_pass() {
         let mut exec_scopes = ExecutionScopes::new();
-        exec_scopes.assign_or_update_variable("a", any_box!(String::from("Hello")));
+        exec_scopes.assign_or_update_variable("a", cloneable_box!(String::from("Hello")));
         exec_scopes.assign_or_update_variable(
             "",
-            any_box!(Rc::new(RefCell::new(HashMap::<usize, Vec<usize>>::new()))),
+            cloneable_box!(Rc::new(RefCell::new(HashMap::<usize, Vec<usize>>::new()))),
         );
-        exec_scopes.assign_or_update_variable("c", any_box!(vec![1, 2, 3, 4]));
+        exec_scopes.assign_or_update_variable("c", cloneable_box!(vec![1, 2, 3, 4]));
         check_scope!(
             &exec_scopes,
             [
@@ -715,12 +725,12 @@ mod test {
     #[should_panic]
     fn check_scope_test_fail() {
         let mut exec_scopes = ExecutionScopes::new();
-        exec_scopes.assign_or_update_variable("a", any_box!(String::from("Hello")));
+        exec_scopes.assign_or_update_variable("a", cloneable_box!(String::from("Hello")));
         exec_scopes.assign_or_update_variable(
             "",
-            any_box!(Rc::new(RefCell::new(HashMap::<usize, Vec<usize>>::new()))),
+            cloneable_box!(Rc::new(RefCell::new(HashMap::<usize, Vec<usize>>::new()))),
         );
-        exec_scopes.assign_or_update_variable("c", any_box!(vec![1, 2, 3, 4]));
+        exec_scopes.assign_or_update_variable("c", cloneable_box!(vec![1, 2, 3, 4]));
         check_scope!(
             &exec_scopes,
             [
@@ -738,7 +748,7 @@ mod test {
     fn scope_macro_test() {
         let scope_from_macro = scope![("a", Felt::one())];
         let mut scope_verbose = ExecutionScopes::new();
-        scope_verbose.assign_or_update_variable("a", any_box!(Felt::one()));
+        scope_verbose.assign_or_update_variable("a", cloneable_box!(Felt::one()));
         assert_eq!(scope_from_macro.data.len(), scope_verbose.data.len());
         assert_eq!(scope_from_macro.data[0].len(), scope_verbose.data[0].len());
         assert_eq!(
@@ -759,7 +769,7 @@ mod test {
         let mut exec_scopes = ExecutionScopes::new();
         exec_scopes.assign_or_update_variable(
             "dict_manager",
-            any_box!(Rc::new(RefCell::new(dict_manager))),
+            cloneable_box!(Rc::new(RefCell::new(dict_manager))),
         );
         check_dictionary!(&exec_scopes, 2, (5, 10));
     }
@@ -777,7 +787,7 @@ mod test {
         let mut exec_scopes = ExecutionScopes::new();
         exec_scopes.assign_or_update_variable(
             "dict_manager",
-            any_box!(Rc::new(RefCell::new(dict_manager))),
+            cloneable_box!(Rc::new(RefCell::new(dict_manager))),
         );
         check_dictionary!(&exec_scopes, 2, (5, 11));
     }
@@ -790,7 +800,7 @@ mod test {
         let mut exec_scopes = ExecutionScopes::new();
         exec_scopes.assign_or_update_variable(
             "dict_manager",
-            any_box!(Rc::new(RefCell::new(dict_manager))),
+            cloneable_box!(Rc::new(RefCell::new(dict_manager))),
         );
         check_dict_ptr!(&exec_scopes, 2, (2, 0));
     }
@@ -804,7 +814,7 @@ mod test {
         let mut exec_scopes = ExecutionScopes::new();
         exec_scopes.assign_or_update_variable(
             "dict_manager",
-            any_box!(Rc::new(RefCell::new(dict_manager))),
+            cloneable_box!(Rc::new(RefCell::new(dict_manager))),
         );
         check_dict_ptr!(&exec_scopes, 2, (3, 0));
     }
@@ -882,7 +892,7 @@ mod test {
     #[test]
     fn program_macro_with_builtin() {
         let program = Program {
-            builtins: vec!["range_check".to_string()],
+            builtins: vec![BuiltinName::RangeCheck],
             prime: "0x800000000000011000000000000000000000000000000000000000000000001".to_string(),
             data: Vec::new(),
             constants: HashMap::new(),
@@ -898,13 +908,13 @@ mod test {
             instruction_locations: None,
         };
 
-        assert_eq!(program, program!["range_check"])
+        assert_eq!(program, program![BuiltinName::RangeCheck])
     }
 
     #[test]
     fn program_macro_custom_definition() {
         let program = Program {
-            builtins: vec!["range_check".to_string()],
+            builtins: vec![BuiltinName::RangeCheck],
             prime: "0x800000000000011000000000000000000000000000000000000000000000001".to_string(),
             data: Vec::new(),
             constants: HashMap::new(),
@@ -922,7 +932,7 @@ mod test {
 
         assert_eq!(
             program,
-            program!(builtins = vec!["range_check".to_string()], main = Some(2),)
+            program!(builtins = vec![BuiltinName::RangeCheck], main = Some(2),)
         )
     }
 }
@@ -0,0 +1,166 @@
+//Compact binary format of a set of accessed addresses, written by
+//VirtualMachine::export_accessed_addresses so two runs can be diffed without dumping their memory.
+//
+//The format is the number of addresses followed by each address, sorted by segment and offset.
+//Each address is stored as the zigzag varint difference between its segment index and the
+//previous one, followed by the varint difference between its offset and the previous one when
+//both share the segment, or by its offset otherwise.
+
+use crate::types::relocatable::Relocatable;
+use std::io::{self, Read, Write};
+
+pub(crate) fn write_accessed_addresses<'a>(
+    writer: &mut impl Write,
+    addresses: impl Iterator<Item = &'a Relocatable>,
+) -> io::Result<()> {
+    let mut addresses: Vec<_> = addresses
+        .map(|addr| (addr.segment_index, addr.offset))
+        .collect();
+    addresses.sort_unstable();
+
+    let mut bytes = Vec::with_capacity(addresses.len() * 2 + 10);
+    write_varint(&mut bytes, addresses.len() as u64);
+    let (mut prev_segment, mut prev_offset) = (0, 0);
+    for (segment_index, offset) in addresses {
+        let segment_delta = segment_index - prev_segment;
+        write_varint(&mut bytes, zigzag_encode(segment_delta as i64));
+        let offset_delta = if segment_delta == 0 {
+            offset - prev_offset
+        } else {
+            offset
+        };
+        write_varint(&mut bytes, offset_delta as u64);
+        (prev_segment, prev_offset) = (segment_index, offset);
+    }
+    writer.write_all(&bytes)
+}
+
+///Reads the addresses written by VirtualMachine::export_accessed_addresses, sorted by segment and
+///offset. Fails with InvalidData if the data isn't in the format described in this module.
+pub fn read_accessed_addresses(reader: &mut impl Read) -> io::Result<Vec<Relocatable>> {
+    let mut data = Vec::new();
+    reader.read_to_end(&mut data)?;
+    let mut bytes = data.into_iter();
+    let len = read_varint(&mut bytes)?;
+    let mut addresses = Vec::new();
+    let (mut segment_index, mut offset) = (0_isize, 0_usize);
+    for _ in 0..len {
+        let segment_delta = zigzag_decode(read_varint(&mut bytes)?);
+        let offset_delta = read_varint(&mut bytes)?;
+        segment_index = isize::try_from(segment_delta)
+            .ok()
+            .and_then(|delta| segment_index.checked_add(delta))
+            .ok_or_else(|| invalid_data("segment index out of range"))?;
+        let base = if segment_delta == 0 { offset } else { 0 };
+        offset = usize::try_from(offset_delta)
+            .ok()
+            .and_then(|delta| base.checked_add(delta))
+            .ok_or_else(|| invalid_data("offset out of range"))?;
+        addresses.push(Relocatable::from((segment_index, offset)));
+    }
+    Ok(addresses)
+}
+
+fn write_varint(bytes: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        bytes.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+fn read_varint(bytes: &mut impl Iterator<Item = u8>) -> io::Result<u64> {
+    let mut value = 0_u64;
+    for shift in (0..64).step_by(7) {
+        let byte = bytes
+            .next()
+            .ok_or_else(|| invalid_data("unexpected end of data"))?;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(invalid_data("varint longer than 64 bits"))
+}
+
+fn zigzag_encode(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+fn zigzag_decode(value: u64) -> i64 {
+    ((value >> 1) as i64) ^ -((value & 1) as i64)
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::relocatable;
+
+    fn round_trip(addresses: &[Relocatable]) -> Vec<Relocatable> {
+        let mut bytes = Vec::new();
+        write_accessed_addresses(&mut bytes, addresses.iter()).unwrap();
+        read_accessed_addresses(&mut bytes.as_slice()).unwrap()
+    }
+
+    #[test]
+    fn round_trip_sorts_addresses() {
+        let addresses = [
+            relocatable!(1, 300),
+            relocatable!(0, 2),
+            relocatable!(-1, 4),
+            relocatable!(1, 0),
+            relocatable!(0, 0),
+        ];
+        assert_eq!(
+            round_trip(&addresses),
+            vec![
+                relocatable!(-1, 4),
+                relocatable!(0, 0),
+                relocatable!(0, 2),
+                relocatable!(1, 0),
+                relocatable!(1, 300),
+            ]
+        );
+    }
+
+    #[test]
+    fn round_trip_empty() {
+        assert_eq!(round_trip(&[]), vec![]);
+    }
+
+    #[test]
+    fn consecutive_addresses_take_two_bytes_each() {
+        let addresses: Vec<_> = (0..100).map(|offset| relocatable!(1, offset)).collect();
+        let mut bytes = Vec::new();
+        write_accessed_addresses(&mut bytes, addresses.iter()).unwrap();
+        //The count, then the jump to segment 1 and one byte per delta
+        assert_eq!(bytes.len(), 1 + 2 * 100);
+        assert_eq!(&bytes[..5], &[100, 2, 0, 0, 1]);
+    }
+
+    #[test]
+    fn zigzag_round_trip() {
+        for value in [0, 1, -1, 2, -2, i64::MAX, i64::MIN] {
+            assert_eq!(zigzag_decode(zigzag_encode(value)), value);
+        }
+        assert_eq!(zigzag_encode(-1), 1);
+        assert_eq!(zigzag_encode(1), 2);
+    }
+
+    #[test]
+    fn read_truncated_data() {
+        let mut bytes = Vec::new();
+        write_accessed_addresses(&mut bytes, [relocatable!(1, 200)].iter()).unwrap();
+        bytes.pop();
+        assert_eq!(
+            read_accessed_addresses(&mut bytes.as_slice())
+                .unwrap_err()
+                .kind(),
+            io::ErrorKind::InvalidData
+        );
+    }
+}
@@ -0,0 +1,83 @@
+use felt::{Felt, PRIME_STR};
+use num_bigint::BigUint;
+use num_traits::Num;
+
+///Read-only constants of a run, given to the custom hints registered in the BuiltinHintProcessor
+///along with the VM. Built by CairoRunner::initialize_vm, a VM which wasn't initialized by a runner
+///only knows the prime.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VmConstView {
+    prime: BigUint,
+    rc_bound: Option<Felt>,
+    layout: String,
+    program_hash: Option<Felt>,
+}
+
+impl VmConstView {
+    pub(crate) fn new(rc_bound: Option<Felt>, layout: &str, program_hash: Option<Felt>) -> Self {
+        VmConstView {
+            prime: prime(),
+            rc_bound,
+            layout: layout.to_string(),
+            program_hash,
+        }
+    }
+
+    ///The prime of the field the program runs on
+    pub fn prime(&self) -> &BigUint {
+        &self.prime
+    }
+
+    ///The bound checked by the range check builtin, if the run has one
+    pub fn rc_bound(&self) -> Option<&Felt> {
+        self.rc_bound.as_ref()
+    }
+
+    ///The name of the layout the program runs with, empty if the VM wasn't initialized by a runner
+    pub fn layout(&self) -> &str {
+        &self.layout
+    }
+
+    ///The hash of the program, if it was computed before the run was initialized, see
+    ///CairoRunner::compute_program_hash
+    pub fn program_hash(&self) -> Option<&Felt> {
+        self.program_hash.as_ref()
+    }
+}
+
+impl Default for VmConstView {
+    fn default() -> Self {
+        VmConstView::new(None, "", None)
+    }
+}
+
+fn prime() -> BigUint {
+    BigUint::from_str_radix(&PRIME_STR[2..], 16).expect("PRIME_STR is a valid hex number")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use felt::NewFelt;
+    use num_traits::One;
+
+    #[test]
+    fn default_view_only_knows_the_prime() {
+        let view = VmConstView::default();
+        assert_eq!(
+            view.prime(),
+            &((BigUint::one() << 251) + (BigUint::from(17_u32) << 192) + 1_u32)
+        );
+        assert_eq!(view.rc_bound(), None);
+        assert_eq!(view.layout(), "");
+        assert_eq!(view.program_hash(), None);
+    }
+
+    #[test]
+    fn view_keeps_the_run_constants() {
+        let view = VmConstView::new(Some(Felt::new(1_u128 << 64)), "small", Some(Felt::new(7)));
+        assert_eq!(view.rc_bound(), Some(&Felt::new(1_u128 << 64)));
+        assert_eq!(view.layout(), "small");
+        assert_eq!(view.program_hash(), Some(&Felt::new(7)));
+    }
+}
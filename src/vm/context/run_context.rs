@@ -3,24 +3,29 @@ use crate::{
         instruction::{Instruction, Op1Addr, Register},
         relocatable::{MaybeRelocatable, Relocatable},
     },
-    vm::errors::{
-        memory_errors::MemoryError::AddressNotRelocatable, vm_errors::VirtualMachineError,
+    vm::{
+        errors::{
+            memory_errors::MemoryError::{self, AddressNotRelocatable},
+            vm_errors::VirtualMachineError,
+        },
+        state_transition::Registers,
     },
 };
-use num_traits::ToPrimitive;
 
 pub struct RunContext {
     pub(crate) pc: Relocatable,
     pub(crate) ap: usize,
     pub(crate) fp: usize,
+    ///Segment ap and fp point into, 1 unless the runner placed the execution segment elsewhere
+    pub(crate) execution_segment: isize,
 }
 
 impl RunContext {
     pub fn get_ap(&self) -> Relocatable {
-        Relocatable::from((1, self.ap))
+        Relocatable::from((self.execution_segment, self.ap))
     }
     pub fn get_fp(&self) -> Relocatable {
-        Relocatable::from((1, self.fp))
+        Relocatable::from((self.execution_segment, self.fp))
     }
     pub fn get_pc(&self) -> &Relocatable {
         &self.pc
@@ -30,34 +35,22 @@ impl RunContext {
         &self,
         instruction: &Instruction,
     ) -> Result<Relocatable, VirtualMachineError> {
-        let base_addr = match instruction.dst_register {
-            Register::AP => self.get_ap(),
-            Register::FP => self.get_fp(),
+        let (register, base_addr) = match instruction.dst_register {
+            Register::AP => ("ap", self.get_ap()),
+            Register::FP => ("fp", self.get_fp()),
         };
-        let new_offset = instruction.off0 + base_addr.offset as isize;
-        Ok(Relocatable::from((
-            base_addr.segment_index,
-            new_offset
-                .to_usize()
-                .ok_or(VirtualMachineError::BigintToUsizeFail)?,
-        )))
+        offset_address(register, &base_addr, instruction.off0)
     }
 
     pub fn compute_op0_addr(
         &self,
         instruction: &Instruction,
     ) -> Result<Relocatable, VirtualMachineError> {
-        let base_addr = match instruction.op0_register {
-            Register::AP => self.get_ap(),
-            Register::FP => self.get_fp(),
+        let (register, base_addr) = match instruction.op0_register {
+            Register::AP => ("ap", self.get_ap()),
+            Register::FP => ("fp", self.get_fp()),
         };
-        let new_offset = instruction.off1 + base_addr.offset as isize;
-        Ok(Relocatable::from((
-            base_addr.segment_index,
-            new_offset
-                .to_usize()
-                .ok_or(VirtualMachineError::BigintToUsizeFail)?,
-        )))
+        offset_address(register, &base_addr, instruction.off1)
     }
 
     pub fn compute_op1_addr(
@@ -65,26 +58,29 @@ impl RunContext {
         instruction: &Instruction,
         op0: Option<&MaybeRelocatable>,
     ) -> Result<Relocatable, VirtualMachineError> {
-        let base_addr = match instruction.op1_addr {
-            Op1Addr::FP => self.get_fp(),
-            Op1Addr::AP => self.get_ap(),
+        let (register, base_addr) = match instruction.op1_addr {
+            Op1Addr::FP => ("fp", self.get_fp()),
+            Op1Addr::AP => ("ap", self.get_ap()),
             Op1Addr::Imm => match instruction.off2 == 1 {
-                true => self.pc,
+                true => ("pc", self.pc),
                 false => return Err(VirtualMachineError::ImmShouldBe1),
             },
             Op1Addr::Op0 => match op0 {
-                Some(MaybeRelocatable::RelocatableValue(addr)) => *addr,
+                Some(MaybeRelocatable::RelocatableValue(addr)) => ("op0", *addr),
                 Some(_) => return Err(VirtualMachineError::MemoryError(AddressNotRelocatable)),
                 None => return Err(VirtualMachineError::UnknownOp0),
             },
         };
-        let new_offset = instruction.off2 + base_addr.offset as isize;
-        Ok(Relocatable::from((
-            base_addr.segment_index,
-            new_offset
-                .to_usize()
-                .ok_or(VirtualMachineError::BigintToUsizeFail)?,
-        )))
+        offset_address(register, &base_addr, instruction.off2)
+    }
+
+    ///Returns the current values of pc, ap and fp
+    pub fn registers(&self) -> Registers {
+        Registers {
+            pc: self.pc,
+            ap: self.get_ap(),
+            fp: self.get_fp(),
+        }
     }
 
     #[doc(hidden)]
@@ -103,6 +99,22 @@ impl RunContext {
     }
 }
 
+///Adds an instruction offset to the address held by register, failing if the resulting offset is negative
+fn offset_address(
+    register: &'static str,
+    base_addr: &Relocatable,
+    offset: isize,
+) -> Result<Relocatable, VirtualMachineError> {
+    let new_offset = (base_addr.offset as isize)
+        .checked_add(offset)
+        .filter(|new_offset| !new_offset.is_negative())
+        .ok_or(MemoryError::AddressUnderflow(register, offset))?;
+    Ok(Relocatable::from((
+        base_addr.segment_index,
+        new_offset as usize,
+    )))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -118,7 +130,6 @@ mod tests {
             off0: 1,
             off1: 2,
             off2: 3,
-            imm: None,
             dst_register: Register::AP,
             op0_register: Register::FP,
             op1_addr: Op1Addr::AP,
@@ -133,6 +144,7 @@ mod tests {
             pc: relocatable!(0, 4),
             ap: 5,
             fp: 6,
+            execution_segment: 1,
         };
         assert_eq!(
             Ok(relocatable!(1, 6)),
@@ -146,7 +158,6 @@ mod tests {
             off0: 1,
             off1: 2,
             off2: 3,
-            imm: None,
             dst_register: Register::FP,
             op0_register: Register::AP,
             op1_addr: Op1Addr::AP,
@@ -161,6 +172,7 @@ mod tests {
             pc: relocatable!(0, 4),
             ap: 5,
             fp: 6,
+            execution_segment: 1,
         };
         assert_eq!(
             Ok(relocatable!(1, 7)),
@@ -168,13 +180,42 @@ mod tests {
         );
     }
 
+    #[test]
+    fn compute_dst_addr_fp_underflow() {
+        let instruction = Instruction {
+            off0: -3,
+            off1: 2,
+            off2: 3,
+            dst_register: Register::FP,
+            op0_register: Register::AP,
+            op1_addr: Op1Addr::AP,
+            res: Res::Add,
+            pc_update: PcUpdate::Regular,
+            ap_update: ApUpdate::Regular,
+            fp_update: FpUpdate::Regular,
+            opcode: Opcode::NOp,
+        };
+
+        let run_context = RunContext {
+            pc: relocatable!(0, 4),
+            ap: 5,
+            fp: 1,
+            execution_segment: 1,
+        };
+        assert_eq!(
+            Err(VirtualMachineError::MemoryError(
+                MemoryError::AddressUnderflow("fp", -3)
+            )),
+            run_context.compute_dst_addr(&instruction)
+        );
+    }
+
     #[test]
     fn compute_op0_addr_for_ap_register() {
         let instruction = Instruction {
             off0: 1,
             off1: 2,
             off2: 3,
-            imm: None,
             dst_register: Register::AP,
             op0_register: Register::AP,
             op1_addr: Op1Addr::AP,
@@ -189,6 +230,7 @@ mod tests {
             pc: relocatable!(0, 4),
             ap: 5,
             fp: 6,
+            execution_segment: 1,
         };
         assert_eq!(
             Ok(relocatable!(1, 7)),
@@ -202,7 +244,6 @@ mod tests {
             off0: 1,
             off1: 2,
             off2: 3,
-            imm: None,
             dst_register: Register::FP,
             op0_register: Register::FP,
             op1_addr: Op1Addr::AP,
@@ -217,6 +258,7 @@ mod tests {
             pc: relocatable!(0, 4),
             ap: 5,
             fp: 6,
+            execution_segment: 1,
         };
         assert_eq!(
             Ok(relocatable!(1, 8)),
@@ -230,7 +272,6 @@ mod tests {
             off0: 1,
             off1: 2,
             off2: 3,
-            imm: None,
             dst_register: Register::FP,
             op0_register: Register::AP,
             op1_addr: Op1Addr::FP,
@@ -245,6 +286,7 @@ mod tests {
             pc: relocatable!(0, 4),
             ap: 5,
             fp: 6,
+            execution_segment: 1,
         };
         assert_eq!(
             Ok(relocatable!(1, 9)),
@@ -258,7 +300,6 @@ mod tests {
             off0: 1,
             off1: 2,
             off2: 3,
-            imm: None,
             dst_register: Register::FP,
             op0_register: Register::AP,
             op1_addr: Op1Addr::AP,
@@ -273,6 +314,7 @@ mod tests {
             pc: relocatable!(0, 4),
             ap: 5,
             fp: 6,
+            execution_segment: 1,
         };
         assert_eq!(
             Ok(relocatable!(1, 8)),
@@ -286,7 +328,6 @@ mod tests {
             off0: 1,
             off1: 2,
             off2: 1,
-            imm: None,
             dst_register: Register::FP,
             op0_register: Register::AP,
             op1_addr: Op1Addr::Imm,
@@ -301,6 +342,7 @@ mod tests {
             pc: relocatable!(0, 4),
             ap: 5,
             fp: 6,
+            execution_segment: 1,
         };
         assert_eq!(
             Ok(relocatable!(0, 5)),
@@ -314,7 +356,6 @@ mod tests {
             off0: 1,
             off1: 2,
             off2: 3,
-            imm: None,
             dst_register: Register::FP,
             op0_register: Register::AP,
             op1_addr: Op1Addr::Imm,
@@ -329,6 +370,7 @@ mod tests {
             pc: relocatable!(0, 4),
             ap: 5,
             fp: 6,
+            execution_segment: 1,
         };
 
         let error = run_context.compute_op1_addr(&instruction, None);
@@ -345,7 +387,6 @@ mod tests {
             off0: 1,
             off1: 2,
             off2: 1,
-            imm: None,
             dst_register: Register::FP,
             op0_register: Register::AP,
             op1_addr: Op1Addr::Op0,
@@ -360,6 +401,7 @@ mod tests {
             pc: relocatable!(0, 4),
             ap: 5,
             fp: 6,
+            execution_segment: 1,
         };
 
         let op0 = mayberelocatable!(1, 7);
@@ -369,13 +411,44 @@ mod tests {
         );
     }
 
+    #[test]
+    fn compute_op1_addr_for_op0_op1_addr_underflow() {
+        let instruction = Instruction {
+            off0: 1,
+            off1: 2,
+            off2: -8,
+            dst_register: Register::FP,
+            op0_register: Register::AP,
+            op1_addr: Op1Addr::Op0,
+            res: Res::Add,
+            pc_update: PcUpdate::Regular,
+            ap_update: ApUpdate::Regular,
+            fp_update: FpUpdate::Regular,
+            opcode: Opcode::NOp,
+        };
+
+        let run_context = RunContext {
+            pc: relocatable!(0, 4),
+            ap: 5,
+            fp: 6,
+            execution_segment: 1,
+        };
+
+        let op0 = mayberelocatable!(2, 7);
+        assert_eq!(
+            Err(VirtualMachineError::MemoryError(
+                MemoryError::AddressUnderflow("op0", -8)
+            )),
+            run_context.compute_op1_addr(&instruction, Some(&op0))
+        );
+    }
+
     #[test]
     fn compute_op1_addr_with_no_relocatable_address() {
         let instruction = Instruction {
             off0: 1,
             off1: 2,
             off2: 1,
-            imm: None,
             dst_register: Register::FP,
             op0_register: Register::AP,
             op1_addr: Op1Addr::Op0,
@@ -390,6 +463,7 @@ mod tests {
             pc: relocatable!(0, 4),
             ap: 5,
             fp: 6,
+            execution_segment: 1,
         };
 
         let op0 = MaybeRelocatable::from(Felt::new(7));
@@ -407,7 +481,6 @@ mod tests {
             off0: 1,
             off1: 2,
             off2: 3,
-            imm: None,
             dst_register: Register::FP,
             op0_register: Register::AP,
             op1_addr: Op1Addr::Op0,
@@ -422,6 +495,7 @@ mod tests {
             pc: relocatable!(0, 4),
             ap: 5,
             fp: 6,
+            execution_segment: 1,
         };
 
         let error = run_context.compute_op1_addr(&instruction, None);
@@ -9,10 +9,22 @@ use felt::Felt;
 //  0|  opcode|ap_update|pc_update|res_logic|op1_src|op0_reg|dst_reg
 // 15|14 13 12|    11 10|  9  8  7|     6  5|4  3  2|      1|      0
 
+// Flags start on the 48th bit.
+const FLAGS_OFFSET: i64 = 48;
+const OPCODE_MASK: i64 = 0x7000;
+const OPCODE_OFF: i64 = 12;
+// Opcode value left undefined by the instruction set, reserved for opcode extensions
+const EXTENSION_OPCODE: i64 = 7;
+
+///Returns true if the instruction's opcode flags hold the combination reserved for opcode extensions
+pub fn is_extension_instruction(encoded_instr: i64) -> bool {
+    ((encoded_instr >> FLAGS_OFFSET) & OPCODE_MASK) >> OPCODE_OFF == EXTENSION_OPCODE
+}
+
 /// Decodes an instruction. The encoding is little endian, so flags go from bit 63 to 48.
 pub fn decode_instruction(
     encoded_instr: i64,
-    mut imm: Option<&Felt>,
+    imm: Option<&Felt>,
 ) -> Result<Instruction, VirtualMachineError> {
     const DST_REG_MASK: i64 = 0x0001;
     const DST_REG_OFF: i64 = 0;
@@ -26,11 +38,7 @@ pub fn decode_instruction(
     const PC_UPDATE_OFF: i64 = 7;
     const AP_UPDATE_MASK: i64 = 0x0C00;
     const AP_UPDATE_OFF: i64 = 10;
-    const OPCODE_MASK: i64 = 0x7000;
-    const OPCODE_OFF: i64 = 12;
 
-    // Flags start on the 48th bit.
-    const FLAGS_OFFSET: i64 = 48;
     const OFF0_OFF: i64 = 0;
     const OFF1_OFF: i64 = 16;
     const OFF2_OFF: i64 = 32;
@@ -73,12 +81,8 @@ pub fn decode_instruction(
         _ => return Err(VirtualMachineError::InvalidOp1Reg(op1_src_num)),
     };
 
-    if op1_addr == Op1Addr::Imm {
-        if imm.is_none() {
-            return Err(VirtualMachineError::NoImm);
-        }
-    } else {
-        imm = None
+    if op1_addr == Op1Addr::Imm && imm.is_none() {
+        return Err(VirtualMachineError::NoImm);
     }
 
     let pc_update = match pc_update_num {
@@ -123,7 +127,6 @@ pub fn decode_instruction(
         off0,
         off1,
         off2,
-        imm: imm.cloned(),
         dst_register,
         op0_register,
         op1_addr,
@@ -179,6 +182,16 @@ mod decoder_test {
         assert_eq!(error.unwrap_err().to_string(), "Invalid opcode value: 3")
     }
 
+    #[test]
+    fn extension_opcode() {
+        assert!(is_extension_instruction(0x7000800080008000));
+        assert!(!is_extension_instruction(0x3948800080008000));
+        assert_eq!(
+            decode_instruction(0x7000800080008000, None),
+            Err(VirtualMachineError::InvalidOpcode(7))
+        );
+    }
+
     #[test]
     fn invalid_ap_update() {
         let error = decode_instruction(0x2D48800080008000, None);
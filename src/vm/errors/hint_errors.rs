@@ -16,6 +16,8 @@ pub enum HintError {
     NoRegisterInReference,
     #[error("Custom Hint Error: {0}")]
     CustomHint(String),
+    #[error("Value {0} exceeds the limit {2} = {1}")]
+    ValueExceedsLimit(Felt, Felt, String),
     #[error("Missing constant: {0}")]
     MissingConstant(&'static str),
     #[error("Fail to get constants for hint execution")]
@@ -40,8 +42,6 @@ pub enum HintError {
     NoValueForKeyFindElement(Felt),
     #[error("Assertion failed, a = {0} % PRIME is not less than b = {1} % PRIME")]
     AssertLtFelt(Felt, Felt),
-    #[error("find_element() can only be used with n_elms <= {0}.\nGot: n_elms = {1}")]
-    FindElemMaxSize(Felt, Felt),
     #[error(
         "Invalid index found in find_element_index. Index: {0}.\nExpected key: {1}, found_key {2}"
     )]
@@ -74,16 +74,12 @@ pub enum HintError {
     EmptyKeys,
     #[error("squash_dict fail: Accesses array size must be divisible by DictAccess.SIZE")]
     PtrDiffNotDivisibleByDictAccessSize,
-    #[error("squash_dict() can only be used with n_accesses<={0}. ' \nGot: n_accesses={1}")]
-    SquashDictMaxSizeExceeded(Felt, Felt),
     #[error("squash_dict fail: n_accesses: {0} is too big to be converted into an iterator")]
     NAccessesTooBig(Felt),
     #[error(transparent)]
     Internal(#[from] VirtualMachineError),
     #[error("Couldn't convert BigInt to usize")]
     BigintToUsizeFail,
-    #[error("usort() can only be used with input_len<={0}. Got: input_len={1}.")]
-    UsortOutOfRange(u64, Felt),
     #[error("unexpected usort fail: positions_dict or key value pair not found")]
     UnexpectedPositionsDictFail,
     #[error("unexpected verify multiplicity fail: positions not found")]
@@ -108,8 +104,6 @@ pub enum HintError {
     SecpSplitOutOfRange(BigUint),
     #[error("verify_zero: Invalid input {0}")]
     SecpVerifyZero(BigInt),
-    #[error("unsafe_keccak() can only be used with length<={0}. Got: length={1}")]
-    KeccakMaxSize(Felt, Felt),
     #[error("Invalid word size: {0}")]
     InvalidWordSize(Felt),
     #[error("Invalid input length, Got: length={0}")]
@@ -144,4 +138,34 @@ pub enum HintError {
     NonLeFelt(Felt, Felt),
     #[error("Unknown Hint: {0}")]
     UnknownHint(String),
+    #[error("Hints are disallowed in hint-less mode, got: {0}")]
+    HintsDisallowed(String),
+    #[error("Unknown identifier in expression hint: ids.{0}")]
+    UnknownExpressionIdentifier(String),
+    #[error("Expected ids.{0} to be an integer in expression hint")]
+    ExpressionIdentifierNotInteger(String),
+    #[error("Struct {0} has no member {1}")]
+    UnknownStructMember(String, String),
+    #[error("Shift count out of range in expression hint: {0}")]
+    ExpressionShiftOutOfRange(BigInt),
+    #[error("Expected a pointer at hop {1} of ids.{0}, found none at {2}")]
+    NestedExpectedPointer(String, usize, Relocatable),
+    #[error("Expected an integer at hop {1} of ids.{0}, found none at {2}")]
+    NestedExpectedInteger(String, usize, Relocatable),
+    #[error("Hint wasn't registered for the program: {0}")]
+    HintNotRegisteredForProgram(String),
+}
+
+///Wraps an error returned by a hint during the step loop, recording which hint failed and where.
+#[derive(Debug, PartialEq, Error)]
+#[error("Got an exception while executing a hint at pc={pc} (`{hint_code_snippet}`): {inner}")]
+pub struct HintException {
+    pub pc: Relocatable,
+    pub hint_index: usize,
+    ///First line of the failing hint's code, empty if the hint processor doesn't expose it
+    pub hint_code_snippet: String,
+    ///Values of the hint's ids at the time of the failure, sorted by name. These are read after
+    ///the hint returned, so they include any write the hint made before failing
+    pub ids_values_at_failure: Vec<(String, MaybeRelocatable)>,
+    pub inner: HintError,
 }
@@ -6,6 +6,10 @@ use crate::types::relocatable::{MaybeRelocatable, Relocatable};
 pub enum MemoryError {
     #[error("Can't insert into segment #{0}; memory only has {1} segment")]
     UnallocatedSegment(usize, usize),
+    #[error(
+        "Can't read from segment #{0}, it wasn't allocated; memory has {1} segments of its kind"
+    )]
+    UnallocatedSegmentRead(isize, usize),
     #[error("Memory addresses must be relocatable")]
     AddressNotRelocatable,
     #[error("Range-check validation failed, number is out of valid range")]
@@ -26,6 +30,8 @@ pub enum MemoryError {
     AddressNotInTemporarySegment(isize),
     #[error("Temporary segment found while relocating (flattening), segment: {0}")]
     TemporarySegmentInRelocation(isize),
+    #[error("Two keys of the dictionary at segment {0} are relocated to {1}")]
+    RelocatedDictKeyCollision(isize, MaybeRelocatable),
     #[error("The TemporarySegment: {0} doesn't have a relocation address")]
     NonZeroOffset(usize),
     #[error("Attempt to overwrite a relocation rule, segment: {0}")]
@@ -62,4 +68,20 @@ pub enum MemoryError {
     ErrorVerifyingSignature,
     #[error("Couldn't obtain a mutable accessed offset")]
     CantGetMutAccessedOffset,
+    #[error("Address computed from {0} with offset {1} is below the start of its segment")]
+    AddressUnderflow(&'static str, isize),
+    #[error("A memory overlay was already started")]
+    OverlayAlreadyStarted,
+    #[error("No memory overlay was started")]
+    NoOverlay,
+    #[error("Memory can't be relocated while an overlay is active")]
+    RelocationDuringOverlay,
+    #[error("Couldn't read the binary memory file: {0}")]
+    BinaryMemoryFileRead(String),
+    #[error(
+        "The binary memory file has {0} bytes, which isn't a multiple of the 40 bytes of a cell"
+    )]
+    BinaryMemoryFileLength(usize),
+    #[error("The binary memory file has address {0} after address {1}, addresses must be strictly increasing")]
+    BinaryMemoryFileUnsorted(usize, usize),
 }
@@ -3,6 +3,7 @@ pub mod exec_scope_errors;
 pub mod hint_errors;
 pub mod memory_errors;
 pub mod runner_errors;
+pub mod security_errors;
 pub mod trace_errors;
 pub mod vm_errors;
 pub mod vm_exception;
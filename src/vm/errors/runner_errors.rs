@@ -1,7 +1,11 @@
 use std::collections::HashSet;
 
 use super::memory_errors::MemoryError;
-use crate::types::relocatable::MaybeRelocatable;
+use crate::types::{
+    builtin_name::BuiltinName,
+    program_lint::LintIssue,
+    relocatable::{MaybeRelocatable, Relocatable},
+};
 use felt::Felt;
 use thiserror::Error;
 
@@ -15,6 +19,8 @@ pub enum RunnerError {
     NoProgBase,
     #[error("Missing main()")]
     MissingMain,
+    #[error("The program has no instructions to run")]
+    EmptyProgram,
     #[error("Uninitialized base for builtin")]
     UninitializedBase,
     #[error("Base for builtin is not finished")]
@@ -54,7 +60,7 @@ pub enum RunnerError {
     #[error("EcOpBuiltin: point {0:?} is not on the curve")]
     PointNotOnCurve((usize, usize)),
     #[error("Builtin(s) {0:?} not present in layout {1}")]
-    NoBuiltinForInstance(HashSet<String>, String),
+    NoBuiltinForInstance(HashSet<BuiltinName>, String),
     #[error("Invalid layout {0}")]
     InvalidLayoutName(String),
     #[error("Run has already ended.")]
@@ -62,17 +68,19 @@ pub enum RunnerError {
     #[error("Run must be ended before calling finalize_segments.")]
     FinalizeNoEndRun,
     #[error("Builtin {0} not included.")]
-    BuiltinNotIncluded(String),
+    BuiltinNotIncluded(BuiltinName),
     #[error("Builtin segment name collision on '{0}'")]
-    BuiltinSegmentNameCollision(&'static str),
+    BuiltinSegmentNameCollision(BuiltinName),
     #[error("Error while finalizing segments: {0}")]
     FinalizeSegements(MemoryError),
     #[error("finalize_segments called but proof_mode is not enabled")]
     FinalizeSegmentsNoProofMode,
     #[error("Final stack error")]
     FinalStack,
-    #[error("Invalid stop pointer for {0} ")]
-    InvalidStopPointer(String),
+    #[error("Invalid stop pointer for {0}: the program returned {1}, but {2} instances were used, so {3} was expected")]
+    InvalidStopPointer(BuiltinName, Relocatable, usize, Relocatable),
+    #[error("The stop pointer of {0} wasn't set, the run wasn't finalized")]
+    NoStopPointer(BuiltinName),
     #[error("Running in proof-mode but no __start__ label found, try compiling with proof-mode")]
     NoProgramStart,
     #[error("Running in proof-mode but no __end__ label found, try compiling with proof-mode")]
@@ -80,7 +88,9 @@ pub enum RunnerError {
     #[error("Could not convert slice to array")]
     SliceToArrayError,
     #[error("Missing builtin: {0}")]
-    MissingBuiltin(String),
+    MissingBuiltin(BuiltinName),
+    #[error("Builtin {0} was already added")]
+    DuplicateBuiltin(BuiltinName),
     #[error("Cannot add the return values to the public memory after segment finalization.")]
     FailedAddingReturnValues,
     #[error("Missing execution public memory")]
@@ -95,4 +105,22 @@ pub enum RunnerError {
     SafeDivFailUsize(usize, usize),
     #[error(transparent)]
     MemoryError(#[from] MemoryError),
+    #[error("Function {0} not found, near misses: {1:?}")]
+    FunctionNotFound(String, Vec<String>),
+    #[error("Segment arena allocated {0} segments but its final state counts {1}")]
+    SegmentArenaCountMismatch(usize, usize),
+    #[error("Invalid segment arena info at index {0}: its end must follow its start in the same segment")]
+    InvalidSegmentArenaInfo(usize),
+    #[error("Program lint found errors: {0:?}")]
+    ProgramLint(Vec<LintIssue>),
+    #[error("Output verification failed: {0}")]
+    OutputVerificationFailed(String),
+    #[error("{0}: n must be at least 1, the instance at {1} has n = 0")]
+    ModBuiltinNLessThanOne(BuiltinName, Relocatable),
+    #[error("Couldn't fill the mod builtins memory: stuck at add_mod operation {0} and mul_mod operation {1}")]
+    ModBuiltinFillMemory(usize, usize),
+    #[error("{0}: instance {1} doesn't continue the batch of the previous instance")]
+    ModBuiltinBatchMismatch(BuiltinName, usize),
+    #[error("{0}: operation {1} doesn't hold modulo p")]
+    ModBuiltinSecurityCheck(BuiltinName, usize),
 }
@@ -0,0 +1,18 @@
+use crate::types::{
+    builtin_name::BuiltinName,
+    relocatable::{MaybeRelocatable, Relocatable},
+};
+use thiserror::Error;
+
+///A check of verify_secure_runner that the run didn't pass
+#[derive(Debug, PartialEq, Eq, Error)]
+pub enum SecurityError {
+    #[error("Cell {0} is past the end of the program's data, it holds {1}")]
+    ProgramOutOfBounds(Relocatable, MaybeRelocatable),
+    #[error("Cell {1} is past the end of the {0} builtin's segment, it holds {2}")]
+    BuiltinOutOfBounds(BuiltinName, Relocatable, MaybeRelocatable),
+    #[error("Range check cell {0} holds {1}, which isn't an integer below {2}")]
+    RangeCheckOutOfBounds(Relocatable, MaybeRelocatable, MaybeRelocatable),
+    #[error("Address {0} was accessed but isn't in any of the allocated segments")]
+    AccessOutOfBounds(Relocatable),
+}
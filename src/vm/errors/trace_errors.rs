@@ -1,4 +1,5 @@
-use crate::vm::errors::memory_errors::MemoryError;
+use crate::vm::errors::{memory_errors::MemoryError, vm_errors::VirtualMachineError};
+use felt::{Felt, PRIME_STR};
 use thiserror::Error;
 
 #[derive(Debug, PartialEq, Eq, Error)]
@@ -14,3 +15,25 @@ pub enum TraceError {
     #[error(transparent)]
     MemoryError(#[from] MemoryError),
 }
+
+#[derive(Debug, PartialEq, Error)]
+pub enum TraceVerificationError {
+    #[error("Expected prime {PRIME_STR}, got {0}")]
+    PrimeDiffers(String),
+    #[error("Step {0}: no instruction at pc {1}")]
+    MissingInstruction(usize, usize),
+    #[error("Step {0}: invalid instruction at pc {1}")]
+    InvalidInstruction(usize, usize),
+    #[error("Step {0}: invalid {1} address")]
+    InvalidOperandAddress(usize, &'static str),
+    #[error("Step {0}: {1} at address {2} is missing from memory")]
+    MissingOperand(usize, &'static str, usize),
+    #[error("Step {0}: assert_eq failed, dst is {1} but res is {2}")]
+    AssertEqFailed(usize, Felt, Felt),
+    #[error("Step {0}: call didn't store the return pc and fp")]
+    InvalidCall(usize),
+    #[error("Step {0}: couldn't compute the next registers: {1}")]
+    RegisterUpdate(usize, VirtualMachineError),
+    #[error("Step {0}: {1} is {3}, expected {2}")]
+    RegisterMismatch(usize, &'static str, usize, usize),
+}
@@ -1,12 +1,17 @@
 use crate::{
-    types::relocatable::{MaybeRelocatable, Relocatable},
+    types::{
+        builtin_name::BuiltinName,
+        instruction::{Register, VmRegister},
+        relocatable::{MaybeRelocatable, Relocatable},
+    },
     vm::errors::{
-        exec_scope_errors::ExecScopeError, hint_errors::HintError, memory_errors::MemoryError,
-        runner_errors::RunnerError, trace_errors::TraceError,
+        exec_scope_errors::ExecScopeError, hint_errors::HintException, memory_errors::MemoryError,
+        runner_errors::RunnerError, security_errors::SecurityError, trace_errors::TraceError,
     },
 };
 use felt::Felt;
 use num_bigint::{BigInt, BigUint};
+use std::fmt;
 use thiserror::Error;
 
 #[derive(Debug, PartialEq, Error)]
@@ -43,6 +48,8 @@ pub enum VirtualMachineError {
     NoDst,
     #[error("Pure Value Error")]
     PureValue,
+    #[error("The jump offset of a taken jnz must be an integer, got {0}")]
+    JumpOffsetNotInt(MaybeRelocatable),
     #[error("Invalid res value: {0}")]
     InvalidRes(i64),
     #[error("Invalid opcode value: {0}")]
@@ -51,14 +58,30 @@ pub enum VirtualMachineError {
     RelocatableAdd,
     #[error("Offset {0} exceeds maximum offset value")]
     OffsetExceeded(Felt),
-    #[error("This is not implemented")]
-    NotImplemented,
+    #[error("Cannot subtract relocatable {1} from integer {0}")]
+    SubRelocatableFromInt(Felt, Relocatable),
+    #[error("Cannot divmod {0} by {1}, both values must be integers")]
+    DivModNonInt(MaybeRelocatable, MaybeRelocatable),
+    #[error("{0} received an argument of an unsupported type")]
+    UnsupportedArgType(&'static str),
     #[error("Can only subtract two relocatable values of the same segment")]
     DiffIndexSub,
-    #[error("Inconsistent auto-deduction for builtin {0}, expected {1}, got {2:?}")]
-    InconsistentAutoDeduction(String, MaybeRelocatable, Option<MaybeRelocatable>),
+    #[error("Inconsistent auto-deduction at {0}, expected {1}, got {2:?}")]
+    InconsistentAutoDeduction(Relocatable, MaybeRelocatable, Option<MaybeRelocatable>),
+    #[error(
+        "The {0} builtin instance at {1} has deduced cells but some of its inputs are missing"
+    )]
+    MissingBuiltinInputs(BuiltinName, Relocatable),
+    #[error("Maximum call depth of {0} exceeded")]
+    MaxCallDepthExceeded(usize),
+    #[error("Step hook failed: {0}")]
+    StepHookFailed(String),
+    #[error("Can't set {0:?} to {1}, it must point into the execution segment")]
+    RegisterOutsideExecutionSegment(Register, Relocatable),
     #[error(transparent)]
     RunnerError(#[from] RunnerError),
+    #[error(transparent)]
+    SecurityError(#[from] SecurityError),
     #[error("Invalid hint encoding at pc: {0}")]
     InvalidHintEncoding(MaybeRelocatable),
     #[error(transparent)]
@@ -107,6 +130,8 @@ pub enum VirtualMachineError {
     DividedByZero,
     #[error("Failed to calculate the square root of: {0})")]
     FailedToGetSqrt(BigUint),
+    #[error("Can't raise to the negative power {0}")]
+    NegativeExponent(BigInt),
     #[error("Expected integer, found: {0:?}")]
     ExpectedIntAtRange(Option<MaybeRelocatable>),
     #[error("Could not convert slice to array")]
@@ -119,6 +144,14 @@ pub enum VirtualMachineError {
     CantSubOffset(usize, usize),
     #[error("Execution reached the end of the program. Requested remaining steps: {0}.")]
     EndOfProgram(usize),
+    #[error("Reached the limit of {0} steps before the final pc")]
+    StepsExceeded(usize),
+    #[error("Ran out of resources after {0} steps")]
+    RunResourcesExceeded(usize),
+    #[error("Pc left the program's memory at step {0}: {1}")]
+    PcOutOfMemory(usize, Relocatable),
+    #[error("Step {0} failed: {1}")]
+    StepFailed(usize, Box<VirtualMachineError>),
     #[error(transparent)]
     TracerError(#[from] TraceError),
     #[error(transparent)]
@@ -131,8 +164,37 @@ pub enum VirtualMachineError {
     CouldntParsePrime(String),
     #[error("{0}, {1}")]
     ErrorMessageAttribute(String, Box<VirtualMachineError>),
-    #[error("Got an exception while executing a hint: {1}")]
-    Hint(usize, Box<HintError>),
+    #[error(transparent)]
+    Hint(Box<HintException>),
+    #[error("Register {0:?} left its segment with value {1}")]
+    RegisterOutOfBounds(VmRegister, MaybeRelocatable),
     #[error("Unexpected Failure")]
     Unexpected,
 }
+
+///The operands of a failed instruction along with their addresses, including the ones that were
+///deduced and not yet written to memory
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OperandsDump {
+    pub dst_addr: Relocatable,
+    pub dst: MaybeRelocatable,
+    pub op0_addr: Relocatable,
+    pub op0: MaybeRelocatable,
+    pub op1_addr: Relocatable,
+    pub op1: MaybeRelocatable,
+    pub res: Option<MaybeRelocatable>,
+}
+
+impl fmt::Display for OperandsDump {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Operands: dst=[{}]={}, op0=[{}]={}, op1=[{}]={}, res=",
+            self.dst_addr, self.dst, self.op0_addr, self.op0, self.op1_addr, self.op1
+        )?;
+        match &self.res {
+            Some(res) => write!(f, "{res}"),
+            None => write!(f, "unconstrained"),
+        }
+    }
+}
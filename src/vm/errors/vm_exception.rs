@@ -13,18 +13,77 @@ use crate::{
         hint_processor_utils::get_maybe_relocatable_from_reference,
     },
     serde::deserialize_program::{ApTracking, Attribute, Location, OffsetValue},
-    types::{instruction::Register, relocatable::MaybeRelocatable},
+    types::{
+        instruction::{Instruction, Register},
+        relocatable::{MaybeRelocatable, Relocatable},
+    },
     vm::{runners::cairo_runner::CairoRunner, vm_core::VirtualMachine},
 };
 
-use super::vm_errors::VirtualMachineError;
+use super::vm_errors::{OperandsDump, VirtualMachineError};
 #[derive(Debug, PartialEq, Error)]
 pub struct VmException {
-    pc: usize,
-    inst_location: Option<Location>,
-    inner_exc: VirtualMachineError,
-    error_attr_value: Option<String>,
-    traceback: Option<String>,
+    pub(crate) pc: usize,
+    pub(crate) inst_location: Option<Location>,
+    pub(crate) inner_exc: VirtualMachineError,
+    pub(crate) error_attr_value: Option<String>,
+    pub(crate) traceback: Option<String>,
+    pub(crate) step_context: Option<Box<StepErrorContext>>,
+}
+
+///State of the vm when a step failed, as reported by VirtualMachine::step_ext
+#[derive(Debug, PartialEq, Eq)]
+pub struct StepErrorContext {
+    pub pc: Relocatable,
+    pub ap: Relocatable,
+    pub fp: Relocatable,
+    ///The instruction at pc, if it could be decoded
+    pub instruction: Option<Instruction>,
+    ///The operands that were in memory when the step failed, deduced ones aren't included
+    pub dst: Option<MaybeRelocatable>,
+    pub op0: Option<MaybeRelocatable>,
+    pub op1: Option<MaybeRelocatable>,
+    ///The operands and their addresses, deduced ones included, if the instruction failed after
+    ///computing them, see VirtualMachine::get_failed_operands. They replace dst, op0 and op1
+    ///when displayed.
+    pub operands: Option<OperandsDump>,
+}
+
+impl Display for StepErrorContext {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let operand = |value: &Option<MaybeRelocatable>| match value {
+            Some(value) => value.to_string(),
+            None => String::from("unknown"),
+        };
+        writeln!(f, "pc={}, ap={}, fp={}", self.pc, self.ap, self.fp)?;
+        if let Some(instruction) = &self.instruction {
+            writeln!(
+                f,
+                "Instruction: {:?}, dst=[{:?}{:+}], op0=[{:?}{:+}], op1={:?}{:+}, res={:?}, pc_update={:?}, ap_update={:?}, fp_update={:?}",
+                instruction.opcode,
+                instruction.dst_register,
+                instruction.off0,
+                instruction.op0_register,
+                instruction.off1,
+                instruction.op1_addr,
+                instruction.off2,
+                instruction.res,
+                instruction.pc_update,
+                instruction.ap_update,
+                instruction.fp_update,
+            )?;
+        }
+        match &self.operands {
+            Some(operands) => write!(f, "{operands}"),
+            None => write!(
+                f,
+                "Operands: dst={}, op0={}, op1={}",
+                operand(&self.dst),
+                operand(&self.op0),
+                operand(&self.op1)
+            ),
+        }
+    }
 }
 
 impl VmException {
@@ -35,8 +94,8 @@ impl VmException {
     ) -> Self {
         let pc = vm.run_context.pc.offset;
         let error_attr_value = get_error_attr_value(pc, runner, vm);
-        let hint_index = if let VirtualMachineError::Hint(hint_index, _) = error {
-            Some(hint_index)
+        let hint_index = if let VirtualMachineError::Hint(ref hint_exception) = error {
+            Some(hint_exception.hint_index)
         } else {
             None
         };
@@ -46,8 +105,32 @@ impl VmException {
             inner_exc: error,
             error_attr_value,
             traceback: get_traceback(vm, runner),
+            step_context: vm
+                .get_failed_operands()
+                .map(|_| Box::new(vm.step_error_context())),
+        }
+    }
+
+    ///Builds the VmException for an error returned by the step at the vm's current pc. Unlike
+    ///from_vm_error, it doesn't need the runner, so it has no location, error message attributes
+    ///or traceback, but describes the registers, instruction and operands of the failed step.
+    pub fn from_step_error(vm: &VirtualMachine, error: VirtualMachineError) -> Self {
+        VmException {
+            pc: vm.run_context.pc.offset,
+            inst_location: None,
+            inner_exc: error,
+            error_attr_value: None,
+            traceback: None,
+            step_context: Some(Box::new(vm.step_error_context())),
         }
     }
+
+    ///Returns the state of the vm when the step failed. It's always there for exceptions built
+    ///by from_step_error, while from_vm_error only fills it when the instruction failed after
+    ///computing its operands, see StepErrorContext::operands.
+    pub fn step_context(&self) -> Option<&StepErrorContext> {
+        self.step_context.as_deref()
+    }
 }
 
 pub fn get_error_attr_value(
@@ -72,14 +155,16 @@ pub fn get_location(
     runner: &CairoRunner,
     hint_index: Option<usize>,
 ) -> Option<Location> {
-    let instruction_location = runner.program.instruction_locations.as_ref()?.get(&pc)?;
-    if let Some(index) = hint_index {
-        instruction_location
+    match hint_index {
+        Some(index) => runner
+            .program
+            .instruction_locations
+            .as_ref()?
+            .get(&pc)?
             .hints
             .get(index)
-            .map(|hint_location| hint_location.location.clone())
-    } else {
-        Some(instruction_location.inst.clone())
+            .map(|hint_location| hint_location.location.clone()),
+        None => runner.program.get_location(pc).cloned(),
     }
 }
 
@@ -109,50 +194,58 @@ fn substitute_error_message_references(
     runner: &CairoRunner,
     vm: &VirtualMachine,
 ) -> String {
-    let mut error_msg = error_message_attr.value.clone();
-    if let Some(tracking_data) = &error_message_attr.flow_tracking_data {
-        let mut invalid_references = Vec::<String>::new();
-        // Iterate over the available references and check if one of them is addressed in the error message
-        for (cairo_variable_path, ref_id) in &tracking_data.reference_ids {
-            // Get the cairo variable name from its path ie: __main__.main.x -> x
-            let cairo_variable_name = match cairo_variable_path.rsplit('.').next() {
-                Some(string) => string,
-                None => continue,
-            };
-            // Format the variable name to make it easier to search for and replace in the error message
-            // ie: x -> {x}
-            let formated_variable_name = format!("{{{}}}", cairo_variable_name);
-            // Look for the formated name inside the error message
-            if error_msg.contains(&formated_variable_name) {
-                // Get the value of the cairo variable from its reference id
-                match get_value_from_simple_reference(
-                    *ref_id,
-                    &tracking_data.ap_tracking,
-                    runner,
-                    vm,
-                ) {
-                    Some(cairo_variable) => {
-                        // Replace the value in the error message
-                        error_msg = error_msg
-                            .replace(&formated_variable_name, &format!("{}", cairo_variable))
-                    }
-                    None => {
-                        // If the reference is too complex or ap-based it might lead to a wrong value
-                        // So we append the variable's name to the list of invalid reference
-                        invalid_references.push(cairo_variable_name.to_string());
-                    }
+    let tracking_data = match &error_message_attr.flow_tracking_data {
+        Some(tracking_data) => tracking_data,
+        None => return error_message_attr.value.clone(),
+    };
+    let mut error_msg = String::new();
+    let mut invalid_references = Vec::<String>::new();
+    let mut rest = error_message_attr.value.as_str();
+    // Scan the references in the order they appear in the message
+    while let Some(open) = rest.find('{') {
+        error_msg.push_str(&rest[..open]);
+        rest = &rest[open..];
+        let name_len = rest[1..]
+            .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '.'))
+            .unwrap_or(rest.len() - 1);
+        if name_len == 0 || !rest[1 + name_len..].starts_with('}') {
+            // Not a reference, keep the brace verbatim
+            error_msg.push('{');
+            rest = &rest[1..];
+            continue;
+        }
+        let cairo_variable_name = &rest[1..1 + name_len];
+        let reference = &rest[..name_len + 2];
+        rest = &rest[name_len + 2..];
+        // Get the reference id of the cairo variable from its path ie: __main__.main.x -> x
+        let ref_id = tracking_data
+            .reference_ids
+            .iter()
+            .find(|(cairo_variable_path, _)| {
+                cairo_variable_path.rsplit('.').next() == Some(cairo_variable_name)
+            })
+            .map(|(_, ref_id)| *ref_id);
+        match ref_id.and_then(|ref_id| {
+            get_value_from_simple_reference(ref_id, &tracking_data.ap_tracking, runner, vm)
+        }) {
+            Some(cairo_variable) => error_msg.push_str(&cairo_variable.to_string()),
+            None => {
+                error_msg.push_str(reference);
+                // If the reference is too complex or ap-based it might lead to a wrong value
+                // So we append the variable's name to the list of invalid reference
+                if ref_id.is_some() {
+                    invalid_references.push(format!("'{cairo_variable_name}'"));
                 }
             }
         }
-        if !invalid_references.is_empty() {
-            // Add the invalid references (if any) to the error_msg
-            error_msg.push_str(&format!(
-                " (Cannot evaluate ap-based or complex references: [{}])",
-                invalid_references
-                    .iter()
-                    .fold(String::new(), |acc, arg| acc + &format!("'{}'", arg))
-            ));
-        }
+    }
+    error_msg.push_str(rest);
+    if !invalid_references.is_empty() {
+        // Add the invalid references (if any) to the error_msg
+        error_msg.push_str(&format!(
+            " (Cannot evaluate ap-based or complex references: [{}])",
+            invalid_references.join(", ")
+        ));
     }
     error_msg
 }
@@ -188,7 +281,12 @@ fn get_value_from_simple_reference(
 impl Display for VmException {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         // Build initial message
-        let message = format!("Error at pc=0:{}:\n{}", self.pc, self.inner_exc);
+        let mut message = format!("Error at pc=0:{}:\n{}", self.pc, self.inner_exc);
+        //Located errors point at the failing code instead, the context stays available through
+        //step_context
+        if let (Some(step_context), None) = (&self.step_context, &self.inst_location) {
+            message.push_str(&format!("\n{}", step_context));
+        }
         let mut error_msg = String::new();
         // Add error attribute value
         if let Some(ref string) = self.error_attr_value {
@@ -271,16 +369,19 @@ impl Location {
 }
 #[cfg(test)]
 mod test {
+    use felt::{Felt, NewFelt};
     use std::collections::HashMap;
     use std::path::Path;
 
     use crate::hint_processor::builtin_hint_processor::builtin_hint_processor_definition::BuiltinHintProcessor;
     use crate::serde::deserialize_program::{
-        Attribute, HintLocation, InputFile, InstructionLocation,
+        Attribute, FlowTrackingData, HintLocation, InputFile, InstructionLocation, Reference,
+        ReferenceManager, ValueAddress,
     };
     use crate::types::program::Program;
     use crate::types::relocatable::Relocatable;
     use crate::utils::test_utils::*;
+    use crate::vm::{errors::memory_errors::MemoryError, vm_memory::memory::Memory};
 
     use super::*;
     #[test]
@@ -299,6 +400,7 @@ mod test {
         let instruction_location = InstructionLocation {
             inst: location.clone(),
             hints: vec![],
+            accessible_scopes: vec![],
         };
         let program =
             program!(instruction_locations = Some(HashMap::from([(pc, instruction_location)])),);
@@ -309,6 +411,7 @@ mod test {
             inner_exc: VirtualMachineError::NoImm,
             error_attr_value: None,
             traceback: None,
+            step_context: None,
         };
         assert_eq!(
             VmException::from_vm_error(&runner, &vm!(), VirtualMachineError::NoImm,),
@@ -316,6 +419,55 @@ mod test {
         )
     }
 
+    #[test]
+    fn get_vm_exception_from_vm_error_with_failed_operands() {
+        let location = Location {
+            end_line: 2,
+            end_col: 2,
+            input_file: InputFile {
+                filename: String::from("Folder/file.cairo"),
+            },
+            parent_location: None,
+            start_line: 1,
+            start_col: 1,
+        };
+        let instruction_location = InstructionLocation {
+            inst: location,
+            hints: vec![],
+            accessible_scopes: vec![],
+        };
+        let program =
+            program!(instruction_locations = Some(HashMap::from([(0, instruction_location)])),);
+        let runner = cairo_runner!(program);
+        let mut vm = vm!();
+        vm.run_context.ap = 2;
+        vm.run_context.fp = 2;
+        //[ap - 1] = 2, with [ap - 1] = 1
+        vm.memory = memory![((0, 0), 0x400680017fff7fff_i64), ((0, 1), 2), ((1, 1), 1)];
+        let error = vm.step_instruction().unwrap_err();
+        let error_string = error.to_string();
+        let vm_excep = VmException::from_vm_error(&runner, &vm, error);
+        assert_eq!(
+            vm_excep.inner_exc,
+            VirtualMachineError::DiffAssertValues(
+                MaybeRelocatable::from(Felt::new(1)),
+                MaybeRelocatable::from(Felt::new(2))
+            )
+        );
+        assert_eq!(
+            vm_excep
+                .step_context()
+                .and_then(|step_context| step_context.operands.as_ref()),
+            vm.get_failed_operands()
+        );
+        assert!(vm.get_failed_operands().is_some());
+        //The operands aren't part of the located message
+        assert_eq!(
+            vm_excep.to_string(),
+            format!("Folder/file.cairo:1:1: Error at pc=0:0:\n{error_string}\n")
+        );
+    }
+
     #[test]
     fn location_to_string_no_message() {
         let location = Location {
@@ -365,6 +517,7 @@ mod test {
             ),
             error_attr_value: None,
             traceback: None,
+            step_context: None,
         };
         assert_eq!(
             vm_excep.to_string(),
@@ -389,6 +542,7 @@ mod test {
             ),
             error_attr_value: Some(String::from("Error message: Block may fail\n")),
             traceback: None,
+            step_context: None,
         };
         assert_eq!(
             vm_excep.to_string(),
@@ -423,6 +577,7 @@ mod test {
             ),
             error_attr_value: None,
             traceback: None,
+            step_context: None,
         };
         assert_eq!(
             vm_excep.to_string(),
@@ -469,6 +624,7 @@ mod test {
             ),
             error_attr_value: None,
             traceback: None,
+            step_context: None,
         };
         assert_eq!(
             vm_excep.to_string(),
@@ -527,6 +683,7 @@ mod test {
         let instruction_location = InstructionLocation {
             inst: location.clone(),
             hints: vec![],
+            accessible_scopes: vec![],
         };
         let program =
             program!(instruction_locations = Some(HashMap::from([(2, instruction_location)])),);
@@ -549,6 +706,7 @@ mod test {
         let instruction_location = InstructionLocation {
             inst: location,
             hints: vec![],
+            accessible_scopes: vec![],
         };
         let program =
             program!(instruction_locations = Some(HashMap::from([(2, instruction_location)])),);
@@ -585,6 +743,7 @@ mod test {
         let instruction_location = InstructionLocation {
             inst: location_a,
             hints: vec![hint_location],
+            accessible_scopes: vec![],
         };
         let program =
             program!(instruction_locations = Some(HashMap::from([(2, instruction_location)])),);
@@ -770,7 +929,7 @@ cairo_programs/bad_programs/bad_range_check.cairo:11:5: (pc=0:6)
     #[test]
     fn run_bad_usort_and_check_error_displayed() {
         let expected_error_string = r#"cairo_programs/bad_programs/bad_usort.cairo:79:5: Error at pc=0:75:
-Got an exception while executing a hint: unexpected verify multiplicity fail: positions length != 0
+Got an exception while executing a hint at pc=0:75 (`assert len(positions) == 0`): unexpected verify multiplicity fail: positions length != 0
     %{ assert len(positions) == 0 %}
     ^******************************^
 Cairo traceback (most recent call last):
@@ -879,4 +1038,78 @@ cairo_programs/bad_programs/bad_usort.cairo:64:5: (pc=0:60)
             )
         );
     }
+
+    //Returns a program whose references are [fp], [ap - 1] and [ap - 2], all felts
+    fn program_with_felt_references() -> Program {
+        let reference = |register, offset| Reference {
+            ap_tracking_data: ApTracking::default(),
+            pc: Some(0),
+            value_address: ValueAddress {
+                offset1: OffsetValue::Reference(register, offset, false),
+                offset2: OffsetValue::Value(0),
+                dereference: true,
+                value_type: String::from("felt"),
+            },
+        };
+        program!(
+            reference_manager = ReferenceManager {
+                references: vec![
+                    reference(Register::FP, 0),
+                    reference(Register::AP, -1),
+                    reference(Register::AP, -2),
+                ],
+            },
+        )
+    }
+
+    #[test]
+    fn substitute_error_message_references_value() {
+        let program = program_with_felt_references();
+        let runner = cairo_runner!(program);
+        let mut vm = vm!();
+        run_context!(vm, 0, 2, 1);
+        vm.memory = memory![((1, 1), 42)];
+        let attribute = Attribute {
+            name: String::from("error_message"),
+            start_pc: 0,
+            end_pc: 2,
+            value: String::from("amount {amount} is too large: {amount} > {limit} {"),
+            flow_tracking_data: Some(FlowTrackingData {
+                ap_tracking: ApTracking::default(),
+                reference_ids: HashMap::from([
+                    (String::from("__main__.main.amount"), 0),
+                    (String::from("__main__.main.limit"), 1),
+                ]),
+            }),
+        };
+        assert_eq!(
+            substitute_error_message_references(&attribute, &runner, &vm),
+            "amount 42 is too large: 42 > {limit} { \
+             (Cannot evaluate ap-based or complex references: ['limit'])"
+        );
+    }
+
+    #[test]
+    fn substitute_error_message_references_keeps_order_of_invalid_ones() {
+        let program = program_with_felt_references();
+        let runner = cairo_runner!(program);
+        let vm = vm!();
+        let attribute = Attribute {
+            name: String::from("error_message"),
+            start_pc: 0,
+            end_pc: 2,
+            value: String::from("{y} {x} {z}"),
+            flow_tracking_data: Some(FlowTrackingData {
+                ap_tracking: ApTracking::default(),
+                reference_ids: HashMap::from([
+                    (String::from("__main__.main.x"), 1),
+                    (String::from("__main__.main.y"), 2),
+                ]),
+            }),
+        };
+        assert_eq!(
+            substitute_error_message_references(&attribute, &runner, &vm),
+            "{y} {x} {z} (Cannot evaluate ap-based or complex references: ['y', 'x'])"
+        );
+    }
 }
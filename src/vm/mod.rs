@@ -1,8 +1,14 @@
+pub mod accessed_addresses;
+pub mod const_view;
 pub mod context;
 pub mod decoding;
 pub mod errors;
+pub mod opcode_extension;
+pub mod run_outcome;
 pub mod runners;
 pub mod security;
+pub mod state_transition;
+pub mod step_hook;
 pub mod trace;
 pub mod vm_core;
 pub mod vm_memory;
@@ -0,0 +1,96 @@
+use crate::{
+    types::relocatable::{MaybeRelocatable, Relocatable},
+    vm::{
+        context::run_context::RunContext, errors::vm_errors::VirtualMachineError,
+        vm_memory::memory::Memory,
+    },
+};
+
+///Registers after an instruction executed by an OpcodeExtension
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RegisterUpdate {
+    pub pc: Relocatable,
+    ///Offset of ap in the execution segment
+    pub ap: usize,
+    ///Offset of fp in the execution segment
+    pub fp: usize,
+}
+
+///Restricted view of the VM given to an OpcodeExtension: it can read the registers and the
+///current step, and read and write memory, but can't touch segments, builtins or the trace.
+///The cells it reads or writes are recorded as accessed by the instruction.
+pub struct VmView<'a> {
+    run_context: &'a RunContext,
+    memory: &'a mut Memory,
+    current_step: usize,
+    accessed_addresses: Vec<Relocatable>,
+}
+
+impl<'a> VmView<'a> {
+    pub(crate) fn new(
+        run_context: &'a RunContext,
+        memory: &'a mut Memory,
+        current_step: usize,
+    ) -> VmView<'a> {
+        VmView {
+            run_context,
+            memory,
+            current_step,
+            accessed_addresses: Vec::new(),
+        }
+    }
+
+    pub(crate) fn into_accessed_addresses(self) -> Vec<Relocatable> {
+        self.accessed_addresses
+    }
+
+    pub fn get_pc(&self) -> Relocatable {
+        *self.run_context.get_pc()
+    }
+
+    pub fn get_ap(&self) -> Relocatable {
+        self.run_context.get_ap()
+    }
+
+    pub fn get_fp(&self) -> Relocatable {
+        self.run_context.get_fp()
+    }
+
+    pub fn current_step(&self) -> usize {
+        self.current_step
+    }
+
+    ///Returns the value at addr, or None if it wasn't written yet
+    pub fn get_maybe(
+        &mut self,
+        addr: &Relocatable,
+    ) -> Result<Option<MaybeRelocatable>, VirtualMachineError> {
+        let value = self.memory.get(addr)?.map(|value| value.into_owned());
+        if value.is_some() {
+            self.accessed_addresses.push(*addr);
+        }
+        Ok(value)
+    }
+
+    ///Writes val at addr, failing if a different value was already written there
+    pub fn insert_value<T: Into<MaybeRelocatable>>(
+        &mut self,
+        addr: &Relocatable,
+        val: T,
+    ) -> Result<(), VirtualMachineError> {
+        self.memory.insert_value(addr, val)?;
+        self.accessed_addresses.push(*addr);
+        Ok(())
+    }
+}
+
+///Executes the instructions whose opcode flags hold the combination reserved for extensions,
+///see decoder::is_extension_instruction. Used to prototype opcodes outside of the Cairo instruction set.
+pub trait OpcodeExtension: Send {
+    ///Performs the semantics of the encoded instruction, returning the registers after it
+    fn execute(
+        &mut self,
+        vm: &mut VmView,
+        instruction_word: i64,
+    ) -> Result<RegisterUpdate, VirtualMachineError>;
+}
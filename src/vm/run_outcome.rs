@@ -0,0 +1,17 @@
+use crate::types::relocatable::Relocatable;
+
+///Why CairoRunner::run_until_breakpoint stopped
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum BreakReason {
+    ReachedFinalPc,
+    Breakpoint(Relocatable),
+    StepLimit,
+    Cancelled,
+}
+
+///What a run did: the steps it executed and why it stopped
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct RunOutcome {
+    pub steps_executed: usize,
+    pub break_reason: BreakReason,
+}
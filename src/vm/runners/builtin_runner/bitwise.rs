@@ -1,6 +1,6 @@
 use crate::{
-    math_utils::safe_div_usize,
     types::{
+        builtin_name::BuiltinName,
         instance_definitions::bitwise_instance_def::{
             BitwiseInstanceDef, CELLS_PER_BITWISE, INPUT_CELLS_PER_BITWISE,
         },
@@ -13,7 +13,6 @@ use crate::{
     },
 };
 use felt::FeltOps;
-use num_integer::div_ceil;
 
 #[derive(Debug, Clone)]
 pub struct BitwiseBuiltinRunner {
@@ -24,7 +23,7 @@ pub struct BitwiseBuiltinRunner {
     bitwise_builtin: BitwiseInstanceDef,
     pub(crate) stop_ptr: Option<usize>,
     pub(crate) _included: bool,
-    instances_per_component: u32,
+    pub(crate) instances_per_component: u32,
 }
 
 impl BitwiseBuiltinRunner {
@@ -115,14 +114,8 @@ impl BitwiseBuiltinRunner {
         Ok(None)
     }
 
-    pub fn get_allocated_memory_units(&self, vm: &VirtualMachine) -> Result<usize, MemoryError> {
-        let value = safe_div_usize(vm.current_step, self.ratio as usize)
-            .map_err(|_| MemoryError::ErrorCalculatingMemoryUnits)?;
-        Ok(self.cells_per_instance as usize * value)
-    }
-
-    pub fn get_memory_segment_addresses(&self) -> (&'static str, (isize, Option<usize>)) {
-        ("bitwise", (self.base, self.stop_ptr))
+    pub fn get_memory_segment_addresses(&self) -> (BuiltinName, (isize, Option<usize>)) {
+        (BuiltinName::Bitwise, (self.base, self.stop_ptr))
     }
 
     pub fn get_used_cells(&self, vm: &VirtualMachine) -> Result<usize, MemoryError> {
@@ -135,27 +128,6 @@ impl BitwiseBuiltinRunner {
             .ok_or(MemoryError::MissingSegmentUsedSizes)
     }
 
-    pub fn get_used_cells_and_allocated_size(
-        &self,
-        vm: &VirtualMachine,
-    ) -> Result<(usize, usize), MemoryError> {
-        let ratio = self.ratio as usize;
-        let cells_per_instance = self.cells_per_instance;
-        let min_step = ratio * self.instances_per_component as usize;
-        if vm.current_step < min_step {
-            Err(MemoryError::InsufficientAllocatedCells)
-        } else {
-            let used = self.get_used_cells(vm)?;
-            let size = cells_per_instance as usize
-                * safe_div_usize(vm.current_step, ratio)
-                    .map_err(|_| MemoryError::InsufficientAllocatedCells)?;
-            if used > size {
-                return Err(MemoryError::InsufficientAllocatedCells);
-            }
-            Ok((used, size))
-        }
-    }
-
     pub fn get_used_diluted_check_units(&self, diluted_spacing: u32, diluted_n_bits: u32) -> usize {
         let total_n_bits = self.bitwise_builtin.total_n_bits;
         let mut partition = Vec::with_capacity(total_n_bits as usize);
@@ -173,44 +145,6 @@ impl BitwiseBuiltinRunner {
             .count();
         4 * partition_lengh + num_trimmed
     }
-
-    pub fn final_stack(
-        &self,
-        vm: &VirtualMachine,
-        pointer: Relocatable,
-    ) -> Result<(Relocatable, usize), RunnerError> {
-        if self._included {
-            if let Ok(stop_pointer) =
-                vm.get_relocatable(&(pointer.sub_usize(1)).map_err(|_| RunnerError::FinalStack)?)
-            {
-                if self.base() != stop_pointer.segment_index {
-                    return Err(RunnerError::InvalidStopPointer("bitwise".to_string()));
-                }
-                let stop_ptr = stop_pointer.offset;
-                let num_instances = self
-                    .get_used_instances(vm)
-                    .map_err(|_| RunnerError::FinalStack)?;
-                let used_cells = num_instances * self.cells_per_instance as usize;
-                if stop_ptr != used_cells {
-                    return Err(RunnerError::InvalidStopPointer("bitwise".to_string()));
-                }
-                Ok((
-                    pointer.sub_usize(1).map_err(|_| RunnerError::FinalStack)?,
-                    stop_ptr,
-                ))
-            } else {
-                Err(RunnerError::FinalStack)
-            }
-        } else {
-            let stop_ptr = self.base() as usize;
-            Ok((pointer, stop_ptr))
-        }
-    }
-
-    pub fn get_used_instances(&self, vm: &VirtualMachine) -> Result<usize, MemoryError> {
-        let used_cells = self.get_used_cells(vm)?;
-        Ok(div_ceil(used_cells, self.cells_per_instance as usize))
-    }
 }
 
 #[cfg(test)]
@@ -239,7 +173,7 @@ mod tests {
 
         vm.segments.segment_used_sizes = Some(vec![1]);
 
-        assert_eq!(builtin.get_used_instances(&vm), Ok(1));
+        assert_eq!(BuiltinRunner::from(builtin).get_used_instances(&vm), Ok(1));
     }
 
     #[test]
@@ -260,7 +194,9 @@ mod tests {
         let pointer = Relocatable::from((2, 2));
 
         assert_eq!(
-            builtin.final_stack(&vm, pointer).unwrap(),
+            BuiltinRunner::from(builtin)
+                .final_stack(&vm, pointer)
+                .unwrap(),
             (Relocatable::from((2, 1)), 0)
         );
     }
@@ -283,8 +219,13 @@ mod tests {
         let pointer = Relocatable::from((2, 2));
 
         assert_eq!(
-            builtin.final_stack(&vm, pointer),
-            Err(RunnerError::InvalidStopPointer("bitwise".to_string()))
+            BuiltinRunner::from(builtin).final_stack(&vm, pointer),
+            Err(RunnerError::InvalidStopPointer(
+                BuiltinName::Bitwise,
+                Relocatable::from((0, 0)),
+                200,
+                Relocatable::from((0, 1000))
+            ))
         );
     }
 
@@ -306,7 +247,9 @@ mod tests {
         let pointer = Relocatable::from((2, 2));
 
         assert_eq!(
-            builtin.final_stack(&vm, pointer).unwrap(),
+            BuiltinRunner::from(builtin)
+                .final_stack(&vm, pointer)
+                .unwrap(),
             (Relocatable::from((2, 2)), 0)
         );
     }
@@ -329,7 +272,7 @@ mod tests {
         let pointer = Relocatable::from((2, 2));
 
         assert_eq!(
-            builtin.final_stack(&vm, pointer),
+            BuiltinRunner::from(builtin).final_stack(&vm, pointer),
             Err(RunnerError::FinalStack)
         );
     }
@@ -344,7 +287,7 @@ mod tests {
         vm.segments.segment_used_sizes = Some(vec![0]);
 
         let program = program!(
-            builtins = vec![String::from("pedersen")],
+            builtins = vec![BuiltinName::Pedersen],
             data = vec_data!(
                 (4612671182993129469_i64),
                 (5189976364521848832_i64),
@@ -382,12 +325,13 @@ mod tests {
 
     #[test]
     fn get_allocated_memory_units() {
-        let builtin = BitwiseBuiltinRunner::new(&BitwiseInstanceDef::new(10), true);
+        let builtin: BuiltinRunner =
+            BitwiseBuiltinRunner::new(&BitwiseInstanceDef::new(10), true).into();
 
         let mut vm = vm!();
 
         let program = program!(
-            builtins = vec![String::from("output"), String::from("bitwise")],
+            builtins = vec![BuiltinName::Output, BuiltinName::Bitwise],
             data = vec_data!(
                 (4612671182993129469_i64),
                 (5189976364521848832_i64),
@@ -469,7 +413,7 @@ mod tests {
 
         assert_eq!(
             builtin.get_memory_segment_addresses(),
-            ("bitwise", (0, None)),
+            (BuiltinName::Bitwise, (0, None)),
         );
     }
 
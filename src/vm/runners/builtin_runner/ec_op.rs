@@ -1,4 +1,5 @@
-use crate::math_utils::{ec_add, ec_double, safe_div_usize};
+use crate::math_utils::{ec_add, ec_double};
+use crate::types::builtin_name::BuiltinName;
 use crate::types::instance_definitions::ec_op_instance_def::{
     EcOpInstanceDef, CELLS_PER_EC_OP, INPUT_CELLS_PER_EC_OP,
 };
@@ -10,7 +11,7 @@ use crate::vm::vm_memory::memory::Memory;
 use crate::vm::vm_memory::memory_segments::MemorySegmentManager;
 use felt::{Felt, FeltOps, NewFelt};
 use num_bigint::BigInt;
-use num_integer::{div_ceil, Integer};
+use num_integer::Integer;
 use num_traits::{Num, One, Pow, Zero};
 use std::borrow::Cow;
 
@@ -22,8 +23,8 @@ pub struct EcOpBuiltinRunner {
     pub(crate) n_input_cells: u32,
     ec_op_builtin: EcOpInstanceDef,
     pub(crate) stop_ptr: Option<usize>,
-    _included: bool,
-    instances_per_component: u32,
+    pub(crate) _included: bool,
+    pub(crate) instances_per_component: u32,
 }
 
 impl EcOpBuiltinRunner {
@@ -188,14 +189,8 @@ impl EcOpBuiltinRunner {
         }
     }
 
-    pub fn get_allocated_memory_units(&self, vm: &VirtualMachine) -> Result<usize, MemoryError> {
-        let value = safe_div_usize(vm.current_step, self.ratio as usize)
-            .map_err(|_| MemoryError::ErrorCalculatingMemoryUnits)?;
-        Ok(self.cells_per_instance as usize * value)
-    }
-
-    pub fn get_memory_segment_addresses(&self) -> (&'static str, (isize, Option<usize>)) {
-        ("ec_op", (self.base, self.stop_ptr))
+    pub fn get_memory_segment_addresses(&self) -> (BuiltinName, (isize, Option<usize>)) {
+        (BuiltinName::EcOp, (self.base, self.stop_ptr))
     }
 
     pub fn get_used_cells(&self, vm: &VirtualMachine) -> Result<usize, MemoryError> {
@@ -208,66 +203,6 @@ impl EcOpBuiltinRunner {
             .ok_or(MemoryError::MissingSegmentUsedSizes)
     }
 
-    pub fn get_used_cells_and_allocated_size(
-        &self,
-        vm: &VirtualMachine,
-    ) -> Result<(usize, usize), MemoryError> {
-        let ratio = self.ratio as usize;
-        let cells_per_instance = self.cells_per_instance;
-        let min_step = ratio * self.instances_per_component as usize;
-        if vm.current_step < min_step {
-            Err(MemoryError::InsufficientAllocatedCells)
-        } else {
-            let used = self.get_used_cells(vm)?;
-            let size = cells_per_instance as usize
-                * safe_div_usize(vm.current_step, ratio)
-                    .map_err(|_| MemoryError::InsufficientAllocatedCells)?;
-            if used > size {
-                return Err(MemoryError::InsufficientAllocatedCells);
-            }
-            Ok((used, size))
-        }
-    }
-
-    pub fn get_used_instances(&self, vm: &VirtualMachine) -> Result<usize, MemoryError> {
-        let used_cells = self.get_used_cells(vm)?;
-        Ok(div_ceil(used_cells, self.cells_per_instance as usize))
-    }
-
-    pub fn final_stack(
-        &self,
-        vm: &VirtualMachine,
-        pointer: Relocatable,
-    ) -> Result<(Relocatable, usize), RunnerError> {
-        if self._included {
-            if let Ok(stop_pointer) =
-                vm.get_relocatable(&(pointer.sub_usize(1)).map_err(|_| RunnerError::FinalStack)?)
-            {
-                if self.base() != stop_pointer.segment_index {
-                    return Err(RunnerError::InvalidStopPointer("ec_op".to_string()));
-                }
-                let stop_ptr = stop_pointer.offset;
-                let num_instances = self
-                    .get_used_instances(vm)
-                    .map_err(|_| RunnerError::FinalStack)?;
-                let used_cells = num_instances * self.cells_per_instance as usize;
-                if stop_ptr != used_cells {
-                    return Err(RunnerError::InvalidStopPointer("ec_op".to_string()));
-                }
-
-                Ok((
-                    pointer.sub_usize(1).map_err(|_| RunnerError::FinalStack)?,
-                    stop_ptr,
-                ))
-            } else {
-                Err(RunnerError::FinalStack)
-            }
-        } else {
-            let stop_ptr = self.base() as usize;
-            Ok((pointer, stop_ptr))
-        }
-    }
-
     pub fn format_ec_op_error(
         p: (num_bigint::BigInt, num_bigint::BigInt),
         m: num_bigint::BigInt,
@@ -311,7 +246,7 @@ mod tests {
 
         vm.segments.segment_used_sizes = Some(vec![1]);
 
-        assert_eq!(builtin.get_used_instances(&vm), Ok(1));
+        assert_eq!(BuiltinRunner::from(builtin).get_used_instances(&vm), Ok(1));
     }
 
     #[test]
@@ -332,7 +267,9 @@ mod tests {
         let pointer = Relocatable::from((2, 2));
 
         assert_eq!(
-            builtin.final_stack(&vm, pointer).unwrap(),
+            BuiltinRunner::from(builtin)
+                .final_stack(&vm, pointer)
+                .unwrap(),
             (Relocatable::from((2, 1)), 0)
         );
     }
@@ -355,8 +292,13 @@ mod tests {
         let pointer = Relocatable::from((2, 2));
 
         assert_eq!(
-            builtin.final_stack(&vm, pointer),
-            Err(RunnerError::InvalidStopPointer("ec_op".to_string()))
+            BuiltinRunner::from(builtin).final_stack(&vm, pointer),
+            Err(RunnerError::InvalidStopPointer(
+                BuiltinName::EcOp,
+                Relocatable::from((0, 0)),
+                143,
+                Relocatable::from((0, 1001))
+            ))
         );
     }
 
@@ -378,7 +320,9 @@ mod tests {
         let pointer = Relocatable::from((2, 2));
 
         assert_eq!(
-            builtin.final_stack(&vm, pointer).unwrap(),
+            BuiltinRunner::from(builtin)
+                .final_stack(&vm, pointer)
+                .unwrap(),
             (Relocatable::from((2, 2)), 0)
         );
     }
@@ -401,7 +345,7 @@ mod tests {
         let pointer = Relocatable::from((2, 2));
 
         assert_eq!(
-            builtin.final_stack(&vm, pointer),
+            BuiltinRunner::from(builtin).final_stack(&vm, pointer),
             Err(RunnerError::FinalStack)
         );
     }
@@ -415,7 +359,7 @@ mod tests {
         vm.segments.segment_used_sizes = Some(vec![0]);
 
         let program = program!(
-            builtins = vec![String::from("pedersen")],
+            builtins = vec![BuiltinName::Pedersen],
             data = vec_data!(
                 (4612671182993129469_i64),
                 (5189976364521848832_i64),
@@ -452,12 +396,12 @@ mod tests {
 
     #[test]
     fn get_allocated_memory_units() {
-        let builtin = EcOpBuiltinRunner::new(&EcOpInstanceDef::new(10), true);
+        let builtin: BuiltinRunner = EcOpBuiltinRunner::new(&EcOpInstanceDef::new(10), true).into();
 
         let mut vm = vm!();
 
         let program = program!(
-            builtins = vec![String::from("ec_op")],
+            builtins = vec![BuiltinName::EcOp],
             data = vec_data!(
                 (4612671182993129469_i64),
                 (5189976364521848832_i64),
@@ -917,7 +861,10 @@ mod tests {
     fn get_memory_segment_addresses() {
         let builtin = EcOpBuiltinRunner::new(&EcOpInstanceDef::default(), true);
 
-        assert_eq!(builtin.get_memory_segment_addresses(), ("ec_op", (0, None)));
+        assert_eq!(
+            builtin.get_memory_segment_addresses(),
+            (BuiltinName::EcOp, (0, None))
+        );
     }
 
     #[test]
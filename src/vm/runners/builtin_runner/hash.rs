@@ -1,6 +1,6 @@
 use std::cell::RefCell;
 
-use crate::math_utils::safe_div_usize;
+use crate::types::builtin_name::BuiltinName;
 use crate::types::instance_definitions::pedersen_instance_def::{
     CELLS_PER_HASH, INPUT_CELLS_PER_HASH,
 };
@@ -11,7 +11,7 @@ use crate::vm::vm_core::VirtualMachine;
 use crate::vm::vm_memory::memory::Memory;
 use crate::vm::vm_memory::memory_segments::MemorySegmentManager;
 use felt::{Felt, FeltOps};
-use num_integer::{div_ceil, Integer};
+use num_integer::Integer;
 use starknet_crypto::{pedersen_hash, FieldElement};
 
 #[derive(Debug, Clone)]
@@ -22,10 +22,10 @@ pub struct HashBuiltinRunner {
     pub(crate) n_input_cells: u32,
     pub(crate) stop_ptr: Option<usize>,
     pub(crate) _included: bool,
-    instances_per_component: u32,
+    pub(crate) instances_per_component: u32,
     // This act as a cache to optimize calls to deduce_memory_cell
     // Therefore need interior mutability
-    pub(self) verified_addresses: RefCell<Vec<Relocatable>>,
+    pub(crate) verified_addresses: RefCell<Vec<Relocatable>>,
 }
 
 impl HashBuiltinRunner {
@@ -118,14 +118,8 @@ impl HashBuiltinRunner {
         Ok(None)
     }
 
-    pub fn get_allocated_memory_units(&self, vm: &VirtualMachine) -> Result<usize, MemoryError> {
-        let value = safe_div_usize(vm.current_step, self.ratio as usize)
-            .map_err(|_| MemoryError::ErrorCalculatingMemoryUnits)?;
-        Ok(self.cells_per_instance as usize * value)
-    }
-
-    pub fn get_memory_segment_addresses(&self) -> (&'static str, (isize, Option<usize>)) {
-        ("pedersen", (self.base, self.stop_ptr))
+    pub fn get_memory_segment_addresses(&self) -> (BuiltinName, (isize, Option<usize>)) {
+        (BuiltinName::Pedersen, (self.base, self.stop_ptr))
     }
 
     pub fn get_used_cells(&self, vm: &VirtualMachine) -> Result<usize, MemoryError> {
@@ -137,66 +131,6 @@ impl HashBuiltinRunner {
             )
             .ok_or(MemoryError::MissingSegmentUsedSizes)
     }
-
-    pub fn get_used_cells_and_allocated_size(
-        &self,
-        vm: &VirtualMachine,
-    ) -> Result<(usize, usize), MemoryError> {
-        let ratio = self.ratio as usize;
-        let cells_per_instance = self.cells_per_instance;
-        let min_step = ratio * self.instances_per_component as usize;
-        if vm.current_step < min_step {
-            Err(MemoryError::InsufficientAllocatedCells)
-        } else {
-            let used = self.get_used_cells(vm)?;
-            let size = cells_per_instance as usize
-                * safe_div_usize(vm.current_step, ratio)
-                    .map_err(|_| MemoryError::InsufficientAllocatedCells)?;
-            if used > size {
-                return Err(MemoryError::InsufficientAllocatedCells);
-            }
-            Ok((used, size))
-        }
-    }
-
-    pub fn get_used_instances(&self, vm: &VirtualMachine) -> Result<usize, MemoryError> {
-        let used_cells = self.get_used_cells(vm)?;
-        Ok(div_ceil(used_cells, self.cells_per_instance as usize))
-    }
-
-    pub fn final_stack(
-        &self,
-        vm: &VirtualMachine,
-        pointer: Relocatable,
-    ) -> Result<(Relocatable, usize), RunnerError> {
-        if self._included {
-            if let Ok(stop_pointer) =
-                vm.get_relocatable(&(pointer.sub_usize(1)).map_err(|_| RunnerError::FinalStack)?)
-            {
-                if self.base() != stop_pointer.segment_index {
-                    return Err(RunnerError::InvalidStopPointer("pedersen".to_string()));
-                }
-
-                let stop_ptr = stop_pointer.offset;
-                let num_instances = self
-                    .get_used_instances(vm)
-                    .map_err(|_| RunnerError::FinalStack)?;
-                let used_cells = num_instances * self.cells_per_instance as usize;
-                if stop_ptr != used_cells {
-                    return Err(RunnerError::InvalidStopPointer("pedersen".to_string()));
-                }
-                Ok((
-                    pointer.sub_usize(1).map_err(|_| RunnerError::FinalStack)?,
-                    stop_ptr,
-                ))
-            } else {
-                Err(RunnerError::FinalStack)
-            }
-        } else {
-            let stop_ptr = self.base() as usize;
-            Ok((pointer, stop_ptr))
-        }
-    }
 }
 
 #[cfg(test)]
@@ -227,7 +161,7 @@ mod tests {
 
         vm.segments.segment_used_sizes = Some(vec![1]);
 
-        assert_eq!(builtin.get_used_instances(&vm), Ok(1));
+        assert_eq!(BuiltinRunner::from(builtin).get_used_instances(&vm), Ok(1));
     }
 
     #[test]
@@ -248,7 +182,9 @@ mod tests {
         let pointer = Relocatable::from((2, 2));
 
         assert_eq!(
-            builtin.final_stack(&vm, pointer).unwrap(),
+            BuiltinRunner::from(builtin)
+                .final_stack(&vm, pointer)
+                .unwrap(),
             (Relocatable::from((2, 1)), 0)
         );
     }
@@ -271,8 +207,13 @@ mod tests {
         let pointer = Relocatable::from((2, 2));
 
         assert_eq!(
-            builtin.final_stack(&vm, pointer),
-            Err(RunnerError::InvalidStopPointer("pedersen".to_string()))
+            BuiltinRunner::from(builtin).final_stack(&vm, pointer),
+            Err(RunnerError::InvalidStopPointer(
+                BuiltinName::Pedersen,
+                Relocatable::from((0, 0)),
+                333,
+                Relocatable::from((0, 999))
+            ))
         );
     }
 
@@ -294,7 +235,9 @@ mod tests {
         let pointer = Relocatable::from((2, 2));
 
         assert_eq!(
-            builtin.final_stack(&vm, pointer).unwrap(),
+            BuiltinRunner::from(builtin)
+                .final_stack(&vm, pointer)
+                .unwrap(),
             (Relocatable::from((2, 2)), 0)
         );
     }
@@ -317,7 +260,7 @@ mod tests {
         let pointer = Relocatable::from((2, 2));
 
         assert_eq!(
-            builtin.final_stack(&vm, pointer),
+            BuiltinRunner::from(builtin).final_stack(&vm, pointer),
             Err(RunnerError::FinalStack)
         );
     }
@@ -331,7 +274,7 @@ mod tests {
         vm.segments.segment_used_sizes = Some(vec![0]);
 
         let program = program!(
-            builtins = vec![String::from("pedersen")],
+            builtins = vec![BuiltinName::Pedersen],
             data = vec_data!(
                 (4612671182993129469_i64),
                 (5189976364521848832_i64),
@@ -369,12 +312,12 @@ mod tests {
 
     #[test]
     fn get_allocated_memory_units() {
-        let builtin = HashBuiltinRunner::new(10, true);
+        let builtin: BuiltinRunner = HashBuiltinRunner::new(10, true).into();
 
         let mut vm = vm!();
 
         let program = program!(
-            builtins = vec![String::from("pedersen")],
+            builtins = vec![BuiltinName::Pedersen],
             data = vec_data!(
                 (4612671182993129469_i64),
                 (5189976364521848832_i64),
@@ -459,7 +402,7 @@ mod tests {
 
         assert_eq!(
             builtin.get_memory_segment_addresses(),
-            ("pedersen", (0, None)),
+            (BuiltinName::Pedersen, (0, None)),
         );
     }
 
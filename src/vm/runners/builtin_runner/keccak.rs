@@ -3,6 +3,7 @@ use crate::hint_processor::builtin_hint_processor::cairo_keccak::keccak_hints::{
 };
 use crate::hint_processor::builtin_hint_processor::keccak_utils::left_pad_u64;
 use crate::math_utils::safe_div_usize;
+use crate::types::builtin_name::BuiltinName;
 use crate::types::instance_definitions::keccak_instance_def::KeccakInstanceDef;
 use crate::types::relocatable::{MaybeRelocatable, Relocatable};
 use crate::vm::errors::memory_errors::MemoryError;
@@ -11,7 +12,6 @@ use crate::vm::vm_core::VirtualMachine;
 use crate::vm::vm_memory::memory::Memory;
 use crate::vm::vm_memory::memory_segments::MemorySegmentManager;
 use felt::Felt;
-use num_integer::div_ceil;
 use num_traits::One;
 
 const KECCAK_ARRAY_LEN: usize = 25;
@@ -24,9 +24,9 @@ pub struct KeccakBuiltinRunner {
     pub(crate) n_input_cells: u32,
     verified_addresses: Vec<Relocatable>,
     pub(crate) stop_ptr: Option<usize>,
-    _included: bool,
+    pub(crate) _included: bool,
     state_rep: Vec<u32>,
-    instances_per_component: u32,
+    pub(crate) instances_per_component: u32,
 }
 
 impl KeccakBuiltinRunner {
@@ -144,14 +144,8 @@ impl KeccakBuiltinRunner {
         Ok(None)
     }
 
-    pub fn get_allocated_memory_units(&self, vm: &VirtualMachine) -> Result<usize, MemoryError> {
-        let value = safe_div_usize(vm.current_step, self.ratio as usize)
-            .map_err(|_| MemoryError::ErrorCalculatingMemoryUnits)?;
-        Ok(self.cells_per_instance as usize * value)
-    }
-
-    pub fn get_memory_segment_addresses(&self) -> (&'static str, (isize, Option<usize>)) {
-        ("keccak", (self.base, self.stop_ptr))
+    pub fn get_memory_segment_addresses(&self) -> (BuiltinName, (isize, Option<usize>)) {
+        (BuiltinName::Keccak, (self.base, self.stop_ptr))
     }
 
     pub fn get_used_cells(&self, vm: &VirtualMachine) -> Result<usize, MemoryError> {
@@ -164,64 +158,6 @@ impl KeccakBuiltinRunner {
             .ok_or(MemoryError::MissingSegmentUsedSizes)
     }
 
-    pub fn get_used_cells_and_allocated_size(
-        &self,
-        vm: &VirtualMachine,
-    ) -> Result<(usize, usize), MemoryError> {
-        let ratio = self.ratio as usize;
-
-        let cells_per_instance = self.cells_per_instance;
-        let min_step = ratio * self.instances_per_component as usize;
-        if vm.current_step < min_step {
-            Err(MemoryError::InsufficientAllocatedCells)
-        } else {
-            let used = self.get_used_cells(vm)?;
-            let size = cells_per_instance as usize
-                * safe_div_usize(vm.current_step, ratio)
-                    .map_err(|_| MemoryError::InsufficientAllocatedCells)?;
-            Ok((used, size))
-        }
-    }
-
-    pub fn get_used_instances(&self, vm: &VirtualMachine) -> Result<usize, MemoryError> {
-        let used_cells = self.get_used_cells(vm)?;
-        Ok(div_ceil(used_cells, self.cells_per_instance as usize))
-    }
-
-    pub fn final_stack(
-        &self,
-        vm: &VirtualMachine,
-        pointer: Relocatable,
-    ) -> Result<(Relocatable, usize), RunnerError> {
-        if self._included {
-            if let Ok(stop_pointer) =
-                vm.get_relocatable(&(pointer.sub_usize(1)).map_err(|_| RunnerError::FinalStack)?)
-            {
-                if self.base() != stop_pointer.segment_index {
-                    return Err(RunnerError::InvalidStopPointer("keccak".to_string()));
-                }
-                let stop_ptr = stop_pointer.offset;
-                let num_instances = self
-                    .get_used_instances(vm)
-                    .map_err(|_| RunnerError::FinalStack)?;
-                let used_cells = num_instances * self.cells_per_instance as usize;
-                if stop_ptr != used_cells {
-                    return Err(RunnerError::InvalidStopPointer("keccak".to_string()));
-                }
-
-                Ok((
-                    pointer.sub_usize(1).map_err(|_| RunnerError::FinalStack)?,
-                    stop_ptr,
-                ))
-            } else {
-                Err(RunnerError::FinalStack)
-            }
-        } else {
-            let stop_ptr = self.base() as usize;
-            Ok((pointer, stop_ptr))
-        }
-    }
-
     pub fn get_memory_accesses(
         &self,
         vm: &VirtualMachine,
@@ -283,7 +219,7 @@ mod tests {
 
         vm.segments.segment_used_sizes = Some(vec![1]);
 
-        assert_eq!(builtin.get_used_instances(&vm), Ok(1));
+        assert_eq!(BuiltinRunner::from(builtin).get_used_instances(&vm), Ok(1));
     }
 
     #[test]
@@ -304,7 +240,9 @@ mod tests {
         let pointer = Relocatable::from((2, 2));
 
         assert_eq!(
-            builtin.final_stack(&vm, pointer).unwrap(),
+            BuiltinRunner::from(builtin)
+                .final_stack(&vm, pointer)
+                .unwrap(),
             (Relocatable::from((2, 1)), 0)
         );
     }
@@ -327,8 +265,13 @@ mod tests {
         let pointer = Relocatable::from((2, 2));
 
         assert_eq!(
-            builtin.final_stack(&vm, pointer),
-            Err(RunnerError::InvalidStopPointer("keccak".to_string()))
+            BuiltinRunner::from(builtin).final_stack(&vm, pointer),
+            Err(RunnerError::InvalidStopPointer(
+                BuiltinName::Keccak,
+                Relocatable::from((0, 0)),
+                63,
+                Relocatable::from((0, 1008))
+            ))
         );
     }
 
@@ -350,7 +293,9 @@ mod tests {
         let pointer = Relocatable::from((2, 2));
 
         assert_eq!(
-            builtin.final_stack(&vm, pointer).unwrap(),
+            BuiltinRunner::from(builtin)
+                .final_stack(&vm, pointer)
+                .unwrap(),
             (Relocatable::from((2, 2)), 0)
         );
     }
@@ -373,7 +318,7 @@ mod tests {
         let pointer = Relocatable::from((2, 2));
 
         assert_eq!(
-            builtin.final_stack(&vm, pointer),
+            BuiltinRunner::from(builtin).final_stack(&vm, pointer),
             Err(RunnerError::FinalStack)
         );
     }
@@ -413,7 +358,7 @@ mod tests {
         let mut vm = vm!();
 
         let program = program!(
-            builtins = vec![String::from("keccak")],
+            builtins = vec![BuiltinName::Keccak],
             data = vec_data!(
                 (4612671182993129469_i64),
                 (5189976364521848832_i64),
@@ -455,7 +400,7 @@ mod tests {
 
         assert_eq!(
             builtin.get_memory_segment_addresses(),
-            ("keccak", (0, None))
+            (BuiltinName::Keccak, (0, None))
         );
     }
 
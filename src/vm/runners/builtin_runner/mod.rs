@@ -1,3 +1,5 @@
+use crate::math_utils::safe_div_usize;
+use crate::types::builtin_name::BuiltinName;
 use crate::types::relocatable::{MaybeRelocatable, Relocatable};
 use crate::vm::errors::memory_errors::{self, MemoryError};
 use crate::vm::errors::runner_errors::RunnerError;
@@ -10,17 +12,21 @@ mod bitwise;
 mod ec_op;
 mod hash;
 mod keccak;
+mod modulo;
 mod output;
 mod range_check;
+mod segment_arena;
 mod signature;
 
 pub use self::keccak::KeccakBuiltinRunner;
 pub use bitwise::BitwiseBuiltinRunner;
 pub use ec_op::EcOpBuiltinRunner;
 pub use hash::HashBuiltinRunner;
-use num_integer::div_floor;
+pub use modulo::{ModBuiltinRunner, ModBuiltinType};
+use num_integer::{div_ceil, div_floor};
 pub use output::OutputBuiltinRunner;
 pub use range_check::RangeCheckBuiltinRunner;
+pub use segment_arena::SegmentArenaBuiltinRunner;
 pub use signature::SignatureBuiltinRunner;
 
 /* NB: this enum is no accident: we may need (and cairo-rs-py *does* need)
@@ -39,7 +45,9 @@ pub enum BuiltinRunner {
     Output(OutputBuiltinRunner),
     RangeCheck(RangeCheckBuiltinRunner),
     Keccak(KeccakBuiltinRunner),
+    SegmentArena(SegmentArenaBuiltinRunner),
     Signature(SignatureBuiltinRunner),
+    Mod(ModBuiltinRunner),
 }
 
 impl BuiltinRunner {
@@ -48,7 +56,7 @@ impl BuiltinRunner {
         &mut self,
         segments: &mut MemorySegmentManager,
         memory: &mut Memory,
-    ) {
+    ) -> Result<(), MemoryError> {
         match *self {
             BuiltinRunner::Bitwise(ref mut bitwise) => {
                 bitwise.initialize_segments(segments, memory)
@@ -60,10 +68,15 @@ impl BuiltinRunner {
                 range_check.initialize_segments(segments, memory)
             }
             BuiltinRunner::Keccak(ref mut keccak) => keccak.initialize_segments(segments, memory),
+            BuiltinRunner::SegmentArena(ref mut segment_arena) => {
+                segment_arena.initialize_segments(segments, memory)?
+            }
             BuiltinRunner::Signature(ref mut signature) => {
                 signature.initialize_segments(segments, memory)
             }
+            BuiltinRunner::Mod(ref mut modulo) => modulo.initialize_segments(segments, memory),
         }
+        Ok(())
     }
 
     pub fn initial_stack(&self) -> Vec<MaybeRelocatable> {
@@ -74,25 +87,58 @@ impl BuiltinRunner {
             BuiltinRunner::Output(ref output) => output.initial_stack(),
             BuiltinRunner::RangeCheck(ref range_check) => range_check.initial_stack(),
             BuiltinRunner::Keccak(ref keccak) => keccak.initial_stack(),
+            BuiltinRunner::SegmentArena(ref segment_arena) => segment_arena.initial_stack(),
             BuiltinRunner::Signature(ref signature) => signature.initial_stack(),
+            BuiltinRunner::Mod(ref modulo) => modulo.initial_stack(),
         }
     }
 
+    ///Checks the stop pointer the program returned for the builtin at stack_pointer - 1 against
+    ///the one expected from its used instances, returning the stack pointer past it and the stop
+    ///pointer's offset. Builtins that aren't included leave the stack untouched.
     pub fn final_stack(
         &self,
         vm: &VirtualMachine,
         stack_pointer: Relocatable,
     ) -> Result<(Relocatable, usize), RunnerError> {
-        match *self {
-            BuiltinRunner::Bitwise(ref bitwise) => bitwise.final_stack(vm, stack_pointer),
-            BuiltinRunner::EcOp(ref ec) => ec.final_stack(vm, stack_pointer),
-            BuiltinRunner::Hash(ref hash) => hash.final_stack(vm, stack_pointer),
-            BuiltinRunner::Output(ref output) => output.final_stack(vm, stack_pointer),
-            BuiltinRunner::RangeCheck(ref range_check) => {
-                range_check.final_stack(vm, stack_pointer)
-            }
-            BuiltinRunner::Keccak(ref keccak) => keccak.final_stack(vm, stack_pointer),
-            BuiltinRunner::Signature(ref signature) => signature.final_stack(vm, stack_pointer),
+        if let BuiltinRunner::SegmentArena(ref segment_arena) = *self {
+            return segment_arena.final_stack(vm, stack_pointer);
+        }
+        if !self.included() {
+            return Ok((stack_pointer, self.base() as usize));
+        }
+        let stop_pointer_addr = stack_pointer
+            .sub_usize(1)
+            .map_err(|_| RunnerError::FinalStack)?;
+        let stop_pointer = vm
+            .get_relocatable(&stop_pointer_addr)
+            .map_err(|_| RunnerError::FinalStack)?;
+        let (num_instances, expected_stop_pointer) = self
+            .get_expected_stop_ptr(vm)
+            .map_err(|_| RunnerError::FinalStack)?;
+        if stop_pointer != expected_stop_pointer {
+            let (name, _) = self.get_memory_segment_addresses();
+            return Err(RunnerError::InvalidStopPointer(
+                name,
+                stop_pointer,
+                num_instances,
+                expected_stop_pointer,
+            ));
+        }
+        Ok((stop_pointer_addr, stop_pointer.offset))
+    }
+
+    fn included(&self) -> bool {
+        match self {
+            BuiltinRunner::Bitwise(bitwise) => bitwise._included,
+            BuiltinRunner::EcOp(ec) => ec._included,
+            BuiltinRunner::Hash(hash) => hash._included,
+            BuiltinRunner::Output(output) => output._included,
+            BuiltinRunner::RangeCheck(range_check) => range_check._included,
+            BuiltinRunner::Keccak(keccak) => keccak._included,
+            BuiltinRunner::SegmentArena(segment_arena) => segment_arena.included,
+            BuiltinRunner::Signature(signature) => signature.included,
+            BuiltinRunner::Mod(modulo) => modulo.included,
         }
     }
 
@@ -101,16 +147,13 @@ impl BuiltinRunner {
         &self,
         vm: &VirtualMachine,
     ) -> Result<usize, memory_errors::MemoryError> {
-        match *self {
-            BuiltinRunner::Bitwise(ref bitwise) => bitwise.get_allocated_memory_units(vm),
-            BuiltinRunner::EcOp(ref ec) => ec.get_allocated_memory_units(vm),
-            BuiltinRunner::Hash(ref hash) => hash.get_allocated_memory_units(vm),
-            BuiltinRunner::Output(ref output) => output.get_allocated_memory_units(vm),
-            BuiltinRunner::RangeCheck(ref range_check) => {
-                range_check.get_allocated_memory_units(vm)
+        match self.ratio() {
+            Some(ratio) => {
+                let value = safe_div_usize(vm.current_step, ratio as usize)
+                    .map_err(|_| MemoryError::ErrorCalculatingMemoryUnits)?;
+                Ok(self.cells_per_instance() as usize * value)
             }
-            BuiltinRunner::Keccak(ref keccak) => keccak.get_allocated_memory_units(vm),
-            BuiltinRunner::Signature(ref signature) => signature.get_allocated_memory_units(vm),
+            None => Ok(0),
         }
     }
 
@@ -123,7 +166,9 @@ impl BuiltinRunner {
             BuiltinRunner::Output(ref output) => output.base(),
             BuiltinRunner::RangeCheck(ref range_check) => range_check.base(),
             BuiltinRunner::Keccak(ref keccak) => keccak.base(),
+            BuiltinRunner::SegmentArena(ref segment_arena) => segment_arena.base(),
             BuiltinRunner::Signature(ref signature) => signature.base(),
+            BuiltinRunner::Mod(ref modulo) => modulo.base(),
         }
     }
 
@@ -135,7 +180,55 @@ impl BuiltinRunner {
             BuiltinRunner::Output(_) => None,
             BuiltinRunner::RangeCheck(range_check) => Some(range_check.ratio()),
             BuiltinRunner::Keccak(keccak) => Some(keccak.ratio()),
+            BuiltinRunner::SegmentArena(_) => None,
             BuiltinRunner::Signature(ref signature) => Some(signature.ratio()),
+            BuiltinRunner::Mod(ref modulo) => Some(modulo.ratio()),
+        }
+    }
+
+    ///Returns the number of memory cells each instance of the builtin takes up.
+    ///The output builtin has no instances, so each of its cells is counted as one.
+    pub fn cells_per_instance(&self) -> u32 {
+        match self {
+            BuiltinRunner::Bitwise(bitwise) => bitwise.cells_per_instance,
+            BuiltinRunner::EcOp(ec) => ec.cells_per_instance,
+            BuiltinRunner::Hash(hash) => hash.cells_per_instance,
+            BuiltinRunner::Output(_) => 1,
+            BuiltinRunner::RangeCheck(range_check) => range_check.cells_per_instance,
+            BuiltinRunner::Keccak(keccak) => keccak.cells_per_instance,
+            BuiltinRunner::SegmentArena(segment_arena) => segment_arena.cells_per_instance,
+            BuiltinRunner::Signature(signature) => signature.cells_per_instance,
+            BuiltinRunner::Mod(modulo) => modulo.cells_per_instance,
+        }
+    }
+
+    ///Returns the number of cells of each instance that are written by the program, the rest
+    ///being deduced by the builtin.
+    pub fn n_input_cells(&self) -> u32 {
+        match self {
+            BuiltinRunner::Bitwise(bitwise) => bitwise.n_input_cells,
+            BuiltinRunner::EcOp(ec) => ec.n_input_cells,
+            BuiltinRunner::Hash(hash) => hash.n_input_cells,
+            BuiltinRunner::Output(_) => 1,
+            BuiltinRunner::RangeCheck(range_check) => range_check.n_input_cells,
+            BuiltinRunner::Keccak(keccak) => keccak.n_input_cells,
+            BuiltinRunner::SegmentArena(segment_arena) => segment_arena.n_input_cells,
+            BuiltinRunner::Signature(signature) => signature.n_input_cells,
+            BuiltinRunner::Mod(modulo) => modulo.n_input_cells,
+        }
+    }
+
+    fn instances_per_component(&self) -> u32 {
+        match self {
+            BuiltinRunner::Bitwise(bitwise) => bitwise.instances_per_component,
+            BuiltinRunner::EcOp(ec) => ec.instances_per_component,
+            BuiltinRunner::Hash(hash) => hash.instances_per_component,
+            BuiltinRunner::Output(_) => 1,
+            BuiltinRunner::RangeCheck(range_check) => range_check.instances_per_component,
+            BuiltinRunner::Keccak(keccak) => keccak.instances_per_component,
+            BuiltinRunner::SegmentArena(_) => 1,
+            BuiltinRunner::Signature(signature) => signature.instances_per_component,
+            BuiltinRunner::Mod(modulo) => modulo.instances_per_component,
         }
     }
 
@@ -147,7 +240,11 @@ impl BuiltinRunner {
             BuiltinRunner::Output(ref output) => output.add_validation_rule(memory),
             BuiltinRunner::RangeCheck(ref range_check) => range_check.add_validation_rule(memory),
             BuiltinRunner::Keccak(ref keccak) => keccak.add_validation_rule(memory),
+            BuiltinRunner::SegmentArena(ref segment_arena) => {
+                segment_arena.add_validation_rule(memory)
+            }
             BuiltinRunner::Signature(ref signature) => signature.add_validation_rule(memory),
+            BuiltinRunner::Mod(ref modulo) => modulo.add_validation_rule(memory),
         }
     }
 
@@ -156,6 +253,10 @@ impl BuiltinRunner {
         address: &Relocatable,
         memory: &Memory,
     ) -> Result<Option<MaybeRelocatable>, RunnerError> {
+        //A deduced cell can't be filled from a partially written instance
+        if self.instance_missing_inputs(address, memory).is_some() {
+            return Ok(None);
+        }
         match *self {
             BuiltinRunner::Bitwise(ref bitwise) => bitwise.deduce_memory_cell(address, memory),
             BuiltinRunner::EcOp(ref ec) => ec.deduce_memory_cell(address, memory),
@@ -165,10 +266,48 @@ impl BuiltinRunner {
                 range_check.deduce_memory_cell(address, memory)
             }
             BuiltinRunner::Keccak(ref keccak) => keccak.deduce_memory_cell(address, memory),
+            BuiltinRunner::SegmentArena(ref segment_arena) => {
+                segment_arena.deduce_memory_cell(address, memory)
+            }
             BuiltinRunner::Signature(ref signature) => {
                 signature.deduce_memory_cell(address, memory)
             }
+            BuiltinRunner::Mod(ref modulo) => modulo.deduce_memory_cell(address, memory),
+        }
+    }
+
+    ///Returns the number of deductions cached by the builtin, which truncate_verified_addresses
+    ///goes back to
+    pub(crate) fn verified_addresses_len(&self) -> usize {
+        match self {
+            BuiltinRunner::Hash(hash) => hash.verified_addresses.borrow().len(),
+            _ => 0,
+        }
+    }
+
+    ///Forgets the deductions cached after the first len ones, so that their cells are deduced again
+    pub(crate) fn truncate_verified_addresses(&self, len: usize) {
+        if let BuiltinRunner::Hash(hash) = self {
+            hash.verified_addresses.borrow_mut().truncate(len);
+        }
+    }
+
+    ///Returns the base of the instance holding the address if the address is one of the cells
+    ///deduced by the builtin and some of the instance's input cells haven't been written.
+    pub(crate) fn instance_missing_inputs(
+        &self,
+        address: &Relocatable,
+        memory: &Memory,
+    ) -> Option<Relocatable> {
+        let n_input_cells = self.n_input_cells() as usize;
+        let index = address.offset % self.cells_per_instance() as usize;
+        if index < n_input_cells {
+            return None;
         }
+        let instance_base = Relocatable::from((address.segment_index, address.offset - index));
+        (0..n_input_cells)
+            .any(|i| !matches!(memory.get(&(instance_base + i)), Ok(Some(_))))
+            .then_some(instance_base)
     }
 
     pub fn get_memory_accesses(
@@ -187,7 +326,7 @@ impl BuiltinRunner {
         Ok((0..segment_size).map(|i| (base, i).into()).collect())
     }
 
-    pub fn get_memory_segment_addresses(&self) -> (&'static str, (isize, Option<usize>)) {
+    pub fn get_memory_segment_addresses(&self) -> (BuiltinName, (isize, Option<usize>)) {
         match self {
             BuiltinRunner::Bitwise(ref bitwise) => bitwise.get_memory_segment_addresses(),
             BuiltinRunner::EcOp(ref ec) => ec.get_memory_segment_addresses(),
@@ -197,7 +336,11 @@ impl BuiltinRunner {
                 range_check.get_memory_segment_addresses()
             }
             BuiltinRunner::Keccak(ref keccak) => keccak.get_memory_segment_addresses(),
+            BuiltinRunner::SegmentArena(ref segment_arena) => {
+                segment_arena.get_memory_segment_addresses()
+            }
             BuiltinRunner::Signature(ref signature) => signature.get_memory_segment_addresses(),
+            BuiltinRunner::Mod(ref modulo) => modulo.get_memory_segment_addresses(),
         }
     }
 
@@ -209,20 +352,29 @@ impl BuiltinRunner {
             BuiltinRunner::Output(ref output) => output.get_used_cells(vm),
             BuiltinRunner::RangeCheck(ref range_check) => range_check.get_used_cells(vm),
             BuiltinRunner::Keccak(ref keccak) => keccak.get_used_cells(vm),
+            BuiltinRunner::SegmentArena(ref segment_arena) => segment_arena.get_used_cells(vm),
             BuiltinRunner::Signature(ref signature) => signature.get_used_cells(vm),
+            BuiltinRunner::Mod(ref modulo) => modulo.get_used_cells(vm),
         }
     }
 
     pub fn get_used_instances(&self, vm: &VirtualMachine) -> Result<usize, MemoryError> {
-        match self {
-            BuiltinRunner::Bitwise(ref bitwise) => bitwise.get_used_instances(vm),
-            BuiltinRunner::EcOp(ref ec) => ec.get_used_instances(vm),
-            BuiltinRunner::Hash(ref hash) => hash.get_used_instances(vm),
-            BuiltinRunner::Output(ref output) => output.get_used_instances(vm),
-            BuiltinRunner::RangeCheck(ref range_check) => range_check.get_used_instances(vm),
-            BuiltinRunner::Keccak(ref keccak) => keccak.get_used_instances(vm),
-            BuiltinRunner::Signature(ref signature) => signature.get_used_instances(vm),
-        }
+        let used_cells = self.get_used_cells(vm)?;
+        Ok(div_ceil(used_cells, self.cells_per_instance() as usize))
+    }
+
+    ///Returns the number of used instances and the stop pointer final_stack expects the program
+    ///to return for the builtin.
+    pub fn get_expected_stop_ptr(
+        &self,
+        vm: &VirtualMachine,
+    ) -> Result<(usize, Relocatable), MemoryError> {
+        let used_instances = self.get_used_instances(vm)?;
+        let used_cells = match self {
+            BuiltinRunner::SegmentArena(_) => self.get_used_cells(vm)?,
+            _ => used_instances * self.cells_per_instance() as usize,
+        };
+        Ok((used_instances, Relocatable::from((self.base(), used_cells))))
     }
 
     pub fn get_range_check_usage(&self, memory: &Memory) -> Option<(usize, usize)> {
@@ -239,7 +391,8 @@ impl BuiltinRunner {
     ) -> Result<usize, MemoryError> {
         match self {
             BuiltinRunner::RangeCheck(range_check) => {
-                range_check.get_used_perm_range_check_units(vm)
+                let (used_cells, _) = self.get_used_cells_and_allocated_size(vm)?;
+                Ok(used_cells * range_check.n_parts as usize)
             }
             _ => Ok(0),
         }
@@ -258,19 +411,16 @@ impl BuiltinRunner {
     }
 
     pub fn run_security_checks(&self, vm: &mut VirtualMachine) -> Result<(), VirtualMachineError> {
-        if let BuiltinRunner::Output(_) = self {
+        if let BuiltinRunner::Output(_) | BuiltinRunner::SegmentArena(_) = self {
             return Ok(());
         }
+        //Every cell of a mod builtin instance is an input, and most of them are integers
+        if let BuiltinRunner::Mod(modulo) = self {
+            return Ok(modulo.run_additional_security_checks(vm)?);
+        }
 
-        let (cells_per_instance, n_input_cells) = match self {
-            BuiltinRunner::Bitwise(x) => (x.cells_per_instance, x.n_input_cells),
-            BuiltinRunner::EcOp(x) => (x.cells_per_instance, x.n_input_cells),
-            BuiltinRunner::Hash(x) => (x.cells_per_instance, x.n_input_cells),
-            BuiltinRunner::RangeCheck(x) => (x.cells_per_instance, x.n_input_cells),
-            BuiltinRunner::Output(_) => unreachable!(),
-            BuiltinRunner::Keccak(x) => (x.cells_per_instance, x.n_input_cells),
-            BuiltinRunner::Signature(ref x) => (x.cells_per_instance, x.n_input_cells),
-        };
+        let cells_per_instance = self.cells_per_instance();
+        let n_input_cells = self.n_input_cells();
 
         let base = self.base();
         let offsets = vm
@@ -298,7 +448,9 @@ impl BuiltinRunner {
                 BuiltinRunner::Output(_) => "output",
                 BuiltinRunner::RangeCheck(_) => "range_check",
                 BuiltinRunner::Keccak(_) => "keccak",
+                BuiltinRunner::SegmentArena(_) => "segment_arena",
                 BuiltinRunner::Signature(_) => "ecdsa",
+                BuiltinRunner::Mod(ref modulo) => modulo.name().as_str(),
             })
             .into());
         }
@@ -328,7 +480,9 @@ impl BuiltinRunner {
                     BuiltinRunner::Output(_) => "output",
                     BuiltinRunner::RangeCheck(_) => "range_check",
                     BuiltinRunner::Keccak(_) => "keccak",
+                    BuiltinRunner::SegmentArena(_) => "segment_arena",
                     BuiltinRunner::Signature(_) => "ecdsa",
+                    BuiltinRunner::Mod(ref modulo) => modulo.name().as_str(),
                 },
                 missing_offsets,
             )
@@ -355,19 +509,32 @@ impl BuiltinRunner {
         &self,
         vm: &VirtualMachine,
     ) -> Result<(usize, usize), MemoryError> {
-        match self {
-            BuiltinRunner::Bitwise(ref bitwise) => bitwise.get_used_cells_and_allocated_size(vm),
-            BuiltinRunner::EcOp(ref ec) => ec.get_used_cells_and_allocated_size(vm),
-            BuiltinRunner::Hash(ref hash) => hash.get_used_cells_and_allocated_size(vm),
-            BuiltinRunner::Output(ref output) => output.get_used_cells_and_allocated_size(vm),
-            BuiltinRunner::RangeCheck(ref range_check) => {
-                range_check.get_used_cells_and_allocated_size(vm)
-            }
-            BuiltinRunner::Keccak(ref keccak) => keccak.get_used_cells_and_allocated_size(vm),
-            BuiltinRunner::Signature(ref signature) => {
-                signature.get_used_cells_and_allocated_size(vm)
+        let ratio = match self.ratio() {
+            Some(ratio) => ratio as usize,
+            None => {
+                let used = self.get_used_cells(vm)?;
+                return Ok((used, used));
             }
+        };
+        let min_step = ratio * self.instances_per_component() as usize;
+        if vm.current_step < min_step {
+            return Err(MemoryError::InsufficientAllocatedCells);
+        }
+        let used = self.get_used_cells(vm)?;
+        let size = self.cells_per_instance() as usize
+            * safe_div_usize(vm.current_step, ratio)
+                .map_err(|_| MemoryError::InsufficientAllocatedCells)?;
+        if used > size {
+            return Err(MemoryError::InsufficientAllocatedCells);
         }
+        Ok((used, size))
+    }
+
+    ///Returns the pointer a new call should receive for this builtin: its stop pointer if a
+    ///previous run already advanced it, or its base otherwise
+    pub fn current_ptr(&self) -> Relocatable {
+        let (_, (base, stop_ptr)) = self.get_memory_segment_addresses();
+        Relocatable::from((base, stop_ptr.unwrap_or(0)))
     }
 
     pub fn set_stop_ptr(&mut self, stop_ptr: usize) {
@@ -378,11 +545,21 @@ impl BuiltinRunner {
             BuiltinRunner::Output(ref mut output) => output.stop_ptr = Some(stop_ptr),
             BuiltinRunner::RangeCheck(ref mut range_check) => range_check.stop_ptr = Some(stop_ptr),
             BuiltinRunner::Keccak(ref mut keccak) => keccak.stop_ptr = Some(stop_ptr),
+            BuiltinRunner::SegmentArena(ref mut segment_arena) => {
+                segment_arena.stop_ptr = Some(stop_ptr)
+            }
             BuiltinRunner::Signature(ref mut signature) => signature.stop_ptr = Some(stop_ptr),
+            BuiltinRunner::Mod(ref mut modulo) => modulo.stop_ptr = Some(stop_ptr),
         }
     }
 }
 
+impl From<ModBuiltinRunner> for BuiltinRunner {
+    fn from(runner: ModBuiltinRunner) -> Self {
+        BuiltinRunner::Mod(runner)
+    }
+}
+
 impl From<KeccakBuiltinRunner> for BuiltinRunner {
     fn from(runner: KeccakBuiltinRunner) -> Self {
         BuiltinRunner::Keccak(runner)
@@ -419,6 +596,12 @@ impl From<RangeCheckBuiltinRunner> for BuiltinRunner {
     }
 }
 
+impl From<SegmentArenaBuiltinRunner> for BuiltinRunner {
+    fn from(runner: SegmentArenaBuiltinRunner) -> Self {
+        BuiltinRunner::SegmentArena(runner)
+    }
+}
+
 impl From<SignatureBuiltinRunner> for BuiltinRunner {
     fn from(runner: SignatureBuiltinRunner) -> Self {
         BuiltinRunner::Signature(runner)
@@ -429,6 +612,7 @@ impl From<SignatureBuiltinRunner> for BuiltinRunner {
 mod tests {
     use super::*;
     use crate::hint_processor::builtin_hint_processor::builtin_hint_processor_definition::BuiltinHintProcessor;
+    use crate::relocatable;
     use crate::types::instance_definitions::ecdsa_instance_def::EcdsaInstanceDef;
     use crate::types::instance_definitions::keccak_instance_def::KeccakInstanceDef;
     use crate::types::program::Program;
@@ -441,6 +625,26 @@ mod tests {
         vm::vm_core::VirtualMachine,
     };
 
+    #[test]
+    fn deduce_memory_cell_with_missing_inputs() {
+        let builtin: BuiltinRunner =
+            EcOpBuiltinRunner::new(&EcOpInstanceDef::default(), true).into();
+        let memory = memory![((0, 7), 1), ((0, 8), 2), ((0, 9), 3), ((0, 10), 4)];
+        assert_eq!(
+            builtin.instance_missing_inputs(&relocatable!(0, 12), &memory),
+            Some(relocatable!(0, 7))
+        );
+        assert_eq!(
+            builtin.deduce_memory_cell(&relocatable!(0, 12), &memory),
+            Ok(None)
+        );
+        //Input cells are never reported as missing inputs
+        assert_eq!(
+            builtin.instance_missing_inputs(&relocatable!(0, 11), &memory),
+            None
+        );
+    }
+
     #[test]
     fn get_memory_accesses_missing_segment_used_sizes() {
         let builtin: BuiltinRunner =
@@ -491,7 +695,7 @@ mod tests {
         let mut vm = vm!();
 
         let program = program!(
-            builtins = vec![String::from("bitwise")],
+            builtins = vec![BuiltinName::Bitwise],
             data = vec_data!(
                 (4612671182993129469_i64),
                 (5189976364521848832_i64),
@@ -534,7 +738,7 @@ mod tests {
         let mut vm = vm!();
 
         let program = program!(
-            builtins = vec![String::from("ec_op")],
+            builtins = vec![BuiltinName::EcOp],
             data = vec_data!(
                 (4612671182993129469_i64),
                 (5189976364521848832_i64),
@@ -577,7 +781,7 @@ mod tests {
         let mut vm = vm!();
 
         let program = program!(
-            builtins = vec![String::from("pedersen")],
+            builtins = vec![BuiltinName::Pedersen],
             data = vec_data!(
                 (4612671182993129469_i64),
                 (5189976364521848832_i64),
@@ -620,7 +824,7 @@ mod tests {
         let mut vm = vm!();
 
         let program = program!(
-            builtins = vec![String::from("range_check")],
+            builtins = vec![BuiltinName::RangeCheck],
             data = vec_data!(
                 (4612671182993129469_i64),
                 (5189976364521848832_i64),
@@ -664,7 +868,7 @@ mod tests {
         let mut vm = vm!();
 
         let program = program!(
-            builtins = vec![String::from("keccak")],
+            builtins = vec![BuiltinName::Keccak],
             data = vec_data!(
                 (4612671182993129469_i64),
                 (5189976364521848832_i64),
@@ -847,29 +1051,29 @@ mod tests {
             BitwiseBuiltinRunner::new(&BitwiseInstanceDef::default(), true).into();
         assert_eq!(
             bitwise_builtin.get_memory_segment_addresses(),
-            ("bitwise", (0, None)),
+            (BuiltinName::Bitwise, (0, None)),
         );
         let ec_op_builtin: BuiltinRunner =
             EcOpBuiltinRunner::new(&EcOpInstanceDef::default(), true).into();
         assert_eq!(
             ec_op_builtin.get_memory_segment_addresses(),
-            ("ec_op", (0, None)),
+            (BuiltinName::EcOp, (0, None)),
         );
         let hash_builtin: BuiltinRunner = HashBuiltinRunner::new(8, true).into();
         assert_eq!(
             hash_builtin.get_memory_segment_addresses(),
-            ("pedersen", (0, None)),
+            (BuiltinName::Pedersen, (0, None)),
         );
         let output_builtin: BuiltinRunner = OutputBuiltinRunner::new(true).into();
         assert_eq!(
             output_builtin.get_memory_segment_addresses(),
-            ("output", (0, None)),
+            (BuiltinName::Output, (0, None)),
         );
         let range_check_builtin: BuiltinRunner =
             BuiltinRunner::RangeCheck(RangeCheckBuiltinRunner::new(8, 8, true));
         assert_eq!(
             range_check_builtin.get_memory_segment_addresses(),
-            ("range_check", (0, None)),
+            (BuiltinName::RangeCheck, (0, None)),
         );
     }
 
@@ -1222,6 +1426,71 @@ mod tests {
         assert_eq!(keccak_builtin.ratio(), (Some(2048)),);
     }
 
+    #[test]
+    fn get_cells_per_instance_and_n_input_cells() {
+        let builtins: Vec<(BuiltinRunner, u32, u32)> = vec![
+            (
+                BitwiseBuiltinRunner::new(&BitwiseInstanceDef::default(), true).into(),
+                5,
+                2,
+            ),
+            (
+                EcOpBuiltinRunner::new(&EcOpInstanceDef::default(), true).into(),
+                7,
+                5,
+            ),
+            (HashBuiltinRunner::new(8, true).into(), 3, 2),
+            (OutputBuiltinRunner::new(true).into(), 1, 1),
+            (RangeCheckBuiltinRunner::new(8, 8, true).into(), 1, 1),
+            (
+                KeccakBuiltinRunner::new(&KeccakInstanceDef::default(), true).into(),
+                16,
+                8,
+            ),
+            (
+                SignatureBuiltinRunner::new(&EcdsaInstanceDef::default(), true).into(),
+                2,
+                2,
+            ),
+        ];
+        for (builtin, cells_per_instance, n_input_cells) in builtins {
+            assert_eq!(builtin.cells_per_instance(), cells_per_instance);
+            assert_eq!(builtin.n_input_cells(), n_input_cells);
+        }
+    }
+
+    #[test]
+    fn get_used_cells_and_allocated_size_custom_ratio() {
+        let builtin: BuiltinRunner =
+            BitwiseBuiltinRunner::new(&BitwiseInstanceDef::new(7), true).into();
+        let mut vm = vm!();
+        vm.segments.segment_used_sizes = Some(vec![12]);
+
+        vm.current_step = 6;
+        assert_eq!(
+            builtin.get_used_cells_and_allocated_size(&vm),
+            Err(MemoryError::InsufficientAllocatedCells)
+        );
+
+        vm.current_step = 21;
+        assert_eq!(builtin.get_allocated_memory_units(&vm), Ok(15));
+        assert_eq!(builtin.get_used_cells_and_allocated_size(&vm), Ok((12, 15)));
+
+        vm.current_step = 14;
+        assert_eq!(
+            builtin.get_used_cells_and_allocated_size(&vm),
+            Err(MemoryError::InsufficientAllocatedCells)
+        );
+    }
+
+    #[test]
+    fn get_used_cells_and_allocated_size_output() {
+        let builtin: BuiltinRunner = OutputBuiltinRunner::new(true).into();
+        let mut vm = vm!();
+        vm.segments.segment_used_sizes = Some(vec![3]);
+        assert_eq!(builtin.get_used_cells_and_allocated_size(&vm), Ok((3, 3)));
+    }
+
     #[test]
     fn bitwise_get_used_instances_test() {
         let mut vm = vm!();
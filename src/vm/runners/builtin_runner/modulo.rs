@@ -0,0 +1,651 @@
+use crate::{
+    math_utils::div_mod,
+    types::{
+        builtin_name::BuiltinName,
+        instance_definitions::mod_instance_def::{
+            ModInstanceDef, CELLS_PER_MOD, INPUT_CELLS_PER_MOD, N_WORDS,
+        },
+        relocatable::{MaybeRelocatable, Relocatable},
+    },
+    vm::{
+        errors::{memory_errors::MemoryError, runner_errors::RunnerError},
+        vm_core::VirtualMachine,
+        vm_memory::{memory::Memory, memory_segments::MemorySegmentManager},
+    },
+};
+use felt::{Felt, FeltOps};
+use num_bigint::{BigInt, BigUint};
+use num_integer::{div_ceil, Integer};
+use num_traits::{One, ToPrimitive, Zero};
+
+// Offsets of the inputs of an instance, which starts with the words of p
+const VALUES_PTR_OFFSET: usize = N_WORDS;
+const OFFSETS_PTR_OFFSET: usize = N_WORDS + 1;
+const N_OFFSET: usize = N_WORDS + 2;
+
+///Operation performed by a modular arithmetic builtin
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ModBuiltinType {
+    Add,
+    Mul,
+}
+
+///Runner of the add_mod and mul_mod builtins. Each instance describes a batch of operations
+///a op b = c (mod p) over values of N_WORDS words, whose addresses are given as offsets into a
+///values table.
+#[derive(Debug, Clone)]
+pub struct ModBuiltinRunner {
+    builtin_type: ModBuiltinType,
+    ratio: u32,
+    pub base: isize,
+    pub(crate) cells_per_instance: u32,
+    pub(crate) n_input_cells: u32,
+    instance_def: ModInstanceDef,
+    pub(crate) stop_ptr: Option<usize>,
+    pub(crate) included: bool,
+    pub(crate) instances_per_component: u32,
+}
+
+//The inputs of an instance
+struct ModInputs {
+    p: BigUint,
+    p_words: Vec<Felt>,
+    values_ptr: Relocatable,
+    offsets_ptr: Relocatable,
+    n: usize,
+}
+
+//A builtin given to fill_memory, along with the inputs of its first instance
+type ModBatch<'a> = (&'a ModBuiltinRunner, ModInputs, usize);
+
+fn read_batch<'a>(
+    memory: &mut Memory,
+    builtin: Option<(Relocatable, &'a ModBuiltinRunner, usize)>,
+) -> Result<Option<ModBatch<'a>>, RunnerError> {
+    let (ptr, runner, n) = match builtin {
+        Some(builtin) => builtin,
+        None => return Ok(None),
+    };
+    let inputs = runner.read_inputs(memory, ptr)?;
+    runner.fill_inputs(memory, ptr, &inputs)?;
+    Ok(Some((runner, inputs, n)))
+}
+
+//Fills the operation at index of the batch, returns false if it can't be filled yet
+fn fill_next(
+    memory: &mut Memory,
+    batch: &Option<ModBatch>,
+    index: usize,
+) -> Result<bool, RunnerError> {
+    match batch {
+        Some((runner, inputs, n)) if index < *n => runner.fill_value(memory, inputs, index),
+        _ => Ok(false),
+    }
+}
+
+impl ModBuiltinRunner {
+    pub(crate) fn new(
+        instance_def: &ModInstanceDef,
+        builtin_type: ModBuiltinType,
+        included: bool,
+    ) -> Self {
+        ModBuiltinRunner {
+            builtin_type,
+            ratio: instance_def.ratio,
+            base: 0,
+            cells_per_instance: CELLS_PER_MOD,
+            n_input_cells: INPUT_CELLS_PER_MOD,
+            instance_def: instance_def.clone(),
+            stop_ptr: None,
+            included,
+            instances_per_component: 1,
+        }
+    }
+
+    pub fn builtin_type(&self) -> ModBuiltinType {
+        self.builtin_type
+    }
+
+    pub fn name(&self) -> BuiltinName {
+        match self.builtin_type {
+            ModBuiltinType::Add => BuiltinName::AddMod,
+            ModBuiltinType::Mul => BuiltinName::MulMod,
+        }
+    }
+
+    ///Returns the number of operations described by each instance
+    pub fn batch_size(&self) -> usize {
+        self.instance_def.batch_size
+    }
+
+    pub fn initialize_segments(
+        &mut self,
+        segments: &mut MemorySegmentManager,
+        memory: &mut Memory,
+    ) {
+        self.base = segments.add(memory).segment_index
+    }
+
+    pub fn initial_stack(&self) -> Vec<MaybeRelocatable> {
+        if self.included {
+            vec![MaybeRelocatable::from((self.base, 0))]
+        } else {
+            vec![]
+        }
+    }
+
+    pub fn base(&self) -> isize {
+        self.base
+    }
+
+    pub fn ratio(&self) -> u32 {
+        self.ratio
+    }
+
+    pub fn add_validation_rule(&self, _memory: &mut Memory) -> Result<(), RunnerError> {
+        Ok(())
+    }
+
+    //Every cell of an instance is an input, the values are filled by fill_memory instead
+    pub fn deduce_memory_cell(
+        &self,
+        _address: &Relocatable,
+        _memory: &Memory,
+    ) -> Result<Option<MaybeRelocatable>, RunnerError> {
+        Ok(None)
+    }
+
+    pub fn get_memory_segment_addresses(&self) -> (BuiltinName, (isize, Option<usize>)) {
+        (self.name(), (self.base, self.stop_ptr))
+    }
+
+    pub fn get_used_cells(&self, vm: &VirtualMachine) -> Result<usize, MemoryError> {
+        let base = self.base();
+        vm.segments
+            .get_segment_used_size(
+                base.try_into()
+                    .map_err(|_| MemoryError::AddressInTemporarySegment(base))?,
+            )
+            .ok_or(MemoryError::MissingSegmentUsedSizes)
+    }
+
+    //Reads a value stored as N_WORDS words, from the least significant one. None if a word is missing
+    fn read_n_words_value(
+        &self,
+        memory: &Memory,
+        addr: Relocatable,
+    ) -> Result<Option<BigUint>, RunnerError> {
+        let word_bit_len = self.instance_def.word_bit_len;
+        let mut value = BigUint::zero();
+        for i in (0..N_WORDS).rev() {
+            let word = match memory.get(&(addr + i))? {
+                None => return Ok(None),
+                Some(word) => match word.as_ref() {
+                    MaybeRelocatable::Int(word) => word.clone(),
+                    _ => return Err(RunnerError::ExpectedInteger((addr + i).into())),
+                },
+            };
+            if word.bits() > word_bit_len as u64 {
+                return Err(RunnerError::IntegerBiggerThanPowerOfTwo(
+                    (addr + i).into(),
+                    word_bit_len,
+                    word,
+                ));
+            }
+            value = (value << word_bit_len) + word.to_biguint();
+        }
+        Ok(Some(value))
+    }
+
+    fn write_n_words_value(
+        &self,
+        memory: &mut Memory,
+        addr: Relocatable,
+        value: &BigUint,
+    ) -> Result<(), RunnerError> {
+        let word_bit_len = self.instance_def.word_bit_len as usize;
+        let mask = (BigUint::one() << word_bit_len) - 1_u32;
+        for i in 0..N_WORDS {
+            let word = Felt::from((value >> (i * word_bit_len)) & &mask);
+            memory.insert(&(addr + i), &MaybeRelocatable::from(word))?;
+        }
+        Ok(())
+    }
+
+    fn read_inputs(&self, memory: &Memory, addr: Relocatable) -> Result<ModInputs, RunnerError> {
+        let p = self
+            .read_n_words_value(memory, addr)?
+            .ok_or_else(|| RunnerError::MemoryGet(addr.into()))?;
+        let p_words = (0..N_WORDS)
+            .map(|i| {
+                memory
+                    .get_integer(&(addr + i))
+                    .map(|word| word.into_owned())
+            })
+            .collect::<Result<_, _>>()
+            .map_err(|_| RunnerError::ExpectedInteger(addr.into()))?;
+        let values_ptr = memory
+            .get_relocatable(&(addr + VALUES_PTR_OFFSET))
+            .map_err(|_| RunnerError::MemoryGet((addr + VALUES_PTR_OFFSET).into()))?;
+        let offsets_ptr = memory
+            .get_relocatable(&(addr + OFFSETS_PTR_OFFSET))
+            .map_err(|_| RunnerError::MemoryGet((addr + OFFSETS_PTR_OFFSET).into()))?;
+        let n = memory
+            .get_integer(&(addr + N_OFFSET))
+            .map_err(|_| RunnerError::ExpectedInteger((addr + N_OFFSET).into()))?
+            .to_usize()
+            .ok_or_else(|| RunnerError::ExpectedInteger((addr + N_OFFSET).into()))?;
+        if n < 1 {
+            return Err(RunnerError::ModBuiltinNLessThanOne(self.name(), addr));
+        }
+        Ok(ModInputs {
+            p,
+            p_words,
+            values_ptr,
+            offsets_ptr,
+            n,
+        })
+    }
+
+    //Returns the address and value, if known, of a, b and c for the operation at index of the
+    //batch starting at inputs
+    fn read_operation(
+        &self,
+        memory: &Memory,
+        inputs: &ModInputs,
+        index: usize,
+    ) -> Result<[(Relocatable, Option<BigUint>); 3], RunnerError> {
+        let read_operand = |i: usize| -> Result<_, RunnerError> {
+            let offset_addr = inputs.offsets_ptr + (3 * index + i);
+            let offset = memory
+                .get_integer(&offset_addr)
+                .map_err(|_| RunnerError::ExpectedInteger(offset_addr.into()))?
+                .to_usize()
+                .ok_or_else(|| RunnerError::ExpectedInteger(offset_addr.into()))?;
+            let addr = inputs.values_ptr + offset;
+            Ok((addr, self.read_n_words_value(memory, addr)?))
+        };
+        Ok([read_operand(0)?, read_operand(1)?, read_operand(2)?])
+    }
+
+    //Writes the inputs of the instances that follow the one at ptr, each one describing the next
+    //batch of the n operations
+    fn fill_inputs(
+        &self,
+        memory: &mut Memory,
+        ptr: Relocatable,
+        inputs: &ModInputs,
+    ) -> Result<(), RunnerError> {
+        let batch_size = self.instance_def.batch_size;
+        for instance in 1..div_ceil(inputs.n, batch_size) {
+            let instance_ptr = ptr + instance * self.cells_per_instance as usize;
+            for (i, word) in inputs.p_words.iter().enumerate() {
+                memory.insert(&(instance_ptr + i), &MaybeRelocatable::from(word.clone()))?;
+            }
+            memory.insert(&(instance_ptr + VALUES_PTR_OFFSET), &inputs.values_ptr)?;
+            memory.insert(
+                &(instance_ptr + OFFSETS_PTR_OFFSET),
+                &(inputs.offsets_ptr + instance * 3 * batch_size),
+            )?;
+            memory.insert(
+                &(instance_ptr + N_OFFSET),
+                &MaybeRelocatable::from(inputs.n - instance * batch_size),
+            )?;
+        }
+        Ok(())
+    }
+
+    fn apply_operation(&self, a: &BigUint, b: &BigUint, p: &BigUint) -> Option<BigUint> {
+        if p.is_zero() {
+            return None;
+        }
+        Some(match self.builtin_type {
+            ModBuiltinType::Add => (a + b) % p,
+            ModBuiltinType::Mul => (a * b) % p,
+        })
+    }
+
+    //Returns x such that x op operand = c (mod p), None for a product if operand isn't invertible
+    fn apply_inverse_operation(
+        &self,
+        c: &BigUint,
+        operand: &BigUint,
+        p: &BigUint,
+    ) -> Option<BigUint> {
+        if p.is_zero() {
+            return None;
+        }
+        let (c, operand, p) = (
+            BigInt::from(c.clone()),
+            BigInt::from(operand.clone()),
+            BigInt::from(p.clone()),
+        );
+        let value = match self.builtin_type {
+            ModBuiltinType::Add => (c - operand).mod_floor(&p),
+            ModBuiltinType::Mul if operand.gcd(&p).is_one() => div_mod(&c, &operand, &p),
+            ModBuiltinType::Mul => return None,
+        };
+        value.to_biguint()
+    }
+
+    //Deduces the missing value of the operation at index when the other two are known. Returns
+    //whether all three values are known afterwards
+    fn fill_value(
+        &self,
+        memory: &mut Memory,
+        inputs: &ModInputs,
+        index: usize,
+    ) -> Result<bool, RunnerError> {
+        let [(a_addr, a), (b_addr, b), (c_addr, c)] = self.read_operation(memory, inputs, index)?;
+        let p = &inputs.p;
+        let (addr, value) = match (a, b, c) {
+            (Some(_), Some(_), Some(_)) => return Ok(true),
+            (Some(a), Some(b), None) => (c_addr, self.apply_operation(&a, &b, p)),
+            (Some(a), None, Some(c)) => (b_addr, self.apply_inverse_operation(&c, &a, p)),
+            (None, Some(b), Some(c)) => (a_addr, self.apply_inverse_operation(&c, &b, p)),
+            _ => return Ok(false),
+        };
+        match value {
+            Some(value) => {
+                self.write_n_words_value(memory, addr, &value)?;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    ///Fills the inputs of the instances after the first one of each builtin, and the values of
+    ///their operations, deducing each missing value from the other two of its operation.
+    ///Each builtin is given as the address of its first instance, its runner and the number of
+    ///operations to fill. When one builtin can't fill its next operation the other one is tried,
+    ///as its values may be needed first.
+    pub fn fill_memory(
+        memory: &mut Memory,
+        add_mod: Option<(Relocatable, &ModBuiltinRunner, usize)>,
+        mul_mod: Option<(Relocatable, &ModBuiltinRunner, usize)>,
+    ) -> Result<(), RunnerError> {
+        let add_mod = read_batch(memory, add_mod)?;
+        let mul_mod = read_batch(memory, mul_mod)?;
+        let n_operations = |batch: &Option<ModBatch>| batch.as_ref().map_or(0, |(_, _, n)| *n);
+        let (add_mod_n, mul_mod_n) = (n_operations(&add_mod), n_operations(&mul_mod));
+        let (mut add_mod_index, mut mul_mod_index) = (0, 0);
+        while add_mod_index < add_mod_n || mul_mod_index < mul_mod_n {
+            if fill_next(memory, &add_mod, add_mod_index)? {
+                add_mod_index += 1;
+            } else if fill_next(memory, &mul_mod, mul_mod_index)? {
+                mul_mod_index += 1;
+            } else {
+                return Err(RunnerError::ModBuiltinFillMemory(
+                    add_mod_index,
+                    mul_mod_index,
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    ///Checks that each instance describes the batch following the previous one, and that the
+    ///values of every operation satisfy it modulo p
+    pub fn run_additional_security_checks(&self, vm: &VirtualMachine) -> Result<(), RunnerError> {
+        let segment_len = vm
+            .memory
+            .data
+            .get(self.base as usize)
+            .map_or(0, |segment| segment.len());
+        let n_instances = div_ceil(segment_len, self.cells_per_instance as usize);
+        let batch_size = self.instance_def.batch_size;
+        let mut prev_inputs: Option<ModInputs> = None;
+        for instance in 0..n_instances {
+            let instance_ptr =
+                Relocatable::from((self.base, instance * self.cells_per_instance as usize));
+            let inputs = self.read_inputs(&vm.memory, instance_ptr)?;
+            if let Some(prev_inputs) = prev_inputs {
+                if prev_inputs.p != inputs.p
+                    || prev_inputs.values_ptr != inputs.values_ptr
+                    || prev_inputs.offsets_ptr + 3 * batch_size != inputs.offsets_ptr
+                    || prev_inputs.n != inputs.n + batch_size
+                {
+                    return Err(RunnerError::ModBuiltinBatchMismatch(self.name(), instance));
+                }
+            }
+            for index_in_batch in 0..batch_size.min(inputs.n) {
+                let holds = match self.read_operation(&vm.memory, &inputs, index_in_batch)? {
+                    [(_, Some(a)), (_, Some(b)), (_, Some(c))] => {
+                        self.apply_operation(&a, &b, &inputs.p) == Some(c % &inputs.p)
+                    }
+                    _ => false,
+                };
+                if !holds {
+                    return Err(RunnerError::ModBuiltinSecurityCheck(
+                        self.name(),
+                        instance * batch_size + index_in_batch,
+                    ));
+                }
+            }
+            prev_inputs = Some(inputs);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::relocatable;
+    use crate::utils::test_utils::*;
+    use crate::vm::runners::builtin_runner::BuiltinRunner;
+
+    //An instance at (0, 0) describing n operations mod p, with the values table at (1, 0) and
+    //the offsets table at (2, 0)
+    fn instance_memory(p: u32, n: usize, offsets: &[usize]) -> Memory {
+        let mut memory = memory![
+            ((0, 0), p),
+            ((0, 1), 0),
+            ((0, 2), 0),
+            ((0, 3), 0),
+            ((0, 4), (1, 0)),
+            ((0, 5), (2, 0)),
+            ((0, 6), n)
+        ];
+        memory.data.extend([Vec::new(), Vec::new()]);
+        for (i, offset) in offsets.iter().enumerate() {
+            memory
+                .insert(&relocatable!(2, i), &MaybeRelocatable::from(*offset))
+                .unwrap();
+        }
+        memory
+    }
+
+    fn write_value(runner: &ModBuiltinRunner, memory: &mut Memory, offset: usize, value: u32) {
+        runner
+            .write_n_words_value(memory, relocatable!(1, offset), &BigUint::from(value))
+            .unwrap();
+    }
+
+    fn read_value(runner: &ModBuiltinRunner, memory: &Memory, offset: usize) -> Option<BigUint> {
+        runner
+            .read_n_words_value(memory, relocatable!(1, offset))
+            .unwrap()
+    }
+
+    fn add_mod() -> ModBuiltinRunner {
+        ModBuiltinRunner::new(&ModInstanceDef::new(128, 1), ModBuiltinType::Add, true)
+    }
+
+    fn mul_mod() -> ModBuiltinRunner {
+        ModBuiltinRunner::new(&ModInstanceDef::new(256, 1), ModBuiltinType::Mul, true)
+    }
+
+    #[test]
+    fn write_and_read_n_words_value() {
+        let runner = add_mod();
+        let mut memory = Memory::new();
+        memory.data.extend([Vec::new(), Vec::new()]);
+        let value = (BigUint::from(5_u32) << 96) + 3_u32;
+        runner
+            .write_n_words_value(&mut memory, relocatable!(1, 0), &value)
+            .unwrap();
+        check_memory![memory, ((1, 0), 3), ((1, 1), 5), ((1, 2), 0), ((1, 3), 0)];
+        assert_eq!(read_value(&runner, &memory, 0), Some(value));
+    }
+
+    #[test]
+    fn read_n_words_value_word_too_big() {
+        let runner = add_mod();
+        let mut memory = memory![((1, 0), 0), ((1, 2), 0), ((1, 3), 0)];
+        memory
+            .insert(
+                &relocatable!(1, 1),
+                &MaybeRelocatable::from(Felt::from(BigUint::one() << 96)),
+            )
+            .unwrap();
+        assert_eq!(
+            runner.read_n_words_value(&memory, relocatable!(1, 0)),
+            Err(RunnerError::IntegerBiggerThanPowerOfTwo(
+                (1, 1).into(),
+                96,
+                Felt::from(BigUint::one() << 96)
+            ))
+        );
+    }
+
+    #[test]
+    fn fill_memory_add_mod() {
+        let runner = add_mod();
+        //3 + 5 = c and c + b = 6 (mod 7)
+        let mut memory = instance_memory(7, 2, &[0, 4, 8, 8, 12, 16]);
+        write_value(&runner, &mut memory, 0, 3);
+        write_value(&runner, &mut memory, 4, 5);
+        write_value(&runner, &mut memory, 16, 6);
+
+        ModBuiltinRunner::fill_memory(&mut memory, Some(((0, 0).into(), &runner, 2)), None)
+            .unwrap();
+
+        assert_eq!(read_value(&runner, &memory, 8), Some(BigUint::from(1_u32)));
+        assert_eq!(read_value(&runner, &memory, 12), Some(BigUint::from(5_u32)));
+        //The second instance continues the batch
+        check_memory![
+            memory,
+            ((0, 7), 7),
+            ((0, 10), 0),
+            ((0, 11), (1, 0)),
+            ((0, 12), (2, 3)),
+            ((0, 13), 1)
+        ];
+    }
+
+    #[test]
+    fn fill_memory_add_mod_deduces_first_operand() {
+        let runner = add_mod();
+        //a + 5 = 2 (mod 7)
+        let mut memory = instance_memory(7, 1, &[0, 4, 8]);
+        write_value(&runner, &mut memory, 4, 5);
+        write_value(&runner, &mut memory, 8, 2);
+
+        ModBuiltinRunner::fill_memory(&mut memory, Some(((0, 0).into(), &runner, 1)), None)
+            .unwrap();
+
+        assert_eq!(read_value(&runner, &memory, 0), Some(BigUint::from(4_u32)));
+    }
+
+    #[test]
+    fn fill_memory_mul_mod_deduces_operand() {
+        let runner = mul_mod();
+        //3 * b = 1 (mod 7)
+        let mut memory = instance_memory(7, 1, &[0, 4, 8]);
+        write_value(&runner, &mut memory, 0, 3);
+        write_value(&runner, &mut memory, 8, 1);
+
+        ModBuiltinRunner::fill_memory(&mut memory, None, Some(((0, 0).into(), &runner, 1)))
+            .unwrap();
+
+        assert_eq!(read_value(&runner, &memory, 4), Some(BigUint::from(5_u32)));
+    }
+
+    #[test]
+    fn fill_memory_mul_mod_operand_not_invertible() {
+        let runner = mul_mod();
+        //2 * b = 4 (mod 6) has no unique solution
+        let mut memory = instance_memory(6, 1, &[0, 4, 8]);
+        write_value(&runner, &mut memory, 0, 2);
+        write_value(&runner, &mut memory, 8, 4);
+
+        assert_eq!(
+            ModBuiltinRunner::fill_memory(&mut memory, None, Some(((0, 0).into(), &runner, 1))),
+            Err(RunnerError::ModBuiltinFillMemory(0, 0))
+        );
+    }
+
+    #[test]
+    fn fill_memory_n_less_than_one() {
+        let runner = add_mod();
+        let mut memory = instance_memory(7, 0, &[]);
+        assert_eq!(
+            ModBuiltinRunner::fill_memory(&mut memory, Some(((0, 0).into(), &runner, 0)), None),
+            Err(RunnerError::ModBuiltinNLessThanOne(
+                BuiltinName::AddMod,
+                (0, 0).into()
+            ))
+        );
+    }
+
+    #[test]
+    fn run_security_checks_ok() {
+        let runner = add_mod();
+        let mut vm = vm!();
+        vm.memory = instance_memory(7, 2, &[0, 4, 8, 8, 12, 16]);
+        write_value(&runner, &mut vm.memory, 0, 3);
+        write_value(&runner, &mut vm.memory, 4, 5);
+        write_value(&runner, &mut vm.memory, 16, 6);
+        ModBuiltinRunner::fill_memory(&mut vm.memory, Some(((0, 0).into(), &runner, 2)), None)
+            .unwrap();
+
+        assert_eq!(
+            BuiltinRunner::Mod(runner).run_security_checks(&mut vm),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn run_security_checks_wrong_result() {
+        let runner = add_mod();
+        let mut vm = vm!();
+        vm.memory = instance_memory(7, 1, &[0, 4, 8]);
+        write_value(&runner, &mut vm.memory, 0, 3);
+        write_value(&runner, &mut vm.memory, 4, 5);
+        write_value(&runner, &mut vm.memory, 8, 2);
+
+        assert_eq!(
+            runner.run_additional_security_checks(&vm),
+            Err(RunnerError::ModBuiltinSecurityCheck(BuiltinName::AddMod, 0))
+        );
+    }
+
+    #[test]
+    fn run_security_checks_batch_mismatch() {
+        let runner = add_mod();
+        let mut vm = vm!();
+        vm.memory = instance_memory(7, 2, &[0, 4, 8, 8, 12, 16]);
+        write_value(&runner, &mut vm.memory, 0, 3);
+        write_value(&runner, &mut vm.memory, 4, 5);
+        write_value(&runner, &mut vm.memory, 16, 6);
+        ModBuiltinRunner::fill_memory(&mut vm.memory, Some(((0, 0).into(), &runner, 2)), None)
+            .unwrap();
+        //The second instance should start at the fourth offset
+        vm.memory.data[0][12] = Some(MaybeRelocatable::from((2, 0)));
+
+        assert_eq!(
+            runner.run_additional_security_checks(&vm),
+            Err(RunnerError::ModBuiltinBatchMismatch(BuiltinName::AddMod, 1))
+        );
+    }
+
+    #[test]
+    fn get_memory_segment_addresses() {
+        let runner = mul_mod();
+        assert_eq!(
+            runner.get_memory_segment_addresses(),
+            (BuiltinName::MulMod, (0, None))
+        );
+    }
+}
@@ -1,3 +1,4 @@
+use crate::types::builtin_name::BuiltinName;
 use crate::types::relocatable::{MaybeRelocatable, Relocatable};
 use crate::vm::errors::memory_errors::MemoryError;
 use crate::vm::errors::runner_errors::RunnerError;
@@ -53,12 +54,8 @@ impl OutputBuiltinRunner {
         Ok(None)
     }
 
-    pub fn get_allocated_memory_units(&self, _vm: &VirtualMachine) -> Result<usize, MemoryError> {
-        Ok(0)
-    }
-
-    pub fn get_memory_segment_addresses(&self) -> (&'static str, (isize, Option<usize>)) {
-        ("output", (self.base, self.stop_ptr))
+    pub fn get_memory_segment_addresses(&self) -> (BuiltinName, (isize, Option<usize>)) {
+        (BuiltinName::Output, (self.base, self.stop_ptr))
     }
 
     pub fn get_used_cells(&self, vm: &VirtualMachine) -> Result<usize, MemoryError> {
@@ -70,51 +67,6 @@ impl OutputBuiltinRunner {
             )
             .ok_or(MemoryError::MissingSegmentUsedSizes)
     }
-
-    pub fn get_used_cells_and_allocated_size(
-        &self,
-        vm: &VirtualMachine,
-    ) -> Result<(usize, usize), MemoryError> {
-        let used = self.get_used_cells(vm)?;
-        Ok((used, used))
-    }
-
-    pub fn get_used_instances(&self, vm: &VirtualMachine) -> Result<usize, MemoryError> {
-        self.get_used_cells(vm)
-    }
-
-    pub fn final_stack(
-        &self,
-        vm: &VirtualMachine,
-        pointer: Relocatable,
-    ) -> Result<(Relocatable, usize), RunnerError> {
-        if self._included {
-            if let Ok(stop_pointer) =
-                vm.get_relocatable(&(pointer.sub_usize(1)).map_err(|_| RunnerError::FinalStack)?)
-            {
-                if self.base() != stop_pointer.segment_index {
-                    return Err(RunnerError::InvalidStopPointer("range_check".to_string()));
-                }
-                let stop_ptr = stop_pointer.offset;
-                let used = self
-                    .get_used_cells(vm)
-                    .map_err(|_| RunnerError::FinalStack)?;
-                if stop_ptr != used {
-                    return Err(RunnerError::InvalidStopPointer("output".to_string()));
-                }
-
-                Ok((
-                    pointer.sub_usize(1).map_err(|_| RunnerError::FinalStack)?,
-                    stop_ptr,
-                ))
-            } else {
-                Err(RunnerError::FinalStack)
-            }
-        } else {
-            let stop_ptr = self.base() as usize;
-            Ok((pointer, stop_ptr))
-        }
-    }
 }
 
 impl Default for OutputBuiltinRunner {
@@ -149,7 +101,7 @@ mod tests {
 
         vm.segments.segment_used_sizes = Some(vec![1]);
 
-        assert_eq!(builtin.get_used_instances(&vm), Ok(1));
+        assert_eq!(BuiltinRunner::from(builtin).get_used_instances(&vm), Ok(1));
     }
 
     #[test]
@@ -170,7 +122,9 @@ mod tests {
         let pointer = Relocatable::from((2, 2));
 
         assert_eq!(
-            builtin.final_stack(&vm, pointer).unwrap(),
+            BuiltinRunner::from(builtin)
+                .final_stack(&vm, pointer)
+                .unwrap(),
             (Relocatable::from((2, 1)), 0)
         );
     }
@@ -193,8 +147,13 @@ mod tests {
         let pointer = Relocatable::from((2, 2));
 
         assert_eq!(
-            builtin.final_stack(&vm, pointer),
-            Err(RunnerError::InvalidStopPointer("output".to_string()))
+            BuiltinRunner::from(builtin).final_stack(&vm, pointer),
+            Err(RunnerError::InvalidStopPointer(
+                BuiltinName::Output,
+                Relocatable::from((0, 0)),
+                999,
+                Relocatable::from((0, 999))
+            ))
         );
     }
 
@@ -216,7 +175,9 @@ mod tests {
         let pointer = Relocatable::from((2, 2));
 
         assert_eq!(
-            builtin.final_stack(&vm, pointer).unwrap(),
+            BuiltinRunner::from(builtin)
+                .final_stack(&vm, pointer)
+                .unwrap(),
             (Relocatable::from((2, 2)), 0)
         );
     }
@@ -239,7 +200,7 @@ mod tests {
         let pointer = Relocatable::from((2, 2));
 
         assert_eq!(
-            builtin.final_stack(&vm, pointer),
+            BuiltinRunner::from(builtin).final_stack(&vm, pointer),
             Err(RunnerError::FinalStack)
         );
     }
@@ -260,7 +221,7 @@ mod tests {
 
     #[test]
     fn get_allocated_memory_units() {
-        let builtin = OutputBuiltinRunner::new(true);
+        let builtin: BuiltinRunner = OutputBuiltinRunner::new(true).into();
 
         let vm = vm!();
 
@@ -294,7 +255,7 @@ mod tests {
 
         assert_eq!(
             builtin.get_memory_segment_addresses(),
-            ("output", (0, None)),
+            (BuiltinName::Output, (0, None)),
         );
     }
 
@@ -0,0 +1,349 @@
+use crate::types::builtin_name::BuiltinName;
+use crate::types::relocatable::{MaybeRelocatable, Relocatable};
+use crate::vm::errors::memory_errors::MemoryError;
+use crate::vm::errors::runner_errors::RunnerError;
+use crate::vm::vm_core::VirtualMachine;
+use crate::vm::vm_memory::memory::Memory;
+use crate::vm::vm_memory::memory_segments::MemorySegmentManager;
+use num_integer::div_ceil;
+use num_traits::ToPrimitive;
+
+// Size of the SegmentArena struct (infos, n_segments, n_finalized) and of each SegmentInfo (start, end, finalizer_index)
+pub(crate) const ARENA_BUILTIN_SIZE: u32 = 3;
+pub(crate) const SEGMENT_INFO_SIZE: usize = 3;
+
+#[derive(Debug, Clone)]
+pub struct SegmentArenaBuiltinRunner {
+    base: isize,
+    infos_base: isize,
+    pub(crate) cells_per_instance: u32,
+    pub(crate) n_input_cells: u32,
+    pub(crate) stop_ptr: Option<usize>,
+    pub(crate) included: bool,
+}
+
+impl SegmentArenaBuiltinRunner {
+    pub fn new(included: bool) -> SegmentArenaBuiltinRunner {
+        SegmentArenaBuiltinRunner {
+            base: 0,
+            infos_base: 0,
+            cells_per_instance: ARENA_BUILTIN_SIZE,
+            n_input_cells: ARENA_BUILTIN_SIZE,
+            stop_ptr: None,
+            included,
+        }
+    }
+
+    ///Adds the infos segment and the arena segment, which starts with an empty SegmentArena pointing to the infos
+    pub fn initialize_segments(
+        &mut self,
+        segments: &mut MemorySegmentManager,
+        memory: &mut Memory,
+    ) -> Result<(), MemoryError> {
+        self.infos_base = segments.add(memory).segment_index;
+        let arena = segments.add(memory);
+        let initial_arena = vec![
+            MaybeRelocatable::from((self.infos_base, 0)),
+            MaybeRelocatable::from(0),
+            MaybeRelocatable::from(0),
+        ];
+        segments.load_data(memory, &arena.into(), &initial_arena)?;
+        self.base = arena.segment_index;
+        Ok(())
+    }
+
+    pub fn initial_stack(&self) -> Vec<MaybeRelocatable> {
+        if self.included {
+            vec![MaybeRelocatable::from((
+                self.base,
+                ARENA_BUILTIN_SIZE as usize,
+            ))]
+        } else {
+            vec![]
+        }
+    }
+
+    pub fn base(&self) -> isize {
+        self.base
+    }
+
+    ///Returns the index of the segment holding the SegmentInfo of each allocated segment
+    pub fn infos_base(&self) -> isize {
+        self.infos_base
+    }
+
+    pub fn add_validation_rule(&self, _memory: &mut Memory) -> Result<(), RunnerError> {
+        Ok(())
+    }
+
+    pub fn deduce_memory_cell(
+        &self,
+        _address: &Relocatable,
+        _memory: &Memory,
+    ) -> Result<Option<MaybeRelocatable>, RunnerError> {
+        Ok(None)
+    }
+
+    pub fn get_memory_segment_addresses(&self) -> (BuiltinName, (isize, Option<usize>)) {
+        (BuiltinName::SegmentArena, (self.base, self.stop_ptr))
+    }
+
+    pub fn get_used_cells(&self, vm: &VirtualMachine) -> Result<usize, MemoryError> {
+        let base = self.base();
+        vm.segments
+            .get_segment_used_size(
+                base.try_into()
+                    .map_err(|_| MemoryError::AddressInTemporarySegment(base))?,
+            )
+            .ok_or(MemoryError::MissingSegmentUsedSizes)
+    }
+
+    ///Returns the number of segments allocated through the arena so far
+    fn n_infos(&self, memory: &Memory) -> usize {
+        let infos_len = memory
+            .data
+            .get(self.infos_base as usize)
+            .map_or(0, |infos| infos.len());
+        div_ceil(infos_len, SEGMENT_INFO_SIZE)
+    }
+
+    ///Adds a new segment and records its start in the next SegmentInfo of the infos segment
+    pub fn add_segment(
+        &self,
+        segments: &mut MemorySegmentManager,
+        memory: &mut Memory,
+    ) -> Result<Relocatable, MemoryError> {
+        let info_addr =
+            Relocatable::from((self.infos_base, self.n_infos(memory) * SEGMENT_INFO_SIZE));
+        let segment = segments.add(memory);
+        memory.insert(&info_addr, &segment)?;
+        Ok(segment)
+    }
+
+    ///Checks that the final SegmentArena counts every allocated segment and that each finalized
+    ///segment ends within itself, after its start
+    fn validate_infos(&self, vm: &VirtualMachine, stop_ptr: usize) -> Result<(), RunnerError> {
+        let n_segments_addr = Relocatable::from((self.base, stop_ptr))
+            .sub_usize(2)
+            .map_err(|_| RunnerError::FinalStack)?;
+        let n_segments = vm
+            .get_integer(&n_segments_addr)
+            .map_err(|_| RunnerError::FinalStack)?
+            .to_usize()
+            .ok_or(RunnerError::FinalStack)?;
+        let n_infos = self.n_infos(&vm.memory);
+        if n_segments != n_infos {
+            return Err(RunnerError::SegmentArenaCountMismatch(n_infos, n_segments));
+        }
+        for index in 0..n_infos {
+            let info_addr = Relocatable::from((self.infos_base, index * SEGMENT_INFO_SIZE));
+            let start = vm
+                .get_relocatable(&info_addr)
+                .map_err(|_| RunnerError::InvalidSegmentArenaInfo(index))?;
+            let end = match vm.get_maybe(&(info_addr + 1_usize)) {
+                Ok(Some(MaybeRelocatable::RelocatableValue(end))) => end,
+                Ok(None) => continue,
+                _ => return Err(RunnerError::InvalidSegmentArenaInfo(index)),
+            };
+            if end.segment_index != start.segment_index || end.offset < start.offset {
+                return Err(RunnerError::InvalidSegmentArenaInfo(index));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn final_stack(
+        &self,
+        vm: &VirtualMachine,
+        pointer: Relocatable,
+    ) -> Result<(Relocatable, usize), RunnerError> {
+        if self.included {
+            if let Ok(stop_pointer) =
+                vm.get_relocatable(&(pointer.sub_usize(1)).map_err(|_| RunnerError::FinalStack)?)
+            {
+                let used_cells = self
+                    .get_used_cells(vm)
+                    .map_err(|_| RunnerError::FinalStack)?;
+                let num_instances = div_ceil(used_cells, self.cells_per_instance as usize);
+                let expected_stop_pointer = Relocatable::from((self.base(), used_cells));
+                if stop_pointer != expected_stop_pointer {
+                    return Err(RunnerError::InvalidStopPointer(
+                        BuiltinName::SegmentArena,
+                        stop_pointer,
+                        num_instances,
+                        expected_stop_pointer,
+                    ));
+                }
+                let stop_ptr = stop_pointer.offset;
+                self.validate_infos(vm, stop_ptr)?;
+
+                Ok((
+                    pointer.sub_usize(1).map_err(|_| RunnerError::FinalStack)?,
+                    stop_ptr,
+                ))
+            } else {
+                Err(RunnerError::FinalStack)
+            }
+        } else {
+            let stop_ptr = self.base() as usize;
+            Ok((pointer, stop_ptr))
+        }
+    }
+}
+
+impl Default for SegmentArenaBuiltinRunner {
+    fn default() -> Self {
+        Self::new(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        any_box,
+        hint_processor::{
+            builtin_hint_processor::{
+                builtin_hint_processor_definition::{BuiltinHintProcessor, HintProcessorData},
+                hint_code,
+            },
+            hint_processor_definition::HintProcessor,
+        },
+        relocatable,
+        types::exec_scope::ExecutionScopes,
+        utils::test_utils::*,
+        vm::vm_core::VirtualMachine,
+    };
+    use std::{any::Any, collections::HashMap};
+
+    ///Initializes the arena on segments 2 (infos) and 3 (arena) and allocates two segments
+    ///(4 and 5) through the hint, then writes the final SegmentArena and its pointer at (1, 2)
+    fn vm_with_two_arena_segments() -> VirtualMachine {
+        let mut vm = vm!();
+        add_segments!(vm, 2);
+        let mut builtin = SegmentArenaBuiltinRunner::new(true);
+        builtin
+            .initialize_segments(&mut vm.segments, &mut vm.memory)
+            .unwrap();
+        vm.builtin_runners
+            .push((BuiltinName::SegmentArena, builtin.into()));
+        for ap in 0..2 {
+            vm.run_context.ap = ap;
+            assert_eq!(
+                run_hint!(vm, HashMap::new(), hint_code::ALLOC_ARENA_SEGMENT),
+                Ok(())
+            );
+        }
+        for (addr, value) in [
+            (relocatable!(3, 3), mayberelocatable!(2, 0)),
+            (relocatable!(3, 4), mayberelocatable!(2)),
+            (relocatable!(3, 5), mayberelocatable!(2)),
+            (relocatable!(1, 2), mayberelocatable!(3, 6)),
+        ] {
+            vm.memory.insert(&addr, &value).unwrap();
+        }
+        vm.segments.segment_used_sizes = Some(vec![0, 3, 5, 6]);
+        vm
+    }
+
+    #[test]
+    fn initialize_segments_writes_empty_arena() {
+        let mut vm = vm!();
+        add_segments!(vm, 2);
+        let mut builtin = SegmentArenaBuiltinRunner::new(true);
+        builtin
+            .initialize_segments(&mut vm.segments, &mut vm.memory)
+            .unwrap();
+
+        assert_eq!(builtin.infos_base(), 2);
+        assert_eq!(builtin.base(), 3);
+        check_memory![vm.memory, ((3, 0), (2, 0)), ((3, 1), 0), ((3, 2), 0)];
+        assert_eq!(builtin.initial_stack(), vec![mayberelocatable!(3, 3)]);
+    }
+
+    #[test]
+    fn add_segments_through_hint_and_final_stack() {
+        let mut vm = vm_with_two_arena_segments();
+        assert_eq!(
+            vm.get_relocatable(&relocatable!(1, 0)),
+            Ok(relocatable!(4, 0))
+        );
+        assert_eq!(
+            vm.get_relocatable(&relocatable!(1, 1)),
+            Ok(relocatable!(5, 0))
+        );
+        check_memory![vm.memory, ((2, 0), (4, 0)), ((2, 3), (5, 0))];
+        //Both segments are finalized
+        vm.memory
+            .insert(&relocatable!(2, 1), &mayberelocatable!(4, 2))
+            .unwrap();
+        vm.memory
+            .insert(&relocatable!(2, 4), &mayberelocatable!(5, 0))
+            .unwrap();
+
+        let builtin = &vm.builtin_runners[0].1;
+        assert_eq!(
+            builtin.final_stack(&vm, relocatable!(1, 3)),
+            Ok((relocatable!(1, 2), 6))
+        );
+    }
+
+    #[test]
+    fn final_stack_out_of_order_ends() {
+        let mut vm = vm_with_two_arena_segments();
+        //The ends of both segments are swapped
+        vm.memory
+            .insert(&relocatable!(2, 1), &mayberelocatable!(5, 1))
+            .unwrap();
+        vm.memory
+            .insert(&relocatable!(2, 4), &mayberelocatable!(4, 2))
+            .unwrap();
+
+        let builtin = &vm.builtin_runners[0].1;
+        assert_eq!(
+            builtin.final_stack(&vm, relocatable!(1, 3)),
+            Err(RunnerError::InvalidSegmentArenaInfo(0))
+        );
+    }
+
+    #[test]
+    fn add_segment_hint_bypasses_arena() {
+        let mut vm = vm_with_two_arena_segments();
+        vm.memory
+            .insert(&relocatable!(2, 1), &mayberelocatable!(4, 2))
+            .unwrap();
+        vm.run_context.ap = 5;
+        //The Cairo 0 alloc() hint adds a plain segment without a SegmentInfo
+        assert_eq!(
+            run_hint!(vm, HashMap::new(), hint_code::ADD_SEGMENT),
+            Ok(())
+        );
+        assert_eq!(
+            vm.get_relocatable(&relocatable!(1, 5)),
+            Ok(relocatable!(6, 0))
+        );
+        assert_eq!(vm.memory.data[2].len(), 4);
+
+        let builtin = &vm.builtin_runners[0].1;
+        assert_eq!(
+            builtin.final_stack(&vm, relocatable!(1, 3)),
+            Ok((relocatable!(1, 2), 6))
+        );
+    }
+
+    #[test]
+    fn final_stack_segment_count_mismatch() {
+        let mut vm = vm_with_two_arena_segments();
+        vm.run_context.ap = 5;
+        assert_eq!(
+            run_hint!(vm, HashMap::new(), hint_code::ALLOC_ARENA_SEGMENT),
+            Ok(())
+        );
+
+        let builtin = &vm.builtin_runners[0].1;
+        assert_eq!(
+            builtin.final_stack(&vm, relocatable!(1, 3)),
+            Err(RunnerError::SegmentArenaCountMismatch(3, 2))
+        );
+    }
+}
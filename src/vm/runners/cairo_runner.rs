@@ -1,24 +1,34 @@
 use crate::{
-    hint_processor::hint_processor_definition::{HintProcessor, HintReference},
+    hint_processor::{
+        builtin_hint_processor::dict_manager::Dictionary,
+        hint_processor_definition::{HintProcessor, HintReference},
+    },
     math_utils::safe_div_usize,
     serde::deserialize_program::OffsetValue,
     types::{
+        builtin_name::BuiltinName,
         errors::program_errors::ProgramError,
         exec_scope::ExecutionScopes,
         instance_definitions::{
             bitwise_instance_def::BitwiseInstanceDef, ec_op_instance_def::EcOpInstanceDef,
-            ecdsa_instance_def::EcdsaInstanceDef,
+            ecdsa_instance_def::EcdsaInstanceDef, mod_instance_def::ModInstanceDef,
         },
         instruction::Register,
         layout::CairoLayout,
         program::Program,
-        relocatable::{relocate_address, relocate_value, MaybeRelocatable, Relocatable},
+        program_lint::LintIssue,
+        relocatable::{MaybeRelocatable, Relocatable},
     },
     utils::is_subsequence,
     vm::{
+        const_view::VmConstView,
         errors::{
-            memory_errors::MemoryError, runner_errors::RunnerError, trace_errors::TraceError,
-            vm_errors::VirtualMachineError,
+            cairo_run_errors::CairoRunError, memory_errors::MemoryError,
+            runner_errors::RunnerError, trace_errors::TraceError, vm_errors::VirtualMachineError,
+        },
+        runners::{
+            output_verifier::OutputVerifier,
+            run_report::{RegistersReport, RunErrorReport, RunReport},
         },
         security::verify_secure_runner,
         trace::get_perm_range_check_limits,
@@ -26,24 +36,31 @@ use crate::{
         {
             runners::builtin_runner::{
                 BitwiseBuiltinRunner, BuiltinRunner, EcOpBuiltinRunner, HashBuiltinRunner,
-                OutputBuiltinRunner, RangeCheckBuiltinRunner, SignatureBuiltinRunner,
+                ModBuiltinRunner, ModBuiltinType, OutputBuiltinRunner, RangeCheckBuiltinRunner,
+                SegmentArenaBuiltinRunner, SignatureBuiltinRunner,
             },
             trace::trace_entry::{relocate_trace_register, RelocatedTraceEntry},
-            vm_core::VirtualMachine,
+            vm_core::{RegisterBounds, VirtualMachine},
         },
     },
 };
 use felt::{Felt, FeltOps};
 use num_integer::div_rem;
 use num_traits::Zero;
+use serde::Serialize;
 use std::{
     any::Any,
-    collections::{HashMap, HashSet},
-    io,
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt, io,
+    rc::Rc,
+    sync::atomic::{AtomicBool, Ordering},
+    time::{Duration, Instant},
 };
 
 use super::builtin_runner::KeccakBuiltinRunner;
 
+pub use crate::vm::run_outcome::{BreakReason, RunOutcome};
+
 pub struct CairoRunner {
     pub(crate) program: Program,
     layout: CairoLayout,
@@ -58,12 +75,26 @@ pub struct CairoRunner {
     execution_public_memory: Option<Vec<usize>>,
     proof_mode: bool,
     pub original_steps: Option<usize>,
+    #[deprecated(note = "use CairoRunner::get_relocated_memory, the field will become private")]
     pub relocated_memory: Vec<Option<Felt>>,
+    #[deprecated(note = "use CairoRunner::get_relocated_trace, the field will become private")]
     pub relocated_trace: Option<Vec<RelocatedTraceEntry>>,
     pub exec_scopes: ExecutionScopes,
+    ///Makes the vm fail as soon as pc or fp leave their segments, enabled by default
+    pub strict_registers: bool,
+    ///Makes initialize fail if Program::lint finds errors, disabled by default
+    pub strict_lint: bool,
+    ///Makes read_return_values and run_from_function take a builtin whose stop pointer isn't on
+    ///the stack as not returned, for debugging programs that don't return their builtins.
+    ///Disabled by default
+    pub allow_missing_builtin_stop_ptrs: bool,
+    run_started: Option<Instant>,
+    run_duration: Option<Duration>,
+    program_hash: Option<Felt>,
 }
 
 impl CairoRunner {
+    #[allow(deprecated)]
     pub fn new(
         program: &Program,
         layout: &str,
@@ -77,6 +108,7 @@ impl CairoRunner {
             "bitwise" => CairoLayout::bitwise_instance(),
             "recursive" => CairoLayout::recursive_instance(),
             "all" => CairoLayout::all_instance(),
+            "all_cairo" => CairoLayout::all_cairo_instance(),
             name => return Err(RunnerError::InvalidLayoutName(name.to_string())),
         };
         Ok(CairoRunner {
@@ -95,58 +127,74 @@ impl CairoRunner {
             relocated_memory: Vec::new(),
             relocated_trace: None,
             exec_scopes: ExecutionScopes::new(),
+            strict_registers: true,
+            strict_lint: false,
+            allow_missing_builtin_stop_ptrs: false,
             execution_public_memory: if proof_mode { Some(Vec::new()) } else { None },
+            run_started: None,
+            run_duration: None,
+            program_hash: None,
         })
     }
 
     pub fn initialize(&mut self, vm: &mut VirtualMachine) -> Result<Relocatable, RunnerError> {
+        if self.strict_lint {
+            let errors: Vec<LintIssue> = self
+                .program
+                .lint()
+                .into_iter()
+                .filter(LintIssue::is_error)
+                .collect();
+            if !errors.is_empty() {
+                return Err(RunnerError::ProgramLint(errors));
+            }
+        }
         self.initialize_builtins(vm)?;
-        self.initialize_segments(vm, None);
+        self.initialize_segments(vm, None)?;
         let end = self.initialize_main_entrypoint(vm)?;
         self.initialize_vm(vm)?;
         Ok(end)
     }
 
     pub fn initialize_builtins(&self, vm: &mut VirtualMachine) -> Result<(), RunnerError> {
-        let builtin_ordered_list = vec![
-            String::from("output"),
-            String::from("pedersen"),
-            String::from("range_check"),
-            String::from("ecdsa"),
-            String::from("bitwise"),
-            String::from("ec_op"),
-            String::from("keccak"),
-        ];
-        if !is_subsequence(&self.program.builtins, &builtin_ordered_list) {
+        let program_builtins = &self.program.builtins;
+        if let Some(duplicate) = program_builtins
+            .iter()
+            .enumerate()
+            .find_map(|(index, name)| program_builtins[..index].contains(name).then_some(name))
+        {
+            return Err(RunnerError::DuplicateBuiltin(*duplicate));
+        }
+        if !is_subsequence(program_builtins, &BuiltinName::ORDERED) {
             return Err(RunnerError::DisorderedBuiltins);
         };
-        let mut builtin_runners = Vec::<(String, BuiltinRunner)>::new();
+        let mut builtin_runners = Vec::<(BuiltinName, BuiltinRunner)>::new();
 
         if self.layout.builtins._output {
-            let included = self.program.builtins.contains(&"output".to_string());
+            let included = program_builtins.contains(&BuiltinName::Output);
             if included || self.proof_mode {
                 builtin_runners.push((
-                    "output".to_string(),
+                    BuiltinName::Output,
                     OutputBuiltinRunner::new(included).into(),
                 ));
             }
         }
 
         if let Some(instance_def) = self.layout.builtins.pedersen.as_ref() {
-            let included = self.program.builtins.contains(&"pedersen".to_string());
+            let included = program_builtins.contains(&BuiltinName::Pedersen);
             if included || self.proof_mode {
                 builtin_runners.push((
-                    "pedersen".to_string(),
+                    BuiltinName::Pedersen,
                     HashBuiltinRunner::new(instance_def.ratio, included).into(),
                 ));
             }
         }
 
         if let Some(instance_def) = self.layout.builtins.range_check.as_ref() {
-            let included = self.program.builtins.contains(&"range_check".to_string());
+            let included = program_builtins.contains(&BuiltinName::RangeCheck);
             if included || self.proof_mode {
                 builtin_runners.push((
-                    "range_check".to_string(),
+                    BuiltinName::RangeCheck,
                     RangeCheckBuiltinRunner::new(
                         instance_def.ratio,
                         instance_def.n_parts,
@@ -158,57 +206,84 @@ impl CairoRunner {
         }
 
         if let Some(instance_def) = self.layout.builtins._ecdsa.as_ref() {
-            let included = self.program.builtins.contains(&"ecdsa".to_string());
+            let included = program_builtins.contains(&BuiltinName::Ecdsa);
             if included || self.proof_mode {
                 builtin_runners.push((
-                    "ecdsa".to_string(),
+                    BuiltinName::Ecdsa,
                     SignatureBuiltinRunner::new(instance_def, included).into(),
                 ));
             }
         }
 
         if let Some(instance_def) = self.layout.builtins.bitwise.as_ref() {
-            let included = self.program.builtins.contains(&"bitwise".to_string());
+            let included = program_builtins.contains(&BuiltinName::Bitwise);
             if included || self.proof_mode {
                 builtin_runners.push((
-                    "bitwise".to_string(),
+                    BuiltinName::Bitwise,
                     BitwiseBuiltinRunner::new(instance_def, included).into(),
                 ));
             }
         }
 
         if let Some(instance_def) = self.layout.builtins.ec_op.as_ref() {
-            let included = self.program.builtins.contains(&"ec_op".to_string());
+            let included = program_builtins.contains(&BuiltinName::EcOp);
             if included || self.proof_mode {
                 builtin_runners.push((
-                    "ec_op".to_string(),
+                    BuiltinName::EcOp,
                     EcOpBuiltinRunner::new(instance_def, included).into(),
                 ));
             }
         }
 
         if let Some(instance_def) = self.layout.builtins.keccak.as_ref() {
-            let included = self.program.builtins.contains(&"keccak".to_string());
+            let included = program_builtins.contains(&BuiltinName::Keccak);
             if included || self.proof_mode {
                 builtin_runners.push((
-                    "keccak".to_string(),
+                    BuiltinName::Keccak,
                     KeccakBuiltinRunner::new(instance_def, included).into(),
                 ));
             }
         }
 
+        // The segment arena isn't part of any layout, Cairo 1 programs get it whenever they request it
+        if program_builtins.contains(&BuiltinName::SegmentArena) {
+            builtin_runners.push((
+                BuiltinName::SegmentArena,
+                SegmentArenaBuiltinRunner::new(true).into(),
+            ));
+        }
+
+        if let Some(instance_def) = self.layout.builtins.add_mod.as_ref() {
+            let included = program_builtins.contains(&BuiltinName::AddMod);
+            if included || self.proof_mode {
+                builtin_runners.push((
+                    BuiltinName::AddMod,
+                    ModBuiltinRunner::new(instance_def, ModBuiltinType::Add, included).into(),
+                ));
+            }
+        }
+
+        if let Some(instance_def) = self.layout.builtins.mul_mod.as_ref() {
+            let included = program_builtins.contains(&BuiltinName::MulMod);
+            if included || self.proof_mode {
+                builtin_runners.push((
+                    BuiltinName::MulMod,
+                    ModBuiltinRunner::new(instance_def, ModBuiltinType::Mul, included).into(),
+                ));
+            }
+        }
+
         let inserted_builtins = builtin_runners
             .iter()
             .map(|x| &x.0)
-            .collect::<HashSet<&String>>();
-        let program_builtins: HashSet<&String> =
-            self.program.builtins.iter().collect::<HashSet<&String>>();
+            .collect::<HashSet<&BuiltinName>>();
+        let program_builtins: HashSet<&BuiltinName> = program_builtins.iter().collect();
         // Get the builtins that belong to the program but weren't inserted (those who dont belong to the instance)
         if !program_builtins.is_subset(&inserted_builtins) {
             return Err(RunnerError::NoBuiltinForInstance(
                 program_builtins
                     .difference(&inserted_builtins)
-                    .map(|x| (**x).clone())
+                    .map(|x| **x)
                     .collect(),
                 self.layout._name.clone(),
             ));
@@ -221,54 +296,54 @@ impl CairoRunner {
     // Initialize all the builtins. Values used are the original one from the CairoFunctionRunner
     // Values extracted from here: https://github.com/starkware-libs/cairo-lang/blob/4fb83010ab77aa7ead0c9df4b0c05e030bc70b87/src/starkware/cairo/common/cairo_function_runner.py#L28
     fn initialize_all_builtins(&self, vm: &mut VirtualMachine) -> Result<(), RunnerError> {
-        let starknet_preset_builtins = vec![
-            String::from("pedersen"),
-            String::from("range_check"),
-            String::from("output"),
-            String::from("ecdsa"),
-            String::from("bitwise"),
-            String::from("ec_op"),
-            String::from("keccak"),
+        let starknet_preset_builtins = [
+            BuiltinName::Pedersen,
+            BuiltinName::RangeCheck,
+            BuiltinName::Output,
+            BuiltinName::Ecdsa,
+            BuiltinName::Bitwise,
+            BuiltinName::EcOp,
+            BuiltinName::Keccak,
         ];
 
-        fn initialize_builtin(name: &str, vm: &mut VirtualMachine) {
-            match name {
-                "pedersen" => vm
-                    .builtin_runners
-                    .push((name.to_string(), HashBuiltinRunner::new(32, true).into())),
-                "range_check" => vm.builtin_runners.push((
-                    name.to_string(),
-                    RangeCheckBuiltinRunner::new(1, 8, true).into(),
-                )),
-                "output" => vm
-                    .builtin_runners
-                    .push((name.to_string(), OutputBuiltinRunner::new(true).into())),
-                "ecdsa" => vm.builtin_runners.push((
-                    name.to_string(),
-                    SignatureBuiltinRunner::new(&EcdsaInstanceDef::new(1), true).into(),
-                )),
-                "bitwise" => vm.builtin_runners.push((
-                    name.to_string(),
-                    BitwiseBuiltinRunner::new(&BitwiseInstanceDef::new(1), true).into(),
-                )),
-                "ec_op" => vm.builtin_runners.push((
-                    name.to_string(),
-                    EcOpBuiltinRunner::new(&EcOpInstanceDef::new(1), true).into(),
-                )),
-                "keccak" => vm.builtin_runners.push((
-                    name.to_string(),
-                    EcOpBuiltinRunner::new(&EcOpInstanceDef::new(1), true).into(),
-                )),
-                _ => {}
-            }
+        fn initialize_builtin(
+            name: BuiltinName,
+            vm: &mut VirtualMachine,
+        ) -> Result<(), RunnerError> {
+            let builtin: BuiltinRunner = match name {
+                BuiltinName::Pedersen => HashBuiltinRunner::new(32, true).into(),
+                BuiltinName::RangeCheck => RangeCheckBuiltinRunner::new(1, 8, true).into(),
+                BuiltinName::Output => OutputBuiltinRunner::new(true).into(),
+                BuiltinName::Ecdsa => {
+                    SignatureBuiltinRunner::new(&EcdsaInstanceDef::new(1), true).into()
+                }
+                BuiltinName::Bitwise => {
+                    BitwiseBuiltinRunner::new(&BitwiseInstanceDef::new(1), true).into()
+                }
+                BuiltinName::EcOp => EcOpBuiltinRunner::new(&EcOpInstanceDef::new(1), true).into(),
+                BuiltinName::Keccak => {
+                    EcOpBuiltinRunner::new(&EcOpInstanceDef::new(1), true).into()
+                }
+                BuiltinName::SegmentArena => SegmentArenaBuiltinRunner::new(true).into(),
+                BuiltinName::AddMod => {
+                    ModBuiltinRunner::new(&ModInstanceDef::new(1, 1), ModBuiltinType::Add, true)
+                        .into()
+                }
+                BuiltinName::MulMod => {
+                    ModBuiltinRunner::new(&ModInstanceDef::new(1, 1), ModBuiltinType::Mul, true)
+                        .into()
+                }
+                BuiltinName::AdditionalHash => return Ok(()),
+            };
+            vm.add_builtin_runner(name, builtin)
         }
 
         for builtin_name in &self.program.builtins {
-            initialize_builtin(builtin_name, vm);
+            initialize_builtin(*builtin_name, vm)?;
         }
         for builtin_name in starknet_preset_builtins {
             if !self.program.builtins.contains(&builtin_name) {
-                initialize_builtin(&builtin_name, vm)
+                initialize_builtin(builtin_name, vm)?
             }
         }
         Ok(())
@@ -279,15 +354,21 @@ impl CairoRunner {
         &mut self,
         vm: &mut VirtualMachine,
         program_base: Option<Relocatable>,
-    ) {
+    ) -> Result<(), RunnerError> {
         self.program_base = match program_base {
             Some(base) => Some(base),
-            None => Some(vm.segments.add(&mut vm.memory)),
+            None => Some(
+                vm.segments
+                    .add_with_capacity(&mut vm.memory, self.program.data.len()),
+            ),
         };
         self.execution_base = Some(vm.segments.add(&mut vm.memory));
         for (_key, builtin_runner) in vm.builtin_runners.iter_mut() {
-            builtin_runner.initialize_segments(&mut vm.segments, &mut vm.memory);
+            builtin_runner
+                .initialize_segments(&mut vm.segments, &mut vm.memory)
+                .map_err(RunnerError::MemoryInitializationError)?;
         }
+        Ok(())
     }
 
     fn initialize_state(
@@ -408,29 +489,54 @@ impl CairoRunner {
         vm.run_context.pc = *self.initial_pc.as_ref().ok_or(RunnerError::NoPC)?;
         vm.run_context.ap = self.initial_ap.as_ref().ok_or(RunnerError::NoAP)?.offset;
         vm.run_context.fp = self.initial_fp.as_ref().ok_or(RunnerError::NoFP)?.offset;
-        vm._program_base = Some(MaybeRelocatable::from(
-            self.program_base.as_ref().ok_or(RunnerError::NoProgBase)?,
-        ));
+        let program_base = self.program_base.ok_or(RunnerError::NoProgBase)?;
+        vm.program_base = Some(program_base);
         for (_, builtin) in vm.builtin_runners.iter() {
             builtin.add_validation_rule(&mut vm.memory)?;
         }
 
         // Mark all addresses from the program segment as accessed
-        let prog_segment_index = self
-            .program_base
-            .as_ref()
-            .unwrap_or(&Relocatable::from((0, 0)))
-            .segment_index;
-
         let initial_accessed_addresses = (0..self.program.data.len())
-            .map(|offset| Relocatable::from((prog_segment_index, offset)))
+            .map(|offset| program_base + offset)
             .collect();
 
         vm.accessed_addresses = Some(initial_accessed_addresses);
 
+        let execution_segment = self
+            .initial_ap
+            .as_ref()
+            .ok_or(RunnerError::NoAP)?
+            .segment_index;
+        vm.run_context.execution_segment = execution_segment;
+        let rc_bound = vm
+            .builtin_runners
+            .iter()
+            .find_map(|(_, builtin)| match builtin {
+                BuiltinRunner::RangeCheck(range_check) => range_check._bound.clone(),
+                _ => None,
+            });
+        vm.const_view = Rc::new(VmConstView::new(
+            rc_bound,
+            &self.layout._name,
+            self.program_hash.clone(),
+        ));
+        vm.register_bounds = self.strict_registers.then_some(RegisterBounds {
+            program_segment: program_base.segment_index,
+            execution_segment,
+            final_pc: self.final_pc,
+        });
+
         vm.memory
             .validate_existing_memory()
-            .map_err(RunnerError::MemoryValidationError)
+            .map_err(RunnerError::MemoryValidationError)?;
+        //wasm32 has no clock, Instant::now panics there, so runs on it aren't timed
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            self.run_started = Some(Instant::now());
+            #[cfg(feature = "run_timer")]
+            vm.start_timer();
+        }
+        Ok(())
     }
 
     pub fn get_initial_fp(&self) -> Option<Relocatable> {
@@ -465,6 +571,15 @@ impl CairoRunner {
         references
     }
 
+    ///Computes the hash of the program, see Program::compute_hash, and keeps it so the hints are
+    ///compiled knowing the program they belong to. Needed by hint processors which dispatch hints
+    ///by program and pc, see BuiltinHintProcessor::register_for_program.
+    pub fn compute_program_hash(&mut self) -> Result<Felt, ProgramError> {
+        let program_hash = self.program.compute_hash()?;
+        self.program_hash = Some(program_hash.clone());
+        Ok(program_hash)
+    }
+
     /// Gets the data used by the HintProcessor to execute each hint
     pub fn get_hint_data_dictionary(
         &self,
@@ -474,7 +589,10 @@ impl CairoRunner {
         let mut hint_data_dictionary = HashMap::<usize, Vec<Box<dyn Any>>>::new();
         for (hint_index, hints) in self.program.hints.iter() {
             for hint in hints {
-                let hint_data = hint_executor.compile_hint(
+                let hint_data = hint_executor.compile_hint_at(
+                    self.program_hash.as_ref(),
+                    *hint_index,
+                    &hint.accessible_scopes,
                     &hint.code,
                     &hint.flow_tracking_data.ap_tracking,
                     &hint.flow_tracking_data.reference_ids,
@@ -493,18 +611,24 @@ impl CairoRunner {
         &self.program.constants
     }
 
-    pub fn get_program_builtins(&self) -> &Vec<String> {
+    pub fn get_program_builtins(&self) -> &Vec<BuiltinName> {
         &self.program.builtins
     }
 
+    ///Runs until pc reaches address, returning the number of steps executed
     pub fn run_until_pc(
         &mut self,
         address: Relocatable,
         vm: &mut VirtualMachine,
         hint_processor: &mut dyn HintProcessor,
-    ) -> Result<(), VirtualMachineError> {
+    ) -> Result<RunOutcome, VirtualMachineError> {
         let references = self.get_reference_list();
         let hint_data_dictionary = self.get_hint_data_dictionary(&references, hint_processor)?;
+        //Without instructions the first step would fail to decode an empty cell
+        if vm.run_context.pc != address && self.program.data.is_empty() {
+            return Err(RunnerError::EmptyProgram.into());
+        }
+        let mut steps_executed = 0;
         while vm.run_context.pc != address {
             vm.step(
                 hint_processor,
@@ -512,8 +636,50 @@ impl CairoRunner {
                 &hint_data_dictionary,
                 &self.program.constants,
             )?;
+            steps_executed += 1;
+        }
+        Ok(RunOutcome {
+            steps_executed,
+            break_reason: BreakReason::ReachedFinalPc,
+        })
+    }
+
+    ///Runs like run_until_pc, but also pauses before executing the instruction at any of the vm's
+    ///breakpoints, after step_limit steps or once cancel is set, returning why the run stopped.
+    ///The instruction at the current pc is always executed, so calling it again after a
+    ///breakpoint resumes the run.
+    pub fn run_until_breakpoint(
+        &mut self,
+        address: Relocatable,
+        step_limit: Option<usize>,
+        cancel: Option<&AtomicBool>,
+        vm: &mut VirtualMachine,
+        hint_processor: &mut dyn HintProcessor,
+    ) -> Result<BreakReason, VirtualMachineError> {
+        let references = self.get_reference_list();
+        let hint_data_dictionary = self.get_hint_data_dictionary(&references, hint_processor)?;
+        let mut steps = 0;
+        loop {
+            if vm.run_context.pc == address {
+                return Ok(BreakReason::ReachedFinalPc);
+            }
+            if steps > 0 && vm.breakpoints.contains(&vm.run_context.pc) {
+                return Ok(BreakReason::Breakpoint(vm.run_context.pc));
+            }
+            if step_limit == Some(steps) {
+                return Ok(BreakReason::StepLimit);
+            }
+            if matches!(cancel, Some(cancel) if cancel.load(Ordering::Relaxed)) {
+                return Ok(BreakReason::Cancelled);
+            }
+            vm.step(
+                hint_processor,
+                &mut self.exec_scopes,
+                &hint_data_dictionary,
+                &self.program.constants,
+            )?;
+            steps += 1;
         }
-        Ok(())
     }
 
     /// Execute an exact number of steps on the program from the actual position.
@@ -522,7 +688,7 @@ impl CairoRunner {
         steps: usize,
         vm: &mut VirtualMachine,
         hint_processor: &mut dyn HintProcessor,
-    ) -> Result<(), VirtualMachineError> {
+    ) -> Result<RunOutcome, VirtualMachineError> {
         let references = self.get_reference_list();
         let hint_data_dictionary = self.get_hint_data_dictionary(&references, hint_processor)?;
 
@@ -539,7 +705,10 @@ impl CairoRunner {
             )?;
         }
 
-        Ok(())
+        Ok(RunOutcome {
+            steps_executed: steps,
+            break_reason: BreakReason::StepLimit,
+        })
     }
 
     /// Execute steps until a number of steps since the start of the program is reached.
@@ -548,7 +717,7 @@ impl CairoRunner {
         steps: usize,
         vm: &mut VirtualMachine,
         hint_processor: &mut dyn HintProcessor,
-    ) -> Result<(), VirtualMachineError> {
+    ) -> Result<RunOutcome, VirtualMachineError> {
         self.run_for_steps(steps.saturating_sub(vm.current_step), vm, hint_processor)
     }
 
@@ -557,7 +726,7 @@ impl CairoRunner {
         &mut self,
         vm: &mut VirtualMachine,
         hint_processor: &mut dyn HintProcessor,
-    ) -> Result<(), VirtualMachineError> {
+    ) -> Result<RunOutcome, VirtualMachineError> {
         self.run_until_steps(vm.current_step.next_power_of_two(), vm, hint_processor)
     }
 
@@ -614,8 +783,11 @@ impl CairoRunner {
 
     /// Count the number of holes present in the segments.
     pub fn get_memory_holes(&self, vm: &VirtualMachine) -> Result<usize, MemoryError> {
-        let program_addresses =
-            (0..self.program.data.len()).map(|offset| Relocatable::from((0, offset)));
+        let program_len = self.program.data.len();
+        let program_addresses = self
+            .program_base
+            .into_iter()
+            .flat_map(|base| (0..program_len).map(move |offset| base + offset));
 
         let accessed_addresses = vm
             .accessed_addresses
@@ -624,21 +796,18 @@ impl CairoRunner {
             .iter()
             .map(|addr| vm.memory.relocate_value(*addr));
 
-        let builtin_addresses = vm
+        //Builtin segments are filled by their runners, they have no holes
+        let builtin_segments: HashSet<_> = vm
             .builtin_runners
             .iter()
             .map(|(_, runner)| runner.base())
-            .collect::<Vec<_>>()
-            .into_iter()
-            .flat_map(|base| {
-                let size = vm.segments.get_segment_size(base as usize).unwrap();
-                (0..size).map(move |offset| Relocatable::from((base, offset)))
-            });
+            .collect();
 
-        let addresses = program_addresses
+        let addresses: HashSet<_> = program_addresses
             .chain(accessed_addresses)
-            .chain(builtin_addresses);
-        vm.segments.get_memory_holes(addresses)
+            .filter(|addr| !builtin_segments.contains(&addr.segment_index))
+            .collect();
+        vm.segments.get_memory_holes(&addresses)
     }
 
     /// Check if there are enough trace cells to fill the entire diluted checks.
@@ -691,6 +860,7 @@ impl CairoRunner {
         vm.end_run(&self.exec_scopes)?;
 
         if disable_finalize_all {
+            self.run_duration = self.get_run_duration();
             return Ok(());
         }
 
@@ -713,49 +883,40 @@ impl CairoRunner {
             }
         }
 
+        self.run_duration = self.get_run_duration();
         self.run_ended = true;
         Ok(())
     }
 
+    ///Returns the wall-clock time since the vm was initialized, up to the end of the run if
+    ///end_run was called. None if initialize_vm wasn't called, or on wasm32, where runs aren't timed.
+    pub fn get_run_duration(&self) -> Option<Duration> {
+        self.run_duration
+            .or_else(|| self.run_started.map(|run_started| run_started.elapsed()))
+    }
+
     /// Relocates the VM's memory, turning bidimensional indexes into contiguous numbers, and values
     /// into Felts. Uses the relocation_table to asign each index a number according to the value
     /// on its segment number.
+    #[allow(deprecated)]
     fn relocate_memory(
         &mut self,
         vm: &mut VirtualMachine,
-        relocation_table: &Vec<usize>,
+        relocation_table: &[usize],
     ) -> Result<(), MemoryError> {
         if !(self.relocated_memory.is_empty()) {
             return Err(MemoryError::Relocation);
         }
-        //Relocated addresses start at 1
-        self.relocated_memory.push(None);
-        for (index, segment) in vm.memory.data.iter().enumerate() {
-            for (seg_offset, element) in segment.iter().enumerate() {
-                match element {
-                    Some(elem) => {
-                        let relocated_addr = relocate_address(
-                            Relocatable::from((index as isize, seg_offset)),
-                            relocation_table,
-                        )?;
-                        let value = relocate_value(elem.clone(), relocation_table)?;
-                        if self.relocated_memory.len() <= relocated_addr {
-                            self.relocated_memory.resize(relocated_addr + 1, None);
-                        }
-                        self.relocated_memory[relocated_addr] = Some(value);
-                    }
-                    None => self.relocated_memory.push(None),
-                }
-            }
-        }
+        self.relocated_memory = vm.memory.relocate_as_felts(relocation_table)?;
         Ok(())
     }
 
     ///Relocates the VM's trace, turning relocatable registers to numbered ones
+    #[allow(deprecated)]
     fn relocate_trace(
         &mut self,
         vm: &mut VirtualMachine,
-        relocation_table: &Vec<usize>,
+        relocation_table: &[usize],
     ) -> Result<(), TraceError> {
         if self.relocated_trace.is_some() {
             return Err(TraceError::AlreadyRelocated);
@@ -791,10 +952,38 @@ impl CairoRunner {
         Ok(())
     }
 
+    ///Returns a relocated copy of the dictionaries of the dict manager in the runner's execution
+    ///scopes, by segment index, see DictManager::relocated_dicts. Empty if there is no dict
+    ///manager, fails if the segments weren't relocated by CairoRunner::relocate yet.
+    pub fn get_relocated_dicts(
+        &self,
+        vm: &VirtualMachine,
+    ) -> Result<HashMap<isize, Dictionary>, MemoryError> {
+        let dict_manager = match self.exec_scopes.get_dict_manager() {
+            Ok(dict_manager) => dict_manager,
+            Err(_) => return Ok(HashMap::new()),
+        };
+        let relocation_table = vm.segments.relocate_segments()?;
+        let dict_manager = dict_manager.borrow();
+        dict_manager.relocated_dicts(&relocation_table)
+    }
+
+    ///Returns the memory relocated by CairoRunner::relocate, indexed by flat address
+    #[allow(deprecated)]
+    pub fn get_relocated_memory(&self) -> &[Option<Felt>] {
+        &self.relocated_memory
+    }
+
+    ///Returns the trace relocated by CairoRunner::relocate, or None if the vm had no trace
+    #[allow(deprecated)]
+    pub fn get_relocated_trace(&self) -> Option<&[RelocatedTraceEntry]> {
+        self.relocated_trace.as_deref()
+    }
+
     pub fn get_builtin_segments_info(
         &self,
         vm: &VirtualMachine,
-    ) -> Result<HashMap<&'static str, SegmentInfo>, RunnerError> {
+    ) -> Result<HashMap<BuiltinName, SegmentInfo>, RunnerError> {
         let mut builtin_segments = HashMap::new();
 
         for (_, builtin) in &vm.builtin_runners {
@@ -812,6 +1001,33 @@ impl CairoRunner {
         Ok(builtin_segments)
     }
 
+    ///Returns the relocated address of the start of each builtin's segment and of its stop
+    ///pointer, which is only known once the run was finalized by read_return_values, or by
+    ///run_from_entrypoint. The segments' effective sizes must have been computed, as done by end_run.
+    pub fn get_builtin_segment_addresses(
+        &self,
+        vm: &VirtualMachine,
+    ) -> Result<HashMap<BuiltinName, BuiltinSegmentAddresses>, RunnerError> {
+        let relocation_table = vm.segments.relocate_segments()?;
+        let mut builtin_segments = HashMap::new();
+        for (_, builtin) in &vm.builtin_runners {
+            let (name, (base, stop_ptr)) = builtin.get_memory_segment_addresses();
+            let stop_ptr = stop_ptr.ok_or(RunnerError::NoStopPointer(name))?;
+            let begin_addr = usize::try_from(base)
+                .ok()
+                .and_then(|index| relocation_table.get(index))
+                .ok_or(MemoryError::Relocation)?;
+            builtin_segments.insert(
+                name,
+                BuiltinSegmentAddresses {
+                    begin_addr: *begin_addr,
+                    stop_ptr: begin_addr + stop_ptr,
+                },
+            );
+        }
+        Ok(builtin_segments)
+    }
+
     pub fn get_execution_resources(
         &self,
         vm: &VirtualMachine,
@@ -822,12 +1038,9 @@ impl CairoRunner {
         };
         let n_memory_holes = self.get_memory_holes(vm)?;
 
-        let mut builtin_instance_counter = HashMap::new();
+        let mut builtin_instance_counter = BTreeMap::new();
         for (builtin_name, builtin_runner) in &vm.builtin_runners {
-            builtin_instance_counter.insert(
-                builtin_name.to_string(),
-                builtin_runner.get_used_instances(vm)?,
-            );
+            builtin_instance_counter.insert(*builtin_name, builtin_runner.get_used_instances(vm)?);
         }
 
         Ok(ExecutionResources {
@@ -837,6 +1050,32 @@ impl CairoRunner {
         })
     }
 
+    ///Returns the values in the output builtin's segment, or None if the program doesn't use it
+    pub fn get_output_values(
+        &self,
+        vm: &mut VirtualMachine,
+    ) -> Result<Option<Vec<Felt>>, RunnerError> {
+        let base = match vm.get_builtin_runner(BuiltinName::Output) {
+            Some(builtin) => builtin.base(),
+            None => return Ok(None),
+        };
+
+        let segment_used_sizes = vm.segments.compute_effective_sizes(&vm.memory);
+        let segment_index: usize = base
+            .try_into()
+            .map_err(|_| RunnerError::RunnerInTemporarySegment(base))?;
+
+        (0..segment_used_sizes[segment_index])
+            .map(|i| {
+                vm.memory
+                    .get_integer(&(base, i).into())
+                    .map(|value| value.into_owned())
+                    .map_err(|_| RunnerError::MemoryGet((base, i).into()))
+            })
+            .collect::<Result<_, _>>()
+            .map(Some)
+    }
+
     pub fn get_output(&mut self, vm: &mut VirtualMachine) -> Result<String, RunnerError> {
         let mut output = Vec::<u8>::new();
         self.write_output(vm, &mut output)?;
@@ -844,6 +1083,24 @@ impl CairoRunner {
         Ok(output)
     }
 
+    ///Checks the values hosted in the output builtin's segment with the given verifier. A program
+    ///without the output builtin is verified as having no outputs.
+    pub fn verify_output(
+        &self,
+        vm: &mut VirtualMachine,
+        verifier: &dyn OutputVerifier,
+    ) -> Result<(), RunnerError> {
+        let outputs = self
+            .get_output_values(vm)?
+            .unwrap_or_default()
+            .iter()
+            .map(|value| value.to_bigint())
+            .collect::<Vec<_>>();
+        verifier
+            .verify(&outputs)
+            .map_err(RunnerError::OutputVerificationFailed)
+    }
+
     /// Writes the values hosted in the output builtin's segment.
     /// Does nothing if the output builtin is not present in the program.
     pub fn write_output(
@@ -851,37 +1108,49 @@ impl CairoRunner {
         vm: &mut VirtualMachine,
         stdout: &mut dyn io::Write,
     ) -> Result<(), RunnerError> {
-        let builtin = vm
-            .builtin_runners
-            .iter_mut()
-            .find_map(|(k, v)| match k.as_str() {
-                "output" => Some(v),
-                _ => None,
-            });
-        let builtin = match builtin {
-            Some(x) => x,
-            _ => return Ok(()),
-        };
-
-        let segment_used_sizes = vm.segments.compute_effective_sizes(&vm.memory);
-        let base = builtin.base();
-
-        let segment_index: usize = base
-            .try_into()
-            .map_err(|_| RunnerError::RunnerInTemporarySegment(base))?;
-
-        for i in 0..segment_used_sizes[segment_index] {
-            let value = vm
-                .memory
-                .get_integer(&(base, i).into())
-                .map_err(|_| RunnerError::MemoryGet((base, i).into()))?
-                .to_bigint();
-            writeln!(stdout, "{}", value).map_err(|_| RunnerError::WriteFail)?;
+        for value in self.get_output_values(vm)?.unwrap_or_default() {
+            writeln!(stdout, "{}", value.to_bigint()).map_err(|_| RunnerError::WriteFail)?;
         }
 
         Ok(())
     }
 
+    ///Summarizes a run for logging: whether it failed and how, its resources, output, hint
+    ///statistics, duration and final registers. error is the error the run failed with, if any.
+    ///Sections that aren't available, like the hint statistics when they weren't enabled, are
+    ///left as None and omitted when serialized.
+    pub fn run_report(&self, vm: &mut VirtualMachine, error: Option<&CairoRunError>) -> RunReport {
+        vm.segments.compute_effective_sizes(&vm.memory);
+        RunReport {
+            success: error.is_none(),
+            error: error.map(RunErrorReport::from),
+            execution_resources: self.get_execution_resources(vm).ok(),
+            output: self.get_output_values(vm).ok().flatten().map(|values| {
+                values
+                    .iter()
+                    .map(|value| value.to_bigint().to_string())
+                    .collect()
+            }),
+            hint_statistics: vm
+                .get_hint_statistics()
+                .map(|statistics| statistics.clone().into_iter().collect()),
+            duration_secs: self
+                .get_run_duration()
+                .map(|duration| duration.as_secs_f64()),
+            steps_per_sec: vm
+                .time_elapsed()
+                .map(|elapsed| elapsed.as_secs_f64())
+                .filter(|secs| *secs > 0.0)
+                .map(|secs| vm.current_step as f64 / secs),
+            trace_length: vm.trace.as_ref().map(Vec::len),
+            registers: RegistersReport {
+                pc: *vm.get_pc(),
+                ap: vm.get_ap(),
+                fp: vm.get_fp(),
+            },
+        }
+    }
+
     // Finalizes the segments.
     //     Note:
     //     1.  end_run() must precede a call to this method.
@@ -933,47 +1202,187 @@ impl CairoRunner {
         Ok(())
     }
 
+    ///Runs the function at the given entrypoint with the given arguments.
+    ///The current pointers of the builtins in ordered_builtins are passed before args, and the
+    ///advanced pointers are read back from the function's return values once the run ends, so a
+    ///subsequent call continues from them. Calls can be repeated on the same runner and vm.
+    ///Returns the outcome of the function's run.
     #[allow(clippy::too_many_arguments)]
     pub fn run_from_entrypoint(
         &mut self,
         entrypoint: usize,
         args: Vec<&dyn Any>,
+        ordered_builtins: &[BuiltinName],
         typed_args: bool,
         verify_secure: bool,
         _apply_modulo_to_args: bool,
         vm: &mut VirtualMachine,
         hint_processor: &mut dyn HintProcessor,
-    ) -> Result<(), VirtualMachineError> {
-        let stack = if typed_args {
+    ) -> Result<RunOutcome, VirtualMachineError> {
+        let mut stack = Vec::new();
+        for builtin_name in ordered_builtins {
+            let builtin = vm
+                .get_builtin_runner(*builtin_name)
+                .ok_or(RunnerError::MissingBuiltin(*builtin_name))?;
+            stack.push(builtin.current_ptr().into());
+        }
+
+        if typed_args {
             if args.len() != 1 {
                 return Err(VirtualMachineError::InvalidArgCount(1, args.len()));
             }
 
-            gen_typed_args(args)?
+            stack.extend(gen_typed_args(args)?);
         } else {
-            let mut stack = Vec::new();
             for arg in args {
                 stack.push(vm.segments.gen_arg(arg, &mut vm.memory)?);
             }
+        }
 
-            stack
-        };
+        if self.run_ended {
+            self.prepare_next_run(vm)?;
+        }
 
         let return_fp = vm.segments.add(&mut vm.memory);
         let end = self.initialize_function_entrypoint(vm, entrypoint, stack, return_fp.into())?;
 
         self.initialize_vm(vm)?;
 
-        self.run_until_pc(end, vm, hint_processor)?;
+        let outcome = self.run_until_pc(end, vm, hint_processor)?;
         self.end_run(true, false, vm, hint_processor)?;
+        self.read_builtin_pointers(ordered_builtins, vm)?;
 
         if verify_secure {
             verify_secure_runner(self, false, vm)?;
         }
 
+        Ok(outcome)
+    }
+
+    ///Moves the execution base past the previous run's stack, as memory is write-once, and
+    ///clears the state left by end_run so the runner can run another entrypoint.
+    fn prepare_next_run(&mut self, vm: &mut VirtualMachine) -> Result<(), RunnerError> {
+        let execution_base = self.execution_base.ok_or(RunnerError::NoExecBase)?;
+        let used_size = vm
+            .memory
+            .data
+            .get(execution_base.segment_index as usize)
+            .map_or(0, Vec::len);
+        self.execution_base = Some(Relocatable::from((
+            execution_base.segment_index,
+            used_size.max(execution_base.offset),
+        )));
+        vm.segments.segment_used_sizes = None;
+        self.run_ended = false;
+        Ok(())
+    }
+
+    ///Reads the pointers of the given builtins from the end of the stack, where a function
+    ///returning only its implicit builtin arguments leaves them, and stores them as the builtins'
+    ///stop pointers.
+    fn read_builtin_pointers(
+        &self,
+        ordered_builtins: &[BuiltinName],
+        vm: &mut VirtualMachine,
+    ) -> Result<(), RunnerError> {
+        let mut pointer = vm.get_ap();
+        for builtin_name in ordered_builtins.iter().rev() {
+            let index = vm
+                .builtin_runners
+                .iter()
+                .position(|(name, _)| name == builtin_name)
+                .ok_or(RunnerError::MissingBuiltin(*builtin_name))?;
+            let (new_pointer, stop_ptr) =
+                self.builtin_final_stack(&vm.builtin_runners[index].1, vm, pointer)?;
+            vm.builtin_runners[index].1.set_stop_ptr(stop_ptr);
+            pointer = new_pointer;
+        }
         Ok(())
     }
 
+    //Calls final_stack on the builtin. If allow_missing_builtin_stop_ptrs is set and the stack
+    //doesn't hold a pointer to the builtin's segment, the builtin is taken as not returned: the
+    //stack pointer is left as is and the builtin's used cells are taken as its stop pointer.
+    fn builtin_final_stack(
+        &self,
+        builtin: &BuiltinRunner,
+        vm: &VirtualMachine,
+        pointer: Relocatable,
+    ) -> Result<(Relocatable, usize), RunnerError> {
+        match builtin.final_stack(vm, pointer) {
+            Err(RunnerError::FinalStack) if self.allow_missing_builtin_stop_ptrs => (),
+            Err(RunnerError::InvalidStopPointer(_, claimed_ptr, _, expected_ptr))
+                if self.allow_missing_builtin_stop_ptrs
+                    && claimed_ptr.segment_index != expected_ptr.segment_index => {}
+            result => return result,
+        }
+        let (_, expected_ptr) = builtin
+            .get_expected_stop_ptr(vm)
+            .map_err(|_| RunnerError::FinalStack)?;
+        Ok((pointer, expected_ptr.offset))
+    }
+
+    ///Returns, for each builtin of the program, the stop pointer found in its slot at the end of
+    ///the stack next to the one final_stack expects, to find out which builtins a program fails
+    ///to return properly. Should be called after end_run.
+    pub fn get_builtin_stop_ptr_diagnostics(
+        &self,
+        vm: &VirtualMachine,
+    ) -> Result<Vec<BuiltinStopPtrDiagnostic>, RunnerError> {
+        let mut pointer = vm.get_ap();
+        let mut diagnostics = Vec::new();
+        for builtin_name in self.program.builtins.iter().rev() {
+            let builtin = vm
+                .get_builtin_runner(*builtin_name)
+                .ok_or(RunnerError::MissingBuiltin(*builtin_name))?;
+            pointer = pointer.sub_usize(1).map_err(|_| RunnerError::FinalStack)?;
+            let (used_instances, expected_ptr) = builtin.get_expected_stop_ptr(vm)?;
+            diagnostics.push(BuiltinStopPtrDiagnostic {
+                builtin: *builtin_name,
+                claimed_ptr: vm.get_relocatable(&pointer).ok(),
+                used_instances,
+                expected_ptr,
+            });
+        }
+        diagnostics.reverse();
+        Ok(diagnostics)
+    }
+
+    ///Runs the function with the given full name (ie: "__main__.main"), see run_from_entrypoint.
+    #[allow(clippy::too_many_arguments)]
+    pub fn run_from_function(
+        &mut self,
+        function_name: &str,
+        args: Vec<&dyn Any>,
+        ordered_builtins: &[BuiltinName],
+        typed_args: bool,
+        verify_secure: bool,
+        apply_modulo_to_args: bool,
+        vm: &mut VirtualMachine,
+        hint_processor: &mut dyn HintProcessor,
+    ) -> Result<RunOutcome, VirtualMachineError> {
+        let entrypoint = match self.program.get_function(function_name) {
+            Some(function) => function.pc,
+            None => {
+                return Err(RunnerError::FunctionNotFound(
+                    function_name.to_string(),
+                    self.program.get_near_miss_identifiers(function_name),
+                )
+                .into())
+            }
+        };
+        self.run_from_entrypoint(
+            entrypoint,
+            args,
+            ordered_builtins,
+            typed_args,
+            verify_secure,
+            apply_modulo_to_args,
+            vm,
+            hint_processor,
+        )
+    }
+
     // Returns Ok(()) if there are enough allocated cells for the builtins.
     // If not, the number of steps should be increased or a different layout should be used.
     pub fn check_used_cells(&self, vm: &VirtualMachine) -> Result<(), VirtualMachineError> {
@@ -1035,7 +1444,7 @@ impl CairoRunner {
         vm: &mut VirtualMachine,
     ) -> Result<(), RunnerError> {
         self.initialize_all_builtins(vm)?;
-        self.initialize_segments(vm, self.program_base);
+        self.initialize_segments(vm, self.program_base)?;
         Ok(())
     }
 
@@ -1060,18 +1469,11 @@ impl CairoRunner {
         }
         let mut pointer = vm.get_ap();
         for builtin_name in self.program.builtins.iter().rev() {
-            let builtin_runner = vm
-                .builtin_runners
-                .iter()
-                .find(|(name, _builtin)| builtin_name == name);
-
-            match builtin_runner {
-                None => return Err(RunnerError::MissingBuiltin(builtin_name.to_string())),
-                Some((_, builtin)) => {
-                    let (new_pointer, _) = builtin.final_stack(vm, pointer)?;
-                    pointer = new_pointer;
-                }
-            }
+            let builtin = vm
+                .get_builtin_runner(*builtin_name)
+                .ok_or(RunnerError::MissingBuiltin(*builtin_name))?;
+            let (new_pointer, _) = self.builtin_final_stack(builtin, vm, pointer)?;
+            pointer = new_pointer;
         }
         if self.segments_finalized {
             return Err(RunnerError::FailedAddingReturnValues);
@@ -1095,14 +1497,15 @@ impl CairoRunner {
     pub fn add_additional_hash_builtin(&self, vm: &mut VirtualMachine) -> Relocatable {
         // Remove the custom hash runner if it was already present.
         vm.builtin_runners
-            .retain(|(name, _)| name != "hash_builtin");
+            .retain(|(name, _)| *name != BuiltinName::AdditionalHash);
 
         // Create, initialize and insert the new custom hash runner.
-        let mut builtin: BuiltinRunner = HashBuiltinRunner::new(32, true).into();
+        let mut builtin = HashBuiltinRunner::new(32, true);
         builtin.initialize_segments(&mut vm.segments, &mut vm.memory);
+        let builtin: BuiltinRunner = builtin.into();
         let segment_index = builtin.base();
         vm.builtin_runners
-            .push(("hash_builtin".to_string(), builtin));
+            .push((BuiltinName::AdditionalHash, builtin));
 
         Relocatable {
             segment_index,
@@ -1117,11 +1520,48 @@ pub struct SegmentInfo {
     pub size: usize,
 }
 
+///Relocated addresses of a builtin's segment, as the public input and the PIE's metadata expect
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+pub struct BuiltinSegmentAddresses {
+    pub begin_addr: usize,
+    pub stop_ptr: usize,
+}
+
+///Stop pointer of a builtin as returned by the program, next to the one expected from the
+///instances the vm counted, see CairoRunner::get_builtin_stop_ptr_diagnostics
 #[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BuiltinStopPtrDiagnostic {
+    pub builtin: BuiltinName,
+    ///None if the stack doesn't hold a pointer where the builtin's stop pointer should be
+    pub claimed_ptr: Option<Relocatable>,
+    pub used_instances: usize,
+    pub expected_ptr: Relocatable,
+}
+
+///Formats the diagnostic as a row of a table with one line per builtin
+impl fmt::Display for BuiltinStopPtrDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let claimed_ptr = match self.claimed_ptr {
+            Some(ptr) => ptr.to_string(),
+            None => "missing".to_string(),
+        };
+        write!(
+            f,
+            "{:<16} claimed: {:<10} expected: {:<10} used instances: {}",
+            self.builtin.to_string(),
+            claimed_ptr,
+            self.expected_ptr.to_string(),
+            self.used_instances
+        )
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
 pub struct ExecutionResources {
     pub n_steps: usize,
     pub n_memory_holes: usize,
-    pub builtin_instance_counter: HashMap<String, usize>,
+    ///Sorted by builtin, so serializing the resources gives the same result on every run
+    pub builtin_instance_counter: BTreeMap<BuiltinName, usize>,
 }
 
 #[cfg(test)]
@@ -1129,23 +1569,30 @@ mod tests {
     use super::*;
     use crate::{
         hint_processor::builtin_hint_processor::builtin_hint_processor_definition::BuiltinHintProcessor,
+        hint_processor::builtin_hint_processor::dict_manager::DictManager,
         relocatable,
         serde::deserialize_program::{Identifier, ReferenceManager},
         types::instance_definitions::bitwise_instance_def::BitwiseInstanceDef,
         utils::test_utils::*,
-        vm::{trace::trace_entry::TraceEntry, vm_memory::memory::Memory},
+        vm::{
+            accessed_addresses::read_accessed_addresses, trace::trace_entry::TraceEntry,
+            vm_memory::memory::Memory,
+        },
     };
     use felt::{felt_str, NewFelt};
+    use num_bigint::BigInt;
     use num_traits::One;
     use std::{
-        collections::{HashMap, HashSet},
+        cell::RefCell,
+        collections::{BTreeMap, HashMap, HashSet},
         path::Path,
+        rc::Rc,
     };
 
     #[test]
     fn check_memory_usage_ok_case() {
         //This test works with basic Program definition, will later be updated to use Program::new() when fully defined
-        let program = program!["range_check", "output"];
+        let program = program![BuiltinName::RangeCheck, BuiltinName::Output];
         let cairo_runner = cairo_runner!(program);
         let mut vm = vm!();
         vm.segments.segment_used_sizes = Some(vec![4]);
@@ -1160,12 +1607,14 @@ mod tests {
         let cairo_runner = cairo_runner!(program);
         let mut vm = vm!();
 
-        vm.accessed_addresses = Some(vec![(1, 0).into(), (1, 3).into()]);
+        vm.accessed_addresses = Some(HashSet::from([(1, 0).into(), (1, 3).into()]));
         vm.builtin_runners = vec![{
             let mut builtin_runner: BuiltinRunner = OutputBuiltinRunner::new(true).into();
-            builtin_runner.initialize_segments(&mut vm.segments, &mut vm.memory);
+            builtin_runner
+                .initialize_segments(&mut vm.segments, &mut vm.memory)
+                .unwrap();
 
-            ("output".to_string(), builtin_runner)
+            (BuiltinName::Output, builtin_runner)
         }];
         vm.segments.segment_used_sizes = Some(vec![4, 12]);
         assert_eq!(
@@ -1176,19 +1625,87 @@ mod tests {
         );
     }
 
+    #[test]
+    fn initialize_builtins_with_segment_arena() {
+        let program = program![BuiltinName::RangeCheck, BuiltinName::SegmentArena];
+        let cairo_runner = cairo_runner!(program, "small");
+        let mut vm = vm!();
+        cairo_runner.initialize_builtins(&mut vm).unwrap();
+        assert_eq!(vm.builtin_runners.len(), 2);
+        assert_eq!(vm.builtin_runners[1].0, BuiltinName::SegmentArena);
+        assert!(matches!(
+            vm.builtin_runners[1].1,
+            BuiltinRunner::SegmentArena(_)
+        ));
+    }
+
+    #[test]
+    fn initialize_builtins_with_mod_builtins() {
+        let program = program![
+            BuiltinName::RangeCheck,
+            BuiltinName::AddMod,
+            BuiltinName::MulMod
+        ];
+        let cairo_runner = cairo_runner!(program, "all_cairo");
+        let mut vm = vm!();
+        cairo_runner.initialize_builtins(&mut vm).unwrap();
+        let names: Vec<_> = vm.builtin_runners.iter().map(|(name, _)| *name).collect();
+        assert_eq!(
+            names,
+            vec![
+                BuiltinName::RangeCheck,
+                BuiltinName::AddMod,
+                BuiltinName::MulMod
+            ]
+        );
+        assert!(matches!(
+            &vm.builtin_runners[2].1,
+            BuiltinRunner::Mod(mul_mod) if mul_mod.builtin_type() == ModBuiltinType::Mul
+        ));
+    }
+
+    #[test]
+    fn initialize_builtins_mod_builtins_not_in_layout() {
+        let program = program![BuiltinName::AddMod];
+        let cairo_runner = cairo_runner!(program, "all");
+        let mut vm = vm!();
+        assert_eq!(
+            cairo_runner.initialize_builtins(&mut vm),
+            Err(RunnerError::NoBuiltinForInstance(
+                HashSet::from([BuiltinName::AddMod]),
+                String::from("all")
+            ))
+        );
+    }
+
     #[test]
     fn initialize_builtins_with_disordered_builtins() {
         //This test works with basic Program definition, will later be updated to use Program::new() when fully defined
-        let program = program!["range_check", "output"];
+        let program = program![BuiltinName::RangeCheck, BuiltinName::Output];
         let cairo_runner = cairo_runner!(program);
         let mut vm = vm!();
         assert!(cairo_runner.initialize_builtins(&mut vm).is_err());
     }
 
+    #[test]
+    fn initialize_builtins_with_duplicate_builtins() {
+        let program = program![
+            BuiltinName::Output,
+            BuiltinName::RangeCheck,
+            BuiltinName::RangeCheck
+        ];
+        let cairo_runner = cairo_runner!(program);
+        let mut vm = vm!();
+        assert_eq!(
+            cairo_runner.initialize_builtins(&mut vm),
+            Err(RunnerError::DuplicateBuiltin(BuiltinName::RangeCheck))
+        );
+    }
+
     #[test]
     fn create_cairo_runner_with_ordered_but_missing_builtins() {
         //This test works with basic Program definition, will later be updated to use Program::new() when fully defined
-        let program = program!["output", "ecdsa"];
+        let program = program![BuiltinName::Output, BuiltinName::Ecdsa];
         //We only check that the creation doesnt panic
         let _cairo_runner = cairo_runner!(program);
     }
@@ -1196,16 +1713,18 @@ mod tests {
     #[test]
     fn initialize_segments_with_base() {
         //This test works with basic Program definition, will later be updated to use Program::new() when fully defined
-        let program = program!["output"];
+        let program = program![BuiltinName::Output];
         let mut cairo_runner = cairo_runner!(program);
         let mut vm = vm!();
         let program_base = Some(Relocatable {
             segment_index: 5,
             offset: 9,
         });
-        vm.segments.num_segments = 6;
+        add_segments!(vm, 6);
         cairo_runner.initialize_builtins(&mut vm).unwrap();
-        cairo_runner.initialize_segments(&mut vm, program_base);
+        cairo_runner
+            .initialize_segments(&mut vm, program_base)
+            .unwrap();
         assert_eq!(
             cairo_runner.program_base,
             Some(Relocatable {
@@ -1220,7 +1739,7 @@ mod tests {
                 offset: 0,
             })
         );
-        assert_eq!(vm.builtin_runners[0].0, String::from("output"));
+        assert_eq!(vm.builtin_runners[0].0, BuiltinName::Output);
         assert_eq!(vm.builtin_runners[0].1.base(), 7);
 
         assert_eq!(vm.segments.num_segments, 8);
@@ -1229,11 +1748,11 @@ mod tests {
     #[test]
     fn initialize_segments_no_base() {
         //This test works with basic Program definition, will later be updated to use Program::new() when fully defined
-        let program = program!["output"];
+        let program = program![BuiltinName::Output];
         let mut cairo_runner = cairo_runner!(program);
         let mut vm = vm!();
         cairo_runner.initialize_builtins(&mut vm).unwrap();
-        cairo_runner.initialize_segments(&mut vm, None);
+        cairo_runner.initialize_segments(&mut vm, None).unwrap();
         assert_eq!(
             cairo_runner.program_base,
             Some(Relocatable {
@@ -1248,7 +1767,7 @@ mod tests {
                 offset: 0
             })
         );
-        assert_eq!(vm.builtin_runners[0].0, String::from("output"));
+        assert_eq!(vm.builtin_runners[0].0, BuiltinName::Output);
         assert_eq!(vm.builtin_runners[0].1.base(), 2);
 
         assert_eq!(vm.segments.num_segments, 3);
@@ -1257,7 +1776,7 @@ mod tests {
     #[test]
     fn initialize_state_empty_data_and_stack() {
         //This test works with basic Program definition, will later be updated to use Program::new() when fully defined
-        let program = program!["output"];
+        let program = program![BuiltinName::Output];
         let mut cairo_runner = cairo_runner!(program);
         let mut vm = vm!();
         cairo_runner.program_base = Some(relocatable!(1, 0));
@@ -1278,7 +1797,7 @@ mod tests {
     fn initialize_state_some_data_empty_stack() {
         //This test works with basic Program definition, will later be updated to use Program::new() when fully defined
         let program = program!(
-            builtins = vec![String::from("output")],
+            builtins = vec![BuiltinName::Output],
             data = vec_data!((4), (6)),
         );
         let mut cairo_runner = cairo_runner!(program);
@@ -1299,7 +1818,7 @@ mod tests {
     #[test]
     fn initialize_state_empty_data_some_stack() {
         //This test works with basic Program definition, will later be updated to use Program::new() when fully defined
-        let program = program!["output"];
+        let program = program![BuiltinName::Output];
         let mut cairo_runner = cairo_runner!(program);
         let mut vm = vm!();
         for _ in 0..3 {
@@ -1315,7 +1834,7 @@ mod tests {
     #[test]
     fn initialize_state_no_program_base() {
         //This test works with basic Program definition, will later be updated to use Program::new() when fully defined
-        let program = program!["output"];
+        let program = program![BuiltinName::Output];
         let mut cairo_runner = cairo_runner!(program);
         let mut vm = vm!();
         for _ in 0..2 {
@@ -1336,7 +1855,7 @@ mod tests {
     #[should_panic]
     fn initialize_state_no_execution_base() {
         //This test works with basic Program definition, will later be updated to use Program::new() when fully defined
-        let program = program!["output"];
+        let program = program![BuiltinName::Output];
         let mut cairo_runner = cairo_runner!(program);
         let mut vm = vm!();
         for _ in 0..2 {
@@ -1353,7 +1872,7 @@ mod tests {
     #[test]
     fn initialize_function_entrypoint_empty_stack() {
         //This test works with basic Program definition, will later be updated to use Program::new() when fully defined
-        let program = program!["output"];
+        let program = program![BuiltinName::Output];
         let mut cairo_runner = cairo_runner!(program);
         let mut vm = vm!();
         for _ in 0..2 {
@@ -1374,7 +1893,7 @@ mod tests {
     #[test]
     fn initialize_function_entrypoint_some_stack() {
         //This test works with basic Program definition, will later be updated to use Program::new() when fully defined
-        let program = program!["output"];
+        let program = program![BuiltinName::Output];
         let mut cairo_runner = cairo_runner!(program);
         let mut vm = vm!();
         for _ in 0..2 {
@@ -1396,7 +1915,7 @@ mod tests {
     #[should_panic]
     fn initialize_function_entrypoint_no_execution_base() {
         //This test works with basic Program definition, will later be updated to use Program::new() when fully defined
-        let program = program!["output"];
+        let program = program![BuiltinName::Output];
         let mut cairo_runner = cairo_runner!(program);
         let mut vm = vm!();
         let stack = vec![MaybeRelocatable::from(Felt::new(7_i32))];
@@ -1410,7 +1929,7 @@ mod tests {
     #[should_panic]
     fn initialize_main_entrypoint_no_main() {
         //This test works with basic Program definition, will later be updated to use Program::new() when fully defined
-        let program = program!["output"];
+        let program = program![BuiltinName::Output];
         let mut cairo_runner = cairo_runner!(program);
         let mut vm = vm!();
         cairo_runner.initialize_main_entrypoint(&mut vm).unwrap();
@@ -1447,7 +1966,7 @@ mod tests {
         // Add some arbitrary values to the VM's memory to check they are not being accessed
         vm.memory = memory![((1, 0), 1)];
 
-        let expected_accessed_addresses: Vec<Relocatable> = (0..24)
+        let expected_accessed_addresses: HashSet<Relocatable> = (0..24)
             .map(|offset| Relocatable::from((0, offset)))
             .collect();
 
@@ -1477,22 +1996,148 @@ mod tests {
         assert_eq!(vm.run_context.pc, relocatable!(0, 1));
         assert_eq!(vm.run_context.ap, 2);
         assert_eq!(vm.run_context.fp, 2);
-        assert_eq!(vm._program_base, Some(MaybeRelocatable::from((0, 0))));
+        assert_eq!(vm.program_base, Some(relocatable!(0, 0)));
     }
 
     #[test]
-    fn initialize_vm_with_range_check_valid() {
+    fn initialize_vm_enables_strict_registers() {
+        let program = program!(main = Some(1),);
+        let mut cairo_runner = cairo_runner!(program);
+        let mut vm = vm!();
+        cairo_runner.program_base = Some(relocatable!(0, 0));
+        cairo_runner.initial_pc = Some(relocatable!(0, 1));
+        cairo_runner.initial_ap = Some(relocatable!(1, 2));
+        cairo_runner.initial_fp = Some(relocatable!(1, 2));
+        cairo_runner.final_pc = Some(relocatable!(3, 0));
+        cairo_runner.initialize_vm(&mut vm).unwrap();
+        assert_eq!(
+            vm.register_bounds,
+            Some(RegisterBounds {
+                program_segment: 0,
+                execution_segment: 1,
+                final_pc: Some(relocatable!(3, 0)),
+            })
+        );
+    }
+
+    #[test]
+    fn initialize_vm_strict_registers_disabled() {
+        let program = program!(main = Some(1),);
+        let mut cairo_runner = cairo_runner!(program);
+        let mut vm = vm!();
+        cairo_runner.program_base = Some(relocatable!(0, 0));
+        cairo_runner.initial_pc = Some(relocatable!(0, 1));
+        cairo_runner.initial_ap = Some(relocatable!(1, 2));
+        cairo_runner.initial_fp = Some(relocatable!(1, 2));
+        cairo_runner.strict_registers = false;
+        cairo_runner.initialize_vm(&mut vm).unwrap();
+        assert_eq!(vm.register_bounds, None);
+    }
+
+    #[test]
+    fn initialize_strict_lint() {
+        let program = Program::from_file(
+            Path::new("cairo_programs/manually_compiled/lint_issues_program.json"),
+            Some("main"),
+        )
+        .unwrap();
+        let mut cairo_runner = cairo_runner!(program, "all");
+        assert!(cairo_runner.initialize(&mut vm!()).is_ok());
+
+        let mut cairo_runner = cairo_runner!(program, "all");
+        cairo_runner.strict_lint = true;
+        //Only errors make initialize fail, the undeclared pedersen_ptr is a warning
+        assert_eq!(
+            cairo_runner.initialize(&mut vm!()),
+            Err(RunnerError::ProgramLint(
+                program
+                    .lint()
+                    .into_iter()
+                    .filter(|issue| !matches!(issue, LintIssue::UndeclaredBuiltinPointer(_)))
+                    .collect()
+            ))
+        );
+    }
+
+    ///Runs the fibonacci fixture with the program loaded at program_segment, or at a new segment
+    ///if None, returning the runner and vm after relocating
+    fn run_fibonacci_at(program_segment: Option<usize>) -> (CairoRunner, VirtualMachine) {
+        let program = Program::from_file(
+            Path::new("cairo_programs/manually_compiled/fibonacci.json"),
+            Some("main"),
+        )
+        .unwrap();
+        let mut cairo_runner = cairo_runner!(program);
+        let mut vm = vm!(true);
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        cairo_runner.initialize_builtins(&mut vm).unwrap();
+        let program_base = program_segment.map(|segment| {
+            add_segments!(vm, segment);
+            vm.segments.add(&mut vm.memory)
+        });
+        cairo_runner
+            .initialize_segments(&mut vm, program_base)
+            .unwrap();
+        let end = cairo_runner.initialize_main_entrypoint(&mut vm).unwrap();
+        cairo_runner.initialize_vm(&mut vm).unwrap();
+        cairo_runner
+            .run_until_pc(end, &mut vm, &mut hint_processor)
+            .unwrap();
+        cairo_runner.relocate(&mut vm).unwrap();
+        (cairo_runner, vm)
+    }
+
+    #[test]
+    fn run_program_loaded_at_segment_4() {
+        let (cairo_runner, vm) = run_fibonacci_at(Some(4));
+        assert_eq!(cairo_runner.program_base, Some(relocatable!(4, 0)));
+        assert_eq!(cairo_runner.execution_base, Some(relocatable!(5, 0)));
+        assert_eq!(vm.program_base, Some(relocatable!(4, 0)));
+        assert_eq!(vm.get_ap().segment_index, 5);
+        assert_eq!(vm.get_fp().segment_index, 5);
+        //The strict register checks were made against the program segment
+        assert_eq!(
+            vm.register_bounds
+                .as_ref()
+                .map(|bounds| bounds.program_segment),
+            Some(4)
+        );
+        assert!(vm
+            .accessed_addresses
+            .as_ref()
+            .unwrap()
+            .contains(&relocatable!(4, 0)));
+
+        //The segments before the program are empty, so it relocates as a run loaded at segment 0
+        let (default_runner, default_vm) = run_fibonacci_at(None);
+        assert_eq!(default_runner.program_base, Some(relocatable!(0, 0)));
+        assert_eq!(
+            cairo_runner.get_relocated_trace(),
+            default_runner.get_relocated_trace()
+        );
+        assert_eq!(
+            cairo_runner.get_relocated_memory(),
+            default_runner.get_relocated_memory()
+        );
+        assert_eq!(
+            cairo_runner.get_execution_resources(&vm),
+            default_runner.get_execution_resources(&default_vm)
+        );
+    }
+
+    #[test]
+    fn initialize_vm_with_range_check_valid() {
         //This test works with basic Program definition, will later be updated to use Program::new() when fully defined
-        let program = program!(builtins = vec![String::from("range_check")], main = Some(1),);
+        let program = program!(builtins = vec![BuiltinName::RangeCheck], main = Some(1),);
         let mut cairo_runner = cairo_runner!(program);
         let mut vm = vm!();
         cairo_runner.initial_pc = Some(relocatable!(0, 1));
         cairo_runner.initial_ap = Some(relocatable!(1, 2));
         cairo_runner.initial_fp = Some(relocatable!(1, 2));
         cairo_runner.initialize_builtins(&mut vm).unwrap();
-        cairo_runner.initialize_segments(&mut vm, None);
+        cairo_runner.initialize_segments(&mut vm, None).unwrap();
         vm.memory = memory![((2, 0), 23), ((2, 1), 233)];
-        assert_eq!(vm.builtin_runners[0].0, String::from("range_check"));
+        assert_eq!(vm.builtin_runners[0].0, BuiltinName::RangeCheck);
         assert_eq!(vm.builtin_runners[0].1.base(), 2);
         cairo_runner.initialize_vm(&mut vm).unwrap();
         assert!(vm
@@ -1509,14 +2154,14 @@ mod tests {
     #[test]
     fn initialize_vm_with_range_check_invalid() {
         //This test works with basic Program definition, will later be updated to use Program::new() when fully defined
-        let program = program!(builtins = vec![String::from("range_check")], main = Some(1),);
+        let program = program!(builtins = vec![BuiltinName::RangeCheck], main = Some(1),);
         let mut cairo_runner = cairo_runner!(program);
         let mut vm = vm!();
         cairo_runner.initial_pc = Some(relocatable!(0, 1));
         cairo_runner.initial_ap = Some(relocatable!(1, 2));
         cairo_runner.initial_fp = Some(relocatable!(1, 2));
         cairo_runner.initialize_builtins(&mut vm).unwrap();
-        cairo_runner.initialize_segments(&mut vm, None);
+        cairo_runner.initialize_segments(&mut vm, None).unwrap();
         vm.memory = memory![((2, 1), 23), ((2, 4), (-1))];
 
         assert_eq!(
@@ -1562,7 +2207,7 @@ mod tests {
         );
         let mut cairo_runner = cairo_runner!(program);
         let mut vm = vm!();
-        cairo_runner.initialize_segments(&mut vm, None);
+        cairo_runner.initialize_segments(&mut vm, None).unwrap();
         cairo_runner.initialize_main_entrypoint(&mut vm).unwrap();
         cairo_runner.initialize_vm(&mut vm).unwrap();
 
@@ -1614,7 +2259,7 @@ mod tests {
     */
     fn initialization_phase_output_builtin() {
         let program = program!(
-            builtins = vec![String::from("output")],
+            builtins = vec![BuiltinName::Output],
             data = vec_data!(
                 (4612671182993129469_u64),
                 (5198983563776393216_u64),
@@ -1636,7 +2281,7 @@ mod tests {
         let mut vm = vm!();
 
         cairo_runner.initialize_builtins(&mut vm).unwrap();
-        cairo_runner.initialize_segments(&mut vm, None);
+        cairo_runner.initialize_segments(&mut vm, None).unwrap();
         cairo_runner.initialize_main_entrypoint(&mut vm).unwrap();
         cairo_runner.initialize_vm(&mut vm).unwrap();
 
@@ -1697,7 +2342,7 @@ mod tests {
     */
     fn initialization_phase_range_check_builtin() {
         let program = program!(
-            builtins = vec![String::from("range_check")],
+            builtins = vec![BuiltinName::RangeCheck],
             data = vec_data!(
                 (4612671182993129469_u64),
                 (5189976364521848832_u64),
@@ -1724,7 +2369,7 @@ mod tests {
         let mut vm = vm!();
 
         cairo_runner.initialize_builtins(&mut vm).unwrap();
-        cairo_runner.initialize_segments(&mut vm, None);
+        cairo_runner.initialize_segments(&mut vm, None).unwrap();
         cairo_runner.initialize_main_entrypoint(&mut vm).unwrap();
         cairo_runner.initialize_vm(&mut vm).unwrap();
 
@@ -1805,14 +2450,17 @@ mod tests {
         let mut hint_processor = BuiltinHintProcessor::new_empty();
         let mut cairo_runner = cairo_runner!(program);
         let mut vm = vm!(true);
-        cairo_runner.initialize_segments(&mut vm, None);
+        cairo_runner.initialize_segments(&mut vm, None).unwrap();
         let end = cairo_runner.initialize_main_entrypoint(&mut vm).unwrap();
         assert_eq!(end, Relocatable::from((3, 0)));
         cairo_runner.initialize_vm(&mut vm).unwrap();
         //Execution Phase
         assert_eq!(
             cairo_runner.run_until_pc(end, &mut vm, &mut hint_processor),
-            Ok(())
+            Ok(RunOutcome {
+                steps_executed: 5,
+                break_reason: BreakReason::ReachedFinalPc
+            })
         );
         //Check final values against Python VM
         //Check final register values
@@ -1837,6 +2485,153 @@ mod tests {
         );
     }
 
+    ///The program run by initialize_and_run_function_call
+    fn function_call_program() -> Program {
+        program!(
+            data = vec_data!(
+                (5207990763031199744_i64),
+                (2),
+                (2345108766317314046_i64),
+                (5189976364521848832_i64),
+                (1),
+                (1226245742482522112_i64),
+                ((
+                    "3618502788666131213697322783095070105623107215331596699973092056135872020476",
+                    10
+                )),
+                (2345108766317314046_i64)
+            ),
+            main = Some(3),
+        )
+    }
+
+    #[test]
+    fn iterate_function_call_memory() {
+        let program = function_call_program();
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        let mut cairo_runner = cairo_runner!(program);
+        let mut vm = vm!();
+        let end = cairo_runner.initialize(&mut vm).unwrap();
+        cairo_runner
+            .run_until_pc(end, &mut vm, &mut hint_processor)
+            .unwrap();
+
+        let expected_execution_cells = vec![
+            (relocatable!(1, 0), mayberelocatable!(2, 0)),
+            (relocatable!(1, 1), mayberelocatable!(3, 0)),
+            (relocatable!(1, 2), mayberelocatable!(1)),
+            (relocatable!(1, 3), mayberelocatable!(1, 2)),
+            (relocatable!(1, 4), mayberelocatable!(0, 7)),
+            (relocatable!(1, 5), mayberelocatable!(2)),
+        ];
+        let cells: Vec<_> = vm
+            .memory
+            .iter()
+            .map(|(addr, value)| (addr, value.clone()))
+            .collect();
+        assert_eq!(
+            cells[..8],
+            cairo_runner
+                .program
+                .data
+                .iter()
+                .enumerate()
+                .map(|(offset, value)| (relocatable!(0, offset), value.clone()))
+                .collect::<Vec<_>>()[..]
+        );
+        assert_eq!(cells[8..], expected_execution_cells[..]);
+        assert_eq!(
+            vm.memory
+                .segment_iter(1)
+                .map(|(addr, value)| (addr, value.clone()))
+                .collect::<Vec<_>>(),
+            expected_execution_cells
+        );
+    }
+
+    fn run_and_export_accessed_addresses(program: Program) -> Vec<Relocatable> {
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        let mut cairo_runner = cairo_runner!(program);
+        let mut vm = vm!();
+        let end = cairo_runner.initialize(&mut vm).unwrap();
+        cairo_runner
+            .run_until_pc(end, &mut vm, &mut hint_processor)
+            .unwrap();
+        let mut exported = Vec::new();
+        vm.export_accessed_addresses(&mut exported).unwrap();
+        read_accessed_addresses(&mut exported.as_slice()).unwrap()
+    }
+
+    #[test]
+    fn export_function_call_accessed_addresses() {
+        let program = function_call_program();
+        let mut expected: Vec<_> = (0..8).map(|offset| relocatable!(0, offset)).collect();
+        expected.extend((0..6).map(|offset| relocatable!(1, offset)));
+        assert_eq!(run_and_export_accessed_addresses(program), expected);
+    }
+
+    #[test]
+    fn relocate_function_call_memory() {
+        let program = function_call_program();
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        let mut cairo_runner = cairo_runner!(program);
+        let mut vm = vm!();
+        let end = cairo_runner.initialize(&mut vm).unwrap();
+        cairo_runner
+            .run_until_pc(end, &mut vm, &mut hint_processor)
+            .unwrap();
+
+        assert_eq!(
+            vm.segments.relocate_segments(),
+            Err(MemoryError::EffectiveSizesNotCalled)
+        );
+        vm.segments.compute_effective_sizes(&vm.memory);
+        let relocation_table = vm.segments.relocate_segments().unwrap();
+        assert_eq!(relocation_table, vec![1, 9, 15, 15]);
+
+        let relocated = vm.memory.relocate(&relocation_table).unwrap();
+        assert_eq!(relocated.len(), 15);
+        assert_eq!(relocated[0], None);
+        //The program starts at 1
+        assert_eq!(relocated[1], Some(BigInt::from(5207990763031199744_i64)));
+        //The negative call offset is stored as prime - 5
+        assert_eq!(relocated[7], Some(BigInt::from(Felt::new(-5).to_biguint())));
+        assert_eq!(relocated[8], Some(BigInt::from(2345108766317314046_i64)));
+        //The execution segment starts at 9, its first two cells are the return fp and pc,
+        //(2, 0) and (3, 0), both empty segments starting at 15
+        assert_eq!(relocated[9], Some(BigInt::from(15)));
+        assert_eq!(relocated[10], Some(BigInt::from(15)));
+        assert_eq!(relocated[11], Some(BigInt::from(1)));
+        //The fp (1, 2) and return pc (0, 7) pushed by the call
+        assert_eq!(relocated[12], Some(BigInt::from(11)));
+        assert_eq!(relocated[13], Some(BigInt::from(8)));
+        assert_eq!(relocated[14], Some(BigInt::from(2)));
+    }
+
+    #[test]
+    fn diff_accessed_addresses_of_two_programs() {
+        let function_call = function_call_program();
+        //[ap] = 2, ap++
+        //ret
+        let single_write = program!(
+            data = vec_data!((5189976364521848832_i64), (2), (2345108766317314046_i64)),
+            main = Some(0),
+        );
+        let first: HashSet<_> = run_and_export_accessed_addresses(function_call)
+            .into_iter()
+            .collect();
+        let second: HashSet<_> = run_and_export_accessed_addresses(single_write)
+            .into_iter()
+            .collect();
+
+        let mut only_first: Vec<_> = first.difference(&second).copied().collect();
+        only_first.sort_by_key(|addr| (addr.segment_index, addr.offset));
+        let mut expected: Vec<_> = (3..8).map(|offset| relocatable!(0, offset)).collect();
+        expected.extend((3..6).map(|offset| relocatable!(1, offset)));
+        assert_eq!(only_first, expected);
+        assert!(second.is_subset(&first));
+    }
+
     #[test]
     /*Program used:
     %builtins range_check
@@ -1861,7 +2656,7 @@ mod tests {
     fn initialize_and_run_range_check_builtin() {
         //Initialization Phase
         let program = program!(
-            builtins = vec![String::from("range_check")],
+            builtins = vec![BuiltinName::RangeCheck],
             data = vec_data!(
                 (4612671182993129469_i64),
                 (5189976364521848832_i64),
@@ -1887,14 +2682,13 @@ mod tests {
         let mut cairo_runner = cairo_runner!(program);
         let mut vm = vm!(true);
         cairo_runner.initialize_builtins(&mut vm).unwrap();
-        cairo_runner.initialize_segments(&mut vm, None);
+        cairo_runner.initialize_segments(&mut vm, None).unwrap();
         let end = cairo_runner.initialize_main_entrypoint(&mut vm).unwrap();
         cairo_runner.initialize_vm(&mut vm).unwrap();
         //Execution Phase
-        assert_eq!(
-            cairo_runner.run_until_pc(end, &mut vm, &mut hint_processor),
-            Ok(())
-        );
+        cairo_runner
+            .run_until_pc(end, &mut vm, &mut hint_processor)
+            .unwrap();
         //Check final values against Python VM
         //Check final register values
         assert_eq!(vm.run_context.pc, Relocatable::from((4, 0)));
@@ -1922,7 +2716,7 @@ mod tests {
             ]
         );
         //Check the range_check builtin segment
-        assert_eq!(vm.builtin_runners[0].0, String::from("range_check"));
+        assert_eq!(vm.builtin_runners[0].0, BuiltinName::RangeCheck);
         assert_eq!(vm.builtin_runners[0].1.base(), 2);
 
         check_memory!(vm.memory, ((2, 0), 7), ((2, 1), 18446744073709551608_i128));
@@ -1964,7 +2758,7 @@ mod tests {
     fn initialize_and_run_output_builtin() {
         //Initialization Phase
         let program = program!(
-            builtins = vec![String::from("output")],
+            builtins = vec![BuiltinName::Output],
             data = vec_data!(
                 (4612671182993129469_i64),
                 (5198983563776393216_i64),
@@ -1993,14 +2787,13 @@ mod tests {
         let mut cairo_runner = cairo_runner!(program);
         let mut vm = vm!(true);
         cairo_runner.initialize_builtins(&mut vm).unwrap();
-        cairo_runner.initialize_segments(&mut vm, None);
+        cairo_runner.initialize_segments(&mut vm, None).unwrap();
         let end = cairo_runner.initialize_main_entrypoint(&mut vm).unwrap();
         cairo_runner.initialize_vm(&mut vm).unwrap();
         //Execution Phase
-        assert_eq!(
-            cairo_runner.run_until_pc(end, &mut vm, &mut hint_processor),
-            Ok(())
-        );
+        cairo_runner
+            .run_until_pc(end, &mut vm, &mut hint_processor)
+            .unwrap();
         //Check final values against Python VM
         //Check final register values
         //todo
@@ -2031,7 +2824,7 @@ mod tests {
             ]
         );
         //Check that the output to be printed is correct
-        assert_eq!(vm.builtin_runners[0].0, String::from("output"));
+        assert_eq!(vm.builtin_runners[0].0, BuiltinName::Output);
         assert_eq!(vm.builtin_runners[0].1.base(), 2);
         check_memory!(vm.memory, ((2, 0), 1), ((2, 1), 17));
         assert_eq!(vm.memory.get(&MaybeRelocatable::from((2, 2))), Ok(None));
@@ -2088,7 +2881,7 @@ mod tests {
     fn initialize_and_run_output_range_check_builtin() {
         //Initialization Phase
         let program = program!(
-            builtins = vec![String::from("output"), String::from("range_check")],
+            builtins = vec![BuiltinName::Output, BuiltinName::RangeCheck],
             data = vec_data!(
                 (4612671182993129469_i64),
                 (5198983563776393216_i64),
@@ -2127,14 +2920,13 @@ mod tests {
         let mut cairo_runner = cairo_runner!(program);
         let mut vm = vm!(true);
         cairo_runner.initialize_builtins(&mut vm).unwrap();
-        cairo_runner.initialize_segments(&mut vm, None);
+        cairo_runner.initialize_segments(&mut vm, None).unwrap();
         let end = cairo_runner.initialize_main_entrypoint(&mut vm).unwrap();
         cairo_runner.initialize_vm(&mut vm).unwrap();
         //Execution Phase
-        assert_eq!(
-            cairo_runner.run_until_pc(end, &mut vm, &mut hint_processor),
-            Ok(())
-        );
+        cairo_runner
+            .run_until_pc(end, &mut vm, &mut hint_processor)
+            .unwrap();
         //Check final values against Python VM
         //Check final register values
         assert_eq!(vm.run_context.pc, Relocatable::from((5, 0)));
@@ -2170,7 +2962,7 @@ mod tests {
             ]
         );
         //Check the range_check builtin segment
-        assert_eq!(vm.builtin_runners[1].0, String::from("range_check"));
+        assert_eq!(vm.builtin_runners[1].0, BuiltinName::RangeCheck);
         assert_eq!(vm.builtin_runners[1].1.base(), 3);
 
         check_memory!(vm.memory, ((3, 0), 7), ((3, 1), 18446744073709551608_i128));
@@ -2180,7 +2972,7 @@ mod tests {
         );
 
         //Check the output segment
-        assert_eq!(vm.builtin_runners[0].0, String::from("output"));
+        assert_eq!(vm.builtin_runners[0].0, BuiltinName::Output);
         assert_eq!(vm.builtin_runners[0].1.base(), 2);
 
         check_memory!(vm.memory, ((2, 0), 7));
@@ -2265,22 +3057,68 @@ mod tests {
             .relocate_segments()
             .expect("Couldn't relocate after compute effective sizes");
         assert_eq!(cairo_runner.relocate_memory(&mut vm, &rel_table), Ok(()));
-        assert_eq!(cairo_runner.relocated_memory[0], None);
+        assert_eq!(cairo_runner.get_relocated_memory()[0], None);
         assert_eq!(
-            cairo_runner.relocated_memory[1],
+            cairo_runner.get_relocated_memory()[1],
             Some(Felt::new(4613515612218425347_i64))
         );
-        assert_eq!(cairo_runner.relocated_memory[2], Some(Felt::new(5)));
+        assert_eq!(cairo_runner.get_relocated_memory()[2], Some(Felt::new(5)));
         assert_eq!(
-            cairo_runner.relocated_memory[3],
+            cairo_runner.get_relocated_memory()[3],
             Some(Felt::new(2345108766317314046_i64))
         );
-        assert_eq!(cairo_runner.relocated_memory[4], Some(Felt::new(10)));
-        assert_eq!(cairo_runner.relocated_memory[5], Some(Felt::new(10)));
-        assert_eq!(cairo_runner.relocated_memory[6], None);
-        assert_eq!(cairo_runner.relocated_memory[7], None);
-        assert_eq!(cairo_runner.relocated_memory[8], None);
-        assert_eq!(cairo_runner.relocated_memory[9], Some(Felt::new(5)));
+        assert_eq!(cairo_runner.get_relocated_memory()[4], Some(Felt::new(10)));
+        assert_eq!(cairo_runner.get_relocated_memory()[5], Some(Felt::new(10)));
+        assert_eq!(cairo_runner.get_relocated_memory()[6], None);
+        assert_eq!(cairo_runner.get_relocated_memory()[7], None);
+        assert_eq!(cairo_runner.get_relocated_memory()[8], None);
+        assert_eq!(cairo_runner.get_relocated_memory()[9], Some(Felt::new(5)));
+    }
+
+    #[test]
+    fn relocate_dict_manager_pointer_values() {
+        let program = program!();
+        let mut cairo_runner = cairo_runner!(program);
+        let mut vm = vm!();
+        vm.memory = memory![
+            ((0, 0), 1),
+            ((0, 1), 2),
+            ((1, 0), 7),
+            ((1, 1), 8),
+            ((2, 0), 5)
+        ];
+
+        let mut dict_manager = DictManager::new();
+        let dict = HashMap::from([(mayberelocatable!(5), mayberelocatable!(1, 1))]);
+        dict_manager.new_dict(&mut vm, dict).unwrap();
+        cairo_runner
+            .exec_scopes
+            .insert_value("dict_manager", Rc::new(RefCell::new(dict_manager)));
+
+        assert_eq!(cairo_runner.relocate(&mut vm), Ok(()));
+        let relocated_dicts = cairo_runner.get_relocated_dicts(&vm).unwrap();
+        //Segment 1 starts after the two cells of segment 0, and relocated addresses start at 1
+        assert_eq!(
+            relocated_dicts[&3],
+            Dictionary::SimpleDictionary(HashMap::from([(
+                mayberelocatable!(5),
+                mayberelocatable!(4)
+            )]))
+        );
+        assert_eq!(cairo_runner.get_relocated_memory()[4], Some(Felt::new(8)));
+        //The dict manager still holds the pointer, for the hints that run later
+        let dict_manager = cairo_runner.exec_scopes.get_dict_manager().unwrap();
+        assert_eq!(
+            dict_manager.borrow().trackers[&3].get_dictionary_copy()[&mayberelocatable!(5)],
+            mayberelocatable!(1, 1)
+        );
+    }
+
+    #[test]
+    fn get_relocated_dicts_without_dict_manager() {
+        let program = program!();
+        let cairo_runner = cairo_runner!(program);
+        assert_eq!(cairo_runner.get_relocated_dicts(&vm!()), Ok(HashMap::new()));
     }
 
     #[test]
@@ -2328,7 +3166,7 @@ mod tests {
      */
     fn initialize_run_and_relocate_output_builtin() {
         let program = program!(
-            builtins = vec![String::from("output")],
+            builtins = vec![BuiltinName::Output],
             data = vec_data!(
                 (4612671182993129469_i64),
                 (5198983563776393216_i64),
@@ -2357,85 +3195,87 @@ mod tests {
         let mut cairo_runner = cairo_runner!(program);
         let mut vm = vm!(true);
         cairo_runner.initialize_builtins(&mut vm).unwrap();
-        cairo_runner.initialize_segments(&mut vm, None);
+        cairo_runner.initialize_segments(&mut vm, None).unwrap();
         let end = cairo_runner.initialize_main_entrypoint(&mut vm).unwrap();
         cairo_runner.initialize_vm(&mut vm).unwrap();
-        assert_eq!(
-            cairo_runner.run_until_pc(end, &mut vm, &mut hint_processor),
-            Ok(())
-        );
+        cairo_runner
+            .run_until_pc(end, &mut vm, &mut hint_processor)
+            .unwrap();
         vm.segments.compute_effective_sizes(&vm.memory);
         let rel_table = vm
             .segments
             .relocate_segments()
             .expect("Couldn't relocate after compute effective sizes");
         assert_eq!(cairo_runner.relocate_memory(&mut vm, &rel_table), Ok(()));
-        assert_eq!(cairo_runner.relocated_memory[0], None);
+        assert_eq!(cairo_runner.get_relocated_memory()[0], None);
         assert_eq!(
-            cairo_runner.relocated_memory[1],
+            cairo_runner.get_relocated_memory()[1],
             Some(Felt::new(4612671182993129469_i64))
         );
         assert_eq!(
-            cairo_runner.relocated_memory[2],
+            cairo_runner.get_relocated_memory()[2],
             Some(Felt::new(5198983563776393216_i64))
         );
-        assert_eq!(cairo_runner.relocated_memory[3], Some(Felt::one()));
+        assert_eq!(cairo_runner.get_relocated_memory()[3], Some(Felt::one()));
         assert_eq!(
-            cairo_runner.relocated_memory[4],
+            cairo_runner.get_relocated_memory()[4],
             Some(Felt::new(2345108766317314046_i64))
         );
         assert_eq!(
-            cairo_runner.relocated_memory[5],
+            cairo_runner.get_relocated_memory()[5],
             Some(Felt::new(5191102247248822272_i64))
         );
         assert_eq!(
-            cairo_runner.relocated_memory[6],
+            cairo_runner.get_relocated_memory()[6],
             Some(Felt::new(5189976364521848832_i64))
         );
-        assert_eq!(cairo_runner.relocated_memory[7], Some(Felt::one()));
+        assert_eq!(cairo_runner.get_relocated_memory()[7], Some(Felt::one()));
         assert_eq!(
-            cairo_runner.relocated_memory[8],
+            cairo_runner.get_relocated_memory()[8],
             Some(Felt::new(1226245742482522112_i64))
         );
         assert_eq!(
-            cairo_runner.relocated_memory[9],
+            cairo_runner.get_relocated_memory()[9],
             Some(felt_str!(
                 "3618502788666131213697322783095070105623107215331596699973092056135872020474"
             ))
         );
         assert_eq!(
-            cairo_runner.relocated_memory[10],
+            cairo_runner.get_relocated_memory()[10],
             Some(Felt::new(5189976364521848832_i64))
         );
-        assert_eq!(cairo_runner.relocated_memory[11], Some(Felt::new(17)));
+        assert_eq!(cairo_runner.get_relocated_memory()[11], Some(Felt::new(17)));
         assert_eq!(
-            cairo_runner.relocated_memory[12],
+            cairo_runner.get_relocated_memory()[12],
             Some(Felt::new(1226245742482522112_i64))
         );
         assert_eq!(
-            cairo_runner.relocated_memory[13],
+            cairo_runner.get_relocated_memory()[13],
             Some(felt_str!(
                 "3618502788666131213697322783095070105623107215331596699973092056135872020470"
             ))
         );
         assert_eq!(
-            cairo_runner.relocated_memory[14],
+            cairo_runner.get_relocated_memory()[14],
             Some(Felt::new(2345108766317314046_i64))
         );
-        assert_eq!(cairo_runner.relocated_memory[15], Some(Felt::new(27_i32)));
-        assert_eq!(cairo_runner.relocated_memory[16], Some(Felt::new(29)));
-        assert_eq!(cairo_runner.relocated_memory[17], Some(Felt::new(29)));
-        assert_eq!(cairo_runner.relocated_memory[18], Some(Felt::new(27)));
-        assert_eq!(cairo_runner.relocated_memory[19], Some(Felt::one()));
-        assert_eq!(cairo_runner.relocated_memory[20], Some(Felt::new(18)));
-        assert_eq!(cairo_runner.relocated_memory[21], Some(Felt::new(10)));
-        assert_eq!(cairo_runner.relocated_memory[22], Some(Felt::new(28)));
-        assert_eq!(cairo_runner.relocated_memory[23], Some(Felt::new(17)));
-        assert_eq!(cairo_runner.relocated_memory[24], Some(Felt::new(18)));
-        assert_eq!(cairo_runner.relocated_memory[25], Some(Felt::new(14)));
-        assert_eq!(cairo_runner.relocated_memory[26], Some(Felt::new(29)));
-        assert_eq!(cairo_runner.relocated_memory[27], Some(Felt::one()));
-        assert_eq!(cairo_runner.relocated_memory[28], Some(Felt::new(17)));
+        assert_eq!(
+            cairo_runner.get_relocated_memory()[15],
+            Some(Felt::new(27_i32))
+        );
+        assert_eq!(cairo_runner.get_relocated_memory()[16], Some(Felt::new(29)));
+        assert_eq!(cairo_runner.get_relocated_memory()[17], Some(Felt::new(29)));
+        assert_eq!(cairo_runner.get_relocated_memory()[18], Some(Felt::new(27)));
+        assert_eq!(cairo_runner.get_relocated_memory()[19], Some(Felt::one()));
+        assert_eq!(cairo_runner.get_relocated_memory()[20], Some(Felt::new(18)));
+        assert_eq!(cairo_runner.get_relocated_memory()[21], Some(Felt::new(10)));
+        assert_eq!(cairo_runner.get_relocated_memory()[22], Some(Felt::new(28)));
+        assert_eq!(cairo_runner.get_relocated_memory()[23], Some(Felt::new(17)));
+        assert_eq!(cairo_runner.get_relocated_memory()[24], Some(Felt::new(18)));
+        assert_eq!(cairo_runner.get_relocated_memory()[25], Some(Felt::new(14)));
+        assert_eq!(cairo_runner.get_relocated_memory()[26], Some(Felt::new(29)));
+        assert_eq!(cairo_runner.get_relocated_memory()[27], Some(Felt::one()));
+        assert_eq!(cairo_runner.get_relocated_memory()[28], Some(Felt::new(17)));
     }
 
     #[test]
@@ -2463,7 +3303,7 @@ mod tests {
     */
     fn relocate_trace_output_builtin() {
         let program = program!(
-            builtins = vec![String::from("output")],
+            builtins = vec![BuiltinName::Output],
             data = vec_data!(
                 (4612671182993129469_i64),
                 (5198983563776393216_i64),
@@ -2492,20 +3332,19 @@ mod tests {
         let mut cairo_runner = cairo_runner!(program);
         let mut vm = vm!(true);
         cairo_runner.initialize_builtins(&mut vm).unwrap();
-        cairo_runner.initialize_segments(&mut vm, None);
+        cairo_runner.initialize_segments(&mut vm, None).unwrap();
         let end = cairo_runner.initialize_main_entrypoint(&mut vm).unwrap();
         cairo_runner.initialize_vm(&mut vm).unwrap();
-        assert_eq!(
-            cairo_runner.run_until_pc(end, &mut vm, &mut hint_processor),
-            Ok(())
-        );
+        cairo_runner
+            .run_until_pc(end, &mut vm, &mut hint_processor)
+            .unwrap();
         vm.segments.compute_effective_sizes(&vm.memory);
         let rel_table = vm
             .segments
             .relocate_segments()
             .expect("Couldn't relocate after compute effective sizes");
         cairo_runner.relocate_trace(&mut vm, &rel_table).unwrap();
-        let relocated_trace = cairo_runner.relocated_trace.unwrap();
+        let relocated_trace = cairo_runner.get_relocated_trace().unwrap();
         assert_eq!(relocated_trace.len(), 12);
         assert_eq!(
             relocated_trace[0],
@@ -2607,12 +3446,12 @@ mod tests {
 
     #[test]
     fn write_output_from_preset_memory() {
-        let program = program!["output"];
+        let program = program![BuiltinName::Output];
         let mut cairo_runner = cairo_runner!(program);
         let mut vm = vm!();
         cairo_runner.initialize_builtins(&mut vm).unwrap();
-        cairo_runner.initialize_segments(&mut vm, None);
-        assert_eq!(vm.builtin_runners[0].0, String::from("output"));
+        cairo_runner.initialize_segments(&mut vm, None).unwrap();
+        assert_eq!(vm.builtin_runners[0].0, BuiltinName::Output);
         assert_eq!(vm.builtin_runners[0].1.base(), 2);
 
         vm.memory = memory![((2, 0), 1), ((2, 1), 2)];
@@ -2636,7 +3475,7 @@ mod tests {
     fn write_output_from_program() {
         //Initialization Phase
         let program = program!(
-            builtins = vec![String::from("output")],
+            builtins = vec![BuiltinName::Output],
             data = vec_data!(
                 (4612671182993129469_i64),
                 (5198983563776393216_i64),
@@ -2664,29 +3503,69 @@ mod tests {
         let mut cairo_runner = cairo_runner!(program);
         let mut vm = vm!();
         cairo_runner.initialize_builtins(&mut vm).unwrap();
-        cairo_runner.initialize_segments(&mut vm, None);
+        cairo_runner.initialize_segments(&mut vm, None).unwrap();
         let end = cairo_runner.initialize_main_entrypoint(&mut vm).unwrap();
         cairo_runner.initialize_vm(&mut vm).unwrap();
         //Execution Phase
         let mut hint_processor = BuiltinHintProcessor::new_empty();
-        assert_eq!(
-            cairo_runner.run_until_pc(end, &mut vm, &mut hint_processor),
-            Ok(())
-        );
+        cairo_runner
+            .run_until_pc(end, &mut vm, &mut hint_processor)
+            .unwrap();
 
         let mut stdout = Vec::<u8>::new();
         cairo_runner.write_output(&mut vm, &mut stdout).unwrap();
         assert_eq!(String::from_utf8(stdout), Ok(String::from("1\n17\n")));
     }
 
+    #[test]
+    fn write_output_three_values() {
+        let program = program![BuiltinName::Output];
+        let mut cairo_runner = cairo_runner!(program);
+        let mut vm = vm!();
+        cairo_runner.initialize_builtins(&mut vm).unwrap();
+        cairo_runner.initialize_segments(&mut vm, None).unwrap();
+        vm.memory = memory![((2, 0), 7), ((2, 1), 0), ((2, 2), 42)];
+        let mut stdout = Vec::<u8>::new();
+        cairo_runner.write_output(&mut vm, &mut stdout).unwrap();
+        assert_eq!(String::from_utf8(stdout), Ok(String::from("7\n0\n42\n")));
+    }
+
+    #[test]
+    fn get_output_values_with_hole() {
+        let program = program![BuiltinName::Output];
+        let mut cairo_runner = cairo_runner!(program);
+        let mut vm = vm!();
+        cairo_runner.initialize_builtins(&mut vm).unwrap();
+        cairo_runner.initialize_segments(&mut vm, None).unwrap();
+        vm.memory = memory![((2, 0), 1), ((2, 2), 3)];
+        assert_eq!(
+            cairo_runner.get_output_values(&mut vm),
+            Err(RunnerError::MemoryGet(mayberelocatable!(2, 1)))
+        );
+    }
+
+    #[test]
+    fn get_output_values_with_relocatable() {
+        let program = program![BuiltinName::Output];
+        let mut cairo_runner = cairo_runner!(program);
+        let mut vm = vm!();
+        cairo_runner.initialize_builtins(&mut vm).unwrap();
+        cairo_runner.initialize_segments(&mut vm, None).unwrap();
+        vm.memory = memory![((2, 0), 1), ((2, 1), (1, 0))];
+        assert_eq!(
+            cairo_runner.get_output_values(&mut vm),
+            Err(RunnerError::MemoryGet(mayberelocatable!(2, 1)))
+        );
+    }
+
     #[test]
     fn write_output_from_preset_memory_neg_output() {
-        let program = program!["output"];
+        let program = program![BuiltinName::Output];
         let mut cairo_runner = cairo_runner!(program);
         let mut vm = vm!();
         cairo_runner.initialize_builtins(&mut vm).unwrap();
-        cairo_runner.initialize_segments(&mut vm, None);
-        assert_eq!(vm.builtin_runners[0].0, String::from("output"));
+        cairo_runner.initialize_segments(&mut vm, None).unwrap();
+        assert_eq!(vm.builtin_runners[0].0, BuiltinName::Output);
         assert_eq!(vm.builtin_runners[0].1.base(), 2);
         vm.memory = memory![(
             (2, 0),
@@ -2706,7 +3585,7 @@ mod tests {
     fn write_output_unordered_builtins() {
         //Initialization Phase
         let program = program!(
-            builtins = vec!["output".to_string(), "bitwise".to_string()],
+            builtins = vec![BuiltinName::Output, BuiltinName::Bitwise],
             data = vec_data!(
                 (4612671182993129469_i64),
                 (5198983563776393216_i64),
@@ -2742,7 +3621,7 @@ mod tests {
         // Swap the first and second builtins (first should be `output`).
         vm.builtin_runners.swap(0, 1);
 
-        cairo_runner.initialize_segments(&mut vm, None);
+        cairo_runner.initialize_segments(&mut vm, None).unwrap();
 
         let end = cairo_runner
             .initialize_main_entrypoint(&mut vm)
@@ -2752,10 +3631,9 @@ mod tests {
             .expect("Couldn't initialize the VM.");
 
         let mut hint_processor = BuiltinHintProcessor::new_empty();
-        assert_eq!(
-            cairo_runner.run_until_pc(end, &mut vm, &mut hint_processor),
-            Ok(())
-        );
+        cairo_runner
+            .run_until_pc(end, &mut vm, &mut hint_processor)
+            .unwrap();
 
         let mut stdout = Vec::<u8>::new();
         cairo_runner
@@ -2766,15 +3644,21 @@ mod tests {
 
     #[test]
     fn insert_all_builtins_in_order() {
-        let program = program!["output", "pedersen", "range_check", "bitwise", "ec_op"];
+        let program = program![
+            BuiltinName::Output,
+            BuiltinName::Pedersen,
+            BuiltinName::RangeCheck,
+            BuiltinName::Bitwise,
+            BuiltinName::EcOp
+        ];
         let cairo_runner = cairo_runner!(program);
         let mut vm = vm!();
         cairo_runner.initialize_builtins(&mut vm).unwrap();
-        assert_eq!(vm.builtin_runners[0].0, String::from("output"));
-        assert_eq!(vm.builtin_runners[1].0, String::from("pedersen"));
-        assert_eq!(vm.builtin_runners[2].0, String::from("range_check"));
-        assert_eq!(vm.builtin_runners[3].0, String::from("bitwise"));
-        assert_eq!(vm.builtin_runners[4].0, String::from("ec_op"));
+        assert_eq!(vm.builtin_runners[0].0, BuiltinName::Output);
+        assert_eq!(vm.builtin_runners[1].0, BuiltinName::Pedersen);
+        assert_eq!(vm.builtin_runners[2].0, BuiltinName::RangeCheck);
+        assert_eq!(vm.builtin_runners[3].0, BuiltinName::Bitwise);
+        assert_eq!(vm.builtin_runners[4].0, BuiltinName::EcOp);
     }
 
     #[test]
@@ -2799,7 +3683,7 @@ mod tests {
     */
     fn run_for_steps() {
         let program = program!(
-            builtins = vec![String::from("range_check")],
+            builtins = vec![BuiltinName::RangeCheck],
             data = vec_data!(
                 (4612671182993129469_i64),
                 (5189976364521848832_i64),
@@ -2827,16 +3711,15 @@ mod tests {
 
         let mut vm = vm!(true);
         cairo_runner.initialize_builtins(&mut vm).unwrap();
-        cairo_runner.initialize_segments(&mut vm, None);
+        cairo_runner.initialize_segments(&mut vm, None).unwrap();
 
         cairo_runner.initialize_main_entrypoint(&mut vm).unwrap();
         cairo_runner.initialize_vm(&mut vm).unwrap();
 
         // Full takes 10 steps.
-        assert_eq!(
-            cairo_runner.run_for_steps(8, &mut vm, &mut hint_processor),
-            Ok(())
-        );
+        cairo_runner
+            .run_for_steps(8, &mut vm, &mut hint_processor)
+            .unwrap();
         assert_eq!(
             cairo_runner.run_for_steps(8, &mut vm, &mut hint_processor),
             Err(VirtualMachineError::EndOfProgram(8 - 2))
@@ -2865,7 +3748,7 @@ mod tests {
     */
     fn run_until_steps() {
         let program = program!(
-            builtins = vec![String::from("range_check")],
+            builtins = vec![BuiltinName::RangeCheck],
             data = vec_data!(
                 (4612671182993129469_i64),
                 (5189976364521848832_i64),
@@ -2893,20 +3776,18 @@ mod tests {
 
         let mut vm = vm!(true);
         cairo_runner.initialize_builtins(&mut vm).unwrap();
-        cairo_runner.initialize_segments(&mut vm, None);
+        cairo_runner.initialize_segments(&mut vm, None).unwrap();
 
         cairo_runner.initialize_main_entrypoint(&mut vm).unwrap();
         cairo_runner.initialize_vm(&mut vm).unwrap();
 
         // Full takes 10 steps.
-        assert_eq!(
-            cairo_runner.run_until_steps(8, &mut vm, &mut hint_processor),
-            Ok(())
-        );
-        assert_eq!(
-            cairo_runner.run_until_steps(10, &mut vm, &mut hint_processor),
-            Ok(())
-        );
+        cairo_runner
+            .run_until_steps(8, &mut vm, &mut hint_processor)
+            .unwrap();
+        cairo_runner
+            .run_until_steps(10, &mut vm, &mut hint_processor)
+            .unwrap();
         assert_eq!(
             cairo_runner.run_until_steps(11, &mut vm, &mut hint_processor),
             Err(VirtualMachineError::EndOfProgram(1)),
@@ -2937,7 +3818,7 @@ mod tests {
     /// step reaches a power of two, or an error occurs.
     fn run_until_next_power_of_2() {
         let program = program!(
-            builtins = vec![String::from("range_check")],
+            builtins = vec![BuiltinName::RangeCheck],
             data = vec_data!(
                 (4612671182993129469_i64),
                 (5189976364521848832_i64),
@@ -2965,56 +3846,54 @@ mod tests {
 
         let mut vm = vm!(true);
         cairo_runner.initialize_builtins(&mut vm).unwrap();
-        cairo_runner.initialize_segments(&mut vm, None);
+        cairo_runner.initialize_segments(&mut vm, None).unwrap();
 
         cairo_runner.initialize_main_entrypoint(&mut vm).unwrap();
         cairo_runner.initialize_vm(&mut vm).unwrap();
 
         // Full takes 10 steps.
-        assert_eq!(
-            cairo_runner.run_for_steps(1, &mut vm, &mut hint_processor),
-            Ok(()),
-        );
-        assert_eq!(
-            cairo_runner.run_until_next_power_of_2(&mut vm, &mut hint_processor),
-            Ok(())
-        );
-        assert_eq!(vm.current_step, 1);
-
-        assert_eq!(
-            cairo_runner.run_for_steps(1, &mut vm, &mut hint_processor),
-            Ok(()),
-        );
-        assert_eq!(
-            cairo_runner.run_until_next_power_of_2(&mut vm, &mut hint_processor),
-            Ok(())
-        );
+        cairo_runner
+            .run_for_steps(1, &mut vm, &mut hint_processor)
+            .unwrap();
+        cairo_runner
+            .run_until_next_power_of_2(&mut vm, &mut hint_processor)
+            .unwrap();
+        assert_eq!(vm.current_step, 1);
+
+        cairo_runner
+            .run_for_steps(1, &mut vm, &mut hint_processor)
+            .unwrap();
+        cairo_runner
+            .run_until_next_power_of_2(&mut vm, &mut hint_processor)
+            .unwrap();
         assert_eq!(vm.current_step, 2);
 
         assert_eq!(
             cairo_runner.run_for_steps(1, &mut vm, &mut hint_processor),
-            Ok(()),
+            Ok(RunOutcome {
+                steps_executed: 1,
+                break_reason: BreakReason::StepLimit
+            })
         );
         assert_eq!(
-            cairo_runner.run_until_next_power_of_2(&mut vm, &mut hint_processor),
-            Ok(())
+            cairo_runner
+                .run_until_next_power_of_2(&mut vm, &mut hint_processor)
+                .map(|outcome| outcome.steps_executed),
+            Ok(1)
         );
         assert_eq!(vm.current_step, 4);
 
-        assert_eq!(
-            cairo_runner.run_for_steps(1, &mut vm, &mut hint_processor),
-            Ok(()),
-        );
-        assert_eq!(
-            cairo_runner.run_until_next_power_of_2(&mut vm, &mut hint_processor),
-            Ok(())
-        );
+        cairo_runner
+            .run_for_steps(1, &mut vm, &mut hint_processor)
+            .unwrap();
+        cairo_runner
+            .run_until_next_power_of_2(&mut vm, &mut hint_processor)
+            .unwrap();
         assert_eq!(vm.current_step, 8);
 
-        assert_eq!(
-            cairo_runner.run_for_steps(1, &mut vm, &mut hint_processor),
-            Ok(()),
-        );
+        cairo_runner
+            .run_for_steps(1, &mut vm, &mut hint_processor)
+            .unwrap();
         assert_eq!(
             cairo_runner.run_until_next_power_of_2(&mut vm, &mut hint_processor),
             Err(VirtualMachineError::EndOfProgram(6)),
@@ -3033,6 +3912,87 @@ mod tests {
         assert_eq!(cairo_runner.get_constants(), &program_constants);
     }
 
+    fn initialize_fibonacci() -> (CairoRunner, VirtualMachine, Relocatable) {
+        let program = Program::from_file(
+            Path::new("cairo_programs/manually_compiled/fibonacci.json"),
+            Some("main"),
+        )
+        .unwrap();
+        let mut cairo_runner = cairo_runner!(program);
+        let mut vm = vm!(true);
+        let end = cairo_runner.initialize(&mut vm).unwrap();
+        (cairo_runner, vm, end)
+    }
+
+    #[test]
+    fn run_until_breakpoint_hits_breakpoints_in_order() {
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        let (mut expected_runner, mut expected_vm, end) = initialize_fibonacci();
+        expected_runner
+            .run_until_pc(end, &mut expected_vm, &mut hint_processor)
+            .unwrap();
+
+        let (mut cairo_runner, mut vm, end) = initialize_fibonacci();
+        //The call to fib and the return from main
+        vm.add_breakpoint(relocatable!(0, 8));
+        vm.add_breakpoint(relocatable!(0, 6));
+        assert_eq!(
+            cairo_runner.run_until_breakpoint(end, None, None, &mut vm, &mut hint_processor),
+            Ok(BreakReason::Breakpoint(relocatable!(0, 6)))
+        );
+        assert_eq!(vm.current_step, 3);
+        assert_eq!(
+            cairo_runner.run_until_breakpoint(end, None, None, &mut vm, &mut hint_processor),
+            Ok(BreakReason::Breakpoint(relocatable!(0, 8)))
+        );
+        assert_eq!(vm.current_step, expected_vm.current_step - 1);
+        assert_eq!(
+            cairo_runner.run_until_breakpoint(end, None, None, &mut vm, &mut hint_processor),
+            Ok(BreakReason::ReachedFinalPc)
+        );
+        assert_eq!(vm.current_step, expected_vm.current_step);
+        assert_eq!(vm.get_ap(), expected_vm.get_ap());
+        assert_eq!(vm.trace, expected_vm.trace);
+    }
+
+    #[test]
+    fn run_until_breakpoint_step_limit_and_cancel() {
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        let (mut cairo_runner, mut vm, end) = initialize_fibonacci();
+        vm.add_breakpoint(relocatable!(0, 6));
+        assert!(vm.remove_breakpoint(&relocatable!(0, 6)));
+        assert!(!vm.remove_breakpoint(&relocatable!(0, 6)));
+        assert_eq!(
+            cairo_runner.run_until_breakpoint(end, Some(5), None, &mut vm, &mut hint_processor),
+            Ok(BreakReason::StepLimit)
+        );
+        assert_eq!(vm.current_step, 5);
+
+        let cancel = AtomicBool::new(true);
+        assert_eq!(
+            cairo_runner.run_until_breakpoint(
+                end,
+                None,
+                Some(&cancel),
+                &mut vm,
+                &mut hint_processor
+            ),
+            Ok(BreakReason::Cancelled)
+        );
+        assert_eq!(vm.current_step, 5);
+        cancel.store(false, Ordering::Relaxed);
+        assert_eq!(
+            cairo_runner.run_until_breakpoint(
+                end,
+                None,
+                Some(&cancel),
+                &mut vm,
+                &mut hint_processor
+            ),
+            Ok(BreakReason::ReachedFinalPc)
+        );
+    }
+
     #[test]
     fn get_memory_holes_missing_accessed_addresses() {
         let program = program!();
@@ -3080,7 +4040,7 @@ mod tests {
         let cairo_runner = cairo_runner!(program);
         let mut vm = vm!();
 
-        vm.accessed_addresses = Some(vec![(0, 0).into(), (0, 2).into()]);
+        vm.accessed_addresses = Some(HashSet::from([(0, 0).into(), (0, 2).into()]));
         vm.builtin_runners = Vec::new();
         vm.segments.segment_used_sizes = Some(vec![4]);
         assert_eq!(cairo_runner.get_memory_holes(&vm), Ok(2));
@@ -3095,9 +4055,11 @@ mod tests {
 
         vm.builtin_runners = vec![{
             let mut builtin_runner: BuiltinRunner = OutputBuiltinRunner::new(true).into();
-            builtin_runner.initialize_segments(&mut vm.segments, &mut vm.memory);
+            builtin_runner
+                .initialize_segments(&mut vm.segments, &mut vm.memory)
+                .unwrap();
 
-            ("output".to_string(), builtin_runner)
+            (BuiltinName::Output, builtin_runner)
         }];
         vm.segments.segment_used_sizes = Some(vec![4]);
         assert_eq!(cairo_runner.get_memory_holes(&vm), Ok(0));
@@ -3110,12 +4072,14 @@ mod tests {
         let cairo_runner = cairo_runner!(program);
         let mut vm = vm!();
 
-        vm.accessed_addresses = Some(vec![(1, 0).into(), (1, 2).into()]);
+        vm.accessed_addresses = Some(HashSet::from([(1, 0).into(), (1, 2).into()]));
         vm.builtin_runners = vec![{
             let mut builtin_runner: BuiltinRunner = OutputBuiltinRunner::new(true).into();
-            builtin_runner.initialize_segments(&mut vm.segments, &mut vm.memory);
+            builtin_runner
+                .initialize_segments(&mut vm.segments, &mut vm.memory)
+                .unwrap();
 
-            ("output".to_string(), builtin_runner)
+            (BuiltinName::Output, builtin_runner)
         }];
         vm.segments.segment_used_sizes = Some(vec![4, 4]);
         assert_eq!(cairo_runner.get_memory_holes(&vm), Ok(2));
@@ -3175,7 +4139,7 @@ mod tests {
 
         vm.current_step = 8192;
         vm.builtin_runners = vec![(
-            "bitwise".to_string(),
+            BuiltinName::Bitwise,
             BitwiseBuiltinRunner::new(&BitwiseInstanceDef::default(), true).into(),
         )];
         assert_eq!(cairo_runner.check_diluted_check_usage(&vm), Ok(()),);
@@ -3204,14 +4168,13 @@ mod tests {
         let mut cairo_runner = cairo_runner!(program);
         let mut vm = vm!();
 
-        vm.accessed_addresses = Some(Vec::new());
+        vm.accessed_addresses = Some(HashSet::new());
         assert_eq!(
             cairo_runner.end_run(true, false, &mut vm, &mut hint_processor),
             Ok(()),
         );
 
         cairo_runner.run_ended = false;
-        cairo_runner.relocated_memory.clear();
         assert_eq!(
             cairo_runner.end_run(true, true, &mut vm, &mut hint_processor),
             Ok(()),
@@ -3262,7 +4225,7 @@ mod tests {
         let mut vm = vm!();
 
         vm.builtin_runners = vec![(
-            "output".to_string(),
+            BuiltinName::Output,
             BuiltinRunner::Output(OutputBuiltinRunner::new(true)),
         )];
         assert_eq!(
@@ -3271,6 +4234,63 @@ mod tests {
         );
     }
 
+    #[test]
+    fn get_builtin_segment_addresses_after_run() {
+        let program = Program::from_file(
+            Path::new("cairo_programs/manually_compiled/pedersen_output_program.json"),
+            Some("main"),
+        )
+        .unwrap();
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        let mut cairo_runner = cairo_runner!(program);
+        let mut vm = vm!();
+        cairo_runner.initialize_builtins(&mut vm).unwrap();
+        cairo_runner.initialize_segments(&mut vm, None).unwrap();
+        vm.segments.compute_effective_sizes(&vm.memory);
+        assert_eq!(
+            cairo_runner.get_builtin_segment_addresses(&vm),
+            Err(RunnerError::NoStopPointer(BuiltinName::Output))
+        );
+
+        vm.segments.segment_used_sizes = None;
+        cairo_runner
+            .run_from_entrypoint(
+                0,
+                vec![],
+                &[BuiltinName::Output, BuiltinName::Pedersen],
+                false,
+                false,
+                false,
+                &mut vm,
+                &mut hint_processor,
+            )
+            .unwrap();
+        //The program (13 cells) and execution (9 cells) segments come first, relocated from 1
+        assert_eq!(
+            vm.segments.segment_used_sizes,
+            Some(vec![13, 9, 1, 3, 0, 0])
+        );
+        assert_eq!(
+            cairo_runner.get_builtin_segment_addresses(&vm),
+            Ok(HashMap::from([
+                (
+                    BuiltinName::Output,
+                    BuiltinSegmentAddresses {
+                        begin_addr: 23,
+                        stop_ptr: 24
+                    }
+                ),
+                (
+                    BuiltinName::Pedersen,
+                    BuiltinSegmentAddresses {
+                        begin_addr: 24,
+                        stop_ptr: 27
+                    }
+                )
+            ]))
+        );
+    }
+
     #[test]
     fn get_execution_resources_trace_not_enabled() {
         let program = program!();
@@ -3284,7 +4304,7 @@ mod tests {
             Ok(ExecutionResources {
                 n_steps: 0,
                 n_memory_holes: 0,
-                builtin_instance_counter: HashMap::new(),
+                builtin_instance_counter: BTreeMap::new(),
             }),
         );
     }
@@ -3303,7 +4323,7 @@ mod tests {
             Ok(ExecutionResources {
                 n_steps: 10,
                 n_memory_holes: 0,
-                builtin_instance_counter: HashMap::new(),
+                builtin_instance_counter: BTreeMap::new(),
             }),
         );
     }
@@ -3321,14 +4341,14 @@ mod tests {
             let mut builtin = OutputBuiltinRunner::new(true);
             builtin.initialize_segments(&mut vm.segments, &mut vm.memory);
 
-            ("output".to_string(), BuiltinRunner::Output(builtin))
+            (BuiltinName::Output, BuiltinRunner::Output(builtin))
         }];
         assert_eq!(
             cairo_runner.get_execution_resources(&vm),
             Ok(ExecutionResources {
                 n_steps: 10,
                 n_memory_holes: 0,
-                builtin_instance_counter: HashMap::from([("output".to_string(), 4)]),
+                builtin_instance_counter: BTreeMap::from([(BuiltinName::Output, 4)]),
             }),
         );
     }
@@ -3353,6 +4373,7 @@ mod tests {
             cairo_runner.run_from_entrypoint(
                 entrypoint,
                 vec![],
+                &[],
                 true,
                 true,
                 true,
@@ -3365,6 +4386,7 @@ mod tests {
             cairo_runner.run_from_entrypoint(
                 entrypoint,
                 vec![&mayberelocatable!(0), &mayberelocatable!(1)],
+                &[],
                 true,
                 true,
                 true,
@@ -3392,21 +4414,21 @@ mod tests {
             .pc
             .unwrap();
 
-        vm.accessed_addresses = Some(Vec::new());
+        vm.accessed_addresses = Some(HashSet::new());
         cairo_runner.initialize_builtins(&mut vm).unwrap();
-        cairo_runner.initialize_segments(&mut vm, None);
-        assert_eq!(
-            cairo_runner.run_from_entrypoint(
+        cairo_runner.initialize_segments(&mut vm, None).unwrap();
+        cairo_runner
+            .run_from_entrypoint(
                 entrypoint,
                 vec![&mayberelocatable!(0)],
+                &[],
                 true,
                 true,
                 true,
                 &mut vm,
                 &mut hint_processor,
-            ),
-            Ok(()),
-        );
+            )
+            .unwrap();
     }
 
     /// Test that the call to .run_from_entrypoint() when typed_args is false
@@ -3426,20 +4448,178 @@ mod tests {
             .pc
             .unwrap();
 
-        vm.accessed_addresses = Some(Vec::new());
+        vm.accessed_addresses = Some(HashSet::new());
         cairo_runner.initialize_builtins(&mut vm).unwrap();
-        cairo_runner.initialize_segments(&mut vm, None);
+        cairo_runner.initialize_segments(&mut vm, None).unwrap();
+        cairo_runner
+            .run_from_entrypoint(
+                entrypoint,
+                vec![],
+                &[],
+                false,
+                true,
+                true,
+                &mut vm,
+                &mut hint_processor,
+            )
+            .unwrap();
+    }
+
+    /// Test that sequential calls to .run_from_entrypoint() pass the builtin
+    /// pointers the previous call ended with.
+    /// func hash_pair{pedersen_ptr: HashBuiltin*}(x: felt, y: felt) {
+    ///     assert pedersen_ptr.x = x;
+    ///     assert pedersen_ptr.y = y;
+    ///     let pedersen_ptr = pedersen_ptr + HashBuiltin.SIZE;
+    ///     return ();
+    /// }
+    #[test]
+    fn run_from_entrypoint_sequential_calls_with_builtins() {
+        let program = program!(
+            builtins = vec![BuiltinName::Pedersen],
+            data = vec![
+                mayberelocatable!(0x400380007ffb7ffc_i64),
+                mayberelocatable!(0x400380017ffb7ffd_i64),
+                mayberelocatable!(0x482680017ffb8000_i64),
+                mayberelocatable!(3),
+                mayberelocatable!(0x208b7fff7fff7ffe_i64),
+            ],
+        );
+        let mut cairo_runner = cairo_runner!(program);
+        let mut vm = vm!();
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        let ordered_builtins = vec![BuiltinName::Pedersen];
+
+        cairo_runner.initialize_builtins(&mut vm).unwrap();
+        cairo_runner.initialize_segments(&mut vm, None).unwrap();
+        let pedersen_base = vm.builtin_runners[0].1.base();
+
+        cairo_runner
+            .run_from_entrypoint(
+                0,
+                vec![&mayberelocatable!(1), &mayberelocatable!(2)],
+                &ordered_builtins,
+                false,
+                true,
+                true,
+                &mut vm,
+                &mut hint_processor,
+            )
+            .unwrap();
+        assert_eq!(
+            vm.builtin_runners[0].1.current_ptr(),
+            Relocatable::from((pedersen_base, 3))
+        );
+
+        cairo_runner
+            .run_from_entrypoint(
+                0,
+                vec![&mayberelocatable!(3), &mayberelocatable!(4)],
+                &ordered_builtins,
+                false,
+                true,
+                true,
+                &mut vm,
+                &mut hint_processor,
+            )
+            .unwrap();
+        assert_eq!(
+            vm.builtin_runners[0].1.current_ptr(),
+            Relocatable::from((pedersen_base, 6))
+        );
+        check_memory![
+            vm.memory,
+            ((pedersen_base, 0), 1),
+            ((pedersen_base, 1), 2),
+            ((pedersen_base, 3), 3),
+            ((pedersen_base, 4), 4)
+        ];
+    }
+
+    #[test]
+    fn run_from_entrypoint_missing_builtin() {
+        let program = program!();
+        let mut cairo_runner = cairo_runner!(program);
+        let mut vm = vm!();
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+
         assert_eq!(
             cairo_runner.run_from_entrypoint(
-                entrypoint,
+                0,
                 vec![],
+                &[BuiltinName::Pedersen],
                 false,
                 true,
                 true,
                 &mut vm,
                 &mut hint_processor,
             ),
-            Ok(()),
+            Err(VirtualMachineError::RunnerError(
+                RunnerError::MissingBuiltin(BuiltinName::Pedersen)
+            )),
+        );
+    }
+
+    #[test]
+    fn run_from_function() {
+        let program = Program::from_file(
+            Path::new("cairo_programs/manually_compiled/identifiers_program.json"),
+            Some("main"),
+        )
+        .unwrap();
+        let mut cairo_runner = cairo_runner!(program);
+        let mut vm = vm!();
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+
+        cairo_runner.initialize_builtins(&mut vm).unwrap();
+        cairo_runner.initialize_segments(&mut vm, None).unwrap();
+        cairo_runner
+            .run_from_function(
+                "__main__.main",
+                vec![],
+                &[],
+                false,
+                true,
+                true,
+                &mut vm,
+                &mut hint_processor,
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn run_from_function_not_found() {
+        let program = Program::from_file(
+            Path::new("cairo_programs/manually_compiled/identifiers_program.json"),
+            Some("main"),
+        )
+        .unwrap();
+        let mut cairo_runner = cairo_runner!(program);
+        let mut vm = vm!();
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+
+        let error = cairo_runner
+            .run_from_function(
+                "__main__.mian",
+                vec![],
+                &[],
+                false,
+                true,
+                true,
+                &mut vm,
+                &mut hint_processor,
+            )
+            .unwrap_err();
+        assert_eq!(
+            error,
+            VirtualMachineError::RunnerError(RunnerError::FunctionNotFound(
+                String::from("__main__.mian"),
+                vec![String::from("__main__.main")]
+            ))
+        );
+        assert_eq!(
+            error.to_string(),
+            "Function __main__.mian not found, near misses: [\"__main__.main\"]"
         );
     }
 
@@ -3659,7 +4839,7 @@ mod tests {
         }]);
         vm.memory.data = vec![vec![mayberelocatable!(0x80FF_8000_0530u64).into()]];
         vm.builtin_runners = vec![(
-            "range_check".to_string(),
+            BuiltinName::RangeCheck,
             RangeCheckBuiltinRunner::new(12, 5, true).into(),
         )];
 
@@ -3711,7 +4891,7 @@ mod tests {
         let cairo_runner = cairo_runner!(program);
         let mut vm = vm!();
         vm.builtin_runners = vec![(
-            "range_check".to_string(),
+            BuiltinName::RangeCheck,
             RangeCheckBuiltinRunner::new(8, 8, true).into(),
         )];
         vm.memory.data = vec![vec![Some(mayberelocatable!(0x80FF_8000_0530u64))]];
@@ -3739,7 +4919,7 @@ mod tests {
     #[test]
     fn get_initial_fp_can_be_obtained() {
         //This test works with basic Program definition, will later be updated to use Program::new() when fully defined
-        let program = program!["output"];
+        let program = program![BuiltinName::Output];
         let mut cairo_runner = cairo_runner!(program);
         let mut vm = vm!();
         for _ in 0..2 {
@@ -3756,7 +4936,7 @@ mod tests {
 
     #[test]
     fn check_used_cells_valid_case() {
-        let program = program!["range_check", "output"];
+        let program = program![BuiltinName::RangeCheck, BuiltinName::Output];
         let mut cairo_runner = cairo_runner!(program);
         let mut vm = vm!();
         vm.segments.segment_used_sizes = Some(vec![4]);
@@ -3773,7 +4953,7 @@ mod tests {
         let cairo_runner = cairo_runner!(program);
         let mut vm = vm!();
         vm.builtin_runners = vec![(
-            "range_check".to_string(),
+            BuiltinName::RangeCheck,
             RangeCheckBuiltinRunner::new(8, 8, true).into(),
         )];
         vm.memory.data = vec![vec![Some(mayberelocatable!(0x80FF_8000_0530u64))]];
@@ -3798,12 +4978,14 @@ mod tests {
         let cairo_runner = cairo_runner!(program);
         let mut vm = vm!();
 
-        vm.accessed_addresses = Some(vec![(1, 0).into(), (1, 3).into()]);
+        vm.accessed_addresses = Some(HashSet::from([(1, 0).into(), (1, 3).into()]));
         vm.builtin_runners = vec![{
             let mut builtin_runner: BuiltinRunner = OutputBuiltinRunner::new(true).into();
-            builtin_runner.initialize_segments(&mut vm.segments, &mut vm.memory);
+            builtin_runner
+                .initialize_segments(&mut vm.segments, &mut vm.memory)
+                .unwrap();
 
-            ("output".to_string(), builtin_runner)
+            (BuiltinName::Output, builtin_runner)
         }];
         vm.segments.segment_used_sizes = Some(vec![4, 12]);
         vm.trace = Some(vec![]);
@@ -3818,7 +5000,7 @@ mod tests {
 
     #[test]
     fn check_used_cells_check_diluted_check_usage_error() {
-        let program = program!["range_check", "output"];
+        let program = program![BuiltinName::RangeCheck, BuiltinName::Output];
         let cairo_runner = cairo_runner!(program);
         let mut vm = vm!();
         vm.segments.segment_used_sizes = Some(vec![4]);
@@ -3845,18 +5027,22 @@ mod tests {
 
         let given_output = vm.get_builtin_runners();
 
-        assert_eq!(given_output[0].0, "pedersen");
-        assert_eq!(given_output[1].0, "range_check");
-        assert_eq!(given_output[2].0, "output");
-        assert_eq!(given_output[3].0, "ecdsa");
-        assert_eq!(given_output[4].0, "bitwise");
-        assert_eq!(given_output[5].0, "ec_op");
-        assert_eq!(given_output[6].0, "keccak");
+        assert_eq!(given_output[0].0, BuiltinName::Pedersen);
+        assert_eq!(given_output[1].0, BuiltinName::RangeCheck);
+        assert_eq!(given_output[2].0, BuiltinName::Output);
+        assert_eq!(given_output[3].0, BuiltinName::Ecdsa);
+        assert_eq!(given_output[4].0, BuiltinName::Bitwise);
+        assert_eq!(given_output[5].0, BuiltinName::EcOp);
+        assert_eq!(given_output[6].0, BuiltinName::Keccak);
     }
 
     #[test]
     fn initialize_all_builtins_maintain_program_order() {
-        let program = program!["pedersen", "range_check", "ecdsa"];
+        let program = program![
+            BuiltinName::Pedersen,
+            BuiltinName::RangeCheck,
+            BuiltinName::Ecdsa
+        ];
 
         let cairo_runner = cairo_runner!(program);
         let mut vm = vm!();
@@ -3867,13 +5053,13 @@ mod tests {
 
         let given_output = vm.get_builtin_runners();
 
-        assert_eq!(given_output[0].0, "pedersen");
-        assert_eq!(given_output[1].0, "range_check");
-        assert_eq!(given_output[2].0, "ecdsa");
-        assert_eq!(given_output[3].0, "output");
-        assert_eq!(given_output[4].0, "bitwise");
-        assert_eq!(given_output[5].0, "ec_op");
-        assert_eq!(given_output[6].0, "keccak");
+        assert_eq!(given_output[0].0, BuiltinName::Pedersen);
+        assert_eq!(given_output[1].0, BuiltinName::RangeCheck);
+        assert_eq!(given_output[2].0, BuiltinName::Ecdsa);
+        assert_eq!(given_output[3].0, BuiltinName::Output);
+        assert_eq!(given_output[4].0, BuiltinName::Bitwise);
+        assert_eq!(given_output[5].0, BuiltinName::EcOp);
+        assert_eq!(given_output[6].0, BuiltinName::Keccak);
     }
 
     #[test]
@@ -3889,13 +5075,13 @@ mod tests {
 
         let builtin_runners = vm.get_builtin_runners();
 
-        assert_eq!(builtin_runners[0].0, "pedersen");
-        assert_eq!(builtin_runners[1].0, "range_check");
-        assert_eq!(builtin_runners[2].0, "output");
-        assert_eq!(builtin_runners[3].0, "ecdsa");
-        assert_eq!(builtin_runners[4].0, "bitwise");
-        assert_eq!(builtin_runners[5].0, "ec_op");
-        assert_eq!(builtin_runners[6].0, "keccak");
+        assert_eq!(builtin_runners[0].0, BuiltinName::Pedersen);
+        assert_eq!(builtin_runners[1].0, BuiltinName::RangeCheck);
+        assert_eq!(builtin_runners[2].0, BuiltinName::Output);
+        assert_eq!(builtin_runners[3].0, BuiltinName::Ecdsa);
+        assert_eq!(builtin_runners[4].0, BuiltinName::Bitwise);
+        assert_eq!(builtin_runners[5].0, BuiltinName::EcOp);
+        assert_eq!(builtin_runners[6].0, BuiltinName::Keccak);
 
         assert_eq!(
             cairo_runner.program_base,
@@ -3916,13 +5102,13 @@ mod tests {
 
     #[test]
     fn initialize_segments_incorrect_layout_plain_one_builtin() {
-        let program = program!["output"];
+        let program = program![BuiltinName::Output];
         let mut vm = vm!();
         let cairo_runner = cairo_runner!(program, "plain");
         assert_eq!(
             cairo_runner.initialize_builtins(&mut vm),
             Err(RunnerError::NoBuiltinForInstance(
-                HashSet::from([String::from("output")]),
+                HashSet::from([BuiltinName::Output]),
                 String::from("plain")
             ))
         );
@@ -3930,13 +5116,13 @@ mod tests {
 
     #[test]
     fn initialize_segments_incorrect_layout_plain_two_builtins() {
-        let program = program!["output", "pedersen"];
+        let program = program![BuiltinName::Output, BuiltinName::Pedersen];
         let mut vm = vm!();
         let cairo_runner = cairo_runner!(program, "plain");
         assert_eq!(
             cairo_runner.initialize_builtins(&mut vm),
             Err(RunnerError::NoBuiltinForInstance(
-                HashSet::from([String::from("output"), String::from("pedersen")]),
+                HashSet::from([BuiltinName::Output, BuiltinName::Pedersen]),
                 String::from("plain")
             ))
         );
@@ -3944,13 +5130,13 @@ mod tests {
 
     #[test]
     fn initialize_segments_incorrect_layout_small_two_builtins() {
-        let program = program!["output", "bitwise"];
+        let program = program![BuiltinName::Output, BuiltinName::Bitwise];
         let mut vm = vm!();
         let cairo_runner = cairo_runner!(program, "small");
         assert_eq!(
             cairo_runner.initialize_builtins(&mut vm),
             Err(RunnerError::NoBuiltinForInstance(
-                HashSet::from([String::from("bitwise")]),
+                HashSet::from([BuiltinName::Bitwise]),
                 String::from("small")
             ))
         );
@@ -3961,7 +5147,7 @@ mod tests {
         let mut runner = cairo_runner!(program);
         runner.proof_mode = true;
         let mut vm = vm!();
-        runner.initialize_segments(&mut vm, None);
+        runner.initialize_segments(&mut vm, None).unwrap();
         assert_eq!(runner.execution_base, Some(Relocatable::from((1, 0))));
         assert_eq!(runner.program_base, Some(Relocatable::from((0, 0))));
         assert_eq!(
@@ -3979,13 +5165,13 @@ mod tests {
             start = Some(0),
             end = Some(0),
             main = Some(8),
-            builtins = vec!["output".to_string(), "ec_op".to_string()],
+            builtins = vec![BuiltinName::Output, BuiltinName::EcOp],
         );
         let mut runner = cairo_runner!(program);
         runner.proof_mode = true;
         let mut vm = vm!();
         runner.initialize_builtins(&mut vm).unwrap();
-        runner.initialize_segments(&mut vm, None);
+        runner.initialize_segments(&mut vm, None).unwrap();
         assert_eq!(runner.execution_base, Some(Relocatable::from((1, 0))));
         assert_eq!(runner.program_base, Some(Relocatable::from((0, 0))));
         assert_eq!(
@@ -4003,7 +5189,7 @@ mod tests {
             start = Some(0),
             end = Some(0),
             main = Some(8),
-            builtins = vec!["output".to_string(), "ec_op".to_string()],
+            builtins = vec![BuiltinName::Output, BuiltinName::EcOp],
         );
         let runner = cairo_runner!(program);
 
@@ -4023,6 +5209,8 @@ mod tests {
                 value: None,
                 full_name: None,
                 members: None,
+                size: None,
+                decorators: None,
             },
         )]
         .into_iter()
@@ -4049,6 +5237,8 @@ mod tests {
                     value: None,
                     full_name: None,
                     members: None,
+                    size: None,
+                    decorators: None,
                 },
             ),
             (
@@ -4059,6 +5249,8 @@ mod tests {
                     value: None,
                     full_name: None,
                     members: None,
+                    size: None,
+                    decorators: None,
                 },
             ),
         ]
@@ -4086,6 +5278,8 @@ mod tests {
                 value: None,
                 full_name: None,
                 members: None,
+                size: None,
+                decorators: None,
             },
         )]
         .into_iter()
@@ -4153,6 +5347,93 @@ mod tests {
         );
     }
 
+    //Runner for a program with the output and range check builtins, with ap pointing after the
+    //given stop pointers. Used cells: 1 for output and 2 for range check
+    fn runner_returning_builtins(stop_ptrs: &[MaybeRelocatable]) -> (CairoRunner, VirtualMachine) {
+        let program = program![BuiltinName::Output, BuiltinName::RangeCheck];
+        let mut cairo_runner = cairo_runner!(program);
+        let mut vm = vm!();
+        cairo_runner.initialize_builtins(&mut vm).unwrap();
+        cairo_runner.initialize_segments(&mut vm, None).unwrap();
+        for (i, stop_ptr) in stop_ptrs.iter().enumerate() {
+            vm.insert_value(&Relocatable::from((1, i)), stop_ptr)
+                .unwrap();
+        }
+        vm.run_context.ap = stop_ptrs.len();
+        vm.segments.segment_used_sizes = Some(vec![0, stop_ptrs.len(), 1, 2]);
+        cairo_runner.run_ended = true;
+        cairo_runner.execution_public_memory = Some(Vec::new());
+        (cairo_runner, vm)
+    }
+
+    #[test]
+    fn read_return_values_invalid_stop_pointer() {
+        let (mut cairo_runner, vm) =
+            runner_returning_builtins(&[mayberelocatable!(2, 1), mayberelocatable!(3, 3)]);
+        assert_eq!(
+            cairo_runner.read_return_values(&vm),
+            Err(RunnerError::InvalidStopPointer(
+                BuiltinName::RangeCheck,
+                Relocatable::from((3, 3)),
+                2,
+                Relocatable::from((3, 2))
+            ))
+        );
+    }
+
+    #[test]
+    fn get_builtin_stop_ptr_diagnostics() {
+        let (cairo_runner, vm) =
+            runner_returning_builtins(&[mayberelocatable!(7), mayberelocatable!(3, 3)]);
+        let diagnostics = cairo_runner.get_builtin_stop_ptr_diagnostics(&vm).unwrap();
+        assert_eq!(
+            diagnostics,
+            vec![
+                BuiltinStopPtrDiagnostic {
+                    builtin: BuiltinName::Output,
+                    claimed_ptr: None,
+                    used_instances: 1,
+                    expected_ptr: Relocatable::from((2, 1)),
+                },
+                BuiltinStopPtrDiagnostic {
+                    builtin: BuiltinName::RangeCheck,
+                    claimed_ptr: Some(Relocatable::from((3, 3))),
+                    used_instances: 2,
+                    expected_ptr: Relocatable::from((3, 2)),
+                },
+            ]
+        );
+        assert_eq!(
+            diagnostics[0].to_string(),
+            "output           claimed: missing    expected: 2:1        used instances: 1"
+        );
+    }
+
+    #[test]
+    fn read_return_values_missing_stop_pointers() {
+        let (mut cairo_runner, vm) = runner_returning_builtins(&[mayberelocatable!(7)]);
+        assert_eq!(
+            cairo_runner.read_return_values(&vm),
+            Err(RunnerError::FinalStack)
+        );
+
+        cairo_runner.allow_missing_builtin_stop_ptrs = true;
+        assert_eq!(cairo_runner.read_return_values(&vm), Ok(()));
+        //Neither builtin was returned, so the stack pointer stays at ap
+        assert_eq!(cairo_runner.execution_public_memory, Some(vec![]));
+    }
+
+    #[test]
+    fn allow_missing_builtin_stop_ptrs_still_checks_returned_pointers() {
+        let (mut cairo_runner, vm) =
+            runner_returning_builtins(&[mayberelocatable!(2, 1), mayberelocatable!(3, 3)]);
+        cairo_runner.allow_missing_builtin_stop_ptrs = true;
+        assert!(matches!(
+            cairo_runner.read_return_values(&vm),
+            Err(RunnerError::InvalidStopPointer(BuiltinName::RangeCheck, ..))
+        ));
+    }
+
     /// Test that add_additional_hash_builtin() creates an additional builtin.
     #[test]
     fn add_additional_hash_builtin() {
@@ -4168,7 +5449,7 @@ mod tests {
             .builtin_runners
             .last()
             .expect("missing last builtin runner");
-        assert_eq!(key, "hash_builtin");
+        assert_eq!(key, &BuiltinName::AdditionalHash);
         match value {
             BuiltinRunner::Hash(builtin) => {
                 assert_eq!(builtin.base(), 0);
@@ -4196,7 +5477,7 @@ mod tests {
             .builtin_runners
             .last()
             .expect("missing last builtin runner");
-        assert_eq!(key, "hash_builtin");
+        assert_eq!(key, &BuiltinName::AdditionalHash);
         match value {
             BuiltinRunner::Hash(builtin) => {
                 assert_eq!(builtin.base(), 1);
@@ -4223,29 +5504,31 @@ mod tests {
             .pc
             .unwrap();
 
-        vm.accessed_addresses = Some(Vec::new());
+        vm.accessed_addresses = Some(HashSet::new());
         cairo_runner.initialize_builtins(&mut vm).unwrap();
-        cairo_runner.initialize_segments(&mut vm, None);
-        assert_eq!(
-            cairo_runner.run_from_entrypoint(
+        cairo_runner.initialize_segments(&mut vm, None).unwrap();
+        cairo_runner
+            .run_from_entrypoint(
                 main_entrypoint,
                 vec![&mayberelocatable!(2), &MaybeRelocatable::from((2, 0))], //range_check_ptr
+                &[],
                 false,
                 true,
                 true,
                 &mut vm,
                 &mut hint_processor,
-            ),
-            Ok(()),
-        );
+            )
+            .unwrap();
 
         let mut new_cairo_runner = cairo_runner!(program);
         let mut new_vm = vm!(true); //this true expression dictates that the trace is enabled
         let mut hint_processor = BuiltinHintProcessor::new_empty();
 
-        new_vm.accessed_addresses = Some(Vec::new());
+        new_vm.accessed_addresses = Some(HashSet::new());
         new_cairo_runner.initialize_builtins(&mut new_vm).unwrap();
-        new_cairo_runner.initialize_segments(&mut new_vm, None);
+        new_cairo_runner
+            .initialize_segments(&mut new_vm, None)
+            .unwrap();
 
         let fib_entrypoint = program
             .identifiers
@@ -4254,17 +5537,56 @@ mod tests {
             .pc
             .unwrap();
 
-        assert_eq!(
-            new_cairo_runner.run_from_entrypoint(
+        new_cairo_runner
+            .run_from_entrypoint(
                 fib_entrypoint,
                 vec![&mayberelocatable!(2), &MaybeRelocatable::from((2, 0))],
+                &[],
                 false,
                 true,
                 true,
                 &mut new_vm,
                 &mut hint_processor,
-            ),
-            Ok(()),
-        );
+            )
+            .unwrap();
+    }
+
+    ///Runs the program on the given vm, returning its relocated memory and trace
+    fn run_and_relocate(
+        program_path: &str,
+        vm: &mut VirtualMachine,
+    ) -> (Vec<Option<Felt>>, Option<Vec<RelocatedTraceEntry>>) {
+        let program = Program::from_file(Path::new(program_path), Some("main")).unwrap();
+        let mut cairo_runner = cairo_runner!(program, "all");
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        let end = cairo_runner.initialize(vm).unwrap();
+        cairo_runner
+            .run_until_pc(end, vm, &mut hint_processor)
+            .unwrap();
+        cairo_runner
+            .end_run(false, false, vm, &mut hint_processor)
+            .unwrap();
+        cairo_runner.relocate(vm).unwrap();
+        (
+            cairo_runner.get_relocated_memory().to_vec(),
+            cairo_runner.get_relocated_trace().map(<[_]>::to_vec),
+        )
+    }
+
+    #[test]
+    fn reset_vm_runs_programs_like_fresh_vm() {
+        let programs = [
+            "cairo_programs/manually_compiled/hintless_program.json",
+            "cairo_programs/manually_compiled/identifiers_program.json",
+        ];
+        let mut reused_vm = vm!(true);
+        for program_path in programs {
+            reused_vm.reset();
+            let fresh_results = run_and_relocate(program_path, &mut vm!(true));
+            assert_eq!(
+                run_and_relocate(program_path, &mut reused_vm),
+                fresh_results
+            );
+        }
     }
 }
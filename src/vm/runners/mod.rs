@@ -1,2 +1,4 @@
 pub mod builtin_runner;
 pub mod cairo_runner;
+pub mod output_verifier;
+pub mod run_report;
@@ -0,0 +1,80 @@
+use felt::{Felt, FeltOps};
+use num_bigint::BigInt;
+use sha2::{Digest, Sha256};
+
+///Checks the values written to the output builtin once a run has ended, so that applications
+///can reject runs whose outputs don't match what they expected.
+pub trait OutputVerifier {
+    ///Returns the reason why the outputs were rejected, if they were.
+    fn verify(&self, outputs: &[BigInt]) -> Result<(), String>;
+}
+
+///Accepts the outputs if their sha256 hash matches the expected one. See
+///Sha256OutputVerifier::hash_outputs for how the outputs are hashed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sha256OutputVerifier {
+    expected_hash: [u8; 32],
+}
+
+impl Sha256OutputVerifier {
+    pub fn new(expected_hash: [u8; 32]) -> Sha256OutputVerifier {
+        Sha256OutputVerifier { expected_hash }
+    }
+
+    ///Hashes the outputs as field elements, each one encoded as 32 big endian bytes.
+    pub fn hash_outputs(outputs: &[BigInt]) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        for output in outputs {
+            let bytes = Felt::from(output).to_bytes_be();
+            hasher.update([0; 32][bytes.len()..].as_ref());
+            hasher.update(&bytes);
+        }
+        hasher.finalize().into()
+    }
+}
+
+impl OutputVerifier for Sha256OutputVerifier {
+    fn verify(&self, outputs: &[BigInt]) -> Result<(), String> {
+        let hash = Sha256OutputVerifier::hash_outputs(outputs);
+        if hash != self.expected_hash {
+            return Err(format!(
+                "Expected outputs hash {}, got {}",
+                hex_encode(&self.expected_hash),
+                hex_encode(&hash)
+            ));
+        }
+        Ok(())
+    }
+}
+
+fn hex_encode(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hash_outputs_reduces_negative_values() {
+        let prime = BigInt::parse_bytes(&felt::PRIME_STR.as_bytes()[2..], 16).unwrap();
+        assert_eq!(
+            Sha256OutputVerifier::hash_outputs(&[BigInt::from(-1)]),
+            Sha256OutputVerifier::hash_outputs(&[prime - 1])
+        );
+    }
+
+    #[test]
+    fn verify_matching_hash() {
+        let outputs = [BigInt::from(7), BigInt::from(1) << 200];
+        let verifier = Sha256OutputVerifier::new(Sha256OutputVerifier::hash_outputs(&outputs));
+        assert_eq!(verifier.verify(&outputs), Ok(()));
+    }
+
+    #[test]
+    fn verify_mismatching_hash() {
+        let verifier = Sha256OutputVerifier::new([0; 32]);
+        let error = verifier.verify(&[BigInt::from(7)]).unwrap_err();
+        assert!(error.starts_with(&format!("Expected outputs hash {}, got ", "0".repeat(64))));
+    }
+}
@@ -0,0 +1,191 @@
+use crate::{
+    types::relocatable::Relocatable,
+    vm::{errors::cairo_run_errors::CairoRunError, runners::cairo_runner::ExecutionResources},
+};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+///Summary of a run, built by CairoRunner::run_report and meant to be serialized to JSON.
+///Sections that weren't available for the run are None and left out of the JSON.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct RunReport {
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<RunErrorReport>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub execution_resources: Option<ExecutionResources>,
+    ///Values written to the output builtin, as decimal strings
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<Vec<String>>,
+    ///Executions per hint code, only if VirtualMachine::enable_hint_statistics was called
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hint_statistics: Option<BTreeMap<String, usize>>,
+    ///Wall-clock time from initialize_vm to end_run, or to the report if the run didn't end
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_secs: Option<f64>,
+    ///Average steps executed per second, only with the run_timer feature
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub steps_per_sec: Option<f64>,
+    ///Only if the vm was created with a trace
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trace_length: Option<usize>,
+    pub registers: RegistersReport,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct RegistersReport {
+    pub pc: Relocatable,
+    pub ap: Relocatable,
+    pub fp: Relocatable,
+}
+
+///The error a run failed with. The pc, location, error attribute and traceback are only known
+///for errors wrapped in a VmException.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct RunErrorReport {
+    ///Name of the CairoRunError variant
+    pub kind: &'static str,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pc: Option<usize>,
+    ///Instruction location as "filename:line:column"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_attr_value: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub traceback: Option<String>,
+}
+
+impl From<&CairoRunError> for RunErrorReport {
+    fn from(error: &CairoRunError) -> Self {
+        let kind = match error {
+            CairoRunError::Program(_) => "Program",
+            CairoRunError::VirtualMachine(_) => "VirtualMachine",
+            CairoRunError::Trace(_) => "Trace",
+            CairoRunError::Runner(_) => "Runner",
+            CairoRunError::MemoryError(_) => "MemoryError",
+            CairoRunError::VmException(_) => "VmException",
+        };
+        let mut report = RunErrorReport {
+            kind,
+            message: error.to_string(),
+            pc: None,
+            location: None,
+            error_attr_value: None,
+            traceback: None,
+        };
+        if let CairoRunError::VmException(exception) = error {
+            report.pc = Some(exception.pc);
+            report.location = exception.inst_location.as_ref().map(|location| {
+                format!(
+                    "{}:{}:{}",
+                    location.input_file.filename, location.start_line, location.start_col
+                )
+            });
+            report.error_attr_value = exception.error_attr_value.clone();
+            report.traceback = exception.traceback.clone();
+        }
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        hint_processor::builtin_hint_processor::builtin_hint_processor_definition::BuiltinHintProcessor,
+        types::program::Program,
+        utils::test_utils::*,
+        vm::{
+            errors::vm_exception::VmException, runners::cairo_runner::CairoRunner,
+            vm_core::VirtualMachine,
+        },
+    };
+    use serde_json::json;
+    use std::path::Path;
+
+    fn run_report(program: &Program, trace_enabled: bool, hint_statistics: bool) -> RunReport {
+        let mut cairo_runner = cairo_runner!(program);
+        let mut vm = vm!(trace_enabled);
+        if hint_statistics {
+            vm.enable_hint_statistics();
+        }
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        let end = cairo_runner.initialize(&mut vm).unwrap();
+        let error = cairo_runner
+            .run_until_pc(end, &mut vm, &mut hint_processor)
+            .and_then(|_| cairo_runner.end_run(false, false, &mut vm, &mut hint_processor))
+            .err()
+            .map(|error| VmException::from_vm_error(&cairo_runner, &vm, error).into());
+        cairo_runner.run_report(&mut vm, error.as_ref())
+    }
+
+    fn load(program_path: &str) -> Program {
+        Program::from_file(Path::new(program_path), Some("main")).unwrap()
+    }
+
+    #[test]
+    fn run_report_of_output_program() {
+        let program = load("cairo_programs/manually_compiled/output_program.json");
+        let report = run_report(&program, true, false);
+        assert!(report.duration_secs.is_some());
+
+        let mut value = serde_json::to_value(&report).unwrap();
+        value.as_object_mut().unwrap().remove("duration_secs");
+        value.as_object_mut().unwrap().remove("steps_per_sec");
+        assert_eq!(
+            value,
+            json!({
+                "success": true,
+                "execution_resources": {
+                    "n_steps": 4,
+                    "n_memory_holes": 0,
+                    "builtin_instance_counter": {"output": 1}
+                },
+                "output": ["7"],
+                "trace_length": 4,
+                "registers": {
+                    "pc": {"segment_index": 4, "offset": 0},
+                    "ap": {"segment_index": 1, "offset": 5},
+                    "fp": {"segment_index": 1, "offset": 0}
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn run_report_with_hint_statistics_and_no_trace() {
+        let program = load("cairo_programs/manually_compiled/skip_next_instruction.json");
+        let value = serde_json::to_value(run_report(&program, false, true)).unwrap();
+        assert_eq!(value["success"], json!(true));
+        assert_eq!(
+            value["hint_statistics"],
+            json!({"skip_next_instruction()": 1})
+        );
+        //No output builtin and no trace
+        assert_eq!(value.get("output"), None);
+        assert_eq!(value.get("trace_length"), None);
+        assert_eq!(value.get("error"), None);
+    }
+
+    #[test]
+    fn run_report_of_failing_run() {
+        let mut program = load("cairo_programs/manually_compiled/skip_next_instruction.json");
+        program.hints.clear();
+        let value = serde_json::to_value(run_report(&program, false, false)).unwrap();
+        assert_eq!(value["success"], json!(false));
+        assert_eq!(value.get("hint_statistics"), None);
+        assert_eq!(value["error"]["kind"], json!("VmException"));
+        assert_eq!(value["error"]["pc"], json!(2));
+        assert!(value["error"]["message"]
+            .as_str()
+            .unwrap()
+            .starts_with("Error at pc=0:2:"));
+        //The registers are the ones of the failing step
+        assert_eq!(
+            value["registers"]["pc"],
+            json!({"segment_index": 0, "offset": 2})
+        );
+    }
+}
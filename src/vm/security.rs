@@ -1,11 +1,12 @@
 use super::{
     errors::{
-        memory_errors::MemoryError, runner_errors::RunnerError, vm_errors::VirtualMachineError,
+        memory_errors::MemoryError, runner_errors::RunnerError, security_errors::SecurityError,
+        vm_errors::VirtualMachineError,
     },
-    runners::cairo_runner::CairoRunner,
+    runners::{builtin_runner::BuiltinRunner, cairo_runner::CairoRunner},
     vm_core::VirtualMachine,
 };
-use crate::types::relocatable::Relocatable;
+use crate::types::relocatable::{MaybeRelocatable, Relocatable};
 use std::{collections::HashMap, mem::swap};
 
 /// Verify that the completed run in a runner is safe to be relocated and be
@@ -17,6 +18,9 @@ use std::{collections::HashMap, mem::swap};
 ///     the builtins themselves.
 ///   - There mustn't be accesses to the program segment outside the program
 ///     data range.
+///   - Every address accessed by the run's instructions must lie within the
+///     allocated segments.
+///   - The range check builtin's cells must hold integers below its bound.
 ///
 /// Note: Each builtin is responsible for checking its own segments' data.
 pub fn verify_secure_runner(
@@ -53,9 +57,9 @@ pub fn verify_secure_runner(
         });
     for (addr, value) in memory_iter {
         // Check builtin segment bounds.
-        if let Some((_, seg_info)) = builtin_segment_by_index.get(&addr.segment_index) {
+        if let Some((name, seg_info)) = builtin_segment_by_index.get(&addr.segment_index) {
             if addr.offset >= seg_info.size {
-                return Err(RunnerError::FailedMemoryGet(MemoryError::NumOutOfBounds).into());
+                return Err(SecurityError::BuiltinOutOfBounds(**name, addr, value.clone()).into());
             }
         }
 
@@ -63,7 +67,7 @@ pub fn verify_secure_runner(
         if addr.segment_index == program_base.segment_index
             && addr.offset >= runner.program.data.len()
         {
-            return Err(RunnerError::FailedMemoryGet(MemoryError::NumOutOfBounds).into());
+            return Err(SecurityError::ProgramOutOfBounds(addr, value.clone()).into());
         }
 
         // Check value validity (when relocatable, that the segment exists and
@@ -80,6 +84,48 @@ pub fn verify_secure_runner(
         }
     }
 
+    // Check that the instructions only accessed allocated segments.
+    if vm.segments.segment_used_sizes.is_some() {
+        if let Some(accessed_addresses) = &vm.accessed_addresses {
+            for addr in accessed_addresses {
+                let segment_size = usize::try_from(addr.segment_index)
+                    .ok()
+                    .and_then(|index| vm.segments.get_segment_size(index));
+                if !matches!(segment_size, Some(size) if addr.offset < size) {
+                    return Err(SecurityError::AccessOutOfBounds(*addr).into());
+                }
+            }
+        }
+    }
+
+    // Check that the range check cells are within the builtin's bound.
+    for (_, builtin) in &vm.builtin_runners {
+        if let BuiltinRunner::RangeCheck(range_check) = builtin {
+            let bound = match &range_check._bound {
+                Some(bound) => bound,
+                None => continue,
+            };
+            let segment = usize::try_from(range_check.base())
+                .ok()
+                .and_then(|index| vm.memory.data.get(index));
+            for (offset, value) in segment.into_iter().flatten().enumerate() {
+                let addr = Relocatable::from((range_check.base(), offset));
+                match value {
+                    Some(MaybeRelocatable::Int(num)) if num < bound => {}
+                    Some(value) => {
+                        return Err(SecurityError::RangeCheckOutOfBounds(
+                            addr,
+                            value.clone(),
+                            bound.clone().into(),
+                        )
+                        .into())
+                    }
+                    None => {}
+                }
+            }
+        }
+    }
+
     // This swap is needed to avoid double mutable borrows.
     let mut tmp = Vec::new();
     swap(&mut tmp, &mut vm.builtin_runners);
@@ -94,9 +140,14 @@ pub fn verify_secure_runner(
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::{relocatable, types::program::Program, utils::test_utils::*};
-    use felt::Felt;
-    use num_traits::Zero;
+    use crate::{
+        relocatable,
+        types::{builtin_name::BuiltinName, program::Program},
+        utils::test_utils::*,
+    };
+    use felt::{Felt, NewFelt};
+    use num_traits::{One, Zero};
+    use std::collections::HashSet;
 
     #[test]
     fn verify_secure_runner_without_program_base() {
@@ -137,7 +188,88 @@ mod test {
 
         assert_eq!(
             verify_secure_runner(&runner, true, &mut vm),
-            Err(RunnerError::FailedMemoryGet(MemoryError::NumOutOfBounds).into())
+            Err(SecurityError::ProgramOutOfBounds(
+                relocatable!(0, 0),
+                relocatable!(0, 1000).into()
+            )
+            .into())
+        );
+    }
+
+    #[test]
+    fn verify_secure_runner_write_into_program_segment() {
+        let program = program!(data = vec![Felt::zero().into()], main = Some(0),);
+
+        let mut runner = cairo_runner!(program);
+        let mut vm = vm!();
+
+        runner.initialize(&mut vm).unwrap();
+        vm.memory
+            .insert(&relocatable!(0, 1), &MaybeRelocatable::from(Felt::new(7)))
+            .unwrap();
+        vm.segments.compute_effective_sizes(&vm.memory);
+
+        assert_eq!(
+            verify_secure_runner(&runner, true, &mut vm),
+            Err(SecurityError::ProgramOutOfBounds(relocatable!(0, 1), Felt::new(7).into()).into())
+        );
+    }
+
+    #[test]
+    fn verify_secure_runner_access_out_of_bounds() {
+        let program = program!(main = Some(0),);
+
+        let mut runner = cairo_runner!(program);
+        let mut vm = vm!();
+
+        runner.initialize(&mut vm).unwrap();
+        vm.segments.compute_effective_sizes(&vm.memory);
+        vm.accessed_addresses = Some(HashSet::from([relocatable!(1, 0), relocatable!(7, 0)]));
+
+        assert_eq!(
+            verify_secure_runner(&runner, true, &mut vm),
+            Err(SecurityError::AccessOutOfBounds(relocatable!(7, 0)).into())
+        );
+    }
+
+    #[test]
+    fn verify_secure_runner_temporary_access() {
+        let program = program!(main = Some(0),);
+
+        let mut runner = cairo_runner!(program);
+        let mut vm = vm!();
+
+        runner.initialize(&mut vm).unwrap();
+        vm.segments.compute_effective_sizes(&vm.memory);
+        vm.accessed_addresses = Some(HashSet::from([relocatable!(-1, 0)]));
+
+        assert_eq!(
+            verify_secure_runner(&runner, true, &mut vm),
+            Err(SecurityError::AccessOutOfBounds(relocatable!(-1, 0)).into())
+        );
+    }
+
+    #[test]
+    fn verify_secure_runner_range_check_out_of_bounds() {
+        let program = program!(builtins = vec![BuiltinName::RangeCheck], main = Some(0),);
+
+        let mut runner = cairo_runner!(program);
+        let mut vm = vm!();
+
+        runner.initialize(&mut vm).unwrap();
+        let bound = Felt::one() << 128_u32;
+        vm.memory.data[2].push(Some(Felt::new(5).into()));
+        vm.memory.data[2].push(Some(bound.clone().into()));
+        vm.segments.compute_effective_sizes(&vm.memory);
+
+        assert_eq!(
+            verify_secure_runner(&runner, false, &mut vm),
+            Err(SecurityError::RangeCheckOutOfBounds(
+                relocatable!(2, 1),
+                bound.clone().into(),
+                bound.into()
+            )
+            .into())
         );
     }
 
@@ -0,0 +1,340 @@
+use crate::{
+    types::{
+        instruction::{ApUpdate, FpUpdate, Instruction, PcUpdate},
+        relocatable::{MaybeRelocatable, Relocatable},
+    },
+    vm::{
+        errors::vm_errors::VirtualMachineError,
+        vm_core::{Operands, VirtualMachine},
+    },
+};
+use num_traits::ToPrimitive;
+
+///Values of the pc, ap and fp registers before or after a step
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Registers {
+    pub pc: Relocatable,
+    pub ap: Relocatable,
+    pub fp: Relocatable,
+}
+
+///Returns the registers after executing the instruction with the given operands.
+///Only depends on its arguments, so the VirtualMachine and the trace verifier share it.
+pub fn next_registers(
+    instruction: &Instruction,
+    operands: &Operands,
+    registers: &Registers,
+) -> Result<Registers, VirtualMachineError> {
+    Ok(Registers {
+        pc: next_pc(instruction, operands, registers)?,
+        ap: next_ap(instruction, operands, registers)?,
+        fp: next_fp(instruction, operands, registers)?,
+    })
+}
+
+pub(crate) fn next_fp(
+    instruction: &Instruction,
+    operands: &Operands,
+    registers: &Registers,
+) -> Result<Relocatable, VirtualMachineError> {
+    let offset = match instruction.fp_update {
+        FpUpdate::APPlus2 => registers.ap.offset + 2,
+        FpUpdate::Dst => match operands.dst() {
+            MaybeRelocatable::RelocatableValue(rel) => rel.offset,
+            MaybeRelocatable::Int(num) => num
+                .to_usize()
+                .ok_or(VirtualMachineError::BigintToUsizeFail)?,
+        },
+        FpUpdate::Regular => return Ok(registers.fp),
+    };
+    Ok(Relocatable::from((registers.fp.segment_index, offset)))
+}
+
+pub(crate) fn next_ap(
+    instruction: &Instruction,
+    operands: &Operands,
+    registers: &Registers,
+) -> Result<Relocatable, VirtualMachineError> {
+    Ok(match instruction.ap_update {
+        ApUpdate::Add => match operands.res() {
+            Some(res) => registers.ap.add_maybe(res)?,
+            None => return Err(VirtualMachineError::UnconstrainedResAdd),
+        },
+        ApUpdate::Add1 => registers.ap + 1_i32,
+        ApUpdate::Add2 => registers.ap + 2_i32,
+        ApUpdate::Regular => registers.ap,
+    })
+}
+
+pub(crate) fn next_pc(
+    instruction: &Instruction,
+    operands: &Operands,
+    registers: &Registers,
+) -> Result<Relocatable, VirtualMachineError> {
+    let pc = registers.pc;
+    Ok(match instruction.pc_update {
+        PcUpdate::Regular => pc + instruction.size(),
+        PcUpdate::Jump => match operands.res() {
+            Some(res) => res.get_relocatable()?,
+            None => return Err(VirtualMachineError::UnconstrainedResJump),
+        },
+        PcUpdate::JumpRel => match operands.res() {
+            Some(MaybeRelocatable::Int(num_res)) => pc.add_int(num_res)?,
+            Some(_) => return Err(VirtualMachineError::PureValue),
+            None => return Err(VirtualMachineError::UnconstrainedResJumpRel),
+        },
+        PcUpdate::Jnz => match VirtualMachine::is_zero(operands.dst())? {
+            true => pc + instruction.size(),
+            false => match operands.op1() {
+                MaybeRelocatable::Int(jump_offset) => pc.add_int(jump_offset)?,
+                op1 => return Err(VirtualMachineError::JumpOffsetNotInt(op1.clone())),
+            },
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        relocatable,
+        types::instruction::{Op1Addr, Opcode, Register, Res},
+        utils::test_utils::mayberelocatable,
+    };
+    use felt::{Felt, NewFelt};
+    use std::borrow::Cow;
+
+    fn instruction(pc_update: PcUpdate, ap_update: ApUpdate, fp_update: FpUpdate) -> Instruction {
+        Instruction {
+            off0: 0,
+            off1: -1,
+            off2: 1,
+            dst_register: Register::AP,
+            op0_register: Register::FP,
+            op1_addr: Op1Addr::Imm,
+            res: Res::Op1,
+            pc_update,
+            ap_update,
+            fp_update,
+            opcode: Opcode::NOp,
+        }
+    }
+
+    fn operands(
+        dst: MaybeRelocatable,
+        res: Option<MaybeRelocatable>,
+        op1: MaybeRelocatable,
+    ) -> Operands<'static> {
+        Operands {
+            dst: Cow::Owned(dst),
+            res,
+            op0: Cow::Owned(mayberelocatable!(0)),
+            op1: Cow::Owned(op1),
+        }
+    }
+
+    fn registers() -> Registers {
+        Registers {
+            pc: relocatable!(0, 10),
+            ap: relocatable!(1, 20),
+            fp: relocatable!(1, 15),
+        }
+    }
+
+    #[test]
+    fn next_registers_call() {
+        //call rel 3
+        let instruction = instruction(PcUpdate::JumpRel, ApUpdate::Add2, FpUpdate::APPlus2);
+        let operands = operands(
+            mayberelocatable!(1, 15),
+            Some(mayberelocatable!(3)),
+            mayberelocatable!(3),
+        );
+        assert_eq!(
+            next_registers(&instruction, &operands, &registers()),
+            Ok(Registers {
+                pc: relocatable!(0, 13),
+                ap: relocatable!(1, 22),
+                fp: relocatable!(1, 22),
+            })
+        );
+    }
+
+    #[test]
+    fn next_registers_ret() {
+        let instruction = instruction(PcUpdate::Jump, ApUpdate::Regular, FpUpdate::Dst);
+        let operands = operands(
+            mayberelocatable!(1, 5),
+            Some(mayberelocatable!(0, 4)),
+            mayberelocatable!(0, 4),
+        );
+        assert_eq!(
+            next_registers(&instruction, &operands, &registers()),
+            Ok(Registers {
+                pc: relocatable!(0, 4),
+                ap: relocatable!(1, 20),
+                fp: relocatable!(1, 5),
+            })
+        );
+    }
+
+    #[test]
+    fn next_registers_add_ap_without_res() {
+        let instruction = instruction(PcUpdate::Regular, ApUpdate::Add, FpUpdate::Regular);
+        let operands = operands(mayberelocatable!(1), None, mayberelocatable!(1));
+        assert_eq!(
+            next_registers(&instruction, &operands, &registers()),
+            Err(VirtualMachineError::UnconstrainedResAdd)
+        );
+    }
+
+    #[test]
+    fn next_registers_jump_to_int() {
+        let instruction = instruction(PcUpdate::Jump, ApUpdate::Regular, FpUpdate::Regular);
+        let operands = operands(
+            mayberelocatable!(1),
+            Some(mayberelocatable!(4)),
+            mayberelocatable!(4),
+        );
+        assert!(next_registers(&instruction, &operands, &registers()).is_err());
+    }
+
+    //Xorshift generator, so the property tests explore the same cases on every run
+    struct Rng(u64);
+
+    impl Rng {
+        fn below(&mut self, bound: u64) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0 % bound
+        }
+
+        fn value(&mut self) -> MaybeRelocatable {
+            match self.below(3) {
+                0 => mayberelocatable!(0),
+                1 => MaybeRelocatable::from(Felt::new(self.below(40) as i64 - 10)),
+                _ => MaybeRelocatable::from((self.below(3) as isize, self.below(40) as usize)),
+            }
+        }
+
+        fn case(&mut self) -> (Instruction, Operands<'static>, Registers) {
+            let pc_update = [
+                PcUpdate::Regular,
+                PcUpdate::Jump,
+                PcUpdate::JumpRel,
+                PcUpdate::Jnz,
+            ][self.below(4) as usize]
+                .clone();
+            let ap_update = [
+                ApUpdate::Regular,
+                ApUpdate::Add,
+                ApUpdate::Add1,
+                ApUpdate::Add2,
+            ][self.below(4) as usize]
+                .clone();
+            let fp_update = [FpUpdate::Regular, FpUpdate::APPlus2, FpUpdate::Dst]
+                [self.below(3) as usize]
+                .clone();
+            let mut instruction = instruction(pc_update, ap_update, fp_update);
+            instruction.op1_addr = [Op1Addr::Imm, Op1Addr::AP][self.below(2) as usize].clone();
+            let res = match self.below(4) {
+                0 => None,
+                _ => Some(self.value()),
+            };
+            let operands = operands(self.value(), res, self.value());
+            let registers = Registers {
+                pc: relocatable!(0, self.below(100) as usize + 20),
+                ap: relocatable!(1, self.below(100) as usize),
+                fp: relocatable!(1, self.below(100) as usize),
+            };
+            (instruction, operands, registers)
+        }
+    }
+
+    fn for_each_case(mut check: impl FnMut(&Instruction, &Operands, &Registers)) {
+        let mut rng = Rng(0x2545f4914f6cdd1d);
+        for _ in 0..5000 {
+            let (instruction, operands, registers) = rng.case();
+            check(&instruction, &operands, &registers);
+        }
+    }
+
+    #[test]
+    fn ap_moves_forward_by_its_fixed_increment() {
+        for_each_case(|instruction, operands, registers| {
+            let ap = next_ap(instruction, operands, registers);
+            match instruction.ap_update {
+                ApUpdate::Add1 => assert_eq!(ap, Ok(registers.ap + 1_usize)),
+                ApUpdate::Add2 => assert_eq!(ap, Ok(registers.ap + 2_usize)),
+                ApUpdate::Regular => assert_eq!(ap, Ok(registers.ap)),
+                ApUpdate::Add => {
+                    if let Ok(ap) = ap {
+                        assert_eq!(ap.segment_index, registers.ap.segment_index);
+                    }
+                }
+            }
+        });
+    }
+
+    #[test]
+    fn pc_advances_by_the_instruction_size_or_the_jump() {
+        for_each_case(|instruction, operands, registers| {
+            let pc = match next_pc(instruction, operands, registers) {
+                Ok(pc) => pc,
+                Err(_) => return,
+            };
+            let size = instruction.size();
+            match instruction.pc_update {
+                PcUpdate::Regular => assert_eq!(pc, registers.pc + size),
+                PcUpdate::Jump => {
+                    assert_eq!(Some(MaybeRelocatable::from(pc)).as_ref(), operands.res())
+                }
+                PcUpdate::JumpRel => {
+                    assert_eq!(pc.segment_index, registers.pc.segment_index);
+                    assert!(pc.offset.abs_diff(registers.pc.offset) < 30);
+                }
+                PcUpdate::Jnz => {
+                    assert_eq!(pc.segment_index, registers.pc.segment_index);
+                    if operands.dst() == &mayberelocatable!(0) {
+                        assert_eq!(pc, registers.pc + size);
+                    } else {
+                        assert!(pc.offset.abs_diff(registers.pc.offset) < 30);
+                    }
+                }
+            }
+        });
+    }
+
+    #[test]
+    fn fp_is_kept_by_regular_updates() {
+        for_each_case(|instruction, operands, registers| {
+            let fp = next_fp(instruction, operands, registers);
+            match instruction.fp_update {
+                FpUpdate::Regular => assert_eq!(fp, Ok(registers.fp)),
+                FpUpdate::APPlus2 => assert_eq!(fp, Ok(registers.ap + 2_usize)),
+                FpUpdate::Dst => {
+                    if let Ok(fp) = fp {
+                        assert_eq!(fp.segment_index, registers.fp.segment_index);
+                    }
+                }
+            }
+        });
+    }
+
+    #[test]
+    fn next_registers_fails_only_if_a_register_does() {
+        for_each_case(|instruction, operands, registers| {
+            let pc = next_pc(instruction, operands, registers);
+            let ap = next_ap(instruction, operands, registers);
+            let fp = next_fp(instruction, operands, registers);
+            match (pc, ap, fp) {
+                (Ok(pc), Ok(ap), Ok(fp)) => assert_eq!(
+                    next_registers(instruction, operands, registers),
+                    Ok(Registers { pc, ap, fp })
+                ),
+                _ => assert!(next_registers(instruction, operands, registers).is_err()),
+            }
+        });
+    }
+}
@@ -0,0 +1,25 @@
+use crate::{
+    types::instruction::Instruction,
+    vm::vm_core::{Operands, VirtualMachine},
+};
+
+///Observes the execution of each step, e.g. to collect metrics such as per-opcode counts.
+///Hooks only get a shared reference to the VM, so they can't change the execution. Returning an
+///error aborts the run with VirtualMachineError::StepHookFailed.
+pub trait StepHook: Send {
+    ///Called before executing the instruction at pc
+    fn pre_step(&mut self, _vm: &VirtualMachine) -> Result<(), String> {
+        Ok(())
+    }
+
+    ///Called once the instruction was executed and the registers were updated. Not called for
+    ///instructions skipped by a hint or executed by an OpcodeExtension, as they have no operands.
+    fn post_step(
+        &mut self,
+        _vm: &VirtualMachine,
+        _operands: &Operands,
+        _instruction: &Instruction,
+    ) -> Result<(), String> {
+        Ok(())
+    }
+}
@@ -8,6 +8,7 @@ use num_traits::ToPrimitive;
 use std::borrow::Cow;
 
 pub mod trace_entry;
+pub mod verifier;
 
 /// Return the minimum and maximum values in the perm_range_check component.
 pub fn get_perm_range_check_limits(
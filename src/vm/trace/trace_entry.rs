@@ -4,14 +4,14 @@ use serde::{Deserialize, Serialize};
 
 ///A trace entry for every instruction that was executed.
 ///Holds the register values before the instruction was executed.
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct TraceEntry {
     pub pc: Relocatable,
     pub ap: Relocatable,
     pub fp: Relocatable,
 }
 
-#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct RelocatedTraceEntry {
     pub ap: usize,
     pub fp: usize,
@@ -20,7 +20,7 @@ pub struct RelocatedTraceEntry {
 
 pub fn relocate_trace_register(
     value: &Relocatable,
-    relocation_table: &Vec<usize>,
+    relocation_table: &[usize],
 ) -> Result<usize, TraceError> {
     let segment_index: usize = value.segment_index.try_into().map_err(|_| {
         TraceError::MemoryError(MemoryError::AddressInTemporarySegment(value.segment_index))
@@ -0,0 +1,261 @@
+use super::trace_entry::RelocatedTraceEntry;
+use crate::{
+    types::{
+        instruction::{Instruction, Opcode, PcUpdate, Res},
+        relocatable::{MaybeRelocatable, Relocatable},
+    },
+    vm::{
+        context::run_context::RunContext,
+        decoding::decoder::decode_instruction,
+        errors::{trace_errors::TraceVerificationError, vm_errors::VirtualMachineError},
+        state_transition::next_registers,
+        vm_core::Operands,
+    },
+};
+use felt::{Felt, NewFelt, PRIME_STR};
+use num_traits::ToPrimitive;
+use std::borrow::Cow;
+
+///Memory after relocation, indexed by flat address
+pub type RelocatedMemory = [Option<Felt>];
+
+///Checks that each entry of a relocated trace executes the instruction at its pc against the
+///relocated memory and leads to the registers of the next entry. Doesn't need a VirtualMachine,
+///so it can be used to re-check a trace and memory written by a previous run.
+pub fn verify_trace(
+    memory: &RelocatedMemory,
+    trace: &[RelocatedTraceEntry],
+    prime: &str,
+) -> Result<(), TraceVerificationError> {
+    if prime != PRIME_STR {
+        return Err(TraceVerificationError::PrimeDiffers(prime.to_string()));
+    }
+    for (step, entry) in trace.iter().enumerate() {
+        //Registers are flat addresses, so only the offsets of the addresses computed from them are used
+        let run_context = RunContext {
+            pc: Relocatable::from((0, entry.pc)),
+            ap: entry.ap,
+            fp: entry.fp,
+            execution_segment: 1,
+        };
+        let instruction = decode_at(memory, step, entry.pc)?;
+        let operands = compute_operands(memory, step, &run_context, &instruction)?;
+        check_opcode(step, entry, &instruction, &operands)?;
+
+        let next = match trace.get(step + 1) {
+            Some(next) => next,
+            None => break,
+        };
+        //Jumps take the flat address in res as the new pc
+        let operands = match (&instruction.pc_update, &operands.res) {
+            (PcUpdate::Jump, Some(MaybeRelocatable::Int(res))) => Operands {
+                res: Some(MaybeRelocatable::from((
+                    0,
+                    res.to_usize()
+                        .ok_or(TraceVerificationError::RegisterUpdate(
+                            step,
+                            VirtualMachineError::BigintToUsizeFail,
+                        ))?,
+                ))),
+                ..operands
+            },
+            _ => operands,
+        };
+        let registers = next_registers(&instruction, &operands, &run_context.registers())
+            .map_err(|error| TraceVerificationError::RegisterUpdate(step, error))?;
+        for (register, expected, got) in [
+            ("pc", registers.pc.offset, next.pc),
+            ("ap", registers.ap.offset, next.ap),
+            ("fp", registers.fp.offset, next.fp),
+        ] {
+            if expected != got {
+                return Err(TraceVerificationError::RegisterMismatch(
+                    step + 1,
+                    register,
+                    expected,
+                    got,
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn decode_at(
+    memory: &RelocatedMemory,
+    step: usize,
+    pc: usize,
+) -> Result<Instruction, TraceVerificationError> {
+    let encoded_instr = memory
+        .get(pc)
+        .and_then(Option::as_ref)
+        .ok_or(TraceVerificationError::MissingInstruction(step, pc))?
+        .to_i64()
+        .ok_or(TraceVerificationError::InvalidInstruction(step, pc))?;
+    let imm = memory.get(pc + 1).and_then(Option::as_ref);
+    decode_instruction(encoded_instr, imm)
+        .map_err(|_| TraceVerificationError::InvalidInstruction(step, pc))
+}
+
+fn get_operand<'a>(
+    memory: &'a RelocatedMemory,
+    step: usize,
+    name: &'static str,
+    addr: Result<Relocatable, impl std::error::Error>,
+) -> Result<&'a Felt, TraceVerificationError> {
+    let addr = addr
+        .map_err(|_| TraceVerificationError::InvalidOperandAddress(step, name))?
+        .offset;
+    memory
+        .get(addr)
+        .and_then(Option::as_ref)
+        .ok_or(TraceVerificationError::MissingOperand(step, name, addr))
+}
+
+fn compute_operands(
+    memory: &RelocatedMemory,
+    step: usize,
+    run_context: &RunContext,
+    instruction: &Instruction,
+) -> Result<Operands<'static>, TraceVerificationError> {
+    let dst = get_operand(
+        memory,
+        step,
+        "dst",
+        run_context.compute_dst_addr(instruction),
+    )?;
+    let op0 = get_operand(
+        memory,
+        step,
+        "op0",
+        run_context.compute_op0_addr(instruction),
+    )?;
+    let op0_addr = op0.to_usize().map(|addr| MaybeRelocatable::from((0, addr)));
+    let op1 = get_operand(
+        memory,
+        step,
+        "op1",
+        run_context.compute_op1_addr(instruction, op0_addr.as_ref()),
+    )?;
+    let res = match instruction.res {
+        Res::Op1 => Some(op1.clone()),
+        Res::Add => Some(op0 + op1),
+        Res::Mul => Some(op0 * op1),
+        Res::Unconstrained => None,
+    };
+    Ok(Operands {
+        dst: Cow::Owned(MaybeRelocatable::Int(dst.clone())),
+        res: res.map(MaybeRelocatable::Int),
+        op0: Cow::Owned(MaybeRelocatable::Int(op0.clone())),
+        op1: Cow::Owned(MaybeRelocatable::Int(op1.clone())),
+    })
+}
+
+///Checks the assertions made by the opcode, the register updates are checked against the next entry
+fn check_opcode(
+    step: usize,
+    entry: &RelocatedTraceEntry,
+    instruction: &Instruction,
+    operands: &Operands,
+) -> Result<(), TraceVerificationError> {
+    match (&instruction.opcode, operands.dst.as_ref(), &operands.res) {
+        (Opcode::AssertEq, MaybeRelocatable::Int(dst), Some(MaybeRelocatable::Int(res)))
+            if dst != res =>
+        {
+            Err(TraceVerificationError::AssertEqFailed(
+                step,
+                dst.clone(),
+                res.clone(),
+            ))
+        }
+        (Opcode::AssertEq, _, None) => Err(TraceVerificationError::RegisterUpdate(
+            step,
+            VirtualMachineError::UnconstrainedResAssertEq,
+        )),
+        (Opcode::Call, _, _)
+            if *operands.op0
+                != MaybeRelocatable::from(Felt::new(entry.pc + instruction.size()))
+                || *operands.dst != MaybeRelocatable::from(Felt::new(entry.fp)) =>
+        {
+            Err(TraceVerificationError::InvalidCall(step))
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        hint_processor::builtin_hint_processor::builtin_hint_processor_definition::BuiltinHintProcessor,
+        types::program::Program,
+        utils::test_utils::*,
+        vm::{runners::cairo_runner::CairoRunner, vm_core::VirtualMachine},
+    };
+    use std::path::Path;
+
+    fn run_fixture(program_path: &str) -> (Vec<Option<Felt>>, Vec<RelocatedTraceEntry>) {
+        let program = Program::from_file(Path::new(program_path), Some("main")).unwrap();
+        let mut cairo_runner = cairo_runner!(program, "all");
+        let mut vm = vm!(true);
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        let end = cairo_runner.initialize(&mut vm).unwrap();
+        cairo_runner
+            .run_until_pc(end, &mut vm, &mut hint_processor)
+            .unwrap();
+        cairo_runner.relocate(&mut vm).unwrap();
+        (
+            cairo_runner.get_relocated_memory().to_vec(),
+            cairo_runner.get_relocated_trace().unwrap().to_vec(),
+        )
+    }
+
+    #[test]
+    fn verify_trace_of_fixture_run() {
+        for program_path in [
+            "cairo_programs/manually_compiled/hintless_program.json",
+            "cairo_programs/manually_compiled/identifiers_program.json",
+        ] {
+            let (memory, trace) = run_fixture(program_path);
+            assert_eq!(verify_trace(&memory, &trace, PRIME_STR), Ok(()));
+        }
+    }
+
+    #[test]
+    fn verify_trace_with_flipped_ap() {
+        let (memory, mut trace) =
+            run_fixture("cairo_programs/manually_compiled/hintless_program.json");
+        let expected_ap = trace[2].ap;
+        trace[2].ap += 1;
+        assert_eq!(
+            verify_trace(&memory, &trace, PRIME_STR),
+            Err(TraceVerificationError::RegisterMismatch(
+                2,
+                "ap",
+                expected_ap,
+                expected_ap + 1
+            ))
+        );
+    }
+
+    #[test]
+    fn verify_trace_with_wrong_prime() {
+        assert_eq!(
+            verify_trace(&[], &[], "0x11"),
+            Err(TraceVerificationError::PrimeDiffers("0x11".to_string()))
+        );
+    }
+
+    #[test]
+    fn verify_trace_with_missing_instruction() {
+        let trace = [RelocatedTraceEntry {
+            pc: 1,
+            ap: 2,
+            fp: 2,
+        }];
+        assert_eq!(
+            verify_trace(&[None, None], &trace, PRIME_STR),
+            Err(TraceVerificationError::MissingInstruction(0, 1))
+        );
+    }
+}
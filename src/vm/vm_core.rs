@@ -1,39 +1,116 @@
 use crate::{
-    hint_processor::hint_processor_definition::HintProcessor,
+    hint_processor::{
+        builtin_hint_processor::dict_manager::DictManager, hint_processor_definition::HintProcessor,
+    },
     serde::deserialize_program::ApTracking,
     types::{
+        builtin_name::BuiltinName,
         exec_scope::ExecutionScopes,
         instruction::{
-            is_call_instruction, ApUpdate, FpUpdate, Instruction, Opcode, PcUpdate, Res,
+            is_call_instruction, FpUpdate, Instruction, Opcode, Register, Res, VmRegister,
         },
+        program::Program,
         relocatable::{MaybeRelocatable, Relocatable},
     },
     vm::{
+        accessed_addresses::write_accessed_addresses,
+        const_view::VmConstView,
         context::run_context::RunContext,
-        decoding::decoder::decode_instruction,
+        decoding::decoder::{decode_instruction, is_extension_instruction},
         errors::{
-            exec_scope_errors::ExecScopeError, memory_errors::MemoryError,
-            vm_errors::VirtualMachineError,
+            exec_scope_errors::ExecScopeError,
+            hint_errors::{HintError, HintException},
+            memory_errors::MemoryError,
+            runner_errors::RunnerError,
+            vm_errors::{OperandsDump, VirtualMachineError},
+            vm_exception::{StepErrorContext, VmException},
         },
+        opcode_extension::{OpcodeExtension, VmView},
+        run_outcome::{BreakReason, RunOutcome},
         runners::builtin_runner::{BuiltinRunner, RangeCheckBuiltinRunner, SignatureBuiltinRunner},
+        state_transition::next_registers,
+        step_hook::StepHook,
         trace::trace_entry::TraceEntry,
-        vm_memory::{memory::Memory, memory_segments::MemorySegmentManager},
+        vm_memory::{
+            memory::{Memory, MemoryOrigin},
+            memory_segments::{MemorySegmentManager, PackingMode},
+        },
     },
 };
 use felt::Felt;
 use num_traits::{ToPrimitive, Zero};
-use std::{any::Any, borrow::Cow, collections::HashMap};
+#[cfg(feature = "run_timer")]
+use std::time::Instant;
+use std::{
+    any::Any,
+    borrow::Cow,
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    io,
+    rc::Rc,
+    time::Duration,
+};
 
 use super::vm_memory::memory_segments::gen_typed_args;
 
 const MAX_TRACEBACK_ENTRIES: u32 = 20;
 
+///op1 is borrowed from memory unless it had to be deduced, as it is usually an immediate.
 #[derive(PartialEq, Eq, Debug)]
-pub struct Operands {
-    dst: MaybeRelocatable,
-    res: Option<MaybeRelocatable>,
-    op0: MaybeRelocatable,
-    op1: MaybeRelocatable,
+pub struct Operands<'a> {
+    pub(crate) dst: Cow<'a, MaybeRelocatable>,
+    pub(crate) res: Option<MaybeRelocatable>,
+    pub(crate) op0: Cow<'a, MaybeRelocatable>,
+    pub(crate) op1: Cow<'a, MaybeRelocatable>,
+}
+
+impl Operands<'_> {
+    pub fn dst(&self) -> &MaybeRelocatable {
+        &self.dst
+    }
+
+    pub fn res(&self) -> Option<&MaybeRelocatable> {
+        self.res.as_ref()
+    }
+
+    pub fn op0(&self) -> &MaybeRelocatable {
+        &self.op0
+    }
+
+    pub fn op1(&self) -> &MaybeRelocatable {
+        &self.op1
+    }
+
+    //Copies the operands along with their addresses
+    fn dump(&self, addresses: &OperandsAddresses) -> OperandsDump {
+        OperandsDump {
+            dst_addr: addresses.dst_addr,
+            dst: self.dst.clone().into_owned(),
+            op0_addr: addresses.op0_addr,
+            op0: self.op0.clone().into_owned(),
+            op1_addr: addresses.op1_addr,
+            op1: self.op1.clone().into_owned(),
+            res: self.res.clone(),
+        }
+    }
+
+    //Copies the operands, so they can outlive the memory op1 may be borrowed from
+    fn to_owned_operands(&self) -> Operands<'static> {
+        Operands {
+            dst: Cow::Owned(self.dst.clone().into_owned()),
+            res: self.res.clone(),
+            op0: Cow::Owned(self.op0.clone().into_owned()),
+            op1: Cow::Owned(self.op1.clone().into_owned()),
+        }
+    }
+}
+
+//Takes the value of an operand that was deduced, borrowed operands are already in memory
+fn deduced(operand: Cow<'_, MaybeRelocatable>) -> Option<MaybeRelocatable> {
+    match operand {
+        Cow::Owned(operand) => Some(operand),
+        Cow::Borrowed(_) => None,
+    }
 }
 
 #[derive(PartialEq, Eq, Debug)]
@@ -46,6 +123,13 @@ pub struct OperandsAddresses {
 #[derive(Default, Debug, Clone, Copy)]
 pub struct DeducedOperands(u8);
 
+///Operand of the current instruction whose memory cell a builtin may deduce
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BuiltinOperand {
+    Op0,
+    Op1,
+}
+
 impl DeducedOperands {
     fn set_dst(&mut self, value: bool) {
         self.0 |= value as u8;
@@ -56,6 +140,12 @@ impl DeducedOperands {
     fn set_op1(&mut self, value: bool) {
         self.0 |= (value as u8) << 2;
     }
+    fn set_op0_by_builtin(&mut self, value: bool) {
+        self.0 |= (value as u8) << 3;
+    }
+    fn set_op1_by_builtin(&mut self, value: bool) {
+        self.0 |= (value as u8) << 4;
+    }
 
     fn was_dest_deducted(&self) -> bool {
         self.0 & 1 != 0
@@ -66,6 +156,12 @@ impl DeducedOperands {
     fn was_op1_deducted(&self) -> bool {
         self.0 & 1 << 2 != 0
     }
+    fn was_op0_deducted_by_builtin(&self) -> bool {
+        self.0 & 1 << 3 != 0
+    }
+    fn was_op1_deducted_by_builtin(&self) -> bool {
+        self.0 & 1 << 4 != 0
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -76,17 +172,108 @@ pub struct HintData {
     pub ap_tracking_data: ApTracking,
 }
 
+///Segments the registers are expected to stay in when strict register checks are enabled
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RegisterBounds {
+    ///Segment pc must stay in
+    pub program_segment: isize,
+    ///Segment ap and fp must stay in
+    pub execution_segment: isize,
+    ///Address pc jumps to when the run ends, the only one allowed outside of the program segment
+    pub final_pc: Option<Relocatable>,
+}
+
+///Deduces the value of a memory cell of the segment it was added for, returning None if it can't.
+///Lets builtins implemented outside of the vm fill and verify their segments like the vm's ones.
+#[allow(clippy::type_complexity)]
+pub struct Rule(
+    pub Box<dyn Fn(&Memory, &Relocatable) -> Result<Option<MaybeRelocatable>, VirtualMachineError>>,
+);
+
+///Budget of a run, consumed by step_with_resources. n_steps is the number of steps left, a run
+///without a limit on its steps has None
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RunResources {
+    pub n_steps: Option<usize>,
+}
+
+impl RunResources {
+    pub fn new(n_steps: usize) -> Self {
+        RunResources {
+            n_steps: Some(n_steps),
+        }
+    }
+
+    pub fn consumed(&self) -> bool {
+        self.n_steps == Some(0)
+    }
+
+    pub fn consume_step(&mut self) {
+        if let Some(n_steps) = self.n_steps.as_mut() {
+            *n_steps = n_steps.saturating_sub(1);
+        }
+    }
+}
+
+///A frame of the call stack rebuilt by VirtualMachine::reconstruct_backtrace
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BacktraceEntry {
+    ///The pc the frame's function returns to
+    pub return_pc: Relocatable,
+    ///The function return_pc belongs to, if it is in the program
+    pub function_name: Option<String>,
+}
+
+///State of the vm taken by VirtualMachine::snapshot, which VirtualMachine::restore rolls back to
+#[derive(Debug)]
+pub struct VmSnapshot {
+    pc: Relocatable,
+    ap: usize,
+    fp: usize,
+    current_step: usize,
+    call_depth: usize,
+    trace_len: Option<usize>,
+    //Number of deductions cached by each builtin
+    builtin_verified_lens: Vec<usize>,
+    dict_manager: Option<Rc<RefCell<DictManager>>>,
+}
+
 pub struct VirtualMachine {
     pub(crate) run_context: RunContext,
-    pub(crate) builtin_runners: Vec<(String, BuiltinRunner)>,
+    pub(crate) builtin_runners: Vec<(BuiltinName, BuiltinRunner)>,
+    auto_deduction: HashMap<usize, Vec<Rule>>,
     pub(crate) segments: MemorySegmentManager,
-    pub(crate) _program_base: Option<MaybeRelocatable>,
+    pub(crate) program_base: Option<Relocatable>,
     pub(crate) memory: Memory,
-    pub(crate) accessed_addresses: Option<Vec<Relocatable>>,
+    pub(crate) accessed_addresses: Option<HashSet<Relocatable>>,
     pub(crate) trace: Option<Vec<TraceEntry>>,
     pub(crate) current_step: usize,
+    pub(crate) register_bounds: Option<RegisterBounds>,
+    //Constants of the run given to custom hints, shared so they can be passed along the vm
+    pub(crate) const_view: Rc<VmConstView>,
+    opcode_extension: Option<Box<dyn OpcodeExtension>>,
+    step_hooks: Vec<Box<dyn StepHook>>,
     skip_instruction_execution: bool,
     run_finished: bool,
+    pub(crate) breakpoints: HashSet<Relocatable>,
+    hint_statistics: Option<HashMap<String, usize>>,
+    //Addresses first accessed since the last snapshot, only recorded while there is one
+    accessed_since_snapshot: Option<Vec<Relocatable>>,
+    //Number of calls that haven't returned yet
+    call_depth: usize,
+    max_call_depth: Option<usize>,
+    //Operands of the last instruction, if it failed after computing them
+    failed_operands: Option<Box<OperandsDump>>,
+    //Decoded instructions indexed by pc offset, along with the encoding they were decoded from
+    instruction_cache: Vec<Option<(i64, Instruction)>>,
+    //Number of instructions decoded by decode_current_instruction, to check the cache is used
+    #[cfg(test)]
+    instructions_decoded: usize,
+    #[cfg(feature = "run_timer")]
+    run_started: Option<Instant>,
+    //Time between start_timer and end_run, once the run ended
+    #[cfg(feature = "run_timer")]
+    run_time: Option<Duration>,
 }
 
 impl HintData {
@@ -104,11 +291,26 @@ impl HintData {
 }
 
 impl VirtualMachine {
+    ///Creates a vm with the given builtin runners, which keep the given order instead of being
+    ///sorted by name, so they can follow the order in which the program declares them. Fails if a
+    ///name is repeated.
+    pub fn with_builtins(
+        trace_enabled: bool,
+        builtins: Vec<(BuiltinName, BuiltinRunner)>,
+    ) -> Result<VirtualMachine, RunnerError> {
+        let mut vm = VirtualMachine::new(trace_enabled);
+        for (name, builtin) in builtins {
+            vm.add_builtin_runner(name, builtin)?;
+        }
+        Ok(vm)
+    }
+
     pub fn new(trace_enabled: bool) -> VirtualMachine {
         let run_context = RunContext {
             pc: Relocatable::from((0, 0)),
             ap: 0,
             fp: 0,
+            execution_segment: 1,
         };
 
         let trace = if trace_enabled {
@@ -120,16 +322,81 @@ impl VirtualMachine {
         VirtualMachine {
             run_context,
             builtin_runners: Vec::new(),
-            _program_base: None,
+            auto_deduction: HashMap::new(),
+            program_base: None,
             memory: Memory::new(),
             // We had to change this from None to this Some because when calling run_from_entrypoint from cairo-rs-py
             // we could not change this value and faced an Error. This is the behaviour that the original VM implements also.
-            accessed_addresses: Some(Vec::new()),
+            accessed_addresses: Some(HashSet::new()),
             trace,
             current_step: 0,
+            register_bounds: None,
+            const_view: Rc::new(VmConstView::default()),
+            opcode_extension: None,
+            step_hooks: Vec::new(),
             skip_instruction_execution: false,
             segments: MemorySegmentManager::new(),
             run_finished: false,
+            breakpoints: HashSet::new(),
+            hint_statistics: None,
+            accessed_since_snapshot: None,
+            call_depth: 0,
+            max_call_depth: None,
+            failed_operands: None,
+            instruction_cache: Vec::new(),
+            #[cfg(test)]
+            instructions_decoded: 0,
+            #[cfg(feature = "run_timer")]
+            run_started: None,
+            #[cfg(feature = "run_timer")]
+            run_time: None,
+        }
+    }
+
+    ///Clears the state of the previous run so the vm can be reused for another one, keeping the
+    ///allocations of its memory, trace and accessed addresses.
+    ///The registers, segments, memory, builtins, auto deduction rules, trace, accessed addresses,
+    ///step count, hint statistics and register bounds are cleared, the vm ending up as a freshly
+    ///created one with the same trace and hint statistics settings.
+    ///The registered opcode extension, the step hooks, the breakpoints, the maximum call depth and
+    ///whether memory origin tracking is enabled survive the reset. Programs, hint processors and
+    ///execution scopes live outside of the vm and aren't affected, the scopes of a reused
+    ///CairoRunner can be cleared with ExecutionScopes::reset.
+    pub fn reset(&mut self) {
+        self.run_context = RunContext {
+            pc: Relocatable::from((0, 0)),
+            ap: 0,
+            fp: 0,
+            execution_segment: 1,
+        };
+        self.builtin_runners.clear();
+        self.auto_deduction.clear();
+        self.segments.reset();
+        self.program_base = None;
+        self.memory.reset();
+        match &mut self.accessed_addresses {
+            Some(accessed_addresses) => accessed_addresses.clear(),
+            None => self.accessed_addresses = Some(HashSet::new()),
+        }
+        if let Some(trace) = &mut self.trace {
+            trace.clear();
+        }
+        self.current_step = 0;
+        self.register_bounds = None;
+        self.const_view = Rc::new(VmConstView::default());
+        self.skip_instruction_execution = false;
+        self.run_finished = false;
+        if let Some(hint_statistics) = &mut self.hint_statistics {
+            hint_statistics.clear();
+        }
+        self.accessed_since_snapshot = None;
+        self.call_depth = 0;
+        self.failed_operands = None;
+        self.instruction_cache.clear();
+        #[cfg(feature = "run_timer")]
+        {
+            self.run_started = None;
+            self.run_time = None;
         }
     }
 
@@ -137,13 +404,20 @@ impl VirtualMachine {
     fn get_instruction_encoding(
         &self,
     ) -> Result<(Cow<Felt>, Option<Cow<MaybeRelocatable>>), VirtualMachineError> {
-        let encoding_ref = match self.memory.get(&self.run_context.pc) {
+        self.get_instruction_encoding_at(&self.run_context.pc)
+    }
+
+    fn get_instruction_encoding_at(
+        &self,
+        pc: &Relocatable,
+    ) -> Result<(Cow<'_, Felt>, Option<Cow<'_, MaybeRelocatable>>), VirtualMachineError> {
+        let encoding_ref = match self.memory.get(pc) {
             Ok(Some(Cow::Owned(MaybeRelocatable::Int(encoding)))) => Cow::Owned(encoding),
             Ok(Some(Cow::Borrowed(MaybeRelocatable::Int(encoding)))) => Cow::Borrowed(encoding),
             _ => return Err(VirtualMachineError::InvalidInstructionEncoding),
         };
 
-        let imm_addr = &self.run_context.pc + 1_i32;
+        let imm_addr = pc + 1_i32;
 
         if let Ok(optional_imm) = self.memory.get(&imm_addr) {
             Ok((encoding_ref, optional_imm))
@@ -152,85 +426,80 @@ impl VirtualMachine {
         }
     }
 
-    fn update_fp(
-        &mut self,
-        instruction: &Instruction,
-        operands: &Operands,
-    ) -> Result<(), VirtualMachineError> {
-        let new_fp_offset: usize = match instruction.fp_update {
-            FpUpdate::APPlus2 => self.run_context.ap + 2,
-            FpUpdate::Dst => match operands.dst {
-                MaybeRelocatable::RelocatableValue(ref rel) => rel.offset,
-                MaybeRelocatable::Int(ref num) => num
-                    .to_usize()
-                    .ok_or(VirtualMachineError::BigintToUsizeFail)?,
-            },
-            FpUpdate::Regular => return Ok(()),
-        };
-        self.run_context.fp = new_fp_offset;
-        Ok(())
-    }
-
-    fn update_ap(
-        &mut self,
-        instruction: &Instruction,
-        operands: &Operands,
+    ///Checks that the registers stay in their segments after the instruction is executed.
+    ///ap is always relative to the execution segment, so only pc and fp can leave it. new_fp is
+    ///only given when the instruction sets fp to a value which isn't an offset.
+    ///Does nothing if strict register checks are disabled.
+    fn check_register_bounds(
+        &self,
+        new_pc: &Relocatable,
+        new_fp: Option<&MaybeRelocatable>,
     ) -> Result<(), VirtualMachineError> {
-        let new_ap: Relocatable = match instruction.ap_update {
-            ApUpdate::Add => match operands.res.clone() {
-                Some(res) => self.run_context.get_ap().add_maybe(&res)?,
-                None => return Err(VirtualMachineError::UnconstrainedResAdd),
-            },
-            ApUpdate::Add1 => self.run_context.get_ap() + 1_i32,
-            ApUpdate::Add2 => self.run_context.get_ap() + 2_i32,
-            ApUpdate::Regular => return Ok(()),
+        let bounds = match &self.register_bounds {
+            Some(bounds) => bounds,
+            None => return Ok(()),
         };
-        self.run_context.ap = new_ap.offset;
-        Ok(())
+        //The final return leaves both pc and fp outside of their segments
+        if bounds.final_pc.as_ref() == Some(new_pc) {
+            return Ok(());
+        }
+        if new_pc.segment_index != bounds.program_segment {
+            return Err(VirtualMachineError::RegisterOutOfBounds(
+                VmRegister::PC,
+                MaybeRelocatable::from(*new_pc),
+            ));
+        }
+        match new_fp {
+            Some(MaybeRelocatable::RelocatableValue(rel))
+                if rel.segment_index == bounds.execution_segment =>
+            {
+                Ok(())
+            }
+            Some(fp) => Err(VirtualMachineError::RegisterOutOfBounds(
+                VmRegister::FP,
+                fp.clone(),
+            )),
+            None => Ok(()),
+        }
     }
 
-    fn update_pc(
+    //Bookkeeping shared by every executed instruction, once it succeeded: appends the trace
+    //entry, records the accessed addresses, moves the registers and counts the step
+    fn record_step(
         &mut self,
-        instruction: &Instruction,
-        operands: &Operands,
-    ) -> Result<(), VirtualMachineError> {
-        let new_pc: Relocatable = match instruction.pc_update {
-            PcUpdate::Regular => self.run_context.pc + instruction.size(),
-            PcUpdate::Jump => match &operands.res {
-                Some(ref res) => res.get_relocatable()?,
-                None => return Err(VirtualMachineError::UnconstrainedResJump),
-            },
-            PcUpdate::JumpRel => match operands.res.clone() {
-                Some(res) => match res {
-                    MaybeRelocatable::Int(num_res) => self.run_context.pc.add_int(&num_res)?,
+        accessed: impl IntoIterator<Item = Relocatable>,
+        pc: Relocatable,
+        ap: usize,
+        fp: usize,
+    ) {
+        if let Some(ref mut trace) = &mut self.trace {
+            trace.push(TraceEntry {
+                pc: self.run_context.pc,
+                ap: self.run_context.get_ap(),
+                fp: self.run_context.get_fp(),
+            });
+        }
 
-                    _ => return Err(VirtualMachineError::PureValue),
-                },
-                None => return Err(VirtualMachineError::UnconstrainedResJumpRel),
-            },
-            PcUpdate::Jnz => match VirtualMachine::is_zero(&operands.dst)? {
-                true => self.run_context.pc + instruction.size(),
-                false => (self.run_context.pc.add_maybe(&operands.op1))?,
-            },
-        };
-        self.run_context.pc = new_pc;
-        Ok(())
-    }
+        if let Some(ref mut accessed_addresses) = self.accessed_addresses {
+            match &mut self.accessed_since_snapshot {
+                Some(accessed_since_snapshot) => accessed_since_snapshot.extend(
+                    accessed
+                        .into_iter()
+                        .filter(|addr| accessed_addresses.insert(*addr)),
+                ),
+                None => accessed_addresses.extend(accessed),
+            }
+        }
 
-    fn update_registers(
-        &mut self,
-        instruction: Instruction,
-        operands: Operands,
-    ) -> Result<(), VirtualMachineError> {
-        self.update_fp(&instruction, &operands)?;
-        self.update_ap(&instruction, &operands)?;
-        self.update_pc(&instruction, &operands)?;
-        Ok(())
+        self.run_context.pc = pc;
+        self.run_context.ap = ap;
+        self.run_context.fp = fp;
+        self.current_step += 1;
     }
 
     /// Returns true if the value is zero
     /// Used for JNZ instructions
-    fn is_zero(addr: &MaybeRelocatable) -> Result<bool, VirtualMachineError> {
+    pub(crate) fn is_zero(addr: &MaybeRelocatable) -> Result<bool, VirtualMachineError> {
         match addr {
             MaybeRelocatable::Int(num) => Ok(num.is_zero()),
             MaybeRelocatable::RelocatableValue(rel_value) if rel_value.offset > 0 => Ok(false),
@@ -329,21 +598,64 @@ impl VirtualMachine {
         Ok((None, None))
     }
 
-    fn deduce_memory_cell(
+    ///Deduces the value of the memory cell with the builtin owning its segment, or with the auto
+    ///deduction rules added for it. Returns None if none of them can deduce it.
+    pub fn deduce_memory_cell(
         &self,
         address: &Relocatable,
     ) -> Result<Option<MaybeRelocatable>, VirtualMachineError> {
-        for (_, builtin) in self.builtin_runners.iter() {
-            if builtin.base() == address.segment_index {
-                match builtin.deduce_memory_cell(address, &self.memory) {
-                    Ok(maybe_reloc) => return Ok(maybe_reloc),
-                    Err(error) => return Err(VirtualMachineError::RunnerError(error)),
-                };
+        if let Some((_, builtin)) = self
+            .builtin_runners
+            .iter()
+            .find(|(_, builtin)| builtin.base() == address.segment_index)
+        {
+            if let Some(deduced) = builtin.deduce_memory_cell(address, &self.memory)? {
+                return Ok(Some(deduced));
+            }
+        }
+        let rules = match usize::try_from(address.segment_index)
+            .ok()
+            .and_then(|segment_index| self.auto_deduction.get(&segment_index))
+        {
+            Some(rules) => rules,
+            None => return Ok(None),
+        };
+        for rule in rules {
+            if let Some(deduced) = rule.0(&self.memory, address)? {
+                return Ok(Some(deduced));
             }
         }
         Ok(None)
     }
 
+    ///Adds a rule deducing the memory cells of the segment, it's used when an operand in the
+    ///segment is missing and checked against the segment's cells by verify_auto_deductions
+    pub fn add_auto_deduction_rule(&mut self, segment_index: usize, rule: Rule) {
+        self.auto_deduction
+            .entry(segment_index)
+            .or_default()
+            .push(rule);
+    }
+
+    ///Deduces the operand with deduce_memory_cell, and records the deduction in deduced_operands.
+    ///This is the only place where memory cells are deduced during a run: plain memory reads,
+    ///including the ones performed by hints, never do.
+    fn deduce_operand_by_builtin(
+        &self,
+        operand: BuiltinOperand,
+        address: &Relocatable,
+        deduced_operands: &mut DeducedOperands,
+    ) -> Result<Option<MaybeRelocatable>, VirtualMachineError> {
+        let deduced = self.deduce_memory_cell(address)?;
+        if deduced.is_some() {
+            match operand {
+                BuiltinOperand::Op0 => deduced_operands.set_op0_by_builtin(true),
+                BuiltinOperand::Op1 => deduced_operands.set_op1_by_builtin(true),
+            }
+        }
+        Ok(deduced)
+    }
+
     ///Computes the value of res if possible
     fn compute_res(
         &self,
@@ -389,31 +701,30 @@ impl VirtualMachine {
     ) -> Result<(), VirtualMachineError> {
         match instruction.opcode {
             Opcode::AssertEq => {
+                //Values of different kinds, or pointers to different segments, are never equal
                 match &operands.res {
-                    None => return Err(VirtualMachineError::UnconstrainedResAssertEq),
-                    Some(res) => {
-                        if res != &operands.dst {
-                            return Err(VirtualMachineError::DiffAssertValues(
-                                operands.dst.clone(),
-                                res.clone(),
-                            ));
-                        };
+                    None => Err(VirtualMachineError::UnconstrainedResAssertEq),
+                    Some(res) if res != operands.dst.as_ref() => {
+                        Err(VirtualMachineError::DiffAssertValues(
+                            operands.dst.clone().into_owned(),
+                            res.clone(),
+                        ))
                     }
-                };
-                Ok(())
+                    Some(_) => Ok(()),
+                }
             }
             Opcode::Call => {
                 let return_pc = MaybeRelocatable::from(self.run_context.pc + instruction.size());
-                if operands.op0 != return_pc {
+                if *operands.op0 != return_pc {
                     return Err(VirtualMachineError::CantWriteReturnPc(
-                        operands.op0.clone(),
+                        operands.op0.clone().into_owned(),
                         return_pc,
                     ));
                 };
 
-                if MaybeRelocatable::from(self.run_context.get_fp()) != operands.dst {
+                if MaybeRelocatable::from(self.run_context.get_fp()) != *operands.dst {
                     return Err(VirtualMachineError::CantWriteReturnFp(
-                        operands.dst.clone(),
+                        operands.dst.clone().into_owned(),
                         MaybeRelocatable::from(self.run_context.get_fp()),
                     ));
                 };
@@ -423,58 +734,191 @@ impl VirtualMachine {
         }
     }
 
+    ///Inserts the deduced operands into memory.
+    ///A deduced operand is always owned, a borrowed operand is already in memory and is never written.
     fn insert_deduced_operands(
         &mut self,
         deduced_operands: DeducedOperands,
-        operands: &Operands,
+        dst: Option<&MaybeRelocatable>,
+        op0: Option<&MaybeRelocatable>,
+        op1: Option<&MaybeRelocatable>,
         operands_addresses: &OperandsAddresses,
     ) -> Result<(), VirtualMachineError> {
-        if deduced_operands.was_op0_deducted() {
+        let track_origins = self.memory.is_origin_tracking_enabled();
+        if let (true, Some(op0)) = (deduced_operands.was_op0_deducted(), op0) {
+            if track_origins {
+                self.set_operand_origin(
+                    &operands_addresses.op0_addr,
+                    deduced_operands.was_op0_deducted_by_builtin(),
+                );
+            }
             self.memory
-                .insert(&operands_addresses.op0_addr, &operands.op0)
+                .insert(&operands_addresses.op0_addr, op0)
                 .map_err(VirtualMachineError::MemoryError)?;
+            if deduced_operands.was_op0_deducted_by_builtin() {
+                self.validate_memory_cell(&operands_addresses.op0_addr)?;
+            }
         }
         if deduced_operands.was_op1_deducted() {
-            self.memory
-                .insert(&operands_addresses.op1_addr, &operands.op1)
-                .map_err(VirtualMachineError::MemoryError)?;
+            if let Some(op1) = op1 {
+                if track_origins {
+                    self.set_operand_origin(
+                        &operands_addresses.op1_addr,
+                        deduced_operands.was_op1_deducted_by_builtin(),
+                    );
+                }
+                self.memory
+                    .insert(&operands_addresses.op1_addr, op1)
+                    .map_err(VirtualMachineError::MemoryError)?;
+                if deduced_operands.was_op1_deducted_by_builtin() {
+                    self.validate_memory_cell(&operands_addresses.op1_addr)?;
+                }
+            }
         }
-        if deduced_operands.was_dest_deducted() {
+        if let (true, Some(dst)) = (deduced_operands.was_dest_deducted(), dst) {
+            if track_origins {
+                self.set_operand_origin(&operands_addresses.dst_addr, false);
+            }
             self.memory
-                .insert(&operands_addresses.dst_addr, &operands.dst)
+                .insert(&operands_addresses.dst_addr, dst)
                 .map_err(VirtualMachineError::MemoryError)?;
         }
+        if track_origins {
+            self.memory.set_origin(MemoryOrigin::Preload);
+        }
 
         Ok(())
     }
 
+    ///Runs the validation rule of the segment holding addr on its value, if the segment has one,
+    ///and records addr in validated_addresses so it isn't validated again
+    pub fn validate_memory_cell(&mut self, addr: &Relocatable) -> Result<(), VirtualMachineError> {
+        let addr = MaybeRelocatable::from(addr);
+        if self.memory.get(&addr)?.is_none() {
+            return Err(VirtualMachineError::MemoryGet(addr));
+        }
+        self.memory.validate_memory_cell(&addr)?;
+        self.memory.add_validated_address(addr);
+        Ok(())
+    }
+
+    ///Sets the origin of the operand about to be inserted at addr: the builtin owning its segment
+    ///if the builtin deduced it, or the current instruction otherwise
+    fn set_operand_origin(&mut self, addr: &Relocatable, deduced_by_builtin: bool) {
+        let builtin = self
+            .builtin_runners
+            .iter()
+            .find(|(_, builtin)| deduced_by_builtin && builtin.base() == addr.segment_index);
+        let origin = match builtin {
+            Some((_, builtin)) => MemoryOrigin::Deduction {
+                builtin: builtin.get_memory_segment_addresses().0,
+            },
+            None => MemoryOrigin::Instruction {
+                pc: self.run_context.pc,
+            },
+        };
+        self.memory.set_origin(origin);
+    }
+
     fn run_instruction(&mut self, instruction: Instruction) -> Result<(), VirtualMachineError> {
+        if let (Opcode::Call, Some(max_call_depth)) = (&instruction.opcode, self.max_call_depth) {
+            if self.call_depth >= max_call_depth {
+                return Err(VirtualMachineError::MaxCallDepthExceeded(max_call_depth));
+            }
+        }
         let (operands, operands_addresses, deduced_operands) =
             self.compute_operands(&instruction)?;
-        self.insert_deduced_operands(deduced_operands, &operands, &operands_addresses)?;
-        self.opcode_assertions(&instruction, &operands)?;
+        let hook_operands = (!self.step_hooks.is_empty()).then(|| operands.to_owned_operands());
+        let registers = match self
+            .opcode_assertions(&instruction, &operands)
+            .and_then(|_| next_registers(&instruction, &operands, &self.run_context.registers()))
+        {
+            Ok(registers) => registers,
+            Err(error) => {
+                let failed_operands = operands.dump(&operands_addresses);
+                self.failed_operands = Some(Box::new(failed_operands));
+                return Err(error);
+            }
+        };
+        let new_fp = (instruction.fp_update == FpUpdate::Dst).then(|| operands.dst.as_ref());
+        self.check_register_bounds(&registers.pc, new_fp)?;
+        let Operands { dst, op0, op1, .. } = operands;
+        let (dst, op0, op1) = (deduced(dst), deduced(op0), deduced(op1));
+        self.insert_deduced_operands(
+            deduced_operands,
+            dst.as_ref(),
+            op0.as_ref(),
+            op1.as_ref(),
+            &operands_addresses,
+        )?;
 
-        if let Some(ref mut trace) = &mut self.trace {
-            trace.push(TraceEntry {
-                pc: self.run_context.pc,
-                ap: self.run_context.get_ap(),
-                fp: self.run_context.get_fp(),
-            });
+        match instruction.opcode {
+            Opcode::Call => self.call_depth += 1,
+            Opcode::Ret => self.call_depth = self.call_depth.saturating_sub(1),
+            _ => (),
         }
 
-        if let Some(ref mut accessed_addresses) = self.accessed_addresses {
-            let op_addrs = operands_addresses;
-            let addresses = [op_addrs.dst_addr, op_addrs.op0_addr, op_addrs.op1_addr];
-            accessed_addresses.extend(addresses.into_iter());
+        let op_addrs = operands_addresses;
+        self.record_step(
+            [op_addrs.dst_addr, op_addrs.op0_addr, op_addrs.op1_addr],
+            registers.pc,
+            registers.ap.offset,
+            registers.fp.offset,
+        );
+        if let Some(operands) = hook_operands {
+            self.run_step_hooks(|hook, vm| hook.post_step(vm, &operands, &instruction))?;
         }
-
-        self.update_registers(instruction, operands)?;
-        self.current_step += 1;
         Ok(())
     }
 
-    fn decode_current_instruction(&self) -> Result<Instruction, VirtualMachineError> {
-        let (instruction_ref, imm) = self.get_instruction_encoding()?;
+    //Calls run_hook on every step hook in registration order, stopping at the first error.
+    //The hooks are taken out of the vm meanwhile, so they can be given a reference to it.
+    fn run_step_hooks(
+        &mut self,
+        mut run_hook: impl FnMut(&mut dyn StepHook, &VirtualMachine) -> Result<(), String>,
+    ) -> Result<(), VirtualMachineError> {
+        let mut hooks = std::mem::take(&mut self.step_hooks);
+        let result = hooks
+            .iter_mut()
+            .try_for_each(|hook| run_hook(hook.as_mut(), self));
+        self.step_hooks = hooks;
+        result.map_err(VirtualMachineError::StepHookFailed)
+    }
+
+    ///Decodes the instruction at pc, reusing the instruction decoded the last time pc had the same
+    ///offset as long as the encoding found there hasn't changed since.
+    fn decode_current_instruction(&mut self) -> Result<Instruction, VirtualMachineError> {
+        let pc = self.run_context.pc;
+        let encoding = self
+            .get_instruction_encoding_at(&pc)?
+            .0
+            .to_i64()
+            .ok_or(VirtualMachineError::InvalidInstructionEncoding)?;
+        if let Some(Some((cached_encoding, instruction))) = self.instruction_cache.get(pc.offset) {
+            if *cached_encoding == encoding {
+                return Ok(instruction.clone());
+            }
+        }
+
+        let instruction = self.decode_instruction_at(&pc)?;
+        #[cfg(test)]
+        {
+            self.instructions_decoded += 1;
+        }
+        if self.instruction_cache.len() <= pc.offset {
+            self.instruction_cache.resize(pc.offset + 1, None);
+        }
+        self.instruction_cache[pc.offset] = Some((encoding, instruction.clone()));
+        Ok(instruction)
+    }
+
+    ///Decodes the instruction at pc without executing it, so hints can inspect the instructions
+    ///around the current one
+    pub fn decode_instruction_at(
+        &self,
+        pc: &Relocatable,
+    ) -> Result<Instruction, VirtualMachineError> {
+        let (instruction_ref, imm) = self.get_instruction_encoding_at(pc)?;
         match instruction_ref.to_i64() {
             Some(instruction) => {
                 if let Some(MaybeRelocatable::Int(imm_ref)) = imm.as_ref().map(|x| x.as_ref()) {
@@ -496,22 +940,130 @@ impl VirtualMachine {
         constants: &HashMap<String, Felt>,
     ) -> Result<(), VirtualMachineError> {
         if let Some(hint_list) = hint_data_dictionary.get(&self.run_context.pc.offset) {
+            let track_origins = self.memory.is_origin_tracking_enabled();
             for (hint_index, hint_data) in hint_list.iter().enumerate() {
-                hint_executor
-                    .execute_hint(self, exec_scopes, hint_data, constants)
-                    .map_err(|err| VirtualMachineError::Hint(hint_index, Box::new(err)))?
+                if track_origins {
+                    self.memory.set_origin(MemoryOrigin::Hint {
+                        pc: self.run_context.pc,
+                        index: hint_index,
+                    });
+                }
+                if let Some(hint_statistics) = &mut self.hint_statistics {
+                    let code = hint_executor.get_hint_code(hint_data).unwrap_or("");
+                    match hint_statistics.get_mut(code) {
+                        Some(count) => *count += 1,
+                        None => {
+                            hint_statistics.insert(code.to_string(), 1);
+                        }
+                    }
+                }
+                let result = hint_executor.execute_hint(self, exec_scopes, hint_data, constants);
+                if track_origins {
+                    self.memory.set_origin(MemoryOrigin::Preload);
+                }
+                result.map_err(|err| {
+                    VirtualMachineError::Hint(Box::new(self.hint_exception(
+                        &*hint_executor,
+                        hint_index,
+                        hint_data,
+                        err,
+                    )))
+                })?
             }
         }
         Ok(())
     }
 
+    ///Builds the HintException for an error returned by the hint at hint_index of the current pc.
+    ///The hint's code and ids values are the ones exposed by the hint processor.
+    fn hint_exception(
+        &self,
+        hint_executor: &dyn HintProcessor,
+        hint_index: usize,
+        hint_data: &Box<dyn Any>,
+        inner: HintError,
+    ) -> HintException {
+        let hint_code_snippet = hint_executor
+            .get_hint_code(hint_data)
+            .and_then(|code| code.lines().next())
+            .unwrap_or("")
+            .trim()
+            .to_string();
+        HintException {
+            pc: self.run_context.pc,
+            hint_index,
+            hint_code_snippet,
+            ids_values_at_failure: hint_executor.get_ids_values(self, hint_data),
+            inner,
+        }
+    }
+
     pub fn step_instruction(&mut self) -> Result<(), VirtualMachineError> {
-        let instruction = self.decode_current_instruction()?;
-        self.run_instruction(instruction)?;
+        self.failed_operands = None;
+        if !self.step_hooks.is_empty() {
+            self.run_step_hooks(|hook, vm| hook.pre_step(vm))?;
+        }
+        if self.skip_instruction_execution {
+            let instruction = self.decode_current_instruction()?;
+            self.run_context.pc = self.run_context.pc + instruction.size();
+            self.skip_instruction_execution = false;
+            return Ok(());
+        }
+        match self.get_extension_instruction()? {
+            Some(instruction_word) => self.run_extension_instruction(instruction_word)?,
+            None => {
+                let instruction = self.decode_current_instruction()?;
+                self.run_instruction(instruction)?;
+            }
+        }
         self.skip_instruction_execution = false;
         Ok(())
     }
 
+    ///Makes the next call to step_instruction move pc past the current instruction without
+    ///executing it. Used by the skip_next_instruction hint.
+    pub fn skip_next_instruction_execution(&mut self) {
+        self.skip_instruction_execution = true;
+    }
+
+    ///Returns the encoded instruction at pc if it is reserved for the registered opcode extension
+    fn get_extension_instruction(&self) -> Result<Option<i64>, VirtualMachineError> {
+        if self.opcode_extension.is_none() {
+            return Ok(None);
+        }
+        let (instruction_ref, _) = self.get_instruction_encoding()?;
+        Ok(instruction_ref
+            .to_i64()
+            .filter(|instruction| is_extension_instruction(*instruction)))
+    }
+
+    fn run_extension_instruction(
+        &mut self,
+        instruction_word: i64,
+    ) -> Result<(), VirtualMachineError> {
+        let extension = match self.opcode_extension.as_mut() {
+            Some(extension) => extension,
+            None => return Err(VirtualMachineError::InvalidInstructionEncoding),
+        };
+        let track_origins = self.memory.is_origin_tracking_enabled();
+        if track_origins {
+            self.memory.set_origin(MemoryOrigin::Instruction {
+                pc: self.run_context.pc,
+            });
+        }
+        let mut vm_view = VmView::new(&self.run_context, &mut self.memory, self.current_step);
+        let result = extension.execute(&mut vm_view, instruction_word);
+        let accessed = vm_view.into_accessed_addresses();
+        if track_origins {
+            self.memory.set_origin(MemoryOrigin::Preload);
+        }
+        let registers = result?;
+        //fp is an offset of the execution segment, so only pc can leave its segment
+        self.check_register_bounds(&registers.pc, None)?;
+        self.record_step(accessed, registers.pc, registers.ap, registers.fp);
+        Ok(())
+    }
+
     pub fn step(
         &mut self,
         hint_executor: &mut dyn HintProcessor,
@@ -523,18 +1075,135 @@ impl VirtualMachine {
         self.step_instruction()
     }
 
+    ///Like step, but fails with a VmException describing the registers, instruction and operands
+    ///of the step that failed.
+    #[allow(clippy::result_large_err)]
+    pub fn step_ext(
+        &mut self,
+        hint_executor: &mut dyn HintProcessor,
+        exec_scopes: &mut ExecutionScopes,
+        hint_data_dictionary: &HashMap<usize, Vec<Box<dyn Any>>>,
+        constants: &HashMap<String, Felt>,
+    ) -> Result<(), VmException> {
+        self.step(hint_executor, exec_scopes, hint_data_dictionary, constants)
+            .map_err(|error| VmException::from_step_error(self, error))
+    }
+
+    //Reads the operands of the instruction at pc from memory, without deducing them, along with
+    //the ones computed by the instruction if it failed after computing them
+    pub(crate) fn step_error_context(&self) -> StepErrorContext {
+        let instruction = self.decode_instruction_at(&self.run_context.pc).ok();
+        let read = |addr: Result<Relocatable, VirtualMachineError>| {
+            self.memory
+                .get(&addr.ok()?)
+                .ok()
+                .flatten()
+                .map(Cow::into_owned)
+        };
+        let (dst, op0, op1) = match &instruction {
+            Some(instruction) => {
+                let op0 = read(self.run_context.compute_op0_addr(instruction));
+                (
+                    read(self.run_context.compute_dst_addr(instruction)),
+                    op0.clone(),
+                    read(self.run_context.compute_op1_addr(instruction, op0.as_ref())),
+                )
+            }
+            None => (None, None, None),
+        };
+        StepErrorContext {
+            pc: self.run_context.pc,
+            ap: self.run_context.get_ap(),
+            fp: self.run_context.get_fp(),
+            instruction,
+            dst,
+            op0,
+            op1,
+            operands: self.failed_operands.as_deref().cloned(),
+        }
+    }
+
+    ///Returns the operands of the last instruction, with their addresses, if it failed after
+    ///computing them
+    pub fn get_failed_operands(&self) -> Option<&OperandsDump> {
+        self.failed_operands.as_deref()
+    }
+
+    ///Like step, but takes the step from run_resources, failing with RunResourcesExceeded if there
+    ///are no steps left. Failed steps aren't charged, so run_resources holds the steps left to
+    ///bill the execution that did happen.
+    pub fn step_with_resources(
+        &mut self,
+        hint_executor: &mut dyn HintProcessor,
+        exec_scopes: &mut ExecutionScopes,
+        hint_data_dictionary: &HashMap<usize, Vec<Box<dyn Any>>>,
+        constants: &HashMap<String, Felt>,
+        run_resources: &mut RunResources,
+    ) -> Result<(), VirtualMachineError> {
+        if run_resources.consumed() {
+            return Err(VirtualMachineError::RunResourcesExceeded(self.current_step));
+        }
+        self.step(hint_executor, exec_scopes, hint_data_dictionary, constants)?;
+        run_resources.consume_step();
+        Ok(())
+    }
+
+    pub fn get_current_step(&self) -> usize {
+        self.current_step
+    }
+
+    ///Steps until pc reaches target, running the hints attached to each pc like step does, and
+    ///returns the number of steps executed. Fails with StepsExceeded after max_steps steps, with
+    ///PcOutOfMemory if pc points to a cell that was never written, as happens when a program
+    ///jumps past its end, and with StepFailed wrapping any error raised by a step along with the
+    ///number of steps executed before it.
+    pub fn run_until_pc(
+        &mut self,
+        target: Relocatable,
+        max_steps: Option<usize>,
+        hint_executor: &mut dyn HintProcessor,
+        exec_scopes: &mut ExecutionScopes,
+        hint_data_dictionary: &HashMap<usize, Vec<Box<dyn Any>>>,
+        constants: &HashMap<String, Felt>,
+    ) -> Result<RunOutcome, VirtualMachineError> {
+        let mut steps = 0;
+        while self.run_context.pc != target {
+            if max_steps == Some(steps) {
+                return Err(VirtualMachineError::StepsExceeded(steps));
+            }
+            if !matches!(self.memory.get(&self.run_context.pc), Ok(Some(_))) {
+                return Err(VirtualMachineError::PcOutOfMemory(
+                    steps,
+                    self.run_context.pc,
+                ));
+            }
+            self.step(hint_executor, exec_scopes, hint_data_dictionary, constants)
+                .map_err(|error| VirtualMachineError::StepFailed(steps, Box::new(error)))?;
+            steps += 1;
+        }
+        Ok(RunOutcome {
+            steps_executed: steps,
+            break_reason: BreakReason::ReachedFinalPc,
+        })
+    }
+
     fn compute_op0_deductions(
         &self,
         op0_addr: &Relocatable,
         res: &mut Option<MaybeRelocatable>,
         instruction: &Instruction,
-        dst_op: &Option<MaybeRelocatable>,
-        op1_op: &Option<MaybeRelocatable>,
+        dst_op: Option<&MaybeRelocatable>,
+        op1_op: Option<&MaybeRelocatable>,
+        deduced_operands: &mut DeducedOperands,
     ) -> Result<MaybeRelocatable, VirtualMachineError> {
-        let op0_op = match self.deduce_memory_cell(op0_addr)? {
+        let op0_op = match self.deduce_operand_by_builtin(
+            BuiltinOperand::Op0,
+            op0_addr,
+            deduced_operands,
+        )? {
             None => {
                 let op0;
-                (op0, *res) = self.deduce_op0(instruction, dst_op.as_ref(), op1_op.as_ref())?;
+                (op0, *res) = self.deduce_op0(instruction, dst_op, op1_op)?;
                 op0
             }
             deduced_memory_cell => deduced_memory_cell,
@@ -550,13 +1219,17 @@ impl VirtualMachine {
         op1_addr: &Relocatable,
         res: &mut Option<MaybeRelocatable>,
         instruction: &Instruction,
-        dst_op: &Option<MaybeRelocatable>,
+        dst_op: Option<&MaybeRelocatable>,
         op0: &MaybeRelocatable,
+        deduced_operands: &mut DeducedOperands,
     ) -> Result<MaybeRelocatable, VirtualMachineError> {
-        let op1_op = match self.deduce_memory_cell(op1_addr)? {
+        let op1_op = match self.deduce_operand_by_builtin(
+            BuiltinOperand::Op1,
+            op1_addr,
+            deduced_operands,
+        )? {
             None => {
-                let (op1, deduced_res) =
-                    self.deduce_op1(instruction, dst_op.as_ref(), Some(op0.clone()))?;
+                let (op1, deduced_res) = self.deduce_op1(instruction, dst_op, Some(op0.clone()))?;
                 if res.is_none() {
                     *res = deduced_res
                 }
@@ -575,13 +1248,13 @@ impl VirtualMachine {
         instruction: &Instruction,
         res: &Option<MaybeRelocatable>,
     ) -> Result<MaybeRelocatable, VirtualMachineError> {
-        let dst_op = match instruction.opcode {
-            Opcode::AssertEq if res.is_some() => Option::clone(res),
-            Opcode::Call => Some(MaybeRelocatable::from(self.run_context.get_fp())),
-            _ => self.deduce_dst(instruction, res.as_ref()),
-        };
-        let dst = dst_op.ok_or(VirtualMachineError::NoDst)?;
-        Ok(dst)
+        match instruction.opcode {
+            //Without a res there is nothing to deduce dst from, or to compare it against
+            Opcode::AssertEq if res.is_none() => Err(VirtualMachineError::UnconstrainedResAssertEq),
+            _ => self
+                .deduce_dst(instruction, res.as_ref())
+                .ok_or(VirtualMachineError::NoDst),
+        }
     }
 
     /// Compute operands and result, trying to deduce them if normal memory access returns a None
@@ -589,30 +1262,27 @@ impl VirtualMachine {
     pub fn compute_operands(
         &self,
         instruction: &Instruction,
-    ) -> Result<(Operands, OperandsAddresses, DeducedOperands), VirtualMachineError> {
+    ) -> Result<(Operands<'_>, OperandsAddresses, DeducedOperands), VirtualMachineError> {
         //Get operands from memory
         let dst_addr = self.run_context.compute_dst_addr(instruction)?;
         let dst_op = self
             .memory
             .get(&dst_addr)
-            .map_err(VirtualMachineError::MemoryError)?
-            .map(Cow::into_owned);
+            .map_err(VirtualMachineError::MemoryError)?;
 
         let op0_addr = self.run_context.compute_op0_addr(instruction)?;
         let op0_op = self
             .memory
             .get(&op0_addr)
-            .map_err(VirtualMachineError::MemoryError)?
-            .map(Cow::into_owned);
+            .map_err(VirtualMachineError::MemoryError)?;
 
         let op1_addr = self
             .run_context
-            .compute_op1_addr(instruction, op0_op.as_ref())?;
+            .compute_op1_addr(instruction, op0_op.as_deref())?;
         let op1_op = self
             .memory
             .get(&op1_addr)
-            .map_err(VirtualMachineError::MemoryError)?
-            .map(Cow::into_owned);
+            .map_err(VirtualMachineError::MemoryError)?;
 
         let mut res: Option<MaybeRelocatable> = None;
 
@@ -623,7 +1293,14 @@ impl VirtualMachine {
             Some(op0) => op0,
             None => {
                 deduced_operands.set_op0(true);
-                self.compute_op0_deductions(&op0_addr, &mut res, instruction, &dst_op, &op1_op)?
+                Cow::Owned(self.compute_op0_deductions(
+                    &op0_addr,
+                    &mut res,
+                    instruction,
+                    dst_op.as_deref(),
+                    op1_op.as_deref(),
+                    &mut deduced_operands,
+                )?)
             }
         };
 
@@ -632,7 +1309,14 @@ impl VirtualMachine {
             Some(op1) => op1,
             None => {
                 deduced_operands.set_op1(true);
-                self.compute_op1_deductions(&op1_addr, &mut res, instruction, &dst_op, &op0)?
+                Cow::Owned(self.compute_op1_deductions(
+                    &op1_addr,
+                    &mut res,
+                    instruction,
+                    dst_op.as_deref(),
+                    &op0,
+                    &mut deduced_operands,
+                )?)
             }
         };
 
@@ -646,7 +1330,7 @@ impl VirtualMachine {
             Some(dst) => dst,
             None => {
                 deduced_operands.set_dst(true);
-                self.compute_dst_deductions(instruction, &res)?
+                Cow::Owned(self.compute_dst_deductions(instruction, &res)?)
             }
         };
         let accessed_addresses = OperandsAddresses {
@@ -661,7 +1345,10 @@ impl VirtualMachine {
         ))
     }
 
-    ///Makes sure that all assigned memory cells are consistent with their auto deduction rules.
+    ///Makes sure that all assigned memory cells are consistent with their builtin's deductions
+    ///and with the auto deduction rules added for their segment.
+    ///Builtin cells which are deduced by the builtin can only hold a value if all the inputs of
+    ///their instance were written, as there is nothing else to check them against.
     pub fn verify_auto_deductions(&mut self) -> Result<(), VirtualMachineError> {
         for (name, builtin) in self.builtin_runners.iter_mut() {
             let index: usize = builtin
@@ -669,13 +1356,24 @@ impl VirtualMachine {
                 .try_into()
                 .map_err(|_| MemoryError::AddressInTemporarySegment(builtin.base()))?;
             for (offset, value) in self.memory.data[index].iter().enumerate() {
+                let addr = Relocatable::from((index as isize, offset));
+                if value.is_some() {
+                    if let Some(instance_base) =
+                        builtin.instance_missing_inputs(&addr, &self.memory)
+                    {
+                        return Err(VirtualMachineError::MissingBuiltinInputs(
+                            *name,
+                            instance_base,
+                        ));
+                    }
+                }
                 if let Some(deduced_memory_cell) = builtin
-                    .deduce_memory_cell(&Relocatable::from((index as isize, offset)), &self.memory)
+                    .deduce_memory_cell(&addr, &self.memory)
                     .map_err(VirtualMachineError::RunnerError)?
                 {
                     if Some(&deduced_memory_cell) != value.as_ref() && value.is_some() {
                         return Err(VirtualMachineError::InconsistentAutoDeduction(
-                            name.to_owned(),
+                            addr,
                             deduced_memory_cell,
                             value.to_owned(),
                         ));
@@ -683,18 +1381,66 @@ impl VirtualMachine {
                 }
             }
         }
-        Ok(())
-    }
+        for (index, rules) in self.auto_deduction.iter() {
+            let segment = match self.memory.data.get(*index) {
+                Some(segment) => segment,
+                None => continue,
+            };
+            for (offset, value) in segment.iter().enumerate() {
+                let value = match value {
+                    Some(value) => value,
+                    None => continue,
+                };
+                let addr = Relocatable::from((*index as isize, offset));
+                for rule in rules {
+                    match rule.0(&self.memory, &addr)? {
+                        Some(deduced) if &deduced != value => {
+                            return Err(VirtualMachineError::InconsistentAutoDeduction(
+                                addr,
+                                deduced,
+                                Some(value.clone()),
+                            ))
+                        }
+                        _ => (),
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
 
     pub fn end_run(&mut self, exec_scopes: &ExecutionScopes) -> Result<(), VirtualMachineError> {
         self.verify_auto_deductions()?;
         self.run_finished = true;
+        #[cfg(feature = "run_timer")]
+        {
+            self.run_time = self.time_elapsed();
+        }
         match exec_scopes.data.len() {
             1 => Ok(()),
             _ => Err(ExecScopeError::NoScopeError.into()),
         }
     }
 
+    ///Starts timing the run, which CairoRunner::initialize_vm does. Instant::now panics on
+    ///wasm32, so the run_timer feature can't be used there.
+    #[cfg(feature = "run_timer")]
+    pub fn start_timer(&mut self) {
+        self.run_started = Some(Instant::now());
+        self.run_time = None;
+    }
+
+    ///Returns the wall-clock time since the timer was started, up to the end of the run if
+    ///end_run was called. Always None without the run_timer feature.
+    pub fn time_elapsed(&self) -> Option<Duration> {
+        #[cfg(feature = "run_timer")]
+        return self
+            .run_time
+            .or_else(|| self.run_started.map(|run_started| run_started.elapsed()));
+        #[cfg(not(feature = "run_timer"))]
+        None
+    }
+
     pub fn mark_address_range_as_accessed(
         &mut self,
         base: Relocatable,
@@ -710,11 +1456,33 @@ impl VirtualMachine {
         Ok(())
     }
 
+    ///Returns the addresses accessed so far, without repetitions and in no particular order.
+    ///Returns None if the accessed addresses aren't being tracked.
+    pub fn get_accessed_addresses(&self) -> Option<impl Iterator<Item = &Relocatable>> {
+        self.accessed_addresses
+            .as_ref()
+            .map(|addresses| addresses.iter())
+    }
+
+    ///Writes the accessed addresses to writer in the compact sorted format read by
+    ///accessed_addresses::read_accessed_addresses, so the accesses of two runs can be compared.
+    ///If origin tracking is enabled, the cells whose value was deduced by a builtin are left out.
+    ///Writes no addresses if the accessed addresses aren't being tracked.
+    pub fn export_accessed_addresses(&self, writer: &mut impl io::Write) -> io::Result<()> {
+        let addresses = self.accessed_addresses.iter().flatten().filter(|addr| {
+            !matches!(
+                self.memory.get_origin(addr),
+                Some(MemoryOrigin::Deduction { .. })
+            )
+        });
+        write_accessed_addresses(writer, addresses)
+    }
+
     // Returns the values (fp, pc) corresponding to each call instruction in the traceback.
     // Returns the most recent call last.
     pub(crate) fn get_traceback_entries(&self) -> Vec<(Relocatable, Relocatable)> {
         let mut entries = Vec::<(Relocatable, Relocatable)>::new();
-        let mut fp = Relocatable::from((1, self.run_context.fp));
+        let mut fp = self.run_context.get_fp();
         // Fetch the fp and pc traceback entries
         for _ in 0..MAX_TRACEBACK_ENTRIES {
             // Get return pc
@@ -771,11 +1539,190 @@ impl VirtualMachine {
         entries
     }
 
+    ///Rebuilds the call stack by walking the chain of frames from the current fp, each frame
+    ///holding the caller's fp at [fp - 2] and the return pc at [fp - 1]. The entries are sorted
+    ///with the most recent call last, and the return pcs in the program segment are annotated
+    ///with their function's name when a program is given.
+    pub fn reconstruct_backtrace(&self, program: Option<&Program>) -> Vec<BacktraceEntry> {
+        let mut entries = Vec::new();
+        let mut fp = self.run_context.get_fp();
+        while let (Ok(fp_addr), Ok(ret_pc_addr)) = (fp.sub_usize(2), fp.sub_usize(1)) {
+            let (caller_fp, return_pc) = match (
+                self.memory.get_relocatable(&fp_addr),
+                self.memory.get_relocatable(&ret_pc_addr),
+            ) {
+                (Ok(caller_fp), Ok(return_pc)) => (caller_fp, return_pc),
+                _ => break,
+            };
+            let function_name = match (program, self.program_base) {
+                (Some(program), Some(program_base))
+                    if return_pc.segment_index == program_base.segment_index =>
+                {
+                    return_pc
+                        .sub(&program_base)
+                        .ok()
+                        .and_then(|pc_offset| program.find_function_name(pc_offset))
+                        .map(str::to_string)
+                }
+                _ => None,
+            };
+            entries.push(BacktraceEntry {
+                return_pc,
+                function_name,
+            });
+            if caller_fp == fp
+                || entries.len() > self.call_depth.max(MAX_TRACEBACK_ENTRIES as usize)
+            {
+                break;
+            }
+            fp = caller_fp;
+        }
+        entries.reverse();
+        entries
+    }
+
+    ///Starts a speculative run: the changes made to the memory and segments from now on can be
+    ///undone with discard_overlay or kept with commit_overlay. The registers, trace and builtins
+    ///aren't covered, snapshot does that. Dictionaries are covered by DictManager::start_overlay.
+    pub fn start_overlay(&mut self) -> Result<(), VirtualMachineError> {
+        self.memory.start_overlay()?;
+        self.segments.start_overlay()?;
+        Ok(())
+    }
+
+    ///Keeps the memory changes made since start_overlay
+    pub fn commit_overlay(&mut self) -> Result<(), VirtualMachineError> {
+        self.memory.commit_overlay()?;
+        self.segments.commit_overlay()?;
+        Ok(())
+    }
+
+    ///Restores the memory and segments to their state when start_overlay was called
+    pub fn discard_overlay(&mut self) -> Result<(), VirtualMachineError> {
+        self.memory.discard_overlay()?;
+        self.segments.discard_overlay()?;
+        Ok(())
+    }
+
+    ///Checkpoints the vm so that it can be rolled back with restore, for speculative runs. The
+    ///registers, step count, trace, accessed addresses, builtin deductions, memory and segments
+    ///are covered, along with the dictionaries of the dict_manager in the current scope, if any.
+    ///Memory changes are recorded through an overlay, so the memory isn't copied and no other
+    ///overlay can be started until the snapshot is restored or committed.
+    pub fn snapshot(
+        &mut self,
+        exec_scopes: &ExecutionScopes,
+    ) -> Result<VmSnapshot, VirtualMachineError> {
+        self.start_overlay()?;
+        let dict_manager = exec_scopes.get_dict_manager().ok();
+        if let Some(dict_manager) = &dict_manager {
+            if let Err(error) = dict_manager.borrow_mut().start_overlay() {
+                self.discard_overlay()?;
+                return Err(error.into());
+            }
+        }
+        self.accessed_since_snapshot = Some(Vec::new());
+        Ok(VmSnapshot {
+            pc: self.run_context.pc,
+            ap: self.run_context.ap,
+            fp: self.run_context.fp,
+            current_step: self.current_step,
+            call_depth: self.call_depth,
+            trace_len: self.trace.as_ref().map(Vec::len),
+            builtin_verified_lens: self
+                .builtin_runners
+                .iter()
+                .map(|(_, builtin)| builtin.verified_addresses_len())
+                .collect(),
+            dict_manager,
+        })
+    }
+
+    ///Rolls the vm back to the state it had when the snapshot was taken, discarding every memory
+    ///cell written since, including the ones deduced by builtins
+    pub fn restore(&mut self, snapshot: VmSnapshot) -> Result<(), VirtualMachineError> {
+        self.discard_overlay()?;
+        if let Some(dict_manager) = snapshot.dict_manager {
+            dict_manager.borrow_mut().discard_overlay()?;
+        }
+        if let (Some(accessed_addresses), Some(accessed_since_snapshot)) = (
+            &mut self.accessed_addresses,
+            self.accessed_since_snapshot.take(),
+        ) {
+            for addr in accessed_since_snapshot {
+                accessed_addresses.remove(&addr);
+            }
+        }
+        for ((_, builtin), len) in self
+            .builtin_runners
+            .iter()
+            .zip(snapshot.builtin_verified_lens)
+        {
+            builtin.truncate_verified_addresses(len);
+        }
+        if let (Some(trace), Some(trace_len)) = (&mut self.trace, snapshot.trace_len) {
+            trace.truncate(trace_len);
+        }
+        self.run_context.pc = snapshot.pc;
+        self.run_context.ap = snapshot.ap;
+        self.run_context.fp = snapshot.fp;
+        self.current_step = snapshot.current_step;
+        self.call_depth = snapshot.call_depth;
+        Ok(())
+    }
+
+    ///Keeps everything done since the snapshot was taken and stops recording it
+    pub fn commit_snapshot(&mut self, snapshot: VmSnapshot) -> Result<(), VirtualMachineError> {
+        self.commit_overlay()?;
+        if let Some(dict_manager) = snapshot.dict_manager {
+            dict_manager.borrow_mut().commit_overlay()?;
+        }
+        self.accessed_since_snapshot = None;
+        Ok(())
+    }
+
+    ///Writes the program's data at program_base, or at the start of a new segment if it is None,
+    ///and records it as the program segment. Returns the first address after the data.
+    ///Fails if a cell already holds a different value.
+    pub fn load_program(
+        &mut self,
+        data: &[MaybeRelocatable],
+        program_base: Option<Relocatable>,
+    ) -> Result<Relocatable, VirtualMachineError> {
+        let program_base = match program_base {
+            Some(program_base) => program_base,
+            None => self.add_memory_segment(),
+        };
+        for (offset, value) in data.iter().enumerate() {
+            self.memory.insert(&(program_base + offset), value)?;
+        }
+        self.program_base = Some(program_base);
+        Ok(program_base + data.len())
+    }
+
     ///Adds a new segment and to the VirtualMachine.memory returns its starting location as a RelocatableValue.
     pub fn add_memory_segment(&mut self) -> Relocatable {
         self.segments.add(&mut self.memory)
     }
 
+    ///Adds a new segment through the segment_arena builtin, which records it in its infos segment.
+    ///Falls back to a plain segment if the builtin isn't present.
+    pub fn add_arena_segment(&mut self) -> Result<Relocatable, VirtualMachineError> {
+        let segment_arena = self
+            .builtin_runners
+            .iter()
+            .find_map(|(_, builtin)| match builtin {
+                BuiltinRunner::SegmentArena(segment_arena) => Some(segment_arena),
+                _ => None,
+            });
+        match segment_arena {
+            Some(segment_arena) => segment_arena
+                .add_segment(&mut self.segments, &mut self.memory)
+                .map_err(VirtualMachineError::MemoryError),
+            None => Ok(self.add_memory_segment()),
+        }
+    }
+
     pub fn get_ap(&self) -> Relocatable {
         self.run_context.get_ap()
     }
@@ -814,21 +1761,47 @@ impl VirtualMachine {
     }
 
     /// Returns a reference to the vector with all builtins present in the virtual machine
-    pub fn get_builtin_runners(&self) -> &Vec<(String, BuiltinRunner)> {
+    pub fn get_builtin_runners(&self) -> &Vec<(BuiltinName, BuiltinRunner)> {
         &self.builtin_runners
     }
 
-    pub fn get_builtin_runners_as_mut(&mut self) -> &mut Vec<(String, BuiltinRunner)> {
+    pub fn get_builtin_runners_as_mut(&mut self) -> &mut Vec<(BuiltinName, BuiltinRunner)> {
         &mut self.builtin_runners
     }
 
+    ///Returns the builtin runner added under the given name, if any
+    pub fn get_builtin_runner(&self, name: BuiltinName) -> Option<&BuiltinRunner> {
+        self.builtin_runners
+            .iter()
+            .find(|(added, _)| *added == name)
+            .map(|(_, builtin)| builtin)
+    }
+
+    ///Adds a builtin runner under the given name, failing if a runner was already added under it
+    pub fn add_builtin_runner(
+        &mut self,
+        name: BuiltinName,
+        builtin: BuiltinRunner,
+    ) -> Result<(), RunnerError> {
+        if self.builtin_runners.iter().any(|(added, _)| *added == name) {
+            return Err(RunnerError::DuplicateBuiltin(name));
+        }
+        self.builtin_runners.push((name, builtin));
+        Ok(())
+    }
+
+    ///Returns the constants of the run, such as the prime and the layout
+    pub fn const_view(&self) -> &VmConstView {
+        &self.const_view
+    }
+
     ///Inserts a value into a memory address given by a Relocatable value
     pub fn insert_value<T: Into<MaybeRelocatable>>(
         &mut self,
         key: &Relocatable,
         val: T,
     ) -> Result<(), VirtualMachineError> {
-        self.memory.insert_value(key, val)
+        Ok(self.memory.insert_value(key, val)?)
     }
 
     ///Writes data into the memory at address ptr and returns the first address after the data.
@@ -840,6 +1813,18 @@ impl VirtualMachine {
         self.segments.load_data(&mut self.memory, ptr, data)
     }
 
+    ///Packs bytes into felts according to packing and writes them into the memory at address ptr.
+    ///Returns the first address after the data and the number of felts written.
+    pub fn write_bytes(
+        &mut self,
+        ptr: &Relocatable,
+        bytes: &[u8],
+        packing: PackingMode,
+    ) -> Result<(Relocatable, usize), MemoryError> {
+        self.segments
+            .write_bytes(&mut self.memory, ptr, bytes, packing)
+    }
+
     /// Writes args into the memory at address ptr and returns the first address after the data.
     /// Perfroms modulo on each element
     pub fn write_arg(
@@ -850,14 +1835,22 @@ impl VirtualMachine {
         self.segments.write_arg(&mut self.memory, ptr, arg)
     }
 
-    ///Gets `n_ret` return values from memory
-    pub fn get_return_values(&self, n_ret: usize) -> Result<Vec<MaybeRelocatable>, MemoryError> {
-        let addr = &self
-            .run_context
-            .get_ap()
-            .sub_usize(n_ret)
-            .map_err(|_| MemoryError::NumOutOfBounds)?;
-        self.memory.get_continuous_range(&addr.into(), n_ret)
+    ///Gets the `n_ret` return values at the top of the ap stack, in [ap - n_ret, ap).
+    ///Fails with MemoryGet naming the first address which holds no value
+    pub fn get_return_values(
+        &self,
+        n_ret: usize,
+    ) -> Result<Vec<MaybeRelocatable>, VirtualMachineError> {
+        let addr = self.run_context.get_ap().sub_usize(n_ret)?;
+        (0..n_ret)
+            .map(|i| {
+                let value_addr = addr + i;
+                self.memory
+                    .get(&value_addr)?
+                    .map(Cow::into_owned)
+                    .ok_or_else(|| VirtualMachineError::MemoryGet(value_addr.into()))
+            })
+            .collect()
     }
 
     ///Gets n elements from memory starting from addr (n being size)
@@ -889,7 +1882,7 @@ impl VirtualMachine {
 
     pub fn get_range_check_builtin(&self) -> Result<&RangeCheckBuiltinRunner, VirtualMachineError> {
         for (name, builtin) in &self.builtin_runners {
-            if name == &String::from("range_check") {
+            if *name == BuiltinName::RangeCheck {
                 if let BuiltinRunner::RangeCheck(range_check_builtin) = builtin {
                     return Ok(range_check_builtin);
                 };
@@ -902,7 +1895,7 @@ impl VirtualMachine {
         &mut self,
     ) -> Result<&mut SignatureBuiltinRunner, VirtualMachineError> {
         for (name, builtin) in self.get_builtin_runners_as_mut() {
-            if name == &String::from("ecdsa") {
+            if *name == BuiltinName::Ecdsa {
                 if let BuiltinRunner::Signature(signature_builtin) = builtin {
                     return Ok(signature_builtin);
                 };
@@ -915,14 +1908,98 @@ impl VirtualMachine {
         self.trace = None
     }
 
-    #[doc(hidden)]
-    pub fn set_ap(&mut self, ap: usize) {
-        self.run_context.set_ap(ap)
+    ///Sets ap, which must point into the execution segment
+    pub fn set_ap(&mut self, ap: Relocatable) -> Result<(), VirtualMachineError> {
+        self.check_register_segment(Register::AP, &ap)?;
+        self.run_context.set_ap(ap.offset);
+        Ok(())
     }
 
-    #[doc(hidden)]
-    pub fn set_fp(&mut self, fp: usize) {
-        self.run_context.set_fp(fp)
+    ///Sets fp, which must point into the execution segment
+    pub fn set_fp(&mut self, fp: Relocatable) -> Result<(), VirtualMachineError> {
+        self.check_register_segment(Register::FP, &fp)?;
+        self.run_context.set_fp(fp.offset);
+        Ok(())
+    }
+
+    //ap and fp are stored as offsets into the execution segment
+    fn check_register_segment(
+        &self,
+        register: Register,
+        value: &Relocatable,
+    ) -> Result<(), VirtualMachineError> {
+        if value.segment_index != self.run_context.execution_segment {
+            return Err(VirtualMachineError::RegisterOutsideExecutionSegment(
+                register, *value,
+            ));
+        }
+        Ok(())
+    }
+
+    ///Returns the cells written by hints into the program segment or a builtin segment, sorted by address.
+    ///Only cells written while origin tracking was enabled on the memory are reported.
+    pub fn get_hint_writes_into_protected_segments(&self) -> Vec<(Relocatable, &MemoryOrigin)> {
+        let program_segment = self.program_base.map(|base| base.segment_index);
+        self.memory
+            .get_hint_origins()
+            .into_iter()
+            .filter(|(addr, _)| {
+                Some(addr.segment_index) == program_segment
+                    || self
+                        .builtin_runners
+                        .iter()
+                        .any(|(_, builtin)| builtin.base() == addr.segment_index)
+            })
+            .collect()
+    }
+
+    ///Starts counting how many times each hint is executed, by hint code. Hints whose code isn't
+    ///exposed by the hint processor are counted under an empty code.
+    pub fn enable_hint_statistics(&mut self) {
+        self.hint_statistics.get_or_insert_with(HashMap::new);
+    }
+
+    ///Returns the number of executions of each hint, or None if enable_hint_statistics wasn't called
+    pub fn get_hint_statistics(&self) -> Option<&HashMap<String, usize>> {
+        self.hint_statistics.as_ref()
+    }
+
+    ///Makes CairoRunner::run_until_breakpoint pause before executing the instruction at pc
+    pub fn add_breakpoint(&mut self, pc: Relocatable) {
+        self.breakpoints.insert(pc);
+    }
+
+    ///Removes a breakpoint added with add_breakpoint, returning whether it was set
+    pub fn remove_breakpoint(&mut self, pc: &Relocatable) -> bool {
+        self.breakpoints.remove(pc)
+    }
+
+    ///Registers the extension executing the instructions that use the opcode reserved for extensions.
+    ///Without one, these instructions fail to decode.
+    pub fn set_opcode_extension(&mut self, extension: Box<dyn OpcodeExtension>) {
+        self.opcode_extension = Some(extension);
+    }
+
+    ///Registers a hook called around each step, hooks are called in the order they were added.
+    ///Hooks are kept when the vm is reset.
+    pub fn add_step_hook(&mut self, hook: Box<dyn StepHook>) {
+        self.step_hooks.push(hook);
+    }
+
+    ///Enables strict register checks with the given bounds, or disables them if None
+    pub fn set_register_bounds(&mut self, register_bounds: Option<RegisterBounds>) {
+        self.register_bounds = register_bounds;
+    }
+
+    ///Makes call instructions fail with MaxCallDepthExceeded once max_call_depth calls haven't
+    ///returned yet. None, the default, doesn't limit the call depth.
+    pub fn set_max_call_depth(&mut self, max_call_depth: Option<usize>) {
+        self.max_call_depth = max_call_depth;
+    }
+
+    ///Returns the number of call instructions executed that haven't returned yet
+    pub fn get_call_depth(&self) -> usize {
+        self.call_depth
     }
 
     #[doc(hidden)]
@@ -975,30 +2052,44 @@ mod tests {
     use super::*;
     use crate::{
         any_box,
-        hint_processor::builtin_hint_processor::builtin_hint_processor_definition::{
-            BuiltinHintProcessor, HintProcessorData,
+        hint_processor::{
+            builtin_hint_processor::builtin_hint_processor_definition::{
+                BuiltinHintProcessor, HintFunc, HintProcessorData,
+            },
+            hint_processor_definition::HintReference,
         },
         relocatable,
+        serde::deserialize_program::ApTracking,
         types::{
             instance_definitions::{
                 bitwise_instance_def::BitwiseInstanceDef, ec_op_instance_def::EcOpInstanceDef,
             },
-            instruction::{Op1Addr, Register},
+            instruction::{ApUpdate, Op1Addr, PcUpdate, Register},
             program::Program,
             relocatable::Relocatable,
         },
         utils::test_utils::*,
         vm::{
+            accessed_addresses::read_accessed_addresses,
             errors::memory_errors::MemoryError,
+            opcode_extension::RegisterUpdate,
             runners::{
-                builtin_runner::{BitwiseBuiltinRunner, EcOpBuiltinRunner, HashBuiltinRunner},
+                builtin_runner::{
+                    BitwiseBuiltinRunner, EcOpBuiltinRunner, HashBuiltinRunner, OutputBuiltinRunner,
+                },
                 cairo_runner::CairoRunner,
             },
+            state_transition::{next_ap, next_fp, next_pc, Registers},
         },
     };
 
     use felt::{felt_str, NewFelt};
-    use std::{collections::HashSet, path::Path};
+    use std::{
+        collections::HashSet,
+        path::Path,
+        rc::Rc,
+        sync::{Arc, Mutex},
+    };
 
     #[test]
     fn get_instruction_encoding_successful_without_imm() {
@@ -1041,7 +2132,6 @@ mod tests {
             off0: 1,
             off1: 2,
             off2: 3,
-            imm: None,
             dst_register: Register::FP,
             op0_register: Register::AP,
             op1_addr: Op1Addr::AP,
@@ -1053,17 +2143,19 @@ mod tests {
         };
 
         let operands = Operands {
-            dst: MaybeRelocatable::Int(Felt::new(11)),
+            dst: Cow::Owned(MaybeRelocatable::Int(Felt::new(11))),
             res: Some(MaybeRelocatable::Int(Felt::new(8))),
-            op0: MaybeRelocatable::Int(Felt::new(9)),
-            op1: MaybeRelocatable::Int(Felt::new(10)),
+            op0: Cow::Owned(MaybeRelocatable::Int(Felt::new(9))),
+            op1: Cow::Owned(MaybeRelocatable::Int(Felt::new(10))),
         };
 
         let mut vm = vm!();
         run_context!(vm, 4, 5, 6);
 
-        assert_eq!(Ok(()), vm.update_fp(&instruction, &operands));
-        assert_eq!(vm.run_context.fp, 7)
+        assert_eq!(
+            next_fp(&instruction, &operands, &vm.run_context.registers()).map(|fp| fp.offset),
+            Ok(7)
+        );
     }
 
     #[test]
@@ -1072,7 +2164,6 @@ mod tests {
             off0: 1,
             off1: 2,
             off2: 3,
-            imm: None,
             dst_register: Register::FP,
             op0_register: Register::AP,
             op1_addr: Op1Addr::AP,
@@ -1084,16 +2175,18 @@ mod tests {
         };
 
         let operands = Operands {
-            dst: mayberelocatable!(1, 6),
+            dst: Cow::Owned(mayberelocatable!(1, 6)),
             res: Some(mayberelocatable!(8)),
-            op0: mayberelocatable!(9),
-            op1: mayberelocatable!(10),
+            op0: Cow::Owned(mayberelocatable!(9)),
+            op1: Cow::Owned(mayberelocatable!(10)),
         };
 
-        let mut vm = vm!();
+        let vm = vm!();
 
-        assert_eq!(Ok(()), vm.update_fp(&instruction, &operands));
-        assert_eq!(vm.run_context.fp, 6)
+        assert_eq!(
+            next_fp(&instruction, &operands, &vm.run_context.registers()).map(|fp| fp.offset),
+            Ok(6)
+        );
     }
 
     #[test]
@@ -1102,7 +2195,6 @@ mod tests {
             off0: 1,
             off1: 2,
             off2: 3,
-            imm: None,
             dst_register: Register::FP,
             op0_register: Register::AP,
             op1_addr: Op1Addr::AP,
@@ -1114,16 +2206,18 @@ mod tests {
         };
 
         let operands = Operands {
-            dst: MaybeRelocatable::Int(Felt::new(11)),
+            dst: Cow::Owned(MaybeRelocatable::Int(Felt::new(11))),
             res: Some(MaybeRelocatable::Int(Felt::new(8))),
-            op0: MaybeRelocatable::Int(Felt::new(9)),
-            op1: MaybeRelocatable::Int(Felt::new(10)),
+            op0: Cow::Owned(MaybeRelocatable::Int(Felt::new(9))),
+            op1: Cow::Owned(MaybeRelocatable::Int(Felt::new(10))),
         };
 
-        let mut vm = vm!();
+        let vm = vm!();
 
-        assert_eq!(Ok(()), vm.update_fp(&instruction, &operands));
-        assert_eq!(vm.run_context.fp, 0)
+        assert_eq!(
+            next_fp(&instruction, &operands, &vm.run_context.registers()).map(|fp| fp.offset),
+            Ok(0)
+        );
     }
 
     #[test]
@@ -1132,7 +2226,6 @@ mod tests {
             off0: 1,
             off1: 2,
             off2: 3,
-            imm: None,
             dst_register: Register::FP,
             op0_register: Register::AP,
             op1_addr: Op1Addr::AP,
@@ -1144,17 +2237,19 @@ mod tests {
         };
 
         let operands = Operands {
-            dst: MaybeRelocatable::Int(Felt::new(11)),
+            dst: Cow::Owned(MaybeRelocatable::Int(Felt::new(11))),
             res: Some(MaybeRelocatable::Int(Felt::new(8))),
-            op0: MaybeRelocatable::Int(Felt::new(9)),
-            op1: MaybeRelocatable::Int(Felt::new(10)),
+            op0: Cow::Owned(MaybeRelocatable::Int(Felt::new(9))),
+            op1: Cow::Owned(MaybeRelocatable::Int(Felt::new(10))),
         };
 
         let mut vm = vm!();
         run_context!(vm, 4, 5, 6);
 
-        assert_eq!(Ok(()), vm.update_fp(&instruction, &operands));
-        assert_eq!(vm.run_context.fp, 11)
+        assert_eq!(
+            next_fp(&instruction, &operands, &vm.run_context.registers()).map(|fp| fp.offset),
+            Ok(11)
+        );
     }
 
     #[test]
@@ -1163,7 +2258,6 @@ mod tests {
             off0: 1,
             off1: 2,
             off2: 3,
-            imm: None,
             dst_register: Register::FP,
             op0_register: Register::AP,
             op1_addr: Op1Addr::AP,
@@ -1175,10 +2269,10 @@ mod tests {
         };
 
         let operands = Operands {
-            dst: MaybeRelocatable::Int(Felt::new(11)),
+            dst: Cow::Owned(MaybeRelocatable::Int(Felt::new(11))),
             res: Some(MaybeRelocatable::Int(Felt::new(8))),
-            op0: MaybeRelocatable::Int(Felt::new(9)),
-            op1: MaybeRelocatable::Int(Felt::new(10)),
+            op0: Cow::Owned(MaybeRelocatable::Int(Felt::new(9))),
+            op1: Cow::Owned(MaybeRelocatable::Int(Felt::new(10))),
         };
 
         let mut vm = VirtualMachine::new(false);
@@ -1186,8 +2280,10 @@ mod tests {
         vm.run_context.ap = 5;
         vm.run_context.fp = 6;
 
-        assert_eq!(Ok(()), vm.update_ap(&instruction, &operands));
-        assert_eq!(vm.run_context.ap, 13);
+        assert_eq!(
+            next_ap(&instruction, &operands, &vm.run_context.registers()).map(|ap| ap.offset),
+            Ok(13)
+        );
     }
 
     #[test]
@@ -1196,7 +2292,6 @@ mod tests {
             off0: 1,
             off1: 2,
             off2: 3,
-            imm: None,
             dst_register: Register::FP,
             op0_register: Register::AP,
             op1_addr: Op1Addr::AP,
@@ -1208,10 +2303,10 @@ mod tests {
         };
 
         let operands = Operands {
-            dst: MaybeRelocatable::Int(Felt::new(11)),
+            dst: Cow::Owned(MaybeRelocatable::Int(Felt::new(11))),
             res: None,
-            op0: MaybeRelocatable::Int(Felt::new(9)),
-            op1: MaybeRelocatable::Int(Felt::new(10)),
+            op0: Cow::Owned(MaybeRelocatable::Int(Felt::new(9))),
+            op1: Cow::Owned(MaybeRelocatable::Int(Felt::new(10))),
         };
 
         let mut vm = vm!();
@@ -1220,7 +2315,7 @@ mod tests {
         vm.run_context.fp = 6;
 
         assert_eq!(
-            vm.update_ap(&instruction, &operands),
+            next_ap(&instruction, &operands, &vm.run_context.registers()).map(|ap| ap.offset),
             Err(VirtualMachineError::UnconstrainedResAdd)
         );
     }
@@ -1231,7 +2326,6 @@ mod tests {
             off0: 1,
             off1: 2,
             off2: 3,
-            imm: None,
             dst_register: Register::FP,
             op0_register: Register::AP,
             op1_addr: Op1Addr::AP,
@@ -1243,10 +2337,10 @@ mod tests {
         };
 
         let operands = Operands {
-            dst: MaybeRelocatable::Int(Felt::new(11)),
+            dst: Cow::Owned(MaybeRelocatable::Int(Felt::new(11))),
             res: Some(MaybeRelocatable::Int(Felt::new(8))),
-            op0: MaybeRelocatable::Int(Felt::new(9)),
-            op1: MaybeRelocatable::Int(Felt::new(10)),
+            op0: Cow::Owned(MaybeRelocatable::Int(Felt::new(9))),
+            op1: Cow::Owned(MaybeRelocatable::Int(Felt::new(10))),
         };
 
         let mut vm = vm!();
@@ -1254,8 +2348,10 @@ mod tests {
         vm.run_context.ap = 5;
         vm.run_context.fp = 6;
 
-        assert_eq!(Ok(()), vm.update_ap(&instruction, &operands));
-        assert_eq!(vm.run_context.ap, 6);
+        assert_eq!(
+            next_ap(&instruction, &operands, &vm.run_context.registers()).map(|ap| ap.offset),
+            Ok(6)
+        );
     }
 
     #[test]
@@ -1264,7 +2360,6 @@ mod tests {
             off0: 1,
             off1: 2,
             off2: 3,
-            imm: None,
             dst_register: Register::FP,
             op0_register: Register::AP,
             op1_addr: Op1Addr::AP,
@@ -1276,10 +2371,10 @@ mod tests {
         };
 
         let operands = Operands {
-            dst: MaybeRelocatable::Int(Felt::new(11)),
+            dst: Cow::Owned(MaybeRelocatable::Int(Felt::new(11))),
             res: Some(MaybeRelocatable::Int(Felt::new(8))),
-            op0: MaybeRelocatable::Int(Felt::new(9)),
-            op1: MaybeRelocatable::Int(Felt::new(10)),
+            op0: Cow::Owned(MaybeRelocatable::Int(Felt::new(9))),
+            op1: Cow::Owned(MaybeRelocatable::Int(Felt::new(10))),
         };
 
         let mut vm = vm!();
@@ -1287,8 +2382,10 @@ mod tests {
         vm.run_context.ap = 5;
         vm.run_context.fp = 6;
 
-        assert_eq!(Ok(()), vm.update_ap(&instruction, &operands));
-        assert_eq!(vm.run_context.ap, 7);
+        assert_eq!(
+            next_ap(&instruction, &operands, &vm.run_context.registers()).map(|ap| ap.offset),
+            Ok(7)
+        );
     }
 
     #[test]
@@ -1297,7 +2394,6 @@ mod tests {
             off0: 1,
             off1: 2,
             off2: 3,
-            imm: None,
             dst_register: Register::FP,
             op0_register: Register::AP,
             op1_addr: Op1Addr::AP,
@@ -1309,10 +2405,10 @@ mod tests {
         };
 
         let operands = Operands {
-            dst: MaybeRelocatable::Int(Felt::new(11)),
+            dst: Cow::Owned(MaybeRelocatable::Int(Felt::new(11))),
             res: Some(MaybeRelocatable::Int(Felt::new(8))),
-            op0: MaybeRelocatable::Int(Felt::new(9)),
-            op1: MaybeRelocatable::Int(Felt::new(10)),
+            op0: Cow::Owned(MaybeRelocatable::Int(Felt::new(9))),
+            op1: Cow::Owned(MaybeRelocatable::Int(Felt::new(10))),
         };
 
         let mut vm = vm!();
@@ -1320,8 +2416,10 @@ mod tests {
         vm.run_context.ap = 5;
         vm.run_context.fp = 6;
 
-        assert_eq!(Ok(()), vm.update_ap(&instruction, &operands));
-        assert_eq!(vm.run_context.ap, 5);
+        assert_eq!(
+            next_ap(&instruction, &operands, &vm.run_context.registers()).map(|ap| ap.offset),
+            Ok(5)
+        );
     }
 
     #[test]
@@ -1330,7 +2428,6 @@ mod tests {
             off0: 1,
             off1: 2,
             off2: 3,
-            imm: None,
             dst_register: Register::FP,
             op0_register: Register::AP,
             op1_addr: Op1Addr::AP,
@@ -1342,16 +2439,18 @@ mod tests {
         };
 
         let operands = Operands {
-            dst: MaybeRelocatable::Int(Felt::new(11)),
+            dst: Cow::Owned(MaybeRelocatable::Int(Felt::new(11))),
             res: Some(MaybeRelocatable::Int(Felt::new(8))),
-            op0: MaybeRelocatable::Int(Felt::new(9)),
-            op1: MaybeRelocatable::Int(Felt::new(10)),
+            op0: Cow::Owned(MaybeRelocatable::Int(Felt::new(9))),
+            op1: Cow::Owned(MaybeRelocatable::Int(Felt::new(10))),
         };
 
-        let mut vm = vm!();
+        let vm = vm!();
 
-        assert_eq!(Ok(()), vm.update_pc(&instruction, &operands));
-        assert_eq!(vm.run_context.pc, Relocatable::from((0, 1)));
+        assert_eq!(
+            next_pc(&instruction, &operands, &vm.run_context.registers()),
+            Ok(Relocatable::from((0, 1)))
+        );
     }
 
     #[test]
@@ -1360,10 +2459,9 @@ mod tests {
             off0: 1,
             off1: 2,
             off2: 3,
-            imm: Some(Felt::new(5)),
             dst_register: Register::FP,
             op0_register: Register::AP,
-            op1_addr: Op1Addr::AP,
+            op1_addr: Op1Addr::Imm,
             res: Res::Add,
             pc_update: PcUpdate::Regular,
             ap_update: ApUpdate::Regular,
@@ -1372,16 +2470,18 @@ mod tests {
         };
 
         let operands = Operands {
-            dst: MaybeRelocatable::Int(Felt::new(11)),
+            dst: Cow::Owned(MaybeRelocatable::Int(Felt::new(11))),
             res: Some(MaybeRelocatable::Int(Felt::new(8))),
-            op0: MaybeRelocatable::Int(Felt::new(9)),
-            op1: MaybeRelocatable::Int(Felt::new(10)),
+            op0: Cow::Owned(MaybeRelocatable::Int(Felt::new(9))),
+            op1: Cow::Owned(MaybeRelocatable::Int(Felt::new(10))),
         };
 
-        let mut vm = vm!();
+        let vm = vm!();
 
-        assert_eq!(Ok(()), vm.update_pc(&instruction, &operands));
-        assert_eq!(vm.run_context.pc, Relocatable::from((0, 2)));
+        assert_eq!(
+            next_pc(&instruction, &operands, &vm.run_context.registers()),
+            Ok(Relocatable::from((0, 2)))
+        );
     }
 
     #[test]
@@ -1390,7 +2490,6 @@ mod tests {
             off0: 1,
             off1: 2,
             off2: 3,
-            imm: None,
             dst_register: Register::FP,
             op0_register: Register::AP,
             op1_addr: Op1Addr::AP,
@@ -1402,16 +2501,18 @@ mod tests {
         };
 
         let operands = Operands {
-            dst: mayberelocatable!(1, 11),
+            dst: Cow::Owned(mayberelocatable!(1, 11)),
             res: Some(mayberelocatable!(0, 8)),
-            op0: MaybeRelocatable::Int(Felt::new(9)),
-            op1: MaybeRelocatable::Int(Felt::new(10)),
+            op0: Cow::Owned(MaybeRelocatable::Int(Felt::new(9))),
+            op1: Cow::Owned(MaybeRelocatable::Int(Felt::new(10))),
         };
 
-        let mut vm = vm!();
+        let vm = vm!();
 
-        assert_eq!(Ok(()), vm.update_pc(&instruction, &operands));
-        assert_eq!(vm.run_context.pc, Relocatable::from((0, 8)));
+        assert_eq!(
+            next_pc(&instruction, &operands, &vm.run_context.registers()),
+            Ok(Relocatable::from((0, 8)))
+        );
     }
 
     #[test]
@@ -1420,7 +2521,6 @@ mod tests {
             off0: 1,
             off1: 2,
             off2: 3,
-            imm: None,
             dst_register: Register::FP,
             op0_register: Register::AP,
             op1_addr: Op1Addr::AP,
@@ -1432,10 +2532,10 @@ mod tests {
         };
 
         let operands = Operands {
-            dst: MaybeRelocatable::Int(Felt::new(11)),
+            dst: Cow::Owned(MaybeRelocatable::Int(Felt::new(11))),
             res: None,
-            op0: MaybeRelocatable::Int(Felt::new(9)),
-            op1: MaybeRelocatable::Int(Felt::new(10)),
+            op0: Cow::Owned(MaybeRelocatable::Int(Felt::new(9))),
+            op1: Cow::Owned(MaybeRelocatable::Int(Felt::new(10))),
         };
 
         let mut vm = vm!();
@@ -1444,49 +2544,59 @@ mod tests {
         vm.run_context.fp = 6;
 
         assert_eq!(
-            vm.update_pc(&instruction, &operands),
+            next_pc(&instruction, &operands, &vm.run_context.registers()),
             Err(VirtualMachineError::UnconstrainedResJump)
         );
     }
 
     #[test]
-    fn update_pc_jump_rel_with_int_res() {
+    fn run_instruction_jump_without_res_dumps_operands() {
         let instruction = Instruction {
             off0: 1,
             off1: 2,
             off2: 3,
-            imm: None,
             dst_register: Register::FP,
             op0_register: Register::AP,
             op1_addr: Op1Addr::AP,
-            res: Res::Add,
-            pc_update: PcUpdate::JumpRel,
+            res: Res::Unconstrained,
+            pc_update: PcUpdate::Jump,
             ap_update: ApUpdate::Regular,
             fp_update: FpUpdate::Regular,
             opcode: Opcode::NOp,
         };
-
-        let operands = Operands {
-            dst: MaybeRelocatable::Int(Felt::new(11)),
-            res: Some(MaybeRelocatable::Int(Felt::new(8))),
-            op0: MaybeRelocatable::Int(Felt::new(9)),
-            op1: MaybeRelocatable::Int(Felt::new(10)),
-        };
-
         let mut vm = vm!();
-        run_context!(vm, 1, 1, 1);
+        run_context!(vm, 4, 5, 8);
+        vm.memory = memory![((1, 7), 9), ((1, 8), 10), ((1, 9), 11)];
 
-        assert_eq!(Ok(()), vm.update_pc(&instruction, &operands));
-        assert_eq!(vm.run_context.pc, Relocatable::from((0, 9)));
+        assert_eq!(
+            vm.run_instruction(instruction),
+            Err(VirtualMachineError::UnconstrainedResJump)
+        );
+        let operands = vm.get_failed_operands().unwrap();
+        assert_eq!(
+            operands,
+            &OperandsDump {
+                dst_addr: relocatable!(1, 9),
+                dst: mayberelocatable!(11),
+                op0_addr: relocatable!(1, 7),
+                op0: mayberelocatable!(9),
+                op1_addr: relocatable!(1, 8),
+                op1: mayberelocatable!(10),
+                res: None,
+            }
+        );
+        assert_eq!(
+            operands.to_string(),
+            "Operands: dst=[1:9]=11, op0=[1:7]=9, op1=[1:8]=10, res=unconstrained"
+        );
     }
 
     #[test]
-    fn update_pc_jump_rel_without_res() {
+    fn update_pc_jump_rel_with_int_res() {
         let instruction = Instruction {
             off0: 1,
             off1: 2,
             off2: 3,
-            imm: None,
             dst_register: Register::FP,
             op0_register: Register::AP,
             op1_addr: Op1Addr::AP,
@@ -1498,219 +2608,412 @@ mod tests {
         };
 
         let operands = Operands {
-            dst: MaybeRelocatable::Int(Felt::new(11)),
-            res: None,
-            op0: MaybeRelocatable::Int(Felt::new(9)),
-            op1: MaybeRelocatable::Int(Felt::new(10)),
+            dst: Cow::Owned(MaybeRelocatable::Int(Felt::new(11))),
+            res: Some(MaybeRelocatable::Int(Felt::new(8))),
+            op0: Cow::Owned(MaybeRelocatable::Int(Felt::new(9))),
+            op1: Cow::Owned(MaybeRelocatable::Int(Felt::new(10))),
         };
 
         let mut vm = vm!();
+        run_context!(vm, 1, 1, 1);
 
         assert_eq!(
-            vm.update_pc(&instruction, &operands),
-            Err(VirtualMachineError::UnconstrainedResJumpRel)
+            next_pc(&instruction, &operands, &vm.run_context.registers()),
+            Ok(Relocatable::from((0, 9)))
         );
     }
 
     #[test]
-    fn update_pc_jump_rel_with_non_int_res() {
+    fn update_pc_jump_rel_backwards() {
         let instruction = Instruction {
             off0: 1,
             off1: 2,
             off2: 3,
-            imm: None,
             dst_register: Register::FP,
             op0_register: Register::AP,
             op1_addr: Op1Addr::AP,
-            res: Res::Add,
+            res: Res::Op1,
             pc_update: PcUpdate::JumpRel,
             ap_update: ApUpdate::Regular,
             fp_update: FpUpdate::Regular,
             opcode: Opcode::NOp,
         };
 
+        //PRIME - 4, as encoded by `jmp rel -4`
         let operands = Operands {
-            dst: MaybeRelocatable::Int(Felt::new(11)),
-            res: Some(MaybeRelocatable::from((1, 4))),
-            op0: MaybeRelocatable::Int(Felt::new(9)),
-            op1: MaybeRelocatable::Int(Felt::new(10)),
+            dst: Cow::Owned(MaybeRelocatable::Int(Felt::new(11))),
+            res: Some(MaybeRelocatable::Int(Felt::zero() - Felt::new(4))),
+            op0: Cow::Owned(MaybeRelocatable::Int(Felt::new(9))),
+            op1: Cow::Owned(MaybeRelocatable::Int(Felt::zero() - Felt::new(4))),
         };
 
         let mut vm = vm!();
+        run_context!(vm, 10, 1, 1);
 
         assert_eq!(
-            Err(VirtualMachineError::PureValue),
-            vm.update_pc(&instruction, &operands)
+            next_pc(&instruction, &operands, &vm.run_context.registers()),
+            Ok(Relocatable::from((0, 6)))
         );
     }
 
     #[test]
-    fn update_pc_jnz_dst_is_zero() {
+    fn update_pc_jump_rel_backwards_before_segment_start() {
         let instruction = Instruction {
             off0: 1,
             off1: 2,
             off2: 3,
-            imm: None,
             dst_register: Register::FP,
             op0_register: Register::AP,
             op1_addr: Op1Addr::AP,
-            res: Res::Add,
-            pc_update: PcUpdate::Jnz,
+            res: Res::Op1,
+            pc_update: PcUpdate::JumpRel,
             ap_update: ApUpdate::Regular,
             fp_update: FpUpdate::Regular,
             opcode: Opcode::NOp,
         };
 
         let operands = Operands {
-            dst: MaybeRelocatable::Int(Felt::new(0)),
-            res: Some(MaybeRelocatable::Int(Felt::new(0))),
-            op0: MaybeRelocatable::Int(Felt::new(9)),
-            op1: MaybeRelocatable::Int(Felt::new(10)),
+            dst: Cow::Owned(MaybeRelocatable::Int(Felt::new(11))),
+            res: Some(MaybeRelocatable::Int(Felt::zero() - Felt::new(4))),
+            op0: Cow::Owned(MaybeRelocatable::Int(Felt::new(9))),
+            op1: Cow::Owned(MaybeRelocatable::Int(Felt::zero() - Felt::new(4))),
         };
 
         let mut vm = vm!();
+        run_context!(vm, 2, 1, 1);
 
-        assert_eq!(Ok(()), vm.update_pc(&instruction, &operands));
-        assert_eq!(vm.run_context.pc, Relocatable::from((0, 1)));
+        assert_eq!(
+            next_pc(&instruction, &operands, &vm.run_context.registers()),
+            Err(VirtualMachineError::CantSubOffset(2, 4))
+        );
     }
 
     #[test]
-    fn update_pc_jnz_dst_is_not_zero() {
+    fn update_pc_jump_rel_without_res() {
         let instruction = Instruction {
             off0: 1,
             off1: 2,
             off2: 3,
-            imm: None,
             dst_register: Register::FP,
             op0_register: Register::AP,
             op1_addr: Op1Addr::AP,
             res: Res::Add,
-            pc_update: PcUpdate::Jnz,
+            pc_update: PcUpdate::JumpRel,
             ap_update: ApUpdate::Regular,
             fp_update: FpUpdate::Regular,
             opcode: Opcode::NOp,
         };
 
         let operands = Operands {
-            dst: MaybeRelocatable::Int(Felt::new(11)),
-            res: Some(MaybeRelocatable::Int(Felt::new(8))),
-            op0: MaybeRelocatable::Int(Felt::new(9)),
-            op1: MaybeRelocatable::Int(Felt::new(10)),
+            dst: Cow::Owned(MaybeRelocatable::Int(Felt::new(11))),
+            res: None,
+            op0: Cow::Owned(MaybeRelocatable::Int(Felt::new(9))),
+            op1: Cow::Owned(MaybeRelocatable::Int(Felt::new(10))),
         };
 
-        let mut vm = vm!();
+        let vm = vm!();
 
-        assert_eq!(Ok(()), vm.update_pc(&instruction, &operands));
-        assert_eq!(vm.run_context.pc, Relocatable::from((0, 10)));
+        assert_eq!(
+            next_pc(&instruction, &operands, &vm.run_context.registers()),
+            Err(VirtualMachineError::UnconstrainedResJumpRel)
+        );
     }
 
     #[test]
-    fn update_registers_all_regular() {
+    fn update_pc_jump_rel_with_non_int_res() {
         let instruction = Instruction {
             off0: 1,
             off1: 2,
             off2: 3,
-            imm: None,
             dst_register: Register::FP,
             op0_register: Register::AP,
             op1_addr: Op1Addr::AP,
             res: Res::Add,
-            pc_update: PcUpdate::Regular,
+            pc_update: PcUpdate::JumpRel,
             ap_update: ApUpdate::Regular,
             fp_update: FpUpdate::Regular,
             opcode: Opcode::NOp,
         };
 
         let operands = Operands {
-            dst: MaybeRelocatable::Int(Felt::new(11)),
-            res: Some(MaybeRelocatable::Int(Felt::new(8))),
-            op0: MaybeRelocatable::Int(Felt::new(9)),
-            op1: MaybeRelocatable::Int(Felt::new(10)),
+            dst: Cow::Owned(MaybeRelocatable::Int(Felt::new(11))),
+            res: Some(MaybeRelocatable::from((1, 4))),
+            op0: Cow::Owned(MaybeRelocatable::Int(Felt::new(9))),
+            op1: Cow::Owned(MaybeRelocatable::Int(Felt::new(10))),
         };
 
-        let mut vm = vm!();
-        vm.run_context.pc = Relocatable::from((0, 4));
-        vm.run_context.ap = 5;
-        vm.run_context.fp = 6;
+        let vm = vm!();
 
-        assert_eq!(Ok(()), vm.update_registers(instruction, operands));
-        assert_eq!(vm.run_context.pc, Relocatable::from((0, 5)));
-        assert_eq!(vm.run_context.ap, 5);
-        assert_eq!(vm.run_context.fp, 6);
+        assert_eq!(
+            Err(VirtualMachineError::PureValue),
+            next_pc(&instruction, &operands, &vm.run_context.registers())
+        );
     }
 
     #[test]
-    fn update_registers_mixed_types() {
+    fn update_pc_jnz_dst_is_zero() {
         let instruction = Instruction {
             off0: 1,
             off1: 2,
             off2: 3,
-            imm: None,
             dst_register: Register::FP,
             op0_register: Register::AP,
             op1_addr: Op1Addr::AP,
             res: Res::Add,
-            pc_update: PcUpdate::JumpRel,
-            ap_update: ApUpdate::Add2,
-            fp_update: FpUpdate::Dst,
+            pc_update: PcUpdate::Jnz,
+            ap_update: ApUpdate::Regular,
+            fp_update: FpUpdate::Regular,
             opcode: Opcode::NOp,
         };
 
         let operands = Operands {
-            dst: MaybeRelocatable::from((1, 11)),
-            res: Some(MaybeRelocatable::Int(Felt::new(8))),
-            op0: MaybeRelocatable::Int(Felt::new(9)),
-            op1: MaybeRelocatable::Int(Felt::new(10)),
+            dst: Cow::Owned(MaybeRelocatable::Int(Felt::new(0))),
+            res: Some(MaybeRelocatable::Int(Felt::new(0))),
+            op0: Cow::Owned(MaybeRelocatable::Int(Felt::new(9))),
+            op1: Cow::Owned(MaybeRelocatable::Int(Felt::new(10))),
         };
 
-        let mut vm = vm!();
-        run_context!(vm, 4, 5, 6);
-
-        assert_eq!(Ok(()), vm.update_registers(instruction, operands));
-        assert_eq!(vm.run_context.pc, Relocatable::from((0, 12)));
-        assert_eq!(vm.run_context.ap, 7);
-        assert_eq!(vm.run_context.fp, 11);
-    }
-
-    #[test]
-    fn is_zero_int_value() {
-        let value = MaybeRelocatable::Int(Felt::new(1));
-        assert_eq!(Ok(false), VirtualMachine::is_zero(&value));
-    }
-
-    #[test]
-    fn is_zero_relocatable_value() {
-        let value = MaybeRelocatable::from((1, 2));
-        assert_eq!(Ok(false), VirtualMachine::is_zero(&value));
-    }
+        let vm = vm!();
 
-    #[test]
-    fn is_zero_relocatable_value_negative() {
-        let value = MaybeRelocatable::from((1, 0));
         assert_eq!(
-            Err(VirtualMachineError::PureValue),
-            VirtualMachine::is_zero(&value)
+            next_pc(&instruction, &operands, &vm.run_context.registers()),
+            Ok(Relocatable::from((0, 1)))
         );
     }
 
     #[test]
-    fn deduce_op0_opcode_call() {
+    fn update_pc_jnz_dst_is_not_zero() {
         let instruction = Instruction {
             off0: 1,
             off1: 2,
             off2: 3,
-            imm: None,
             dst_register: Register::FP,
             op0_register: Register::AP,
             op1_addr: Op1Addr::AP,
             res: Res::Add,
-            pc_update: PcUpdate::Jump,
+            pc_update: PcUpdate::Jnz,
             ap_update: ApUpdate::Regular,
             fp_update: FpUpdate::Regular,
-            opcode: Opcode::Call,
+            opcode: Opcode::NOp,
         };
 
-        let vm = vm!();
+        let operands = Operands {
+            dst: Cow::Owned(MaybeRelocatable::Int(Felt::new(11))),
+            res: Some(MaybeRelocatable::Int(Felt::new(8))),
+            op0: Cow::Owned(MaybeRelocatable::Int(Felt::new(9))),
+            op1: Cow::Owned(MaybeRelocatable::Int(Felt::new(10))),
+        };
+
+        let vm = vm!();
+
+        assert_eq!(
+            next_pc(&instruction, &operands, &vm.run_context.registers()),
+            Ok(Relocatable::from((0, 10)))
+        );
+    }
+
+    #[test]
+    fn update_pc_jnz_relocatable_jump_offset() {
+        let instruction = Instruction {
+            off0: -1,
+            off1: -1,
+            off2: 0,
+            dst_register: Register::AP,
+            op0_register: Register::FP,
+            op1_addr: Op1Addr::FP,
+            res: Res::Unconstrained,
+            pc_update: PcUpdate::Jnz,
+            ap_update: ApUpdate::Regular,
+            fp_update: FpUpdate::Regular,
+            opcode: Opcode::NOp,
+        };
+
+        let operands = Operands {
+            dst: Cow::Owned(mayberelocatable!(1)),
+            res: None,
+            op0: Cow::Owned(mayberelocatable!(1, 0)),
+            op1: Cow::Owned(mayberelocatable!(1, 0)),
+        };
+
+        let vm = vm!();
+
+        assert_eq!(
+            next_pc(&instruction, &operands, &vm.run_context.registers()),
+            Err(VirtualMachineError::JumpOffsetNotInt(mayberelocatable!(
+                1, 0
+            )))
+        );
+    }
+
+    //[ap] = 3, ap++; loop: [ap] = [ap - 1] - 1, ap++; jmp rel -2 if [ap - 1] != 0
+    #[test]
+    fn run_jnz_countdown_loop() {
+        let mut vm = vm!();
+        let data: Vec<MaybeRelocatable> = [
+            Felt::new(0x480680017fff8000_i64),
+            Felt::new(3),
+            Felt::new(0x482480017fff8000_i64),
+            Felt::zero() - 1_u32,
+            Felt::new(0x20680017fff7fff_i64),
+            Felt::zero() - 2_u32,
+        ]
+        .into_iter()
+        .map(MaybeRelocatable::from)
+        .collect();
+        vm.load_program(&data, None).unwrap();
+        vm.add_memory_segment();
+        vm.insert_value(&relocatable!(1, 0), 0_usize).unwrap();
+        run_context!(vm, 0, 1, 1);
+
+        let mut steps = 0;
+        while vm.run_context.pc != relocatable!(0, 6) {
+            vm.step_instruction().unwrap();
+            steps += 1;
+        }
+        assert_eq!(steps, 7);
+        assert_eq!(vm.run_context.ap, 5);
+        check_memory![
+            vm.memory,
+            ((1, 1), 3),
+            ((1, 2), 2),
+            ((1, 3), 1),
+            ((1, 4), 0)
+        ];
+    }
+
+    //jmp rel [fp] if [ap - 1] != 0, with a pointer at [fp]
+    #[test]
+    fn run_jnz_with_relocatable_jump_offset() {
+        let mut vm = vm!();
+        vm.memory = memory![
+            ((0, 0), 0x20a80007fff7fff_i64),
+            ((1, 0), 0),
+            ((1, 1), (0, 0)),
+            ((1, 2), 1)
+        ];
+        run_context!(vm, 0, 3, 1);
+
+        assert_eq!(
+            vm.step_instruction(),
+            Err(VirtualMachineError::JumpOffsetNotInt(mayberelocatable!(
+                0, 0
+            )))
+        );
+        assert_eq!(vm.run_context.pc, relocatable!(0, 0));
+    }
+
+    #[test]
+    fn update_registers_all_regular() {
+        let instruction = Instruction {
+            off0: 1,
+            off1: 2,
+            off2: 3,
+            dst_register: Register::FP,
+            op0_register: Register::AP,
+            op1_addr: Op1Addr::AP,
+            res: Res::Add,
+            pc_update: PcUpdate::Regular,
+            ap_update: ApUpdate::Regular,
+            fp_update: FpUpdate::Regular,
+            opcode: Opcode::NOp,
+        };
+
+        let operands = Operands {
+            dst: Cow::Owned(MaybeRelocatable::Int(Felt::new(11))),
+            res: Some(MaybeRelocatable::Int(Felt::new(8))),
+            op0: Cow::Owned(MaybeRelocatable::Int(Felt::new(9))),
+            op1: Cow::Owned(MaybeRelocatable::Int(Felt::new(10))),
+        };
+
+        let mut vm = vm!();
+        vm.run_context.pc = Relocatable::from((0, 4));
+        vm.run_context.ap = 5;
+        vm.run_context.fp = 6;
+
+        assert_eq!(
+            next_registers(&instruction, &operands, &vm.run_context.registers()),
+            Ok(Registers {
+                pc: Relocatable::from((0, 5)),
+                ap: Relocatable::from((1, 5)),
+                fp: Relocatable::from((1, 6)),
+            })
+        );
+    }
+
+    #[test]
+    fn update_registers_mixed_types() {
+        let instruction = Instruction {
+            off0: 1,
+            off1: 2,
+            off2: 3,
+            dst_register: Register::FP,
+            op0_register: Register::AP,
+            op1_addr: Op1Addr::AP,
+            res: Res::Add,
+            pc_update: PcUpdate::JumpRel,
+            ap_update: ApUpdate::Add2,
+            fp_update: FpUpdate::Dst,
+            opcode: Opcode::NOp,
+        };
+
+        let operands = Operands {
+            dst: Cow::Owned(MaybeRelocatable::from((1, 11))),
+            res: Some(MaybeRelocatable::Int(Felt::new(8))),
+            op0: Cow::Owned(MaybeRelocatable::Int(Felt::new(9))),
+            op1: Cow::Owned(MaybeRelocatable::Int(Felt::new(10))),
+        };
+
+        let mut vm = vm!();
+        run_context!(vm, 4, 5, 6);
+
+        assert_eq!(
+            next_registers(&instruction, &operands, &vm.run_context.registers()),
+            Ok(Registers {
+                pc: Relocatable::from((0, 12)),
+                ap: Relocatable::from((1, 7)),
+                fp: Relocatable::from((1, 11)),
+            })
+        );
+    }
+
+    #[test]
+    fn is_zero_int_value() {
+        let value = MaybeRelocatable::Int(Felt::new(1));
+        assert_eq!(Ok(false), VirtualMachine::is_zero(&value));
+    }
+
+    #[test]
+    fn is_zero_relocatable_value() {
+        let value = MaybeRelocatable::from((1, 2));
+        assert_eq!(Ok(false), VirtualMachine::is_zero(&value));
+    }
+
+    #[test]
+    fn is_zero_relocatable_value_negative() {
+        let value = MaybeRelocatable::from((1, 0));
+        assert_eq!(
+            Err(VirtualMachineError::PureValue),
+            VirtualMachine::is_zero(&value)
+        );
+    }
+
+    #[test]
+    fn deduce_op0_opcode_call() {
+        let instruction = Instruction {
+            off0: 1,
+            off1: 2,
+            off2: 3,
+            dst_register: Register::FP,
+            op0_register: Register::AP,
+            op1_addr: Op1Addr::AP,
+            res: Res::Add,
+            pc_update: PcUpdate::Jump,
+            ap_update: ApUpdate::Regular,
+            fp_update: FpUpdate::Regular,
+            opcode: Opcode::Call,
+        };
+
+        let vm = vm!();
 
         assert_eq!(
             Ok((Some(MaybeRelocatable::from((0, 1))), None)),
@@ -1724,7 +3027,6 @@ mod tests {
             off0: 1,
             off1: 2,
             off2: 3,
-            imm: None,
             dst_register: Register::FP,
             op0_register: Register::AP,
             op1_addr: Op1Addr::AP,
@@ -1754,7 +3056,6 @@ mod tests {
             off0: 1,
             off1: 2,
             off2: 3,
-            imm: None,
             dst_register: Register::FP,
             op0_register: Register::AP,
             op1_addr: Op1Addr::AP,
@@ -1776,7 +3077,6 @@ mod tests {
             off0: 1,
             off1: 2,
             off2: 3,
-            imm: None,
             dst_register: Register::FP,
             op0_register: Register::AP,
             op1_addr: Op1Addr::AP,
@@ -1800,13 +3100,44 @@ mod tests {
         );
     }
 
+    #[test]
+    fn deduce_op0_opcode_assert_eq_res_mul_inexact_division() {
+        let instruction = Instruction {
+            off0: 1,
+            off1: 2,
+            off2: 3,
+            dst_register: Register::FP,
+            op0_register: Register::AP,
+            op1_addr: Op1Addr::AP,
+            res: Res::Mul,
+            pc_update: PcUpdate::Jump,
+            ap_update: ApUpdate::Regular,
+            fp_update: FpUpdate::Regular,
+            opcode: Opcode::AssertEq,
+        };
+
+        let vm = vm!();
+
+        //2 doesn't divide 1, so op0 is the inverse of 2 in the field, (PRIME + 1) / 2
+        let dst = MaybeRelocatable::Int(Felt::new(1));
+        let op1 = MaybeRelocatable::Int(Felt::new(2));
+        assert_eq!(
+            Ok((
+                Some(MaybeRelocatable::Int(felt_str!(
+                    "1809251394333065606848661391547535052811553607665798349986546028067936010241"
+                ))),
+                Some(MaybeRelocatable::Int(Felt::new(1)))
+            )),
+            vm.deduce_op0(&instruction, Some(&dst), Some(&op1))
+        );
+    }
+
     #[test]
     fn deduce_op0_opcode_assert_eq_res_mul_zero_op1() {
         let instruction = Instruction {
             off0: 1,
             off1: 2,
             off2: 3,
-            imm: None,
             dst_register: Register::FP,
             op0_register: Register::AP,
             op1_addr: Op1Addr::AP,
@@ -1833,7 +3164,6 @@ mod tests {
             off0: 1,
             off1: 2,
             off2: 3,
-            imm: None,
             dst_register: Register::FP,
             op0_register: Register::AP,
             op1_addr: Op1Addr::AP,
@@ -1860,7 +3190,6 @@ mod tests {
             off0: 1,
             off1: 2,
             off2: 3,
-            imm: None,
             dst_register: Register::FP,
             op0_register: Register::AP,
             op1_addr: Op1Addr::AP,
@@ -1887,7 +3216,6 @@ mod tests {
             off0: 1,
             off1: 2,
             off2: 3,
-            imm: None,
             dst_register: Register::FP,
             op0_register: Register::AP,
             op1_addr: Op1Addr::AP,
@@ -1909,7 +3237,6 @@ mod tests {
             off0: 1,
             off1: 2,
             off2: 3,
-            imm: None,
             dst_register: Register::FP,
             op0_register: Register::AP,
             op1_addr: Op1Addr::AP,
@@ -1939,7 +3266,6 @@ mod tests {
             off0: 1,
             off1: 2,
             off2: 3,
-            imm: None,
             dst_register: Register::FP,
             op0_register: Register::AP,
             op1_addr: Op1Addr::AP,
@@ -1961,7 +3287,6 @@ mod tests {
             off0: 1,
             off1: 2,
             off2: 3,
-            imm: None,
             dst_register: Register::FP,
             op0_register: Register::AP,
             op1_addr: Op1Addr::AP,
@@ -1985,13 +3310,44 @@ mod tests {
         );
     }
 
+    #[test]
+    fn deduce_op1_opcode_assert_eq_res_mul_inexact_division() {
+        let instruction = Instruction {
+            off0: 1,
+            off1: 2,
+            off2: 3,
+            dst_register: Register::FP,
+            op0_register: Register::AP,
+            op1_addr: Op1Addr::AP,
+            res: Res::Mul,
+            pc_update: PcUpdate::Jump,
+            ap_update: ApUpdate::Regular,
+            fp_update: FpUpdate::Regular,
+            opcode: Opcode::AssertEq,
+        };
+
+        let vm = vm!();
+
+        //2 doesn't divide 1, so op1 is the inverse of 2 in the field, (PRIME + 1) / 2
+        let dst = MaybeRelocatable::Int(Felt::new(1));
+        let op0 = MaybeRelocatable::Int(Felt::new(2));
+        assert_eq!(
+            Ok((
+                Some(MaybeRelocatable::Int(felt_str!(
+                    "1809251394333065606848661391547535052811553607665798349986546028067936010241"
+                ))),
+                Some(MaybeRelocatable::Int(Felt::new(1)))
+            )),
+            vm.deduce_op1(&instruction, Some(&dst), Some(op0))
+        );
+    }
+
     #[test]
     fn deduce_op1_opcode_assert_eq_res_mul_zero_op0() {
         let instruction = Instruction {
             off0: 1,
             off1: 2,
             off2: 3,
-            imm: None,
             dst_register: Register::FP,
             op0_register: Register::AP,
             op1_addr: Op1Addr::AP,
@@ -2018,7 +3374,6 @@ mod tests {
             off0: 1,
             off1: 2,
             off2: 3,
-            imm: None,
             dst_register: Register::FP,
             op0_register: Register::AP,
             op1_addr: Op1Addr::AP,
@@ -2044,7 +3399,6 @@ mod tests {
             off0: 1,
             off1: 2,
             off2: 3,
-            imm: None,
             dst_register: Register::FP,
             op0_register: Register::AP,
             op1_addr: Op1Addr::AP,
@@ -2073,7 +3427,6 @@ mod tests {
             off0: 1,
             off1: 2,
             off2: 3,
-            imm: None,
             dst_register: Register::FP,
             op0_register: Register::AP,
             op1_addr: Op1Addr::AP,
@@ -2100,7 +3453,6 @@ mod tests {
             off0: 1,
             off1: 2,
             off2: 3,
-            imm: None,
             dst_register: Register::FP,
             op0_register: Register::AP,
             op1_addr: Op1Addr::AP,
@@ -2127,7 +3479,6 @@ mod tests {
             off0: 1,
             off1: 2,
             off2: 3,
-            imm: None,
             dst_register: Register::FP,
             op0_register: Register::AP,
             op1_addr: Op1Addr::AP,
@@ -2154,7 +3505,6 @@ mod tests {
             off0: 1,
             off1: 2,
             off2: 3,
-            imm: None,
             dst_register: Register::FP,
             op0_register: Register::AP,
             op1_addr: Op1Addr::AP,
@@ -2181,7 +3531,6 @@ mod tests {
             off0: 1,
             off1: 2,
             off2: 3,
-            imm: None,
             dst_register: Register::FP,
             op0_register: Register::AP,
             op1_addr: Op1Addr::AP,
@@ -2205,7 +3554,6 @@ mod tests {
             off0: 1,
             off1: 2,
             off2: 3,
-            imm: None,
             dst_register: Register::FP,
             op0_register: Register::AP,
             op1_addr: Op1Addr::AP,
@@ -2231,7 +3579,6 @@ mod tests {
             off0: 1,
             off1: 2,
             off2: 3,
-            imm: None,
             dst_register: Register::FP,
             op0_register: Register::AP,
             op1_addr: Op1Addr::AP,
@@ -2253,7 +3600,6 @@ mod tests {
             off0: 1,
             off1: 2,
             off2: 3,
-            imm: None,
             dst_register: Register::FP,
             op0_register: Register::AP,
             op1_addr: Op1Addr::AP,
@@ -2278,7 +3624,6 @@ mod tests {
             off0: 1,
             off1: 2,
             off2: 3,
-            imm: None,
             dst_register: Register::FP,
             op0_register: Register::AP,
             op1_addr: Op1Addr::AP,
@@ -2300,7 +3645,6 @@ mod tests {
             off0: 0,
             off1: 1,
             off2: 2,
-            imm: None,
             dst_register: Register::AP,
             op0_register: Register::AP,
             op1_addr: Op1Addr::AP,
@@ -2312,7 +3656,7 @@ mod tests {
         };
 
         let mut vm = vm!();
-        vm.accessed_addresses = Some(Vec::new());
+        vm.accessed_addresses = Some(HashSet::new());
         for _ in 0..2 {
             vm.segments.add(&mut vm.memory);
         }
@@ -2329,10 +3673,10 @@ mod tests {
         vm.memory.insert(&op1_addr, &op1_addr_value).unwrap();
 
         let expected_operands = Operands {
-            dst: dst_addr_value.clone(),
+            dst: Cow::Owned(dst_addr_value.clone()),
             res: Some(dst_addr_value.clone()),
-            op0: op0_addr_value.clone(),
-            op1: op1_addr_value.clone(),
+            op0: Cow::Owned(op0_addr_value.clone()),
+            op1: Cow::Owned(op1_addr_value.clone()),
         };
 
         let expected_addresses = OperandsAddresses {
@@ -2346,13 +3690,40 @@ mod tests {
         assert!(addresses == expected_addresses);
     }
 
+    #[test]
+    fn compute_operands_fp_underflow() {
+        let inst = Instruction {
+            off0: -3,
+            off1: 1,
+            off2: 2,
+            dst_register: Register::FP,
+            op0_register: Register::AP,
+            op1_addr: Op1Addr::AP,
+            res: Res::Add,
+            pc_update: PcUpdate::Regular,
+            ap_update: ApUpdate::Regular,
+            fp_update: FpUpdate::Regular,
+            opcode: Opcode::NOp,
+        };
+
+        let mut vm = vm!();
+        run_context!(vm, 0, 1, 1);
+        add_segments!(vm, 2);
+
+        assert_eq!(
+            vm.compute_operands(&inst).err(),
+            Some(VirtualMachineError::MemoryError(
+                MemoryError::AddressUnderflow("fp", -3)
+            ))
+        );
+    }
+
     #[test]
     fn compute_operands_mul_fp() {
         let inst = Instruction {
             off0: 0,
             off1: 1,
             off2: 2,
-            imm: None,
             dst_register: Register::FP,
             op0_register: Register::FP,
             op1_addr: Op1Addr::FP,
@@ -2367,7 +3738,7 @@ mod tests {
         for _ in 0..2 {
             vm.segments.add(&mut vm.memory);
         }
-        vm.accessed_addresses = Some(Vec::new());
+        vm.accessed_addresses = Some(HashSet::new());
         vm.memory.data.push(Vec::new());
         let dst_addr = mayberelocatable!(1, 0);
         let dst_addr_value = mayberelocatable!(6);
@@ -2380,10 +3751,10 @@ mod tests {
         vm.memory.insert(&op1_addr, &op1_addr_value).unwrap();
 
         let expected_operands = Operands {
-            dst: dst_addr_value.clone(),
+            dst: Cow::Owned(dst_addr_value.clone()),
             res: Some(dst_addr_value.clone()),
-            op0: op0_addr_value.clone(),
-            op1: op1_addr_value.clone(),
+            op0: Cow::Owned(op0_addr_value.clone()),
+            op1: Cow::Owned(op1_addr_value.clone()),
         };
 
         let expected_addresses = OperandsAddresses {
@@ -2403,7 +3774,6 @@ mod tests {
             off0: 1,
             off1: 1,
             off2: 1,
-            imm: Some(Felt::new(4)),
             dst_register: Register::AP,
             op0_register: Register::AP,
             op1_addr: Op1Addr::Imm,
@@ -2415,7 +3785,7 @@ mod tests {
         };
 
         let mut vm = vm!();
-        vm.accessed_addresses = Some(Vec::new());
+        vm.accessed_addresses = Some(HashSet::new());
         vm.memory = memory![
             ((0, 0), 0x206800180018001_i64),
             ((1, 1), 0x4),
@@ -2423,10 +3793,10 @@ mod tests {
         ];
 
         let expected_operands = Operands {
-            dst: mayberelocatable!(4),
+            dst: Cow::Owned(mayberelocatable!(4)),
             res: None,
-            op0: mayberelocatable!(4),
-            op1: mayberelocatable!(4),
+            op0: Cow::Owned(mayberelocatable!(4)),
+            op1: Cow::Owned(mayberelocatable!(4)),
         };
 
         let expected_addresses = OperandsAddresses {
@@ -2457,7 +3827,6 @@ mod tests {
             off0: 2,
             off1: 0,
             off2: 0,
-            imm: None,
             dst_register: Register::FP,
             op0_register: Register::AP,
             op1_addr: Op1Addr::AP,
@@ -2477,32 +3846,161 @@ mod tests {
     }
 
     #[test]
-    fn opcode_assertions_res_unconstrained() {
-        let instruction = Instruction {
-            off0: 1,
-            off1: 2,
-            off2: 3,
-            imm: None,
+    fn assert_eq_operand_matrix() {
+        //[fp] = [fp + 2], op0 at [fp + 1] is unused
+        let instruction = |res| Instruction {
+            off0: 0,
+            off1: 1,
+            off2: 2,
             dst_register: Register::FP,
-            op0_register: Register::AP,
-            op1_addr: Op1Addr::AP,
-            res: Res::Add,
+            op0_register: Register::FP,
+            op1_addr: Op1Addr::FP,
+            res,
             pc_update: PcUpdate::Regular,
             ap_update: ApUpdate::Regular,
-            fp_update: FpUpdate::APPlus2,
+            fp_update: FpUpdate::Regular,
             opcode: Opcode::AssertEq,
         };
+        let diff = |dst, res| Err(VirtualMachineError::DiffAssertValues(dst, res));
+        for (res_kind, dst, op1, expected) in [
+            (
+                Res::Op1,
+                Some(mayberelocatable!(5)),
+                mayberelocatable!(5),
+                Ok(()),
+            ),
+            (
+                Res::Op1,
+                Some(mayberelocatable!(5)),
+                mayberelocatable!(6),
+                diff(mayberelocatable!(5), mayberelocatable!(6)),
+            ),
+            (
+                Res::Op1,
+                Some(mayberelocatable!(2, 3)),
+                mayberelocatable!(2, 3),
+                Ok(()),
+            ),
+            (
+                Res::Op1,
+                Some(mayberelocatable!(2, 3)),
+                mayberelocatable!(3, 3),
+                diff(mayberelocatable!(2, 3), mayberelocatable!(3, 3)),
+            ),
+            (
+                Res::Op1,
+                Some(mayberelocatable!(3)),
+                mayberelocatable!(2, 3),
+                diff(mayberelocatable!(3), mayberelocatable!(2, 3)),
+            ),
+            //Missing dst is deduced from res
+            (Res::Op1, None, mayberelocatable!(2, 3), Ok(())),
+            (
+                Res::Unconstrained,
+                None,
+                mayberelocatable!(2, 3),
+                Err(VirtualMachineError::UnconstrainedResAssertEq),
+            ),
+            (
+                Res::Unconstrained,
+                Some(mayberelocatable!(2, 3)),
+                mayberelocatable!(2, 3),
+                Err(VirtualMachineError::UnconstrainedResAssertEq),
+            ),
+        ] {
+            let mut vm = vm!();
+            vm.accessed_addresses = Some(HashSet::new());
+            vm.memory = memory![((1, 1), 0)];
+            vm.memory.insert(&relocatable!(1, 2), &op1).unwrap();
+            if let Some(dst) = &dst {
+                vm.memory.insert(&relocatable!(1, 0), dst).unwrap();
+            }
+            let instruction = instruction(res_kind);
+            let result = vm
+                .compute_operands(&instruction)
+                .and_then(|(operands, _, _)| {
+                    vm.opcode_assertions(&instruction, &operands)?;
+                    Ok(operands.dst.into_owned())
+                });
+            match expected {
+                Ok(()) => assert_eq!(result, Ok(dst.unwrap_or(op1))),
+                Err(error) => assert_eq!(result, Err(error)),
+            }
+        }
+    }
 
-        let operands = Operands {
-            dst: MaybeRelocatable::Int(Felt::new(8)),
-            res: None,
-            op0: MaybeRelocatable::Int(Felt::new(9)),
-            op1: MaybeRelocatable::Int(Felt::new(10)),
-        };
-
-        let vm = vm!();
-
-        let error = vm.opcode_assertions(&instruction, &operands);
+    #[test]
+    fn assert_eq_with_unreduced_values() {
+        use num_bigint::BigUint;
+        use num_traits::Num;
+        //Felts are reduced when created, so memory only holds canonical values
+        let prime = BigUint::from_str_radix(&felt::PRIME_STR[2..], 16).unwrap();
+        let unreduced = |k: u32, times: u32| MaybeRelocatable::from(Felt::new(&prime * times + k));
+        //[fp] = [fp + 1] (+|*) [fp + 2]
+        let instruction = |res| Instruction {
+            off0: 0,
+            off1: 1,
+            off2: 2,
+            dst_register: Register::FP,
+            op0_register: Register::FP,
+            op1_addr: Op1Addr::FP,
+            res,
+            pc_update: PcUpdate::Regular,
+            ap_update: ApUpdate::Regular,
+            fp_update: FpUpdate::Regular,
+            opcode: Opcode::AssertEq,
+        };
+        for (res, dst, expected) in [
+            (Res::Add, unreduced(8, 1), Ok(())),
+            (Res::Add, unreduced(8, 2), Ok(())),
+            (Res::Mul, unreduced(15, 1), Ok(())),
+            (Res::Mul, mayberelocatable!(15), Ok(())),
+            (Res::Op1, unreduced(0, 1), Err(mayberelocatable!(0))),
+            (Res::Mul, unreduced(16, 1), Err(mayberelocatable!(16))),
+        ] {
+            let mut vm = vm!();
+            vm.memory.data = vec![
+                Vec::new(),
+                vec![Some(dst), Some(unreduced(3, 1)), Some(unreduced(5, 3))],
+            ];
+            let instruction = instruction(res);
+            let (operands, _, _) = vm.compute_operands(&instruction).unwrap();
+            assert_eq!(
+                vm.opcode_assertions(&instruction, &operands),
+                expected.map_err(|dst| VirtualMachineError::DiffAssertValues(
+                    dst,
+                    operands.res.clone().unwrap()
+                ))
+            );
+        }
+    }
+
+    #[test]
+    fn opcode_assertions_res_unconstrained() {
+        let instruction = Instruction {
+            off0: 1,
+            off1: 2,
+            off2: 3,
+            dst_register: Register::FP,
+            op0_register: Register::AP,
+            op1_addr: Op1Addr::AP,
+            res: Res::Add,
+            pc_update: PcUpdate::Regular,
+            ap_update: ApUpdate::Regular,
+            fp_update: FpUpdate::APPlus2,
+            opcode: Opcode::AssertEq,
+        };
+
+        let operands = Operands {
+            dst: Cow::Owned(MaybeRelocatable::Int(Felt::new(8))),
+            res: None,
+            op0: Cow::Owned(MaybeRelocatable::Int(Felt::new(9))),
+            op1: Cow::Owned(MaybeRelocatable::Int(Felt::new(10))),
+        };
+
+        let vm = vm!();
+
+        let error = vm.opcode_assertions(&instruction, &operands);
         assert_eq!(error, Err(VirtualMachineError::UnconstrainedResAssertEq));
     }
 
@@ -2512,7 +4010,6 @@ mod tests {
             off0: 1,
             off1: 2,
             off2: 3,
-            imm: None,
             dst_register: Register::FP,
             op0_register: Register::AP,
             op1_addr: Op1Addr::AP,
@@ -2524,10 +4021,10 @@ mod tests {
         };
 
         let operands = Operands {
-            dst: MaybeRelocatable::Int(Felt::new(9_i32)),
+            dst: Cow::Owned(MaybeRelocatable::Int(Felt::new(9_i32))),
             res: Some(MaybeRelocatable::Int(Felt::new(8_i32))),
-            op0: MaybeRelocatable::Int(Felt::new(9_i32)),
-            op1: MaybeRelocatable::Int(Felt::new(10_i32)),
+            op0: Cow::Owned(MaybeRelocatable::Int(Felt::new(9_i32))),
+            op1: Cow::Owned(MaybeRelocatable::Int(Felt::new(10_i32))),
         };
 
         let vm = vm!();
@@ -2547,7 +4044,6 @@ mod tests {
             off0: 1,
             off1: 2,
             off2: 3,
-            imm: None,
             dst_register: Register::FP,
             op0_register: Register::AP,
             op1_addr: Op1Addr::AP,
@@ -2559,10 +4055,10 @@ mod tests {
         };
 
         let operands = Operands {
-            dst: MaybeRelocatable::from((1, 1)),
+            dst: Cow::Owned(MaybeRelocatable::from((1, 1))),
             res: Some(MaybeRelocatable::from((1, 2))),
-            op0: MaybeRelocatable::Int(Felt::new(9_i32)),
-            op1: MaybeRelocatable::Int(Felt::new(10_i32)),
+            op0: Cow::Owned(MaybeRelocatable::Int(Felt::new(9_i32))),
+            op1: Cow::Owned(MaybeRelocatable::Int(Felt::new(10_i32))),
         };
 
         let vm = vm!();
@@ -2582,7 +4078,6 @@ mod tests {
             off0: 1,
             off1: 2,
             off2: 3,
-            imm: None,
             dst_register: Register::FP,
             op0_register: Register::AP,
             op1_addr: Op1Addr::AP,
@@ -2594,10 +4089,10 @@ mod tests {
         };
 
         let operands = Operands {
-            dst: mayberelocatable!(0, 8),
+            dst: Cow::Owned(mayberelocatable!(0, 8)),
             res: Some(mayberelocatable!(8)),
-            op0: mayberelocatable!(9),
-            op1: mayberelocatable!(10),
+            op0: Cow::Owned(mayberelocatable!(9)),
+            op1: Cow::Owned(mayberelocatable!(10)),
         };
 
         let mut vm = vm!();
@@ -2618,7 +4113,6 @@ mod tests {
             off0: 1,
             off1: 2,
             off2: 3,
-            imm: None,
             dst_register: Register::FP,
             op0_register: Register::AP,
             op1_addr: Op1Addr::AP,
@@ -2630,10 +4124,10 @@ mod tests {
         };
 
         let operands = Operands {
-            dst: mayberelocatable!(8),
+            dst: Cow::Owned(mayberelocatable!(8)),
             res: Some(mayberelocatable!(8)),
-            op0: mayberelocatable!(0, 1),
-            op1: mayberelocatable!(10),
+            op0: Cow::Owned(mayberelocatable!(0, 1)),
+            op1: Cow::Owned(mayberelocatable!(10)),
         };
         let mut vm = vm!();
         vm.run_context.fp = 6;
@@ -2666,7 +4160,7 @@ mod tests {
     /// PC 0:0
     fn test_step_for_preset_memory() {
         let mut vm = vm!(true);
-        vm.accessed_addresses = Some(Vec::new());
+        vm.accessed_addresses = Some(HashSet::new());
 
         let mut hint_processor = BuiltinHintProcessor::new_empty();
 
@@ -2699,6 +4193,488 @@ mod tests {
         assert!(accessed_addresses.contains(&Relocatable::from((1, 1))));
     }
 
+    ///Program:
+    ///   [ap] = 5, ap++
+    ///   jmp abs [fp - 1]
+    ///With [fp - 1] pointing to the execution segment
+    fn jump_out_of_program_segment_vm() -> VirtualMachine {
+        let mut vm = vm!();
+        run_context!(vm, 0, 1, 1);
+        vm.memory = memory![
+            ((0, 0), 0x480680017fff8000_i64),
+            ((0, 1), 5),
+            ((0, 2), 0x008b7fff7fff7fff_i64),
+            ((1, 0), (1, 0))
+        ];
+        vm
+    }
+
+    #[test]
+    fn strict_registers_jump_out_of_program_segment() {
+        let mut vm = jump_out_of_program_segment_vm();
+        vm.set_register_bounds(Some(RegisterBounds {
+            program_segment: 0,
+            execution_segment: 1,
+            final_pc: None,
+        }));
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        let exec_scopes = exec_scopes_ref!();
+
+        assert_eq!(
+            vm.step(
+                &mut hint_processor,
+                exec_scopes,
+                &HashMap::new(),
+                &HashMap::new()
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            vm.step(
+                &mut hint_processor,
+                exec_scopes,
+                &HashMap::new(),
+                &HashMap::new()
+            ),
+            Err(VirtualMachineError::RegisterOutOfBounds(
+                VmRegister::PC,
+                mayberelocatable!(1, 0)
+            ))
+        );
+        assert_eq!(vm.current_step, 1);
+        assert_eq!(vm.run_context.pc, relocatable!(0, 2));
+    }
+
+    #[test]
+    fn strict_registers_disabled_allows_jump_out_of_program_segment() {
+        let mut vm = jump_out_of_program_segment_vm();
+        vm.set_register_bounds(None);
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        let exec_scopes = exec_scopes_ref!();
+
+        for _ in 0..2 {
+            assert_eq!(
+                vm.step(
+                    &mut hint_processor,
+                    exec_scopes,
+                    &HashMap::new(),
+                    &HashMap::new()
+                ),
+                Ok(())
+            );
+        }
+        assert_eq!(vm.current_step, 2);
+        assert_eq!(vm.run_context.pc, relocatable!(1, 0));
+    }
+
+    #[test]
+    fn strict_registers_ret_with_integer_fp() {
+        let mut vm = vm!();
+        run_context!(vm, 0, 2, 2);
+        vm.memory = memory![
+            ((0, 0), 2345108766317314046_u64),
+            ((1, 0), 7),
+            ((1, 1), (0, 3))
+        ];
+        vm.set_register_bounds(Some(RegisterBounds {
+            program_segment: 0,
+            execution_segment: 1,
+            final_pc: None,
+        }));
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+
+        assert_eq!(
+            vm.step(
+                &mut hint_processor,
+                exec_scopes_ref!(),
+                &HashMap::new(),
+                &HashMap::new()
+            ),
+            Err(VirtualMachineError::RegisterOutOfBounds(
+                VmRegister::FP,
+                mayberelocatable!(7)
+            ))
+        );
+        assert_eq!(vm.current_step, 0);
+    }
+
+    #[test]
+    fn strict_registers_allow_final_return() {
+        let mut vm = vm!();
+        run_context!(vm, 0, 2, 2);
+        vm.memory = memory![
+            ((0, 0), 2345108766317314046_u64),
+            ((1, 0), (2, 0)),
+            ((1, 1), (3, 0))
+        ];
+        vm.set_register_bounds(Some(RegisterBounds {
+            program_segment: 0,
+            execution_segment: 1,
+            final_pc: Some(relocatable!(3, 0)),
+        }));
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+
+        assert_eq!(
+            vm.step(
+                &mut hint_processor,
+                exec_scopes_ref!(),
+                &HashMap::new(),
+                &HashMap::new()
+            ),
+            Ok(())
+        );
+        assert_eq!(vm.run_context.pc, relocatable!(3, 0));
+    }
+
+    ///Example extension: [ap] = current step, ap++
+    struct StoreStepCounter;
+
+    impl OpcodeExtension for StoreStepCounter {
+        fn execute(
+            &mut self,
+            vm: &mut VmView,
+            _instruction_word: i64,
+        ) -> Result<RegisterUpdate, VirtualMachineError> {
+            let ap = vm.get_ap();
+            vm.insert_value(&ap, Felt::new(vm.current_step()))?;
+            Ok(RegisterUpdate {
+                pc: vm.get_pc() + 1_usize,
+                ap: ap.offset + 1,
+                fp: vm.get_fp().offset,
+            })
+        }
+    }
+
+    #[test]
+    fn run_program_with_opcode_extension() {
+        //Two extension instructions followed by ret
+        let mut vm = vm!(true);
+        run_context!(vm, 0, 2, 2);
+        vm.memory = memory![
+            ((0, 0), 0x7000800080008000_i64),
+            ((0, 1), 0x7000800080008000_i64),
+            ((0, 2), 2345108766317314046_i64),
+            ((1, 0), (2, 0)),
+            ((1, 1), (3, 0))
+        ];
+        vm.set_opcode_extension(Box::new(StoreStepCounter));
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+
+        for _ in 0..3 {
+            assert_eq!(
+                vm.step(
+                    &mut hint_processor,
+                    exec_scopes_ref!(),
+                    &HashMap::new(),
+                    &HashMap::new()
+                ),
+                Ok(())
+            );
+        }
+        check_memory![vm.memory, ((1, 2), 0), ((1, 3), 1)];
+        assert_eq!(vm.run_context.pc, relocatable!(3, 0));
+        assert_eq!(vm.current_step, 3);
+        let trace = vm.trace.unwrap();
+        trace_check!(
+            trace,
+            [
+                ((0, 0), (1, 2), (1, 2)),
+                ((0, 1), (1, 3), (1, 2)),
+                ((0, 2), (1, 4), (1, 2))
+            ]
+        );
+    }
+
+    #[test]
+    fn opcode_extension_records_accessed_addresses() {
+        let mut vm = vm!();
+        run_context!(vm, 0, 2, 2);
+        vm.memory = memory![((0, 0), 0x7000800080008000_i64)];
+        add_segments!(vm, 2);
+        vm.set_opcode_extension(Box::new(StoreStepCounter));
+        assert_eq!(vm.step_instruction(), Ok(()));
+        assert_eq!(
+            vm.accessed_addresses,
+            Some(HashSet::from([relocatable!(1, 2)]))
+        );
+    }
+
+    ///Example extension: jumps to the start of segment 2
+    struct JumpToSegment2;
+
+    impl OpcodeExtension for JumpToSegment2 {
+        fn execute(
+            &mut self,
+            vm: &mut VmView,
+            _instruction_word: i64,
+        ) -> Result<RegisterUpdate, VirtualMachineError> {
+            Ok(RegisterUpdate {
+                pc: relocatable!(2, 0),
+                ap: vm.get_ap().offset,
+                fp: vm.get_fp().offset,
+            })
+        }
+    }
+
+    #[test]
+    fn strict_registers_opcode_extension_pc_out_of_segment() {
+        let mut vm = vm!(true);
+        run_context!(vm, 0, 2, 2);
+        vm.memory = memory![((0, 0), 0x7000800080008000_i64)];
+        vm.set_opcode_extension(Box::new(JumpToSegment2));
+        vm.set_register_bounds(Some(RegisterBounds {
+            program_segment: 0,
+            execution_segment: 1,
+            final_pc: None,
+        }));
+        assert_eq!(
+            vm.step_instruction(),
+            Err(VirtualMachineError::RegisterOutOfBounds(
+                VmRegister::PC,
+                mayberelocatable!(2, 0)
+            ))
+        );
+        assert_eq!(vm.current_step, 0);
+        assert_eq!(vm.trace, Some(vec![]));
+    }
+
+    #[test]
+    fn extension_instruction_without_opcode_extension() {
+        let mut vm = vm!();
+        run_context!(vm, 0, 2, 2);
+        vm.memory = memory![((0, 0), 0x7000800080008000_i64)];
+        assert_eq!(
+            vm.step_instruction(),
+            Err(VirtualMachineError::InvalidOpcode(7))
+        );
+    }
+
+    ///Example hook: records the opcode of each executed instruction
+    struct OpcodeRecorder(Arc<Mutex<Vec<Opcode>>>);
+
+    impl StepHook for OpcodeRecorder {
+        fn post_step(
+            &mut self,
+            _vm: &VirtualMachine,
+            _operands: &Operands,
+            instruction: &Instruction,
+        ) -> Result<(), String> {
+            self.0.lock().unwrap().push(instruction.opcode.clone());
+            Ok(())
+        }
+    }
+
+    //Logs each call along with the hook's name and the step it was called at
+    struct StepLogger(&'static str, Arc<Mutex<Vec<String>>>);
+
+    impl StepHook for StepLogger {
+        fn pre_step(&mut self, vm: &VirtualMachine) -> Result<(), String> {
+            let log = format!("{} pre {}", self.0, vm.current_step);
+            self.1.lock().unwrap().push(log);
+            Ok(())
+        }
+
+        fn post_step(
+            &mut self,
+            vm: &VirtualMachine,
+            operands: &Operands,
+            _instruction: &Instruction,
+        ) -> Result<(), String> {
+            let log = format!("{} post {} dst {}", self.0, vm.current_step, operands.dst());
+            self.1.lock().unwrap().push(log);
+            Ok(())
+        }
+    }
+
+    struct FailingHook;
+
+    impl StepHook for FailingHook {
+        fn pre_step(&mut self, _vm: &VirtualMachine) -> Result<(), String> {
+            Err(String::from("Stopped"))
+        }
+    }
+
+    //call rel 4; jmp rel 0; f: [ap] = 2, ap++; ret
+    fn vm_with_function_call() -> VirtualMachine {
+        let mut vm = vm!();
+        let data: Vec<MaybeRelocatable> = [
+            0x1104800180018000_i64,
+            0x4,
+            0x10780017fff7fff_i64,
+            0x0,
+            0x480680017fff8000_i64,
+            0x2,
+            0x208b7fff7fff7ffe_i64,
+        ]
+        .into_iter()
+        .map(|word| Felt::new(word).into())
+        .collect();
+        vm.load_program(&data, None).unwrap();
+        vm.add_memory_segment();
+        run_context!(vm, 0, 2, 2);
+        vm
+    }
+
+    #[test]
+    fn step_hook_records_executed_opcodes() {
+        let mut vm = vm_with_function_call();
+        let opcodes = Arc::new(Mutex::new(Vec::new()));
+        vm.add_step_hook(Box::new(OpcodeRecorder(Arc::clone(&opcodes))));
+        for _ in 0..3 {
+            vm.step_instruction().unwrap();
+        }
+        assert_eq!(
+            *opcodes.lock().unwrap(),
+            [Opcode::Call, Opcode::AssertEq, Opcode::Ret]
+        );
+    }
+
+    #[test]
+    fn step_hooks_are_called_in_registration_order() {
+        let mut vm = vm_with_function_call();
+        let log = Arc::new(Mutex::new(Vec::new()));
+        vm.add_step_hook(Box::new(StepLogger("a", Arc::clone(&log))));
+        vm.add_step_hook(Box::new(StepLogger("b", Arc::clone(&log))));
+        vm.step_instruction().unwrap();
+        assert_eq!(
+            *log.lock().unwrap(),
+            ["a pre 0", "b pre 0", "a post 1 dst 1:2", "b post 1 dst 1:2"]
+        );
+    }
+
+    #[test]
+    fn failing_step_hook_aborts_step() {
+        let mut vm = vm_with_function_call();
+        vm.add_step_hook(Box::new(FailingHook));
+        assert_eq!(
+            vm.step_instruction(),
+            Err(VirtualMachineError::StepHookFailed(String::from("Stopped")))
+        );
+        assert_eq!(vm.run_context.pc, relocatable!(0, 0));
+        assert_eq!(vm.current_step, 0);
+    }
+
+    #[test]
+    fn origin_tracking_hint_write_and_builtin_deduction() {
+        //[ap + 1] = [[fp - 5] + 2], ap++ with the hint memory[ap] = segments.add()
+        let mut vm = vm!();
+        let mut builtin = BitwiseBuiltinRunner::new(&BitwiseInstanceDef::default(), true);
+        builtin.base = 2;
+        vm.builtin_runners
+            .push((BuiltinName::Bitwise, builtin.into()));
+        run_context!(vm, 0, 9, 8);
+        add_segments!(vm, 3);
+        vm.memory = memory![
+            ((0, 0), 0x480280027ffb8001_u64),
+            ((1, 3), (2, 0)),
+            ((2, 0), 12),
+            ((2, 1), 10)
+        ];
+        vm.memory.enable_origin_tracking();
+        let hint_data_dictionary = HashMap::from([(
+            0_usize,
+            vec![any_box!(HintProcessorData::new_default(
+                "memory[ap] = segments.add()".to_string(),
+                HashMap::new(),
+            ))],
+        )]);
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+
+        assert_eq!(
+            vm.step(
+                &mut hint_processor,
+                exec_scopes_ref!(),
+                &hint_data_dictionary,
+                &HashMap::new()
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            vm.memory.get_origin(&relocatable!(1, 9)),
+            Some(&MemoryOrigin::Hint {
+                pc: relocatable!(0, 0),
+                index: 0
+            })
+        );
+        assert_eq!(
+            vm.memory.get_origin(&relocatable!(2, 2)),
+            Some(&MemoryOrigin::Deduction {
+                builtin: BuiltinName::Bitwise
+            })
+        );
+        assert_eq!(
+            vm.memory.get_origin(&relocatable!(1, 10)),
+            Some(&MemoryOrigin::Instruction {
+                pc: relocatable!(0, 0)
+            })
+        );
+        assert_eq!(vm.memory.get_origin(&relocatable!(1, 3)), None);
+        assert!(vm.get_hint_writes_into_protected_segments().is_empty());
+    }
+
+    #[test]
+    fn export_accessed_addresses_leaves_out_builtin_deductions() {
+        //[ap + 1] = [[fp - 5] + 2], ap++
+        let mut vm = vm!();
+        let mut builtin = BitwiseBuiltinRunner::new(&BitwiseInstanceDef::default(), true);
+        builtin.base = 2;
+        vm.builtin_runners
+            .push((BuiltinName::Bitwise, builtin.into()));
+        run_context!(vm, 0, 9, 8);
+        add_segments!(vm, 3);
+        vm.memory = memory![
+            ((0, 0), 0x480280027ffb8001_u64),
+            ((1, 3), (2, 0)),
+            ((2, 0), 12),
+            ((2, 1), 10)
+        ];
+        vm.memory.enable_origin_tracking();
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        vm.step(
+            &mut hint_processor,
+            exec_scopes_ref!(),
+            &HashMap::new(),
+            &HashMap::new(),
+        )
+        .unwrap();
+        assert!(vm
+            .get_accessed_addresses()
+            .unwrap()
+            .any(|addr| *addr == relocatable!(2, 2)));
+
+        let mut exported = Vec::new();
+        vm.export_accessed_addresses(&mut exported).unwrap();
+        assert_eq!(
+            read_accessed_addresses(&mut exported.as_slice()).unwrap(),
+            vec![relocatable!(1, 3), relocatable!(1, 10)]
+        );
+    }
+
+    #[test]
+    fn get_hint_writes_into_protected_segments_reports_program_and_builtin_segments() {
+        let mut vm = vm!();
+        let mut builtin = BitwiseBuiltinRunner::new(&BitwiseInstanceDef::default(), true);
+        builtin.base = 2;
+        vm.builtin_runners
+            .push((BuiltinName::Bitwise, builtin.into()));
+        vm.program_base = Some(relocatable!(0, 0));
+        add_segments!(vm, 3);
+        vm.memory.enable_origin_tracking();
+        let origin = MemoryOrigin::Hint {
+            pc: relocatable!(0, 4),
+            index: 1,
+        };
+        vm.memory.set_origin(origin.clone());
+        for addr in [relocatable!(2, 1), relocatable!(1, 0), relocatable!(0, 7)] {
+            vm.insert_value(&addr, Felt::new(1)).unwrap();
+        }
+
+        assert_eq!(
+            vm.get_hint_writes_into_protected_segments(),
+            vec![(relocatable!(0, 7), &origin), (relocatable!(2, 1), &origin)]
+        );
+    }
+
     #[test]
     /*
     Test for a simple program execution
@@ -2732,7 +4708,7 @@ mod tests {
     */
     fn test_step_for_preset_memory_function_call() {
         let mut vm = vm!(true);
-        vm.accessed_addresses = Some(Vec::new());
+        vm.accessed_addresses = Some(HashSet::new());
 
         run_context!(vm, 3, 2, 2);
 
@@ -2776,7 +4752,7 @@ mod tests {
 
         assert_eq!(vm.run_context.fp, 0);
         //Check each TraceEntry in trace
-        let trace = vm.trace.unwrap();
+        let trace = vm.trace.as_ref().unwrap();
         assert_eq!(trace.len(), 5);
         trace_check!(
             trace,
@@ -2789,15 +4765,9 @@ mod tests {
             ]
         );
         //Check accessed_addresses
-        //Order will differ from python vm execution, (due to python version using set's update() method)
-        //We will instead check that all elements are contained and not duplicated
-        let accessed_addresses = vm
-            .accessed_addresses
-            .unwrap()
-            .into_iter()
-            .collect::<HashSet<Relocatable>>();
+        let accessed_addresses = vm.accessed_addresses.as_ref().unwrap();
         assert_eq!(accessed_addresses.len(), 9);
-        //Check each element individually
+        assert_eq!(vm.get_accessed_addresses().unwrap().count(), 9);
         assert!(accessed_addresses.contains(&Relocatable::from((0, 1))));
         assert!(accessed_addresses.contains(&Relocatable::from((1, 2))));
         assert!(accessed_addresses.contains(&Relocatable::from((0, 4))));
@@ -2809,6 +4779,272 @@ mod tests {
         assert!(accessed_addresses.contains(&Relocatable::from((1, 3))));
     }
 
+    ///The program of test_step_for_preset_memory_function_call, which ends at pc 3:0 after 5 steps
+    fn preset_function_call_vm() -> VirtualMachine {
+        let mut vm = vm!(true);
+        run_context!(vm, 3, 2, 2);
+        vm.memory =
+            memory![
+            ((0, 0), 5207990763031199744_i64),
+            ((0, 1), 2),
+            ((0, 2), 2345108766317314046_i64),
+            ((0, 3), 5189976364521848832_i64),
+            ((0, 4), 1),
+            ((0, 5), 1226245742482522112_i64),
+            (
+                (0, 6),
+                ("3618502788666131213697322783095070105623107215331596699973092056135872020476",10)
+            ),
+            ((0, 7), 2345108766317314046_i64),
+            ((1, 0), (2, 0)),
+            ((1, 1), (3, 0))
+        ];
+        vm
+    }
+
+    #[test]
+    fn step_with_resources_runs_out_at_step_four() {
+        let mut vm = preset_function_call_vm();
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        let mut run_resources = RunResources::new(3);
+        for _ in 0..3 {
+            assert_eq!(
+                vm.step_with_resources(
+                    &mut hint_processor,
+                    exec_scopes_ref!(),
+                    &HashMap::new(),
+                    &HashMap::new(),
+                    &mut run_resources
+                ),
+                Ok(())
+            );
+        }
+        assert_eq!(
+            vm.step_with_resources(
+                &mut hint_processor,
+                exec_scopes_ref!(),
+                &HashMap::new(),
+                &HashMap::new(),
+                &mut run_resources
+            ),
+            Err(VirtualMachineError::RunResourcesExceeded(3))
+        );
+        assert_eq!(vm.get_current_step(), 3);
+        assert_eq!(run_resources.n_steps, Some(0));
+        assert_eq!(vm.run_context.pc, relocatable!(0, 2));
+    }
+
+    #[test]
+    fn step_with_resources_leaves_unused_steps() {
+        let mut vm = preset_function_call_vm();
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        let mut run_resources = RunResources::new(10);
+        while vm.run_context.pc != relocatable!(3, 0) {
+            assert_eq!(
+                vm.step_with_resources(
+                    &mut hint_processor,
+                    exec_scopes_ref!(),
+                    &HashMap::new(),
+                    &HashMap::new(),
+                    &mut run_resources
+                ),
+                Ok(())
+            );
+        }
+        assert_eq!(vm.get_current_step(), 5);
+        assert_eq!(run_resources.n_steps, Some(5));
+    }
+
+    #[test]
+    fn step_with_unlimited_resources() {
+        let mut run_resources = RunResources::default();
+        run_resources.consume_step();
+        assert!(!run_resources.consumed());
+        assert_eq!(run_resources.n_steps, None);
+    }
+
+    #[test]
+    fn run_until_pc_preset_memory_function_call() {
+        let mut vm = preset_function_call_vm();
+        assert_eq!(
+            vm.run_until_pc(
+                relocatable!(3, 0),
+                Some(5),
+                &mut BuiltinHintProcessor::new_empty(),
+                exec_scopes_ref!(),
+                &HashMap::new(),
+                &HashMap::new()
+            ),
+            Ok(RunOutcome {
+                steps_executed: 5,
+                break_reason: BreakReason::ReachedFinalPc
+            })
+        );
+        assert_eq!(vm.run_context.ap, 6);
+        assert_eq!(vm.run_context.fp, 0);
+        assert_eq!(vm.trace.map(|trace| trace.len()), Some(5));
+    }
+
+    #[test]
+    fn time_elapsed_without_timer() {
+        let mut vm = preset_function_call_vm();
+        assert_eq!(vm.time_elapsed(), None);
+        vm.run_until_pc(
+            relocatable!(3, 0),
+            None,
+            &mut BuiltinHintProcessor::new_empty(),
+            exec_scopes_ref!(),
+            &HashMap::new(),
+            &HashMap::new(),
+        )
+        .unwrap();
+        assert_eq!(vm.time_elapsed(), None);
+    }
+
+    #[cfg(feature = "run_timer")]
+    #[test]
+    fn time_elapsed_stops_at_end_run() {
+        let mut vm = preset_function_call_vm();
+        vm.start_timer();
+        vm.run_until_pc(
+            relocatable!(3, 0),
+            None,
+            &mut BuiltinHintProcessor::new_empty(),
+            exec_scopes_ref!(),
+            &HashMap::new(),
+            &HashMap::new(),
+        )
+        .unwrap();
+        vm.end_run(&ExecutionScopes::new()).unwrap();
+        let time_elapsed = vm.time_elapsed();
+        assert!(time_elapsed.is_some());
+        assert_eq!(vm.time_elapsed(), time_elapsed);
+        vm.reset();
+        assert_eq!(vm.time_elapsed(), None);
+    }
+
+    #[test]
+    fn run_until_pc_runs_hints() {
+        let mut vm = preset_function_call_vm();
+        let hint_data_dictionary = HashMap::from([(
+            2_usize,
+            vec![any_box!(HintProcessorData::new_default(
+                "unknown hint".to_string(),
+                HashMap::new(),
+            ))],
+        )]);
+        let error = vm
+            .run_until_pc(
+                relocatable!(3, 0),
+                None,
+                &mut BuiltinHintProcessor::new_empty(),
+                exec_scopes_ref!(),
+                &hint_data_dictionary,
+                &HashMap::new(),
+            )
+            .unwrap_err();
+        match error {
+            VirtualMachineError::StepFailed(3, error) => match *error {
+                VirtualMachineError::Hint(hint_exception) => assert_eq!(
+                    hint_exception.inner,
+                    HintError::UnknownHint("unknown hint".to_string())
+                ),
+                error => panic!("Expected a hint error, got {error:?}"),
+            },
+            error => panic!("Expected the fourth step to fail, got {error:?}"),
+        }
+        assert_eq!(vm.run_context.pc, relocatable!(0, 2));
+    }
+
+    #[test]
+    fn run_until_pc_steps_exceeded() {
+        let mut vm = preset_function_call_vm();
+        assert_eq!(
+            vm.run_until_pc(
+                relocatable!(3, 0),
+                Some(3),
+                &mut BuiltinHintProcessor::new_empty(),
+                exec_scopes_ref!(),
+                &HashMap::new(),
+                &HashMap::new()
+            ),
+            Err(VirtualMachineError::StepsExceeded(3))
+        );
+        assert_eq!(vm.run_context.pc, relocatable!(0, 2));
+    }
+
+    #[test]
+    fn run_until_pc_stops_when_pc_leaves_memory() {
+        let mut vm = preset_function_call_vm();
+        //Main returns to 3:0, which holds no instruction
+        assert_eq!(
+            vm.run_until_pc(
+                relocatable!(0, 100),
+                None,
+                &mut BuiltinHintProcessor::new_empty(),
+                exec_scopes_ref!(),
+                &HashMap::new(),
+                &HashMap::new()
+            ),
+            Err(VirtualMachineError::PcOutOfMemory(5, relocatable!(3, 0)))
+        );
+    }
+
+    #[test]
+    fn run_until_pc_reports_failing_step() {
+        let mut vm = vm!();
+        run_context!(vm, 0, 2, 2);
+        //[ap] = 1, ap++; [ap - 1] = 2
+        vm.memory = memory![
+            ((0, 0), 0x480680017fff8000_i64),
+            ((0, 1), 1),
+            ((0, 2), 0x400680017fff7fff_i64),
+            ((0, 3), 2),
+            ((1, 1), 0)
+        ];
+        assert_eq!(
+            vm.run_until_pc(
+                relocatable!(0, 4),
+                None,
+                &mut BuiltinHintProcessor::new_empty(),
+                exec_scopes_ref!(),
+                &HashMap::new(),
+                &HashMap::new()
+            ),
+            Err(VirtualMachineError::StepFailed(
+                1,
+                Box::new(VirtualMachineError::DiffAssertValues(
+                    mayberelocatable!(1),
+                    mayberelocatable!(2)
+                ))
+            ))
+        );
+    }
+
+    #[test]
+    fn accessed_addresses_are_not_repeated_in_long_runs() {
+        let mut vm = vm!();
+        run_context!(vm, 0, 2, 1);
+        //jmp rel 0
+        vm.memory = memory![((0, 0), 0x10780017fff7fff_i64), ((0, 1), 0), ((1, 0), 5)];
+        assert_eq!(
+            vm.run_until_pc(
+                relocatable!(0, 2),
+                Some(10000),
+                &mut BuiltinHintProcessor::new_empty(),
+                exec_scopes_ref!(),
+                &HashMap::new(),
+                &HashMap::new()
+            ),
+            Err(VirtualMachineError::StepsExceeded(10000))
+        );
+        assert_eq!(vm.current_step, 10000);
+        assert_eq!(
+            vm.get_accessed_addresses().unwrap().collect::<HashSet<_>>(),
+            HashSet::from([&relocatable!(0, 1), &relocatable!(1, 0)])
+        );
+    }
+
     #[test]
     /// Test the following program:
     /// ...
@@ -2919,10 +5155,160 @@ mod tests {
         );
     }
 
+    #[test]
+    fn load_program_and_run_multiplication() {
+        let mut vm = vm!();
+        let data: Vec<MaybeRelocatable> = [
+            0x400680017fff8000_i64,
+            0x4,
+            0x40780017fff7fff_i64,
+            0x1,
+            0x480680017fff8000_i64,
+            0x5,
+            0x40507ffe7fff8000_i64,
+            0x208b7fff7fff7ffe_i64,
+        ]
+        .into_iter()
+        .map(|word| Felt::new(word).into())
+        .collect();
+
+        assert_eq!(vm.load_program(&data, None), Ok(relocatable!(0, 8)));
+        assert_eq!(vm.program_base, Some(relocatable!(0, 0)));
+        vm.add_memory_segment();
+        vm.memory
+            .insert(&relocatable!(1, 0), &MaybeRelocatable::from((2, 0)))
+            .unwrap();
+        vm.memory
+            .insert(&relocatable!(1, 1), &MaybeRelocatable::from((3, 0)))
+            .unwrap();
+        run_context!(vm, 0, 2, 2);
+
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        for _ in 0..4 {
+            vm.step(
+                &mut hint_processor,
+                exec_scopes_ref!(),
+                &HashMap::new(),
+                &HashMap::new(),
+            )
+            .unwrap();
+        }
+        assert_eq!(vm.run_context.pc, relocatable!(0, 7));
+        check_memory!(vm.memory, ((1, 2), 4), ((1, 3), 5), ((1, 4), 20));
+    }
+
+    #[test]
+    fn run_multiplication_borrows_operands_from_memory() {
+        let mut vm = vm!(true);
+        vm.memory = memory![
+            ((0, 0), 0x400680017fff8000_i64),
+            ((0, 1), 0x4),
+            ((0, 2), 0x40780017fff7fff_i64),
+            ((0, 3), 0x1),
+            ((0, 4), 0x480680017fff8000_i64),
+            ((0, 5), 0x5),
+            ((0, 6), 0x40507ffe7fff8000_i64),
+            ((0, 7), 0x208b7fff7fff7ffe_i64),
+            ((1, 0), (2, 0)),
+            ((1, 1), (3, 0))
+        ];
+        run_context!(vm, 0, 2, 2);
+
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        for _ in 0..3 {
+            vm.step(
+                &mut hint_processor,
+                exec_scopes_ref!(),
+                &HashMap::new(),
+                &HashMap::new(),
+            )
+            .unwrap();
+        }
+        //[ap] = [ap - 2] * [ap - 1], the factors are read from memory and only dst is deduced
+        let instruction = vm.decode_instruction_at(&vm.run_context.pc).unwrap();
+        let (operands, _, deduced_operands) = vm.compute_operands(&instruction).unwrap();
+        assert!(matches!(operands.op0, Cow::Borrowed(_)));
+        assert!(matches!(operands.op1, Cow::Borrowed(_)));
+        assert!(matches!(operands.dst, Cow::Owned(_)));
+        assert!(deduced_operands.was_dest_deducted());
+        assert_eq!(operands.res(), Some(&mayberelocatable!(20)));
+
+        vm.step(
+            &mut hint_processor,
+            exec_scopes_ref!(),
+            &HashMap::new(),
+            &HashMap::new(),
+        )
+        .unwrap();
+        assert_eq!(vm.run_context.pc, relocatable!(0, 7));
+        check_memory!(vm.memory, ((1, 2), 4), ((1, 3), 5), ((1, 4), 20));
+        assert_eq!(
+            vm.trace,
+            Some(vec![
+                TraceEntry {
+                    pc: (0, 0).into(),
+                    ap: (1, 2).into(),
+                    fp: (1, 2).into()
+                },
+                TraceEntry {
+                    pc: (0, 2).into(),
+                    ap: (1, 2).into(),
+                    fp: (1, 2).into()
+                },
+                TraceEntry {
+                    pc: (0, 4).into(),
+                    ap: (1, 3).into(),
+                    fp: (1, 2).into()
+                },
+                TraceEntry {
+                    pc: (0, 6).into(),
+                    ap: (1, 4).into(),
+                    fp: (1, 2).into()
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn load_program_over_different_data() {
+        let mut vm = vm!();
+        let program_base = vm.add_memory_segment();
+        vm.memory
+            .insert(&relocatable!(0, 1), &MaybeRelocatable::from(Felt::new(7)))
+            .unwrap();
+
+        //Loading matching data again is fine
+        let data = vec![Felt::new(3).into(), Felt::new(7).into()];
+        assert_eq!(
+            vm.load_program(&data, Some(program_base)),
+            Ok(relocatable!(0, 2))
+        );
+        assert_eq!(
+            vm.load_program(
+                &[Felt::new(3).into(), Felt::new(8).into()],
+                Some(program_base)
+            ),
+            Err(VirtualMachineError::MemoryError(
+                MemoryError::InconsistentMemory(
+                    relocatable!(0, 1).into(),
+                    Felt::new(7).into(),
+                    Felt::new(8).into()
+                )
+            ))
+        );
+    }
+
     #[test]
     fn deduce_memory_cell_no_pedersen_builtin() {
         let vm = vm!();
-        assert_eq!(vm.deduce_memory_cell(&Relocatable::from((0, 0))), Ok(None));
+        assert_eq!(
+            vm.deduce_operand_by_builtin(
+                BuiltinOperand::Op0,
+                &Relocatable::from((0, 0)),
+                &mut DeducedOperands::default()
+            ),
+            Ok(None)
+        );
     }
 
     #[test]
@@ -2930,16 +5316,80 @@ mod tests {
         let mut vm = vm!();
         let builtin = HashBuiltinRunner::new(8, true);
         vm.builtin_runners
-            .push((String::from("pedersen"), builtin.into()));
+            .push((BuiltinName::Pedersen, builtin.into()));
         vm.memory = memory![((0, 3), 32), ((0, 4), 72), ((0, 5), 0)];
         assert_eq!(
-            vm.deduce_memory_cell(&Relocatable::from((0, 5))),
+            vm.deduce_operand_by_builtin(
+                BuiltinOperand::Op0,
+                &Relocatable::from((0, 5)),
+                &mut DeducedOperands::default()
+            ),
             Ok(Some(MaybeRelocatable::from(felt::felt_str!(
                 "3270867057177188607814717243084834301278723532952411121381966378910183338911"
             ))))
         );
     }
 
+    #[test]
+    fn deduce_memory_cell_dispatches_by_segment() {
+        let mut vm = vm!();
+        //Registered in both orders, the builtin is chosen by its base and not its position
+        for bitwise_first in [false, true] {
+            let mut hash: BuiltinRunner = HashBuiltinRunner::new(8, true).into();
+            let mut bitwise: BuiltinRunner =
+                BitwiseBuiltinRunner::new(&BitwiseInstanceDef::default(), true).into();
+            vm.segments = MemorySegmentManager::new();
+            vm.memory = Memory::new();
+            //Pedersen gets segment 0 and bitwise segment 1
+            hash.initialize_segments(&mut vm.segments, &mut vm.memory)
+                .unwrap();
+            bitwise
+                .initialize_segments(&mut vm.segments, &mut vm.memory)
+                .unwrap();
+            let mut builtins = vec![
+                (BuiltinName::Pedersen, hash),
+                (BuiltinName::Bitwise, bitwise),
+            ];
+            if bitwise_first {
+                builtins.reverse();
+            }
+            vm.builtin_runners = builtins;
+            vm.memory = memory![((0, 0), 32), ((0, 1), 72), ((1, 0), 12), ((1, 1), 10)];
+
+            let mut deduced_operands = DeducedOperands::default();
+            assert_eq!(
+                vm.deduce_operand_by_builtin(
+                    BuiltinOperand::Op0,
+                    &Relocatable::from((0, 2)),
+                    &mut deduced_operands
+                ),
+                Ok(Some(MaybeRelocatable::from(felt::felt_str!(
+                    "3270867057177188607814717243084834301278723532952411121381966378910183338911"
+                ))))
+            );
+            assert!(deduced_operands.was_op0_deducted_by_builtin());
+            let mut deduced_operands = DeducedOperands::default();
+            assert_eq!(
+                vm.deduce_operand_by_builtin(
+                    BuiltinOperand::Op1,
+                    &Relocatable::from((1, 2)),
+                    &mut deduced_operands
+                ),
+                Ok(Some(MaybeRelocatable::from(Felt::new(8_i32))))
+            );
+            assert!(deduced_operands.was_op1_deducted_by_builtin());
+            //No builtin owns segment 2
+            assert_eq!(
+                vm.deduce_operand_by_builtin(
+                    BuiltinOperand::Op0,
+                    &Relocatable::from((2, 2)),
+                    &mut DeducedOperands::default()
+                ),
+                Ok(None)
+            );
+        }
+    }
+
     #[test]
     /* Program used:
     %builtins output pedersen
@@ -2969,7 +5419,6 @@ mod tests {
             off0: 0,
             off1: -5,
             off2: 2,
-            imm: None,
             dst_register: Register::AP,
             op0_register: Register::FP,
             op1_addr: Op1Addr::Op0,
@@ -2982,9 +5431,9 @@ mod tests {
         let mut builtin = HashBuiltinRunner::new(8, true);
         builtin.base = 3;
         let mut vm = vm!();
-        vm.accessed_addresses = Some(Vec::new());
+        vm.accessed_addresses = Some(HashSet::new());
         vm.builtin_runners
-            .push((String::from("pedersen"), builtin.into()));
+            .push((BuiltinName::Pedersen, builtin.into()));
         run_context!(vm, 0, 13, 12);
 
         //Insert values into memory (excluding those from the program segment (instructions))
@@ -3007,16 +5456,16 @@ mod tests {
         ];
 
         let expected_operands = Operands {
-            dst: MaybeRelocatable::from(felt_str!(
+            dst: Cow::Owned(MaybeRelocatable::from(felt_str!(
                 "3270867057177188607814717243084834301278723532952411121381966378910183338911"
-            )),
+            ))),
             res: Some(MaybeRelocatable::from(felt_str!(
                 "3270867057177188607814717243084834301278723532952411121381966378910183338911"
             ))),
-            op0: MaybeRelocatable::from((3, 0)),
-            op1: MaybeRelocatable::from(felt_str!(
+            op0: Cow::Owned(MaybeRelocatable::from((3, 0))),
+            op1: Cow::Owned(MaybeRelocatable::from(felt_str!(
                 "3270867057177188607814717243084834301278723532952411121381966378910183338911"
-            )),
+            ))),
         };
         let expected_operands_mem_addresses = OperandsAddresses {
             dst_addr: Relocatable::from((1, 13)),
@@ -3028,15 +5477,165 @@ mod tests {
         assert_eq!(operands_mem_address, expected_operands_mem_addresses);
     }
 
+    #[test]
+    fn deduced_pedersen_output_is_validated() {
+        let instruction = Instruction {
+            off0: 0,
+            off1: -5,
+            off2: 2,
+            dst_register: Register::AP,
+            op0_register: Register::FP,
+            op1_addr: Op1Addr::Op0,
+            res: Res::Op1,
+            pc_update: PcUpdate::Regular,
+            ap_update: ApUpdate::Add1,
+            fp_update: FpUpdate::Regular,
+            opcode: Opcode::AssertEq,
+        };
+        let mut builtin = HashBuiltinRunner::new(8, true);
+        builtin.base = 3;
+        let mut vm = vm!();
+        vm.builtin_runners
+            .push((BuiltinName::Pedersen, builtin.into()));
+        run_context!(vm, 0, 13, 12);
+        vm.memory = memory![
+            ((3, 0), 32),
+            ((3, 1), 72),
+            ((1, 7), (3, 0)),
+            ((1, 12), (3, 3))
+        ];
+
+        vm.run_instruction(instruction).unwrap();
+        assert!(vm
+            .memory
+            .validated_addresses
+            .contains(&MaybeRelocatable::from((3, 2))));
+        //The dst deduced by the instruction isn't a builtin cell
+        assert!(!vm
+            .memory
+            .validated_addresses
+            .contains(&MaybeRelocatable::from((1, 13))));
+    }
+
+    #[test]
+    fn validate_memory_cell_range_check() {
+        let mut vm = vm!();
+        let builtin = RangeCheckBuiltinRunner::new(8, 8, true);
+        vm.memory = memory![((0, 0), 45)];
+        vm.memory
+            .insert(&relocatable!(0, 1), &MaybeRelocatable::from(Felt::new(-10)))
+            .unwrap();
+        builtin.add_validation_rule(&mut vm.memory).unwrap();
+
+        assert_eq!(vm.validate_memory_cell(&relocatable!(0, 0)), Ok(()));
+        assert!(vm
+            .memory
+            .validated_addresses
+            .contains(&MaybeRelocatable::from((0, 0))));
+        assert_eq!(
+            vm.validate_memory_cell(&relocatable!(0, 1)),
+            Err(VirtualMachineError::MemoryError(
+                MemoryError::NumOutOfBounds
+            ))
+        );
+        assert!(!vm
+            .memory
+            .validated_addresses
+            .contains(&MaybeRelocatable::from((0, 1))));
+        assert_eq!(
+            vm.validate_memory_cell(&relocatable!(0, 2)),
+            Err(VirtualMachineError::MemoryGet(MaybeRelocatable::from((
+                0, 2
+            ))))
+        );
+        assert_eq!(
+            vm.insert_value(&relocatable!(0, 2), Felt::new(-10)),
+            Err(VirtualMachineError::MemoryError(
+                MemoryError::NumOutOfBounds
+            ))
+        );
+    }
+
+    #[test]
+    fn hint_reading_incomplete_pedersen_instance_does_not_deduce() {
+        //Writes 1 at ap if the pedersen result at (2, 2) wasn't computed
+        fn read_pedersen_result(
+            vm: &mut VirtualMachine,
+            _exec_scopes: &mut ExecutionScopes,
+            _ids_data: &HashMap<String, HintReference>,
+            _ap_tracking: &ApTracking,
+            _constants: &HashMap<String, Felt>,
+            _const_view: &VmConstView,
+        ) -> Result<(), HintError> {
+            let result = vm
+                .get_maybe(&relocatable!(2, 2))
+                .map_err(VirtualMachineError::MemoryError)?;
+            vm.insert_value(&vm.get_ap(), Felt::new(result.is_none() as u8))
+                .map_err(HintError::Internal)
+        }
+
+        let mut vm = vm!();
+        let mut builtin = HashBuiltinRunner::new(8, true);
+        builtin.base = 2;
+        vm.builtin_runners
+            .push((BuiltinName::Pedersen, builtin.into()));
+        run_context!(vm, 0, 0, 0);
+        vm.memory = memory![((2, 0), 32), ((2, 1), 72)];
+        add_segments!(vm, 3);
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        hint_processor.add_hint(
+            String::from("read_pedersen_result"),
+            Rc::new(HintFunc(Box::new(read_pedersen_result))),
+        );
+        let hint_data_dictionary = HashMap::from([(
+            0_usize,
+            vec![any_box!(HintProcessorData::new_default(
+                String::from("read_pedersen_result"),
+                HashMap::new(),
+            ))],
+        )]);
+
+        assert_eq!(
+            vm.step_hint(
+                &mut hint_processor,
+                exec_scopes_ref!(),
+                &hint_data_dictionary,
+                &HashMap::new()
+            ),
+            Ok(())
+        );
+        check_memory![vm.memory, ((1, 0), 1)];
+        assert_eq!(vm.get_maybe(&relocatable!(2, 2)), Ok(None));
+
+        //Resolving the cell as an operand deduces it
+        let mut deduced_operands = DeducedOperands::default();
+        assert_eq!(
+            vm.deduce_operand_by_builtin(
+                BuiltinOperand::Op1,
+                &relocatable!(2, 2),
+                &mut deduced_operands
+            ),
+            Ok(Some(MaybeRelocatable::from(felt_str!(
+                "3270867057177188607814717243084834301278723532952411121381966378910183338911"
+            ))))
+        );
+        assert!(deduced_operands.was_op1_deducted_by_builtin());
+        assert!(!deduced_operands.was_op0_deducted_by_builtin());
+    }
+
     #[test]
     fn deduce_memory_cell_bitwise_builtin_valid_and() {
         let mut vm = vm!();
         let builtin = BitwiseBuiltinRunner::new(&BitwiseInstanceDef::default(), true);
         vm.builtin_runners
-            .push((String::from("bitwise"), builtin.into()));
+            .push((BuiltinName::Bitwise, builtin.into()));
         vm.memory = memory![((0, 5), 10), ((0, 6), 12), ((0, 7), 0)];
         assert_eq!(
-            vm.deduce_memory_cell(&Relocatable::from((0, 7))),
+            vm.deduce_operand_by_builtin(
+                BuiltinOperand::Op0,
+                &Relocatable::from((0, 7)),
+                &mut DeducedOperands::default()
+            ),
             Ok(Some(MaybeRelocatable::from(Felt::new(8_i32))))
         );
     }
@@ -3059,7 +5658,6 @@ mod tests {
             off0: 0,
             off1: -5,
             off2: 2,
-            imm: None,
             dst_register: Register::AP,
             op0_register: Register::FP,
             op1_addr: Op1Addr::Op0,
@@ -3074,9 +5672,9 @@ mod tests {
         builtin.base = 2;
         let mut vm = vm!();
 
-        vm.accessed_addresses = Some(Vec::new());
+        vm.accessed_addresses = Some(HashSet::new());
         vm.builtin_runners
-            .push((String::from("bitwise"), builtin.into()));
+            .push((BuiltinName::Bitwise, builtin.into()));
         run_context!(vm, 0, 9, 8);
 
         //Insert values into memory (excluding those from the program segment (instructions))
@@ -3094,10 +5692,10 @@ mod tests {
         ];
 
         let expected_operands = Operands {
-            dst: MaybeRelocatable::from(Felt::new(8_i32)),
+            dst: Cow::Owned(MaybeRelocatable::from(Felt::new(8_i32))),
             res: Some(MaybeRelocatable::from(Felt::new(8_i32))),
-            op0: MaybeRelocatable::from((2, 0)),
-            op1: MaybeRelocatable::from(Felt::new(8_i32)),
+            op0: Cow::Owned(MaybeRelocatable::from((2, 0))),
+            op1: Cow::Owned(MaybeRelocatable::from(Felt::new(8_i32))),
         };
         let expected_operands_mem_addresses = OperandsAddresses {
             dst_addr: Relocatable::from((1, 9)),
@@ -3113,8 +5711,7 @@ mod tests {
     fn deduce_memory_cell_ec_op_builtin_valid() {
         let mut vm = vm!();
         let builtin = EcOpBuiltinRunner::new(&EcOpInstanceDef::default(), true);
-        vm.builtin_runners
-            .push((String::from("ec_op"), builtin.into()));
+        vm.builtin_runners.push((BuiltinName::EcOp, builtin.into()));
 
         vm.memory = memory![
             (
@@ -3155,7 +5752,11 @@ mod tests {
             )
         ];
 
-        let result = vm.deduce_memory_cell(&Relocatable::from((0, 6)));
+        let result = vm.deduce_operand_by_builtin(
+            BuiltinOperand::Op0,
+            &Relocatable::from((0, 6)),
+            &mut DeducedOperands::default(),
+        );
         assert_eq!(
             result,
             Ok(Some(MaybeRelocatable::from(felt_str!(
@@ -3164,6 +5765,73 @@ mod tests {
         );
     }
 
+    ///Deduces each cell of the segment but the first as the double of the previous one
+    fn double_previous_cell_rule() -> Rule {
+        Rule(Box::new(|memory, addr| {
+            if addr.offset == 0 {
+                return Ok(None);
+            }
+            let previous = memory.get(&Relocatable::from((addr.segment_index, addr.offset - 1)))?;
+            Ok(match previous.as_deref() {
+                Some(MaybeRelocatable::Int(value)) => Some(MaybeRelocatable::Int(value + value)),
+                _ => None,
+            })
+        }))
+    }
+
+    #[test]
+    fn deduce_memory_cell_with_auto_deduction_rules() {
+        let mut vm = vm!();
+        vm.add_auto_deduction_rule(2, Rule(Box::new(|_, _| Ok(None))));
+        vm.add_auto_deduction_rule(2, double_previous_cell_rule());
+        vm.memory = memory![((2, 0), 5)];
+        //The first rule can't deduce it, so the second one is used
+        assert_eq!(
+            vm.deduce_memory_cell(&relocatable!(2, 1)),
+            Ok(Some(MaybeRelocatable::from(Felt::new(10))))
+        );
+        assert_eq!(vm.deduce_memory_cell(&relocatable!(2, 0)), Ok(None));
+        //No rules were added for segment 1
+        assert_eq!(vm.deduce_memory_cell(&relocatable!(1, 1)), Ok(None));
+    }
+
+    #[test]
+    fn step_instruction_deduces_missing_operand_with_rule() {
+        let mut vm = vm!();
+        run_context!(vm, 0, 2, 3);
+        //[ap - 1] = [[fp - 3]], with [fp - 3] pointing to the missing cell (2, 1)
+        vm.memory = memory![
+            ((0, 0), 0x400280007ffd7fff_i64),
+            ((1, 0), (2, 1)),
+            ((1, 1), 10),
+            ((2, 0), 5)
+        ];
+        vm.add_auto_deduction_rule(2, double_previous_cell_rule());
+        assert_eq!(vm.step_instruction(), Ok(()));
+        assert_eq!(
+            vm.memory.get(&relocatable!(2, 1)),
+            Ok(Some(Cow::Borrowed(&MaybeRelocatable::from(Felt::new(10)))))
+        );
+        assert_eq!(vm.verify_auto_deductions(), Ok(()));
+    }
+
+    #[test]
+    fn verify_auto_deductions_with_inconsistent_rule() {
+        let mut vm = vm!();
+        vm.add_auto_deduction_rule(2, double_previous_cell_rule());
+        vm.memory = memory![((2, 0), 5), ((2, 1), 10), ((2, 3), 7)];
+        assert_eq!(vm.verify_auto_deductions(), Ok(()));
+        vm.memory = memory![((2, 0), 5), ((2, 1), 11)];
+        assert_eq!(
+            vm.verify_auto_deductions(),
+            Err(VirtualMachineError::InconsistentAutoDeduction(
+                relocatable!(2, 1),
+                MaybeRelocatable::from(Felt::new(10)),
+                Some(MaybeRelocatable::from(Felt::new(11)))
+            ))
+        );
+    }
+
     #[test]
     /* Data taken from this program execution:
        %builtins output ec_op
@@ -3185,8 +5853,7 @@ mod tests {
         let mut builtin = EcOpBuiltinRunner::new(&EcOpInstanceDef::default(), true);
         builtin.base = 3;
         let mut vm = vm!();
-        vm.builtin_runners
-            .push((String::from("ec_op"), builtin.into()));
+        vm.builtin_runners.push((BuiltinName::EcOp, builtin.into()));
         vm.memory = memory![
             (
                 (3, 0),
@@ -3233,8 +5900,7 @@ mod tests {
         let mut builtin = EcOpBuiltinRunner::new(&EcOpInstanceDef::default(), true);
         builtin.base = 3;
         let mut vm = vm!();
-        vm.builtin_runners
-            .push((String::from("ec_op"), builtin.into()));
+        vm.builtin_runners.push((BuiltinName::EcOp, builtin.into()));
         vm.memory = memory![
             (
                 (3, 0),
@@ -3277,7 +5943,7 @@ mod tests {
         assert_eq!(
             error,
             Err(VirtualMachineError::InconsistentAutoDeduction(
-                String::from("ec_op"),
+                relocatable!(3, 5),
                 MaybeRelocatable::Int(felt_str!(
                     "2739017437753868763038285897969098325279422804143820990343394856167768859289"
                 )),
@@ -3286,7 +5952,7 @@ mod tests {
                 )))
             ))
         );
-        assert_eq!(error.unwrap_err().to_string(), "Inconsistent auto-deduction for builtin ec_op, expected 2739017437753868763038285897969098325279422804143820990343394856167768859289, got Some(Int(2778063437308421278851140253538604815869848682781135193774472480292420096757))");
+        assert_eq!(error.unwrap_err().to_string(), "Inconsistent auto-deduction at 3:5, expected 2739017437753868763038285897969098325279422804143820990343394856167768859289, got Some(Int(2778063437308421278851140253538604815869848682781135193774472480292420096757))");
     }
 
     #[test]
@@ -3307,7 +5973,7 @@ mod tests {
         builtin.base = 2;
         let mut vm = vm!();
         vm.builtin_runners
-            .push((String::from("bitwise"), builtin.into()));
+            .push((BuiltinName::Bitwise, builtin.into()));
         vm.memory = memory![((2, 0), 12), ((2, 1), 10)];
         assert_eq!(vm.verify_auto_deductions(), Ok(()));
     }
@@ -3341,15 +6007,69 @@ mod tests {
         builtin.base = 3;
         let mut vm = vm!();
         vm.builtin_runners
-            .push((String::from("pedersen"), builtin.into()));
+            .push((BuiltinName::Pedersen, builtin.into()));
         vm.memory = memory![((3, 0), 32), ((3, 1), 72)];
         assert_eq!(vm.verify_auto_deductions(), Ok(()));
     }
 
+    #[test]
+    fn verify_auto_deductions_pedersen_output_without_inputs() {
+        let mut builtin = HashBuiltinRunner::new(8, true);
+        builtin.base = 3;
+        let mut vm = vm!();
+        vm.builtin_runners
+            .push((BuiltinName::Pedersen, builtin.into()));
+        vm.memory = memory![((3, 0), 32), ((3, 2), 5)];
+        assert_eq!(
+            vm.verify_auto_deductions(),
+            Err(VirtualMachineError::MissingBuiltinInputs(
+                BuiltinName::Pedersen,
+                relocatable!(3, 0)
+            ))
+        );
+    }
+
+    #[test]
+    fn step_reading_builtin_output_before_inputs_is_rejected() {
+        let mut builtin = HashBuiltinRunner::new(8, true);
+        builtin.base = 2;
+        let mut vm = vm!();
+        vm.builtin_runners
+            .push((BuiltinName::Pedersen, builtin.into()));
+        run_context!(vm, 0, 2, 3);
+        //[ap - 1] = [[fp - 3]], with [fp - 3] pointing to the output cell of the first instance
+        vm.memory = memory![
+            ((0, 0), 0x400280007ffd7fff_i64),
+            ((1, 0), (2, 2)),
+            ((1, 1), 10),
+            ((2, 0), 32)
+        ];
+        //The output can't be deduced yet, so it is taken from [ap - 1]
+        assert_eq!(vm.step_instruction(), Ok(()));
+        assert_eq!(
+            vm.memory.get(&relocatable!(2, 2)),
+            Ok(Some(Cow::Borrowed(&MaybeRelocatable::from(Felt::new(10)))))
+        );
+        assert_eq!(
+            vm.verify_auto_deductions(),
+            Err(VirtualMachineError::MissingBuiltinInputs(
+                BuiltinName::Pedersen,
+                relocatable!(2, 0)
+            ))
+        );
+        //Writing the missing input afterwards doesn't make the output valid
+        vm.insert_value(&relocatable!(2, 1), 72).unwrap();
+        assert!(matches!(
+            vm.verify_auto_deductions(),
+            Err(VirtualMachineError::InconsistentAutoDeduction(addr, _, Some(value)))
+                if addr == relocatable!(2, 2) && value == MaybeRelocatable::from(Felt::new(10))
+        ));
+    }
+
     #[test]
     fn can_get_return_values() {
         let mut vm = vm!();
-        vm.set_ap(4);
+        vm.set_ap(relocatable!(1, 4)).unwrap();
         vm.memory = memory![((1, 0), 1), ((1, 1), 2), ((1, 2), 3), ((1, 3), 4)];
         let expected = vec![
             MaybeRelocatable::Int(Felt::new(1_i32)),
@@ -3361,13 +6081,58 @@ mod tests {
     }
 
     #[test]
-    fn get_return_values_fails_when_ap_is_0() {
-        let mut vm = vm!();
-        vm.memory = memory![((1, 0), 1), ((1, 1), 2), ((1, 2), 3), ((1, 3), 4)];
-        assert!(matches!(
-            vm.get_return_values(3),
-            Err(MemoryError::NumOutOfBounds)
-        ));
+    fn get_return_values_fails_when_ap_is_0() {
+        let mut vm = vm!();
+        vm.memory = memory![((1, 0), 1), ((1, 1), 2), ((1, 2), 3), ((1, 3), 4)];
+        assert_eq!(
+            vm.get_return_values(3),
+            Err(VirtualMachineError::CantSubOffset(0, 3))
+        );
+    }
+
+    #[test]
+    fn get_return_values_fails_on_missing_value() {
+        let mut vm = vm!();
+        vm.set_ap(relocatable!(1, 4)).unwrap();
+        vm.memory = memory![((1, 0), 1), ((1, 1), 2), ((1, 3), 4)];
+        assert_eq!(
+            vm.get_return_values(3),
+            Err(VirtualMachineError::MemoryGet(MaybeRelocatable::from((
+                1, 2
+            ))))
+        );
+    }
+
+    #[test]
+    fn segment_sizes_are_updated_during_run() {
+        let mut vm = vm_with_function_call();
+        assert_eq!(vm.memory.get_segment_size(1), Some(0));
+        //call pushes fp and the return pc, then [ap] = 2 is written after them
+        let mut sizes = Vec::new();
+        for _ in 0..3 {
+            vm.step_instruction().unwrap();
+            sizes.push(vm.memory.get_segment_size(1));
+        }
+        assert_eq!(sizes, [Some(4), Some(5), Some(5)]);
+        assert_eq!(vm.memory.get_segment_size(2), None);
+    }
+
+    #[test]
+    fn get_return_values_of_function_call() {
+        let mut vm = vm_with_function_call();
+
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        for _ in 0..3 {
+            vm.step(
+                &mut hint_processor,
+                exec_scopes_ref!(),
+                &HashMap::new(),
+                &HashMap::new(),
+            )
+            .unwrap();
+        }
+        assert_eq!(vm.run_context.pc, relocatable!(0, 2));
+        assert_eq!(vm.get_return_values(1), Ok(vec![MaybeRelocatable::from(2)]));
     }
 
     /*
@@ -3478,14 +6243,135 @@ mod tests {
         let hash_builtin = HashBuiltinRunner::new(8, true);
         let bitwise_builtin = BitwiseBuiltinRunner::new(&BitwiseInstanceDef::default(), true);
         vm.builtin_runners
-            .push((String::from("pedersen"), hash_builtin.into()));
+            .push((BuiltinName::Pedersen, hash_builtin.into()));
         vm.builtin_runners
-            .push((String::from("bitwise"), bitwise_builtin.into()));
+            .push((BuiltinName::Bitwise, bitwise_builtin.into()));
 
         let builtins = vm.get_builtin_runners();
 
-        assert_eq!(builtins[0].0, "pedersen");
-        assert_eq!(builtins[1].0, "bitwise");
+        assert_eq!(builtins[0].0, BuiltinName::Pedersen);
+        assert_eq!(builtins[1].0, BuiltinName::Bitwise);
+    }
+
+    #[test]
+    fn add_builtin_runner_rejects_duplicate_names() {
+        let mut vm = vm!();
+        assert_eq!(
+            vm.add_builtin_runner(
+                BuiltinName::Pedersen,
+                HashBuiltinRunner::new(8, true).into()
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            vm.add_builtin_runner(
+                BuiltinName::Pedersen,
+                HashBuiltinRunner::new(8, true).into()
+            ),
+            Err(RunnerError::DuplicateBuiltin(BuiltinName::Pedersen))
+        );
+        assert_eq!(vm.get_builtin_runners().len(), 1);
+    }
+
+    #[test]
+    fn with_builtins_keeps_declared_order() {
+        let vm = VirtualMachine::with_builtins(
+            false,
+            vec![
+                (BuiltinName::Output, OutputBuiltinRunner::new(true).into()),
+                (
+                    BuiltinName::Pedersen,
+                    HashBuiltinRunner::new(8, true).into(),
+                ),
+                (
+                    BuiltinName::RangeCheck,
+                    RangeCheckBuiltinRunner::new(8, 8, true).into(),
+                ),
+                (
+                    BuiltinName::Bitwise,
+                    BitwiseBuiltinRunner::new(&BitwiseInstanceDef::default(), true).into(),
+                ),
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            vm.get_builtin_runners()
+                .iter()
+                .map(|(name, _)| *name)
+                .collect::<Vec<_>>(),
+            [
+                BuiltinName::Output,
+                BuiltinName::Pedersen,
+                BuiltinName::RangeCheck,
+                BuiltinName::Bitwise
+            ]
+        );
+        assert!(matches!(
+            vm.get_builtin_runner(BuiltinName::RangeCheck),
+            Some(BuiltinRunner::RangeCheck(_))
+        ));
+        assert!(vm.get_builtin_runner(BuiltinName::EcOp).is_none());
+    }
+
+    #[test]
+    fn with_builtins_rejects_duplicate_names() {
+        assert!(matches!(
+            VirtualMachine::with_builtins(
+                false,
+                vec![
+                    (BuiltinName::Output, OutputBuiltinRunner::new(true).into()),
+                    (BuiltinName::Output, OutputBuiltinRunner::new(true).into()),
+                ],
+            ),
+            Err(RunnerError::DuplicateBuiltin(BuiltinName::Output))
+        ));
+    }
+
+    #[test]
+    fn typed_memory_helpers() {
+        let mut vm = vm!();
+        vm.segments.add(&mut vm.memory);
+        vm.segments.add(&mut vm.memory);
+        vm.insert_value(&relocatable!(1, 0), Felt::new(7)).unwrap();
+        vm.insert_value(&relocatable!(1, 1), relocatable!(0, 3))
+            .unwrap();
+
+        assert_eq!(
+            vm.get_integer(&relocatable!(1, 0)).unwrap().as_ref(),
+            &Felt::new(7)
+        );
+        assert_eq!(
+            vm.get_relocatable(&relocatable!(1, 1)),
+            Ok(relocatable!(0, 3))
+        );
+        assert_eq!(
+            vm.get_integer(&relocatable!(1, 1)),
+            Err(VirtualMachineError::ExpectedInteger(mayberelocatable!(
+                1, 1
+            )))
+        );
+        assert_eq!(
+            vm.get_relocatable(&relocatable!(1, 0)),
+            Err(VirtualMachineError::ExpectedRelocatable(mayberelocatable!(
+                1, 0
+            )))
+        );
+        assert_eq!(
+            vm.get_integer(&relocatable!(1, 2)),
+            Err(VirtualMachineError::ExpectedInteger(mayberelocatable!(
+                1, 2
+            )))
+        );
+        assert_eq!(
+            vm.insert_value(&relocatable!(1, 0), Felt::new(8)),
+            Err(VirtualMachineError::MemoryError(
+                MemoryError::InconsistentMemory(
+                    mayberelocatable!(1, 0),
+                    mayberelocatable!(7),
+                    mayberelocatable!(8)
+                )
+            ))
+        );
     }
 
     #[test]
@@ -3605,7 +6491,7 @@ mod tests {
     #[test]
     fn get_and_set_fp() {
         let mut vm = vm!();
-        vm.set_fp(3);
+        vm.set_fp(relocatable!(1, 3)).unwrap();
         assert_eq!(
             vm.get_fp(),
             Relocatable {
@@ -3615,9 +6501,44 @@ mod tests {
         )
     }
 
+    #[test]
+    fn set_registers_outside_execution_segment() {
+        let mut vm = vm!();
+        vm.set_ap(relocatable!(1, 2)).unwrap();
+        assert_eq!(
+            vm.set_ap(relocatable!(2, 5)),
+            Err(VirtualMachineError::RegisterOutsideExecutionSegment(
+                Register::AP,
+                relocatable!(2, 5)
+            ))
+        );
+        assert_eq!(
+            vm.set_fp(relocatable!(0, 1)).unwrap_err().to_string(),
+            "Can't set FP to 0:1, it must point into the execution segment"
+        );
+        //The registers are left unchanged
+        assert_eq!(vm.get_ap(), relocatable!(1, 2));
+        assert_eq!(vm.get_fp(), relocatable!(1, 0));
+    }
+
+    #[test]
+    fn get_maybe_unallocated_segment_or_int_key() {
+        let mut vm = vm!();
+        add_segments!(vm, 2);
+        assert_eq!(
+            vm.get_maybe(&relocatable!(5, 2)),
+            Err(MemoryError::UnallocatedSegmentRead(5, 2))
+        );
+        assert_eq!(
+            vm.get_maybe(&mayberelocatable!(7)),
+            Err(MemoryError::AddressNotRelocatable)
+        );
+    }
+
     #[test]
     fn get_maybe_key_not_in_memory() {
-        let vm = vm!();
+        let mut vm = vm!();
+        add_segments!(vm, 6);
         assert_eq!(
             vm.get_maybe(&Relocatable {
                 segment_index: 5,
@@ -3650,6 +6571,307 @@ mod tests {
         );
     }
 
+    ///Writes 100 cells, after the ones in segment 1 and in a new segment, and adds a temporary segment
+    fn run_speculatively(vm: &mut VirtualMachine) {
+        vm.start_overlay().unwrap();
+        let new_segment = vm.add_memory_segment();
+        for i in 0..50 {
+            vm.memory
+                .insert_value(relocatable!(1, 2 + i), Felt::new(i))
+                .unwrap();
+            vm.memory
+                .insert_value(new_segment + i, Felt::new(i))
+                .unwrap();
+        }
+        vm.add_temporary_segment();
+        vm.segments.compute_effective_sizes(&vm.memory);
+    }
+
+    #[test]
+    fn discard_overlay_restores_memory_and_segments() {
+        let mut vm = vm!();
+        add_segments!(vm, 2);
+        vm.memory
+            .insert_value(relocatable!(0, 0), Felt::new(1))
+            .unwrap();
+        vm.memory
+            .insert_value(relocatable!(1, 1), relocatable!(0, 0))
+            .unwrap();
+        let data = vm.memory.data.clone();
+        let segments = vm.segments.clone();
+
+        run_speculatively(&mut vm);
+        assert_eq!(vm.memory.data[2].len(), 50);
+        assert_eq!(
+            vm.start_overlay(),
+            Err(VirtualMachineError::MemoryError(
+                MemoryError::OverlayAlreadyStarted
+            ))
+        );
+
+        assert_eq!(vm.discard_overlay(), Ok(()));
+        assert_eq!(vm.memory.data, data);
+        assert!(vm.memory.temp_data.is_empty());
+        assert_eq!(vm.segments, segments);
+        assert!(!vm.memory.has_overlay());
+        //The discarded segment index is handed out again
+        assert_eq!(vm.add_memory_segment(), relocatable!(2, 0));
+    }
+
+    #[test]
+    fn commit_overlay_keeps_writes() {
+        let mut vm = vm!();
+        add_segments!(vm, 2);
+        vm.memory
+            .insert_value(relocatable!(1, 1), relocatable!(0, 0))
+            .unwrap();
+
+        run_speculatively(&mut vm);
+        assert_eq!(vm.commit_overlay(), Ok(()));
+        assert_eq!(vm.memory.data.len(), 3);
+        assert_eq!(vm.memory.data[1].len(), 52);
+        assert_eq!(
+            vm.memory.get_integer(&relocatable!(2, 49)),
+            Ok(Cow::Owned(Felt::new(49)))
+        );
+        assert_eq!(vm.segments.num_segments, 3);
+        assert_eq!(vm.segments.num_temp_segments, 1);
+        assert_eq!(
+            vm.discard_overlay(),
+            Err(VirtualMachineError::MemoryError(MemoryError::NoOverlay))
+        );
+    }
+
+    //Everything restore is expected to roll back
+    type VmState = (
+        (Relocatable, usize, usize, usize),
+        Vec<Vec<Option<MaybeRelocatable>>>,
+        Option<Vec<TraceEntry>>,
+        Option<HashSet<Relocatable>>,
+        MemorySegmentManager,
+    );
+
+    fn vm_state(vm: &VirtualMachine) -> VmState {
+        (
+            (
+                vm.run_context.pc,
+                vm.run_context.ap,
+                vm.run_context.fp,
+                vm.current_step,
+            ),
+            vm.memory.data.clone(),
+            vm.trace.clone(),
+            vm.accessed_addresses.clone(),
+            vm.segments.clone(),
+        )
+    }
+
+    #[test]
+    fn reconstruct_backtrace_of_recursion() {
+        let program = Program::from_file(
+            Path::new("cairo_programs/manually_compiled/recursive_countdown_program.json"),
+            Some("main"),
+        )
+        .unwrap();
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        let mut cairo_runner = cairo_runner!(program);
+        let mut vm = vm!();
+        let end = cairo_runner.initialize(&mut vm).unwrap();
+
+        //countdown(0) returns at pc 7, after main called countdown(2)
+        cairo_runner
+            .run_until_pc(relocatable!(0, 7), &mut vm, &mut hint_processor)
+            .unwrap();
+        assert_eq!(vm.get_call_depth(), 3);
+        let entry = |return_pc, function_name: Option<&str>| BacktraceEntry {
+            return_pc,
+            function_name: function_name.map(str::to_string),
+        };
+        assert_eq!(
+            vm.reconstruct_backtrace(Some(&program)),
+            vec![
+                entry(end, None),
+                entry(relocatable!(0, 4), Some("__main__.main")),
+                entry(relocatable!(0, 13), Some("__main__.countdown")),
+                entry(relocatable!(0, 13), Some("__main__.countdown")),
+            ]
+        );
+        assert_eq!(
+            vm.reconstruct_backtrace(None)
+                .iter()
+                .map(|entry| entry.return_pc)
+                .collect::<Vec<_>>(),
+            vec![
+                end,
+                relocatable!(0, 4),
+                relocatable!(0, 13),
+                relocatable!(0, 13)
+            ]
+        );
+
+        cairo_runner
+            .run_until_pc(end, &mut vm, &mut hint_processor)
+            .unwrap();
+        assert_eq!(vm.get_call_depth(), 0);
+    }
+
+    #[test]
+    fn step_ext_reports_failed_assert_eq() {
+        let mut vm = vm!();
+        //[ap - 1] = 2 at (0, 0), with [ap - 1] = 5
+        vm.memory = memory![((0, 0), 0x400680017fff7fff_i64), ((0, 1), 2), ((1, 0), 5)];
+        run_context!(vm, 0, 1, 1);
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+
+        let error = vm
+            .step_ext(
+                &mut hint_processor,
+                exec_scopes_ref!(),
+                &HashMap::new(),
+                &HashMap::new(),
+            )
+            .unwrap_err();
+        assert_eq!(
+            error.inner_exc,
+            VirtualMachineError::DiffAssertValues(
+                MaybeRelocatable::from(Felt::new(5)),
+                MaybeRelocatable::from(Felt::new(2))
+            )
+        );
+        assert_eq!(
+            error.to_string(),
+            "Error at pc=0:0:\n\
+             An ASSERT_EQ instruction failed: 5 != 2.\n\
+             pc=0:0, ap=1:1, fp=1:1\n\
+             Instruction: AssertEq, dst=[AP-1], op0=[FP-1], op1=Imm+1, res=Op1, \
+             pc_update=Regular, ap_update=Regular, fp_update=Regular\n\
+             Operands: dst=[1:0]=5, op0=[1:0]=5, op1=[0:1]=2, res=2\n"
+        );
+    }
+
+    #[test]
+    fn max_call_depth_stops_unbounded_recursion() {
+        let mut vm = vm!();
+        //call rel 0 at (0, 0), calls itself forever
+        vm.memory = memory![((0, 0), 0x1104800180018000_i64), ((0, 1), 0), ((1, 0), 0)];
+        run_context!(vm, 0, 2, 2);
+        vm.set_max_call_depth(Some(3));
+
+        for _ in 0..3 {
+            vm.step_instruction().unwrap();
+        }
+        assert_eq!(vm.get_call_depth(), 3);
+        assert_eq!(
+            vm.step_instruction(),
+            Err(VirtualMachineError::MaxCallDepthExceeded(3))
+        );
+        assert_eq!(vm.current_step, 3);
+    }
+
+    #[test]
+    fn restore_snapshot_after_running_steps() {
+        let program = Program::from_file(
+            Path::new("cairo_programs/manually_compiled/fibonacci_output_program.json"),
+            Some("main"),
+        )
+        .unwrap();
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        let mut cairo_runner = cairo_runner!(program);
+        let mut vm = vm!(true);
+        cairo_runner.initialize(&mut vm).unwrap();
+
+        cairo_runner
+            .run_for_steps(3, &mut vm, &mut hint_processor)
+            .unwrap();
+        let state = vm_state(&vm);
+        let snapshot = vm.snapshot(&cairo_runner.exec_scopes).unwrap();
+        cairo_runner
+            .run_for_steps(3, &mut vm, &mut hint_processor)
+            .unwrap();
+        let next_state = vm_state(&vm);
+        assert_ne!(next_state, state);
+
+        assert_eq!(vm.restore(snapshot), Ok(()));
+        assert_eq!(vm_state(&vm), state);
+        assert!(!vm.memory.has_overlay());
+        //Running the same steps again gets to the same state
+        let _snapshot = vm.snapshot(&cairo_runner.exec_scopes).unwrap();
+        cairo_runner
+            .run_for_steps(3, &mut vm, &mut hint_processor)
+            .unwrap();
+        assert_eq!(vm_state(&vm), next_state);
+    }
+
+    #[test]
+    fn restore_snapshot_discards_builtin_deductions() {
+        let mut builtin = HashBuiltinRunner::new(8, true);
+        builtin.base = 2;
+        let mut vm = vm!();
+        vm.builtin_runners
+            .push((BuiltinName::Pedersen, builtin.into()));
+        run_context!(vm, 0, 2, 3);
+        //[ap - 1] = [[fp - 3]], with [fp - 3] pointing to the output cell of the first instance
+        vm.memory = memory![
+            ((0, 0), 0x400280007ffd7fff_i64),
+            ((1, 0), (2, 2)),
+            ((2, 0), 32),
+            ((2, 1), 72)
+        ];
+        let state = vm_state(&vm);
+        let snapshot = vm.snapshot(&ExecutionScopes::new()).unwrap();
+        assert_eq!(vm.step_instruction(), Ok(()));
+        let hash = vm
+            .memory
+            .get(&relocatable!(2, 2))
+            .unwrap()
+            .unwrap()
+            .into_owned();
+        assert_eq!(
+            vm.memory.get(&relocatable!(1, 1)),
+            Ok(Some(Cow::Borrowed(&hash)))
+        );
+
+        assert_eq!(vm.restore(snapshot), Ok(()));
+        assert_eq!(vm_state(&vm), state);
+        //The output is deduced again instead of being taken as already verified
+        assert_eq!(vm.step_instruction(), Ok(()));
+        assert_eq!(
+            vm.memory.get(&relocatable!(2, 2)),
+            Ok(Some(Cow::Borrowed(&hash)))
+        );
+    }
+
+    #[test]
+    fn restore_snapshot_discards_dictionaries() {
+        let mut vm = vm!();
+        let mut exec_scopes = ExecutionScopes::new();
+        let dict_manager = Rc::new(RefCell::new(DictManager::new()));
+        exec_scopes.insert_value("dict_manager", dict_manager.clone());
+
+        let snapshot = vm.snapshot(&exec_scopes).unwrap();
+        assert_eq!(
+            vm.snapshot(&exec_scopes).unwrap_err(),
+            VirtualMachineError::MemoryError(MemoryError::OverlayAlreadyStarted)
+        );
+        dict_manager
+            .borrow_mut()
+            .new_dict(&mut vm, HashMap::new())
+            .unwrap();
+        assert_eq!(dict_manager.borrow().trackers.len(), 1);
+        assert_eq!(vm.restore(snapshot), Ok(()));
+        assert!(dict_manager.borrow().trackers.is_empty());
+        assert_eq!(vm.segments.num_segments, 0);
+
+        let snapshot = vm.snapshot(&exec_scopes).unwrap();
+        dict_manager
+            .borrow_mut()
+            .new_dict(&mut vm, HashMap::new())
+            .unwrap();
+        assert_eq!(vm.commit_snapshot(snapshot), Ok(()));
+        assert_eq!(dict_manager.borrow().trackers.len(), 1);
+        assert!(!vm.memory.has_overlay());
+    }
+
     #[test]
     fn add_temporary_segments() {
         let mut vm = vm!();
@@ -3681,6 +6903,110 @@ mod tests {
         );
     }
 
+    #[test]
+    fn decode_current_instruction_uses_cache() {
+        let mut vm = vm!();
+        //[ap - 1] = 2 at (0, 0)
+        vm.memory = memory![((0, 0), 0x400680017fff7fff_i64), ((0, 1), 2)];
+        let instruction = vm.decode_current_instruction().unwrap();
+        assert_eq!(vm.decode_current_instruction(), Ok(instruction));
+        assert_eq!(vm.instructions_decoded, 1);
+    }
+
+    #[test]
+    fn loop_decodes_each_instruction_once() {
+        let mut vm = vm!();
+        //jmp rel 0 at (0, 0), loops forever
+        vm.memory = memory![((0, 0), 0x10780017fff7fff_i64), ((0, 1), 0), ((1, 1), 0)];
+        run_context!(vm, 0, 2, 2);
+        for _ in 0..10 {
+            vm.step_instruction().unwrap();
+        }
+        assert_eq!(vm.run_context.pc, relocatable!(0, 0));
+        assert_eq!(vm.current_step, 10);
+        assert_eq!(vm.instructions_decoded, 1);
+    }
+
+    #[test]
+    fn decode_current_instruction_refreshes_rewritten_encoding() {
+        let mut vm = vm!();
+        //[ap - 1] = 2 at (0, 0)
+        vm.memory = memory![((0, 0), 0x400680017fff7fff_i64), ((0, 1), 2)];
+        assert_eq!(
+            vm.decode_current_instruction().unwrap().opcode,
+            Opcode::AssertEq
+        );
+
+        //The program segment is replaced by ret at (0, 0)
+        vm.memory = memory![((0, 0), 0x208b7fff7fff7ffe_i64)];
+        assert_eq!(vm.decode_current_instruction().unwrap().opcode, Opcode::Ret);
+        assert_eq!(vm.instructions_decoded, 2);
+        assert_eq!(
+            vm.instruction_cache[0]
+                .as_ref()
+                .map(|(_, instruction)| &instruction.opcode),
+            Some(&Opcode::Ret)
+        );
+    }
+
+    #[test]
+    fn decode_instruction_at_doesnt_move_pc() {
+        let mut vm = vm!();
+        //[ap - 1] = 2 at (0, 2)
+        vm.memory = memory![((0, 2), 0x400680017fff7fff_i64), ((0, 3), 2)];
+        let instruction = vm
+            .decode_instruction_at(&Relocatable::from((0, 2)))
+            .unwrap();
+        assert_eq!(instruction.opcode, Opcode::AssertEq);
+        assert_eq!(instruction.op1_addr, Op1Addr::Imm);
+        assert_eq!(instruction.size(), 2);
+        assert_eq!(vm.run_context.pc, Relocatable::from((0, 0)));
+    }
+
+    #[test]
+    fn step_instruction_skips_next_instruction() {
+        let mut vm = vm!(true);
+        vm.run_context.pc = Relocatable::from((0, 2));
+        vm.run_context.ap = 2;
+        vm.run_context.fp = 2;
+        //[ap - 1] = 2 would fail, as [ap - 1] = 1
+        vm.memory = memory![((0, 2), 0x400680017fff7fff_i64), ((0, 3), 2), ((1, 1), 1)];
+        vm.skip_next_instruction_execution();
+        assert_eq!(vm.step_instruction(), Ok(()));
+        assert_eq!(vm.run_context.pc, Relocatable::from((0, 4)));
+        assert_eq!(vm.run_context.ap, 2);
+        assert_eq!(vm.current_step, 0);
+        //Only the next instruction is skipped
+        vm.run_context.pc = Relocatable::from((0, 2));
+        assert_eq!(
+            vm.step_instruction(),
+            Err(VirtualMachineError::DiffAssertValues(
+                MaybeRelocatable::from(Felt::new(1)),
+                MaybeRelocatable::from(Felt::new(2))
+            ))
+        );
+    }
+
+    #[test]
+    fn step_instruction_failed_call_leaves_vm_unchanged() {
+        let mut vm = vm!(true);
+        vm.run_context.ap = 2;
+        vm.run_context.fp = 2;
+        //call rel 5, with a value other than fp already at [ap]
+        vm.memory = memory![((0, 0), 0x1104800180018000_i64), ((0, 1), 5), ((1, 2), 7)];
+        assert_eq!(
+            vm.step_instruction(),
+            Err(VirtualMachineError::CantWriteReturnFp(
+                mayberelocatable!(7),
+                mayberelocatable!(1, 2)
+            ))
+        );
+        assert_eq!(vm.run_context.pc, Relocatable::from((0, 0)));
+        assert_eq!(vm.current_step, 0);
+        assert_eq!(vm.trace, Some(Vec::new()));
+        assert_eq!(vm.memory.get(&relocatable!(1, 3)), Ok(None));
+    }
+
     #[test]
     fn add_relocation_rule_test() {
         let mut vm = vm!();
@@ -3761,13 +7087,16 @@ mod tests {
         );
     }
 
-    /// Test that the call to .gen_arg() with any other argument returns a not
-    /// implemented error.
+    /// Test that the call to .gen_arg() with any other argument returns an
+    /// unsupported argument type error.
     #[test]
-    fn gen_arg_not_implemented() {
+    fn gen_arg_unsupported_type() {
         let mut vm = vm!();
 
-        assert_eq!(vm.gen_arg(&""), Err(VirtualMachineError::NotImplemented),);
+        assert_eq!(
+            vm.gen_arg(&""),
+            Err(VirtualMachineError::UnsupportedArgType("gen_arg"))
+        );
     }
 
     #[test]
@@ -3776,12 +7105,12 @@ mod tests {
     }
 
     /// Test that the call to .gen_typed_args() with an unsupported vector
-    /// returns a not implemented error.
+    /// returns an unsupported argument type error.
     #[test]
-    fn gen_typed_args_not_implemented() {
+    fn gen_typed_args_unsupported_type() {
         assert_eq!(
             gen_typed_args(vec![&0usize]),
-            Err(VirtualMachineError::NotImplemented),
+            Err(VirtualMachineError::UnsupportedArgType("gen_typed_args")),
         );
     }
 
@@ -3829,28 +7158,28 @@ mod tests {
     fn mark_as_accessed() {
         let mut vm = vm!();
         vm.run_finished = true;
-        vm.accessed_addresses = Some(Vec::new());
+        vm.accessed_addresses = Some(HashSet::new());
         vm.mark_address_range_as_accessed((0, 0).into(), 3).unwrap();
         vm.mark_address_range_as_accessed((0, 10).into(), 2)
             .unwrap();
         vm.mark_address_range_as_accessed((1, 1).into(), 1).unwrap();
         assert_eq!(
             vm.accessed_addresses,
-            Some(vec![
+            Some(HashSet::from([
                 (0, 0).into(),
                 (0, 1).into(),
                 (0, 2).into(),
                 (0, 10).into(),
                 (0, 11).into(),
                 (1, 1).into(),
-            ]),
+            ])),
         );
     }
 
     #[test]
     fn mark_as_accessed_run_not_finished() {
         let mut vm = vm!();
-        vm.accessed_addresses = Some(Vec::new());
+        vm.accessed_addresses = Some(HashSet::new());
         assert_eq!(
             vm.mark_address_range_as_accessed((0, 0).into(), 3),
             Err(VirtualMachineError::RunNotFinished),
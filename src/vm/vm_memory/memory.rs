@@ -1,13 +1,22 @@
 use crate::{
-    types::relocatable::{MaybeRelocatable, Relocatable},
+    types::{
+        builtin_name::BuiltinName,
+        relocatable::{relocate_address, relocate_value, MaybeRelocatable, Relocatable},
+    },
     utils::from_relocatable_to_indexes,
     vm::errors::{memory_errors::MemoryError, vm_errors::VirtualMachineError},
 };
-use felt::Felt;
+use felt::{Felt, FeltOps};
+use num_bigint::{BigInt, Sign};
 use std::{
     borrow::Cow,
+    cmp,
     collections::{HashMap, HashSet},
+    fs,
+    iter::Enumerate,
     mem::swap,
+    path::Path,
+    slice,
 };
 
 pub struct ValidationRule(
@@ -15,6 +24,19 @@ pub struct ValidationRule(
     pub  Box<dyn Fn(&Memory, &MaybeRelocatable) -> Result<Vec<MaybeRelocatable>, MemoryError>>,
 );
 
+///Who wrote the value of a memory cell
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MemoryOrigin {
+    ///Deduced by the instruction at pc
+    Instruction { pc: Relocatable },
+    ///Written by the hint at the given index of the hints at pc
+    Hint { pc: Relocatable, index: usize },
+    ///Deduced by the builtin with the given name
+    Deduction { builtin: BuiltinName },
+    ///Loaded outside of the run loop (program, entrypoint stack and arguments)
+    Preload,
+}
+
 pub struct Memory {
     pub data: Vec<Vec<Option<MaybeRelocatable>>>,
     pub temp_data: Vec<Vec<Option<MaybeRelocatable>>>,
@@ -23,6 +45,25 @@ pub struct Memory {
     pub(crate) relocation_rules: HashMap<usize, Relocatable>,
     pub validated_addresses: HashSet<MaybeRelocatable>,
     validation_rules: HashMap<usize, ValidationRule>,
+    // Origin of each written cell, only tracked when enabled
+    origins: Option<HashMap<Relocatable, MemoryOrigin>>,
+    // Origin assigned to the cells written by insert
+    current_origin: MemoryOrigin,
+    // Segments emptied by reset, kept so the next run can reuse their allocations
+    spare_segments: Vec<Vec<Option<MaybeRelocatable>>>,
+    // Changes made since start_overlay, undone by discard_overlay
+    overlay: Option<MemoryOverlay>,
+}
+
+///What a speculative run changed, recorded as it happens so that it can be rolled back without
+///copying the memory beforehand
+#[derive(Debug, Default)]
+struct MemoryOverlay {
+    segment_lens: Vec<usize>,
+    temp_segment_lens: Vec<usize>,
+    written: Vec<Relocatable>,
+    validated_addresses: Vec<MaybeRelocatable>,
+    relocation_rules: Vec<usize>,
 }
 
 impl Memory {
@@ -33,21 +74,146 @@ impl Memory {
             relocation_rules: HashMap::new(),
             validated_addresses: HashSet::<MaybeRelocatable>::new(),
             validation_rules: HashMap::new(),
+            origins: None,
+            current_origin: MemoryOrigin::Preload,
+            spare_segments: Vec::new(),
+            overlay: None,
+        }
+    }
+
+    ///Removes every segment, validation rule and relocation rule, leaving the memory as if it was
+    ///just created while keeping its allocations for the next run.
+    ///Origin tracking stays enabled if it was, with no origins recorded.
+    pub fn reset(&mut self) {
+        for mut segment in self.data.drain(..) {
+            segment.clear();
+            self.spare_segments.push(segment);
+        }
+        self.temp_data.clear();
+        self.relocation_rules.clear();
+        self.validated_addresses.clear();
+        self.validation_rules.clear();
+        if let Some(origins) = &mut self.origins {
+            origins.clear();
+        }
+        self.current_origin = MemoryOrigin::Preload;
+        self.overlay = None;
+    }
+
+    ///Starts recording the changes made to the memory, so that they can be undone with
+    ///discard_overlay or kept with commit_overlay. Writes still go to the memory, which is
+    ///never copied, so starting an overlay is cheap regardless of the memory's size.
+    pub fn start_overlay(&mut self) -> Result<(), MemoryError> {
+        if self.overlay.is_some() {
+            return Err(MemoryError::OverlayAlreadyStarted);
+        }
+        self.overlay = Some(MemoryOverlay {
+            segment_lens: self.data.iter().map(Vec::len).collect(),
+            temp_segment_lens: self.temp_data.iter().map(Vec::len).collect(),
+            ..Default::default()
+        });
+        Ok(())
+    }
+
+    ///Returns true if the changes to the memory are being recorded by an overlay
+    pub fn has_overlay(&self) -> bool {
+        self.overlay.is_some()
+    }
+
+    ///Keeps the changes made since start_overlay and stops recording them
+    pub fn commit_overlay(&mut self) -> Result<(), MemoryError> {
+        self.overlay.take().ok_or(MemoryError::NoOverlay)?;
+        Ok(())
+    }
+
+    ///Undoes every change made since start_overlay: written cells, added segments, validated
+    ///addresses, recorded origins and relocation rules
+    pub fn discard_overlay(&mut self) -> Result<(), MemoryError> {
+        let overlay = self.overlay.take().ok_or(MemoryError::NoOverlay)?;
+        for addr in overlay.written {
+            if let Some(origins) = &mut self.origins {
+                origins.remove(&addr);
+            }
+            let (i, j) = from_relocatable_to_indexes(&addr);
+            let data = if addr.segment_index.is_negative() {
+                &mut self.temp_data
+            } else {
+                &mut self.data
+            };
+            if let Some(cell) = data.get_mut(i).and_then(|segment| segment.get_mut(j)) {
+                *cell = None;
+            }
         }
+        for mut segment in self.data.drain(overlay.segment_lens.len()..) {
+            segment.clear();
+            self.spare_segments.push(segment);
+        }
+        self.temp_data.truncate(overlay.temp_segment_lens.len());
+        for (segment, len) in self
+            .data
+            .iter_mut()
+            .zip(overlay.segment_lens)
+            .chain(self.temp_data.iter_mut().zip(overlay.temp_segment_lens))
+        {
+            segment.truncate(len);
+        }
+        for addr in overlay.validated_addresses {
+            self.validated_addresses.remove(&addr);
+        }
+        for segment_index in overlay.relocation_rules {
+            self.relocation_rules.remove(&segment_index);
+        }
+        Ok(())
+    }
+
+    ///Returns the size of the segment, one past the highest offset written to it, or None if the
+    ///segment doesn't exist. Segments grow as insert writes past their end, so the size is kept
+    ///up to date during the run and can be read at any point.
+    pub fn get_segment_size(&self, segment_index: usize) -> Option<usize> {
+        self.data.get(segment_index).map(Vec::len)
+    }
+
+    //Computes the size of each segment by looking for its last written cell. Used to check the
+    //sizes kept by insert.
+    pub(crate) fn scan_segment_sizes(&self) -> Vec<usize> {
+        self.data
+            .iter()
+            .map(|segment| {
+                segment
+                    .iter()
+                    .rposition(Option::is_some)
+                    .map_or(0, |last| last + 1)
+            })
+            .collect()
+    }
+
+    ///Returns an empty segment, reusing the allocation of one removed by reset if available
+    pub(crate) fn take_spare_segment(&mut self) -> Vec<Option<MaybeRelocatable>> {
+        self.spare_segments.pop().unwrap_or_default()
     }
     ///Inserts an MaybeRelocatable value into an address given by a MaybeRelocatable::Relocatable
     /// Will panic if the segment index given by the address corresponds to a non-allocated segment
     /// If the address isnt contiguous with previously inserted data, memory gaps will be represented by inserting None values
     pub fn insert<'a, K: 'a, V: 'a>(&mut self, key: &'a K, val: &'a V) -> Result<(), MemoryError>
     where
-        Relocatable: TryFrom<&'a K>,
         MaybeRelocatable: From<&'a K>,
         MaybeRelocatable: From<&'a V>,
     {
-        let relocatable: Relocatable = key
+        self.insert_value(MaybeRelocatable::from(key), MaybeRelocatable::from(val))
+    }
+
+    ///Inserts val at key, converting both into MaybeRelocatable. Fails with AddressNotRelocatable
+    ///if key is an integer
+    pub fn insert_value<K: Into<MaybeRelocatable>, V: Into<MaybeRelocatable>>(
+        &mut self,
+        key: K,
+        val: V,
+    ) -> Result<(), MemoryError> {
+        let key = key.into();
+        let relocatable: Relocatable = (&key)
             .try_into()
             .map_err(|_| MemoryError::AddressNotRelocatable)?;
-        let val = MaybeRelocatable::from(val);
+        let val = val.into();
         let (value_index, value_offset) = from_relocatable_to_indexes(&relocatable);
 
         let data = if relocatable.segment_index.is_negative() {
@@ -69,7 +235,15 @@ impl Memory {
         // At this point there's *something* in there
 
         match segment[value_offset] {
-            None => segment[value_offset] = Some(val),
+            None => {
+                segment[value_offset] = Some(val);
+                if let Some(origins) = &mut self.origins {
+                    origins.insert(relocatable, self.current_origin.clone());
+                }
+                if let Some(overlay) = &mut self.overlay {
+                    overlay.written.push(relocatable);
+                }
+            }
             Some(ref current_value) => {
                 if current_value != &val {
                     //Existing memory cannot be changed
@@ -81,10 +255,12 @@ impl Memory {
                 }
             }
         };
-        self.validate_memory_cell(&MaybeRelocatable::from(key))
+        self.validate_memory_cell(&key)
     }
 
-    /// Retrieve a value from memory (either normal or temporary) and apply relocation rules
+    /// Retrieve a value from memory (either normal or temporary) and apply relocation rules.
+    /// Returns None if the cell wasn't written, and an error if the key isn't a relocatable or
+    /// its segment wasn't allocated.
     pub(crate) fn get<'a, 'b: 'a, K: 'a>(
         &'b self,
         key: &'a K,
@@ -102,13 +278,14 @@ impl Memory {
             &self.data
         };
         let (i, j) = from_relocatable_to_indexes(&relocatable);
-        if data.len() > i && data[i].len() > j {
-            if let Some(ref element) = data[i][j] {
-                return Ok(Some(self.relocate_value(element)));
-            }
-        }
-
-        Ok(None)
+        let segment = data.get(i).ok_or(MemoryError::UnallocatedSegmentRead(
+            relocatable.segment_index,
+            data.len(),
+        ))?;
+        Ok(segment
+            .get(j)
+            .and_then(Option::as_ref)
+            .map(|element| self.relocate_value(element)))
     }
 
     /// Relocates the memory according to the relocation rules and clears `self.relocaction_rules`.
@@ -116,6 +293,10 @@ impl Memory {
         if self.relocation_rules.is_empty() {
             return Ok(());
         }
+        //Relocation moves every cell, which the overlay can't undo
+        if self.overlay.is_some() {
+            return Err(MemoryError::RelocationDuringOverlay);
+        }
 
         let mut prev_data = Vec::new();
         let mut prev_temp_data = Vec::new();
@@ -178,6 +359,15 @@ impl Memory {
             segment_data[new_addr.offset] = Some(new_value);
         }
 
+        if let Some(origins) = self.origins.take() {
+            self.origins = Some(
+                origins
+                    .into_iter()
+                    .map(|(addr, origin)| (self.relocate_value(addr), origin))
+                    .collect(),
+            );
+        }
+
         self.relocation_rules.clear();
         Ok(())
     }
@@ -210,6 +400,9 @@ impl Memory {
         }
 
         self.relocation_rules.insert(segment_index, dst_ptr);
+        if let Some(overlay) = &mut self.overlay {
+            overlay.relocation_rules.push(segment_index);
+        }
         Ok(())
     }
 
@@ -236,34 +429,99 @@ impl Memory {
         }
     }
 
-    pub fn insert_value<T: Into<MaybeRelocatable>>(
-        &mut self,
-        key: &Relocatable,
-        val: T,
-    ) -> Result<(), VirtualMachineError> {
-        self.insert(key, &val.into())
-            .map_err(VirtualMachineError::MemoryError)
+    ///Starts recording the origin of every cell written from now on
+    pub fn enable_origin_tracking(&mut self) {
+        self.origins.get_or_insert_with(HashMap::new);
+    }
+
+    ///Returns true if the origin of the written cells is being recorded
+    pub fn is_origin_tracking_enabled(&self) -> bool {
+        self.origins.is_some()
+    }
+
+    ///Returns the origin of the value at addr, if origin tracking was enabled when it was written
+    pub fn get_origin(&self, addr: &Relocatable) -> Option<&MemoryOrigin> {
+        self.origins.as_ref()?.get(addr)
+    }
+
+    ///Returns the addresses written by hints along with their origin, sorted by address
+    pub fn get_hint_origins(&self) -> Vec<(Relocatable, &MemoryOrigin)> {
+        let mut hint_origins: Vec<_> = self
+            .origins
+            .iter()
+            .flatten()
+            .filter(|(_, origin)| matches!(origin, MemoryOrigin::Hint { .. }))
+            .map(|(addr, origin)| (*addr, origin))
+            .collect();
+        hint_origins.sort_by_key(|(addr, _)| (addr.segment_index, addr.offset));
+        hint_origins
+    }
+
+    ///Appends data to the segment of ptr in one go if ptr is the end of the segment and
+    ///writing the cells one at a time would have no other effect: no overlay or origin tracking
+    ///is active and the segment has no validation rule. Returns false, without writing anything,
+    ///otherwise.
+    pub(crate) fn extend_segment(&mut self, ptr: &Relocatable, data: &[MaybeRelocatable]) -> bool {
+        if self.overlay.is_some() || self.origins.is_some() {
+            return false;
+        }
+        let (index, offset) = from_relocatable_to_indexes(ptr);
+        let segment = if ptr.segment_index.is_negative() {
+            self.temp_data.get_mut(index)
+        } else if self.validation_rules.contains_key(&index) {
+            None
+        } else {
+            self.data.get_mut(index)
+        };
+        match segment {
+            Some(segment) if segment.len() == offset => {
+                segment.extend(data.iter().cloned().map(Some));
+                true
+            }
+            _ => false,
+        }
+    }
+
+    ///Sets the origin assigned to the cells written from now on
+    pub(crate) fn set_origin(&mut self, origin: MemoryOrigin) {
+        self.current_origin = origin;
     }
 
     pub fn add_validation_rule(&mut self, segment_index: usize, rule: ValidationRule) {
         self.validation_rules.insert(segment_index, rule);
     }
 
-    fn validate_memory_cell(&mut self, address: &MaybeRelocatable) -> Result<(), MemoryError> {
+    pub(crate) fn validate_memory_cell(
+        &mut self,
+        address: &MaybeRelocatable,
+    ) -> Result<(), MemoryError> {
         if let &MaybeRelocatable::RelocatableValue(ref rel_addr) = address {
             if !self.validated_addresses.contains(address) {
+                let mut validated_addresses = Vec::new();
                 for (index, validation_rule) in self.validation_rules.iter() {
                     if rel_addr.segment_index == *index as isize {
-                        self.validated_addresses
-                            .extend(validation_rule.0(self, address)?);
+                        validated_addresses.extend(validation_rule.0(self, address)?);
                     }
                 }
+                for validated in validated_addresses {
+                    self.add_validated_address(validated);
+                }
             }
             Ok(())
         } else {
             Err(MemoryError::AddressNotRelocatable)
         }
     }
+
+    ///Records address as validated, so its validation rule doesn't run on it again
+    pub(crate) fn add_validated_address(&mut self, address: MaybeRelocatable) {
+        if let Some(overlay) = &mut self.overlay {
+            if !self.validated_addresses.contains(&address) {
+                overlay.validated_addresses.push(address.clone());
+            }
+        }
+        self.validated_addresses.insert(address);
+    }
     ///Applies validation_rules to the current memory
     //Should be called during initialization, as None values will raise a FoundNonInt error
     pub fn validate_existing_memory(&mut self) -> Result<(), MemoryError> {
@@ -275,12 +533,37 @@ impl Memory {
         Ok(())
     }
 
+    //Ranges are read from a single segment, which must have been allocated unless the range is empty.
+    //Returns the capacity to allocate for the range's values, capped at the cells left in the
+    //segment, as size may come from a hint and be arbitrarily large
+    fn range_capacity(&self, addr: &Relocatable, size: usize) -> Result<usize, MemoryError> {
+        if size == 0 {
+            return Ok(0);
+        }
+        let (segment_index, offset) = from_relocatable_to_indexes(addr);
+        let data = if addr.segment_index.is_negative() {
+            &self.temp_data
+        } else {
+            &self.data
+        };
+        match data.get(segment_index) {
+            Some(segment) => Ok(size.min(segment.len().saturating_sub(offset))),
+            None => Err(MemoryError::UnallocatedSegmentRead(
+                addr.segment_index,
+                data.len(),
+            )),
+        }
+    }
+
     pub fn get_range(
         &self,
         addr: &MaybeRelocatable,
         size: usize,
     ) -> Result<Vec<Option<Cow<MaybeRelocatable>>>, MemoryError> {
-        let mut values = Vec::new();
+        let start = addr
+            .try_into()
+            .map_err(|_| MemoryError::AddressNotRelocatable)?;
+        let mut values = Vec::with_capacity(self.range_capacity(&start, size)?);
 
         for i in 0..size {
             values.push(self.get(&addr.add_usize(i))?);
@@ -294,7 +577,10 @@ impl Memory {
         addr: &MaybeRelocatable,
         size: usize,
     ) -> Result<Vec<MaybeRelocatable>, MemoryError> {
-        let mut values = Vec::with_capacity(size);
+        let start = addr
+            .try_into()
+            .map_err(|_| MemoryError::AddressNotRelocatable)?;
+        let mut values = Vec::with_capacity(self.range_capacity(&start, size)?);
 
         for i in 0..size {
             values.push(match self.get(&addr.add_usize(i))? {
@@ -311,7 +597,7 @@ impl Memory {
         addr: &Relocatable,
         size: usize,
     ) -> Result<Vec<Cow<Felt>>, VirtualMachineError> {
-        let mut values = Vec::new();
+        let mut values = Vec::with_capacity(self.range_capacity(addr, size)?);
 
         for i in 0..size {
             values.push(self.get_integer(&(addr + i))?);
@@ -319,6 +605,165 @@ impl Memory {
 
         Ok(values)
     }
+
+    ///Relocates the cells of the non temporary segments one at a time, yielding each written cell
+    ///as its flat address and value in ascending address order, holes are skipped.
+    ///The relocation_table is the one returned by MemorySegmentManager::relocate_segments.
+    pub fn relocated_iter<'a>(
+        &'a self,
+        relocation_table: &'a [usize],
+    ) -> impl Iterator<Item = Result<(usize, Felt), MemoryError>> + 'a {
+        (0..self.data.len() as isize)
+            .flat_map(|index| self.segment_iter(index))
+            .map(|(address, value)| {
+                let address = relocate_address(address, relocation_table)?;
+                Ok((address, relocate_value(value.clone(), relocation_table)?))
+            })
+    }
+
+    ///Reads the cells of a binary memory file, like the ones written by cairo-lang or
+    ///cairo_run::write_binary_memory, as (address, value) pairs in ascending address order.
+    ///Fails if the file isn't made of 40 byte cells with strictly increasing addresses.
+    pub fn from_binary_file(path: &Path) -> Result<Vec<(usize, BigInt)>, MemoryError> {
+        let bytes =
+            fs::read(path).map_err(|error| MemoryError::BinaryMemoryFileRead(error.to_string()))?;
+        decode_binary_memory(&bytes)
+    }
+
+    ///Compares the cells loaded by from_binary_file with a relocated memory, such as the one
+    ///returned by relocate, and returns the lowest address with a different value, if any.
+    ///An address that is a hole in one of them and not in the other counts as different.
+    pub fn first_mismatching_address(
+        loaded_cells: &[(usize, BigInt)],
+        relocated_memory: &[Option<BigInt>],
+    ) -> Option<usize> {
+        let mut relocated_cells = relocated_memory
+            .iter()
+            .enumerate()
+            .filter_map(|(addr, value)| Some((addr, value.as_ref()?)));
+        let mut loaded_cells = loaded_cells.iter().map(|(addr, value)| (*addr, value));
+        loop {
+            match (loaded_cells.next(), relocated_cells.next()) {
+                (None, None) => return None,
+                (Some((addr, _)), None) | (None, Some((addr, _))) => return Some(addr),
+                (Some(loaded), Some(relocated)) if loaded != relocated => {
+                    return Some(cmp::min(loaded.0, relocated.0))
+                }
+                _ => (),
+            }
+        }
+    }
+
+    ///Flattens the non temporary segments into a single address space, segment i starting at
+    ///relocation_table[i]. Relocatable values are turned into their flat address, holes and the
+    ///address 0 are None, values are given in their canonical non negative form.
+    ///The relocation_table is the one returned by MemorySegmentManager::relocate_segments.
+    pub fn relocate(&self, relocation_table: &[usize]) -> Result<Vec<Option<BigInt>>, MemoryError> {
+        Ok(self
+            .relocate_as_felts(relocation_table)?
+            .into_iter()
+            .map(|value| value.map(|value| value.to_biguint().into()))
+            .collect())
+    }
+
+    ///Like relocate, but keeps the values as Felts
+    pub(crate) fn relocate_as_felts(
+        &self,
+        relocation_table: &[usize],
+    ) -> Result<Vec<Option<Felt>>, MemoryError> {
+        //Relocated addresses start at 1
+        let mut relocated = vec![None];
+        for cell in self.relocated_iter(relocation_table) {
+            let (address, value) = cell?;
+            if relocated.len() <= address {
+                relocated.resize(address + 1, None);
+            }
+            relocated[address] = Some(value);
+        }
+        Ok(relocated)
+    }
+
+    ///Iterates over the written cells of every segment, temporary ones included, in ascending
+    ///segment index and then offset order, holes are skipped
+    pub fn iter(&self) -> MemoryIter<'_> {
+        MemoryIter {
+            memory: self,
+            next_segment: -(self.temp_data.len() as isize),
+            current: None,
+        }
+    }
+
+    ///Iterates over the written cells of the segment in ascending offset order, holes are skipped.
+    ///Negative indexes refer to temporary segments, the iterator is empty if the segment doesn't
+    ///exist.
+    pub fn segment_iter(&self, segment_index: isize) -> SegmentIter<'_> {
+        let (index, _) = from_relocatable_to_indexes(&Relocatable::from((segment_index, 0)));
+        let data = if segment_index.is_negative() {
+            &self.temp_data
+        } else {
+            &self.data
+        };
+        SegmentIter {
+            segment_index,
+            cells: data
+                .get(index)
+                .map_or(&[][..], Vec::as_slice)
+                .iter()
+                .enumerate(),
+        }
+    }
+}
+
+///Iterator over the written cells of a segment, see Memory::segment_iter
+pub struct SegmentIter<'a> {
+    segment_index: isize,
+    cells: Enumerate<slice::Iter<'a, Option<MaybeRelocatable>>>,
+}
+
+impl<'a> Iterator for SegmentIter<'a> {
+    type Item = (Relocatable, &'a MaybeRelocatable);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.cells.find_map(|(offset, cell)| {
+            Some((
+                Relocatable::from((self.segment_index, offset)),
+                cell.as_ref()?,
+            ))
+        })
+    }
+}
+
+///Iterator over the written cells of every segment, see Memory::iter
+pub struct MemoryIter<'a> {
+    memory: &'a Memory,
+    next_segment: isize,
+    current: Option<SegmentIter<'a>>,
+}
+
+impl<'a> Iterator for MemoryIter<'a> {
+    type Item = (Relocatable, &'a MaybeRelocatable);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(cell) = self.current.as_mut().and_then(Iterator::next) {
+                return Some(cell);
+            }
+            if self.next_segment >= self.memory.data.len() as isize {
+                return None;
+            }
+            self.current = Some(self.memory.segment_iter(self.next_segment));
+            self.next_segment += 1;
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a Memory {
+    type Item = (Relocatable, &'a MaybeRelocatable);
+    type IntoIter = MemoryIter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
 }
 
 pub(crate) trait RelocateValue<'a, Input: 'a, Output: 'a> {
@@ -358,6 +803,27 @@ impl<'a> RelocateValue<'a, &'a MaybeRelocatable, Cow<'a, MaybeRelocatable>> for
     }
 }
 
+fn decode_binary_memory(bytes: &[u8]) -> Result<Vec<(usize, BigInt)>, MemoryError> {
+    let cell_chunks = bytes.chunks_exact(40);
+    if !cell_chunks.remainder().is_empty() {
+        return Err(MemoryError::BinaryMemoryFileLength(bytes.len()));
+    }
+    let mut cells: Vec<(usize, BigInt)> = Vec::with_capacity(cell_chunks.len());
+    for cell in cell_chunks {
+        let (addr, value) = cell.split_at(8);
+        let mut addr_bytes = [0; 8];
+        addr_bytes.copy_from_slice(addr);
+        let addr = u64::from_le_bytes(addr_bytes) as usize;
+        if let Some((prev_addr, _)) = cells.last() {
+            if addr <= *prev_addr {
+                return Err(MemoryError::BinaryMemoryFileUnsorted(addr, *prev_addr));
+            }
+        }
+        cells.push((addr, BigInt::from_bytes_le(Sign::Plus, value)));
+    }
+    Ok(cells)
+}
+
 impl Default for Memory {
     fn default() -> Self {
         Self::new()
@@ -368,8 +834,9 @@ impl Default for Memory {
 mod memory_tests {
     use super::*;
     use crate::{
+        relocatable,
         types::instance_definitions::ecdsa_instance_def::EcdsaInstanceDef,
-        utils::test_utils::{mayberelocatable, memory},
+        utils::test_utils::{check_memory, check_memory_address, mayberelocatable, memory},
         vm::{
             runners::builtin_runner::{RangeCheckBuiltinRunner, SignatureBuiltinRunner},
             vm_memory::memory_segments::MemorySegmentManager,
@@ -423,6 +890,72 @@ mod memory_tests {
         );
     }
 
+    #[test]
+    fn iter_skips_holes_in_segment_then_offset_order() {
+        let mut memory = memory![((0, 1), 1), ((1, 0), (0, 1)), ((1, 3), 4)];
+        memory.temp_data = vec![Vec::new(), vec![None, Some(mayberelocatable!(5))]];
+        memory.data.push(Vec::new());
+        assert_eq!(
+            memory.iter().collect::<Vec<_>>(),
+            vec![
+                (relocatable!(-2, 1), &mayberelocatable!(5)),
+                (relocatable!(0, 1), &mayberelocatable!(1)),
+                (relocatable!(1, 0), &mayberelocatable!(0, 1)),
+                (relocatable!(1, 3), &mayberelocatable!(4)),
+            ]
+        );
+        assert_eq!((&memory).into_iter().count(), 4);
+    }
+
+    #[test]
+    fn segment_iter_yields_the_segment_cells() {
+        let mut memory = memory![((0, 1), 1), ((1, 0), (0, 1)), ((1, 3), 4)];
+        memory.temp_data = vec![vec![Some(mayberelocatable!(2))]];
+        assert_eq!(
+            memory.segment_iter(1).collect::<Vec<_>>(),
+            vec![
+                (relocatable!(1, 0), &mayberelocatable!(0, 1)),
+                (relocatable!(1, 3), &mayberelocatable!(4)),
+            ]
+        );
+        assert_eq!(
+            memory.segment_iter(-1).collect::<Vec<_>>(),
+            vec![(relocatable!(-1, 0), &mayberelocatable!(2))]
+        );
+        assert_eq!(memory.segment_iter(2).next(), None);
+        assert_eq!(memory.segment_iter(-2).next(), None);
+    }
+
+    #[test]
+    fn insert_value_converts_key_and_value() {
+        let mut memory = Memory::new();
+        memory.data.push(Vec::new());
+        memory
+            .insert_value(Relocatable::from((0, 0)), 5_usize)
+            .unwrap();
+        memory.insert_value((0, 1), BigInt::from(-3)).unwrap();
+        memory
+            .insert_value((0, 2), Relocatable::from((0, 0)))
+            .unwrap();
+        memory.insert_value((0, 3), Felt::new(8)).unwrap();
+        check_memory![
+            memory,
+            ((0, 0), 5),
+            ((0, 1), (-3)),
+            ((0, 2), (0, 0)),
+            ((0, 3), 8)
+        ];
+    }
+
+    #[test]
+    fn insert_value_integer_key() {
+        let mut memory = Memory::new();
+        assert_eq!(
+            memory.insert_value(1_usize, 2_usize),
+            Err(MemoryError::AddressNotRelocatable)
+        );
+    }
+
     #[test]
     fn insert_value_in_temp_segment() {
         let key = MaybeRelocatable::from((-1, 3));
@@ -468,13 +1001,29 @@ mod memory_tests {
     fn get_non_allocated_memory() {
         let key = MaybeRelocatable::from((0, 0));
         let memory = Memory::new();
-        assert_eq!(memory.get(&key).unwrap(), None);
+        let error = memory.get(&key);
+        assert_eq!(error, Err(MemoryError::UnallocatedSegmentRead(0, 0)));
+        assert_eq!(
+            error.unwrap_err().to_string(),
+            "Can't read from segment #0, it wasn't allocated; memory has 0 segments of its kind"
+        );
+    }
+
+    #[test]
+    fn get_non_allocated_temporary_segment() {
+        let mut memory = Memory::new();
+        memory.temp_data.push(Vec::new());
+        assert_eq!(memory.get(&relocatable!(-1, 3)), Ok(None));
+        assert_eq!(
+            memory.get(&relocatable!(-2, 0)),
+            Err(MemoryError::UnallocatedSegmentRead(-2, 1))
+        );
     }
 
     #[test]
     fn get_non_existant_element() {
-        let key = MaybeRelocatable::from((0, 0));
-        let memory = Memory::new();
+        let key = MaybeRelocatable::from((0, 4));
+        let memory = memory![((0, 0), 1)];
         assert_eq!(memory.get(&key).unwrap(), None);
     }
 
@@ -618,28 +1167,61 @@ mod memory_tests {
         );
     }
 
+    #[test]
+    fn insert_validates_range_check_values() {
+        let mut builtin = RangeCheckBuiltinRunner::new(8, 8, true);
+        let mut segments = MemorySegmentManager::new();
+        let mut memory = Memory::new();
+        builtin.initialize_segments(&mut segments, &mut memory);
+        builtin.add_validation_rule(&mut memory).unwrap();
+        assert_eq!(
+            memory.insert(
+                &MaybeRelocatable::from((0, 0)),
+                &MaybeRelocatable::from(Felt::new(45)),
+            ),
+            Ok(())
+        );
+        assert!(memory
+            .validated_addresses
+            .contains(&MaybeRelocatable::from((0, 0))));
+        assert_eq!(
+            memory.insert(
+                &MaybeRelocatable::from((0, 1)),
+                &MaybeRelocatable::from(Felt::new(-10)),
+            ),
+            Err(MemoryError::NumOutOfBounds)
+        );
+        assert!(!memory
+            .validated_addresses
+            .contains(&MaybeRelocatable::from((0, 1))));
+    }
+
     #[test]
     fn validate_existing_memory_for_invalid_signature() {
         let mut builtin = SignatureBuiltinRunner::new(&EcdsaInstanceDef::default(), true);
         let mut segments = MemorySegmentManager::new();
-        let mut memory = memory![
+        let mut memory = Memory::new();
+        segments.add(&mut memory);
+        builtin.initialize_segments(&mut segments, &mut memory);
+        memory_from_memory!(
+            memory,
             (
-                (1, 0),
                 (
-                    "874739451078007766457464989774322083649278607533249481151382481072868806602",
-                    10
-                )
-            ),
-            (
-                (1, 1),
+                    (1, 0),
+                    (
+                        "874739451078007766457464989774322083649278607533249481151382481072868806602",
+                        10
+                    )
+                ),
                 (
-                    "-1472574760335685482768423018116732869320670550222259018541069375211356613248",
-                    10
+                    (1, 1),
+                    (
+                        "-1472574760335685482768423018116732869320670550222259018541069375211356613248",
+                        10
+                    )
                 )
             )
-        ];
-        segments.add(&mut memory);
-        builtin.initialize_segments(&mut segments, &mut memory);
+        );
         builtin.add_validation_rule(&mut memory).unwrap();
         let error = memory.validate_existing_memory();
         assert_eq!(error, Err(MemoryError::SignatureNotFound));
@@ -686,9 +1268,10 @@ mod memory_tests {
     fn validate_existing_memory_for_range_check_relocatable_value() {
         let mut builtin = RangeCheckBuiltinRunner::new(8, 8, true);
         let mut segments = MemorySegmentManager::new();
-        let mut memory = memory![((1, 7), (1, 4))];
+        let mut memory = Memory::new();
         segments.add(&mut memory);
         builtin.initialize_segments(&mut segments, &mut memory);
+        memory_from_memory!(memory, (((1, 7), (1, 4))));
         assert_eq!(builtin.add_validation_rule(&mut memory), Ok(()));
         dbg!(builtin._bound);
         dbg!(&memory.data);
@@ -748,6 +1331,19 @@ mod memory_tests {
         );
     }
 
+    #[test]
+    fn get_segment_size_tracks_highest_written_offset() {
+        let mut memory = Memory::new();
+        memory.data.push(Vec::new());
+        assert_eq!(memory.get_segment_size(0), Some(0));
+        memory.insert_value(Relocatable::from((0, 4)), 1).unwrap();
+        assert_eq!(memory.get_segment_size(0), Some(5));
+        memory.insert_value(Relocatable::from((0, 2)), 1).unwrap();
+        assert_eq!(memory.get_segment_size(0), Some(5));
+        assert_eq!(memory.get_segment_size(1), None);
+        assert_eq!(memory.scan_segment_sizes(), vec![5]);
+    }
+
     #[test]
     fn default_memory() {
         let mem: Memory = Default::default();
@@ -941,6 +1537,240 @@ mod memory_tests {
         );
     }
 
+    #[test]
+    fn get_range_empty() {
+        let memory = Memory::new();
+        assert_eq!(memory.get_range(&mayberelocatable!(3, 0), 0), Ok(vec![]));
+        assert_eq!(
+            memory.get_continuous_range(&mayberelocatable!(3, 0), 0),
+            Ok(vec![])
+        );
+        assert_eq!(memory.get_integer_range(&relocatable!(3, 0), 0), Ok(vec![]));
+    }
+
+    #[test]
+    fn get_range_unallocated_segment() {
+        let memory = memory![((0, 0), 2)];
+        assert_eq!(
+            memory.get_range(&mayberelocatable!(1, 0), 2),
+            Err(MemoryError::UnallocatedSegmentRead(1, 1))
+        );
+        assert_eq!(
+            memory.get_continuous_range(&mayberelocatable!(1, 0), 2),
+            Err(MemoryError::UnallocatedSegmentRead(1, 1))
+        );
+        assert_eq!(
+            memory.get_integer_range(&relocatable!(-1, 0), 1),
+            Err(VirtualMachineError::MemoryError(
+                MemoryError::UnallocatedSegmentRead(-1, 0)
+            ))
+        );
+    }
+
+    #[test]
+    fn get_range_huge_size_fails_instead_of_allocating() {
+        let memory = memory![((1, 0), 2)];
+        assert_eq!(
+            memory.get_continuous_range(&mayberelocatable!(1, 0), usize::MAX),
+            Err(MemoryError::GetRangeMemoryGap)
+        );
+        assert_eq!(
+            memory.get_integer_range(&relocatable!(1, 0), usize::MAX),
+            Err(VirtualMachineError::ExpectedInteger(mayberelocatable!(
+                1, 1
+            )))
+        );
+    }
+
+    #[test]
+    fn get_integer_range_for_continuous_memory() {
+        let memory = memory![((1, 0), 2), ((1, 1), 3), ((1, 2), 4)];
+        assert_eq!(
+            memory.get_integer_range(&relocatable!(1, 1), 2),
+            Ok(vec![
+                Cow::Borrowed(&Felt::new(3)),
+                Cow::Borrowed(&Felt::new(4))
+            ])
+        );
+    }
+
+    #[test]
+    fn get_integer_range_names_offending_cell() {
+        let memory = memory![((1, 0), 2), ((1, 1), (1, 0)), ((1, 3), 4)];
+        assert_eq!(
+            memory.get_integer_range(&relocatable!(1, 0), 2),
+            Err(VirtualMachineError::ExpectedInteger(mayberelocatable!(
+                1, 1
+            )))
+        );
+        //Past the end of the segment's data
+        assert_eq!(
+            memory.get_integer_range(&relocatable!(1, 3), 2),
+            Err(VirtualMachineError::ExpectedInteger(mayberelocatable!(
+                1, 4
+            )))
+        );
+    }
+
+    #[test]
+    fn relocated_iter_skips_holes() {
+        let memory = memory![((0, 0), 1), ((0, 2), (1, 1)), ((1, 0), 5), ((1, 1), (0, 2))];
+        let relocation_table = vec![1, 4];
+        assert_eq!(
+            memory.relocated_iter(&relocation_table).collect::<Vec<_>>(),
+            vec![
+                Ok((1, Felt::new(1))),
+                Ok((3, Felt::new(5))),
+                Ok((4, Felt::new(5))),
+                Ok((5, Felt::new(3)))
+            ]
+        );
+    }
+
+    #[test]
+    fn relocated_iter_temporary_segment_value() {
+        let memory = memory![((0, 0), 1), ((0, 1), (-1, 0))];
+        let relocation_table = vec![1];
+        let mut cells = memory.relocated_iter(&relocation_table);
+        assert_eq!(cells.next(), Some(Ok((1, Felt::new(1)))));
+        assert_eq!(
+            cells.next(),
+            Some(Err(MemoryError::TemporarySegmentInRelocation(-1)))
+        );
+    }
+
+    #[test]
+    fn relocate_keeps_holes() {
+        let memory = memory![((0, 0), 1), ((0, 2), (1, 1)), ((1, 1), (0, 2)), ((2, 0), 7)];
+        let relocation_table = vec![1, 4, 6];
+        assert_eq!(
+            memory.relocate(&relocation_table),
+            Ok(vec![
+                None,
+                Some(BigInt::from(1)),
+                None,
+                Some(BigInt::from(5)),
+                None,
+                Some(BigInt::from(3)),
+                Some(BigInt::from(7))
+            ])
+        );
+    }
+
+    #[test]
+    fn relocate_temporary_segment_value() {
+        let memory = memory![((0, 0), (-1, 0))];
+        assert_eq!(
+            memory.relocate(&[1]),
+            Err(MemoryError::TemporarySegmentInRelocation(-1))
+        );
+    }
+
+    fn holes_memory() -> Vec<Option<BigInt>> {
+        vec![
+            None,
+            Some(BigInt::from(1)),
+            None,
+            Some((BigInt::from(1) << 255) + 7),
+            Some(BigInt::from(0)),
+            None,
+            Some((BigInt::from(1) << 256) - 1),
+            Some(BigInt::from(0x0102030405060708_u64)),
+        ]
+    }
+
+    #[test]
+    fn from_binary_file_reads_cells() {
+        let cells =
+            Memory::from_binary_file(Path::new("cairo_programs/trace_memory/holes_golden.memory"))
+                .unwrap();
+        let expected_cells: Vec<_> = holes_memory()
+            .into_iter()
+            .enumerate()
+            .filter_map(|(addr, value)| Some((addr, value?)))
+            .collect();
+        assert_eq!(cells, expected_cells);
+        assert_eq!(
+            Memory::first_mismatching_address(&cells, &holes_memory()),
+            None
+        );
+    }
+
+    #[test]
+    fn from_binary_file_unsorted_addresses() {
+        assert_eq!(
+            Memory::from_binary_file(Path::new(
+                "cairo_programs/trace_memory/unsorted_corrupted.memory"
+            )),
+            Err(MemoryError::BinaryMemoryFileUnsorted(4, 6))
+        );
+    }
+
+    #[test]
+    fn from_binary_file_missing_file() {
+        assert!(matches!(
+            Memory::from_binary_file(Path::new("cairo_programs/trace_memory/missing.memory")),
+            Err(MemoryError::BinaryMemoryFileRead(_))
+        ));
+    }
+
+    #[test]
+    fn decode_binary_memory_truncated_cell() {
+        let bytes = include_bytes!("../../../cairo_programs/trace_memory/holes_golden.memory");
+        assert_eq!(
+            decode_binary_memory(&bytes[..79]),
+            Err(MemoryError::BinaryMemoryFileLength(79))
+        );
+        assert_eq!(decode_binary_memory(&[]), Ok(Vec::new()));
+    }
+
+    #[test]
+    fn first_mismatching_address_different_value() {
+        let cells = decode_binary_memory(include_bytes!(
+            "../../../cairo_programs/trace_memory/holes_golden.memory"
+        ))
+        .unwrap();
+        let mut relocated_memory = holes_memory();
+        relocated_memory[6] = Some(BigInt::from(6));
+        assert_eq!(
+            Memory::first_mismatching_address(&cells, &relocated_memory),
+            Some(6)
+        );
+    }
+
+    #[test]
+    fn first_mismatching_address_missing_cells() {
+        let cells = decode_binary_memory(include_bytes!(
+            "../../../cairo_programs/trace_memory/holes_golden.memory"
+        ))
+        .unwrap();
+        //A hole in the relocated memory
+        let mut relocated_memory = holes_memory();
+        relocated_memory[4] = None;
+        assert_eq!(
+            Memory::first_mismatching_address(&cells, &relocated_memory),
+            Some(4)
+        );
+        //A cell missing from the file
+        let mut relocated_memory = holes_memory();
+        relocated_memory[2] = Some(BigInt::from(2));
+        assert_eq!(
+            Memory::first_mismatching_address(&cells, &relocated_memory),
+            Some(2)
+        );
+        //Trailing cells
+        assert_eq!(
+            Memory::first_mismatching_address(&cells, &holes_memory()[..7]),
+            Some(7)
+        );
+        relocated_memory = holes_memory();
+        relocated_memory.push(Some(BigInt::from(8)));
+        assert_eq!(
+            Memory::first_mismatching_address(&cells, &relocated_memory),
+            Some(8)
+        );
+    }
+
     /// Test that relocate_memory() works when there are no relocation rules.
     #[test]
     fn relocate_memory_empty_relocation_rules() {
@@ -995,4 +1825,101 @@ mod memory_tests {
         );
         assert!(memory.temp_data.is_empty());
     }
+
+    #[test]
+    fn origin_tracking_disabled_by_default() {
+        let mut memory = memory![((0, 0), 1)];
+        memory.set_origin(MemoryOrigin::Deduction {
+            builtin: BuiltinName::Output,
+        });
+        memory
+            .insert(&relocatable!(0, 1), &mayberelocatable!(2))
+            .unwrap();
+        assert!(!memory.is_origin_tracking_enabled());
+        assert_eq!(memory.get_origin(&relocatable!(0, 1)), None);
+        assert!(memory.get_hint_origins().is_empty());
+    }
+
+    #[test]
+    fn origin_tracking_follows_relocation() {
+        let mut memory = memory![((0, 0), 1)];
+        memory.enable_origin_tracking();
+        memory
+            .insert(&relocatable!(0, 1), &mayberelocatable!(2))
+            .unwrap();
+        let hint_origin = MemoryOrigin::Hint {
+            pc: relocatable!(0, 3),
+            index: 0,
+        };
+        memory.set_origin(hint_origin.clone());
+        memory.temp_data = vec![vec![]];
+        memory
+            .insert(&relocatable!(-1, 0), &mayberelocatable!(3))
+            .unwrap();
+        memory
+            .add_relocation_rule((-1, 0).into(), (0, 2).into())
+            .unwrap();
+
+        assert_eq!(memory.relocate_memory(), Ok(()));
+        assert_eq!(
+            memory.get_origin(&relocatable!(0, 1)),
+            Some(&MemoryOrigin::Preload)
+        );
+        assert_eq!(memory.get_origin(&relocatable!(0, 2)), Some(&hint_origin));
+        assert_eq!(
+            memory.get_hint_origins(),
+            vec![(relocatable!(0, 2), &hint_origin)]
+        );
+    }
+
+    #[test]
+    fn discard_overlay_removes_origins_and_relocation_rules() {
+        let mut memory = memory![((0, 0), 1)];
+        memory.enable_origin_tracking();
+        memory.temp_data = vec![vec![]];
+        memory.start_overlay().unwrap();
+        memory
+            .insert(&relocatable!(0, 1), &mayberelocatable!(2))
+            .unwrap();
+        memory
+            .add_relocation_rule((-1, 0).into(), (0, 2).into())
+            .unwrap();
+        assert_eq!(
+            memory.relocate_memory(),
+            Err(MemoryError::RelocationDuringOverlay)
+        );
+
+        assert_eq!(memory.discard_overlay(), Ok(()));
+        assert_eq!(memory.get(&relocatable!(0, 1)), Ok(None));
+        assert_eq!(memory.get_origin(&relocatable!(0, 1)), None);
+        assert!(memory.relocation_rules.is_empty());
+        assert_eq!(memory.discard_overlay(), Err(MemoryError::NoOverlay));
+    }
+
+    #[test]
+    fn reset_reuses_segment_allocations() {
+        let mut segments = MemorySegmentManager::new();
+        let mut memory = Memory::new();
+        memory.enable_origin_tracking();
+        let base = segments.add(&mut memory);
+        for offset in 0..10 {
+            memory
+                .insert(&(base + offset as usize), &mayberelocatable!(offset))
+                .unwrap();
+        }
+        segments.add_temporary_segment(&mut memory);
+
+        memory.reset();
+        segments.reset();
+        assert!(memory.data.is_empty());
+        assert!(memory.temp_data.is_empty());
+        assert!(memory.validated_addresses.is_empty());
+        assert_eq!(memory.get_origin(&base), None);
+        assert!(memory.is_origin_tracking_enabled());
+        assert_eq!(segments, MemorySegmentManager::new());
+
+        assert_eq!(segments.add(&mut memory), base);
+        assert!(memory.data[0].is_empty());
+        assert!(memory.data[0].capacity() >= 10);
+    }
 }
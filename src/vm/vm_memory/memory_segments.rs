@@ -6,14 +6,60 @@ use crate::{
         vm_memory::memory::Memory,
     },
 };
-
+use felt::{Felt, FeltOps};
 use std::{
     any::Any,
+    borrow::Cow,
     cmp,
-    collections::{HashMap, HashSet},
+    collections::{hash_map::Entry, HashMap, HashSet},
 };
 
-#[derive(Debug, PartialEq, Eq)]
+///How MemorySegmentManager::write_bytes packs bytes into felts. The last felt packs the bytes
+///left over when their count isn't a multiple of the word size, in the same byte order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PackingMode {
+    ///Big endian 16 byte words, as used by keccak
+    BigEndianU128,
+    ///Big endian 4 byte words, as used by sha256
+    BigEndianU32,
+    ///Little endian 4 byte words, as used by blake2s
+    LittleEndianU32,
+    ///Big endian 31 byte words, as used by short strings
+    ShortString,
+}
+
+impl PackingMode {
+    fn word_size(&self) -> usize {
+        match self {
+            PackingMode::BigEndianU128 => 16,
+            PackingMode::BigEndianU32 | PackingMode::LittleEndianU32 => 4,
+            PackingMode::ShortString => 31,
+        }
+    }
+
+    fn pack(&self, word: &[u8]) -> Felt {
+        match self {
+            PackingMode::LittleEndianU32 => {
+                Felt::from_bytes_be(&word.iter().rev().copied().collect::<Vec<_>>())
+            }
+            _ => Felt::from_bytes_be(word),
+        }
+    }
+}
+
+///Argument of an entrypoint, written to the memory by MemorySegmentManager::gen_cairo_arg
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CairoArg {
+    ///Passed as is
+    Single(MaybeRelocatable),
+    ///Written to a new segment, passed as a pointer to it
+    Array(Vec<MaybeRelocatable>),
+    ///Each element is generated in turn and the results are written to a new segment, passed as
+    ///a pointer to it
+    Composed(Vec<CairoArg>),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct MemorySegmentManager {
     pub num_segments: usize,
     pub num_temp_segments: usize,
@@ -22,44 +68,121 @@ pub struct MemorySegmentManager {
     // A map from segment index to a list of pairs (offset, page_id) that constitute the
     // public memory. Note that the offset is absolute (not based on the page_id).
     pub public_memory_offsets: HashMap<usize, Vec<(usize, usize)>>,
+    // State before start_overlay, restored by discard_overlay
+    overlay: Option<Box<MemorySegmentManager>>,
 }
 
 impl MemorySegmentManager {
     ///Adds a new segment and returns its starting location as a RelocatableValue.
+    ///The segment index is taken from the memory itself, so segments allocated without going
+    ///through the manager (ie: when loading memory directly) are never handed out twice.
     pub fn add(&mut self, memory: &mut Memory) -> Relocatable {
-        let segment_index = self.num_segments;
-        self.num_segments += 1;
-        memory.data.push(Vec::new());
+        let segment_index = memory.data.len();
+        let segment = memory.take_spare_segment();
+        memory.data.push(segment);
+        self.num_segments = memory.data.len();
         Relocatable {
             segment_index: segment_index as isize,
             offset: 0,
         }
     }
 
+    ///Adds a new segment with room for capacity cells and returns its starting location, like
+    ///add. Avoids growing the segment several times when its size is known in advance.
+    pub fn add_with_capacity(&mut self, memory: &mut Memory, capacity: usize) -> Relocatable {
+        let segment = self.add(memory);
+        memory.data[segment.segment_index as usize].reserve(capacity);
+        segment
+    }
+
     ///Adds a new temporary segment and returns its starting location as a RelocatableValue.
     ///Negative segment_index indicates its refer to a temporary segment
     pub fn add_temporary_segment(&mut self, memory: &mut Memory) -> Relocatable {
-        self.num_temp_segments += 1;
         memory.temp_data.push(Vec::new());
+        self.num_temp_segments = memory.temp_data.len();
         Relocatable {
             segment_index: -(self.num_temp_segments as isize),
             offset: 0,
         }
     }
 
+    ///Writes each value into the memory at its address, first adding through the manager the
+    ///segments and temporary segments the addresses need, so that the manager knows about every
+    ///segment. Used to set up preset memory instead of filling Memory::data directly.
+    ///Each segment is allocated once with room for its highest written offset.
+    pub fn load_memory(
+        &mut self,
+        memory: &mut Memory,
+        cells: &[(Relocatable, MaybeRelocatable)],
+    ) -> Result<(), MemoryError> {
+        let mut capacities = Vec::new();
+        for (addr, _) in cells {
+            if addr.segment_index >= 0 {
+                let index = addr.segment_index as usize;
+                if capacities.len() <= index {
+                    capacities.resize(index + 1, 0);
+                }
+                capacities[index] = cmp::max(capacities[index], addr.offset + 1);
+            }
+        }
+        for (index, capacity) in capacities.into_iter().enumerate() {
+            match memory.data.get_mut(index) {
+                Some(segment) => segment.reserve(capacity.saturating_sub(segment.len())),
+                None => {
+                    self.add_with_capacity(memory, capacity);
+                }
+            }
+        }
+        for (addr, value) in cells {
+            while addr.segment_index < 0 && memory.temp_data.len() < (-addr.segment_index) as usize
+            {
+                self.add_temporary_segment(memory);
+            }
+            memory.insert(addr, value)?;
+        }
+        Ok(())
+    }
+
     ///Writes data into the memory at address ptr and returns the first address after the data.
+    ///Fails with InconsistentMemory at the first cell which already holds a different value,
+    ///the cells before it stay written.
     pub fn load_data(
         &mut self,
         memory: &mut Memory,
         ptr: &MaybeRelocatable,
         data: &Vec<MaybeRelocatable>,
     ) -> Result<MaybeRelocatable, MemoryError> {
+        if let MaybeRelocatable::RelocatableValue(addr) = ptr {
+            if memory.extend_segment(addr, data) {
+                return Ok(ptr.add_usize(data.len()));
+            }
+        }
         for (num, value) in data.iter().enumerate() {
             memory.insert(&ptr.add_usize(num), value)?;
         }
         Ok(ptr.add_usize(data.len()))
     }
 
+    ///Packs bytes into felts according to packing and writes them into the memory at address
+    ///ptr. Returns the first address after the data and the number of felts written.
+    pub fn write_bytes(
+        &mut self,
+        memory: &mut Memory,
+        ptr: &Relocatable,
+        bytes: &[u8],
+        packing: PackingMode,
+    ) -> Result<(Relocatable, usize), MemoryError> {
+        let mut n_felts = 0;
+        for word in bytes.chunks(packing.word_size()) {
+            memory.insert(
+                &(ptr + n_felts),
+                &MaybeRelocatable::from(packing.pack(word)),
+            )?;
+            n_felts += 1;
+        }
+        Ok((ptr + n_felts, n_felts))
+    }
+
     pub fn new() -> MemorySegmentManager {
         MemorySegmentManager {
             num_segments: 0,
@@ -67,13 +190,52 @@ impl MemorySegmentManager {
             segment_sizes: HashMap::new(),
             segment_used_sizes: None,
             public_memory_offsets: HashMap::new(),
+            overlay: None,
         }
     }
 
+    ///Forgets every segment, their sizes and the public memory, to be used along with Memory::reset
+    pub fn reset(&mut self) {
+        self.num_segments = 0;
+        self.num_temp_segments = 0;
+        self.segment_sizes.clear();
+        self.segment_used_sizes = None;
+        self.public_memory_offsets.clear();
+        self.overlay = None;
+    }
+
+    ///Saves the segment counts and sizes so that discard_overlay can restore them, to be used
+    ///along with Memory::start_overlay. Only the per-segment bookkeeping is copied.
+    pub fn start_overlay(&mut self) -> Result<(), MemoryError> {
+        if self.overlay.is_some() {
+            return Err(MemoryError::OverlayAlreadyStarted);
+        }
+        self.overlay = Some(Box::new(self.clone()));
+        Ok(())
+    }
+
+    ///Keeps the segments added since start_overlay
+    pub fn commit_overlay(&mut self) -> Result<(), MemoryError> {
+        self.overlay.take().ok_or(MemoryError::NoOverlay)?;
+        Ok(())
+    }
+
+    ///Forgets the segments added and the sizes computed since start_overlay
+    pub fn discard_overlay(&mut self) -> Result<(), MemoryError> {
+        let overlay = self.overlay.take().ok_or(MemoryError::NoOverlay)?;
+        *self = *overlay;
+        Ok(())
+    }
+
     /// Calculates the size (number of non-none elements) of each memory segment.
+    /// The sizes are kept by the memory as it is written, see Memory::get_segment_size, so the
+    /// memory is only scanned to check them in debug builds.
     pub fn compute_effective_sizes(&mut self, memory: &Memory) -> &Vec<usize> {
-        self.segment_used_sizes
-            .get_or_insert_with(|| memory.data.iter().map(Vec::len).collect())
+        self.segment_used_sizes.get_or_insert_with(|| {
+            let sizes: Vec<usize> = memory.data.iter().map(Vec::len).collect();
+            debug_assert_eq!(sizes, memory.scan_segment_sizes());
+            sizes
+        })
     }
 
     ///Returns the number of used segments when they are already computed.
@@ -125,11 +287,40 @@ impl MemorySegmentManager {
             let base = self.add(memory);
             self.write_arg(memory, &base, value)?;
             Ok(base.into())
+        } else if let Some(value) = arg.downcast_ref::<CairoArg>() {
+            Ok(self.gen_cairo_arg(memory, value)?)
         } else {
-            Err(VirtualMachineError::NotImplemented)
+            Err(VirtualMachineError::UnsupportedArgType("gen_arg"))
         }
     }
 
+    ///Writes the arrays of arg, each to its own new segment, and returns the value to pass for
+    ///arg: the value itself for a single argument, the pointer to its segment otherwise.
+    ///Segments are allocated as in cairo-lang: the outer one first, then the nested ones in order.
+    pub fn gen_cairo_arg(
+        &mut self,
+        memory: &mut Memory,
+        arg: &CairoArg,
+    ) -> Result<MaybeRelocatable, MemoryError> {
+        let (base, data) = match arg {
+            CairoArg::Single(value) => return Ok(value.clone()),
+            CairoArg::Array(values) => (
+                self.add_with_capacity(memory, values.len()),
+                Cow::Borrowed(values),
+            ),
+            CairoArg::Composed(args) => {
+                let base = self.add_with_capacity(memory, args.len());
+                let data = args
+                    .iter()
+                    .map(|arg| self.gen_cairo_arg(memory, arg))
+                    .collect::<Result<Vec<_>, _>>()?;
+                (base, Cow::Owned(data))
+            }
+        };
+        self.load_data(memory, &base.into(), &data)?;
+        Ok(base.into())
+    }
+
     pub fn write_arg(
         &mut self,
         memory: &mut Memory,
@@ -171,42 +362,40 @@ impl MemorySegmentManager {
         }
     }
 
+    ///Counts the memory holes, the cells of the segments with accessed addresses that were never
+    ///accessed. Each of these segments contributes its size minus its number of accessed
+    ///addresses, the segments without accessed addresses don't contribute. Builtin segments are
+    ///handled separately, their addresses shouldn't be included in accessed_addresses.
     pub fn get_memory_holes(
         &self,
-        accessed_addresses: impl Iterator<Item = Relocatable>,
+        accessed_addresses: &HashSet<Relocatable>,
     ) -> Result<usize, MemoryError> {
-        let segment_used_sizes = self
-            .segment_used_sizes
-            .as_ref()
-            .ok_or(MemoryError::MissingSegmentUsedSizes)?;
+        if self.segment_used_sizes.is_none() {
+            return Err(MemoryError::MissingSegmentUsedSizes);
+        }
 
-        let mut accessed_offsets_sets = HashMap::new();
+        //Segment index to its size and number of accessed addresses
+        let mut accessed_counts = HashMap::<usize, (usize, usize)>::new();
         for addr in accessed_addresses {
-            let (index, offset) = from_relocatable_to_indexes(&addr);
-            let (segment_size, offset_set) = match accessed_offsets_sets.get_mut(&index) {
-                Some(x) => x,
-                None => {
+            let (index, offset) = from_relocatable_to_indexes(addr);
+            let (segment_size, accessed_count) = match accessed_counts.entry(index) {
+                Entry::Occupied(entry) => entry.into_mut(),
+                Entry::Vacant(entry) => {
                     let segment_size = self
                         .get_segment_size(index)
                         .ok_or(MemoryError::SegmentNotFinalized(index))?;
-
-                    accessed_offsets_sets.insert(index, (segment_size, HashSet::new()));
-                    accessed_offsets_sets
-                        .get_mut(&index)
-                        .ok_or(MemoryError::CantGetMutAccessedOffset)?
+                    entry.insert((segment_size, 0))
                 }
             };
-            if offset > *segment_size {
+            if offset >= *segment_size {
                 return Err(MemoryError::NumOutOfBounds);
             }
-
-            offset_set.insert(offset);
+            *accessed_count += 1;
         }
 
-        let max = cmp::max(self.segment_sizes.len(), segment_used_sizes.len());
-        Ok((0..max)
-            .filter_map(|index| accessed_offsets_sets.get(&index))
-            .map(|(segment_size, offsets_set)| segment_size - offsets_set.len())
+        Ok(accessed_counts
+            .values()
+            .map(|(segment_size, accessed_count)| segment_size - accessed_count)
             .sum())
     }
 
@@ -239,7 +428,7 @@ pub fn gen_typed_args(args: Vec<&dyn Any>) -> Result<Vec<MaybeRelocatable>, Virt
             let value = value.iter().map(|x| x as &dyn Any).collect::<Vec<_>>();
             cairo_args.extend(gen_typed_args(value)?.into_iter());
         } else {
-            return Err(VirtualMachineError::NotImplemented);
+            return Err(VirtualMachineError::UnsupportedArgType("gen_typed_args"));
         }
     }
 
@@ -255,7 +444,7 @@ impl Default for MemorySegmentManager {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::vm::vm_core::VirtualMachine;
+    use crate::vm::{vm_core::VirtualMachine, vm_memory::memory::MemoryOrigin};
     use crate::{relocatable, utils::test_utils::*};
     use felt::{Felt, NewFelt};
     use num_traits::Num;
@@ -310,6 +499,70 @@ mod tests {
         assert_eq!(segments.num_temp_segments, 2);
     }
 
+    #[test]
+    fn write_bytes_big_endian_u128() {
+        let bytes: Vec<u8> = (0..18).collect();
+        let mut segments = MemorySegmentManager::new();
+        let mut memory = Memory::new();
+        let base = segments.add(&mut memory);
+        let written = segments.write_bytes(&mut memory, &base, &bytes, PackingMode::BigEndianU128);
+        assert_eq!(written, Ok((relocatable!(0, 2), 2)));
+        check_memory!(
+            memory,
+            ((0, 0), 0x000102030405060708090a0b0c0d0e0f_u128),
+            ((0, 1), 0x1011)
+        );
+    }
+
+    #[test]
+    fn write_bytes_big_endian_u32() {
+        let bytes = [1, 2, 3, 4, 5];
+        let mut segments = MemorySegmentManager::new();
+        let mut memory = Memory::new();
+        let base = segments.add(&mut memory);
+        let written = segments.write_bytes(&mut memory, &base, &bytes, PackingMode::BigEndianU32);
+        assert_eq!(written, Ok((relocatable!(0, 2), 2)));
+        check_memory!(memory, ((0, 0), 0x01020304), ((0, 1), 0x05));
+    }
+
+    #[test]
+    fn write_bytes_little_endian_u32() {
+        let bytes = [1, 2, 3, 4, 5, 6];
+        let mut segments = MemorySegmentManager::new();
+        let mut memory = Memory::new();
+        segments.add(&mut memory);
+        let base = segments.add(&mut memory);
+        let written =
+            segments.write_bytes(&mut memory, &base, &bytes, PackingMode::LittleEndianU32);
+        assert_eq!(written, Ok((relocatable!(1, 2), 2)));
+        check_memory!(memory, ((1, 0), 0x04030201), ((1, 1), 0x0605));
+    }
+
+    #[test]
+    fn write_bytes_short_string() {
+        let bytes = [b"a".repeat(31), b"bc".to_vec()].concat();
+        let mut segments = MemorySegmentManager::new();
+        let mut memory = Memory::new();
+        let base = segments.add(&mut memory);
+        let written = segments.write_bytes(&mut memory, &base, &bytes, PackingMode::ShortString);
+        assert_eq!(written, Ok((relocatable!(0, 2), 2)));
+        assert_eq!(
+            memory.get(&relocatable!(0, 0)).unwrap().unwrap().as_ref(),
+            &MaybeRelocatable::from(Felt::from_str_radix(&"61".repeat(31), 16).unwrap())
+        );
+        check_memory!(memory, ((0, 1), 0x6263));
+    }
+
+    #[test]
+    fn write_bytes_empty() {
+        let mut segments = MemorySegmentManager::new();
+        let mut memory = Memory::new();
+        let base = segments.add(&mut memory);
+        let written = segments.write_bytes(&mut memory, &base, &[], PackingMode::ShortString);
+        assert_eq!(written, Ok((relocatable!(0, 0), 0)));
+        assert!(memory.data[0].is_empty());
+    }
+
     #[test]
     fn load_data_empty() {
         let data = Vec::new();
@@ -435,6 +688,19 @@ mod tests {
         assert_eq!(Some(vec![8, 2, 8]), segments.segment_used_sizes);
     }
 
+    #[test]
+    fn compute_effective_sizes_matches_scan_after_discarded_overlay() {
+        let mut segments = MemorySegmentManager::new();
+        let mut memory = memory![((0, 2), 1), ((1, 0), 1)];
+        memory.start_overlay().unwrap();
+        memory.insert_value(Relocatable::from((0, 9)), 1).unwrap();
+        memory.insert_value(Relocatable::from((1, 4)), 1).unwrap();
+        memory.discard_overlay().unwrap();
+        assert_eq!(memory.scan_segment_sizes(), vec![3, 1]);
+        segments.compute_effective_sizes(&memory);
+        assert_eq!(Some(vec![3, 1]), segments.segment_used_sizes);
+    }
+
     #[test]
     fn get_segment_used_size_after_computing_used() {
         let mut segments = MemorySegmentManager::new();
@@ -457,6 +723,82 @@ mod tests {
         assert_eq!(None, segments.get_segment_used_size(2));
     }
 
+    #[test]
+    fn load_memory_adds_segments_through_manager() {
+        let mut segments = MemorySegmentManager::new();
+        let mut memory = Memory::new();
+        segments
+            .load_memory(
+                &mut memory,
+                &[
+                    (relocatable!(0, 0), mayberelocatable!(1)),
+                    (relocatable!(2, 3), mayberelocatable!(1, 0)),
+                    (relocatable!(-1, 0), mayberelocatable!(5)),
+                ],
+            )
+            .unwrap();
+        assert_eq!(segments.num_segments, 3);
+        assert_eq!(segments.num_temp_segments, 1);
+        assert_eq!(
+            memory.get(&relocatable!(2, 3)).unwrap().unwrap().as_ref(),
+            &mayberelocatable!(1, 0)
+        );
+        assert_eq!(segments.compute_effective_sizes(&memory), &vec![1, 0, 4]);
+        assert_eq!(segments.relocate_segments(), Ok(vec![1, 2, 2]));
+        assert_eq!(segments.add(&mut memory), relocatable!(3, 0));
+        assert_eq!(segments.num_segments, 4);
+    }
+
+    #[test]
+    fn load_memory_reserves_segment_capacity() {
+        let mut segments = MemorySegmentManager::new();
+        let mut memory = Memory::new();
+        segments.add(&mut memory);
+        segments
+            .load_memory(
+                &mut memory,
+                &[
+                    (relocatable!(0, 3), mayberelocatable!(5)),
+                    (relocatable!(0, 1), mayberelocatable!(1, 2)),
+                    (relocatable!(2, 0), mayberelocatable!(7)),
+                ],
+            )
+            .unwrap();
+        assert_eq!(segments.num_segments, 3);
+        assert_eq!(
+            memory.data[0],
+            vec![
+                None,
+                Some(mayberelocatable!(1, 2)),
+                None,
+                Some(mayberelocatable!(5))
+            ]
+        );
+        assert!(memory.data[0].capacity() >= 4);
+        assert!(memory.data[1].is_empty());
+        assert_eq!(memory.data[2], vec![Some(mayberelocatable!(7))]);
+    }
+
+    #[test]
+    fn load_memory_inconsistent_value() {
+        let mut segments = MemorySegmentManager::new();
+        let mut memory = Memory::new();
+        assert_eq!(
+            segments.load_memory(
+                &mut memory,
+                &[
+                    (relocatable!(0, 0), mayberelocatable!(1)),
+                    (relocatable!(0, 0), mayberelocatable!(2)),
+                ],
+            ),
+            Err(MemoryError::InconsistentMemory(
+                mayberelocatable!(0, 0),
+                mayberelocatable!(1),
+                mayberelocatable!(2)
+            ))
+        );
+    }
+
     #[test]
     fn relocate_segments_one_segment() {
         let mut segments = MemorySegmentManager::new();
@@ -589,13 +931,103 @@ mod tests {
         );
     }
 
+    #[test]
+    fn add_with_capacity_reserves_room() {
+        let mut segments = MemorySegmentManager::new();
+        let mut memory = Memory::new();
+        segments.add(&mut memory);
+        let base = segments.add_with_capacity(&mut memory, 1000);
+        assert_eq!(base, relocatable!(1, 0));
+        assert_eq!(segments.num_segments, 2);
+        assert!(memory.data[1].is_empty());
+        assert!(memory.data[1].capacity() >= 1000);
+    }
+
+    #[test]
+    fn load_data_large_contiguous() {
+        let mut segments = MemorySegmentManager::new();
+        let mut memory = Memory::new();
+        let base = segments.add_with_capacity(&mut memory, 100_000);
+        let data: Vec<_> = (0..100_000_i64)
+            .map(|value| MaybeRelocatable::from(Felt::new(value)))
+            .collect();
+
+        let end = segments
+            .load_data(&mut memory, &base.into(), &data)
+            .unwrap();
+        assert_eq!(end, mayberelocatable!(0, 100_000));
+        assert_eq!(memory.data[0].len(), 100_000);
+        assert_eq!(
+            memory.get(&relocatable!(0, 99_999)),
+            Ok(Some(Cow::Owned(mayberelocatable!(99_999))))
+        );
+        //Loading after the end, or over existing cells, still goes through insert
+        let end = segments
+            .load_data(
+                &mut memory,
+                &mayberelocatable!(0, 100_002),
+                &data[..2].to_vec(),
+            )
+            .unwrap();
+        assert_eq!(end, mayberelocatable!(0, 100_004));
+        assert_eq!(memory.data[0][100_001], None);
+        assert_eq!(
+            segments.load_data(&mut memory, &mayberelocatable!(0, 0), &data[1..2].to_vec()),
+            Err(MemoryError::InconsistentMemory(
+                mayberelocatable!(0, 0),
+                mayberelocatable!(0),
+                mayberelocatable!(1)
+            ))
+        );
+    }
+
+    #[test]
+    fn load_data_over_conflicting_cell() {
+        let mut segments = MemorySegmentManager::new();
+        let mut memory = Memory::new();
+        let base = segments.add(&mut memory);
+        memory
+            .insert(&relocatable!(0, 2), &mayberelocatable!(9))
+            .unwrap();
+        let data = vec![
+            mayberelocatable!(1),
+            mayberelocatable!(2),
+            mayberelocatable!(3),
+        ];
+        //The error points at the first conflicting cell, the ones before it are kept
+        assert_eq!(
+            segments.load_data(&mut memory, &base.into(), &data),
+            Err(MemoryError::InconsistentMemory(
+                mayberelocatable!(0, 2),
+                mayberelocatable!(9),
+                mayberelocatable!(3)
+            ))
+        );
+        check_memory!(memory, ((0, 0), 1), ((0, 1), 2), ((0, 2), 9));
+    }
+
+    #[test]
+    fn load_data_contiguous_with_origin_tracking() {
+        let mut segments = MemorySegmentManager::new();
+        let mut memory = Memory::new();
+        memory.enable_origin_tracking();
+        let base = segments.add(&mut memory);
+        segments
+            .load_data(&mut memory, &base.into(), &vec![mayberelocatable!(1)])
+            .unwrap();
+        assert_eq!(
+            memory.get_origin(&relocatable!(0, 0)),
+            Some(&MemoryOrigin::Preload)
+        );
+    }
+
     #[test]
     fn get_memory_holes_missing_segment_used_sizes() {
         let memory_segment_manager = MemorySegmentManager::new();
-        let accessed_addresses = Vec::new();
+        let accessed_addresses = HashSet::new();
 
         assert_eq!(
-            memory_segment_manager.get_memory_holes(accessed_addresses.into_iter()),
+            memory_segment_manager.get_memory_holes(&accessed_addresses),
             Err(MemoryError::MissingSegmentUsedSizes),
         );
     }
@@ -605,9 +1037,10 @@ mod tests {
         let mut memory_segment_manager = MemorySegmentManager::new();
         memory_segment_manager.segment_used_sizes = Some(Vec::new());
 
-        let accessed_addresses = vec![(0, 0).into(), (0, 1).into(), (0, 2).into(), (0, 3).into()];
+        let accessed_addresses =
+            HashSet::from([(0, 0).into(), (0, 1).into(), (0, 2).into(), (0, 3).into()]);
         assert_eq!(
-            memory_segment_manager.get_memory_holes(accessed_addresses.into_iter()),
+            memory_segment_manager.get_memory_holes(&accessed_addresses),
             Err(MemoryError::SegmentNotFinalized(0)),
         );
     }
@@ -617,9 +1050,22 @@ mod tests {
         let mut memory_segment_manager = MemorySegmentManager::new();
         memory_segment_manager.segment_used_sizes = Some(vec![2]);
 
-        let accessed_addresses = vec![(0, 0).into(), (0, 1).into(), (0, 2).into(), (0, 3).into()];
+        let accessed_addresses =
+            HashSet::from([(0, 0).into(), (0, 1).into(), (0, 2).into(), (0, 3).into()]);
+        assert_eq!(
+            memory_segment_manager.get_memory_holes(&accessed_addresses),
+            Err(MemoryError::NumOutOfBounds),
+        );
+    }
+
+    #[test]
+    fn get_memory_holes_offset_equal_to_segment_size() {
+        let mut memory_segment_manager = MemorySegmentManager::new();
+        memory_segment_manager.segment_used_sizes = Some(vec![2]);
+
+        let accessed_addresses = HashSet::from([(0, 0).into(), (0, 2).into()]);
         assert_eq!(
-            memory_segment_manager.get_memory_holes(accessed_addresses.into_iter()),
+            memory_segment_manager.get_memory_holes(&accessed_addresses),
             Err(MemoryError::NumOutOfBounds),
         );
     }
@@ -629,9 +1075,9 @@ mod tests {
         let mut memory_segment_manager = MemorySegmentManager::new();
         memory_segment_manager.segment_used_sizes = Some(Vec::new());
 
-        let accessed_addresses = Vec::new();
+        let accessed_addresses = HashSet::new();
         assert_eq!(
-            memory_segment_manager.get_memory_holes(accessed_addresses.into_iter()),
+            memory_segment_manager.get_memory_holes(&accessed_addresses),
             Ok(0),
         );
     }
@@ -641,9 +1087,9 @@ mod tests {
         let mut memory_segment_manager = MemorySegmentManager::new();
         memory_segment_manager.segment_used_sizes = Some(vec![4]);
 
-        let accessed_addresses = Vec::new();
+        let accessed_addresses = HashSet::new();
         assert_eq!(
-            memory_segment_manager.get_memory_holes(accessed_addresses.into_iter()),
+            memory_segment_manager.get_memory_holes(&accessed_addresses),
             Ok(0),
         );
     }
@@ -653,7 +1099,7 @@ mod tests {
         let mut memory_segment_manager = MemorySegmentManager::new();
         memory_segment_manager.segment_used_sizes = Some(vec![10]);
 
-        let accessed_addresses = vec![
+        let accessed_addresses = HashSet::from([
             (0, 0).into(),
             (0, 1).into(),
             (0, 2).into(),
@@ -662,20 +1108,51 @@ mod tests {
             (0, 7).into(),
             (0, 8).into(),
             (0, 9).into(),
-        ];
+        ]);
         assert_eq!(
-            memory_segment_manager.get_memory_holes(accessed_addresses.into_iter()),
+            memory_segment_manager.get_memory_holes(&accessed_addresses),
             Ok(2),
         );
     }
 
+    #[test]
+    fn get_memory_holes_all_accessed() {
+        let mut memory_segment_manager = MemorySegmentManager::new();
+        memory_segment_manager.segment_used_sizes = Some(vec![3, 2]);
+
+        let accessed_addresses = HashSet::from([
+            (0, 0).into(),
+            (0, 1).into(),
+            (0, 2).into(),
+            (1, 0).into(),
+            (1, 1).into(),
+        ]);
+        assert_eq!(
+            memory_segment_manager.get_memory_holes(&accessed_addresses),
+            Ok(0),
+        );
+    }
+
+    #[test]
+    fn get_memory_holes_only_counts_accessed_segments() {
+        let mut memory_segment_manager = MemorySegmentManager::new();
+        memory_segment_manager.segment_used_sizes = Some(vec![3, 5, 4]);
+
+        //Segment 1 is a single access surrounded by holes, segment 2 isn't counted
+        let accessed_addresses = HashSet::from([(0, 0).into(), (0, 2).into(), (1, 4).into()]);
+        assert_eq!(
+            memory_segment_manager.get_memory_holes(&accessed_addresses),
+            Ok(5),
+        );
+    }
+
     #[test]
     fn get_memory_holes2() {
         let mut memory_segment_manager = MemorySegmentManager::new();
 
         memory_segment_manager.segment_sizes = HashMap::from([(0, 15)]);
         memory_segment_manager.segment_used_sizes = Some(vec![10]);
-        let accessed_addresses = vec![
+        let accessed_addresses = HashSet::from([
             (0, 0).into(),
             (0, 1).into(),
             (0, 2).into(),
@@ -684,9 +1161,9 @@ mod tests {
             (0, 7).into(),
             (0, 8).into(),
             (0, 9).into(),
-        ];
+        ]);
         assert_eq!(
-            memory_segment_manager.get_memory_holes(accessed_addresses.into_iter()),
+            memory_segment_manager.get_memory_holes(&accessed_addresses),
             Ok(7),
         );
     }
@@ -795,17 +1272,78 @@ mod tests {
         );
     }
 
-    /// Test that the call to .gen_arg() with any other argument returns a not
-    /// implemented error.
+    /// Test that the call to .gen_arg() with any other argument returns an
+    /// unsupported argument type error.
     #[test]
-    fn gen_arg_not_implemented() {
+    fn gen_arg_unsupported_type() {
         let mut memory_segment_manager = MemorySegmentManager::new();
         let mut vm = vm!();
 
         assert_eq!(
             memory_segment_manager.gen_arg(&"", &mut vm.memory),
-            Err(VirtualMachineError::NotImplemented),
+            Err(VirtualMachineError::UnsupportedArgType("gen_arg")),
+        );
+    }
+
+    #[test]
+    fn gen_cairo_arg_nested() {
+        let mut segments = MemorySegmentManager::new();
+        let mut memory = Memory::new();
+        segments.add(&mut memory);
+        //(5, [1, 2], ([7], 0:0))
+        let arg = CairoArg::Composed(vec![
+            CairoArg::Single(mayberelocatable!(5)),
+            CairoArg::Array(vec![mayberelocatable!(1), mayberelocatable!(2)]),
+            CairoArg::Composed(vec![
+                CairoArg::Array(vec![mayberelocatable!(7)]),
+                CairoArg::Single(mayberelocatable!(0, 0)),
+            ]),
+        ]);
+
+        //The outermost segment comes first, then the nested ones in order
+        let ptr = segments.gen_cairo_arg(&mut memory, &arg).unwrap();
+        assert_eq!(ptr, mayberelocatable!(1, 0));
+        assert_eq!(segments.num_segments, 5);
+        check_memory!(
+            memory,
+            ((1, 0), 5),
+            ((1, 1), (2, 0)),
+            ((1, 2), (3, 0)),
+            ((2, 0), 1),
+            ((2, 1), 2),
+            ((3, 0), (4, 0)),
+            ((3, 1), (0, 0)),
+            ((4, 0), 7)
+        );
+        assert_eq!(memory.data[1].len(), 3);
+    }
+
+    #[test]
+    fn gen_cairo_arg_single_and_empty_array() {
+        let mut segments = MemorySegmentManager::new();
+        let mut memory = Memory::new();
+        assert_eq!(
+            segments.gen_cairo_arg(&mut memory, &CairoArg::Single(mayberelocatable!(3))),
+            Ok(mayberelocatable!(3))
+        );
+        assert_eq!(segments.num_segments, 0);
+        assert_eq!(
+            segments.gen_cairo_arg(&mut memory, &CairoArg::Array(Vec::new())),
+            Ok(mayberelocatable!(0, 0))
+        );
+        assert!(memory.data[0].is_empty());
+    }
+
+    #[test]
+    fn gen_arg_cairo_arg() {
+        let mut segments = MemorySegmentManager::new();
+        let mut vm = vm!();
+        let arg = CairoArg::Composed(vec![CairoArg::Array(vec![mayberelocatable!(4)])]);
+        assert_eq!(
+            segments.gen_arg(&arg, &mut vm.memory),
+            Ok(mayberelocatable!(0, 0))
         );
+        check_memory!(vm.memory, ((0, 0), (1, 0)), ((1, 0), 4));
     }
 
     /// Test that the call to .gen_typed_args() with an empty vector returns an
@@ -816,12 +1354,12 @@ mod tests {
     }
 
     /// Test that the call to .gen_typed_args() with an unsupported vector
-    /// returns a not implemented error.
+    /// returns an unsupported argument type error.
     #[test]
-    fn gen_typed_args_not_implemented() {
+    fn gen_typed_args_unsupported_type() {
         assert_eq!(
             gen_typed_args(vec![&0usize]),
-            Err(VirtualMachineError::NotImplemented),
+            Err(VirtualMachineError::UnsupportedArgType("gen_typed_args")),
         );
     }
 
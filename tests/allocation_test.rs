@@ -0,0 +1,129 @@
+use cairo_vm::{
+    cairo_run::write_relocated_binary_memory,
+    hint_processor::builtin_hint_processor::builtin_hint_processor_definition::BuiltinHintProcessor,
+    serde::deserialize_program::ReferenceManager,
+    types::{program::Program, relocatable::MaybeRelocatable},
+    vm::{runners::cairo_runner::CairoRunner, vm_core::VirtualMachine},
+};
+use felt::{Felt, NewFelt};
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+    collections::HashMap,
+};
+
+///Counts the allocations made by the current thread, and tracks the largest one
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+    static LARGEST_ALLOCATION: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|allocations| allocations.set(allocations.get() + 1));
+        let _ =
+            LARGEST_ALLOCATION.try_with(|largest| largest.set(largest.get().max(layout.size())));
+        System.alloc(layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let _ = LARGEST_ALLOCATION.try_with(|largest| largest.set(largest.get().max(new_size)));
+        System.realloc(ptr, layout, new_size)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOC: CountingAllocator = CountingAllocator;
+
+fn allocations() -> usize {
+    ALLOCATIONS.with(Cell::get)
+}
+
+fn reset_largest_allocation() {
+    LARGEST_ALLOCATION.with(|largest| largest.set(0))
+}
+
+fn largest_allocation() -> usize {
+    LARGEST_ALLOCATION.with(Cell::get)
+}
+
+const ITERATIONS: i32 = 1000;
+
+///Counts down from ITERATIONS to 0, every instruction of the loop uses an immediate:
+///   [ap] = ITERATIONS, ap++
+/// loop:
+///   [ap] = [ap - 1] + (-1), ap++
+///   jmp rel -2 if [ap - 1] != 0
+///   ret
+fn countdown_program() -> Program {
+    let data = vec![
+        MaybeRelocatable::from(Felt::new(0x480680017fff8000_i64)),
+        MaybeRelocatable::from(Felt::new(ITERATIONS)),
+        MaybeRelocatable::from(Felt::new(0x482480017fff8000_i64)),
+        MaybeRelocatable::from(Felt::new(-1)),
+        MaybeRelocatable::from(Felt::new(0x020680017fff7fff_i64)),
+        MaybeRelocatable::from(Felt::new(-2)),
+        MaybeRelocatable::from(Felt::new(0x208b7fff7fff7ffe_i64)),
+    ];
+    Program::new(
+        Vec::new(),
+        felt::PRIME_STR.to_string(),
+        data,
+        Some(0),
+        HashMap::new(),
+        ReferenceManager {
+            references: Vec::new(),
+        },
+        HashMap::new(),
+        Vec::new(),
+        None,
+    )
+    .unwrap()
+}
+
+#[test]
+fn immediates_are_not_cloned_per_step() {
+    let program = countdown_program();
+    let mut hint_processor = BuiltinHintProcessor::new_empty();
+    let mut cairo_runner = CairoRunner::new(&program, "plain", false).unwrap();
+    let mut vm = VirtualMachine::new(false);
+    cairo_runner.initialize(&mut vm).unwrap();
+
+    let steps = 2 * ITERATIONS as usize;
+    let allocations_before = allocations();
+    cairo_runner
+        .run_for_steps(steps, &mut vm, &mut hint_processor)
+        .unwrap();
+    let allocations_per_step = (allocations() - allocations_before) as f64 / steps as f64;
+
+    //Cloning the immediates out of memory used to take two extra allocations per step
+    assert!(allocations_per_step < 2.0, "{allocations_per_step}");
+}
+
+#[test]
+fn relocated_memory_is_written_without_materializing_it() {
+    let program = countdown_program();
+    let mut hint_processor = BuiltinHintProcessor::new_empty();
+    let mut cairo_runner = CairoRunner::new(&program, "plain", false).unwrap();
+    let mut vm = VirtualMachine::new(false);
+    let end = cairo_runner.initialize(&mut vm).unwrap();
+    cairo_runner
+        .run_until_pc(end, &mut vm, &mut hint_processor)
+        .unwrap();
+    let memory_path = std::env::temp_dir().join("countdown_streamed_cairo_rs.memory");
+
+    reset_largest_allocation();
+    write_relocated_binary_memory(&mut vm, &memory_path).unwrap();
+    let largest_allocation = largest_allocation();
+
+    //Each written cell takes 40 bytes, the whole encoded memory is never held at once
+    let memory_size = std::fs::metadata(&memory_path).unwrap().len() as usize;
+    assert!(memory_size >= 40 * ITERATIONS as usize);
+    assert!(largest_allocation < memory_size / 2, "{largest_allocation}");
+}
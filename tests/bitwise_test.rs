@@ -18,10 +18,9 @@ fn bitwise_integration_test() {
     let mut cairo_runner = CairoRunner::new(&program, "all", false).unwrap();
     let mut vm = VirtualMachine::new(true);
     let end = cairo_runner.initialize(&mut vm).unwrap();
-    assert!(
-        cairo_runner.run_until_pc(end, &mut vm, &mut hint_processor) == Ok(()),
-        "Execution failed"
-    );
+    cairo_runner
+        .run_until_pc(end, &mut vm, &mut hint_processor)
+        .unwrap();
     assert!(
         cairo_runner.relocate(&mut vm,) == Ok(()),
         "Execution failed"
@@ -260,6 +259,6 @@ fn bitwise_integration_test() {
         },
     ];
     for (i, entry) in python_vm_relocated_trace.iter().enumerate() {
-        assert_eq!(&cairo_runner.relocated_trace.as_ref().unwrap()[i], entry);
+        assert_eq!(&cairo_runner.get_relocated_trace().unwrap()[i], entry);
     }
 }
@@ -497,6 +497,73 @@ fn cairo_run_assert_nn() {
     .expect("Couldn't run program");
 }
 
+#[test]
+fn cairo_run_assert_nn_le_and_in_range() {
+    let mut hint_executor = BuiltinHintProcessor::new_empty();
+    cairo_run::cairo_run(
+        Path::new("cairo_programs/assert_nn_le_and_in_range.json"),
+        "main",
+        false,
+        false,
+        "all",
+        false,
+        &mut hint_executor,
+    )
+    .expect("Couldn't run program");
+}
+
+#[test]
+fn cairo_run_assert_nn_le_bad() {
+    let mut hint_executor = BuiltinHintProcessor::new_empty();
+    let err = cairo_run::cairo_run(
+        Path::new("cairo_programs/bad_programs/bad_assert_nn_le.json"),
+        "main",
+        false,
+        false,
+        "all",
+        false,
+        &mut hint_executor,
+    );
+    assert!(err.is_err());
+    assert!(err.err().unwrap().to_string().contains("is out of range"));
+}
+
+#[test]
+fn cairo_run_assert_in_range_bad() {
+    let mut hint_executor = BuiltinHintProcessor::new_empty();
+    let err = cairo_run::cairo_run(
+        Path::new("cairo_programs/bad_programs/bad_assert_in_range.json"),
+        "main",
+        false,
+        false,
+        "all",
+        false,
+        &mut hint_executor,
+    );
+    assert!(err.is_err());
+    assert!(err.err().unwrap().to_string().contains("is out of range"));
+}
+
+#[test]
+fn cairo_run_assert_250_bit_bad() {
+    let mut hint_executor = BuiltinHintProcessor::new_empty();
+    let err = cairo_run::cairo_run(
+        Path::new("cairo_programs/bad_programs/bad_assert_250_bit.json"),
+        "main",
+        false,
+        false,
+        "all",
+        false,
+        &mut hint_executor,
+    );
+    assert!(err.is_err());
+    assert!(err
+        .err()
+        .unwrap()
+        .to_string()
+        .contains("is outside of the range [0, 2**250)"));
+}
+
 #[test]
 fn cairo_run_sqrt() {
     let mut hint_executor = BuiltinHintProcessor::new_empty();
@@ -587,6 +654,21 @@ fn cairo_run_math_cmp() {
     .expect("Couldn't run program");
 }
 
+#[test]
+fn cairo_run_math_cmp_boundaries() {
+    let mut hint_executor = BuiltinHintProcessor::new_empty();
+    cairo_run::cairo_run(
+        Path::new("cairo_programs/math_cmp_boundaries.json"),
+        "main",
+        false,
+        false,
+        "all",
+        false,
+        &mut hint_executor,
+    )
+    .expect("Couldn't run program");
+}
+
 #[test]
 fn cairo_run_unsigned_div_rem() {
     let mut hint_executor = BuiltinHintProcessor::new_empty();
@@ -1252,6 +1334,26 @@ fn cairo_run_error_msg_attr() {
     assert!(err.to_string().contains("SafeUint256: addition overflow"));
 }
 
+#[test]
+fn cairo_run_error_msg_attr_value() {
+    let mut hint_executor = BuiltinHintProcessor::new_empty();
+    let err = cairo_run::cairo_run(
+        Path::new("cairo_programs/bad_programs/error_msg_attr_value.json"),
+        "main",
+        false,
+        false,
+        "all",
+        false,
+        &mut hint_executor,
+    )
+    .err()
+    .unwrap();
+
+    assert!(err
+        .to_string()
+        .starts_with("Error message: Amount 3 is too large\n"));
+}
+
 #[test]
 fn cairo_run_error_msg_attr_ap_based_reference() {
     let mut hint_executor = BuiltinHintProcessor::new_empty();
@@ -1301,3 +1403,18 @@ fn cairo_run_dict_store_cast_pointer() {
     )
     .expect("Couldn't run program");
 }
+
+#[test]
+fn cairo_run_pedersen_output_read_by_next_instruction() {
+    let mut hint_executor = BuiltinHintProcessor::new_empty();
+    cairo_run::cairo_run(
+        Path::new("cairo_programs/pedersen_output_read_by_next_instruction.json"),
+        "main",
+        false,
+        false,
+        "all",
+        false,
+        &mut hint_executor,
+    )
+    .expect("Couldn't run program");
+}
@@ -0,0 +1,81 @@
+use cairo_vm::{
+    hint_processor::builtin_hint_processor::builtin_hint_processor_definition::BuiltinHintProcessor,
+    types::program::Program,
+    vm::{
+        errors::{cairo_run_errors::CairoRunError, vm_exception::VmException},
+        runners::cairo_runner::CairoRunner,
+        trace::trace_entry::RelocatedTraceEntry,
+        vm_core::VirtualMachine,
+    },
+};
+use felt::Felt;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+//Everything observable from a run, except for its timing
+#[derive(Debug, PartialEq)]
+struct RunResult {
+    relocated_memory: Vec<Option<Felt>>,
+    relocated_trace: Option<Vec<RelocatedTraceEntry>>,
+    output: Option<Vec<Felt>>,
+    run_report: String,
+}
+
+fn run(path: &Path) -> Result<RunResult, String> {
+    let program = Program::from_file(path, Some("main")).map_err(|err| err.to_string())?;
+    let mut cairo_runner =
+        CairoRunner::new(&program, "all", false).map_err(|err| err.to_string())?;
+    let mut vm = VirtualMachine::new(true);
+    let mut hint_processor = BuiltinHintProcessor::new_empty();
+    let end = cairo_runner
+        .initialize(&mut vm)
+        .map_err(|err| err.to_string())?;
+    let error: Option<CairoRunError> = cairo_runner
+        .run_until_pc(end, &mut vm, &mut hint_processor)
+        .and_then(|_| cairo_runner.end_run(false, false, &mut vm, &mut hint_processor))
+        .err()
+        .map(|err| VmException::from_vm_error(&cairo_runner, &vm, err).into());
+    let mut run_report = cairo_runner.run_report(&mut vm, error.as_ref());
+    run_report.duration_secs = None;
+    run_report.steps_per_sec = None;
+    let run_report = serde_json::to_string(&run_report).map_err(|err| err.to_string())?;
+    if error.is_none() {
+        cairo_runner
+            .relocate(&mut vm)
+            .map_err(|err| err.to_string())?;
+    }
+    Ok(RunResult {
+        relocated_memory: cairo_runner.get_relocated_memory().to_vec(),
+        relocated_trace: cairo_runner.get_relocated_trace().map(<[_]>::to_vec),
+        output: cairo_runner
+            .get_output_values(&mut vm)
+            .map_err(|err| err.to_string())?,
+        run_report,
+    })
+}
+
+//The compiled fixtures under dir, the benchmarks are left out as they take too long to run twice
+fn fixtures(dir: &Path, paths: &mut Vec<PathBuf>) {
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            if !path.ends_with("benchmarks") {
+                fixtures(&path, paths);
+            }
+        } else if path.extension() == Some("json".as_ref()) {
+            paths.push(path);
+        }
+    }
+}
+
+#[test]
+fn running_fixtures_twice_gives_identical_results() {
+    let mut paths = Vec::new();
+    fixtures(Path::new("cairo_programs"), &mut paths);
+    paths.sort();
+    assert!(!paths.is_empty());
+    let diverging: Vec<&PathBuf> = paths.iter().filter(|path| run(path) != run(path)).collect();
+    assert!(diverging.is_empty(), "Runs diverged for {diverging:?}");
+}
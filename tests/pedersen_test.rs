@@ -15,10 +15,9 @@ fn pedersen_integration_test() {
     let mut cairo_runner = CairoRunner::new(&program, "all", false).unwrap();
     let mut vm = VirtualMachine::new(true);
     let end = cairo_runner.initialize(&mut vm).unwrap();
-    assert_eq!(
-        cairo_runner.run_until_pc(end, &mut vm, &mut hint_processor),
-        Ok(())
-    );
+    cairo_runner
+        .run_until_pc(end, &mut vm, &mut hint_processor)
+        .unwrap();
     assert!(cairo_runner.relocate(&mut vm) == Ok(()), "Execution failed");
 
     let python_vm_relocated_trace: Vec<RelocatedTraceEntry> = vec![
@@ -94,7 +93,7 @@ fn pedersen_integration_test() {
         },
     ];
     assert_eq!(
-        cairo_runner.relocated_trace,
-        Some(python_vm_relocated_trace)
+        cairo_runner.get_relocated_trace(),
+        Some(python_vm_relocated_trace.as_slice())
     );
 }
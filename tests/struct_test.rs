@@ -18,10 +18,9 @@ fn struct_integration_test() {
     let mut vm = VirtualMachine::new(true);
     let end = cairo_runner.initialize(&mut vm).unwrap();
 
-    assert!(
-        cairo_runner.run_until_pc(end, &mut vm, &mut hint_processor) == Ok(()),
-        "Execution failed"
-    );
+    cairo_runner
+        .run_until_pc(end, &mut vm, &mut hint_processor)
+        .unwrap();
     assert!(cairo_runner.relocate(&mut vm) == Ok(()), "Execution failed");
     let relocated_entry = RelocatedTraceEntry {
         pc: 1,
@@ -29,5 +28,8 @@ fn struct_integration_test() {
         fp: 4,
     };
 
-    assert_eq!(cairo_runner.relocated_trace, Some(vec![relocated_entry]));
+    assert_eq!(
+        cairo_runner.get_relocated_trace(),
+        Some([relocated_entry].as_slice())
+    );
 }
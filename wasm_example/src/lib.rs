@@ -0,0 +1,41 @@
+// Runs compiled Cairo programs from wasm. The program is given as the bytes of its json, so it
+// doesn't need file I/O, and the run is single threaded, like the rest of the VM.
+//
+// Build with `wasm-pack build` and test with `make test` from this directory.
+
+use cairo_vm::{
+    hint_processor::builtin_hint_processor::builtin_hint_processor_definition::BuiltinHintProcessor,
+    types::program::Program,
+    vm::{runners::cairo_runner::CairoRunner, vm_core::VirtualMachine},
+};
+use felt::FeltOps;
+use wasm_bindgen::prelude::*;
+
+///Runs the main function of a compiled program with the "all" layout, returning the values it
+///wrote to the output builtin as decimal strings, or the error it failed with.
+#[wasm_bindgen(js_name = runProgramJson)]
+pub fn run_program_json(program_json: &[u8]) -> Result<Vec<String>, String> {
+    let program = Program::from_reader(program_json, Some("main")).map_err(|e| e.to_string())?;
+    let mut hint_processor = BuiltinHintProcessor::new_empty();
+    let mut cairo_runner = CairoRunner::new(&program, "all", false).map_err(|e| e.to_string())?;
+    let mut vm = VirtualMachine::new(false);
+
+    let end = cairo_runner
+        .initialize(&mut vm)
+        .map_err(|e| e.to_string())?;
+    cairo_runner
+        .run_until_pc(end, &mut vm, &mut hint_processor)
+        .map_err(|e| e.to_string())?;
+    cairo_runner
+        .end_run(false, false, &mut vm, &mut hint_processor)
+        .map_err(|e| e.to_string())?;
+
+    let output = cairo_runner
+        .get_output_values(&mut vm)
+        .map_err(|e| e.to_string())?
+        .unwrap_or_default();
+    Ok(output
+        .iter()
+        .map(|value| value.to_bigint().to_string())
+        .collect())
+}
@@ -0,0 +1,23 @@
+// Headless browser tests, run with `make test` from this directory.
+
+use cairo_vm_wasm::run_program_json;
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+// Writes fib(10) to the output builtin
+const FIBONACCI_PROGRAM: &[u8] =
+    include_bytes!("../../cairo_programs/manually_compiled/fibonacci_output_program.json");
+
+#[wasm_bindgen_test]
+fn run_fibonacci() {
+    assert_eq!(
+        run_program_json(FIBONACCI_PROGRAM),
+        Ok(vec![String::from("55")])
+    );
+}
+
+#[wasm_bindgen_test]
+fn run_invalid_program() {
+    assert!(run_program_json(b"{").is_err());
+}